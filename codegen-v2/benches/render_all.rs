@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Compares the serial and the parallel rendering of a synthetic manifest
+//! directory. Run with `cargo bench --bench render_all`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use libparser::codegen::swift::{render_all, RenderConfig, SwiftTemplates};
+use libparser::manifest::{parse_str, FileInfo};
+
+const MANIFEST: &str = include_str!("../tests/fixtures/swift/golden.input.yaml");
+const FILE_COUNT: usize = 100;
+
+/// Returns copies of the golden manifest with the types renamed, so they
+/// don't clash.
+fn synthetic_manifests() -> Vec<FileInfo> {
+    (0..FILE_COUNT)
+        .map(|i| {
+            let yaml = MANIFEST
+                .replace("MainStruct", &format!("Struct{i}"))
+                .replace("MainEnum", &format!("Enum{i}"))
+                .replace("Golden", &format!("Golden{i}"));
+            parse_str(&yaml).unwrap()
+        })
+        .collect()
+}
+
+fn bench_render_all(c: &mut Criterion) {
+    let manifests = synthetic_manifests();
+    let render = || {
+        render_all(
+            manifests.clone(),
+            SwiftTemplates::builtin(),
+            RenderConfig::builtin(),
+        )
+        .unwrap()
+    };
+
+    let serial_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("render_all");
+    group.bench_function("serial", |b| b.iter(|| serial_pool.install(render)));
+    group.bench_function("parallel", |b| b.iter(render));
+    group.finish();
+}
+
+criterion_group!(benches, bench_render_all);
+criterion_main!(benches);
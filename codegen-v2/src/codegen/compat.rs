@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Compatibility check of the generated public API against a baseline.
+//!
+//! Both sides are [`ApiSurface`] documents, e.g. the `api_surface.json` of the
+//! previous release and the newly generated one. Every difference is reported
+//! as an [`ApiChange`], classified as breaking or additive for the existing
+//! callers of the Swift bindings.
+
+use crate::codegen::surface::{ApiSurface, MemberSurface, ObjectSurface, TypeSurface};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Compatibility {
+    /// Existing callers may fail to compile.
+    Breaking,
+    /// Existing callers keep compiling.
+    Additive,
+}
+
+/// A kind of object member, as grouped in the [`ObjectSurface`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MemberKind {
+    Init,
+    Method,
+    Property,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ApiChange {
+    RemovedObject {
+        object: String,
+    },
+    AddedObject {
+        object: String,
+    },
+    /// E.g. a struct became a class.
+    ChangedObjectKind {
+        object: String,
+    },
+    RemovedVariant {
+        object: String,
+        variant: String,
+    },
+    AddedVariant {
+        object: String,
+        variant: String,
+    },
+    /// The member is removed. If another member with the same signature is
+    /// added to the object, it's most likely renamed.
+    RemovedMember {
+        object: String,
+        kind: MemberKind,
+        member: String,
+        renamed_to: Option<String>,
+    },
+    AddedMember {
+        object: String,
+        kind: MemberKind,
+        member: String,
+    },
+    /// The member became static or vice versa.
+    ChangedStatic {
+        object: String,
+        member: String,
+    },
+    /// The type or nullability of the parameter at `index` changed.
+    /// `old` or `new` is `None` if the parameter is removed or added.
+    ChangedParam {
+        object: String,
+        member: String,
+        index: usize,
+        old: Option<TypeSurface>,
+        new: Option<TypeSurface>,
+    },
+    ChangedReturn {
+        object: String,
+        member: String,
+        old: TypeSurface,
+        new: TypeSurface,
+    },
+}
+
+impl ApiChange {
+    pub fn compatibility(&self) -> Compatibility {
+        match self {
+            ApiChange::AddedObject { .. } | ApiChange::AddedMember { .. } => {
+                Compatibility::Additive
+            }
+            // A non-nullable parameter becoming nullable accepts every
+            // argument it used to.
+            ApiChange::ChangedParam {
+                old: Some(old),
+                new: Some(new),
+                ..
+            } if old.ty == new.ty && !old.is_nullable && new.is_nullable => Compatibility::Additive,
+            // A new enum case breaks the exhaustive `switch` statements.
+            ApiChange::AddedVariant { .. }
+            | ApiChange::RemovedObject { .. }
+            | ApiChange::ChangedObjectKind { .. }
+            | ApiChange::RemovedVariant { .. }
+            | ApiChange::RemovedMember { .. }
+            | ApiChange::ChangedStatic { .. }
+            | ApiChange::ChangedParam { .. }
+            | ApiChange::ChangedReturn { .. } => Compatibility::Breaking,
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == Compatibility::Breaking
+    }
+}
+
+impl Display for ApiChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiChange::RemovedObject { object } => write!(f, "'{object}' is removed"),
+            ApiChange::AddedObject { object } => write!(f, "'{object}' is added"),
+            ApiChange::ChangedObjectKind { object } => {
+                write!(f, "'{object}' changed its kind")
+            }
+            ApiChange::RemovedVariant { object, variant } => {
+                write!(f, "'{object}.{variant}' case is removed")
+            }
+            ApiChange::AddedVariant { object, variant } => {
+                write!(f, "'{object}.{variant}' case is added")
+            }
+            ApiChange::RemovedMember {
+                object,
+                member,
+                renamed_to: Some(renamed_to),
+                ..
+            } => write!(f, "'{object}.{member}' is renamed to '{renamed_to}'"),
+            ApiChange::RemovedMember {
+                object,
+                member,
+                renamed_to: None,
+                ..
+            } => write!(f, "'{object}.{member}' is removed"),
+            ApiChange::AddedMember { object, member, .. } => {
+                write!(f, "'{object}.{member}' is added")
+            }
+            ApiChange::ChangedStatic { object, member } => {
+                write!(f, "'{object}.{member}' changed from/to static")
+            }
+            ApiChange::ChangedParam {
+                object,
+                member,
+                index,
+                old,
+                new,
+            } => {
+                let display = |ty: &Option<TypeSurface>| match ty {
+                    Some(ty) => ty.to_string(),
+                    None => "none".to_string(),
+                };
+                write!(
+                    f,
+                    "'{object}.{member}' parameter #{index} changed from '{}' to '{}'",
+                    display(old),
+                    display(new)
+                )
+            }
+            ApiChange::ChangedReturn {
+                object,
+                member,
+                old,
+                new,
+            } => write!(
+                f,
+                "'{object}.{member}' return type changed from '{old}' to '{new}'"
+            ),
+        }
+    }
+}
+
+/// Compares the newly generated API surface with the baseline one.
+/// The changes are grouped by object, in the order of the object names.
+pub fn check_compatibility(baseline: &ApiSurface, current: &ApiSurface) -> Vec<ApiChange> {
+    let mut changes = vec![];
+
+    for (name, old) in &baseline.objects {
+        match current.objects.get(name) {
+            Some(new) => compare_objects(name, old, new, &mut changes),
+            None => changes.push(ApiChange::RemovedObject {
+                object: name.clone(),
+            }),
+        }
+    }
+
+    for name in current.objects.keys() {
+        if !baseline.objects.contains_key(name) {
+            changes.push(ApiChange::AddedObject {
+                object: name.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+// Convenience function: compares the objects present in both surfaces.
+fn compare_objects(
+    object: &str,
+    old: &ObjectSurface,
+    new: &ObjectSurface,
+    changes: &mut Vec<ApiChange>,
+) {
+    if old.kind != new.kind {
+        changes.push(ApiChange::ChangedObjectKind {
+            object: object.to_string(),
+        });
+    }
+
+    for variant in &old.variants {
+        if !new.variants.contains(variant) {
+            changes.push(ApiChange::RemovedVariant {
+                object: object.to_string(),
+                variant: variant.clone(),
+            });
+        }
+    }
+    for variant in &new.variants {
+        if !old.variants.contains(variant) {
+            changes.push(ApiChange::AddedVariant {
+                object: object.to_string(),
+                variant: variant.clone(),
+            });
+        }
+    }
+
+    let member_groups = [
+        (MemberKind::Init, &old.inits, &new.inits),
+        (MemberKind::Method, &old.methods, &new.methods),
+        (MemberKind::Property, &old.properties, &new.properties),
+    ];
+    for (kind, old_members, new_members) in member_groups {
+        compare_members(object, kind, old_members, new_members, changes);
+    }
+}
+
+// Convenience function: compares the members of the same kind of an object.
+fn compare_members(
+    object: &str,
+    kind: MemberKind,
+    old: &BTreeMap<String, MemberSurface>,
+    new: &BTreeMap<String, MemberSurface>,
+    changes: &mut Vec<ApiChange>,
+) {
+    let added: Vec<_> = new
+        .iter()
+        .filter(|(name, _)| !old.contains_key(*name))
+        .collect();
+
+    for (name, old_member) in old {
+        let Some(new_member) = new.get(name) else {
+            // Consider the member renamed only if the match is unambiguous.
+            let mut same_signature = added.iter().filter(|(_, added)| *added == old_member);
+            let renamed_to = match (same_signature.next(), same_signature.next()) {
+                (Some((renamed_to, _)), None) => Some(renamed_to.to_string()),
+                _ => None,
+            };
+            changes.push(ApiChange::RemovedMember {
+                object: object.to_string(),
+                kind,
+                member: name.clone(),
+                renamed_to,
+            });
+            continue;
+        };
+
+        if old_member.is_static != new_member.is_static {
+            changes.push(ApiChange::ChangedStatic {
+                object: object.to_string(),
+                member: name.clone(),
+            });
+        }
+
+        let params_len = old_member.params.len().max(new_member.params.len());
+        for index in 0..params_len {
+            let old_param = old_member.params.get(index);
+            let new_param = new_member.params.get(index);
+            if old_param != new_param {
+                changes.push(ApiChange::ChangedParam {
+                    object: object.to_string(),
+                    member: name.clone(),
+                    index,
+                    old: old_param.cloned(),
+                    new: new_param.cloned(),
+                });
+            }
+        }
+
+        if old_member.return_type != new_member.return_type {
+            changes.push(ApiChange::ChangedReturn {
+                object: object.to_string(),
+                member: name.clone(),
+                old: old_member.return_type.clone(),
+                new: new_member.return_type.clone(),
+            });
+        }
+    }
+
+    for (name, _) in added {
+        changes.push(ApiChange::AddedMember {
+            object: object.to_string(),
+            kind,
+            member: name.clone(),
+        });
+    }
+}
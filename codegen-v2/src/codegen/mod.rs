@@ -2,8 +2,11 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+pub mod compat;
 pub mod cpp;
 pub mod proto;
 pub mod rust;
+pub mod stats;
+pub mod surface;
 pub mod swift;
 pub mod template_generator;
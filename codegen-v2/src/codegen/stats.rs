@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Measurement of the generated API.
+//!
+//! The stats are counted on the [`ApiSurface`], so the stats of the current
+//! run can be compared with the `api_surface.json` of the previous one. Note
+//! that getter functions rendered as Swift properties are counted as methods,
+//! same as in the surface.
+
+use crate::codegen::surface::{ApiSurface, ObjectKind};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RenderStats {
+    pub classes: usize,
+    pub structs: usize,
+    pub enums: usize,
+    /// The variants of all enums.
+    pub enum_variants: usize,
+    pub inits: usize,
+    pub methods: usize,
+    pub properties: usize,
+    pub protos: usize,
+    /// The number of variants by the C FFI name of the enum, e.g. `TWCoinType`.
+    pub variants_by_enum: BTreeMap<String, usize>,
+}
+
+/// A row of the stats table.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StatsRow {
+    pub label: &'static str,
+    pub count: usize,
+    /// The difference to the previous run, if known.
+    pub delta: Option<i64>,
+}
+
+impl RenderStats {
+    pub fn from_surface(surface: &ApiSurface) -> Self {
+        let mut stats = RenderStats::default();
+
+        for (name, object) in &surface.objects {
+            match object.kind {
+                ObjectKind::Class => stats.classes += 1,
+                ObjectKind::Struct => stats.structs += 1,
+                ObjectKind::Enum => {
+                    stats.enums += 1;
+                    stats.enum_variants += object.variants.len();
+                    stats
+                        .variants_by_enum
+                        .insert(name.clone(), object.variants.len());
+                }
+                ObjectKind::Proto => stats.protos += 1,
+            }
+
+            stats.inits += object.inits.len();
+            stats.methods += object.methods.len();
+            stats.properties += object.properties.len();
+        }
+
+        stats
+    }
+
+    /// Returns the number of variants of the given enum, or `None` if the enum is not rendered.
+    pub fn variants_of(&self, enum_name: &str) -> Option<usize> {
+        self.variants_by_enum.get(enum_name).copied()
+    }
+
+    /// Returns the table rows, with the deltas if the stats of the `previous` run are given.
+    pub fn rows(&self, previous: Option<&RenderStats>) -> Vec<StatsRow> {
+        let counts = |stats: &RenderStats| {
+            [
+                ("Classes", stats.classes),
+                ("Structs", stats.structs),
+                ("Enums", stats.enums),
+                ("Enum variants", stats.enum_variants),
+                ("Inits", stats.inits),
+                ("Methods", stats.methods),
+                ("Properties", stats.properties),
+                ("Protos", stats.protos),
+            ]
+        };
+
+        let previous = previous.map(counts);
+        counts(self)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (label, count))| StatsRow {
+                label,
+                count,
+                delta: previous
+                    .as_ref()
+                    .map(|previous| count as i64 - previous[idx].1 as i64),
+            })
+            .collect()
+    }
+
+    /// Formats the stats as a plain text table, see [`StatsTable`].
+    pub fn table(&self, previous: Option<&RenderStats>) -> StatsTable {
+        StatsTable {
+            rows: self.rows(previous),
+        }
+    }
+}
+
+/// The stats table, the delta column is left out if there is no previous run.
+///
+/// ```text
+/// | Item          | Count | Delta |
+/// |---------------|-------|-------|
+/// | Classes       |     1 |    +1 |
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StatsTable {
+    pub rows: Vec<StatsRow>,
+}
+
+impl Display for StatsTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let has_delta = self.rows.iter().any(|row| row.delta.is_some());
+
+        let mut header = vec!["Item".to_string(), "Count".to_string()];
+        if has_delta {
+            header.push("Delta".to_string());
+        }
+
+        let lines: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut line = vec![row.label.to_string(), row.count.to_string()];
+                if has_delta {
+                    let delta = match row.delta {
+                        Some(delta) if delta > 0 => format!("+{delta}"),
+                        Some(delta) => delta.to_string(),
+                        None => "-".to_string(),
+                    };
+                    line.push(delta);
+                }
+                line
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..header.len())
+            .map(|col| {
+                lines
+                    .iter()
+                    .chain(std::iter::once(&header))
+                    .map(|line| line[col].len())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        // The item names are left aligned, the numbers right aligned.
+        let write_line = |f: &mut std::fmt::Formatter<'_>, line: &[String]| {
+            for (col, (cell, &width)) in line.iter().zip(&widths).enumerate() {
+                if col == 0 {
+                    write!(f, "| {cell:<width$} ")?;
+                } else {
+                    write!(f, "| {cell:>width$} ")?;
+                }
+            }
+            writeln!(f, "|")
+        };
+
+        write_line(f, &header)?;
+        for width in &widths {
+            write!(f, "|{}", "-".repeat(width + 2))?;
+        }
+        writeln!(f, "|")?;
+        for line in &lines {
+            write_line(f, line)?;
+        }
+
+        Ok(())
+    }
+}
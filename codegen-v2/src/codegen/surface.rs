@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Normalized API surface of the generated bindings.
+//!
+//! Every backend reports the manifest items it actually renders, before any
+//! language-specific mapping (names, types, computed properties etc). Comparing
+//! the surfaces of two backends shows whether they expose the same API, e.g.
+//! when an object is skipped by one backend only.
+
+use crate::manifest::{FunctionInfo, InitInfo, PropertyInfo, TypeInfo, TypeVariant};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// The objects rendered by a backend, by their C FFI name.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ApiSurface {
+    pub objects: BTreeMap<String, ObjectSurface>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Struct,
+    Class,
+    Enum,
+    Proto,
+}
+
+/// The members of an object, by their C FFI name.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSurface {
+    pub kind: ObjectKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inits: BTreeMap<String, MemberSurface>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub methods: BTreeMap<String, MemberSurface>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, MemberSurface>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemberSurface {
+    pub is_static: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<TypeSurface>,
+    #[serde(rename = "return")]
+    pub return_type: TypeSurface,
+}
+
+/// A language independent type, e.g. `uint32_t` or `TWPublicKey`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TypeSurface {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub is_nullable: bool,
+}
+
+impl ApiSurface {
+    /// Adds an object without members, or returns the existing one.
+    pub fn add_object(&mut self, name: &str, kind: ObjectKind) -> &mut ObjectSurface {
+        self.objects
+            .entry(name.to_string())
+            .or_insert_with(|| ObjectSurface {
+                kind,
+                variants: vec![],
+                inits: BTreeMap::new(),
+                methods: BTreeMap::new(),
+                properties: BTreeMap::new(),
+            })
+    }
+
+    /// Merges the surface of another manifest file.
+    pub fn extend(&mut self, other: ApiSurface) {
+        self.objects.extend(other.objects);
+    }
+}
+
+impl ObjectSurface {
+    pub fn add_init(&mut self, object: &str, init: &InitInfo) {
+        let member = MemberSurface {
+            is_static: true,
+            params: init
+                .params
+                .iter()
+                .map(|p| TypeSurface::from(&p.ty))
+                .collect(),
+            return_type: TypeSurface {
+                ty: object.to_string(),
+                is_nullable: init.is_nullable,
+            },
+        };
+        self.inits.insert(init.name.clone(), member);
+    }
+
+    pub fn add_method(&mut self, func: &FunctionInfo) {
+        let member = MemberSurface {
+            is_static: func.is_static,
+            params: func
+                .params
+                .iter()
+                .map(|p| TypeSurface::from(&p.ty))
+                .collect(),
+            return_type: TypeSurface::from(&func.return_type),
+        };
+        self.methods.insert(func.name.clone(), member);
+    }
+
+    pub fn add_property(&mut self, prop: &PropertyInfo) {
+        let member = MemberSurface {
+            is_static: prop.is_static,
+            params: vec![],
+            return_type: TypeSurface::from(&prop.return_type),
+        };
+        self.properties.insert(prop.name.clone(), member);
+    }
+}
+
+impl From<&TypeInfo> for TypeSurface {
+    fn from(ty: &TypeInfo) -> Self {
+        TypeSurface {
+            ty: type_name(&ty.variant),
+            is_nullable: ty.is_nullable,
+        }
+    }
+}
+
+/// E.g. `uint8_t*` for a pointer to bytes, or `TWDataVector` for an array of data.
+fn type_name(variant: &TypeVariant) -> String {
+    let name = match variant {
+        TypeVariant::Void => "void",
+        TypeVariant::Bool => "bool",
+        TypeVariant::Char => "char",
+        TypeVariant::ShortInt => "short",
+        TypeVariant::Int => "int",
+        TypeVariant::UnsignedInt => "unsigned int",
+        TypeVariant::LongInt => "long",
+        TypeVariant::Float => "float",
+        TypeVariant::Double => "double",
+        TypeVariant::SizeT => "size_t",
+        TypeVariant::Int8T => "int8_t",
+        TypeVariant::Int16T => "int16_t",
+        TypeVariant::Int32T => "int32_t",
+        TypeVariant::Int64T => "int64_t",
+        TypeVariant::UInt8T => "uint8_t",
+        TypeVariant::UInt16T => "uint16_t",
+        TypeVariant::UInt32T => "uint32_t",
+        TypeVariant::UInt64T => "uint64_t",
+        TypeVariant::String => "TWString",
+        TypeVariant::Data => "TWData",
+        TypeVariant::Struct(name) | TypeVariant::Enum(name) => name,
+        TypeVariant::Pointer(elem) => return format!("{}*", type_name(elem)),
+        TypeVariant::Array(elem) if **elem == TypeVariant::Data => "TWDataVector",
+        TypeVariant::Array(elem) => return format!("{}[]", type_name(elem)),
+    };
+
+    name.to_string()
+}
+
+/// E.g. `TWString?` for a nullable string.
+impl Display for TypeSurface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_nullable {
+            write!(f, "{}?", self.ty)
+        } else {
+            write!(f, "{}", self.ty)
+        }
+    }
+}
+
+/// A difference between the API surfaces of two backends.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SurfaceMismatch {
+    /// The object is not rendered by the `backend`.
+    MissingObject { object: String, backend: String },
+    /// The member of the object is not rendered by the `backend`.
+    MissingMember {
+        object: String,
+        member: String,
+        backend: String,
+    },
+    /// The object or its member (if specified) is rendered differently,
+    /// e.g. with a different nullability.
+    Different {
+        object: String,
+        member: Option<String>,
+    },
+}
+
+impl Display for SurfaceMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SurfaceMismatch::MissingObject { object, backend } => {
+                write!(f, "'{object}' is not rendered by {backend}")
+            }
+            SurfaceMismatch::MissingMember {
+                object,
+                member,
+                backend,
+            } => write!(f, "'{object}.{member}' is not rendered by {backend}"),
+            SurfaceMismatch::Different {
+                object,
+                member: Some(member),
+            } => write!(f, "'{object}.{member}' differs"),
+            SurfaceMismatch::Different {
+                object,
+                member: None,
+            } => write!(f, "'{object}' differs"),
+        }
+    }
+}
+
+/// Compares the API surfaces of two backends, given as (backend name, surface)
+/// pairs. Returns an empty list if both backends expose the same API.
+pub fn compare_surfaces(
+    (left_name, left): (&str, &ApiSurface),
+    (right_name, right): (&str, &ApiSurface),
+) -> Vec<SurfaceMismatch> {
+    let mut mismatches = vec![];
+
+    for (name, left_obj) in &left.objects {
+        let Some(right_obj) = right.objects.get(name) else {
+            mismatches.push(SurfaceMismatch::MissingObject {
+                object: name.clone(),
+                backend: right_name.to_string(),
+            });
+            continue;
+        };
+
+        if left_obj.kind != right_obj.kind || left_obj.variants != right_obj.variants {
+            mismatches.push(SurfaceMismatch::Different {
+                object: name.clone(),
+                member: None,
+            });
+        }
+
+        let member_groups = [
+            (&left_obj.inits, &right_obj.inits),
+            (&left_obj.methods, &right_obj.methods),
+            (&left_obj.properties, &right_obj.properties),
+        ];
+        for (left_members, right_members) in member_groups {
+            compare_members(
+                name,
+                (left_name, left_members),
+                (right_name, right_members),
+                &mut mismatches,
+            );
+        }
+    }
+
+    for name in right.objects.keys() {
+        if !left.objects.contains_key(name) {
+            mismatches.push(SurfaceMismatch::MissingObject {
+                object: name.clone(),
+                backend: left_name.to_string(),
+            });
+        }
+    }
+
+    mismatches
+}
+
+// Convenience function: compares the members of the same kind of an object.
+fn compare_members(
+    object: &str,
+    (left_name, left): (&str, &BTreeMap<String, MemberSurface>),
+    (right_name, right): (&str, &BTreeMap<String, MemberSurface>),
+    mismatches: &mut Vec<SurfaceMismatch>,
+) {
+    for (name, left_member) in left {
+        match right.get(name) {
+            Some(right_member) if right_member == left_member => {}
+            Some(_) => mismatches.push(SurfaceMismatch::Different {
+                object: object.to_string(),
+                member: Some(name.clone()),
+            }),
+            None => mismatches.push(SurfaceMismatch::MissingMember {
+                object: object.to_string(),
+                member: name.clone(),
+                backend: right_name.to_string(),
+            }),
+        }
+    }
+
+    for name in right.keys() {
+        if !left.contains_key(name) {
+            mismatches.push(SurfaceMismatch::MissingMember {
+                object: object.to_string(),
+                member: name.clone(),
+                backend: left_name.to_string(),
+            });
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The protocols the Swift types conform to, e.g. `Address` or `CaseIterable`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ConformanceConfig {
+    /// Protocols of the structs/classes by the suffix of their Swift name,
+    /// e.g. `Address` for `SegwitAddress`.
+    #[serde(default)]
+    pub suffixes: BTreeMap<String, Vec<String>>,
+    /// Protocols of every enum, e.g. `CaseIterable`.
+    #[serde(default)]
+    pub enums: Vec<String>,
+    /// Protocols of the types by their Swift name, e.g. `CoinType`. Replaces
+    /// the protocols of the rules above and the derived ones.
+    #[serde(default)]
+    pub types: BTreeMap<String, Vec<String>>,
+    /// The enums by their Swift name that are `Codable` by the string
+    /// representations of their variants, instead of the raw values.
+    #[serde(default)]
+    pub codable_strings: Vec<String>,
+}
+
+impl ConformanceConfig {
+    /// The default conformances, see `conformances.yaml`.
+    pub fn builtin() -> Self {
+        // Panicking implies bug, the file is checked by the tests.
+        Self::from_yaml(include_str!("conformances.yaml")).unwrap()
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Returns the protocols of the struct/class by its Swift name, followed
+    /// by the ones derived from the manifest, e.g. `Hashable`.
+    pub fn struct_protocols(&self, name: &str, derived: &[&str]) -> Vec<String> {
+        if let Some(protocols) = self.types.get(name) {
+            return protocols.clone();
+        }
+
+        self.suffixes
+            .iter()
+            .filter(|(suffix, _)| name.ends_with(suffix.as_str()))
+            .flat_map(|(_, protocols)| protocols.iter().cloned())
+            .chain(derived.iter().map(|protocol| protocol.to_string()))
+            .collect()
+    }
+
+    /// Whether the enum is encoded by the string representations of its
+    /// variants, see [`ConformanceConfig::codable_strings`].
+    pub fn is_codable_strings(&self, name: &str) -> bool {
+        self.codable_strings.iter().any(|enm| enm == name)
+    }
+
+    /// Returns the protocols of the enum by its Swift name, followed by the
+    /// ones derived from the manifest, e.g. `CustomStringConvertible`. The raw
+    /// value type is not included.
+    pub fn enum_protocols(&self, name: &str, derived: &[&str]) -> Vec<String> {
+        if let Some(protocols) = self.types.get(name) {
+            return protocols.clone();
+        }
+
+        self.enums
+            .iter()
+            .cloned()
+            .chain(derived.iter().map(|protocol| protocol.to_string()))
+            .collect()
+    }
+}
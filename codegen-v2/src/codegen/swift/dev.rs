@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Development mode for iterating on the Swift templates.
+//!
+//! The manifest is parsed and mapped into the Swift types once, so a template
+//! change only requires re-rendering the affected outputs.
+
+use super::render::{
+    create_engine, generate_swift_types, pretty_file_name, render_generated, GeneratedSwiftTypes,
+    OutputKinds, SwiftTemplates,
+};
+use super::{ConformanceConfig, ExclusionConfig, NamingConfig, ProtoNamingConvention};
+use crate::manifest::FileInfo;
+use crate::Result;
+use handlebars::Handlebars;
+use std::collections::BTreeMap;
+
+/// The Swift types of a manifest file and its rendered files.
+struct CachedManifest {
+    pretty_file_name: String,
+    generated: GeneratedSwiftTypes,
+    /// The rendered files by their relative path, see
+    /// [`super::GeneratedSwiftTypesStrings::into_files`].
+    files: BTreeMap<String, String>,
+}
+
+/// Keeps the generated Swift types and the rendered files in memory between
+/// the template and manifest reloads.
+pub struct DevSession {
+    templates: SwiftTemplates,
+    engine: Handlebars<'static>,
+    naming: NamingConfig,
+    exclusions: ExclusionConfig,
+    conformances: ConformanceConfig,
+    proto_naming: ProtoNamingConvention,
+    /// The cached manifest files by their name.
+    manifests: BTreeMap<String, CachedManifest>,
+}
+
+impl DevSession {
+    /// Generates the Swift types of the given manifest files and renders them.
+    pub fn new(
+        templates: SwiftTemplates,
+        naming: NamingConfig,
+        exclusions: ExclusionConfig,
+        conformances: ConformanceConfig,
+        proto_naming: ProtoNamingConvention,
+        file_infos: Vec<FileInfo>,
+    ) -> Result<Self> {
+        let mut session = DevSession {
+            engine: create_engine(&templates)?,
+            templates,
+            naming,
+            exclusions,
+            conformances,
+            proto_naming,
+            manifests: BTreeMap::new(),
+        };
+        for file_info in file_infos {
+            session.reload_manifest(file_info)?;
+        }
+        Ok(session)
+    }
+
+    /// Returns all the rendered files by their relative path.
+    pub fn files(&self) -> BTreeMap<&str, &str> {
+        self.manifests
+            .values()
+            .flat_map(|manifest| manifest.files.iter())
+            .map(|(path, content)| (path.as_str(), content.as_str()))
+            .collect()
+    }
+
+    /// Returns the content of the rendered file, if any.
+    pub fn file(&self, path: &str) -> Option<&str> {
+        self.manifests
+            .values()
+            .find_map(|manifest| manifest.files.get(path))
+            .map(String::as_str)
+    }
+
+    /// Re-renders the outputs affected by the template changes, reusing the
+    /// generated Swift types.
+    ///
+    /// Returns the paths of the files whose content has changed.
+    pub fn reload_templates(&mut self, templates: SwiftTemplates) -> Result<Vec<String>> {
+        let kinds = self.templates.affected_outputs(&templates);
+        if kinds.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Render everything first, so the session is left unchanged if the
+        // new templates are invalid.
+        let engine = create_engine(&templates)?;
+        let mut rerendered = vec![];
+        for (name, manifest) in &self.manifests {
+            let rendered = render_generated(
+                &engine,
+                &manifest.generated,
+                &manifest.pretty_file_name,
+                kinds,
+            )?;
+            rerendered.push((name.clone(), rendered.into_files()));
+        }
+
+        self.engine = engine;
+        self.templates = templates;
+
+        let mut changed = vec![];
+        for (name, files) in rerendered {
+            // Panicking implies bug, the names are taken from the map above.
+            let manifest = self.manifests.get_mut(&name).unwrap();
+            for (path, content) in files {
+                if manifest.files.get(&path) != Some(&content) {
+                    manifest.files.insert(path.clone(), content);
+                    changed.push(path);
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Generates the Swift types of the given manifest file and renders them.
+    ///
+    /// Returns the paths of the files whose content has changed, including the
+    /// files that are not rendered anymore.
+    pub fn reload_manifest(&mut self, file_info: FileInfo) -> Result<Vec<String>> {
+        let name = file_info.name.clone();
+        let pretty_file_name = pretty_file_name(&file_info, &self.proto_naming)?;
+
+        let mut generated = generate_swift_types(
+            file_info,
+            &self.naming,
+            &self.exclusions,
+            &self.conformances,
+            &self.proto_naming,
+        )?;
+        // Keep the output stable between the manifest reloads.
+        generated.sort();
+        let files: BTreeMap<_, _> = render_generated(
+            &self.engine,
+            &generated,
+            &pretty_file_name,
+            OutputKinds::all(),
+        )?
+        .into_files()
+        .into_iter()
+        .collect();
+
+        let previous = self
+            .manifests
+            .remove(&name)
+            .map(|manifest| manifest.files)
+            .unwrap_or_default();
+
+        let mut changed: Vec<String> = files
+            .iter()
+            .filter(|(path, content)| previous.get(*path) != Some(*content))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            previous
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+
+        self.manifests.insert(
+            name,
+            CachedManifest {
+                pretty_file_name,
+                generated,
+                files,
+            },
+        );
+        Ok(changed)
+    }
+}
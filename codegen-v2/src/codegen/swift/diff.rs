@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Dry-run mode: compares the rendered bindings with the files on disk
+//! without writing anything, so that CI can detect unreviewed binding changes.
+
+use super::render::RenderOutput;
+use super::write::{is_generated, split_license_header, with_generated_header};
+use crate::Result;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Bindings are rendered as Swift files only, other files on disk are ignored.
+const SWIFT_EXTENSION: &str = "swift";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Unchanged,
+    Modified,
+    /// The file is rendered but does not exist on disk.
+    New,
+    /// The generated file exists on disk but is not rendered anymore, see
+    /// [`find_orphans`](super::find_orphans).
+    Orphaned,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// Path relative to the output directory, e.g. `Enums/CoinType.swift`.
+    pub path: String,
+    pub status: FileStatus,
+    /// Public symbols that appear in the rendered file only, e.g. `PrivateKey.sign(digest:curve:)`.
+    pub added_symbols: Vec<String>,
+    /// Public symbols that appear in the file on disk only.
+    pub removed_symbols: Vec<String>,
+    /// The unified diff of the file on disk to the rendered one, if modified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffReport {
+    /// Whether any file is modified, new or orphaned.
+    pub has_changes: bool,
+    /// Files sorted by path.
+    pub files: Vec<FileDiff>,
+}
+
+impl RenderOutput {
+    /// Compares the files as written by [`write_files`](super::write_files),
+    /// i.e. with the [`generated_header`](super::generated_header), with the
+    /// files in `dir`, see [`diff_with_dir`].
+    pub fn diff_with_dir(&self, dir: &Path) -> Result<DiffReport> {
+        diff_with_dir(dir, &with_generated_header(self.files.clone()))
+    }
+
+    /// Returns whether writing the files into `dir` would change anything.
+    pub fn check(&self, dir: &Path) -> Result<bool> {
+        Ok(self.diff_with_dir(dir)?.has_changes)
+    }
+}
+
+/// Compares the rendered `files` (relative path and content) with the files in `out_dir`.
+/// A missing `out_dir` is treated as empty.
+///
+/// The [`generated_header`](super::generated_header) line is ignored, so the
+/// files written by another codegen version are not reported as modified.
+pub fn diff_with_dir(out_dir: &Path, files: &[(String, String)]) -> Result<DiffReport> {
+    let mut on_disk = BTreeMap::new();
+    if out_dir.exists() {
+        collect_swift_files(out_dir, out_dir, &mut on_disk)?;
+    }
+
+    let mut diffs = Vec::new();
+    for (path, rendered) in files {
+        let existing = on_disk.remove(path);
+        let status = match &existing {
+            Some(existing) if without_header(existing) == without_header(rendered) => {
+                FileStatus::Unchanged
+            }
+            Some(_) => FileStatus::Modified,
+            None => FileStatus::New,
+        };
+        diffs.push(file_diff(
+            path.clone(),
+            status,
+            Some(rendered),
+            existing.as_deref(),
+        ));
+    }
+
+    // Whatever generated file is left on disk has not been rendered. The
+    // hand-written files are not reported.
+    for (path, existing) in on_disk
+        .into_iter()
+        .filter(|(_, existing)| is_generated(existing))
+    {
+        diffs.push(file_diff(path, FileStatus::Orphaned, None, Some(&existing)));
+    }
+
+    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(DiffReport {
+        has_changes: diffs.iter().any(|d| d.status != FileStatus::Unchanged),
+        files: diffs,
+    })
+}
+
+fn file_diff(
+    path: String,
+    status: FileStatus,
+    rendered: Option<&str>,
+    existing: Option<&str>,
+) -> FileDiff {
+    let rendered_symbols = rendered.map(public_symbols).unwrap_or_default();
+    let existing_symbols = existing.map(public_symbols).unwrap_or_default();
+
+    let diff = match (status, existing, rendered) {
+        (FileStatus::Modified, Some(existing), Some(rendered)) => Some(unified_diff(
+            &path,
+            &without_header(existing),
+            &without_header(rendered),
+        )),
+        _ => None,
+    };
+
+    FileDiff {
+        path,
+        status,
+        diff,
+        added_symbols: rendered_symbols
+            .difference(&existing_symbols)
+            .cloned()
+            .collect(),
+        removed_symbols: existing_symbols
+            .difference(&rendered_symbols)
+            .cloned()
+            .collect(),
+    }
+}
+
+// Convenience function: returns the content without the generated header line,
+// which changes with the codegen version only.
+fn without_header(content: &str) -> String {
+    if !is_generated(content) {
+        return content.to_string();
+    }
+    let (license, rest) = split_license_header(content);
+    let rest = rest
+        .split_once('\n')
+        .map(|(_header, rest)| rest)
+        .unwrap_or_default();
+    format!("{license}{rest}")
+}
+
+// Convenience function: returns the `git diff` like unified diff of the file.
+fn unified_diff(path: &str, existing: &str, rendered: &str) -> String {
+    TextDiff::from_lines(existing, rendered)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string()
+}
+
+/// Collects the Swift files under `dir` by their path relative to `root`.
+pub(super) fn collect_swift_files(
+    root: &Path,
+    dir: &Path,
+    out: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_swift_files(root, &path, out)?;
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SWIFT_EXTENSION) {
+            continue;
+        }
+
+        // Use forward slashes to match the rendered file paths on any platform.
+        let relative = path
+            .strip_prefix(root)
+            // Panicking implies bug, `path` is always under `root`.
+            .unwrap()
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        out.insert(relative, fs::read_to_string(&path)?);
+    }
+
+    Ok(())
+}
+
+/// Extracts the public symbols from the generated Swift code, such as
+/// `MainStruct`, `MainStruct.isValid`, `MainStruct.init(string:)` or `MainEnum.one`.
+///
+/// This is a light line-based parser that relies on the shape of the codegen templates,
+/// not a Swift parser.
+pub fn public_symbols(swift: &str) -> BTreeSet<String> {
+    let mut symbols = BTreeSet::new();
+    // The type that is currently being declared or extended.
+    let mut scope = String::new();
+
+    for line in swift.lines() {
+        let is_top_level = !line.starts_with(char::is_whitespace);
+        let line = line.trim();
+
+        if is_top_level {
+            if let Some(name) = line.strip_prefix("extension ") {
+                scope = declared_name(name);
+                continue;
+            }
+
+            let Some(decl) = line.strip_prefix("public ") else {
+                continue;
+            };
+            let decl = decl.strip_prefix("final ").unwrap_or(decl);
+            for keyword in ["class ", "struct ", "enum ", "protocol ", "typealias "] {
+                if let Some(name) = decl.strip_prefix(keyword) {
+                    scope = declared_name(name);
+                    symbols.insert(scope.clone());
+                }
+            }
+            continue;
+        }
+
+        // Enum cases are as public as the enum, skip `switch` cases.
+        if let Some(case) = line.strip_prefix("case ") {
+            if !case.starts_with('.') {
+                symbols.insert(format!("{scope}.{}", declared_name(case)));
+            }
+            continue;
+        }
+
+        let Some(decl) = line.strip_prefix("public ") else {
+            continue;
+        };
+        let decl = decl.strip_prefix("static ").unwrap_or(decl);
+        let decl = decl.strip_prefix("convenience ").unwrap_or(decl);
+
+        let member = if let Some(func) = decl.strip_prefix("func ") {
+            let name = declared_name(func);
+            format!("{name}({})", param_labels(func))
+        } else if decl.starts_with("init") {
+            format!("init({})", param_labels(decl))
+        } else if let Some(prop) = decl
+            .strip_prefix("var ")
+            .or_else(|| decl.strip_prefix("let "))
+        {
+            declared_name(prop)
+        } else {
+            continue;
+        };
+        symbols.insert(format!("{scope}.{member}"));
+    }
+
+    symbols
+}
+
+// Convenience function: returns the identifier at the beginning of the declaration.
+fn declared_name(decl: &str) -> String {
+    decl.trim_start_matches('`')
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect()
+}
+
+// Convenience function: returns the Swift-style argument labels of the
+// function declaration, e.g. `data:curve:` for `sign(data: Data, curve: Curve)`.
+fn param_labels(decl: &str) -> String {
+    let Some(start) = decl.find('(') else {
+        return String::new();
+    };
+
+    let mut labels = String::new();
+    let mut depth = 0;
+    let mut param_start = start + 1;
+    for (i, c) in decl.char_indices().skip_while(|(i, _)| *i <= start) {
+        match c {
+            '(' | '[' | '<' => depth += 1,
+            ')' | ']' | '>' if depth > 0 => depth -= 1,
+            ',' | ')' if depth == 0 => {
+                let param = decl[param_start..i].trim();
+                if let Some((label, _)) = param.split_once(':') {
+                    // The external label is the first word, e.g. `_` in `_ builder: Builder`.
+                    let label = label.split_whitespace().next().unwrap_or_default();
+                    labels.push_str(label);
+                    labels.push(':');
+                }
+                if c == ')' {
+                    break;
+                }
+                param_start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    labels
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// The manifest items that are deliberately not rendered, e.g. unsafe
+/// internals or deprecated experiments.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ExclusionConfig {
+    /// Patterns of the excluded objects and members, see `exclusions.yaml`,
+    /// e.g. `TWStoredKey.importPrivateKeyUnsafe` or `TW*Testing*`.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Whether the non-public inits, functions and properties are skipped
+    /// instead of being rendered as `internal`.
+    #[serde(default)]
+    pub skip_non_public: bool,
+}
+
+impl ExclusionConfig {
+    /// The default exclusions, see `exclusions.yaml`.
+    pub fn builtin() -> Self {
+        // Panicking implies bug, the file is checked by the tests.
+        Self::from_yaml(include_str!("exclusions.yaml")).unwrap()
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Whether the object is excluded by its C FFI name, e.g. `TWStoredKey`.
+    pub fn excludes_object(&self, object: &str) -> bool {
+        self.deny
+            .iter()
+            .any(|pattern| !pattern.contains('.') && glob_match(pattern, object))
+    }
+
+    /// Whether the member is excluded by its C FFI name, e.g.
+    /// `TWStoredKeyImportPrivateKeyUnsafe`, or by the C FFI name of its object
+    /// and its Swift name, e.g. `TWStoredKey` and `importPrivateKeyUnsafe`.
+    pub fn excludes_member(&self, name: &str, object_member: Option<(&str, &str)>) -> bool {
+        self.deny
+            .iter()
+            .any(|pattern| match (pattern.split_once('.'), object_member) {
+                (None, _) => glob_match(pattern, name),
+                (Some((object_pattern, member_pattern)), Some((object, member))) => {
+                    glob_match(object_pattern, object) && glob_match(member_pattern, member)
+                }
+                (Some(_), None) => false,
+            })
+    }
+}
+
+/// Matches the whole `text` against the `pattern`, where `*` matches any
+/// number of characters and `?` matches a single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern, and of the text it was
+    // matched at, to backtrack to if the rest does not match.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the `*` match one more character.
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    // The rest of the pattern must match an empty text.
+    pattern[p..].iter().all(|c| *c == '*')
+}
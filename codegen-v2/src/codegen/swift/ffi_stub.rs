@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Swift stub of the C FFI declarations of a manifest, so the rendered
+//! bindings can be compiled with `swiftc` without the WalletCore headers.
+
+use crate::manifest::{FileInfo, ParamInfo, TypeInfo, TypeVariant};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// The `TWString`, `TWData` and `TWDataVector` functions the rendered bindings
+/// call, as (name, declaration) pairs.
+const PRELUDE_FUNCTIONS: &[(&str, &str)] = &[
+    (
+        "TWStringCreateWithNSString",
+        "func TWStringCreateWithNSString(_ string: String) -> UnsafeRawPointer",
+    ),
+    (
+        "TWStringNSString",
+        "func TWStringNSString(_ string: UnsafeRawPointer) -> String",
+    ),
+    (
+        "TWStringDelete",
+        "func TWStringDelete(_ string: UnsafeRawPointer)",
+    ),
+    (
+        "TWDataCreateWithNSData",
+        "func TWDataCreateWithNSData(_ data: Data) -> UnsafeRawPointer",
+    ),
+    (
+        "TWDataCreateWithSize",
+        "func TWDataCreateWithSize(_ size: Int) -> UnsafeRawPointer",
+    ),
+    (
+        "TWDataNSData",
+        "func TWDataNSData(_ data: UnsafeRawPointer) -> Data",
+    ),
+    (
+        "TWDataBytes",
+        "func TWDataBytes(_ data: UnsafeRawPointer) -> UnsafeMutablePointer<UInt8>",
+    ),
+    (
+        "TWDataSize",
+        "func TWDataSize(_ data: UnsafeRawPointer) -> Int",
+    ),
+    (
+        "TWDataDelete",
+        "func TWDataDelete(_ data: UnsafeRawPointer)",
+    ),
+    (
+        "TWDataVectorCreate",
+        "func TWDataVectorCreate() -> OpaquePointer",
+    ),
+    (
+        "TWDataVectorAdd",
+        "func TWDataVectorAdd(_ vector: OpaquePointer, _ data: UnsafeRawPointer)",
+    ),
+    (
+        "TWDataVectorSize",
+        "func TWDataVectorSize(_ vector: OpaquePointer) -> Int",
+    ),
+    (
+        "TWDataVectorGet",
+        "func TWDataVectorGet(_ vector: OpaquePointer, _ index: Int) -> UnsafeRawPointer",
+    ),
+    (
+        "TWDataVectorDelete",
+        "func TWDataVectorDelete(_ vector: OpaquePointer)",
+    ),
+];
+
+/// Renders the Swift stub of the C FFI functions declared by `info`, as the
+/// Swift importer of the C headers would declare them, e.g.
+/// `func TWFooCreate(_ p0: UnsafeRawPointer) -> OpaquePointer?`. The stub
+/// also declares the `TWString`, `TWData` and `TWDataVector` helpers, unless
+/// the manifest declares them itself, and the C enums and value structs the
+/// functions refer to. The function bodies trap, the stub is never run.
+pub fn render_ffi_stub(info: &FileInfo) -> String {
+    let mut functions: Vec<(String, Vec<String>, String)> = vec![];
+
+    for init in &info.inits {
+        let params = init.params.iter().map(param_type).collect();
+        let ret = if init.is_nullable {
+            "OpaquePointer?"
+        } else {
+            "OpaquePointer"
+        };
+        functions.push((init.name.clone(), params, ret.to_string()));
+    }
+    for deinit in &info.deinits {
+        let params = vec!["OpaquePointer".to_string()];
+        functions.push((deinit.name.clone(), params, "Void".to_string()));
+    }
+    for func in &info.functions {
+        let params = func.params.iter().map(param_type).collect();
+        let ret = import_type(&func.return_type, false);
+        functions.push((func.name.clone(), params, ret));
+    }
+    for prop in &info.properties {
+        let params = if prop.is_static {
+            vec![]
+        } else {
+            vec![owner_type(info, &prop.name)]
+        };
+        let ret = import_type(&prop.return_type, false);
+        functions.push((prop.name.clone(), params, ret));
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// C FFI stub of the `{}` manifest, see `render_ffi_stub`.",
+        info.name
+    )
+    .unwrap();
+    out.push_str("\nimport Foundation\n\n");
+    out.push_str("typealias TWString = UnsafeRawPointer\n");
+    out.push_str("typealias TWData = UnsafeRawPointer\n");
+    out.push_str("typealias TWDataVector = OpaquePointer\n\n");
+
+    for (name, raw_type) in referenced_types(info) {
+        match raw_type {
+            // A C enum is imported as a struct wrapping the raw value.
+            Some(raw_type) => writeln!(
+                out,
+                "struct {name}: RawRepresentable, Equatable {{\n    var rawValue: {raw_type}\n\n    init(rawValue: {raw_type}) {{\n        self.rawValue = rawValue\n    }}\n}}\n"
+            )
+            .unwrap(),
+            None => writeln!(out, "struct {name} {{}}\n").unwrap(),
+        }
+    }
+
+    let declared = |name: &str| functions.iter().any(|(func, _, _)| func == name);
+    for (name, decl) in PRELUDE_FUNCTIONS {
+        if !declared(name) {
+            writeln!(out, "{decl} {{ fatalError() }}").unwrap();
+        }
+    }
+
+    for (name, params, ret) in &functions {
+        let params = params
+            .iter()
+            .enumerate()
+            .map(|(index, ty)| format!("_ p{index}: {ty}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(out, "func {name}({params}) -> {ret} {{ fatalError() }}").unwrap();
+    }
+
+    out
+}
+
+// Convenience function: the imported type of the parameter. The
+// out-parameters are written by the C function, so are mutable.
+fn param_type(param: &ParamInfo) -> String {
+    import_type(&param.ty, param.is_out)
+}
+
+// Convenience function: the type of the object parameter of the property
+// `name`, i.e. the enum value or the pointer to the struct it belongs to.
+fn owner_type(info: &FileInfo, name: &str) -> String {
+    let owner_enum = info
+        .enums
+        .iter()
+        .filter(|enm| name.starts_with(&enm.name))
+        .max_by_key(|enm| enm.name.len());
+    let owner_struct_len = info
+        .structs
+        .iter()
+        .filter(|strct| name.starts_with(&strct.name))
+        .map(|strct| strct.name.len())
+        .max()
+        .unwrap_or(0);
+    match owner_enum {
+        Some(enm) if enm.name.len() > owner_struct_len => enm.name.clone(),
+        _ => "OpaquePointer".to_string(),
+    }
+}
+
+// Convenience function: maps the C type onto the type the Swift importer of
+// the C headers declares, e.g. `TWData *_Nullable` as `UnsafeRawPointer?`.
+fn import_type(ty: &TypeInfo, is_mutable: bool) -> String {
+    let (res, is_pointer) = match &ty.variant {
+        TypeVariant::Struct(_) if !ty.is_pointer => (import_variant(&ty.variant), false),
+        // An opaque struct, e.g. `struct TWFoo *_Nonnull`.
+        TypeVariant::Struct(_) => ("OpaquePointer".to_string(), true),
+        TypeVariant::Data | TypeVariant::String | TypeVariant::Array(_) => {
+            (import_variant(&ty.variant), true)
+        }
+        TypeVariant::Pointer(elem) if is_mutable || !ty.is_constant => (
+            format!("UnsafeMutablePointer<{}>", import_variant(elem)),
+            true,
+        ),
+        TypeVariant::Pointer(elem) => (format!("UnsafePointer<{}>", import_variant(elem)), true),
+        variant => (import_variant(variant), false),
+    };
+    if is_pointer && ty.is_nullable {
+        format!("{res}?")
+    } else {
+        res
+    }
+}
+
+// Convenience function: the imported type of the non-nullable C type.
+fn import_variant(variant: &TypeVariant) -> String {
+    let res = match variant {
+        TypeVariant::Void => "Void",
+        TypeVariant::Bool => "Bool",
+        TypeVariant::Char => "CChar",
+        TypeVariant::ShortInt | TypeVariant::Int16T => "Int16",
+        TypeVariant::Int | TypeVariant::Int32T => "Int32",
+        TypeVariant::UnsignedInt | TypeVariant::UInt32T => "UInt32",
+        TypeVariant::LongInt | TypeVariant::SizeT => "Int",
+        TypeVariant::Float => "Float",
+        TypeVariant::Double => "Double",
+        TypeVariant::Int8T => "Int8",
+        TypeVariant::Int64T => "Int64",
+        TypeVariant::UInt8T => "UInt8",
+        TypeVariant::UInt16T => "UInt16",
+        TypeVariant::UInt64T => "UInt64",
+        TypeVariant::Data | TypeVariant::String => "UnsafeRawPointer",
+        TypeVariant::Enum(name) => name,
+        // The value structs are declared by the stub, see `referenced_types`.
+        TypeVariant::Struct(name) => name,
+        // E.g. `const uint8_t *_Nonnull bytes`.
+        TypeVariant::Pointer(elem) => return format!("UnsafePointer<{}>", import_variant(elem)),
+        // A `TWDataVector`.
+        TypeVariant::Array(elem) if **elem == TypeVariant::Data => "OpaquePointer",
+        // An array of pointers to structs.
+        TypeVariant::Array(_) => "UnsafePointer<OpaquePointer?>",
+    };
+    res.to_string()
+}
+
+// Convenience function: the C enums and value structs referred to by the
+// functions of `info`, mapped onto the raw value type of the enum, or `None`
+// for a struct. The raw value type of the enums declared in other manifests is
+// assumed to be `UInt32`.
+fn referenced_types(info: &FileInfo) -> BTreeMap<String, Option<String>> {
+    fn collect(ty: &TypeInfo, out: &mut BTreeMap<String, Option<String>>) {
+        match &ty.variant {
+            TypeVariant::Enum(name) => {
+                out.entry(name.clone())
+                    .or_insert_with(|| Some("UInt32".to_string()));
+            }
+            TypeVariant::Struct(name) if !ty.is_pointer => {
+                out.entry(name.clone()).or_insert(None);
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    for enm in &info.enums {
+        let raw_type = import_variant(&enm.value_type);
+        out.insert(enm.name.clone(), Some(raw_type));
+    }
+    let params = info
+        .inits
+        .iter()
+        .flat_map(|init| &init.params)
+        .chain(info.functions.iter().flat_map(|func| &func.params));
+    for param in params {
+        collect(&param.ty, &mut out);
+    }
+    let returns = info
+        .functions
+        .iter()
+        .map(|func| &func.return_type)
+        .chain(info.properties.iter().map(|prop| &prop.return_type));
+    for ty in returns {
+        collect(ty, &mut out);
+    }
+    out
+}
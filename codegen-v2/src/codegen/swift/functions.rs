@@ -3,8 +3,70 @@
 // Copyright © 2017 Trust Wallet.
 
 use super::*;
-use crate::manifest::{FunctionInfo, TypeVariant};
-use heck::ToLowerCamelCase;
+use crate::manifest::{DeprecationInfo, FunctionInfo, ReturnOwnership, TypeVariant};
+
+/// Name suffixes (after the object name) of the hash functions, e.g. `TWFooHash(obj)`.
+const HASH_SUFFIXES: &[&str] = &["Hash", "HashValue"];
+
+/// Takes the hash function of the passed on class out of `functions`, if the
+/// class has an equality function as well, e.g. `TWFooEqual(lhs, rhs)` and
+/// `TWFooHash(obj)`. The class then conforms to `Hashable`.
+///
+/// The hash function must be non-static, accept the object as its only
+/// parameter and return a non-nullable value. The objects are not expected to
+/// be `Hashable` in Swift, so they are not accepted as the return type.
+///
+/// This function returns a tuple of the `Hashable` conformance, if any, and
+/// the remaining functions.
+pub(super) fn take_hash_function(
+    object: &ObjectVariant,
+    mut functions: Vec<FunctionInfo>,
+) -> (Option<SwiftConformanceHashable>, Vec<FunctionInfo>) {
+    let equal_name = format!("{}Equal", object.name());
+    if !functions.iter().any(|func| func.name == equal_name) {
+        return (None, functions);
+    }
+
+    let idx = functions.iter().position(|func| {
+        let is_hash_name = func
+            .name
+            .strip_prefix(object.name())
+            .is_some_and(|suffix| HASH_SUFFIXES.contains(&suffix));
+
+        is_hash_name
+            && !func.is_static
+            && func.params.len() == 1
+            && matches!(
+                &func.params[0].ty.variant,
+                TypeVariant::Struct(name) if name == object.name()
+            )
+            && !func.return_type.is_nullable
+            && !matches!(
+                func.return_type.variant,
+                TypeVariant::Void | TypeVariant::Struct(_)
+            )
+    });
+    let Some(idx) = idx else {
+        return (None, functions);
+    };
+
+    // Remove the function, so it's not rendered as a method as well.
+    let func = functions.remove(idx);
+    let combine = match wrap_return(&func.return_type) {
+        SwiftOperation::Return { call } => call,
+        // The raw value hashes the same, without constructing the enum.
+        SwiftOperation::ReturnEnum { .. } => "result.rawValue".to_string(),
+        // Panicking implies bug, `wrap_return` returns the value.
+        _ => unreachable!(),
+    };
+
+    let hashable = SwiftConformanceHashable {
+        c_ffi_name: func.name,
+        combine,
+        value_type: SwiftType::from(func.return_type.variant),
+    };
+    (Some(hashable), functions)
+}
 
 /// This function checks each function and determines whether there's an
 /// association with the passed on object (struct or enum), based on common name
@@ -15,17 +77,20 @@ use heck::ToLowerCamelCase;
 pub(super) fn process_methods(
     object: &ObjectVariant,
     functions: Vec<FunctionInfo>,
+    naming: &NamingConfig,
 ) -> Result<(Vec<SwiftFunction>, Vec<FunctionInfo>)> {
     let mut swift_funcs = vec![];
     let mut skipped_funcs = vec![];
 
     for func in functions {
         if !func.name.starts_with(object.name()) {
-            // Function is not assciated with the object.
+            // Function is not associated with the object.
             skipped_funcs.push(func);
             continue;
         }
 
+        let returns_self = returns_self(object, &func)?;
+
         let mut ops = vec![];
 
         // Initalize the 'self' type, which is then passed on to the underlying
@@ -51,105 +116,188 @@ pub(super) fn process_methods(
 
         // For each parameter, we track a list of `params` which is used for the
         // function interface and add the necessary operations on how to process
-        // those parameters.
-        let mut params = vec![];
-        for param in func.params {
-            // Skip self parameter
-            match &param.ty.variant {
-                TypeVariant::Enum(name) | TypeVariant::Struct(name) if name == object.name() => {
-                    continue
-                }
-                _ => {}
-            }
-
-            // Convert parameter to Swift parameter for the function interface.
-            params.push(SwiftParam {
-                name: param.name.clone(),
-                param_type: SwiftType::from(param.ty.variant.clone()),
-                is_nullable: param.ty.is_nullable,
-            });
-
-            // Process parameter.
-            if let Some(op) = param_c_ffi_call(&param) {
-                ops.push(op)
-            }
+        // those parameters. The self parameter, i.e. the first parameter of a
+        // non-static function, is skipped. Any other parameter of the object's
+        // own type is processed as usual, e.g. `TWFooCombine(TWFoo* obj, TWFoo* other)`.
+        let mut func_params = func.params;
+        let has_self_param = func_params.first().is_some_and(|param| {
+            matches!(
+                &param.ty.variant,
+                TypeVariant::Enum(name) | TypeVariant::Struct(name) if name == object.name()
+            )
+        });
+        if !func.is_static && has_self_param {
+            func_params.remove(0);
         }
+        let SwiftParams {
+            params,
+            ops: param_ops,
+            args,
+            out,
+        } = process_params(&func.name, func_params)?;
+        ops.extend(param_ops);
+
+        check_default_values(&func.name, &params)?;
+        check_labels(&func.name, &params)?;
 
         // Prepepare parameter list to be passed on to the underlying C FFI function.
-        let param_name = if func.is_static { vec![] } else { vec!["obj"] };
+        let param_name = if func.is_static {
+            vec![]
+        } else {
+            vec!["obj".to_string()]
+        };
         let param_names = param_name
             .into_iter()
-            .chain(params.iter().map(|p| p.name.as_str()))
-            .collect::<Vec<&str>>()
+            .chain(args)
+            .collect::<Vec<String>>()
             .join(",");
 
-        // Call the underlying C FFI function, passing on the parameter list.
-        let (var_name, call) = (
-            "result".to_string(),
-            format!("{}({})", func.name, param_names),
-        );
-        if func.return_type.is_nullable {
-            ops.push(SwiftOperation::GuardedCall { var_name, call });
-        } else {
-            ops.push(SwiftOperation::Call {
-                var_name,
-                call,
-                defer: None,
+        let return_type = if let Some(out) = out {
+            // The returned `bool` indicates whether the C FFI function wrote
+            // into the out-parameter, e.g:
+            //
+            // ```swift
+            // guard TWFooGet(obj,result) else {
+            //     return nil
+            // }
+            // return Data(bytes: TWDataBytes(result), count: TWDataSize(result))
+            // ```
+            if func.return_type.variant != TypeVariant::Bool || func.return_type.is_nullable {
+                return Err(Error::BadFormat(format!(
+                    "'{}' has an out-parameter, but does not return a non-nullable 'bool'",
+                    func.name
+                )));
+            }
+
+            ops.push(SwiftOperation::CheckedCall {
+                call: format!("{}({})", func.name, param_names),
             });
-        }
+            ops.push(out.ret);
 
-        // Wrap result.
-        ops.push(wrap_return(&func.return_type));
+            SwiftReturn {
+                param_type: out.return_type,
+                is_nullable: true,
+            }
+        } else {
+            // Call the underlying C FFI function, passing on the parameter list.
+            // The result is not used if the function returns the object itself.
+            let var_name = match returns_self {
+                Some(ReturnOwnership::Borrowed) => "_",
+                _ => "result",
+            };
+            let (var_name, call) = (
+                var_name.to_string(),
+                format!("{}({})", func.name, param_names),
+            );
+            if func.return_type.is_nullable {
+                ops.push(SwiftOperation::GuardedCall { var_name, call });
+            } else {
+                ops.push(SwiftOperation::Call {
+                    var_name,
+                    call,
+                    defer: None,
+                });
+            }
 
-        // Convert return type for function interface.
-        let return_type = SwiftReturn {
-            param_type: SwiftType::from(func.return_type.variant),
-            is_nullable: func.return_type.is_nullable,
-        };
+            // Wrap result.
+            //
+            // If the function returns the object itself, we must not wrap the
+            // pointer into a new instance, since both instances would free the
+            // same pointer on deinit.
+            match returns_self {
+                Some(ReturnOwnership::Borrowed) => ops.push(SwiftOperation::Return {
+                    call: "self".to_string(),
+                }),
+                Some(ReturnOwnership::Owned) | None => ops.extend(wrap_return_with_ownership(
+                    &func.name,
+                    &func.return_type,
+                    func.return_ownership,
+                )?),
+            }
 
-        // Prettify name, remove object name prefix from this property.
-        let pretty_name = func
-            .name
-            .strip_prefix(object.name())
-            // Panicing implies bug, checked at the start of the loop.
-            .unwrap()
-            .to_lower_camel_case();
-
-        // Special handling: some functions do not follow standard camelCase
-        // convention.
-        #[rustfmt::skip]
-        let pretty_name = if object.name() == "TWStoredKey" {
-            pretty_name
-                .replace("Json", "JSON")
-                .replace("Hd", "HD")
-        } else if object.name() == "TWPublicKey" {
-            pretty_name
-                .replace("Der", "DER")
-        } else if object.name() == "TWHash" {
-            pretty_name
-                .replace("ripemd", "RIPEMD")
-                .replace("Ripemd", "RIPEMD")
-                .replace("sha512256", "sha512_256")
-                .replace("sha3256", "sha3_256")
-                .replace("sha256sha256", "sha256SHA256")
-        } else if object.name() == "TWAES" {
-            pretty_name
-                .replace("Cbc", "CBC")
-                .replace("Ctr", "CTR")
-        } else {
-            pretty_name
+            // Convert return type for function interface.
+            SwiftReturn {
+                param_type: SwiftType::from(func.return_type.variant),
+                is_nullable: func.return_type.is_nullable,
+            }
         };
 
-        swift_funcs.push(SwiftFunction {
+        // Prettify name, remove object name prefix from this function.
+        let pretty_name = naming.member_name(
+            object.name(),
+            func.name
+                .strip_prefix(object.name())
+                // Panicing implies bug, checked at the start of the loop.
+                .unwrap(),
+        );
+
+        let swift_func = SwiftFunction {
             name: pretty_name,
             is_public: func.is_public,
             is_static: func.is_static,
+            is_async: false,
             operations: ops,
             params,
             return_type,
+            deprecated: func.deprecated.as_ref().map(DeprecationInfo::message),
             comments: vec![],
-        });
+        };
+
+        if func.is_async {
+            let async_func = async_variant(&swift_func);
+            swift_funcs.push(swift_func);
+            swift_funcs.push(async_func);
+        } else {
+            swift_funcs.push(swift_func);
+        }
     }
 
     Ok((swift_funcs, skipped_funcs))
 }
+
+// Convenience function: returns the `async` variant of the blocking function,
+// which runs the latter on a background queue. The parameters are converted
+// by the blocking function, so both variants share the same operations, e.g.
+// `func sign(input: Data) async -> Data` calls `self.sign(input: input)`. For
+// static functions, `self` refers to the type itself.
+fn async_variant(func: &SwiftFunction) -> SwiftFunction {
+    let args = func
+        .params
+        .iter()
+        .map(|param| format!("{0}: {0}", param.name))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    SwiftFunction {
+        is_async: true,
+        operations: vec![SwiftOperation::AsyncCall {
+            call: format!("self.{}({})", func.name, args),
+        }],
+        ..func.clone()
+    }
+}
+
+// Convenience function: returns the ownership of the returned pointer if the
+// function is non-static and returns the object's own type, e.g.
+// `TWFooAddingBar(foo, bar) -> TWFoo`. The `returns_self` manifest flag is
+// required for such functions since it cannot be derived from the signature.
+fn returns_self(object: &ObjectVariant, func: &FunctionInfo) -> Result<Option<ReturnOwnership>> {
+    let returns_own_type = !func.is_static
+        && matches!(
+            &func.return_type.variant,
+            TypeVariant::Struct(name) if name == object.name()
+        );
+
+    match (returns_own_type, func.returns_self) {
+        (true, Some(ownership)) => Ok(Some(ownership)),
+        (true, None) => Err(Error::BadFormat(format!(
+            "'{}' returns the object's own type: 'returns_self' must be set to either 'owned' or 'borrowed'",
+            func.name
+        ))),
+        (false, Some(_)) => Err(Error::BadFormat(format!(
+            "'{}' has 'returns_self' set, but is not a non-static function returning the object's own type",
+            func.name
+        ))),
+        (false, None) => Ok(None),
+    }
+}
@@ -3,7 +3,10 @@
 // Copyright © 2017 Trust Wallet.
 
 use super::*;
-use crate::manifest::InitInfo;
+use crate::manifest::{DeprecationInfo, InitInfo};
+
+/// The error thrown by the throwing initializers, see `swift/Sources/TWError.swift`.
+const THROWING_INIT_ERROR: &str = "TWError.invalidInput";
 
 /// This function checks each constructor and determines whether there's an
 /// association with the passed on object (struct or enum), based on common name
@@ -20,7 +23,7 @@ pub(super) fn process_inits(
 
     for init in inits {
         if !init.name.starts_with(object.name()) {
-            // Init is not assciated with the object.
+            // Init is not associated with the object.
             skipped_inits.push(init);
             continue;
         }
@@ -30,30 +33,42 @@ pub(super) fn process_inits(
         // For each parameter, we track a list of `params` which is used for the
         // function interface and add the necessary operations on how to process
         // those parameters.
-        let mut params = vec![];
-        for param in init.params {
-            // Convert parameter to Swift parameter.
-            params.push(SwiftParam {
-                name: param.name.clone(),
-                param_type: SwiftType::from(param.ty.variant.clone()),
-                is_nullable: param.ty.is_nullable,
-            });
+        let SwiftParams {
+            params,
+            ops: param_ops,
+            args,
+            out,
+        } = process_params(&init.name, init.params)?;
+        ops.extend(param_ops);
 
-            // Process parameter.
-            if let Some(op) = param_c_ffi_call(&param) {
-                ops.push(op);
-            }
+        if out.is_some() {
+            return Err(Error::BadFormat(format!(
+                "init '{}' has an out-parameter, which is only supported for functions",
+                init.name
+            )));
         }
 
+        check_default_values(&init.name, &params)?;
+        check_labels(&init.name, &params)?;
+
         // Prepepare parameter list to be passed on to the underlying C FFI function.
-        let param_names = params
-            .iter()
-            .map(|p| p.name.as_str())
-            .collect::<Vec<&str>>()
-            .join(",");
+        let param_names = args.join(",");
 
         // Call the underlying C FFI function, passing on the parameter list.
-        if init.is_nullable {
+        if init.is_throwing {
+            if !init.is_nullable {
+                return Err(Error::BadFormat(format!(
+                    "init '{}' is throwing, but not nullable",
+                    init.name
+                )));
+            }
+
+            ops.push(SwiftOperation::ThrowingGuardedCall {
+                var_name: "result".to_string(),
+                call: format!("{}({})", init.name, param_names),
+                error: THROWING_INIT_ERROR.to_string(),
+            });
+        } else if init.is_nullable {
             ops.push(SwiftOperation::GuardedCall {
                 var_name: "result".to_string(),
                 call: format!("{}({})", init.name, param_names),
@@ -66,8 +81,17 @@ pub(super) fn process_inits(
             });
         }
 
-        // Note that we do not return a value here; the template sets a
-        // `self.rawValue = result` entry at the end of the constructor.
+        // Assign the result to the wrapped value. Structs wrap the C FFI pointer
+        // directly, while enums delegate to the raw value initializer. A nullable
+        // init already returned `nil` (or has thrown) in the guarded call above.
+        let assign = match object {
+            ObjectVariant::Struct(_) => "self.rawValue = result".to_string(),
+            ObjectVariant::Enum(_) if init.is_nullable && !init.is_throwing => {
+                "self.init(rawValue: result.rawValue)".to_string()
+            }
+            ObjectVariant::Enum(_) => "self.init(rawValue: result.rawValue)!".to_string(),
+        };
+        ops.push(SwiftOperation::InitAssign { call: assign });
 
         // Prettify name, remove object name prefix from this property.
         let pretty_name = init
@@ -79,10 +103,13 @@ pub(super) fn process_inits(
 
         swift_inits.push(SwiftInit {
             name: pretty_name,
-            is_nullable: init.is_nullable,
+            // A throwing initializer is not failable.
+            is_nullable: init.is_nullable && !init.is_throwing,
+            is_throwing: init.is_throwing,
             is_public: init.is_public,
             params,
             operations: ops,
+            deprecated: init.deprecated.as_ref().map(DeprecationInfo::message),
             comments: vec![],
         });
     }
@@ -101,7 +128,7 @@ pub(super) fn process_deinits(
         if deinit.name.starts_with(object.name()) {
             swift_deinits.push(deinit)
         } else {
-            // Deinit is not assciated with the object.
+            // Deinit is not associated with the object.
             skipped_deinits.push(deinit);
             continue;
         }
@@ -2,25 +2,49 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use self::functions::process_methods;
+use self::functions::{process_methods, take_hash_function};
 use self::inits::process_inits;
-use self::properties::process_properties;
-use self::render::pretty_name;
-use crate::manifest::{DeinitInfo, FileInfo, ParamInfo, ProtoInfo, TypeInfo, TypeVariant};
+use self::properties::{has_description, process_properties, take_getter_functions};
+use crate::manifest::{
+    DeinitInfo, FileInfo, ParamInfo, ProtoInfo, ReturnOwnership, TypeInfo, TypeVariant,
+};
 use crate::{Error, Result};
 use handlebars::Handlebars;
 use serde_json::json;
 use std::fmt::Display;
+use std::iter::Peekable;
+use std::vec::IntoIter;
 
+mod conformances;
+mod dev;
+mod diff;
+mod exclusions;
+mod ffi_stub;
 mod functions;
 mod inits;
+mod naming;
 mod properties;
 mod render;
+mod write;
 
 // Re-exports
+pub use self::conformances::ConformanceConfig;
+pub use self::dev::DevSession;
+pub use self::diff::{diff_with_dir, public_symbols, DiffReport, FileDiff, FileStatus};
+pub use self::exclusions::{glob_match, ExclusionConfig};
+pub use self::ffi_stub::render_ffi_stub;
+pub use self::naming::{NameReplacement, NamingConfig, ProtoNamingConvention};
 pub use self::render::{
-    generate_swift_types, render_to_strings, GeneratedSwiftTypes, GeneratedSwiftTypesStrings,
-    RenderIntput,
+    create_engine, exhaustive_enums, generate_swift_types, render_all, render_file_info,
+    render_generated, render_proto_registry, render_to_strings, GeneratedSwiftTypes,
+    GeneratedSwiftTypesStrings, OutputKinds, RenderConfig, RenderIntput, RenderOutput, SkippedItem,
+    SwiftTemplates, ENUM_TEMPLATE, EXCLUDED_BY_CONFIG, EXTENSION_TEMPLATE, NOT_PUBLIC,
+    PARTIAL_FUNC_TEMPLATE, PARTIAL_INIT_TEMPLATE, PARTIAL_PROP_TEMPLATE, PROTO_REGISTRY_FILE,
+    PROTO_REGISTRY_TEMPLATE, PROTO_TEMPLATE, STRUCT_TEMPLATE,
+};
+pub use self::write::{
+    find_orphans, generated_header, is_generated, with_generated_header, write_files, write_output,
+    WriteOptions, GENERATED_HEADER_PREFIX,
 };
 
 /// Represents a Swift struct or class.
@@ -32,6 +56,10 @@ pub struct SwiftStruct {
     init_instance: bool,
     superclasses: Vec<String>,
     eq_operator: Option<SwiftOperatorEquality>,
+    hashable: Option<SwiftConformanceHashable>,
+    /// Whether the `description` property adds the string conversion
+    /// conformances, see [`properties::has_description`].
+    has_description: bool,
     inits: Vec<SwiftInit>,
     deinits: Vec<DeinitInfo>,
     methods: Vec<SwiftFunction>,
@@ -44,6 +72,9 @@ pub struct SwiftEnum {
     name: String,
     is_public: bool,
     add_description: bool,
+    /// Whether `Codable` is implemented with the string representations of
+    /// the variants, see [`ConformanceConfig::codable_strings`].
+    codable_strings: bool,
     superclasses: Vec<String>,
     variants: Vec<SwiftEnumVariant>,
 }
@@ -62,13 +93,29 @@ pub struct SwiftEnumVariant {
 pub struct SwiftEnumExtension {
     name: String,
     init_instance: bool,
+    eq_operator: Option<SwiftOperatorEquality>,
+    inits: Vec<SwiftInit>,
     methods: Vec<SwiftFunction>,
     properties: Vec<SwiftProperty>,
+    raw_value_shim: Option<SwiftRawValueShim>,
+}
+
+/// The conversion between the raw values of a Swift enum and its C enum, if
+/// the C enum is backed by a type that has no Swift counterpart of a fixed
+/// size, e.g. `size_t`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwiftRawValueShim {
+    /// The C enum, e.g. `TWFoo`.
+    c_name: String,
+    /// The Swift type of the C enum raw value, e.g. `Int` for `size_t`.
+    c_type: String,
+    /// The raw value type of the Swift enum, e.g. `UInt32`.
+    swift_type: String,
 }
 
 // Wrapper around a valid Swift type (built in or custom). Meant to be used as
 // `<SwiftType as From<TypeVariant>>::from(...)`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SwiftType(String);
 
 impl Display for SwiftType {
@@ -83,10 +130,14 @@ pub struct SwiftFunction {
     pub name: String,
     pub is_public: bool,
     pub is_static: bool,
+    /// Whether this is the `async` variant of a blocking function.
+    pub is_async: bool,
     pub params: Vec<SwiftParam>,
     pub operations: Vec<SwiftOperation>,
     #[serde(rename = "return")]
     pub return_type: SwiftReturn,
+    /// Deprecation message of the deprecated function, if any.
+    pub deprecated: Option<String>,
     pub comments: Vec<String>,
 }
 
@@ -95,12 +146,23 @@ pub struct SwiftFunction {
 struct SwiftProperty {
     pub name: String,
     pub is_public: bool,
+    pub is_static: bool,
     pub operations: Vec<SwiftOperation>,
     #[serde(rename = "return")]
     pub return_type: SwiftReturn,
+    pub setter: Option<SwiftPropertySetter>,
+    /// Deprecation message of the deprecated property, if any.
+    pub deprecated: Option<String>,
     pub comments: Vec<String>,
 }
 
+/// Represents the `set` block of a read-write Swift property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SwiftPropertySetter {
+    pub operations: Vec<SwiftOperation>,
+    pub call: String,
+}
+
 /// The operation to be interpreted by the templating engine. This handles
 /// parameters and C FFI calls in an appropriate way, depending on context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,11 +208,109 @@ pub enum SwiftOperation {
     },
     // Results in:
     // ```swift
+    // guard <call> else {
+    //     return nil
+    // }
+    // ```
+    // ... for the C FFI functions returning `bool` to indicate success.
+    CheckedCall {
+        call: String,
+    },
+    // Results in:
+    // ```swift
+    // guard let <var_name> = <call> else {
+    //     throw <error>
+    // }
+    // ```
+    ThrowingGuardedCall {
+        var_name: String,
+        call: String,
+        error: String,
+    },
+    // Results in:
+    // ```swift
+    // let <var_name> = <create>
+    // defer {
+    //     <delete>
+    // }
+    // for item in <items> {
+    //     <body>
+    // }
+    // ```
+    ArrayLoop {
+        var_name: String,
+        create: String,
+        delete: String,
+        items: String,
+        body: Vec<String>,
+    },
+    // Results in:
+    // ```swift
+    // var <var_name> = <call>
+    // defer {
+    //     <defer>
+    // }
+    // ```
+    // ... the buffer of an out-parameter, declared with `let` if it's not
+    // passed on as `&<var_name>`.
+    AllocateOut {
+        var_name: String,
+        call: String,
+        is_mutable: bool,
+        defer: Option<String>,
+    },
+    // Results in:
+    // ```swift
+    // defer {
+    //     <call>
+    // }
+    // ```
+    Defer {
+        call: String,
+    },
+    // Results in:
+    // ```swift
+    // return await withCheckedContinuation { continuation in
+    //     DispatchQueue.global(qos: .userInitiated).async {
+    //         continuation.resume(returning: <call>)
+    //     }
+    // }
+    // ```
+    AsyncCall {
+        call: String,
+    },
+    // Results in:
+    // ```swift
+    // <call>
+    // ```
+    // ... as the final operation of an init, e.g. `self.rawValue = result`.
+    InitAssign {
+        call: String,
+    },
+    // Results in:
+    // ```swift
     // return <call>
     // ```
     Return {
         call: String,
     },
+    // Results in:
+    // ```swift
+    // return <call>!
+    // ```
+    // ... if the enum is exhaustive, otherwise:
+    // ```swift
+    // guard let value = <call> else {
+    //     fatalError("unexpected enum raw value")
+    // }
+    // return value
+    // ```
+    ReturnEnum {
+        call: String,
+        #[serde(skip)]
+        enum_name: String,
+        exhaustive: bool,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,6 +319,9 @@ pub struct SwiftParam {
     #[serde(rename = "type")]
     pub param_type: SwiftType,
     pub is_nullable: bool,
+    pub default_value: Option<String>,
+    /// Argument label of the call sites, `None` if it's the name itself.
+    pub label: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,21 +335,52 @@ pub struct SwiftReturn {
 pub struct SwiftInit {
     pub name: String,
     pub is_nullable: bool,
+    pub is_throwing: bool,
     pub is_public: bool,
     pub params: Vec<SwiftParam>,
     pub operations: Vec<SwiftOperation>,
+    /// Deprecation message of the deprecated init, if any.
+    pub deprecated: Option<String>,
     pub comments: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwiftProto {
+    /// The top-level alias, e.g. `BitcoinSigningInput`.
     pub name: String,
+    /// The module the message belongs to, e.g. `Bitcoin`.
+    pub module: String,
+    /// The message name within the module, e.g. `SigningInput`.
+    pub message: String,
     pub c_ffi_name: String,
+    /// Whether an extension with helpers should be rendered.
+    pub has_helpers: bool,
+    pub with_builder: bool,
+    pub serialized_data_or_throw: bool,
 }
 
+/// The `==` operator, calling the C FFI equality function, e.g.
+/// `TWFooEqual(lhs.rawValue, rhs.rawValue)`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwiftOperatorEquality {
     pub c_ffi_name: String,
+    /// The C FFI arguments of the operands, e.g. `TWFoo(rawValue: lhs.rawValue)`
+    /// of an enum.
+    pub lhs: String,
+    pub rhs: String,
+}
+
+/// The `Hashable` conformance, rendered as `func hash(into:)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwiftConformanceHashable {
+    /// The C FFI hash function, e.g. `TWFooHash(obj)`.
+    pub c_ffi_name: String,
+    /// The Swift value of the C FFI `result` to be combined into the hasher,
+    /// e.g. `TWDataNSData(result)`.
+    pub combine: String,
+    /// The Swift type of the combined value, e.g. `Data`.
+    #[serde(skip)]
+    pub value_type: SwiftType,
 }
 
 /// Used for the individual `process_*` functions.
@@ -203,14 +397,19 @@ impl<'a> ObjectVariant<'a> {
     }
 }
 
-impl TryFrom<ProtoInfo> for SwiftProto {
-    type Error = Error;
-
-    fn try_from(value: ProtoInfo) -> std::result::Result<Self, Self::Error> {
+impl SwiftProto {
+    /// Maps the proto declared in the `file` manifest, see [`ProtoNamingConvention`].
+    fn new(value: ProtoInfo, file: &str, naming: &ProtoNamingConvention) -> Result<Self> {
+        // Convert the name into an appropriate format.
+        let (module, message) = naming.message_parts(file, &value.name)?;
         Ok(SwiftProto {
-            // Convert the name into an appropriate format.
-            name: pretty_name(value.0.clone()),
-            c_ffi_name: value.0,
+            name: format!("{module}{message}"),
+            module,
+            message,
+            c_ffi_name: value.name,
+            has_helpers: value.with_builder || value.serialized_data_or_throw,
+            with_builder: value.with_builder,
+            serialized_data_or_throw: value.serialized_data_or_throw,
         })
     }
 }
@@ -244,12 +443,342 @@ impl From<TypeVariant> for SwiftType {
                 // structs/enums.
                 n.strip_prefix("TW").map(|n| n.to_string()).unwrap_or(n)
             }
+            // The bytes are passed on as a copied `[UInt8]` array.
+            TypeVariant::Pointer(elem) if *elem == TypeVariant::UInt8T => "Data".to_string(),
+            TypeVariant::Pointer(elem) => format!("UnsafePointer<{}>", SwiftType::from(*elem)),
+            TypeVariant::Array(elem) => format!("[{}]", SwiftType::from(*elem)),
         };
 
         SwiftType(res)
     }
 }
 
+/// Maps the value type of the C enum onto the raw value type of the Swift enum,
+/// which must be an integer of a fixed size. The `size_t` and `char` backed
+/// enums are rendered as `UInt32` and `UInt8` respectively, with a
+/// [`SwiftRawValueShim`]. Any other type is rejected.
+fn enum_raw_type(
+    enum_name: &str,
+    value_type: TypeVariant,
+) -> Result<(SwiftType, Option<SwiftRawValueShim>)> {
+    let (c_type, swift_type) = match value_type {
+        TypeVariant::ShortInt
+        | TypeVariant::Int
+        | TypeVariant::UnsignedInt
+        | TypeVariant::LongInt
+        | TypeVariant::Int8T
+        | TypeVariant::Int16T
+        | TypeVariant::Int32T
+        | TypeVariant::Int64T
+        | TypeVariant::UInt8T
+        | TypeVariant::UInt16T
+        | TypeVariant::UInt32T
+        | TypeVariant::UInt64T => return Ok((SwiftType::from(value_type), None)),
+        TypeVariant::SizeT => ("Int", "UInt32"),
+        TypeVariant::Char => ("CChar", "UInt8"),
+        _ => {
+            return Err(Error::BadFormat(format!(
+                "enum '{enum_name}' has an unsupported value type: {value_type:?}"
+            )))
+        }
+    };
+
+    let shim = SwiftRawValueShim {
+        c_name: enum_name.to_string(),
+        c_type: c_type.to_string(),
+        swift_type: swift_type.to_string(),
+    };
+    Ok((SwiftType(swift_type.to_string()), Some(shim)))
+}
+
+// Convenience function: converts the manifest parameter to the Swift
+// parameter of the function (or init) interface.
+fn swift_param(param: &ParamInfo) -> SwiftParam {
+    SwiftParam {
+        name: param.name.clone(),
+        param_type: SwiftType::from(param.ty.variant.clone()),
+        is_nullable: param.ty.is_nullable,
+        default_value: param.default_value.clone(),
+        // `name name: Type` is redundant, Swift warns about it.
+        label: param.label.clone().filter(|label| *label != param.name),
+    }
+}
+
+/// The parameters of a Swift function (or init) interface, the operations on
+/// how to process them and the arguments of the underlying C FFI call.
+struct SwiftParams {
+    params: Vec<SwiftParam>,
+    ops: Vec<SwiftOperation>,
+    args: Vec<String>,
+    /// The out-parameter written by the C FFI function, if any.
+    out: Option<SwiftOutParam>,
+}
+
+/// An out-parameter of a C FFI function, returned by the Swift function
+/// instead, see [`process_out_param`].
+struct SwiftOutParam {
+    return_type: SwiftType,
+    /// The operation returning the Swift value of the out-parameter.
+    ret: SwiftOperation,
+}
+
+// Convenience function: processes the parameters of the C FFI function `name`.
+// The number of elements parameter following a pointer to bytes or an array
+// of structs is not a part of the Swift interface, but derived from the
+// parameter instead, e.g. `bytes.count`. Neither is the out-parameter.
+fn process_params(name: &str, params: Vec<ParamInfo>) -> Result<SwiftParams> {
+    let mut swift_params = SwiftParams {
+        params: vec![],
+        ops: vec![],
+        args: vec![],
+        out: None,
+    };
+
+    let mut params = params.into_iter().peekable();
+    while let Some(param) = params.next() {
+        if param.is_out {
+            if swift_params.out.is_some() {
+                return Err(Error::BadFormat(format!(
+                    "'{}' has more than one out-parameter, which is not supported",
+                    name
+                )));
+            }
+
+            let (op, arg, out) = process_out_param(name, &param)?;
+            swift_params.ops.push(op);
+            swift_params.args.push(arg);
+            swift_params.out = Some(out);
+            continue;
+        }
+
+        let is_pointer_or_array = matches!(
+            param.ty.variant,
+            TypeVariant::Pointer(_) | TypeVariant::Array(_)
+        );
+        if is_pointer_or_array && param.ty.is_nullable {
+            return Err(Error::BadFormat(format!(
+                "'{}' parameter '{}' is a nullable pointer or array, which is not supported",
+                name, param.name
+            )));
+        }
+
+        // Convert parameter to Swift parameter for the function interface.
+        swift_params.params.push(swift_param(&param));
+
+        match &param.ty.variant {
+            // E.g. `let bytes = [UInt8](bytes)`, passed on as `bytes,bytes.count`.
+            TypeVariant::Pointer(elem) if **elem == TypeVariant::UInt8T => {
+                take_length_param(name, &param, &mut params)?;
+
+                swift_params.ops.push(SwiftOperation::Call {
+                    var_name: param.name.clone(),
+                    call: format!("[UInt8]({})", param.name),
+                    defer: None,
+                });
+                swift_params.args.push(param.name.clone());
+                swift_params.args.push(format!("{}.count", param.name));
+            }
+            // E.g. `let keys = keys.map { $0.rawValue }`, passed on as `keys,keys.count`.
+            TypeVariant::Array(elem) if matches!(**elem, TypeVariant::Struct(_)) => {
+                take_length_param(name, &param, &mut params)?;
+
+                swift_params.ops.push(SwiftOperation::Call {
+                    var_name: param.name.clone(),
+                    call: format!("{}.map {{ $0.rawValue }}", param.name),
+                    defer: None,
+                });
+                swift_params.args.push(param.name.clone());
+                swift_params.args.push(format!("{}.count", param.name));
+            }
+            // The data items are copied into a `TWDataVector` (which copies
+            // them again), passed on as `<param>Vector`.
+            TypeVariant::Array(elem) if **elem == TypeVariant::Data => {
+                let var_name = format!("{}Vector", param.name);
+                swift_params.ops.push(SwiftOperation::ArrayLoop {
+                    var_name: var_name.clone(),
+                    create: "TWDataVectorCreate()".to_string(),
+                    delete: format!("TWDataVectorDelete({var_name})"),
+                    items: param.name.clone(),
+                    body: vec![
+                        "let data = TWDataCreateWithNSData(item)".to_string(),
+                        "defer { TWDataDelete(data) }".to_string(),
+                        format!("TWDataVectorAdd({var_name}, data)"),
+                    ],
+                });
+                swift_params.args.push(var_name);
+            }
+            TypeVariant::Array(_) => {
+                return Err(Error::BadFormat(format!(
+                    "'{}' parameter '{}' is an array of neither data nor structs",
+                    name, param.name
+                )));
+            }
+            // Process parameter.
+            _ => {
+                if let Some(op) = param_c_ffi_call(&param) {
+                    swift_params.ops.push(op);
+                }
+                swift_params.args.push(param.name);
+            }
+        }
+    }
+
+    Ok(swift_params)
+}
+
+// Convenience function: allocates the buffer of the out-parameter `param`,
+// returning the operation, the argument of the C FFI call and the conversion
+// of the written value. Data is written into a `TWData` created by the
+// bindings, e.g. `TWData* result`, and the scalars into a local variable
+// passed on as `&<param>`, e.g. `uint32_t* count`.
+fn process_out_param(
+    name: &str,
+    param: &ParamInfo,
+) -> Result<(SwiftOperation, String, SwiftOutParam)> {
+    if param.ty.is_nullable {
+        return Err(Error::BadFormat(format!(
+            "'{}' out-parameter '{}' is nullable, which is not supported",
+            name, param.name
+        )));
+    }
+
+    let res = match &param.ty.variant {
+        TypeVariant::Data => (
+            SwiftOperation::AllocateOut {
+                var_name: param.name.clone(),
+                call: "TWDataCreateWithSize(0)".to_string(),
+                is_mutable: false,
+                defer: Some(format!("TWDataDelete({})", param.name)),
+            },
+            param.name.clone(),
+            SwiftOutParam {
+                return_type: SwiftType::from(TypeVariant::Data),
+                ret: SwiftOperation::Return {
+                    call: format!(
+                        "Data(bytes: TWDataBytes({0}), count: TWDataSize({0}))",
+                        param.name
+                    ),
+                },
+            },
+        ),
+        TypeVariant::Pointer(elem) if is_out_scalar(elem) => {
+            let return_type = SwiftType::from((**elem).clone());
+            (
+                SwiftOperation::AllocateOut {
+                    var_name: param.name.clone(),
+                    call: format!("{return_type}()"),
+                    is_mutable: true,
+                    defer: None,
+                },
+                format!("&{}", param.name),
+                SwiftOutParam {
+                    return_type,
+                    ret: SwiftOperation::Return {
+                        call: param.name.clone(),
+                    },
+                },
+            )
+        }
+        _ => {
+            return Err(Error::BadFormat(format!(
+                "'{}' out-parameter '{}' is neither data nor a pointer to a number or bool",
+                name, param.name
+            )))
+        }
+    };
+
+    Ok(res)
+}
+
+// Convenience function: whether the out-parameter can point to the type. The
+// Swift type must be zero-initializable, e.g. `UInt32()`.
+fn is_out_scalar(ty: &TypeVariant) -> bool {
+    matches!(
+        ty,
+        TypeVariant::Bool
+            | TypeVariant::ShortInt
+            | TypeVariant::Int
+            | TypeVariant::UnsignedInt
+            | TypeVariant::LongInt
+            | TypeVariant::Float
+            | TypeVariant::Double
+            | TypeVariant::SizeT
+            | TypeVariant::Int8T
+            | TypeVariant::Int16T
+            | TypeVariant::Int32T
+            | TypeVariant::Int64T
+            | TypeVariant::UInt8T
+            | TypeVariant::UInt16T
+            | TypeVariant::UInt32T
+            | TypeVariant::UInt64T
+    )
+}
+
+// Convenience function: takes the `size_t` number of elements parameter that
+// must follow the pointer (or array) parameter `param`.
+fn take_length_param(
+    name: &str,
+    param: &ParamInfo,
+    params: &mut Peekable<IntoIter<ParamInfo>>,
+) -> Result<ParamInfo> {
+    params
+        .next_if(|next| next.ty.variant == TypeVariant::SizeT)
+        .ok_or_else(|| {
+            Error::BadFormat(format!(
+                "'{}' parameter '{}' must be followed by a 'size_t' number of elements parameter",
+                name, param.name
+            ))
+        })
+}
+
+// Convenience function: checks the default values of the `params` of the
+// function (or init) `name`. Swift call sites can only omit the parameters
+// safely if the defaulted ones are trailing, so the parameters are never
+// reordered, but rejected instead.
+fn check_default_values(name: &str, params: &[SwiftParam]) -> Result<()> {
+    let mut defaulted: Option<&SwiftParam> = None;
+    for param in params {
+        match (&param.default_value, defaulted) {
+            (Some(value), _) if value == "nil" && !param.is_nullable => {
+                return Err(Error::BadFormat(format!(
+                    "'{}' parameter '{}' is not nullable, but defaults to 'nil'",
+                    name, param.name
+                )));
+            }
+            (Some(_), _) => defaulted = Some(param),
+            (None, Some(prev)) => {
+                return Err(Error::BadFormat(format!(
+                    "'{}' parameter '{}' has no default value, but follows the defaulted parameter '{}': only trailing parameters can have default values",
+                    name, param.name, prev.name
+                )));
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+// Convenience function: checks the argument labels of the `params` of the
+// function (or init) `name`. A label is either `_`, omitting the label at the
+// call sites, or an identifier.
+fn check_labels(name: &str, params: &[SwiftParam]) -> Result<()> {
+    for param in params {
+        let Some(label) = &param.label else {
+            continue;
+        };
+        let mut chars = label.chars();
+        let is_identifier = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if !is_identifier {
+            return Err(Error::BadFormat(format!(
+                "'{}' parameter '{}' has an invalid label '{}': expected '_' or an identifier",
+                name, param.name, label
+            )));
+        }
+    }
+    Ok(())
+}
+
 // Covenience function: process the parameter, returning the operation for
 // handling the C FFI call (if any).
 fn param_c_ffi_call(param: &ParamInfo) -> Option<SwiftOperation> {
@@ -352,13 +881,24 @@ fn wrap_return(ty: &TypeInfo) -> SwiftOperation {
         TypeVariant::Data => SwiftOperation::Return {
             call: "TWDataNSData(result)".to_string(),
         },
-        // E.g. `return SomeEnum(rawValue: result.rawValue)`
-        TypeVariant::Enum(_) => SwiftOperation::Return {
+        // E.g. `return SomeEnum(rawValue: result.rawValue)`. The failable
+        // enum init already returns an optional for nullable return types.
+        TypeVariant::Enum(_) if ty.is_nullable => SwiftOperation::Return {
             call: format!(
-                "{}(rawValue: result.rawValue)!",
+                "{}(rawValue: result.rawValue)",
                 SwiftType::from(ty.variant.clone())
             ),
         },
+        // The non-nullable ones are only force-unwrapped if the enum is
+        // exhaustive, see `GeneratedSwiftTypes::mark_exhaustive_enums`.
+        TypeVariant::Enum(enm) => SwiftOperation::ReturnEnum {
+            call: format!(
+                "{}(rawValue: result.rawValue)",
+                SwiftType::from(ty.variant.clone())
+            ),
+            enum_name: enm.clone(),
+            exhaustive: false,
+        },
         // E.g. `return SomeStruct(rawValue: result)`
         TypeVariant::Struct(_) => SwiftOperation::Return {
             call: format!("{}(rawValue: result)", SwiftType::from(ty.variant.clone())),
@@ -368,3 +908,164 @@ fn wrap_return(ty: &TypeInfo) -> SwiftOperation {
         },
     }
 }
+
+// Convenience function: copies the items of the returned `TWDataVector` into
+// a `[Data]` array, e.g:
+//
+// ```swift
+// defer {
+//     TWDataVectorDelete(result)
+// }
+// return (0..<TWDataVectorSize(result)).map { TWDataNSData(TWDataVectorGet(result, $0)!) }
+// ```
+//
+// The returned vector is owned by the caller unless specified otherwise, like
+// the vectors created by `TWDataVectorCreate`. The items are copies either way,
+// consumed by `TWDataNSData`.
+fn wrap_data_vector_return(ownership: Option<ReturnOwnership>) -> Vec<SwiftOperation> {
+    let mut ops = vec![];
+    if ownership != Some(ReturnOwnership::Borrowed) {
+        ops.push(SwiftOperation::Defer {
+            call: format!("{DATA_VECTOR}Delete(result)"),
+        });
+    }
+    ops.push(SwiftOperation::Return {
+        call: format!(
+            "(0..<{DATA_VECTOR}Size(result)).map {{ TWDataNSData({DATA_VECTOR}Get(result, $0)!) }}"
+        ),
+    });
+    ops
+}
+
+/// The C FFI type of a vector of data, e.g. the signatures passed on to
+/// `TWTransactionCompilerCompileWithSignatures`.
+const DATA_VECTOR: &str = "TWDataVector";
+
+/// Maps the `TWDataVector` parameters and return types onto arrays of data,
+/// i.e. `[Data]` in Swift, see [`TypeVariant::Array`]. The members of the
+/// `TWDataVector` object itself are rendered as is.
+fn map_data_vectors(info: &mut FileInfo) {
+    if info.structs.iter().any(|strct| strct.name == DATA_VECTOR) {
+        return;
+    }
+
+    let map = |ty: &mut TypeInfo| {
+        if ty.variant == TypeVariant::Struct(DATA_VECTOR.to_string()) {
+            ty.variant = TypeVariant::Array(Box::new(TypeVariant::Data));
+        }
+    };
+    for init in info.inits.iter_mut() {
+        init.params.iter_mut().for_each(|param| map(&mut param.ty));
+    }
+    for func in info.functions.iter_mut() {
+        func.params.iter_mut().for_each(|param| map(&mut param.ty));
+        map(&mut func.return_type);
+    }
+    for prop in info.properties.iter_mut() {
+        map(&mut prop.return_type);
+    }
+}
+
+// Convenience function: wraps the returned `TWString`/`TWData` respecting its
+// ownership, if specified. The result is copied without being consumed, and
+// the owned one is deleted afterwards. Otherwise, see `wrap_return`.
+//
+// Pointers and arrays cannot be returned, except for a `TWDataVector`, see
+// `wrap_data_vector_return`.
+fn wrap_return_with_ownership(
+    name: &str,
+    ty: &TypeInfo,
+    ownership: Option<ReturnOwnership>,
+) -> Result<Vec<SwiftOperation>> {
+    if let TypeVariant::Array(elem) = &ty.variant {
+        if **elem == TypeVariant::Data {
+            return Ok(wrap_data_vector_return(ownership));
+        }
+    }
+    if matches!(ty.variant, TypeVariant::Pointer(_) | TypeVariant::Array(_)) {
+        return Err(Error::BadFormat(format!(
+            "'{name}' returns a pointer or an array of other than data, which is not supported"
+        )));
+    }
+
+    let Some(ownership) = ownership else {
+        return Ok(vec![wrap_return(ty)]);
+    };
+
+    let (delete, call) = match ty.variant {
+        TypeVariant::String => (
+            "TWStringDelete(result)",
+            "String(utf8String: TWStringUTF8Bytes(result))!",
+        ),
+        TypeVariant::Data => (
+            "TWDataDelete(result)",
+            "Data(bytes: TWDataBytes(result), count: TWDataSize(result))",
+        ),
+        _ => {
+            return Err(Error::BadFormat(format!(
+                "'{name}' has 'return_ownership' set, but returns neither a string nor data"
+            )))
+        }
+    };
+
+    let mut ops = vec![];
+    if ownership == ReturnOwnership::Owned {
+        ops.push(SwiftOperation::Defer {
+            call: delete.to_string(),
+        });
+    }
+    ops.push(SwiftOperation::Return {
+        call: call.to_string(),
+    });
+    Ok(ops)
+}
+
+// Convenience function: checks that the structs and enums of the manifest have
+// the `TW` prefix and are named as Swift types once it's stripped, e.g. `TWFoo`
+// as `Foo`.
+fn check_object_names(info: &FileInfo) -> Result<()> {
+    let names = info
+        .structs
+        .iter()
+        .map(|strct| &strct.name)
+        .chain(info.enums.iter().map(|enm| &enm.name));
+    for name in names {
+        let is_identifier = name.strip_prefix("TW").is_some_and(|name| {
+            let pretty_name = render::pretty_name(name.to_string());
+            let mut chars = pretty_name.chars();
+            chars.next().is_some_and(char::is_alphabetic)
+                && chars.all(|c| c.is_alphanumeric() || c == '_')
+        });
+        if !is_identifier {
+            return Err(Error::BadObjectName {
+                file: info.name.clone(),
+                name: name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// Convenience function: checks that every parameter of the manifest functions
+// has a Swift counterpart. The other unsupported parameters are reported while
+// processing the function.
+fn check_param_types(info: &FileInfo) -> Result<()> {
+    let functions = info
+        .inits
+        .iter()
+        .map(|init| (&init.name, &init.params))
+        .chain(info.functions.iter().map(|func| (&func.name, &func.params)));
+    for (function, params) in functions {
+        let void_param = params
+            .iter()
+            .find(|param| param.ty.variant == TypeVariant::Void);
+        if let Some(param) = void_param {
+            return Err(Error::UnsupportedType {
+                file: info.name.clone(),
+                function: function.clone(),
+                ty: format!("void {}", param.name),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::{Error, Result};
+use heck::ToLowerCamelCase;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Fixups of the Swift member names, since some of them do not follow the
+/// standard camelCase convention, e.g. `exportJSON` instead of `exportJson`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NamingConfig {
+    /// Words replaced in every member name, e.g. `Json` -> `JSON`.
+    #[serde(default)]
+    pub acronyms: BTreeMap<String, String>,
+    /// Replacements applied to the member names of an object, by the C FFI
+    /// object name, e.g. `TWHash`.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, Vec<NameReplacement>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NameReplacement {
+    pub from: String,
+    pub to: String,
+}
+
+impl NamingConfig {
+    /// The default fixups, see `naming.yaml`.
+    pub fn builtin() -> Self {
+        // Panicking implies bug, the file is checked by the tests.
+        Self::from_yaml(include_str!("naming.yaml")).unwrap()
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Converts the C FFI member name without the object prefix into the
+    /// Swift member name, e.g. `ExportJSON` of `TWStoredKey` into `exportJSON`.
+    pub fn member_name(&self, object: &str, name: &str) -> String {
+        let camel = name.to_lower_camel_case();
+
+        // Split into words at the uppercase letters, e.g. `export`, `Json`.
+        let mut words: Vec<String> = vec![];
+        for c in camel.chars() {
+            match words.last_mut() {
+                Some(word) if !c.is_ascii_uppercase() => word.push(c),
+                _ => words.push(c.to_string()),
+            }
+        }
+
+        // The first word is lowercase in camelCase.
+        let mut pretty_name = String::with_capacity(camel.len());
+        for (idx, word) in words.iter().enumerate() {
+            match self.acronyms.get(word) {
+                Some(acronym) if idx > 0 => pretty_name.push_str(acronym),
+                _ => pretty_name.push_str(word),
+            }
+        }
+
+        for replacement in self.overrides.get(object).into_iter().flatten() {
+            pretty_name = pretty_name.replace(&replacement.from, &replacement.to);
+        }
+        pretty_name
+    }
+}
+
+/// The naming convention of the protobuf headers and messages, e.g. the
+/// `TW_Bitcoin_Proto_SigningInput` message of `TWBitcoinProto.h` is rendered
+/// as `BitcoinSigningInput` into `Protobuf/Bitcoin+Proto.swift`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProtoNamingConvention {
+    /// The prefix of the header and the message names, e.g. `TW`.
+    pub prefix: String,
+    /// The suffix of the header name, which also separates the module from
+    /// the message in the message names, e.g. `Proto`.
+    pub suffix: String,
+    /// The separator of the message name parts, e.g. `_`.
+    pub separator: String,
+}
+
+impl Default for ProtoNamingConvention {
+    fn default() -> Self {
+        ProtoNamingConvention {
+            prefix: "TW".to_string(),
+            suffix: "Proto".to_string(),
+            separator: "_".to_string(),
+        }
+    }
+}
+
+impl ProtoNamingConvention {
+    /// Converts the name of the header declaring the protos into the Swift
+    /// file name, e.g. `TWBitcoinProto` into `Bitcoin`.
+    pub fn file_name(&self, file: &str) -> Result<String> {
+        file.strip_prefix(&self.prefix)
+            .and_then(|name| name.strip_suffix(&self.suffix))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::BadFormat(format!(
+                    "proto file '{file}' does not follow the '{}<Name>{}' naming convention",
+                    self.prefix, self.suffix
+                ))
+            })
+    }
+
+    /// Converts the C name of the message declared in the `file` header into
+    /// the Swift type name, e.g. `TW_Bitcoin_Proto_SigningInput` into
+    /// `BitcoinSigningInput`.
+    pub fn message_name(&self, file: &str, name: &str) -> Result<String> {
+        let (module, message) = self.message_parts(file, name)?;
+        Ok(format!("{module}{message}"))
+    }
+
+    /// Splits the C name of the message declared in the `file` header into
+    /// the module and the message names, e.g. `TW_EthereumAbi_Proto_Param_Value`
+    /// into `EthereumAbi` and `ParamValue`.
+    pub fn message_parts(&self, file: &str, name: &str) -> Result<(String, String)> {
+        let sep = &self.separator;
+        let infix = format!("{sep}{}{sep}", self.suffix);
+
+        name.strip_prefix(&self.prefix)
+            .and_then(|name| name.strip_prefix(sep.as_str()))
+            .and_then(|name| name.split_once(&infix))
+            .filter(|(module, message)| !module.is_empty() && !message.is_empty())
+            .map(|(module, message)| (module.replace(sep, ""), message.replace(sep, "")))
+            .ok_or_else(|| {
+                Error::BadFormat(format!(
+                    "proto message '{name}' of file '{file}' does not follow the '{prefix}{sep}<Module>{infix}<Message>' naming convention",
+                    prefix = self.prefix
+                ))
+            })
+    }
+}
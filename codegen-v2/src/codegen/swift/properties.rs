@@ -3,52 +3,52 @@
 // Copyright © 2017 Trust Wallet.
 
 use super::*;
-use crate::manifest::PropertyInfo;
-use heck::ToLowerCamelCase;
+use crate::manifest::{DeprecationInfo, FunctionInfo, ParamInfo, PropertyInfo};
+
+/// Name prefixes (after the object name) of the functions that are rendered as
+/// Swift computed properties by default, e.g. `TWFooIsValid(obj)` as `isValid`.
+const GETTER_PREFIXES: &[&str] = &["Is", "Has", "Can", "Supports"];
 
 /// This function checks each property and determines whether there's an
 /// association with the passed on object (struct or enum), based on common name
 /// prefix, and maps the data into a Swift structure.
 ///
-/// This function returns a tuple of associated Swift properties and skipped
-/// respectively non-associated properties.
+/// Setter functions of the associated properties are taken out of `functions`,
+/// see [`take_setter`]. Unpaired setters remain as plain functions.
+///
+/// This function returns a tuple of associated Swift properties, skipped
+/// respectively non-associated properties and the remaining functions.
 pub(super) fn process_properties(
     object: &ObjectVariant,
     properties: Vec<PropertyInfo>,
-) -> Result<(Vec<SwiftProperty>, Vec<PropertyInfo>)> {
+    mut functions: Vec<FunctionInfo>,
+    naming: &NamingConfig,
+) -> Result<(Vec<SwiftProperty>, Vec<PropertyInfo>, Vec<FunctionInfo>)> {
     let mut swift_props = vec![];
     let mut skipped_props = vec![];
 
     for prop in properties {
         if !prop.name.starts_with(object.name()) {
-            // Property is not assciated with the object.
+            // Property is not associated with the object.
             skipped_props.push(prop);
             continue;
         }
 
         let mut ops = vec![];
 
-        // Initalize the 'self' type, which is then passed on to the underlying
-        // C FFI function.
-        ops.push(match object {
-            // E.g. `let obj = self.rawValue`
-            ObjectVariant::Struct(_) => SwiftOperation::Call {
-                var_name: "obj".to_string(),
-                call: "self.rawValue".to_string(),
-                defer: None,
-            },
-            // E.g. `let obj = TWSomeEnum(rawValue: self.rawValue")`
-            ObjectVariant::Enum(name) => SwiftOperation::Call {
-                var_name: "obj".to_string(),
-                call: format!("{}(rawValue: self.rawValue)", name),
-                defer: None,
-            },
-        });
-
-        // Call the underlying C FFI function, passing on the `obj` instance.
+        // Call the underlying C FFI function. Static properties take no
+        // arguments, the others are passed on the `obj` instance.
         //
         // E.g: `let result = TWSomeFunc(obj)`.
-        let (var_name, call) = ("result".to_string(), format!("{}(obj)", prop.name));
+        let call = if prop.is_static {
+            format!("{}()", prop.name)
+        } else {
+            // Initalize the 'self' type, which is then passed on to the
+            // underlying C FFI function.
+            ops.push(self_operation(object));
+            format!("{}(obj)", prop.name)
+        };
+        let var_name = "result".to_string();
         if prop.return_type.is_nullable {
             ops.push(SwiftOperation::GuardedCall { var_name, call });
         } else {
@@ -60,15 +60,28 @@ pub(super) fn process_properties(
         }
 
         // Wrap result.
-        ops.push(wrap_return(&prop.return_type));
+        ops.extend(wrap_return_with_ownership(
+            &prop.name,
+            &prop.return_type,
+            prop.return_ownership,
+        )?);
 
         // Prettify name, remove object name prefix from this property.
-        let pretty_name = prop
-            .name
-            .strip_prefix(object.name())
-            // Panicing implies bug, checked at the start of the loop.
-            .unwrap()
-            .to_lower_camel_case();
+        let pretty_name = naming.member_name(
+            object.name(),
+            prop.name
+                .strip_prefix(object.name())
+                // Panicing implies bug, checked at the start of the loop.
+                .unwrap(),
+        );
+
+        // Pair the property with its setter, if any. Static properties are
+        // read-only.
+        let setter = if prop.is_static {
+            None
+        } else {
+            take_setter(object, &prop, &mut functions).map(|setter| process_setter(object, setter))
+        };
 
         // Convert return type for property interface.
         let return_type = SwiftReturn {
@@ -79,11 +92,185 @@ pub(super) fn process_properties(
         swift_props.push(SwiftProperty {
             name: pretty_name,
             is_public: prop.is_public,
+            is_static: prop.is_static,
             operations: ops,
             return_type,
+            setter,
+            deprecated: prop.deprecated.as_ref().map(DeprecationInfo::message),
             comments: vec![],
         });
     }
 
-    Ok((swift_props, skipped_props))
+    Ok((swift_props, skipped_props, functions))
+}
+
+/// Takes the getter-like functions of the passed on object out of `functions`
+/// and converts them into properties, so they can be processed by
+/// [`process_properties`].
+///
+/// A function is considered a getter if it's non-static, accepts the object as
+/// its only parameter, returns a value and its name starts with one of the
+/// [`GETTER_PREFIXES`], e.g. `TWFooIsValid(obj)`. The `as_property` manifest
+/// flag overrides the naming heuristic in both directions.
+///
+/// This function returns a tuple of the converted properties and the remaining
+/// functions.
+pub(super) fn take_getter_functions(
+    object: &ObjectVariant,
+    functions: Vec<FunctionInfo>,
+) -> Result<(Vec<PropertyInfo>, Vec<FunctionInfo>)> {
+    let mut props = vec![];
+    let mut remaining = vec![];
+
+    for func in functions {
+        let suffix = match func.name.strip_prefix(object.name()) {
+            Some(suffix) => suffix,
+            None => {
+                // Function is not associated with the object.
+                remaining.push(func);
+                continue;
+            }
+        };
+
+        let is_getter_like = !func.is_static
+            && func.return_type.variant != TypeVariant::Void
+            && func.params.len() == 1
+            && matches!(
+                &func.params[0].ty.variant,
+                TypeVariant::Struct(name) | TypeVariant::Enum(name) if name == object.name()
+            );
+
+        let as_property = match func.as_property {
+            Some(true) if !is_getter_like => {
+                return Err(Error::BadFormat(format!(
+                    "'{}' cannot be rendered as a property: expected a non-static, non-void function accepting the object only",
+                    func.name
+                )));
+            }
+            Some(true) if func.is_async => {
+                return Err(Error::BadFormat(format!(
+                    "'{}' cannot be rendered as a property: async functions are rendered as methods",
+                    func.name
+                )));
+            }
+            Some(as_property) => as_property,
+            // The async variant can only be rendered as a method.
+            None => is_getter_like && !func.is_async && has_getter_prefix(suffix),
+        };
+
+        if !as_property {
+            remaining.push(func);
+            continue;
+        }
+
+        props.push(PropertyInfo {
+            name: func.name,
+            is_public: func.is_public,
+            is_static: false,
+            return_type: func.return_type,
+            setter: None,
+            return_ownership: func.return_ownership,
+            deprecated: func.deprecated,
+            comments: func.comments,
+        });
+    }
+
+    Ok((props, remaining))
+}
+
+/// Checks whether the properties contain a public, non-nullable `description`
+/// string, which satisfies `CustomStringConvertible` as is. The conformance
+/// reuses the property, so the C FFI function is still called once.
+pub(super) fn has_description(properties: &[SwiftProperty]) -> bool {
+    properties.iter().any(|prop| {
+        prop.name == "description"
+            && prop.is_public
+            && !prop.is_static
+            && prop.return_type.param_type.0 == "String"
+            && !prop.return_type.is_nullable
+    })
+}
+
+// Convenience function: checks whether the name starts with a getter prefix
+// followed by a new word, e.g. `IsValid`, but not `Issuer`.
+fn has_getter_prefix(name: &str) -> bool {
+    GETTER_PREFIXES.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| c.is_ascii_uppercase())
+    })
+}
+
+// Convenience function: initalizes the 'self' type.
+//
+// E.g.
+// - `let obj = self.rawValue`
+// - `let obj = TWSomeEnum(rawValue: self.rawValue")`
+fn self_operation(object: &ObjectVariant) -> SwiftOperation {
+    match object {
+        ObjectVariant::Struct(_) => SwiftOperation::Call {
+            var_name: "obj".to_string(),
+            call: "self.rawValue".to_string(),
+            defer: None,
+        },
+        ObjectVariant::Enum(name) => SwiftOperation::Call {
+            var_name: "obj".to_string(),
+            call: format!("{}(rawValue: self.rawValue)", name),
+            defer: None,
+        },
+    }
+}
+
+/// Looks up the setter of the given property and removes it from `functions`.
+///
+/// The setter is either explicitly named in the manifest, or follows the
+/// `<Object>Set<Property>` naming convention, e.g. `TWFooSetBar(obj, value)`
+/// for the `TWFooBar(obj)` getter. In both cases, the setter must be a
+/// non-static function that accepts a single value of the property type and
+/// returns nothing.
+fn take_setter(
+    object: &ObjectVariant,
+    prop: &PropertyInfo,
+    functions: &mut Vec<FunctionInfo>,
+) -> Option<FunctionInfo> {
+    let setter_name = match prop.setter {
+        Some(ref name) => name.clone(),
+        None => {
+            let prop_name = prop.name.strip_prefix(object.name())?;
+            format!("{}Set{}", object.name(), prop_name)
+        }
+    };
+
+    let idx = functions.iter().position(|func| {
+        func.name == setter_name
+            && !func.is_static
+            && func.return_type.variant == TypeVariant::Void
+            && func.params.len() == 2
+            && func.params[1].ty.variant == prop.return_type.variant
+    })?;
+
+    Some(functions.remove(idx))
+}
+
+// Convenience function: marshals the implicit `newValue` parameter of the
+// Swift setter and calls the underlying C FFI setter function.
+fn process_setter(object: &ObjectVariant, setter: FunctionInfo) -> SwiftPropertySetter {
+    let mut ops = vec![self_operation(object)];
+
+    // Reuse the parameter handling, e.g. `let newValue = TWStringCreateWithNSString(newValue)`.
+    let new_value = ParamInfo {
+        name: "newValue".to_string(),
+        ty: setter.params[1].ty.clone(),
+        default_value: None,
+        is_out: false,
+        label: None,
+    };
+    if let Some(op) = param_c_ffi_call(&new_value) {
+        ops.push(op);
+    }
+
+    SwiftPropertySetter {
+        operations: ops,
+        call: format!("{}(obj, newValue)", setter.name),
+    }
 }
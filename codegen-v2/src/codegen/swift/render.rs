@@ -3,6 +3,25 @@
 // Copyright © 2017 Trust Wallet.
 
 use super::{inits::process_deinits, *};
+use crate::codegen::stats::RenderStats;
+use crate::codegen::surface::{ApiSurface, ObjectKind, ObjectSurface};
+use crate::manifest::{FunctionInfo, InitInfo, PropertyInfo};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// The templates shipped with the crate, see [`SwiftTemplates::builtin`].
+pub const STRUCT_TEMPLATE: &str = include_str!("templates/struct.hbs");
+pub const ENUM_TEMPLATE: &str = include_str!("templates/enum.hbs");
+pub const EXTENSION_TEMPLATE: &str = include_str!("templates/extension.hbs");
+pub const PROTO_TEMPLATE: &str = include_str!("templates/proto.hbs");
+pub const PARTIAL_INIT_TEMPLATE: &str = include_str!("templates/partial_init.hbs");
+pub const PARTIAL_FUNC_TEMPLATE: &str = include_str!("templates/partial_func.hbs");
+pub const PARTIAL_PROP_TEMPLATE: &str = include_str!("templates/partial_prop.hbs");
+pub const PROTO_REGISTRY_TEMPLATE: &str = include_str!("templates/proto_registry.hbs");
+
+/// The path of the file rendered by [`render_proto_registry`].
+pub const PROTO_REGISTRY_FILE: &str = "Protobuf/ProtoRegistry.swift";
 
 #[derive(Debug, Clone)]
 pub struct RenderIntput<'a> {
@@ -14,6 +33,44 @@ pub struct RenderIntput<'a> {
     pub partial_init_template: &'a str,
     pub partial_func_tempalte: &'a str,
     pub partial_prop_tempalte: &'a str,
+    /// Fixups of the member names, see [`NamingConfig::builtin`].
+    pub naming: NamingConfig,
+    /// The items that are not rendered, see [`ExclusionConfig::builtin`].
+    pub exclusions: ExclusionConfig,
+    /// The protocols of the types, see [`ConformanceConfig::builtin`].
+    pub conformances: ConformanceConfig,
+    /// The naming of the protobuf file and messages, see [`ProtoNamingConvention`].
+    pub proto_naming: ProtoNamingConvention,
+    /// Whether the types and their members are sorted by name, see
+    /// [`GeneratedSwiftTypes::sort`]. Otherwise, the manifest order is kept.
+    pub stable_sort: bool,
+    /// The modules imported by every rendered file in addition to the ones
+    /// its types require, e.g. `WalletCoreSwiftProtobuf` of a platform that
+    /// ships the protobuf runtime separately.
+    pub extra_imports: Vec<String>,
+}
+
+impl RenderIntput<'static> {
+    /// Returns the input rendering `file_info` with the templates and the
+    /// configs shipped with the crate, sorted by name.
+    pub fn builtin(file_info: FileInfo) -> Self {
+        RenderIntput {
+            file_info,
+            struct_template: STRUCT_TEMPLATE,
+            enum_template: ENUM_TEMPLATE,
+            extension_template: EXTENSION_TEMPLATE,
+            proto_template: PROTO_TEMPLATE,
+            partial_init_template: PARTIAL_INIT_TEMPLATE,
+            partial_func_tempalte: PARTIAL_FUNC_TEMPLATE,
+            partial_prop_tempalte: PARTIAL_PROP_TEMPLATE,
+            naming: NamingConfig::builtin(),
+            exclusions: ExclusionConfig::builtin(),
+            conformances: ConformanceConfig::builtin(),
+            proto_naming: ProtoNamingConvention::default(),
+            stable_sort: true,
+            extra_imports: vec![],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -22,6 +79,51 @@ pub struct GeneratedSwiftTypesStrings {
     pub enums: Vec<(String, String)>,
     pub extensions: Vec<(String, String)>,
     pub protos: Vec<(String, String)>,
+    /// The rendered protos, to be collected across the manifests into the
+    /// registry, see [`render_proto_registry`].
+    pub proto_types: Vec<SwiftProto>,
+    /// Human-readable notes on API shape changes, see [`GeneratedSwiftTypes::changes`].
+    pub changes: Vec<String>,
+    /// The manifest items that are not rendered, see [`GeneratedSwiftTypes::skipped`].
+    pub skipped: Vec<SkippedItem>,
+    /// Issues of the manifest to be fixed, see [`GeneratedSwiftTypes::warnings`].
+    pub warnings: Vec<String>,
+    /// The rendered API surface, see [`GeneratedSwiftTypes::surface`].
+    pub surface: ApiSurface,
+    /// The functions that are not rendered, see [`GeneratedSwiftTypes::unclaimed`].
+    pub unclaimed: Vec<String>,
+    /// The measurement of the rendered API surface.
+    pub stats: RenderStats,
+}
+
+impl GeneratedSwiftTypesStrings {
+    /// Returns the rendered files as (relative path, content) pairs, e.g.
+    /// `Sources/Generated/AnyAddress.swift`, `Enums/CoinType.swift`,
+    /// `Extensions/CoinType+Extension.swift` or `Protobuf/Bitcoin+Proto.swift`.
+    pub fn into_files(self) -> Vec<(String, String)> {
+        let structs = self
+            .structs
+            .into_iter()
+            .map(|(name, out)| (format!("Sources/Generated/{name}.swift"), out));
+        let enums = self
+            .enums
+            .into_iter()
+            .map(|(name, out)| (format!("Enums/{name}.swift"), out));
+        let extensions = self
+            .extensions
+            .into_iter()
+            .map(|(name, out)| (format!("Extensions/{name}+Extension.swift"), out));
+        let protos = self
+            .protos
+            .into_iter()
+            .map(|(name, out)| (format!("Protobuf/{name}+Proto.swift"), out));
+
+        structs
+            .chain(enums)
+            .chain(extensions)
+            .chain(protos)
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -30,12 +132,127 @@ pub struct GeneratedSwiftTypes {
     pub enums: Vec<SwiftEnum>,
     pub extensions: Vec<SwiftEnumExtension>,
     pub protos: Vec<SwiftProto>,
+    /// Human-readable notes on C FFI functions that are rendered differently
+    /// than plain methods, e.g. as computed properties.
+    pub changes: Vec<String>,
+    /// The manifest items that are deliberately not rendered, so they can be
+    /// audited, e.g. the ones excluded by the [`ExclusionConfig`].
+    pub skipped: Vec<SkippedItem>,
+    /// Human-readable issues of the manifest that are worked around, but
+    /// should be fixed in the C headers, e.g. a `size_t`-backed enum.
+    pub warnings: Vec<String>,
+    /// The manifest items that are rendered, before the Swift specific mapping.
+    pub surface: ApiSurface,
+    /// The C FFI functions that no object of the manifest claimed, so they
+    /// are neither rendered nor skipped, e.g. of a misspelled object prefix.
+    pub unclaimed: Vec<String>,
+    /// See [`RenderIntput::extra_imports`].
+    pub extra_imports: Vec<String>,
+}
+
+impl GeneratedSwiftTypes {
+    /// Sorts the types by name, and their inits, methods and properties by
+    /// their Swift name, so the output doesn't depend on the order the C
+    /// headers are parsed in. The enum variants keep the order of their values.
+    pub fn sort(&mut self) {
+        self.structs.sort_by(|a, b| a.name.cmp(&b.name));
+        self.enums.sort_by(|a, b| a.name.cmp(&b.name));
+        self.extensions.sort_by(|a, b| a.name.cmp(&b.name));
+        self.protos.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for strct in self.structs.iter_mut() {
+            sort_members(&mut strct.inits, &mut strct.methods, &mut strct.properties);
+        }
+        for ext in self.extensions.iter_mut() {
+            sort_members(&mut ext.inits, &mut ext.methods, &mut ext.properties);
+        }
+    }
+
+    /// Force-unwraps the returned raw values of the given enums, see
+    /// [`exhaustive_enums`]. The other enums trap on an unexpected raw value.
+    pub fn mark_exhaustive_enums(&mut self, exhaustive: &BTreeSet<String>) {
+        for strct in self.structs.iter_mut() {
+            mark_members(&mut strct.methods, &mut strct.properties, exhaustive);
+        }
+        for ext in self.extensions.iter_mut() {
+            mark_members(&mut ext.methods, &mut ext.properties, exhaustive);
+        }
+    }
+}
+
+// Convenience function: marks the enum returns of the members, see
+// `GeneratedSwiftTypes::mark_exhaustive_enums`.
+fn mark_members(
+    methods: &mut [SwiftFunction],
+    properties: &mut [SwiftProperty],
+    exhaustive: &BTreeSet<String>,
+) {
+    let operations = methods
+        .iter_mut()
+        .flat_map(|func| func.operations.iter_mut())
+        .chain(
+            properties
+                .iter_mut()
+                .flat_map(|prop| prop.operations.iter_mut()),
+        );
+
+    for op in operations {
+        if let SwiftOperation::ReturnEnum {
+            enum_name,
+            exhaustive: is_exhaustive,
+            ..
+        } = op
+        {
+            *is_exhaustive = exhaustive.contains(enum_name);
+        }
+    }
+}
+
+/// Returns the names of the enums declared with `enum_exhaustive` by the
+/// manifests, e.g. `TWCoinType`.
+pub fn exhaustive_enums<'a>(infos: impl IntoIterator<Item = &'a FileInfo>) -> BTreeSet<String> {
+    infos
+        .into_iter()
+        .flat_map(|info| info.enums.iter())
+        .filter(|enm| enm.enum_exhaustive)
+        .map(|enm| enm.name.clone())
+        .collect()
+}
+
+// Convenience function: sorts the members by their Swift name. The sort is
+// stable, so an `async` variant stays next to its blocking function.
+fn sort_members(
+    inits: &mut [SwiftInit],
+    methods: &mut [SwiftFunction],
+    properties: &mut [SwiftProperty],
+) {
+    inits.sort_by(|a, b| a.name.cmp(&b.name));
+    methods.sort_by(|a, b| a.name.cmp(&b.name));
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// The reason of the items excluded by the [`ExclusionConfig`].
+pub const EXCLUDED_BY_CONFIG: &str = "excluded by config";
+
+/// The reason of the non-public members skipped with
+/// [`ExclusionConfig::skip_non_public`].
+pub const NOT_PUBLIC: &str = "not public";
+
+/// A manifest item that is not rendered.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SkippedItem {
+    /// The C FFI name of the object or member, e.g. `TWStoredKeyImportPrivateKeyUnsafe`.
+    pub name: String,
+    /// E.g. [`EXCLUDED_BY_CONFIG`].
+    pub reason: String,
 }
 
 /// Convenience wrapper for setting copyright year when generating bindings.
 #[derive(Debug, Clone, Serialize)]
 struct WithYear<'a, T> {
     pub current_year: u64,
+    /// The modules imported by the file, see [`file_imports`].
+    pub imports: Vec<String>,
     #[serde(flatten)]
     pub data: &'a T,
 }
@@ -44,128 +261,663 @@ pub fn pretty_name(name: String) -> String {
     name.replace("_", "").replace("TW", "").replace("Proto", "")
 }
 
+/// Returns the name of the file the protos of the manifest are rendered into,
+/// e.g. `Bitcoin` of `TWBitcoinProto`. Only the manifests declaring protos must
+/// follow the [`ProtoNamingConvention`].
+pub(super) fn pretty_file_name(
+    info: &FileInfo,
+    proto_naming: &ProtoNamingConvention,
+) -> Result<String> {
+    if info.protos.is_empty() {
+        Ok(pretty_name(info.name.clone()))
+    } else {
+        proto_naming.file_name(&info.name)
+    }
+}
+
 pub fn render_to_strings<'a>(input: RenderIntput<'a>) -> Result<GeneratedSwiftTypesStrings> {
-    // The current year for the copyright header in the generated bindings.
-    let current_year = crate::current_year();
+    let engine = create_engine(&SwiftTemplates::from(&input))?;
     // Convert the name into an appropriate format.
-    let pretty_file_name = pretty_name(input.file_info.name.clone());
+    let pretty_file_name = pretty_file_name(&input.file_info, &input.proto_naming)?;
+
+    let mut rendered = generate_swift_types(
+        input.file_info,
+        &input.naming,
+        &input.exclusions,
+        &input.conformances,
+        &input.proto_naming,
+    )?;
+    if input.stable_sort {
+        rendered.sort();
+    }
+    rendered.extra_imports = input.extra_imports;
+    render_generated(&engine, &rendered, &pretty_file_name, OutputKinds::all())
+}
+
+/// Renders the manifest of a single C header with the built-in templates and
+/// configs, see [`RenderIntput::builtin`].
+pub fn render_file_info(file_info: FileInfo) -> Result<GeneratedSwiftTypesStrings> {
+    render_to_strings(RenderIntput::builtin(file_info))
+}
+
+/// The configs applied to every manifest rendered by [`render_all`], see
+/// [`RenderIntput`].
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    pub naming: NamingConfig,
+    pub exclusions: ExclusionConfig,
+    pub conformances: ConformanceConfig,
+    pub proto_naming: ProtoNamingConvention,
+    pub stable_sort: bool,
+    /// See [`RenderIntput::extra_imports`].
+    pub extra_imports: Vec<String>,
+}
+
+impl RenderConfig {
+    /// Returns the configs shipped with the crate, sorted by name.
+    pub fn builtin() -> Self {
+        RenderConfig {
+            naming: NamingConfig::builtin(),
+            exclusions: ExclusionConfig::builtin(),
+            conformances: ConformanceConfig::builtin(),
+            proto_naming: ProtoNamingConvention::default(),
+            stable_sort: true,
+            extra_imports: vec![],
+        }
+    }
+}
+
+/// The bindings of all the manifests, see [`render_all`].
+#[derive(Debug, Clone, Default)]
+pub struct RenderOutput {
+    /// The rendered files as (relative path, content) pairs, including the
+    /// [`PROTO_REGISTRY_FILE`], see [`GeneratedSwiftTypesStrings::into_files`].
+    pub files: Vec<(String, String)>,
+    /// See [`GeneratedSwiftTypes::changes`].
+    pub changes: Vec<String>,
+    /// See [`GeneratedSwiftTypes::skipped`].
+    pub skipped: Vec<SkippedItem>,
+    /// See [`GeneratedSwiftTypes::warnings`].
+    pub warnings: Vec<String>,
+    /// The merged API surface of all the manifests.
+    pub surface: ApiSurface,
+    /// The C FFI functions that no object of any manifest claims, computed
+    /// once all the manifests are merged, see [`GeneratedSwiftTypes::unclaimed`].
+    pub unclaimed: Vec<String>,
+    /// The measurement of the merged API surface.
+    pub stats: RenderStats,
+}
+
+/// Renders the manifests of all the C headers, e.g. of the manifest directory,
+/// and merges the outputs in the order of the manifests.
+///
+/// The manifests are rendered in parallel with a single engine, since it's
+/// immutable once the templates are registered. The errors of all the
+/// manifests are returned together as [`Error::FileErrors`].
+///
+/// A manifest generating a Swift type of the same name as a previous manifest
+/// fails with an error naming both, since Swift can't resolve it. The proto
+/// aliases are checked by [`render_proto_registry`].
+///
+/// The functions left by every manifest are reported once all the manifests
+/// are merged: as [`RenderOutput::unclaimed`] if no object matches them, or
+/// as a warning if they match an object of another manifest.
+pub fn render_all(
+    inputs: Vec<FileInfo>,
+    templates: SwiftTemplates,
+    config: RenderConfig,
+) -> Result<RenderOutput> {
+    let engine = create_engine(&templates)?;
+    // The enums are often returned by the functions of other manifests.
+    let exhaustive = exhaustive_enums(&inputs);
+    // The C names of the objects, and the manifests declaring them.
+    let objects: Vec<(String, String)> = inputs
+        .iter()
+        .flat_map(|info| {
+            let names = info.structs.iter().map(|strct| strct.name.clone());
+            let names = names.chain(info.enums.iter().map(|enm| enm.name.clone()));
+            names.map(|name| (name, info.name.clone()))
+        })
+        .collect();
+
+    // The order of the manifests is kept, so the output is deterministic.
+    let results: Vec<(String, Result<RenderedManifest>)> = inputs
+        .into_par_iter()
+        .map(|file_info| {
+            let file_name = file_info.name.clone();
+            let manifest = render_manifest(&engine, file_info, &config, &exhaustive);
+            (file_name, manifest)
+        })
+        .collect();
+
+    let mut manifests = vec![];
+    let mut errors = vec![];
+    // The manifest generating the type, by the type name.
+    let mut types: BTreeMap<String, String> = BTreeMap::new();
+    for (file_name, result) in results {
+        let manifest = match result {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                errors.push((file_name, err));
+                continue;
+            }
+        };
+
+        let duplicates: Vec<String> = manifest
+            .type_names
+            .iter()
+            .filter_map(|name| match types.get(name) {
+                Some(other) => Some(format!(
+                    "type '{name}' is generated by both '{other}' and '{file_name}'"
+                )),
+                None => {
+                    types.insert(name.clone(), file_name.clone());
+                    None
+                }
+            })
+            .collect();
+        if duplicates.is_empty() {
+            manifests.push((file_name, manifest));
+        } else {
+            errors.push((file_name, Error::BadFormat(duplicates.join("; "))));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::FileErrors(errors));
+    }
+
+    let mut output = RenderOutput::default();
+    let mut protos = vec![];
+    // The functions left by the manifests, by the declaring manifest.
+    let mut leftovers = vec![];
+    for (file_name, manifest) in manifests {
+        let mut rendered = manifest.rendered;
+        output.changes.append(&mut rendered.changes);
+        output.skipped.append(&mut rendered.skipped);
+        output.warnings.append(&mut rendered.warnings);
+        leftovers.extend(
+            std::mem::take(&mut rendered.unclaimed)
+                .into_iter()
+                .map(|func| (file_name.clone(), func)),
+        );
+        output.surface.extend(std::mem::take(&mut rendered.surface));
+        protos.append(&mut rendered.proto_types);
+        output.files.extend(rendered.into_files());
+    }
+
+    // The functions are claimed by the objects of their own manifest only.
+    for (file_name, func) in leftovers {
+        let owner = objects
+            .iter()
+            .find(|(object, owner)| *owner != file_name && func.starts_with(object.as_str()));
+        match owner {
+            Some((object, owner)) => output.warnings.push(format!(
+                "'{func}' of '{file_name}' is not rendered, since it belongs to '{object}' of '{owner}'"
+            )),
+            None => output.unclaimed.push(func),
+        }
+    }
+
+    if !protos.is_empty() {
+        output.files.push(render_proto_registry(&protos)?);
+    }
+    output.stats = RenderStats::from_surface(&output.surface);
+
+    Ok(output)
+}
+
+/// The outputs of a single manifest, to be merged by [`render_all`].
+struct RenderedManifest {
+    /// The names of the structs and enums. The extensions are named after the
+    /// enums they extend.
+    type_names: Vec<String>,
+    rendered: GeneratedSwiftTypesStrings,
+}
+
+// Convenience function: generates the Swift types of a single manifest and
+// renders them with the shared engine.
+fn render_manifest(
+    engine: &Handlebars<'_>,
+    file_info: FileInfo,
+    config: &RenderConfig,
+    exhaustive: &BTreeSet<String>,
+) -> Result<RenderedManifest> {
+    let pretty_file_name = pretty_file_name(&file_info, &config.proto_naming)?;
+
+    let mut generated = generate_swift_types(
+        file_info,
+        &config.naming,
+        &config.exclusions,
+        &config.conformances,
+        &config.proto_naming,
+    )?;
+    generated.mark_exhaustive_enums(exhaustive);
+    if config.stable_sort {
+        generated.sort();
+    }
+    generated.extra_imports = config.extra_imports.clone();
+
+    let type_names = generated
+        .structs
+        .iter()
+        .map(|strct| strct.name.clone())
+        .chain(generated.enums.iter().map(|enm| enm.name.clone()))
+        .collect();
+    let rendered = render_generated(engine, &generated, &pretty_file_name, OutputKinds::all())?;
+
+    Ok(RenderedManifest {
+        type_names,
+        rendered,
+    })
+}
+
+/// The Swift templates, owned so they can be cached and reloaded.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SwiftTemplates {
+    pub struct_template: String,
+    pub enum_template: String,
+    pub extension_template: String,
+    pub proto_template: String,
+    pub partial_init_template: String,
+    pub partial_func_template: String,
+    pub partial_prop_template: String,
+}
+
+impl SwiftTemplates {
+    /// Returns the templates shipped with the crate.
+    pub fn builtin() -> Self {
+        SwiftTemplates {
+            struct_template: STRUCT_TEMPLATE.to_string(),
+            enum_template: ENUM_TEMPLATE.to_string(),
+            extension_template: EXTENSION_TEMPLATE.to_string(),
+            proto_template: PROTO_TEMPLATE.to_string(),
+            partial_init_template: PARTIAL_INIT_TEMPLATE.to_string(),
+            partial_func_template: PARTIAL_FUNC_TEMPLATE.to_string(),
+            partial_prop_template: PARTIAL_PROP_TEMPLATE.to_string(),
+        }
+    }
+
+    /// Reads the templates from the given directory, e.g. `src/codegen/swift/templates`.
+    pub fn read_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let read = |name: &str| std::fs::read_to_string(dir.as_ref().join(name));
+
+        Ok(SwiftTemplates {
+            struct_template: read("struct.hbs")?,
+            enum_template: read("enum.hbs")?,
+            extension_template: read("extension.hbs")?,
+            proto_template: read("proto.hbs")?,
+            partial_init_template: read("partial_init.hbs")?,
+            partial_func_template: read("partial_func.hbs")?,
+            partial_prop_template: read("partial_prop.hbs")?,
+        })
+    }
+
+    /// Returns the kinds of outputs that are rendered differently with the
+    /// `other` templates.
+    pub fn affected_outputs(&self, other: &SwiftTemplates) -> OutputKinds {
+        // The partials are included by the struct and extension templates.
+        let partials = self.partial_init_template != other.partial_init_template
+            || self.partial_func_template != other.partial_func_template
+            || self.partial_prop_template != other.partial_prop_template;
+
+        OutputKinds {
+            structs: partials || self.struct_template != other.struct_template,
+            enums: self.enum_template != other.enum_template,
+            extensions: partials || self.extension_template != other.extension_template,
+            protos: self.proto_template != other.proto_template,
+        }
+    }
+}
+
+impl<'a> From<&RenderIntput<'a>> for SwiftTemplates {
+    fn from(input: &RenderIntput<'a>) -> Self {
+        SwiftTemplates {
+            struct_template: input.struct_template.to_string(),
+            enum_template: input.enum_template.to_string(),
+            extension_template: input.extension_template.to_string(),
+            proto_template: input.proto_template.to_string(),
+            partial_init_template: input.partial_init_template.to_string(),
+            partial_func_template: input.partial_func_tempalte.to_string(),
+            partial_prop_template: input.partial_prop_tempalte.to_string(),
+        }
+    }
+}
+
+/// The kinds of the rendered outputs, see [`GeneratedSwiftTypesStrings`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct OutputKinds {
+    pub structs: bool,
+    pub enums: bool,
+    pub extensions: bool,
+    pub protos: bool,
+}
 
+impl OutputKinds {
+    pub fn all() -> Self {
+        OutputKinds {
+            structs: true,
+            enums: true,
+            extensions: true,
+            protos: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.structs || self.enums || self.extensions || self.protos)
+    }
+}
+
+/// Creates the rendering engine with the given templates registered.
+pub fn create_engine(templates: &SwiftTemplates) -> Result<Handlebars<'static>> {
     let mut engine = Handlebars::new();
     // Unmatched variables should result in an error.
     engine.set_strict_mode(true);
 
-    engine.register_partial("struct", input.struct_template)?;
-    engine.register_partial("enum", input.enum_template)?;
-    engine.register_partial("extension", input.extension_template)?;
-    engine.register_partial("proto", input.proto_template)?;
-    engine.register_partial("partial_init", input.partial_init_template)?;
-    engine.register_partial("partial_func", input.partial_func_tempalte)?;
-    engine.register_partial("partial_prop", input.partial_prop_tempalte)?;
+    engine.register_partial("struct", &templates.struct_template)?;
+    engine.register_partial("enum", &templates.enum_template)?;
+    engine.register_partial("extension", &templates.extension_template)?;
+    engine.register_partial("proto", &templates.proto_template)?;
+    engine.register_partial("partial_init", &templates.partial_init_template)?;
+    engine.register_partial("partial_func", &templates.partial_func_template)?;
+    engine.register_partial("partial_prop", &templates.partial_prop_template)?;
+
+    Ok(engine)
+}
+
+/// Renders the given `kinds` of outputs of the Swift types generated from a
+/// single manifest file. The protos are rendered into one file named after the
+/// `pretty_file_name`.
+pub fn render_generated(
+    engine: &Handlebars<'_>,
+    rendered: &GeneratedSwiftTypes,
+    pretty_file_name: &str,
+    kinds: OutputKinds,
+) -> Result<GeneratedSwiftTypesStrings> {
+    // The current year for the copyright header in the generated bindings.
+    let current_year = crate::current_year();
 
-    let rendered = generate_swift_types(input.file_info)?;
-    let mut out_str = GeneratedSwiftTypesStrings::default();
+    let mut out_str = GeneratedSwiftTypesStrings {
+        stats: RenderStats::from_surface(&rendered.surface),
+        changes: rendered.changes.clone(),
+        skipped: rendered.skipped.clone(),
+        warnings: rendered.warnings.clone(),
+        surface: rendered.surface.clone(),
+        unclaimed: rendered.unclaimed.clone(),
+        ..GeneratedSwiftTypesStrings::default()
+    };
 
     //  Render structs.
-    for strct in rendered.structs {
-        let out = engine.render(
+    for strct in rendered.structs.iter().filter(|_| kinds.structs) {
+        let out = render_object(
+            engine,
             "struct",
+            &strct.name,
             &WithYear {
                 current_year,
-                data: &strct,
+                imports: file_imports(struct_imports(strct), &rendered.extra_imports),
+                data: strct,
             },
         )?;
 
-        out_str.structs.push((strct.name, out));
+        out_str.structs.push((strct.name.clone(), out));
     }
 
     //  Render enums.
-    for enm in rendered.enums {
-        let out = engine.render(
+    for enm in rendered.enums.iter().filter(|_| kinds.enums) {
+        let out = render_object(
+            engine,
             "enum",
+            &enm.name,
             &WithYear {
                 current_year,
-                data: &enm,
+                // The enums refer to the integer types only.
+                imports: file_imports(BTreeSet::new(), &rendered.extra_imports),
+                data: enm,
             },
         )?;
 
-        out_str.enums.push((enm.name, out));
+        out_str.enums.push((enm.name.clone(), out));
     }
 
     //  Render extensions.
-    for ext in rendered.extensions {
-        let out = engine.render(
+    for ext in rendered.extensions.iter().filter(|_| kinds.extensions) {
+        let out = render_object(
+            engine,
             "extension",
+            &ext.name,
             &WithYear {
                 current_year,
-                data: &ext,
+                imports: file_imports(
+                    members_imports(&ext.inits, &ext.methods, &ext.properties),
+                    &rendered.extra_imports,
+                ),
+                data: ext,
             },
         )?;
 
-        out_str.extensions.push((ext.name, out));
+        out_str.extensions.push((ext.name.clone(), out));
     }
 
     //  Render protos.
-    if !rendered.protos.is_empty() {
-        let out = engine.render(
+    if kinds.protos && !rendered.protos.is_empty() {
+        // Only the helpers call into the protobuf runtime, the aliases don't.
+        let has_helpers = rendered.protos.iter().any(|proto| proto.has_helpers);
+        let required = if has_helpers {
+            BTreeSet::from(["Foundation", "SwiftProtobuf"])
+        } else {
+            BTreeSet::new()
+        };
+        let out = render_object(
+            engine,
             "proto",
+            pretty_file_name,
             &WithYear {
                 current_year,
+                imports: file_imports(required, &rendered.extra_imports),
                 data: &json!({
-                    "protos": &rendered.protos
+                    "protos": &rendered.protos,
+                    "has_helpers": has_helpers,
                 }),
             },
         )?;
 
-        out_str.protos.push((pretty_file_name, out));
+        out_str.protos.push((pretty_file_name.to_string(), out));
+        out_str.proto_types = rendered.protos.clone();
     }
 
     Ok(out_str)
 }
 
+/// The Swift types declared by Foundation, e.g. `Data` of a parameter.
+const FOUNDATION_TYPES: &[&str] = &["Data", "Date"];
+
+// Convenience function: the modules the struct requires, see `members_imports`.
+fn struct_imports(strct: &SwiftStruct) -> BTreeSet<&'static str> {
+    let mut imports = members_imports(&strct.inits, &strct.methods, &strct.properties);
+    if let Some(hashable) = &strct.hashable {
+        imports.extend(type_imports(&hashable.value_type));
+    }
+    imports
+}
+
+// Convenience function: the modules the members require, i.e. Foundation if
+// any member refers to a Foundation type or is `async`, dispatching the call
+// onto a `DispatchQueue`.
+fn members_imports(
+    inits: &[SwiftInit],
+    methods: &[SwiftFunction],
+    properties: &[SwiftProperty],
+) -> BTreeSet<&'static str> {
+    let types = inits
+        .iter()
+        .flat_map(|init| init.params.iter().map(|param| &param.param_type))
+        .chain(methods.iter().flat_map(|func| {
+            func.params
+                .iter()
+                .map(|param| &param.param_type)
+                .chain(std::iter::once(&func.return_type.param_type))
+        }))
+        .chain(properties.iter().map(|prop| &prop.return_type.param_type));
+
+    let mut imports: BTreeSet<_> = types.flat_map(type_imports).collect();
+    if methods.iter().any(|func| func.is_async) {
+        imports.insert("Foundation");
+    }
+    imports
+}
+
+// Convenience function: the module declaring the type, if it's not a part of
+// the standard library, e.g. Foundation of `[Data]`.
+fn type_imports(ty: &SwiftType) -> Option<&'static str> {
+    ty.0.split(|c: char| !c.is_alphanumeric())
+        .any(|name| FOUNDATION_TYPES.contains(&name))
+        .then_some("Foundation")
+}
+
+// Convenience function: the modules imported by a rendered file, i.e. the
+// `required` ones and the `extra` ones of the `RenderIntput`, sorted by name.
+fn file_imports(required: BTreeSet<&str>, extra: &[String]) -> Vec<String> {
+    required
+        .into_iter()
+        .map(str::to_string)
+        .chain(extra.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+// Convenience function: renders the `template` with the data of the `object`,
+// and names both in the error.
+fn render_object<T: serde::Serialize>(
+    engine: &Handlebars<'_>,
+    template: &'static str,
+    object: &str,
+    data: &T,
+) -> Result<String> {
+    engine
+        .render(template, data)
+        .map_err(|source| Error::TemplateRender {
+            template,
+            object: object.to_string(),
+            payload: Box::new(serde_json::to_value(data).unwrap_or_default()),
+            source: Box::new(source),
+        })
+}
+
+/// Renders the registry of the protos of all the manifests, i.e. every alias
+/// grouped by the module, e.g. `ProtoRegistry.Bitcoin.SigningInput`. Returns
+/// the (relative path, content) pair of [`PROTO_REGISTRY_FILE`].
+///
+/// The modules and their messages are sorted by name. The top-level aliases,
+/// e.g. `BitcoinSigningInput`, must be unique across the manifests, otherwise
+/// Swift can't resolve them.
+pub fn render_proto_registry(protos: &[SwiftProto]) -> Result<(String, String)> {
+    let mut aliases: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut modules: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+    for proto in protos {
+        if let Some(other) = aliases.insert(&proto.name, &proto.c_ffi_name) {
+            return Err(Error::BadFormat(format!(
+                "proto alias '{}' is declared by both '{other}' and '{}'",
+                proto.name, proto.c_ffi_name
+            )));
+        }
+
+        // Unique within the module as well, since the alias is the module
+        // name followed by the message name.
+        modules
+            .entry(&proto.module)
+            .or_default()
+            .insert(&proto.message, &proto.c_ffi_name);
+    }
+
+    let modules: Vec<_> = modules
+        .into_iter()
+        .map(|(name, messages)| {
+            let messages: Vec<_> = messages
+                .into_iter()
+                .map(|(name, c_ffi_name)| json!({ "name": name, "c_ffi_name": c_ffi_name }))
+                .collect();
+            json!({ "name": name, "messages": messages })
+        })
+        .collect();
+
+    let mut engine = Handlebars::new();
+    // Unmatched variables should result in an error.
+    engine.set_strict_mode(true);
+    let out = engine.render_template(PROTO_REGISTRY_TEMPLATE, &json!({ "modules": modules }))?;
+
+    Ok((PROTO_REGISTRY_FILE.to_string(), out))
+}
+
 /// Uses the given input templates to render all files.
-pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
-    let mut outputs = GeneratedSwiftTypes::default();
+pub fn generate_swift_types(
+    mut info: FileInfo,
+    naming: &NamingConfig,
+    exclusions: &ExclusionConfig,
+    conformances: &ConformanceConfig,
+    proto_naming: &ProtoNamingConvention,
+) -> Result<GeneratedSwiftTypes> {
+    check_object_names(&info)?;
+    check_param_types(&info)?;
+
+    // Remove the excluded items, so they are neither rendered nor associated
+    // with another object.
+    let mut outputs = GeneratedSwiftTypes {
+        skipped: apply_exclusions(&mut info, naming, exclusions),
+        ..Default::default()
+    };
+    map_data_vectors(&mut info);
+    let exhaustive = exhaustive_enums([&info]);
 
-    // Render structs/classes.
-    for strct in info.structs {
+    // Render structs/classes. The structs are taken out, so that the remaining
+    // manifest items can be snapshotted.
+    for strct in std::mem::take(&mut info.structs) {
         let obj = ObjectVariant::Struct(&strct.name);
+        let unprocessed = Unprocessed::snapshot(&info);
 
         // Process items.
-        let (inits, deinits, mut methods, properties);
+        let (inits, deinits, mut methods, properties, hashable);
         (inits, info.inits) = process_inits(&obj, info.inits)?;
         (deinits, info.deinits) = process_deinits(&obj, info.deinits)?;
-        (methods, info.functions) = process_methods(&obj, info.functions)?;
-        (properties, info.properties) = process_properties(&obj, info.properties)?;
+        (info.properties, info.functions) =
+            process_getter_functions(&obj, info.properties, info.functions, naming, &mut outputs)?;
+        (properties, info.properties, info.functions) =
+            process_properties(&obj, info.properties, info.functions, naming)?;
+        (hashable, info.functions) = if strct.is_class {
+            take_hash_function(&obj, info.functions)
+        } else {
+            (None, info.functions)
+        };
+        (methods, info.functions) = process_methods(&obj, info.functions, naming)?;
 
         // Avoid rendering empty structs.
         if inits.is_empty() && methods.is_empty() && properties.is_empty() {
             continue;
         }
 
+        let kind = if strct.is_class {
+            ObjectKind::Class
+        } else {
+            ObjectKind::Struct
+        };
+        unprocessed.add_processed_to_surface(&strct.name, kind, &info, &mut outputs.surface);
+
         // Convert the name into an appropriate format.
         let pretty_struct_name = pretty_name(strct.name.clone());
 
         // Add superclasses.
-        let superclasses = if pretty_struct_name.ends_with("Address") {
-            vec!["Address".to_string()]
-        } else {
-            vec![]
-        };
+        let mut derived = vec![];
+        if hashable.is_some() {
+            derived.push("Hashable");
+        }
+        let has_description = has_description(&properties);
+        if has_description {
+            derived.extend(["CustomStringConvertible", "CustomDebugStringConvertible"]);
+        }
+        let superclasses = conformances.struct_protocols(&pretty_struct_name, &derived);
 
         // Handle equality operator.
-        let eq_method = methods.iter().enumerate().find(|(_, f)| f.name == "equal");
-        let eq_operator = if let Some((idx, _)) = eq_method {
-            let operator = SwiftOperatorEquality {
-                c_ffi_name: format!("{}Equal", strct.name),
-            };
-
-            // Remove that method from the `methods` list.
-            methods.remove(idx);
-
-            Some(operator)
-        } else {
-            None
-        };
+        let eq_operator = take_eq_operator(&obj, &mut methods);
 
         outputs.structs.push(SwiftStruct {
             name: pretty_struct_name,
@@ -174,6 +926,8 @@ pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
             init_instance: strct.is_class,
             superclasses,
             eq_operator,
+            hashable,
+            has_description,
             inits: inits,
             deinits: deinits,
             methods,
@@ -182,23 +936,58 @@ pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
     }
 
     // Render enums.
-    for enm in info.enums {
+    for enm in std::mem::take(&mut info.enums) {
         let obj = ObjectVariant::Enum(&enm.name);
+        let unprocessed = Unprocessed::snapshot(&info);
 
         // Process items.
-        let (methods, properties);
-        (methods, info.functions) = process_methods(&obj, info.functions)?;
-        (properties, info.properties) = process_properties(&obj, info.properties)?;
+        let (inits, mut methods, properties);
+        (inits, info.inits) = process_inits(&obj, info.inits)?;
+        (info.properties, info.functions) =
+            process_getter_functions(&obj, info.properties, info.functions, naming, &mut outputs)?;
+        (properties, info.properties, info.functions) =
+            process_properties(&obj, info.properties, info.functions, naming)?;
+        (methods, info.functions) = process_methods(&obj, info.functions, naming)?;
+        let eq_operator = take_eq_operator(&obj, &mut methods);
+
+        let enum_surface = unprocessed.add_processed_to_surface(
+            &enm.name,
+            ObjectKind::Enum,
+            &info,
+            &mut outputs.surface,
+        );
+        enum_surface.variants = enm.variants.iter().map(|v| v.name.clone()).collect();
+
+        // Map the value type before the name is taken.
+        let is_size_t = enm.value_type == TypeVariant::SizeT;
+        let (value_type, raw_value_shim) = enum_raw_type(&enm.name, enm.value_type)?;
 
         // Convert the name into an appropriate format.
         let pretty_enum_name = pretty_name(enm.name);
 
-        // Add superclasses.
-        let value_type = SwiftType::from(enm.value_type);
-        let mut superclasses = vec![value_type.0, "CaseIterable".to_string()];
+        if is_size_t {
+            outputs.warnings.push(format!(
+                "{pretty_enum_name}: the size_t-backed enum is rendered as UInt32, the raw values are converted in the extension"
+            ));
+        }
 
         let mut add_class = false;
 
+        // The strings must map to the variants in both directions.
+        let mut codable_strings = conformances.is_codable_strings(&pretty_enum_name);
+        if codable_strings {
+            let mut strings: Vec<_> = enm.variants.iter().map(|v| &v.as_string).collect();
+            let is_complete = strings.iter().all(|string| string.is_some());
+            strings.sort();
+            strings.dedup();
+            if !is_complete || strings.len() != enm.variants.len() {
+                outputs.warnings.push(format!(
+                    "{pretty_enum_name}: not every variant has a unique string representation, the enum is Codable by its raw values"
+                ));
+                codable_strings = false;
+            }
+        }
+
         // Convert to Swift enum variants
         let variants = enm
             .variants
@@ -216,37 +1005,246 @@ pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
             })
             .collect();
 
-        if add_class {
-            superclasses.push("CustomStringConvertible".to_string());
+        // Add superclasses, the raw value type comes first.
+        let derived: &[&str] = if add_class {
+            &["CustomStringConvertible"]
+        } else {
+            &[]
+        };
+        let mut superclasses: Vec<String> = std::iter::once(value_type.0)
+            .chain(conformances.enum_protocols(&pretty_enum_name, derived))
+            .collect();
+        if codable_strings && !superclasses.iter().any(|protocol| protocol == "Codable") {
+            superclasses.push("Codable".to_string());
         }
 
         outputs.enums.push(SwiftEnum {
             name: pretty_enum_name.clone(),
             is_public: enm.is_public,
             add_description: add_class,
+            codable_strings,
             superclasses,
             variants,
         });
 
         // Avoid rendering empty extension for enums.
-        if methods.is_empty() && properties.is_empty() {
+        if inits.is_empty()
+            && methods.is_empty()
+            && properties.is_empty()
+            && raw_value_shim.is_none()
+            && eq_operator.is_none()
+        {
             continue;
         }
 
         outputs.extensions.push(SwiftEnumExtension {
             name: pretty_enum_name,
             init_instance: true,
+            eq_operator,
+            inits,
             methods,
             properties,
+            raw_value_shim,
         });
     }
 
     // Render Protobufs.
-    if !info.protos.is_empty() {
-        for proto in info.protos {
-            outputs.protos.push(SwiftProto::try_from(proto)?);
-        }
+    for proto in info.protos {
+        outputs.surface.add_object(&proto.name, ObjectKind::Proto);
+        outputs
+            .protos
+            .push(SwiftProto::new(proto, &info.name, proto_naming)?);
     }
 
+    outputs.unclaimed = info.functions.into_iter().map(|func| func.name).collect();
+    outputs.mark_exhaustive_enums(&exhaustive);
+
     Ok(outputs)
 }
+
+// Convenience function: removes the objects and members excluded by the config
+// from the manifest, and returns them as skipped. The members of an excluded
+// object are excluded as well.
+fn apply_exclusions(
+    info: &mut FileInfo,
+    naming: &NamingConfig,
+    exclusions: &ExclusionConfig,
+) -> Vec<SkippedItem> {
+    let objects: Vec<String> = info
+        .structs
+        .iter()
+        .map(|s| s.name.clone())
+        .chain(info.enums.iter().map(|e| e.name.clone()))
+        .collect();
+    let excluded_objects: Vec<&String> = objects
+        .iter()
+        .filter(|object| exclusions.excludes_object(object))
+        .collect();
+
+    // The member belongs to the object with the longest matching name prefix.
+    let is_excluded = |name: &str| {
+        let object = objects
+            .iter()
+            .filter(|object| name.starts_with(object.as_str()))
+            .max_by_key(|object| object.len());
+        match object {
+            Some(object) if excluded_objects.contains(&object) => true,
+            Some(object) => {
+                let member = naming.member_name(object, &name[object.len()..]);
+                exclusions.excludes_member(name, Some((object.as_str(), member.as_str())))
+            }
+            None => exclusions.excludes_member(name, None),
+        }
+    };
+
+    let mut excluded: Vec<String> = excluded_objects.iter().map(|o| o.to_string()).collect();
+    info.structs
+        .retain(|s| !excluded_objects.contains(&&s.name));
+    info.enums.retain(|e| !excluded_objects.contains(&&e.name));
+
+    let mut take_excluded = |name: &str| {
+        let is_excluded = is_excluded(name);
+        if is_excluded {
+            excluded.push(name.to_string());
+        }
+        !is_excluded
+    };
+    info.inits.retain(|init| take_excluded(&init.name));
+    info.functions.retain(|func| take_excluded(&func.name));
+    info.properties.retain(|prop| take_excluded(&prop.name));
+    // The deinits are not a part of the Swift interface.
+    info.deinits.retain(|deinit| !is_excluded(&deinit.name));
+
+    let mut skipped: Vec<SkippedItem> = excluded
+        .into_iter()
+        .map(|name| SkippedItem {
+            name,
+            reason: EXCLUDED_BY_CONFIG.to_string(),
+        })
+        .collect();
+
+    // Otherwise, the non-public members are rendered as `internal`.
+    if exclusions.skip_non_public {
+        let mut take_public = |name: &str, is_public: bool| {
+            if !is_public {
+                skipped.push(SkippedItem {
+                    name: name.to_string(),
+                    reason: NOT_PUBLIC.to_string(),
+                });
+            }
+            is_public
+        };
+        info.inits
+            .retain(|init| take_public(&init.name, init.is_public));
+        info.functions
+            .retain(|func| take_public(&func.name, func.is_public));
+        info.properties
+            .retain(|prop| take_public(&prop.name, prop.is_public));
+    }
+
+    skipped
+}
+
+// Convenience function: converts the getter-like functions of the object into
+// properties, see [`take_getter_functions`], and reports the conversions.
+fn process_getter_functions(
+    object: &ObjectVariant,
+    mut properties: Vec<PropertyInfo>,
+    functions: Vec<FunctionInfo>,
+    naming: &NamingConfig,
+    outputs: &mut GeneratedSwiftTypes,
+) -> Result<(Vec<PropertyInfo>, Vec<FunctionInfo>)> {
+    let (getters, functions) = take_getter_functions(object, functions)?;
+
+    for getter in &getters {
+        let method_name = naming.member_name(
+            object.name(),
+            getter
+                .name
+                .strip_prefix(object.name())
+                // Panicking implies bug, checked by `take_getter_functions`.
+                .unwrap(),
+        );
+
+        outputs.changes.push(format!(
+            "{}: '{}' is rendered as the '{method_name}' property instead of the '{method_name}()' method",
+            pretty_name(object.name().to_string()),
+            getter.name,
+        ));
+    }
+
+    properties.extend(getters);
+    Ok((properties, functions))
+}
+
+// Convenience function: takes the `equal` method of the `object` out of the
+// `methods`, to be rendered as the `==` operator instead, e.g. of
+// `TWFooEqual(TWFoo* lhs, TWFoo* rhs)`. The enums are passed on as the C enums.
+fn take_eq_operator(
+    object: &ObjectVariant,
+    methods: &mut Vec<SwiftFunction>,
+) -> Option<SwiftOperatorEquality> {
+    let idx = methods.iter().position(|func| func.name == "equal")?;
+    methods.remove(idx);
+
+    let (lhs, rhs) = match object {
+        ObjectVariant::Struct(_) => ("lhs.rawValue".to_string(), "rhs.rawValue".to_string()),
+        ObjectVariant::Enum(name) => (
+            format!("{name}(rawValue: lhs.rawValue)"),
+            format!("{name}(rawValue: rhs.rawValue)"),
+        ),
+    };
+    Some(SwiftOperatorEquality {
+        c_ffi_name: format!("{}Equal", object.name()),
+        lhs,
+        rhs,
+    })
+}
+
+/// The manifest items that are not processed yet. The items taken by the
+/// `process_*` functions of an object are the ones rendered for that object.
+struct Unprocessed {
+    inits: Vec<InitInfo>,
+    functions: Vec<FunctionInfo>,
+    properties: Vec<PropertyInfo>,
+}
+
+impl Unprocessed {
+    fn snapshot(info: &FileInfo) -> Self {
+        Unprocessed {
+            inits: info.inits.clone(),
+            functions: info.functions.clone(),
+            properties: info.properties.clone(),
+        }
+    }
+
+    // Adds the items processed since the snapshot to the API surface. Getter
+    // functions rendered as Swift properties remain methods of the surface.
+    fn add_processed_to_surface<'a>(
+        &self,
+        object: &str,
+        kind: ObjectKind,
+        remaining: &FileInfo,
+        surface: &'a mut ApiSurface,
+    ) -> &'a mut ObjectSurface {
+        let object_surface = surface.add_object(object, kind);
+
+        for init in &self.inits {
+            if !remaining.inits.iter().any(|i| i.name == init.name) {
+                object_surface.add_init(object, init);
+            }
+        }
+        for func in &self.functions {
+            if !remaining.functions.iter().any(|f| f.name == func.name) {
+                object_surface.add_method(func);
+            }
+        }
+        for prop in &self.properties {
+            if !remaining.properties.iter().any(|p| p.name == prop.name) {
+                object_surface.add_property(prop);
+            }
+        }
+
+        object_surface
+    }
+}
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Writes the rendered bindings to disk, so the consumers don't have to
+//! reimplement the file layout.
+
+use super::diff::collect_swift_files;
+use super::render::GeneratedSwiftTypesStrings;
+use crate::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The start of the line the written files begin with, right after the SPDX
+/// license header if any. Only the files with it are considered orphaned, see
+/// [`find_orphans`].
+pub const GENERATED_HEADER_PREFIX: &str = "// Generated by codegen-v2";
+
+/// The first line of the SPDX license header of the rendered files.
+const LICENSE_HEADER_PREFIX: &str = "// SPDX-License-Identifier:";
+/// The last line of the SPDX license header of the rendered files.
+const COPYRIGHT_PREFIX: &str = "// Copyright";
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct WriteOptions {
+    /// Whether the orphaned files in the directory are removed, see
+    /// [`find_orphans`]. The hand-written files are kept.
+    pub remove_stale: bool,
+}
+
+/// Returns the header inserted into the written files, e.g.
+/// `// Generated by codegen-v2 0.1.0, do not edit.`
+pub fn generated_header() -> String {
+    format!(
+        "{GENERATED_HEADER_PREFIX} {}, do not edit.\n",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Whether the file content has the [`generated_header`] of any version right
+/// after its license header, i.e. the file was written by [`write_files`].
+pub fn is_generated(content: &str) -> bool {
+    let (_license, rest) = split_license_header(content);
+    rest.starts_with(GENERATED_HEADER_PREFIX)
+}
+
+/// Splits the content into the SPDX license header, up to and including the
+/// copyright line, and the rest. The license header is empty if the content
+/// doesn't start with one.
+pub(super) fn split_license_header(content: &str) -> (&str, &str) {
+    if !content.starts_with(LICENSE_HEADER_PREFIX) {
+        return ("", content);
+    }
+    let license_end = content.find(COPYRIGHT_PREFIX).and_then(|copyright| {
+        content[copyright..]
+            .find('\n')
+            .map(|line_end| copyright + line_end + 1)
+    });
+    match license_end {
+        Some(license_end) => content.split_at(license_end),
+        None => ("", content),
+    }
+}
+
+/// Returns the generated files in `dir` that are not among the rendered
+/// `files` (relative path and content) anymore, e.g. the bindings of a removed
+/// or renamed C header, sorted by path. The hand-written files, i.e. not
+/// starting with the [`generated_header`], are never orphaned. A missing `dir`
+/// is treated as empty.
+pub fn find_orphans(dir: &Path, files: &[(String, String)]) -> Result<Vec<String>> {
+    let mut on_disk = BTreeMap::new();
+    if dir.exists() {
+        collect_swift_files(dir, dir, &mut on_disk)?;
+    }
+
+    let rendered: BTreeSet<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+    let orphans = on_disk
+        .into_iter()
+        .filter(|(path, content)| !rendered.contains(path.as_str()) && is_generated(content))
+        .map(|(path, _)| path)
+        .collect();
+    Ok(orphans)
+}
+
+/// Inserts the [`generated_header`] below the license header of the rendered
+/// files, i.e. returns the files as they are written by [`write_files`].
+pub fn with_generated_header(files: Vec<(String, String)>) -> Vec<(String, String)> {
+    let header = generated_header();
+    files
+        .into_iter()
+        .map(|(path, content)| {
+            let (license, rest) = split_license_header(&content);
+            let content = format!("{license}{header}{rest}");
+            (path, content)
+        })
+        .collect()
+}
+
+/// Writes the rendered files of a single manifest into `dir`, see
+/// [`write_files`].
+///
+/// The stale files are never removed, as the files of the other manifests
+/// would be removed as well. Pass the files of all the manifests to
+/// [`write_files`] instead.
+pub fn write_output(out: GeneratedSwiftTypesStrings, dir: &Path) -> Result<Vec<PathBuf>> {
+    write_files(out.into_files(), dir, WriteOptions::default())
+}
+
+/// Writes the rendered files (relative path and content), see
+/// [`GeneratedSwiftTypesStrings::into_files`], into `dir` with the
+/// [`generated_header`]. The directories are created as needed.
+///
+/// The files must be the full set of the rendered bindings if
+/// [`WriteOptions::remove_stale`] is set, otherwise the bindings of the
+/// missing manifests are removed as orphaned.
+///
+/// Returns the paths of the written files, e.g. to update the Xcode project.
+pub fn write_files(
+    files: Vec<(String, String)>,
+    dir: &Path,
+    opts: WriteOptions,
+) -> Result<Vec<PathBuf>> {
+    let orphans = if opts.remove_stale {
+        find_orphans(dir, &files)?
+    } else {
+        vec![]
+    };
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, content) in with_generated_header(files) {
+        let file_path = dir.join(&path);
+        // Every kind of declarations goes into its own subfolder.
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, content.as_bytes())?;
+
+        written.push(file_path);
+    }
+
+    for path in orphans {
+        fs::remove_file(dir.join(path))?;
+    }
+
+    Ok(written)
+}
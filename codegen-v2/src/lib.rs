@@ -25,12 +25,41 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     IoError(IoError),
     YamlError(YamlError),
-    RenderError(RenderError),
-    TemplateError(TemplateError),
+    RenderError(Box<RenderError>),
+    /// Rendering the `template` of the `object`, e.g. a struct name, failed.
+    /// The error of a partial template, e.g. a strict mode error, is named
+    /// after the partial, see [`RenderError::template_name`].
+    TemplateRender {
+        template: &'static str,
+        object: String,
+        /// The data the template is rendered with.
+        payload: Box<serde_json::Value>,
+        source: Box<RenderError>,
+    },
+    TemplateError(Box<TemplateError>),
+    /// The struct or enum `name` of the manifest `file` lacks the `TW` prefix,
+    /// or is not a valid Swift type name once it's stripped, e.g. `Foo`, `TW`
+    /// or `TWFoo-Bar`.
+    BadObjectName {
+        file: String,
+        name: String,
+    },
+    /// The `function` of the manifest `file` takes a parameter of the type
+    /// `ty` that has no Swift counterpart, e.g. `void`.
+    UnsupportedType {
+        file: String,
+        function: String,
+        ty: String,
+    },
     BadFormat(String),
     RegistryError(String),
     TomlFormat(String),
     InvalidCommand,
+    /// The errors of the manifest files processed together, by the file name.
+    FileErrors(Vec<(String, Error)>),
+    /// The command ran, but failed its check, e.g. the bindings are not up to
+    /// date. The message is printed as is.
+    CommandFailed(String),
 }
 
 impl Error {
@@ -53,13 +82,13 @@ impl From<YamlError> for Error {
 
 impl From<RenderError> for Error {
     fn from(err: RenderError) -> Self {
-        Error::RenderError(err)
+        Error::RenderError(Box::new(err))
     }
 }
 
 impl From<TemplateError> for Error {
     fn from(err: TemplateError) -> Self {
-        Error::TemplateError(err)
+        Error::TemplateError(Box::new(err))
     }
 }
 
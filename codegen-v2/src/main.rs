@@ -2,27 +2,52 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use libparser::codegen::swift::RenderIntput;
+use libparser::codegen::compat::{check_compatibility, ApiChange};
+use libparser::codegen::stats::RenderStats;
+use libparser::codegen::surface::ApiSurface;
+use libparser::codegen::swift::{
+    find_orphans, render_all, write_files, ConformanceConfig, DevSession, ExclusionConfig,
+    FileStatus, NamingConfig, ProtoNamingConvention, RenderConfig, SwiftTemplates, WriteOptions,
+};
 use libparser::codegen::{cpp, proto, rust};
 use libparser::coin_id::CoinId;
-use libparser::manifest::parse_dir;
+use libparser::manifest::{parse_dir, parse_str};
 use libparser::registry::read_coin_from_registry;
 use libparser::{Error, Result};
+use std::collections::BTreeSet;
 use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 2 {
         panic!("Invalid command");
     }
 
+    match run_command(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::CommandFailed(message)) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_command(args: &[String]) -> Result<()> {
     match args[1].as_str() {
         "new-blockchain-rust" => new_blockchain_rust(&args[2..]),
         "new-blockchain" => new_blockchain(&args[2..]),
         "new-evmchain" => new_evmchain(&args[2..]),
         "new-cosmos-chain" => new_cosmos_chain(&args[2..]),
-        "swift" => generate_swift_bindings(),
+        "swift" => generate_swift_bindings(&args[2..]),
+        "swift-dev" => watch_swift_templates(&args[2..]),
         _ => Err(Error::InvalidCommand),
     }
 }
@@ -78,71 +103,399 @@ fn new_cosmos_chain(args: &[String]) -> Result<()> {
     Ok(())
 }
 
-fn generate_swift_bindings() -> Result<()> {
+fn generate_swift_bindings(args: &[String]) -> Result<()> {
     // NOTE: The paths will be configurable, eventually.
     const OUT_DIR: &str = "bindings/";
     const IN_DIR: &str = "src/codegen/swift/templates";
+    const SURFACE_FILE: &str = "api_surface.json";
+
+    // In dry-run mode nothing is written, the differences to the bindings
+    // on disk are reported as JSON instead.
+    let mut dry_run = false;
+    // In check mode nothing is written, the unified diffs of the drifted
+    // bindings are printed instead.
+    let mut check = false;
+    // The API surface of the previous bindings to check the compatibility against.
+    let mut baseline = None;
+    let mut deny_breaking = false;
+    let mut naming = NamingConfig::builtin();
+    let mut exclusions = ExclusionConfig::builtin();
+    // Independent of the order of `--exclude`, which replaces the config.
+    let mut skip_non_public = false;
+    let mut conformances = ConformanceConfig::builtin();
+    let mut stable_sort = true;
+    // The modules imported by every rendered file, e.g. for platform quirks.
+    let mut extra_imports = vec![];
+    // Whether the bindings of the removed manifest items are deleted.
+    let mut remove_stale = false;
+    // Whether the data of a failed template is printed.
+    let mut debug_payload = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--check" => check = true,
+            "--debug-payload" => debug_payload = true,
+            "--no-stable-sort" => stable_sort = false,
+            "--import" => extra_imports.push(args.next().ok_or(Error::InvalidCommand)?.clone()),
+            "--prune" | "--remove-stale" => remove_stale = true,
+            "--skip-non-public" => skip_non_public = true,
+            "--naming" => naming = read_naming(args.next().ok_or(Error::InvalidCommand)?)?,
+            "--exclude" => exclusions = read_exclusions(args.next().ok_or(Error::InvalidCommand)?)?,
+            "--conformances" => {
+                conformances = read_conformances(args.next().ok_or(Error::InvalidCommand)?)?
+            }
+            "--baseline" => baseline = Some(args.next().ok_or(Error::InvalidCommand)?),
+            "--deny-breaking" => deny_breaking = true,
+            _ => return Err(Error::InvalidCommand),
+        }
+    }
+    exclusions.skip_non_public |= skip_non_public;
+    if deny_breaking && baseline.is_none() {
+        return Err(Error::InvalidCommand);
+    }
 
-    std::fs::create_dir_all(OUT_DIR)?;
+    // Read the manifest dir, generate bindings for all the entries.
+    let file_infos = parse_dir("manifest/")?;
+    let config = RenderConfig {
+        naming,
+        exclusions,
+        conformances,
+        proto_naming: ProtoNamingConvention::default(),
+        stable_sort,
+        extra_imports,
+    };
+    let rendered = match render_all(file_infos, SwiftTemplates::read_dir(IN_DIR)?, config) {
+        Ok(rendered) => rendered,
+        // Report the errors of all the manifests at once.
+        Err(Error::FileErrors(errors)) => {
+            for (file_name, err) in &errors {
+                report_file_error(file_name, err, debug_payload);
+            }
+            return Err(Error::CommandFailed(format!(
+                "Failed to render {} manifest(s)",
+                errors.len()
+            )));
+        }
+        Err(err) => return Err(err),
+    };
+
+    // Make the API shape changes visible in the codegen output. Keep stdout
+    // clean for the JSON report in dry-run mode.
+    for change in &rendered.changes {
+        if dry_run {
+            eprintln!("Changed: {change}");
+        } else {
+            println!("Changed: {change}");
+        }
+    }
+    for skipped in &rendered.skipped {
+        if dry_run {
+            eprintln!("Skipped: {} ({})", skipped.name, skipped.reason);
+        } else {
+            println!("Skipped: {} ({})", skipped.name, skipped.reason);
+        }
+    }
+    for warning in &rendered.warnings {
+        eprintln!("Warning: {warning}");
+    }
+    // The C functions that are silently dropped otherwise.
+    for unclaimed in &rendered.unclaimed {
+        eprintln!("Warning: {unclaimed} is not claimed by any object, it is not rendered");
+    }
 
-    let struct_t = read_to_string(&format!("{IN_DIR}/struct.hbs"))?;
-    let enum_t = read_to_string(&format!("{IN_DIR}/enum.hbs"))?;
-    let ext_t = read_to_string(&format!("{IN_DIR}/extension.hbs"))?;
-    let proto_t = read_to_string(&format!("{IN_DIR}/proto.hbs"))?;
-    let part_init_t = read_to_string(&format!("{IN_DIR}/partial_init.hbs"))?;
-    let part_func_t = read_to_string(&format!("{IN_DIR}/partial_func.hbs"))?;
-    let part_prop_t = read_to_string(&format!("{IN_DIR}/partial_prop.hbs"))?;
+    let stats = &rendered.stats;
+    // The stats of the previous run, from the baseline or the surface on disk.
+    let previous_surface_path = match baseline {
+        Some(baseline_path) => Path::new(baseline_path).to_path_buf(),
+        None => Path::new(OUT_DIR).join(SURFACE_FILE),
+    };
+    let previous_stats = if previous_surface_path.exists() {
+        Some(RenderStats::from_surface(&read_surface(
+            &previous_surface_path,
+        )?))
+    } else {
+        None
+    };
+    // Keep stdout clean for the JSON report in dry-run mode.
+    let table = stats.table(previous_stats.as_ref());
+    if dry_run {
+        eprint!("{table}");
+    } else {
+        print!("{table}");
+    }
 
-    // Read the manifest dir, generate bindings for each entry.
-    let file_infos = parse_dir("manifest/")?;
+    if let Some(baseline_path) = baseline {
+        let baseline = read_surface(Path::new(baseline_path))?;
+
+        let changes = check_compatibility(&baseline, &rendered.surface);
+        for change in &changes {
+            let class = if change.is_breaking() {
+                "Breaking"
+            } else {
+                "Additive"
+            };
+            // Keep stdout clean for the JSON report in dry-run mode.
+            if dry_run {
+                eprintln!("{class}: {change}");
+            } else {
+                println!("{class}: {change}");
+            }
+        }
 
-    for file_info in file_infos {
-        let input = RenderIntput {
-            file_info,
-            struct_template: &struct_t,
-            enum_template: &enum_t,
-            extension_template: &ext_t,
-            proto_template: &proto_t,
-            partial_init_template: &part_init_t,
-            partial_func_tempalte: &part_func_t,
-            partial_prop_tempalte: &part_prop_t,
-        };
+        // Fail before writing anything.
+        if deny_breaking && changes.iter().any(ApiChange::is_breaking) {
+            return Err(Error::CommandFailed(format!(
+                "The generated API breaks the baseline '{baseline_path}'"
+            )));
+        }
+    }
 
-        let rendered = libparser::codegen::swift::render_to_strings(input)?;
+    if dry_run {
+        let report = rendered.diff_with_dir(Path::new(OUT_DIR))?;
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|err| Error::io_error_other(err.to_string()))?;
+        println!("{json}");
+
+        // Fail the CI job if the committed bindings are not up to date.
+        if report.has_changes {
+            return Err(Error::CommandFailed(format!(
+                "The bindings in '{OUT_DIR}' are not up to date"
+            )));
+        }
+        return Ok(());
+    }
 
-        // Enum declarations go into their own subfolder.
-        if !rendered.enums.is_empty() {
-            std::fs::create_dir_all(format!("{OUT_DIR}/Enums"))?;
+    if check {
+        let report = rendered.diff_with_dir(Path::new(OUT_DIR))?;
+        for file in report
+            .files
+            .iter()
+            .filter(|file| file.status != FileStatus::Unchanged)
+        {
+            println!("{:?}: {}", file.status, file.path);
+            if let Some(diff) = &file.diff {
+                print!("{diff}");
+            }
         }
 
-        // Protobuf declarations go into their own subfolder.
-        if !rendered.protos.is_empty() {
-            std::fs::create_dir_all(format!("{OUT_DIR}/Protobuf"))?;
+        // Fail the CI job if the committed bindings are not up to date.
+        if report.has_changes {
+            return Err(Error::CommandFailed(format!(
+                "The bindings in '{OUT_DIR}' are not up to date, run 'codegen-v2 swift'"
+            )));
         }
+        println!("The bindings in '{OUT_DIR}' are up to date");
+        return Ok(());
+    }
 
-        for (name, rendered) in rendered.structs {
-            let file_path = format!("{OUT_DIR}/{name}.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
+    // The generated files of the removed or renamed manifest items.
+    let orphans = find_orphans(Path::new(OUT_DIR), &rendered.files)?;
+    write_files(
+        rendered.files,
+        Path::new(OUT_DIR),
+        WriteOptions { remove_stale },
+    )?;
+    for orphan in &orphans {
+        if remove_stale {
+            println!("Removed: {orphan}");
+        } else {
+            println!("Orphaned: {orphan}");
         }
+    }
+    if !remove_stale && !orphans.is_empty() {
+        println!(
+            "Found {} orphaned file(s), pass '--prune' to remove them",
+            orphans.len()
+        );
+    }
 
-        for (name, rendered) in rendered.enums {
-            let file_path = format!("{OUT_DIR}/Enums/{name}.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
+    // The normalized API surface, to be compared with other backends.
+    let surface_json = serde_json::to_string_pretty(&rendered.surface)
+        .map_err(|err| Error::io_error_other(err.to_string()))?;
+    std::fs::write(Path::new(OUT_DIR).join(SURFACE_FILE), surface_json)?;
+
+    println!("Created bindings in directory 'bindings/'!");
+    Ok(())
+}
+
+// Convenience function: prints the error of the manifest file. The data of a
+// failed template is printed too if `debug_payload` is set.
+fn report_file_error(file_name: &str, err: &Error, debug_payload: bool) {
+    let Error::TemplateRender {
+        template,
+        object,
+        payload,
+        source,
+    } = err
+    else {
+        eprintln!("Error in '{file_name}': {err:?}");
+        return;
+    };
+
+    eprintln!("Error in '{file_name}': failed to render the '{template}' template of '{object}': {source}");
+    if debug_payload {
+        let payload = serde_json::to_string_pretty(payload).unwrap_or_default();
+        eprintln!("Payload of '{object}':\n{payload}");
+    }
+}
+
+/// Watches the Swift templates and the given manifest file, and re-renders
+/// the affected bindings into a scratch directory on every change.
+fn watch_swift_templates(args: &[String]) -> Result<()> {
+    const IN_DIR: &str = "src/codegen/swift/templates";
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    let mut args = args.iter();
+    let manifest_path = PathBuf::from(args.next().ok_or(Error::InvalidCommand)?);
+    let mut out_dir = PathBuf::from("bindings-dev/");
+    let mut naming = NamingConfig::builtin();
+    let mut exclusions = ExclusionConfig::builtin();
+    let mut skip_non_public = false;
+    let mut conformances = ConformanceConfig::builtin();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_dir = PathBuf::from(args.next().ok_or(Error::InvalidCommand)?),
+            "--skip-non-public" => skip_non_public = true,
+            "--naming" => naming = read_naming(args.next().ok_or(Error::InvalidCommand)?)?,
+            "--exclude" => exclusions = read_exclusions(args.next().ok_or(Error::InvalidCommand)?)?,
+            "--conformances" => {
+                conformances = read_conformances(args.next().ok_or(Error::InvalidCommand)?)?
+            }
+            _ => return Err(Error::InvalidCommand),
+        }
+    }
+    exclusions.skip_non_public |= skip_non_public;
+
+    let file_info = parse_str(&read_to_string(&manifest_path)?)?;
+    let mut session = DevSession::new(
+        SwiftTemplates::read_dir(IN_DIR)?,
+        naming,
+        exclusions,
+        conformances,
+        ProtoNamingConvention::default(),
+        vec![file_info],
+    )?;
+    let paths: Vec<String> = session
+        .files()
+        .keys()
+        .map(|path| path.to_string())
+        .collect();
+    write_dev_files(&session, &out_dir, &paths)?;
+    println!(
+        "Rendered {} file(s) into '{}', watching '{IN_DIR}' and '{}'",
+        paths.len(),
+        out_dir.display(),
+        manifest_path.display()
+    );
+
+    let mut templates_modified = last_modified(Path::new(IN_DIR))?;
+    let mut manifest_modified = last_modified(&manifest_path)?;
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let templates_now = last_modified(Path::new(IN_DIR))?;
+        let manifest_now = last_modified(&manifest_path)?;
+        if templates_now == templates_modified && manifest_now == manifest_modified {
+            continue;
         }
 
-        // Enum extensions.
-        for (name, rendered) in rendered.extensions {
-            let file_path = format!("{OUT_DIR}/{name}+Extension.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
+        let started = Instant::now();
+        // Keep watching if a template or the manifest is invalid while being edited.
+        // The templates are reloaded first, so that the manifest changed within
+        // the same poll is rendered with the new templates.
+        let mut reloaded = vec![];
+        if templates_now != templates_modified {
+            reloaded.push(
+                SwiftTemplates::read_dir(IN_DIR)
+                    .and_then(|templates| session.reload_templates(templates)),
+            );
+        }
+        if manifest_now != manifest_modified {
+            reloaded.push(
+                read_to_string(&manifest_path)
+                    .map_err(Error::from)
+                    .and_then(|manifest| parse_str(&manifest))
+                    .and_then(|file_info| session.reload_manifest(file_info)),
+            );
+        }
+        templates_modified = templates_now;
+        manifest_modified = manifest_now;
+
+        let mut changed = BTreeSet::new();
+        let mut any_reloaded = false;
+        for result in reloaded {
+            match result {
+                Ok(paths) => {
+                    changed.extend(paths);
+                    any_reloaded = true;
+                }
+                Err(err) => eprintln!("Error: {err:?}"),
+            }
+        }
+        if !any_reloaded {
+            continue;
         }
 
-        // Protobuf messages.
-        for (name, rendered) in rendered.protos {
-            let file_path = format!("{OUT_DIR}/Protobuf/{name}+Proto.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
+        let changed: Vec<String> = changed.into_iter().collect();
+        write_dev_files(&session, &out_dir, &changed)?;
+        println!(
+            "Re-rendered {} file(s) in {} ms",
+            changed.len(),
+            started.elapsed().as_millis()
+        );
+        for path in changed {
+            println!("  {path}");
         }
     }
+}
 
-    println!("Created bindings in directory 'bindings/'!");
+// Convenience function: writes the given files of the dev session, and
+// removes the ones that are not rendered anymore.
+fn write_dev_files(session: &DevSession, out_dir: &Path, paths: &[String]) -> Result<()> {
+    for path in paths {
+        let file_path = out_dir.join(path);
+        match session.file(path) {
+            Some(content) => {
+                if let Some(parent) = file_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&file_path, content.as_bytes())?;
+            }
+            None if file_path.exists() => std::fs::remove_file(&file_path)?,
+            None => (),
+        }
+    }
     Ok(())
 }
+
+// Convenience function: returns the latest modification time of the file, or
+// of the files in the directory.
+fn last_modified(path: &Path) -> Result<SystemTime> {
+    if !path.is_dir() {
+        return Ok(std::fs::metadata(path)?.modified()?);
+    }
+
+    let mut latest = SystemTime::UNIX_EPOCH;
+    for entry in std::fs::read_dir(path)? {
+        latest = latest.max(entry?.metadata()?.modified()?);
+    }
+    Ok(latest)
+}
+
+fn read_naming(path: &str) -> Result<NamingConfig> {
+    NamingConfig::from_yaml(&read_to_string(path)?)
+}
+
+fn read_exclusions(path: &str) -> Result<ExclusionConfig> {
+    ExclusionConfig::from_yaml(&read_to_string(path)?)
+}
+
+fn read_conformances(path: &str) -> Result<ConformanceConfig> {
+    ConformanceConfig::from_yaml(&read_to_string(path)?)
+}
+
+fn read_surface(path: &Path) -> Result<ApiSurface> {
+    serde_json::from_str(&read_to_string(path)?).map_err(|err| Error::BadFormat(err.to_string()))
+}
@@ -70,6 +70,12 @@ pub enum TypeVariant {
     Enum(String),
     Data,
     String,
+    /// A pointer to the elements, e.g. `const uint8_t *_Nonnull bytes`. A
+    /// pointer to bytes is followed by the `size_t` number of bytes parameter.
+    Pointer(Box<TypeVariant>),
+    /// An array of the elements, either a `TWDataVector` of data, or a pointer
+    /// to structs followed by the `size_t` number of structs parameter.
+    Array(Box<TypeVariant>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,8 +104,51 @@ pub struct ImportInfo {
     pub path: Vec<String>,
 }
 
+/// A protobuf message, either specified by name only (e.g.
+/// `- TW_Bitcoin_Proto_SigningInput`) or with additional helper flags.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProtoInfo(pub String);
+#[serde(from = "ProtoInfoRepr")]
+pub struct ProtoInfo {
+    pub name: String,
+    /// Whether to render a `static func with(_ builder:)` convenience.
+    pub with_builder: bool,
+    /// Whether to render a `serializedDataOrThrow()` wrapper.
+    pub serialized_data_or_throw: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProtoInfoRepr {
+    Name(String),
+    WithFlags {
+        name: String,
+        #[serde(default)]
+        with_builder: bool,
+        #[serde(default)]
+        serialized_data_or_throw: bool,
+    },
+}
+
+impl From<ProtoInfoRepr> for ProtoInfo {
+    fn from(repr: ProtoInfoRepr) -> Self {
+        match repr {
+            ProtoInfoRepr::Name(name) => ProtoInfo {
+                name,
+                with_builder: false,
+                serialized_data_or_throw: false,
+            },
+            ProtoInfoRepr::WithFlags {
+                name,
+                with_builder,
+                serialized_data_or_throw,
+            } => ProtoInfo {
+                name,
+                with_builder,
+                serialized_data_or_throw,
+            },
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumInfo {
@@ -108,6 +157,11 @@ pub struct EnumInfo {
     pub value_type: TypeVariant,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub variants: Vec<EnumVariantInfo>,
+    /// Whether the C FFI functions only ever return the declared variants,
+    /// so the returned raw values can be force-unwrapped into the Swift enum.
+    /// Otherwise an unexpected raw value traps with a clear message.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enum_exhaustive: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,8 +186,16 @@ pub struct InitInfo {
     pub name: String,
     pub is_public: bool,
     pub is_nullable: bool,
+    /// Whether the nullable init is tagged with `TW_THROWS`, i.e. it should be
+    /// exposed as a throwing initializer rather than a failable one.
+    #[serde(default)]
+    pub is_throwing: bool,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub params: Vec<ParamInfo>,
+    /// Set if the init is tagged with `TW_DEPRECATED` or `TW_DEPRECATED_FOR`.
+    /// Rendered as a deprecation annotation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<DeprecationInfo>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub comments: Vec<String>,
 }
@@ -151,15 +213,83 @@ pub struct FunctionInfo {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub params: Vec<ParamInfo>,
     pub return_type: TypeInfo,
+    /// Whether the function should be exposed as a Swift computed property.
+    /// Overrides the `Is`/`Has` naming heuristic when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_property: Option<bool>,
+    /// Ownership of the pointer returned by a non-static function returning
+    /// the object's own type, e.g. `TWFooAddingBar(foo, bar) -> TWFoo`.
+    /// Required for such functions and not allowed for the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns_self: Option<ReturnOwnership>,
+    /// Ownership of the returned `TWString`/`TWData`. If not set, the result
+    /// is consumed by `TWStringNSString`/`TWDataNSData` in the bindings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_ownership: Option<ReturnOwnership>,
+    /// Whether the function is tagged with `TW_EXPORT_ASYNC`, i.e. an `async`
+    /// variant should be exposed next to the blocking one.
+    #[serde(default)]
+    pub is_async: bool,
+    /// Set if the function is tagged with `TW_DEPRECATED` or `TW_DEPRECATED_FOR`.
+    /// Rendered as a deprecation annotation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<DeprecationInfo>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub comments: Vec<String>,
 }
 
+/// A `TW_DEPRECATED(since)` or `TW_DEPRECATED_FOR(since, replacement)` marker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeprecationInfo {
+    /// The version the symbol is deprecated since, e.g. `3.1.1`.
+    pub since: String,
+    /// The declaration to use instead, set by `TW_DEPRECATED_FOR` only, e.g.
+    /// `init(name:)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+impl DeprecationInfo {
+    /// The message of the deprecation annotation.
+    pub fn message(&self) -> String {
+        match self.replacement {
+            Some(ref replacement) => format!("Use {replacement} instead"),
+            None => format!("Deprecated since {}", self.since),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnOwnership {
+    /// The function returns a new instance owned by the caller.
+    Owned,
+    /// The returned pointer remains owned by the callee, e.g. the function
+    /// returns the passed on object itself.
+    Borrowed,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PropertyInfo {
     pub name: String,
     pub is_public: bool,
+    /// Whether the property is tagged with `TW_EXPORT_STATIC_PROPERTY`, i.e.
+    /// the C FFI function takes no object parameter, e.g. `TWFooDefault()`.
+    #[serde(default)]
+    pub is_static: bool,
     pub return_type: TypeInfo,
+    /// Name of the C FFI setter function, if it does not follow the
+    /// `<Object>Set<Property>` naming convention.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setter: Option<String>,
+    /// Ownership of the returned `TWString`/`TWData`. If not set, the result
+    /// is consumed by `TWStringNSString`/`TWDataNSData` in the bindings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_ownership: Option<ReturnOwnership>,
+    /// Set if the property is tagged with `TW_DEPRECATED` or `TW_DEPRECATED_FOR`.
+    /// Rendered as a deprecation annotation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<DeprecationInfo>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub comments: Vec<String>,
 }
@@ -169,4 +299,19 @@ pub struct ParamInfo {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: TypeInfo,
+    /// Default value expression of the parameter in the generated bindings,
+    /// e.g. `.secp256k1` or `nil`. Only trailing parameters may have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    /// Whether the C FFI function writes into the parameter, e.g. the `result`
+    /// of `bool TWFooGet(TWFoo* obj, TWData* result)`. The function must return
+    /// `bool`, and the bindings return the written value instead, or `nil` on
+    /// failure.
+    #[serde(default)]
+    pub is_out: bool,
+    /// Argument label of the parameter in the generated bindings, if it
+    /// differs from the name, e.g. `_` for `derive(_ path: String)` or `from`
+    /// for `derive(from path: String)`. The C FFI call keeps using the name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
@@ -2,23 +2,64 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::codegen::swift::{render_to_strings, RenderIntput};
-use crate::manifest::parse_str;
+use crate::codegen::compat::{check_compatibility, ApiChange, Compatibility, MemberKind};
+use crate::codegen::stats::{RenderStats, StatsRow};
+use crate::codegen::surface::{
+    compare_surfaces, ApiSurface, MemberSurface, ObjectKind, SurfaceMismatch, TypeSurface,
+};
+use crate::codegen::swift::{
+    diff_with_dir, find_orphans, generated_header, glob_match, public_symbols, render_all,
+    render_ffi_stub, render_proto_registry, render_to_strings, write_files, write_output,
+    ConformanceConfig, DevSession, ExclusionConfig, FileStatus, NamingConfig,
+    ProtoNamingConvention, RenderConfig, RenderIntput, RenderOutput, SkippedItem, SwiftTemplates,
+    WriteOptions, EXCLUDED_BY_CONFIG, NOT_PUBLIC,
+};
+use crate::manifest::{parse_str, ParamInfo, TypeInfo, TypeVariant};
+use crate::Error;
+use std::collections::BTreeMap;
+
+/// The fixture manifests.
+const FIXTURES: &[&str] = &[
+    include_str!("samples/arrays.input.yaml"),
+    include_str!("samples/async.input.yaml"),
+    include_str!("samples/class.input.yaml"),
+    include_str!("samples/data_vector.input.yaml"),
+    include_str!("samples/default_params.input.yaml"),
+    include_str!("samples/deprecated.input.yaml"),
+    include_str!("samples/deprecated_bare.input.yaml"),
+    include_str!("samples/description.input.yaml"),
+    include_str!("samples/enum.input.yaml"),
+    include_str!("samples/enum_codable.input.yaml"),
+    include_str!("samples/enum_char.input.yaml"),
+    include_str!("samples/enum_equal.input.yaml"),
+    include_str!("samples/enum_exhaustive.input.yaml"),
+    include_str!("samples/enum_extension.input.yaml"),
+    include_str!("samples/enum_init.input.yaml"),
+    include_str!("samples/enum_private.input.yaml"),
+    include_str!("samples/enum_size_t.input.yaml"),
+    include_str!("samples/function_property.input.yaml"),
+    include_str!("samples/internal_members.input.yaml"),
+    include_str!("samples/hashable.input.yaml"),
+    include_str!("samples/non-associated.input.yaml"),
+    include_str!("samples/optional.input.yaml"),
+    include_str!("samples/out_params.input.yaml"),
+    include_str!("samples/param_labels.input.yaml"),
+    include_str!("samples/private_class.input.yaml"),
+    include_str!("samples/property_setter.input.yaml"),
+    include_str!("samples/proto.input.yaml"),
+    include_str!("samples/return_ownership.input.yaml"),
+    include_str!("samples/returns_self.input.yaml"),
+    include_str!("samples/self_params.input.yaml"),
+    include_str!("samples/static_property.input.yaml"),
+    include_str!("samples/struct.input.yaml"),
+    include_str!("samples/throwing_init.input.yaml"),
+];
 
 /// Convenience function.
-fn create_intput(yaml: &str) -> RenderIntput {
+fn create_intput(yaml: &str) -> RenderIntput<'_> {
     let file_info = parse_str(yaml).unwrap();
 
-    RenderIntput {
-        file_info,
-        struct_template: include_str!("../codegen/swift/templates/struct.hbs"),
-        enum_template: include_str!("../codegen/swift/templates/enum.hbs"),
-        extension_template: include_str!("../codegen/swift/templates/extension.hbs"),
-        proto_template: include_str!("../codegen/swift/templates/proto.hbs"),
-        partial_init_template: include_str!("../codegen/swift/templates/partial_init.hbs"),
-        partial_func_tempalte: include_str!("../codegen/swift/templates/partial_func.hbs"),
-        partial_prop_tempalte: include_str!("../codegen/swift/templates/partial_prop.hbs"),
-    }
+    RenderIntput::builtin(file_info)
 }
 
 // Convenience function: runs the codegen on the given `input` and compares it
@@ -74,12 +115,50 @@ fn private() {
     render_and_compare_struct(INPUT, EXPECTED);
 }
 
+#[test]
+fn internal_members() {
+    const INPUT: &str = include_str!("samples/internal_members.input.yaml");
+    const EXPECTED: &str = include_str!("samples/internal_members.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The non-public members can be skipped instead.
+    let mut input = create_intput(INPUT);
+    input.exclusions.skip_non_public = true;
+    let rendered = render_to_strings(input).unwrap();
+
+    let (_name, output) = &rendered.structs[0];
+    assert!(!output.contains("internal "));
+    assert!(output.contains("public func sign() -> Bool"));
+    let skipped: Vec<(&str, &str)> = rendered
+        .skipped
+        .iter()
+        .map(|s| (s.name.as_str(), s.reason.as_str()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            ("TWMainStructCreateForTesting", NOT_PUBLIC),
+            ("TWMainStructResetCache", NOT_PUBLIC),
+            ("TWMainStructInternalState", NOT_PUBLIC),
+        ]
+    );
+
+    let object = &rendered.surface.objects["TWMainStruct"];
+    assert!(object.inits.contains_key("TWMainStructCreate"));
+    assert!(!object.inits.contains_key("TWMainStructCreateForTesting"));
+    assert!(object.properties.is_empty());
+}
+
 #[test]
 fn optional() {
     const INPUT: &str = include_str!("samples/optional.input.yaml");
     const EXPECTED: &str = include_str!("samples/optional.output.swift");
 
     render_and_compare_struct(INPUT, EXPECTED);
+
+    // Nullable return values are never force-unwrapped.
+    assert!(!EXPECTED.contains(")!"));
 }
 
 #[test]
@@ -90,6 +169,34 @@ fn enum_with_description() {
     render_and_compare_enum(INPUT, EXPECTED);
 }
 
+#[test]
+fn enum_codable_strings() {
+    const INPUT: &str = include_str!("samples/enum_codable.input.yaml");
+    const EXPECTED_CODABLE: &str = include_str!("samples/enum_codable.output.swift");
+    const EXPECTED_RAW: &str = include_str!("samples/enum_codable_raw.output.swift");
+
+    // Encoded by the raw values unless enabled.
+    render_and_compare_enum(INPUT, EXPECTED_RAW);
+
+    let mut input = create_intput(INPUT);
+    input.conformances.codable_strings = vec!["MainEnum".to_string()];
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.enums[0];
+    assert_eq!(output, EXPECTED_CODABLE);
+    assert!(rendered.warnings.is_empty());
+
+    // The variants without a string representation can't be decoded.
+    let mut input = create_intput(include_str!("samples/enum.input.yaml"));
+    input.conformances.codable_strings = vec!["MainEnum".to_string()];
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.enums[0];
+    assert_eq!(output, include_str!("samples/enum.output.swift"));
+    assert_eq!(
+        rendered.warnings,
+        ["MainEnum: not every variant has a unique string representation, the enum is Codable by its raw values"]
+    );
+}
+
 #[test]
 fn privat_enum_with_description() {
     const INPUT: &str = include_str!("samples/enum_private.input.yaml");
@@ -121,6 +228,98 @@ fn enum_with_extension() {
     assert_eq!(output, EXPECTED_EXTENSION);
 }
 
+#[test]
+fn enum_with_equality_operator() {
+    const INPUT: &str = include_str!("samples/enum_equal.input.yaml");
+    const EXPECTED_EXTENSION: &str = include_str!("samples/enum_equal.output.swift");
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+
+    assert!(rendered.structs.is_empty());
+    assert_eq!(rendered.enums.len(), 1);
+    assert_eq!(rendered.extensions.len(), 1);
+    assert!(rendered.protos.is_empty());
+
+    // The `TWSomeEnumEqual` function is rendered as the `==` operator only.
+    let (_name, output) = &rendered.extensions[0];
+    assert!(!output.contains("func equal("));
+    assert_eq!(output, EXPECTED_EXTENSION);
+}
+
+#[test]
+fn enum_with_inits() {
+    const INPUT: &str = include_str!("samples/enum_init.input.yaml");
+    const EXPECTED_EXTENSION: &str = include_str!("samples/enum_init.output.swift");
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+
+    assert!(rendered.structs.is_empty());
+    assert_eq!(rendered.enums.len(), 1);
+    assert_eq!(rendered.extensions.len(), 1);
+    assert!(rendered.protos.is_empty());
+
+    // The inits are rendered in the extension, delegating to `init(rawValue:)`.
+    let (_name, output) = &rendered.extensions[0];
+    assert_eq!(output, EXPECTED_EXTENSION);
+}
+
+/// Renders an enum with a raw value shim, returning the warnings.
+#[track_caller]
+fn render_and_compare_enum_shim(
+    input: &str,
+    expected_enum: &str,
+    expected_extension: &str,
+) -> Vec<String> {
+    let rendered = render_to_strings(create_intput(input)).unwrap();
+
+    assert!(rendered.structs.is_empty());
+    assert_eq!(rendered.enums.len(), 1);
+    assert_eq!(rendered.extensions.len(), 1);
+
+    let (_name, output) = &rendered.enums[0];
+    assert_eq!(output, expected_enum);
+
+    let (_name, output) = &rendered.extensions[0];
+    assert_eq!(output, expected_extension);
+
+    rendered.warnings
+}
+
+#[test]
+fn enum_size_t_value_type() {
+    let warnings = render_and_compare_enum_shim(
+        include_str!("samples/enum_size_t.input.yaml"),
+        include_str!("samples/enum_size_t.output.swift"),
+        include_str!("samples/enum_size_t_extension.output.swift"),
+    );
+
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("SizeTEnum: the size_t-backed enum"));
+}
+
+#[test]
+fn enum_char_value_type() {
+    const INPUT: &str = include_str!("samples/enum_char.input.yaml");
+
+    let warnings = render_and_compare_enum_shim(
+        INPUT,
+        include_str!("samples/enum_char.output.swift"),
+        include_str!("samples/enum_char_extension.output.swift"),
+    );
+    assert!(warnings.is_empty());
+
+    // Only the integer value types are supported.
+    for variant in ["bool", "double", "string"] {
+        let input = INPUT.replacen("variant: char", &format!("variant: {variant}"), 1);
+        match render_to_strings(create_intput(&input)).unwrap_err() {
+            Error::BadFormat(msg) => assert!(msg.contains("'TWCharEnum'"), "{msg}"),
+            err => panic!("Unexpected error: {err:?}"),
+        }
+    }
+}
+
 #[test]
 fn non_associated() {
     const INPUT: &str = include_str!("samples/non-associated.input.yaml");
@@ -128,3 +327,2068 @@ fn non_associated() {
 
     render_and_compare_struct(INPUT, EXPECTED);
 }
+
+#[test]
+fn enum_exhaustive() {
+    const INPUT: &str = include_str!("samples/enum_exhaustive.input.yaml");
+    const EXPECTED: &str = include_str!("samples/enum_exhaustive.output.swift");
+    const OTHER_ENUM: &str = "
+name: OtherEnum
+enums:
+- name: TWOtherEnum
+  is_public: true
+  value_type:
+    variant: u_int32_t
+  enum_exhaustive: true
+";
+
+    // Only the returns of the exhaustive `MainEnum` are force-unwrapped.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    assert_eq!(rendered.structs.len(), 1);
+    assert_eq!(rendered.enums.len(), 1);
+
+    let (_name, output) = &rendered.structs[0];
+    assert_eq!(output, EXPECTED);
+
+    // The enums declared by other manifests are taken into account as well.
+    let file_infos = [INPUT, OTHER_ENUM]
+        .iter()
+        .map(|yaml| parse_str(yaml).unwrap())
+        .collect();
+    let output = render_all(
+        file_infos,
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap();
+    let content = &output
+        .files
+        .iter()
+        .find(|(path, _)| path == "Sources/Generated/MainStruct.swift")
+        .unwrap()
+        .1;
+    assert!(content.contains("return OtherEnum(rawValue: result.rawValue)!"));
+    assert!(!content.contains("fatalError"));
+}
+
+#[test]
+fn property_setter() {
+    const INPUT: &str = include_str!("samples/property_setter.input.yaml");
+    const EXPECTED: &str = include_str!("samples/property_setter.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+}
+
+#[test]
+fn static_property() {
+    const INPUT: &str = include_str!("samples/static_property.input.yaml");
+    const EXPECTED: &str = include_str!("samples/static_property.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // Static properties are static members of the surface.
+    let surface = render_to_strings(create_intput(INPUT)).unwrap().surface;
+    let properties = &surface.objects["TWMainStruct"].properties;
+    assert!(properties["TWMainStructShared"].is_static);
+    assert!(properties["TWMainStructVersion"].is_static);
+    assert!(!properties["TWMainStructName"].is_static);
+}
+
+#[test]
+fn function_as_property() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+    const EXPECTED: &str = include_str!("samples/function_property.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The conversions are reported.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    assert_eq!(
+        rendered.changes,
+        vec![
+            "MainStruct: 'TWMainStructIsValid' is rendered as the 'isValid' property instead of the 'isValid()' method",
+            "MainStruct: 'TWMainStructChildCount' is rendered as the 'childCount' property instead of the 'childCount()' method",
+        ]
+    );
+}
+
+#[test]
+fn function_as_property_invalid() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    // A function with parameters cannot be forced to be a property.
+    let input = INPUT.replace(
+        "- name: TWMainStructIsNamed\n",
+        "- name: TWMainStructIsNamed\n  as_property: true\n",
+    );
+    assert!(render_to_strings(create_intput(&input)).is_err());
+}
+
+#[test]
+fn hashable() {
+    const INPUT: &str = include_str!("samples/hashable.input.yaml");
+    const EXPECTED: &str = include_str!("samples/hashable.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+}
+
+#[test]
+fn hashable_requires_equal() {
+    const INPUT: &str = include_str!("samples/hashable.input.yaml");
+
+    // Without a hash function, only the equality operator is rendered.
+    let input = INPUT.replace("- name: TWMainStructHash\n", "- name: TWMainStructDigest\n");
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("public final class MainStruct {"));
+    assert!(output.contains("public static func == (lhs: MainStruct, rhs: MainStruct) -> Bool {"));
+    assert!(!output.contains("hash(into"));
+    assert!(output.contains("public func digest() -> Data {"));
+
+    // Without an equality function, the hash function is a plain method.
+    let input = INPUT.replace("- name: TWMainStructEqual\n", "- name: TWMainStructSame\n");
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("public final class MainStruct {"));
+    assert!(!output.contains("hash(into"));
+    assert!(output.contains("public func hash() -> Data {"));
+    assert_eq!(output.matches("TWMainStructHash(").count(), 1);
+}
+
+#[test]
+fn throwing_init() {
+    const INPUT: &str = include_str!("samples/throwing_init.input.yaml");
+    const EXPECTED: &str = include_str!("samples/throwing_init.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // A throwing init must be nullable.
+    let input = INPUT.replacen(
+        "is_nullable: true\n  is_throwing",
+        "is_nullable: false\n  is_throwing",
+        1,
+    );
+    render_to_strings(create_intput(&input)).unwrap_err();
+}
+
+#[test]
+fn default_params() {
+    const INPUT: &str = include_str!("samples/default_params.input.yaml");
+    const EXPECTED: &str = include_str!("samples/default_params.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // Only trailing parameters can have default values.
+    let input = INPUT.replacen(
+        "      is_pointer: true\n  - name: curve",
+        "      is_pointer: true\n    default_value: Data()\n  - name: curve",
+        1,
+    );
+    let input = input.replacen("    default_value: .secp256k1\n", "", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructSign' parameter 'curve'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    // A non-nullable parameter can't default to `nil`.
+    let input = INPUT.replacen("default_value: .secp256k1", "default_value: nil", 1);
+    render_to_strings(create_intput(&input)).unwrap_err();
+}
+
+#[test]
+fn param_labels() {
+    const INPUT: &str = include_str!("samples/param_labels.input.yaml");
+    const EXPECTED: &str = include_str!("samples/param_labels.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The C FFI call keeps using the parameter names.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("TWMainStructDerive(obj,path,data,index)"));
+
+    // A label must be `_` or an identifier.
+    let input = INPUT.replacen("label: with", "label: with data", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructDerive' parameter 'data'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn file_imports() {
+    // The enums only refer to the integer types.
+    let rendered =
+        render_to_strings(create_intput(include_str!("samples/enum.input.yaml"))).unwrap();
+    let (_name, output) = &rendered.enums[0];
+    assert!(!output.contains("import "));
+
+    // A struct returning data imports Foundation, a struct of strings doesn't.
+    let default_params = include_str!("samples/default_params.input.yaml");
+    let rendered = render_to_strings(create_intput(default_params)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("//\n\nimport Foundation\n\npublic final class MainStruct {"));
+    let rendered =
+        render_to_strings(create_intput(include_str!("samples/struct.input.yaml"))).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(!output.contains("import "));
+
+    // The extra imports are added to every file, sorted by name.
+    let mut input = create_intput(include_str!("samples/enum.input.yaml"));
+    input.extra_imports = vec!["WalletCoreSwiftProtobuf".to_string()];
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.enums[0];
+    assert!(output.contains("//\n\nimport WalletCoreSwiftProtobuf\n\npublic enum MainEnum"));
+
+    let mut input = create_intput(default_params);
+    input.extra_imports = vec![
+        "WalletCoreSwiftProtobuf".to_string(),
+        "Foundation".to_string(),
+    ];
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("import Foundation\nimport WalletCoreSwiftProtobuf\n\npublic final"));
+}
+
+#[test]
+fn ffi_stub() {
+    const INPUT: &str = include_str!("samples/default_params.input.yaml");
+
+    let stub = render_ffi_stub(&parse_str(INPUT).unwrap());
+    assert!(stub.contains("typealias TWData = UnsafeRawPointer\n"));
+    assert!(stub.contains("func TWDataNSData(_ data: UnsafeRawPointer) -> Data { fatalError() }"));
+    assert!(
+        stub.contains("struct TWCurve: RawRepresentable, Equatable {\n    var rawValue: UInt32\n")
+    );
+    // The objects are opaque pointers, the nullable parameters are optional.
+    assert!(stub.contains(
+        "func TWMainStructCreate(_ p0: UnsafeRawPointer, _ p1: UnsafeRawPointer?) -> OpaquePointer {"
+    ));
+    assert!(stub.contains(
+        "func TWMainStructSign(_ p0: OpaquePointer, _ p1: UnsafeRawPointer, _ p2: TWCurve) -> UnsafeRawPointer {"
+    ));
+    assert!(stub.contains("func TWMainStructDelete(_ p0: OpaquePointer) -> Void {"));
+
+    // The out-parameters are mutable.
+    let out_params = include_str!("samples/out_params.input.yaml");
+    let stub = render_ffi_stub(&parse_str(out_params).unwrap());
+    assert!(stub.contains("UnsafeMutablePointer<"));
+
+    // The helpers declared by the manifest itself are not redeclared.
+    let data_vector = include_str!("samples/data_vector.input.yaml").replacen(
+        "- name: TWTransactionCompilerPreImageHashesList",
+        "- name: TWDataVectorCreate",
+        1,
+    );
+    let stub = render_ffi_stub(&parse_str(&data_vector).unwrap());
+    assert_eq!(stub.matches("func TWDataVectorCreate(").count(), 1);
+}
+
+#[test]
+fn description_conformance() {
+    const INPUT: &str = include_str!("samples/description.input.yaml");
+    const EXPECTED: &str = include_str!("samples/description.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The conformance reuses the property, the C FFI function is called once.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert_eq!(output.matches("TWMainStructDescription(").count(), 1);
+
+    // A nullable description doesn't satisfy `CustomStringConvertible`.
+    let input = INPUT.replacen("is_nullable: false", "is_nullable: true", 1);
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains("public final class MainStruct {"));
+    assert!(!output.contains("debugDescription"));
+}
+
+#[test]
+fn return_ownership() {
+    const INPUT: &str = include_str!("samples/return_ownership.input.yaml");
+    const EXPECTED: &str = include_str!("samples/return_ownership.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The ownership can only be specified for strings and data.
+    let input = INPUT.replacen("variant: string", "variant: bool", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructDescription'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn arrays() {
+    const INPUT: &str = include_str!("samples/arrays.input.yaml");
+    const EXPECTED: &str = include_str!("samples/arrays.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The pointer and array types round-trip through the manifest.
+    let ty = TypeInfo {
+        variant: TypeVariant::Array(Box::new(TypeVariant::Struct("TWPublicKey".to_string()))),
+        is_constant: true,
+        is_nullable: false,
+        is_pointer: true,
+    };
+    let yaml = serde_yaml::to_string(&ty).unwrap();
+    assert_eq!(serde_yaml::from_str::<TypeInfo>(&yaml).unwrap(), ty);
+
+    // The number of elements must follow the pointer.
+    let input = INPUT.replacen("variant: size_t", "variant: int", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructCreateWithBytes'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn data_vectors() {
+    const INPUT: &str = include_str!("samples/data_vector.input.yaml");
+    const EXPECTED: &str = include_str!("samples/data_vector.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The file declaring the `TWDataVector` object renders it as is.
+    let input = INPUT.replacen(
+        "structs:",
+        "structs:\n- name: TWDataVector\n  is_public: true\n  is_class: true",
+        1,
+    );
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_, out) = rendered
+        .structs
+        .iter()
+        .find(|(name, _)| name == "TransactionCompiler")
+        .unwrap();
+    assert!(out.contains("signatures: DataVector"));
+
+    // Only the vectors of data can be returned.
+    let input = INPUT.replacen(
+        "variant: struct\n    value: TWDataVector",
+        "variant: array\n    value:\n      variant: string",
+        1,
+    );
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWTransactionCompilerPreImageHashesList'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn self_params() {
+    const INPUT: &str = include_str!("samples/self_params.input.yaml");
+    const EXPECTED: &str = include_str!("samples/self_params.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // Only the first parameter of a non-static function is `self`.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    let out = &rendered.structs[0].1;
+    assert!(out.contains("public func combine(other: MainStruct) -> Data"));
+    assert!(out.contains("TWMainStructCombine(obj,other)"));
+    assert!(out
+        .contains("public static func distance(first: MainStruct, second: MainStruct?) -> UInt32"));
+    assert!(out.contains("TWMainStructDistance(first,second)"));
+}
+
+#[test]
+fn out_params() {
+    const INPUT: &str = include_str!("samples/out_params.input.yaml");
+    const EXPECTED: &str = include_str!("samples/out_params.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The written value is returned instead of the success flag.
+    let input = INPUT.replacen("variant: bool", "variant: int", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructGet'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    // Only data and the pointers to numbers or bools can be written into.
+    let input = INPUT.replacen(
+        "variant: u_int32_t",
+        "variant: struct\n        value: TWMainStruct",
+        1,
+    );
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructChildCount'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn async_functions() {
+    const INPUT: &str = include_str!("samples/async.input.yaml");
+    const EXPECTED: &str = include_str!("samples/async.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // The blocking function remains next to the async variant.
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains("public func sign(input: Data) -> Data {"));
+    assert!(swift.contains("public func sign(input: Data) async -> Data {"));
+
+    // Getter-like async functions are not rendered as properties.
+    let input = INPUT.replacen(
+        "- name: TWMainStructIsValid\n  is_public: true\n",
+        "- name: TWMainStructIsValid\n  is_public: true\n  is_async: true\n",
+        1,
+    );
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains("public func isValid() async -> Bool {"));
+    assert!(!swift.contains("public var isValid: Bool {"));
+
+    // Neither explicitly.
+    let input = input.replacen(
+        "  is_public: true\n  is_async: true\n",
+        "  is_public: true\n  is_async: true\n  as_property: true\n",
+        1,
+    );
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert!(msg.contains("'TWMainStructIsValid'")),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn deprecated() {
+    const INPUT: &str = include_str!("samples/deprecated.input.yaml");
+    const EXPECTED: &str = include_str!("samples/deprecated.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+
+    // Properties are annotated as well.
+    let input = INPUT.replacen(
+        "  return_type:\n    variant: bool\n",
+        "  deprecated:\n    since: 3.1.1\n    replacement: isValid\n  return_type:\n    variant: bool\n",
+        1,
+    );
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains(
+        "    @available(*, deprecated, message: \"Use isValid instead\")\n    public var firstProperty: Bool {"
+    ));
+}
+
+#[test]
+fn deprecated_bare() {
+    const INPUT: &str = include_str!("samples/deprecated_bare.input.yaml");
+    const EXPECTED: &str = include_str!("samples/deprecated_bare.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+}
+
+#[test]
+fn naming_acronyms() {
+    let naming = NamingConfig::builtin();
+
+    // The acronyms apply to any object.
+    assert_eq!(
+        naming.member_name("TWBarz", "GetCounterfactualAddressJson"),
+        "getCounterfactualAddressJSON"
+    );
+    assert_eq!(
+        naming.member_name("TWStoredKey", "ImportHDWallet"),
+        "importHDWallet"
+    );
+    assert_eq!(naming.member_name("TWPrivateKey", "SignAsDER"), "signAsDER");
+    assert_eq!(
+        naming.member_name("TWCoinTypeConfiguration", "GetTransactionURL"),
+        "getTransactionURL"
+    );
+    // Whole words only, and the first word stays lowercase.
+    assert_eq!(
+        naming.member_name("TWHDWallet", "GetDerivedKey"),
+        "getDerivedKey"
+    );
+    assert_eq!(naming.member_name("TWFoo", "JsonString"), "jsonString");
+
+    // The overrides apply to the given object only.
+    assert_eq!(naming.member_name("TWHash", "RIPEMD"), "RIPEMD");
+    assert_eq!(
+        naming.member_name("TWHash", "SHA3_256RIPEMD"),
+        "sha3_256RIPEMD"
+    );
+    assert_eq!(naming.member_name("TWHash", "SHA256SHA256"), "sha256SHA256");
+    assert_eq!(naming.member_name("TWFoo", "SHA256SHA256"), "sha256sha256");
+    // The shipped names that predate the acronyms are kept as is.
+    assert_eq!(naming.member_name("TWBase64", "EncodeUrl"), "encodeUrl");
+    assert_eq!(naming.member_name("TWBase64", "DecodeUrl"), "decodeUrl");
+    assert_eq!(
+        naming.member_name("TWAsnParser", "EcdsaSignatureFromDer"),
+        "ecdsaSignatureFromDer"
+    );
+
+    let naming = NamingConfig::from_yaml(
+        "\
+acronyms:
+  Id: ID
+overrides:
+  TWFoo:
+  - from: getID
+    to: identifier
+",
+    )
+    .unwrap();
+    assert_eq!(naming.member_name("TWBar", "GetId"), "getID");
+    assert_eq!(naming.member_name("TWFoo", "GetId"), "identifier");
+    assert_eq!(naming.member_name("TWFoo", "GetIdentity"), "getIdentity");
+    assert_eq!(naming.member_name("TWBar", "ExportJson"), "exportJson");
+}
+
+#[test]
+fn naming_acronyms_render() {
+    let yaml = include_str!("../../manifest/TWBarz.yaml").replace(
+        "TWBarzGetCounterfactualAddress\n",
+        "TWBarzGetCounterfactualAddressJson\n",
+    );
+    let rendered = render_to_strings(create_intput(&yaml)).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(
+        output.contains("public static func getCounterfactualAddressJSON(input: Data) -> String {")
+    );
+    assert!(output.contains("TWBarzGetCounterfactualAddressJson(input)"));
+
+    // Without the acronyms, the standard camelCase is used.
+    let mut input = create_intput(&yaml);
+    input.naming = NamingConfig::default();
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(
+        output.contains("public static func getCounterfactualAddressJson(input: Data) -> String {")
+    );
+}
+
+#[test]
+fn exclusions() {
+    const INPUT: &str = include_str!("samples/deprecated.input.yaml");
+    const EXPECTED: &str = include_str!("samples/deprecated.output.swift");
+    const SIGN_LEGACY: &str = r#"    @available(*, deprecated, message: "Use sign(data:) instead")
+    public func signLegacy(data: Data) -> Data {
+        let obj = self.rawValue
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        let result = TWMainStructSignLegacy(obj,data)
+        return TWDataNSData(result)
+    }
+
+"#;
+
+    assert!(EXPECTED.contains(SIGN_LEGACY));
+    let expected = EXPECTED.replacen(SIGN_LEGACY, "", 1);
+
+    // By the object and the Swift member name, or by the C FFI member name.
+    let patterns = [
+        "TWMainStruct.signLegacy",
+        "TWMain*.sign?egacy",
+        "TWMainStructSignLegacy",
+        "*Legacy",
+    ];
+    for pattern in patterns {
+        let mut input = create_intput(INPUT);
+        input.exclusions.deny = vec![pattern.to_string()];
+        let rendered = render_to_strings(input).unwrap();
+
+        // The sibling methods remain.
+        let (_name, output) = &rendered.structs[0];
+        assert_eq!(output, &expected, "{pattern}");
+        assert_eq!(
+            rendered.skipped,
+            vec![SkippedItem {
+                name: "TWMainStructSignLegacy".to_string(),
+                reason: EXCLUDED_BY_CONFIG.to_string(),
+            }]
+        );
+
+        let methods = &rendered.surface.objects["TWMainStruct"].methods;
+        assert!(methods.contains_key("TWMainStructSign"));
+        assert!(!methods.contains_key("TWMainStructSignLegacy"));
+    }
+
+    // The member of another object, or the member name as an object name.
+    for pattern in ["Other.signLegacy", "signLegacy"] {
+        let mut input = create_intput(INPUT);
+        input.exclusions.deny = vec![pattern.to_string()];
+        let rendered = render_to_strings(input).unwrap();
+        assert_eq!(rendered.structs[0].1, EXPECTED);
+        assert!(rendered.skipped.is_empty());
+    }
+
+    // The members of an excluded object are excluded as well.
+    let mut input = create_intput(INPUT);
+    input.exclusions.deny = vec!["TWMainStruct".to_string()];
+    let rendered = render_to_strings(input).unwrap();
+    assert!(rendered.structs.is_empty());
+    assert!(rendered.surface.objects.is_empty());
+    let skipped: Vec<&str> = rendered.skipped.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        skipped,
+        [
+            "TWMainStruct",
+            "TWMainStructCreate",
+            "TWMainStructCreateWithData",
+            "TWMainStructSign",
+            "TWMainStructSignLegacy",
+            "TWMainStructFirstProperty"
+        ]
+    );
+}
+
+#[test]
+fn conformances() {
+    const ENUM_INPUT: &str = include_str!("samples/enum.input.yaml");
+    const HASHABLE_INPUT: &str = include_str!("samples/hashable.input.yaml");
+
+    // The structs/classes ending with `Address` conform to `Address`, followed
+    // by the derived `Hashable`.
+    let input = HASHABLE_INPUT.replace("MainStruct", "MainAddress");
+    let rendered = render_to_strings(create_intput(&input)).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains("public final class MainAddress: Address, Hashable {"));
+
+    // The suffix rule can be disabled.
+    let mut input = create_intput(&input);
+    input.conformances.suffixes.clear();
+    let rendered = render_to_strings(input.clone()).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains("public final class MainAddress: Hashable {"));
+
+    // The protocols of a type replace the ones of the rules.
+    input.conformances.types = BTreeMap::from([(
+        "MainAddress".to_string(),
+        vec!["Signer".to_string(), "Hashable".to_string()],
+    )]);
+    let rendered = render_to_strings(input).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    assert!(swift.contains("public final class MainAddress: Signer, Hashable {"));
+
+    // The enums keep the raw value type, the `CaseIterable` can be dropped.
+    let mut input = create_intput(ENUM_INPUT);
+    input.conformances.types = BTreeMap::from([(
+        "MainEnum".to_string(),
+        vec!["CustomStringConvertible".to_string()],
+    )]);
+    let rendered = render_to_strings(input).unwrap();
+    let (_, swift) = &rendered.enums[0];
+    assert!(swift.contains("public enum MainEnum: UInt32, CustomStringConvertible {"));
+
+    let mut input = create_intput(ENUM_INPUT);
+    input.conformances.enums.clear();
+    let rendered = render_to_strings(input).unwrap();
+    let (_, swift) = &rendered.enums[0];
+    assert!(swift.contains("public enum MainEnum: UInt32, CustomStringConvertible {"));
+
+    let config = ConformanceConfig::from_yaml("types:\n  CoinType: []\n").unwrap();
+    assert!(config
+        .enum_protocols("CoinType", &["CustomStringConvertible"])
+        .is_empty());
+    assert!(config.struct_protocols("AnyAddress", &[]).is_empty());
+
+    // `CustomStringConvertible` of `DerivationPath` is hand-written.
+    let builtin = ConformanceConfig::builtin();
+    let derived = ["CustomStringConvertible", "CustomDebugStringConvertible"];
+    assert_eq!(
+        builtin.struct_protocols("DerivationPath", &derived),
+        vec!["CustomDebugStringConvertible"]
+    );
+    assert_eq!(
+        builtin.struct_protocols("DerivationPathIndex", &derived),
+        vec!["CustomDebugStringConvertible"]
+    );
+}
+
+#[test]
+fn exclusions_glob_match() {
+    assert!(glob_match("TWStoredKey", "TWStoredKey"));
+    assert!(!glob_match("TWStoredKey", "TWStoredKeyFoo"));
+    assert!(glob_match("TW*Testing*", "TWFooTestingBar"));
+    assert!(glob_match("TW*Testing*", "TWTesting"));
+    assert!(!glob_match("TW*Testing*", "TWFooTestBar"));
+    assert!(glob_match("*Unsafe", "importPrivateKeyUnsafe"));
+    assert!(glob_match("sign?", "signs"));
+    assert!(!glob_match("sign?", "sign"));
+    assert!(glob_match("*", ""));
+    assert!(glob_match("a*b*c", "aXbYbZc"));
+    assert!(!glob_match("a*b*c", "aXbYbZ"));
+
+    let config = ExclusionConfig::from_yaml("deny:\n- TW*Testing*\n").unwrap();
+    assert!(config.excludes_object("TWFooTesting"));
+    assert!(config.excludes_member("TWFooTestingBar", None));
+    assert!(ExclusionConfig::builtin().deny.is_empty());
+}
+
+#[test]
+fn function_returns_self() {
+    const INPUT: &str = include_str!("samples/returns_self.input.yaml");
+    const EXPECTED: &str = include_str!("samples/returns_self.output.swift");
+
+    render_and_compare_struct(INPUT, EXPECTED);
+}
+
+#[test]
+fn function_returns_self_invalid() {
+    const INPUT: &str = include_str!("samples/returns_self.input.yaml");
+
+    // The ownership must be specified for the functions returning the object's own type.
+    let input = INPUT.replace("  returns_self: owned\n", "");
+    assert!(render_to_strings(create_intput(&input)).is_err());
+
+    let input = INPUT.replace("  returns_self: borrowed\n", "");
+    assert!(render_to_strings(create_intput(&input)).is_err());
+
+    // Static functions return new instances only.
+    let input = INPUT.replace(
+        "  is_static: false\n  returns_self: owned\n",
+        "  is_static: true\n  returns_self: owned\n",
+    );
+    assert!(render_to_strings(create_intput(&input)).is_err());
+
+    // Unknown ownership.
+    let input = INPUT.replace("returns_self: owned", "returns_self: shared");
+    assert!(parse_str(&input).is_err());
+}
+
+#[test]
+fn bad_object_name_and_unsupported_type() {
+    const INPUT: &str = include_str!("samples/struct.input.yaml");
+
+    // A struct without the `TW` prefix.
+    let input = INPUT.replace("TWMainStruct", "MainStruct");
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadObjectName { file, name } => {
+            assert_eq!(file, "Struct");
+            assert_eq!(name, "MainStruct");
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    // A struct named `TW` has no Swift name.
+    let input = INPUT.replacen("- name: TWMainStruct \n", "- name: TW\n", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadObjectName { file, name } => {
+            assert_eq!(file, "Struct");
+            assert_eq!(name, "TW");
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    // A `void` parameter has no Swift counterpart.
+    let input = INPUT.replacen("variant: string", "variant: void", 1);
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::UnsupportedType { file, function, ty } => {
+            assert_eq!(file, "Struct");
+            assert_eq!(function, "TWMainStructCreate");
+            assert_eq!(ty, "void string");
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn proto_with_helpers() {
+    const INPUT: &str = include_str!("samples/proto.input.yaml");
+    const EXPECTED: &str = include_str!("samples/proto.output.swift");
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+
+    assert!(rendered.structs.is_empty());
+    assert!(rendered.enums.is_empty());
+    assert!(rendered.extensions.is_empty());
+    assert_eq!(rendered.protos.len(), 1);
+
+    let (name, output) = &rendered.protos[0];
+    assert_eq!(name, "Bitcoin");
+    assert_eq!(output, EXPECTED);
+}
+
+#[test]
+fn proto_naming_convention() {
+    let naming = ProtoNamingConvention::default();
+
+    assert_eq!(naming.file_name("TWBitcoinProto").unwrap(), "Bitcoin");
+    // The prefix and the suffix are stripped at the ends only.
+    assert_eq!(naming.file_name("TWNetworkTWProto").unwrap(), "NetworkTW");
+    assert!(naming.file_name("BitcoinProto").is_err());
+    assert!(naming.file_name("TWBitcoin").is_err());
+    assert!(naming.file_name("TWProto").is_err());
+
+    assert_eq!(
+        naming
+            .message_name("TWBitcoinProto", "TW_Bitcoin_Proto_SigningInput")
+            .unwrap(),
+        "BitcoinSigningInput"
+    );
+    assert_eq!(
+        naming
+            .message_name("TWEthereumAbiProto", "TW_EthereumAbi_Proto_Param_Value")
+            .unwrap(),
+        "EthereumAbiParamValue"
+    );
+    assert_eq!(
+        naming
+            .message_name("TWNetworkTWProto", "TW_NetworkTW_Proto_TWInput")
+            .unwrap(),
+        "NetworkTWTWInput"
+    );
+    assert!(naming
+        .message_name("TWBitcoinProto", "Bitcoin_Proto_SigningInput")
+        .is_err());
+    assert!(naming
+        .message_name("TWBitcoinProto", "TW_Bitcoin_SigningInput")
+        .is_err());
+    assert!(naming
+        .message_name("TWBitcoinProto", "TW_Bitcoin_Proto_")
+        .is_err());
+
+    let naming = ProtoNamingConvention {
+        prefix: "Foo".to_string(),
+        suffix: "Messages".to_string(),
+        separator: "__".to_string(),
+    };
+    assert_eq!(naming.file_name("FooBitcoinMessages").unwrap(), "Bitcoin");
+    assert_eq!(
+        naming
+            .message_name("FooBitcoinMessages", "Foo__Bitcoin__Messages__Input")
+            .unwrap(),
+        "BitcoinInput"
+    );
+}
+
+#[test]
+fn proto_nonconforming_file() {
+    const INPUT: &str = include_str!("samples/proto.input.yaml");
+
+    let input = INPUT.replace("name: TWBitcoinProto", "name: BitcoinMessages");
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert_eq!(
+            msg,
+            "proto file 'BitcoinMessages' does not follow the 'TW<Name>Proto' naming convention"
+        ),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    let input = INPUT.replace("TW_Bitcoin_Proto_Transaction", "Bitcoin_Transaction");
+    match render_to_strings(create_intput(&input)).unwrap_err() {
+        Error::BadFormat(msg) => assert_eq!(
+            msg,
+            "proto message 'Bitcoin_Transaction' of file 'TWBitcoinProto' does not follow the 'TW_<Module>_Proto_<Message>' naming convention"
+        ),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+
+    // The file follows a custom convention.
+    let input = INPUT
+        .replace("TWBitcoinProto", "WCBitcoinMessages")
+        .replace("TW_Bitcoin_Proto_", "WC_Bitcoin_Messages_");
+    let mut input = create_intput(&input);
+    input.proto_naming = ProtoNamingConvention {
+        prefix: "WC".to_string(),
+        suffix: "Messages".to_string(),
+        separator: "_".to_string(),
+    };
+    let rendered = render_to_strings(input).unwrap();
+    let (name, output) = &rendered.protos[0];
+    assert_eq!(name, "Bitcoin");
+    assert!(
+        output.contains("public typealias BitcoinSigningInput = WC_Bitcoin_Messages_SigningInput")
+    );
+}
+
+#[test]
+fn proto_registry() {
+    const INPUT: &str = include_str!("samples/proto.input.yaml");
+    const EXPECTED: &str = include_str!("samples/proto_registry.output.swift");
+    const ABI_INPUT: &str = r#"
+name: TWEthereumAbiProto
+protos:
+- TW_EthereumAbi_Proto_Param_Value
+- TW_EthereumAbi_Proto_ContractCall
+"#;
+
+    // The registry doesn't depend on the order of the manifests.
+    for inputs in [[INPUT, ABI_INPUT], [ABI_INPUT, INPUT]] {
+        let mut protos = vec![];
+        for input in inputs {
+            let mut rendered = render_to_strings(create_intput(input)).unwrap();
+            protos.append(&mut rendered.proto_types);
+        }
+
+        let (path, out) = render_proto_registry(&protos).unwrap();
+        assert_eq!(path, "Protobuf/ProtoRegistry.swift");
+        assert_eq!(out, EXPECTED);
+    }
+}
+
+#[test]
+fn proto_registry_duplicate_alias() {
+    const INPUT: &str = include_str!("samples/proto.input.yaml");
+    // Rendered as `BitcoinSigningInput` as well.
+    const CLASHING_INPUT: &str = r#"
+name: TWBitcoinSigningProto
+protos:
+- TW_BitcoinSigning_Proto_Input
+"#;
+
+    let mut protos = vec![];
+    for input in [INPUT, CLASHING_INPUT] {
+        let mut rendered = render_to_strings(create_intput(input)).unwrap();
+        protos.append(&mut rendered.proto_types);
+    }
+
+    match render_proto_registry(&protos).unwrap_err() {
+        Error::BadFormat(msg) => assert_eq!(
+            msg,
+            "proto alias 'BitcoinSigningInput' is declared by both 'TW_Bitcoin_Proto_SigningInput' and 'TW_BitcoinSigning_Proto_Input'"
+        ),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn render_all_manifests() {
+    const STRUCT_INPUT: &str = include_str!("samples/non-associated.input.yaml");
+    const ENUM_INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+    const PROTO_INPUT: &str = include_str!("samples/proto.input.yaml");
+
+    let inputs = [STRUCT_INPUT, ENUM_INPUT, PROTO_INPUT];
+    let file_infos = inputs.iter().map(|yaml| parse_str(yaml).unwrap()).collect();
+    let output = render_all(
+        file_infos,
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap();
+
+    // The same files as rendered one by one, followed by the registry.
+    let mut expected = vec![];
+    let mut protos = vec![];
+    for yaml in inputs {
+        let mut rendered = render_to_strings(create_intput(yaml)).unwrap();
+        protos.append(&mut rendered.proto_types);
+        expected.extend(rendered.into_files());
+    }
+    expected.push(render_proto_registry(&protos).unwrap());
+    assert_eq!(output.files, expected);
+
+    // The functions of `OtherStruct`, which is not declared by any manifest.
+    assert_eq!(output.unclaimed, vec!["OtherStructFirstFunction"]);
+    assert_eq!((output.stats.classes, output.stats.enums), (1, 1));
+}
+
+#[test]
+fn render_all_unclaimed_functions() {
+    const INPUT: &str = include_str!("samples/non-associated.input.yaml");
+    const OTHER_INPUT: &str = r#"
+name: OtherManifest
+structs:
+- name: TWOtherStruct
+  is_public: true
+  is_class: false
+functions:
+- name: TWMainStructThirdFunction
+  is_public: true
+  is_static: true
+  params: []
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let file_infos = [INPUT, OTHER_INPUT]
+        .iter()
+        .map(|yaml| parse_str(yaml).unwrap())
+        .collect();
+    let output = render_all(
+        file_infos,
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap();
+
+    // `TWMainStructThirdFunction` matches `TWMainStruct` of the other manifest.
+    assert_eq!(output.unclaimed, vec!["OtherStructFirstFunction"]);
+    assert!(output.warnings.contains(
+        &"'TWMainStructThirdFunction' of 'OtherManifest' is not rendered, since it belongs to 'TWMainStruct' of 'NonAssociated'"
+            .to_string()
+    ));
+}
+
+#[test]
+fn render_all_duplicate_type() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+    // The proto files must be named `TW<Module>Proto`.
+    const BAD_FILE: &str = r#"
+name: TWBitcoin
+protos:
+- TW_Bitcoin_Proto_SigningInput
+"#;
+    // Declares `MainStruct` as well.
+    const CLASHING_INPUT: &str = include_str!("samples/non-associated.input.yaml");
+
+    let file_infos = [INPUT, BAD_FILE, CLASHING_INPUT]
+        .iter()
+        .map(|yaml| parse_str(yaml).unwrap())
+        .collect();
+
+    // The duplicate type is reported along with the other file errors.
+    match render_all(
+        file_infos,
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap_err()
+    {
+        Error::FileErrors(errors) => {
+            let files: Vec<_> = errors.iter().map(|(file, _)| file.as_str()).collect();
+            assert_eq!(files, vec!["TWBitcoin", "NonAssociated"]);
+            match &errors[1].1 {
+                Error::BadFormat(msg) => assert_eq!(
+                    msg,
+                    "type 'MainStruct' is generated by both 'FunctionProperty' and 'NonAssociated'"
+                ),
+                err => panic!("Unexpected error: {err:?}"),
+            }
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn render_all_file_errors() {
+    const INPUT: &str = include_str!("samples/struct.input.yaml");
+    // The proto files must be named `TW<Module>Proto`.
+    const BAD_FILE: &str = r#"
+name: TWBitcoin
+protos:
+- TW_Bitcoin_Proto_SigningInput
+"#;
+    const BAD_MESSAGE: &str = r#"
+name: TWEthereumProto
+protos:
+- TW_Ethereum_SigningInput
+"#;
+
+    let file_infos = [BAD_FILE, INPUT, BAD_MESSAGE]
+        .iter()
+        .map(|yaml| parse_str(yaml).unwrap())
+        .collect();
+
+    // Both errors are reported in the order of the manifests.
+    match render_all(
+        file_infos,
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap_err()
+    {
+        Error::FileErrors(errors) => {
+            let files: Vec<_> = errors.iter().map(|(file, _)| file.as_str()).collect();
+            assert_eq!(files, vec!["TWBitcoin", "TWEthereumProto"]);
+            assert!(errors
+                .iter()
+                .all(|(_, err)| matches!(err, Error::BadFormat(_))));
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn template_render_error() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let mut templates = SwiftTemplates::builtin();
+    templates.partial_func_template = "    {{missing}}\n".to_string();
+
+    let file_infos = vec![parse_str(INPUT).unwrap()];
+    let (file_name, err) = match render_all(file_infos, templates, RenderConfig::builtin()) {
+        Err(Error::FileErrors(mut errors)) if errors.len() == 1 => errors.remove(0),
+        Err(err) => panic!("Unexpected error: {err:?}"),
+        Ok(_) => panic!("Expected the rendering to fail"),
+    };
+    assert_eq!(file_name, "FunctionProperty");
+
+    match err {
+        Error::TemplateRender {
+            template,
+            object,
+            payload,
+            source,
+        } => {
+            // Both the partial and the object are named.
+            assert_eq!(template, "struct");
+            assert_eq!(object, "MainStruct");
+            assert_eq!(source.template_name.as_deref(), Some("partial_func"));
+            assert!(source.desc.contains("missing"), "{}", source.desc);
+            // The payload of the object is kept for debugging.
+            assert_eq!(payload["name"], "MainStruct");
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn public_symbols_of_rendered_struct() {
+    const EXPECTED: &str = include_str!("samples/function_property.output.swift");
+
+    let symbols: Vec<_> = public_symbols(EXPECTED).into_iter().collect();
+    assert_eq!(
+        symbols,
+        vec![
+            "MainStruct",
+            "MainStruct.childCount",
+            "MainStruct.hasChildren()",
+            "MainStruct.isNamed(name:)",
+            "MainStruct.isValid",
+        ]
+    );
+}
+
+#[test]
+fn write_output_layout() {
+    const STRUCT_INPUT: &str = include_str!("samples/function_property.input.yaml");
+    const ENUM_INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+    const PROTO_INPUT: &str = include_str!("samples/proto.input.yaml");
+
+    let out_dir = std::env::temp_dir().join(format!("codegen-write-{}", std::process::id()));
+    let write = |yaml: &str| {
+        let rendered = render_to_strings(create_intput(yaml)).unwrap();
+        write_output(rendered, &out_dir)
+    };
+
+    let struct_paths = write(STRUCT_INPUT);
+    let enum_paths = write(ENUM_INPUT);
+    let proto_paths = write(PROTO_INPUT);
+    let struct_content =
+        std::fs::read_to_string(out_dir.join("Sources/Generated/MainStruct.swift"));
+    let remaining: Vec<bool> = [
+        "Sources/Generated/MainStruct.swift",
+        "Enums/MainEnum.swift",
+        "Extensions/MainEnum+Extension.swift",
+        "Protobuf/Bitcoin+Proto.swift",
+    ]
+    .iter()
+    .map(|path| out_dir.join(path).exists())
+    .collect();
+    std::fs::remove_dir_all(&out_dir).unwrap();
+
+    assert_eq!(
+        struct_paths.unwrap(),
+        vec![out_dir.join("Sources/Generated/MainStruct.swift")]
+    );
+    assert_eq!(
+        enum_paths.unwrap(),
+        vec![
+            out_dir.join("Enums/MainEnum.swift"),
+            out_dir.join("Extensions/MainEnum+Extension.swift"),
+        ]
+    );
+    assert_eq!(
+        proto_paths.unwrap(),
+        vec![out_dir.join("Protobuf/Bitcoin+Proto.swift")]
+    );
+
+    // The generated header goes right below the SPDX license header.
+    let expected = include_str!("samples/function_property.output.swift");
+    let (license, rest) = expected.split_at(expected.find("//\n// This is").unwrap());
+    assert!(license.starts_with("// SPDX-License-Identifier:"));
+    assert_eq!(
+        struct_content.unwrap(),
+        format!("{license}{}{rest}", generated_header())
+    );
+
+    // The files of the other manifests are kept.
+    assert_eq!(remaining, vec![true, true, true, true]);
+}
+
+#[test]
+fn orphan_detection() {
+    const STRUCT_INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let files = render_to_strings(create_intput(STRUCT_INPUT))
+        .unwrap()
+        .into_files();
+
+    let out_dir = std::env::temp_dir().join(format!("codegen-orphans-{}", std::process::id()));
+    let run = || -> crate::Result<_> {
+        // The current file, the bindings of a removed header and a hand-written file.
+        write_files(files.clone(), &out_dir, WriteOptions::default())?;
+        std::fs::create_dir_all(out_dir.join("Enums"))?;
+        std::fs::write(
+            out_dir.join("Enums/OldEnum.swift"),
+            format!("{}public enum OldEnum {{}}\n", generated_header()),
+        )?;
+        std::fs::write(out_dir.join("Custom.swift"), "public enum Custom {}\n")?;
+
+        let orphans = find_orphans(&out_dir, &files)?;
+        // The orphans are reported, but kept without pruning.
+        write_files(files.clone(), &out_dir, WriteOptions::default())?;
+        let kept = out_dir.join("Enums/OldEnum.swift").exists();
+        write_files(files.clone(), &out_dir, WriteOptions { remove_stale: true })?;
+
+        let remaining: Vec<bool> = [
+            "Sources/Generated/MainStruct.swift",
+            "Enums/OldEnum.swift",
+            "Custom.swift",
+        ]
+        .iter()
+        .map(|path| out_dir.join(path).exists())
+        .collect();
+        let orphans_after = find_orphans(&out_dir, &files)?;
+        Ok((orphans, kept, remaining, orphans_after))
+    };
+    let result = run();
+    std::fs::remove_dir_all(&out_dir).unwrap();
+    let (orphans, kept, remaining, orphans_after) = result.unwrap();
+
+    assert_eq!(orphans, vec!["Enums/OldEnum.swift"]);
+    assert!(kept);
+    assert_eq!(remaining, vec![true, false, true]);
+    assert!(orphans_after.is_empty());
+
+    // A missing directory has no orphans.
+    assert!(find_orphans(&out_dir, &files).unwrap().is_empty());
+}
+
+#[test]
+fn dry_run_diff_report() {
+    const STRUCT_INPUT: &str = include_str!("samples/function_property.input.yaml");
+    const ENUM_INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+
+    let mut files = render_to_strings(create_intput(STRUCT_INPUT))
+        .unwrap()
+        .into_files();
+    files.extend(
+        render_to_strings(create_intput(ENUM_INPUT))
+            .unwrap()
+            .into_files(),
+    );
+
+    // The bindings on disk were generated before `childCount` became a property.
+    let stale_input = STRUCT_INPUT.replace("as_property: true", "as_property: false");
+    let (_name, stale_struct) = render_to_strings(create_intput(&stale_input))
+        .unwrap()
+        .structs
+        .remove(0);
+    let (_path, extension) = files
+        .iter()
+        .find(|(path, _)| path == "Extensions/MainEnum+Extension.swift")
+        .unwrap();
+
+    let out_dir = std::env::temp_dir().join(format!("codegen-dry-run-{}", std::process::id()));
+    for dir in ["Enums", "Extensions", "Sources/Generated"] {
+        std::fs::create_dir_all(out_dir.join(dir)).unwrap();
+    }
+    std::fs::write(
+        out_dir.join("Sources/Generated/MainStruct.swift"),
+        stale_struct,
+    )
+    .unwrap();
+    std::fs::write(
+        out_dir.join("Extensions/MainEnum+Extension.swift"),
+        extension,
+    )
+    .unwrap();
+    std::fs::write(
+        out_dir.join("Enums/OldEnum.swift"),
+        format!(
+            "{}public enum OldEnum: UInt32 {{\n    case one = 0\n}}\n",
+            generated_header()
+        ),
+    )
+    .unwrap();
+    // Not a binding, must be ignored.
+    std::fs::write(out_dir.join("README.md"), "bindings").unwrap();
+    // Hand-written, must be ignored.
+    std::fs::write(out_dir.join("Custom.swift"), "public enum Custom {}\n").unwrap();
+
+    let report = diff_with_dir(&out_dir, &files);
+    std::fs::remove_dir_all(&out_dir).unwrap();
+    let report = report.unwrap();
+
+    assert!(report.has_changes);
+    let statuses: Vec<_> = report
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("Enums/MainEnum.swift", FileStatus::New),
+            ("Enums/OldEnum.swift", FileStatus::Orphaned),
+            ("Extensions/MainEnum+Extension.swift", FileStatus::Unchanged),
+            ("Sources/Generated/MainStruct.swift", FileStatus::Modified),
+        ]
+    );
+
+    let new_enum = &report.files[0];
+    assert!(new_enum.added_symbols.contains(&"MainEnum".to_string()));
+    assert!(new_enum.removed_symbols.is_empty());
+
+    let orphaned = &report.files[1];
+    assert!(orphaned.added_symbols.is_empty());
+    assert_eq!(orphaned.removed_symbols, vec!["OldEnum", "OldEnum.one"]);
+
+    let unchanged = &report.files[2];
+    assert!(unchanged.added_symbols.is_empty());
+    assert!(unchanged.removed_symbols.is_empty());
+
+    let modified = &report.files[3];
+    assert_eq!(modified.added_symbols, vec!["MainStruct.childCount"]);
+    assert_eq!(modified.removed_symbols, vec!["MainStruct.childCount()"]);
+    assert!(unchanged.diff.is_none());
+
+    // The unified diff of the modified file.
+    let diff = modified.diff.as_deref().unwrap();
+    assert!(diff.starts_with(
+        "--- a/Sources/Generated/MainStruct.swift\n+++ b/Sources/Generated/MainStruct.swift\n@@"
+    ));
+    assert!(
+        diff.contains("\n-    public func childCount() -> UInt32 {\n"),
+        "{diff}"
+    );
+    assert!(
+        diff.contains("\n+    public var childCount: UInt32 {\n"),
+        "{diff}"
+    );
+
+    // The report is serialized with snake case statuses.
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["files"][3]["status"], "modified");
+}
+
+#[test]
+fn check_mode() {
+    const INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+
+    let output = render_all(
+        vec![parse_str(INPUT).unwrap()],
+        SwiftTemplates::builtin(),
+        RenderConfig::builtin(),
+    )
+    .unwrap();
+
+    let out_dir = std::env::temp_dir().join(format!("codegen-check-{}", std::process::id()));
+    let check = |output: &RenderOutput| {
+        let up_to_date = !output.check(&out_dir).unwrap();
+        (up_to_date, output.diff_with_dir(&out_dir).unwrap())
+    };
+
+    // Nothing is written yet.
+    let (up_to_date, report) = check(&output);
+    assert!(!up_to_date);
+    assert!(report
+        .files
+        .iter()
+        .all(|file| file.status == FileStatus::New));
+
+    let files = output.files.clone();
+    write_files(files, &out_dir, WriteOptions::default()).unwrap();
+    assert!(check(&output).0);
+
+    // The bindings written by another codegen version are up to date.
+    let extension_path = out_dir.join("Extensions/MainEnum+Extension.swift");
+    let extension = std::fs::read_to_string(&extension_path).unwrap();
+    let older = extension.replacen(env!("CARGO_PKG_VERSION"), "0.0.1", 1);
+    assert_ne!(older, extension);
+    std::fs::write(&extension_path, &older).unwrap();
+    assert!(check(&output).0);
+
+    // Hand edits are reported.
+    std::fs::write(&extension_path, older.replace("public ", "")).unwrap();
+    let (up_to_date, report) = check(&output);
+    std::fs::remove_dir_all(&out_dir).unwrap();
+
+    assert!(!up_to_date);
+    let modified: Vec<_> = report
+        .files
+        .iter()
+        .filter(|file| file.status != FileStatus::Unchanged)
+        .collect();
+    assert_eq!(modified.len(), 1);
+    assert_eq!(modified[0].path, "Extensions/MainEnum+Extension.swift");
+    assert!(modified[0].diff.is_some());
+}
+
+// Convenience function: a stub backend that renders every item associated with
+// an object by the name prefix, except for the items using `unsupported` types.
+// Objects without any rendered member are skipped, like in the Swift backend.
+fn stub_backend_surface(yaml: &str, unsupported: &[TypeVariant]) -> ApiSurface {
+    let mut info = parse_str(yaml).unwrap();
+    let mut surface = ApiSurface::default();
+
+    let is_supported = |params: &[ParamInfo], ty: Option<&TypeInfo>| {
+        params
+            .iter()
+            .map(|p| &p.ty)
+            .chain(ty)
+            .all(|ty| !unsupported.contains(&ty.variant))
+    };
+
+    let objects = info
+        .structs
+        .iter()
+        .map(|s| {
+            let kind = if s.is_class {
+                ObjectKind::Class
+            } else {
+                ObjectKind::Struct
+            };
+            (s.name.clone(), kind, vec![])
+        })
+        .chain(info.enums.iter().map(|e| {
+            let variants = e.variants.iter().map(|v| v.name.clone()).collect();
+            (e.name.clone(), ObjectKind::Enum, variants)
+        }));
+
+    let mut object_surfaces = vec![];
+    for (name, kind, variants) in objects {
+        let mut object = ApiSurface::default();
+        let object_surface = object.add_object(&name, kind);
+        object_surface.variants = variants;
+
+        let (inits, remaining) = info
+            .inits
+            .into_iter()
+            .partition(|i| i.name.starts_with(&name));
+        info.inits = remaining;
+        let (funcs, remaining) = info
+            .functions
+            .into_iter()
+            .partition(|f| f.name.starts_with(&name));
+        info.functions = remaining;
+        let (props, remaining) = info
+            .properties
+            .into_iter()
+            .partition(|p| p.name.starts_with(&name));
+        info.properties = remaining;
+
+        for init in inits.iter().filter(|i| is_supported(&i.params, None)) {
+            object_surface.add_init(&name, init);
+        }
+        for func in funcs
+            .iter()
+            .filter(|f| is_supported(&f.params, Some(&f.return_type)))
+        {
+            object_surface.add_method(func);
+        }
+        for prop in props
+            .iter()
+            .filter(|p| is_supported(&[], Some(&p.return_type)))
+        {
+            object_surface.add_property(prop);
+        }
+
+        let is_empty = object_surface.inits.is_empty()
+            && object_surface.methods.is_empty()
+            && object_surface.properties.is_empty();
+        if kind == ObjectKind::Enum || !is_empty {
+            object_surfaces.push(object);
+        }
+    }
+
+    for object in object_surfaces {
+        surface.extend(object);
+    }
+    for proto in &info.protos {
+        surface.add_object(&proto.name, ObjectKind::Proto);
+    }
+
+    surface
+}
+
+#[test]
+fn swift_and_stub_backend_surfaces_agree() {
+    for fixture in FIXTURES {
+        let swift = render_to_strings(create_intput(fixture)).unwrap().surface;
+        let stub = stub_backend_surface(fixture, &[]);
+
+        assert!(!swift.objects.is_empty());
+        let mismatches = compare_surfaces(("swift", &swift), ("stub", &stub));
+        assert!(mismatches.is_empty(), "{mismatches:?}");
+    }
+}
+
+#[test]
+fn surface_is_normalized() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let surface = render_to_strings(create_intput(INPUT)).unwrap().surface;
+    let object = &surface.objects["TWMainStruct"];
+
+    // The getters rendered as Swift properties remain C FFI methods.
+    assert!(object.properties.is_empty());
+    assert_eq!(
+        object.methods.keys().collect::<Vec<_>>(),
+        vec![
+            "TWMainStructChildCount",
+            "TWMainStructHasChildren",
+            "TWMainStructIsNamed",
+            "TWMainStructIsValid",
+        ]
+    );
+
+    let json = serde_json::to_value(&surface).unwrap();
+    let is_named = &json["objects"]["TWMainStruct"]["methods"]["TWMainStructIsNamed"];
+    assert_eq!(is_named["return"]["type"], "bool");
+    assert_eq!(is_named["params"][0]["type"], "TWMainStruct");
+    assert_eq!(is_named["params"][1]["type"], "TWString");
+    assert_eq!(is_named["params"][1]["is_nullable"], false);
+
+    let parsed: ApiSurface = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, surface);
+}
+
+#[test]
+fn surface_mismatches() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+    const OPTIONAL_INPUT: &str = include_str!("samples/optional.input.yaml");
+
+    // The stub backend does not support `uint32_t`, so it skips a method.
+    let swift = render_to_strings(create_intput(INPUT)).unwrap().surface;
+    let stub = stub_backend_surface(INPUT, &[TypeVariant::UInt32T]);
+    assert_eq!(
+        compare_surfaces(("swift", &swift), ("stub", &stub)),
+        vec![SurfaceMismatch::MissingMember {
+            object: "TWMainStruct".to_string(),
+            member: "TWMainStructChildCount".to_string(),
+            backend: "stub".to_string(),
+        }]
+    );
+
+    // The whole object is skipped if none of its members is supported.
+    let stub = stub_backend_surface(INPUT, &[TypeVariant::Struct("TWMainStruct".to_string())]);
+    let mismatches = compare_surfaces(("stub", &stub), ("swift", &swift));
+    assert_eq!(
+        mismatches,
+        vec![SurfaceMismatch::MissingObject {
+            object: "TWMainStruct".to_string(),
+            backend: "stub".to_string(),
+        }]
+    );
+    assert_eq!(
+        mismatches[0].to_string(),
+        "'TWMainStruct' is not rendered by stub"
+    );
+
+    // The nullability is a part of the surface.
+    let swift = render_to_strings(create_intput(OPTIONAL_INPUT))
+        .unwrap()
+        .surface;
+    let non_nullable = OPTIONAL_INPUT.replacen("is_nullable: true", "is_nullable: false", 1);
+    let stub = stub_backend_surface(&non_nullable, &[]);
+    assert_eq!(
+        compare_surfaces(("swift", &swift), ("stub", &stub)),
+        vec![SurfaceMismatch::Different {
+            object: "TWMainStruct".to_string(),
+            member: Some("TWMainStructCreate".to_string()),
+        }]
+    );
+}
+
+/// The Swift API surface of the given fixture.
+fn swift_surface(yaml: &str) -> ApiSurface {
+    render_to_strings(create_intput(yaml)).unwrap().surface
+}
+
+#[test]
+fn compat_unchanged() {
+    let mut surface = ApiSurface::default();
+    for fixture in FIXTURES {
+        surface.extend(swift_surface(fixture));
+    }
+    assert!(check_compatibility(&surface, &surface).is_empty());
+}
+
+#[test]
+fn compat_objects() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+    const ENUM_INPUT: &str = include_str!("samples/enum.input.yaml");
+
+    let baseline = swift_surface(INPUT);
+    let mut current = baseline.clone();
+    current.extend(swift_surface(ENUM_INPUT));
+
+    let changes = check_compatibility(&baseline, &current);
+    assert_eq!(
+        changes,
+        vec![ApiChange::AddedObject {
+            object: "TWMainEnum".to_string(),
+        }]
+    );
+    assert_eq!(changes[0].compatibility(), Compatibility::Additive);
+
+    // Swap the sides.
+    let changes = check_compatibility(&current, &baseline);
+    assert_eq!(
+        changes,
+        vec![ApiChange::RemovedObject {
+            object: "TWMainEnum".to_string(),
+        }]
+    );
+    assert_eq!(changes[0].compatibility(), Compatibility::Breaking);
+    assert_eq!(changes[0].to_string(), "'TWMainEnum' is removed");
+
+    // A class became a struct.
+    let mut current = baseline.clone();
+    current.objects.get_mut("TWMainStruct").unwrap().kind = ObjectKind::Struct;
+    let changes = check_compatibility(&baseline, &current);
+    assert_eq!(
+        changes,
+        vec![ApiChange::ChangedObjectKind {
+            object: "TWMainStruct".to_string(),
+        }]
+    );
+    assert!(changes[0].is_breaking());
+}
+
+#[test]
+fn compat_enum_variants() {
+    const INPUT: &str = include_str!("samples/enum.input.yaml");
+
+    let baseline = swift_surface(INPUT);
+    let mut current = baseline.clone();
+    let variants = &mut current.objects.get_mut("TWMainEnum").unwrap().variants;
+    variants.retain(|variant| variant != "two");
+    variants.push("four".to_string());
+
+    let changes = check_compatibility(&baseline, &current);
+    assert_eq!(
+        changes,
+        vec![
+            ApiChange::RemovedVariant {
+                object: "TWMainEnum".to_string(),
+                variant: "two".to_string(),
+            },
+            ApiChange::AddedVariant {
+                object: "TWMainEnum".to_string(),
+                variant: "four".to_string(),
+            },
+        ]
+    );
+    // A new case breaks the exhaustive `switch` statements too.
+    assert!(changes.iter().all(ApiChange::is_breaking));
+}
+
+#[test]
+fn compat_removed_and_renamed_members() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let baseline = swift_surface(INPUT);
+    let mut current = baseline.clone();
+    let methods = &mut current.objects.get_mut("TWMainStruct").unwrap().methods;
+    // Renamed, the signature is unique.
+    let is_named = methods.remove("TWMainStructIsNamed").unwrap();
+    methods.insert("TWMainStructHasName".to_string(), is_named);
+    // Removed.
+    methods.remove("TWMainStructChildCount").unwrap();
+
+    let changes = check_compatibility(&baseline, &current);
+    assert_eq!(
+        changes,
+        vec![
+            ApiChange::RemovedMember {
+                object: "TWMainStruct".to_string(),
+                kind: MemberKind::Method,
+                member: "TWMainStructChildCount".to_string(),
+                renamed_to: None,
+            },
+            ApiChange::RemovedMember {
+                object: "TWMainStruct".to_string(),
+                kind: MemberKind::Method,
+                member: "TWMainStructIsNamed".to_string(),
+                renamed_to: Some("TWMainStructHasName".to_string()),
+            },
+            ApiChange::AddedMember {
+                object: "TWMainStruct".to_string(),
+                kind: MemberKind::Method,
+                member: "TWMainStructHasName".to_string(),
+            },
+        ]
+    );
+    assert!(changes[0].is_breaking());
+    assert!(changes[1].is_breaking());
+    assert_eq!(
+        changes[1].to_string(),
+        "'TWMainStruct.TWMainStructIsNamed' is renamed to 'TWMainStructHasName'"
+    );
+    assert_eq!(changes[2].compatibility(), Compatibility::Additive);
+
+    // `TWMainStructIsValid` and `TWMainStructHasChildren` have the same signature,
+    // so the rename is ambiguous.
+    let mut current = baseline.clone();
+    let methods = &mut current.objects.get_mut("TWMainStruct").unwrap().methods;
+    let is_valid = methods.remove("TWMainStructIsValid").unwrap();
+    methods.insert("TWMainStructIsCorrect".to_string(), is_valid.clone());
+    methods.insert("TWMainStructIsProper".to_string(), is_valid);
+
+    let changes = check_compatibility(&baseline, &current);
+    assert_eq!(
+        changes[0],
+        ApiChange::RemovedMember {
+            object: "TWMainStruct".to_string(),
+            kind: MemberKind::Method,
+            member: "TWMainStructIsValid".to_string(),
+            renamed_to: None,
+        }
+    );
+}
+
+#[test]
+fn compat_changed_signature() {
+    const INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let baseline = swift_surface(INPUT);
+    let string = |is_nullable| TypeSurface {
+        ty: "TWString".to_string(),
+        is_nullable,
+    };
+
+    let changed = |f: &dyn Fn(&mut MemberSurface)| {
+        let mut current = baseline.clone();
+        let object = current.objects.get_mut("TWMainStruct").unwrap();
+        f(object.methods.get_mut("TWMainStructIsNamed").unwrap());
+        check_compatibility(&baseline, &current)
+    };
+
+    // The parameter became nullable.
+    let changes = changed(&|member| member.params[1].is_nullable = true);
+    assert_eq!(
+        changes,
+        vec![ApiChange::ChangedParam {
+            object: "TWMainStruct".to_string(),
+            member: "TWMainStructIsNamed".to_string(),
+            index: 1,
+            old: Some(string(false)),
+            new: Some(string(true)),
+        }]
+    );
+    assert_eq!(changes[0].compatibility(), Compatibility::Additive);
+    assert_eq!(
+        changes[0].to_string(),
+        "'TWMainStruct.TWMainStructIsNamed' parameter #1 changed from 'TWString' to 'TWString?'"
+    );
+
+    // The parameter type changed.
+    let changes = changed(&|member| member.params[1].ty = "TWData".to_string());
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].is_breaking());
+
+    // A parameter is added.
+    let changes = changed(&|member| member.params.push(string(true)));
+    assert_eq!(
+        changes,
+        vec![ApiChange::ChangedParam {
+            object: "TWMainStruct".to_string(),
+            member: "TWMainStructIsNamed".to_string(),
+            index: 2,
+            old: None,
+            new: Some(string(true)),
+        }]
+    );
+    assert!(changes[0].is_breaking());
+
+    // The return value became nullable.
+    let changes = changed(&|member| member.return_type.is_nullable = true);
+    assert_eq!(
+        changes,
+        vec![ApiChange::ChangedReturn {
+            object: "TWMainStruct".to_string(),
+            member: "TWMainStructIsNamed".to_string(),
+            old: TypeSurface {
+                ty: "bool".to_string(),
+                is_nullable: false,
+            },
+            new: TypeSurface {
+                ty: "bool".to_string(),
+                is_nullable: true,
+            },
+        }]
+    );
+    assert!(changes[0].is_breaking());
+
+    // The method became static.
+    let changes = changed(&|member| member.is_static = true);
+    assert_eq!(
+        changes,
+        vec![ApiChange::ChangedStatic {
+            object: "TWMainStruct".to_string(),
+            member: "TWMainStructIsNamed".to_string(),
+        }]
+    );
+    assert!(changes[0].is_breaking());
+}
+
+#[test]
+fn render_stats() {
+    const CLASS_INPUT: &str = include_str!("samples/class.input.yaml");
+    const ENUM_INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+    const PROTO_INPUT: &str = include_str!("samples/proto.input.yaml");
+
+    // The stats of a single manifest file.
+    let rendered = render_to_strings(create_intput(CLASS_INPUT)).unwrap();
+    assert_eq!(
+        rendered.stats,
+        RenderStats {
+            classes: 1,
+            inits: 1,
+            methods: 1,
+            properties: 1,
+            ..RenderStats::default()
+        }
+    );
+
+    let mut surface = ApiSurface::default();
+    for fixture in [CLASS_INPUT, ENUM_INPUT, PROTO_INPUT] {
+        surface.extend(swift_surface(fixture));
+    }
+    let stats = RenderStats::from_surface(&surface);
+    assert_eq!(stats.classes, 1);
+    assert_eq!(stats.structs, 0);
+    assert_eq!(stats.enums, 1);
+    assert_eq!(stats.enum_variants, 3);
+    assert_eq!(stats.inits, 1);
+    assert_eq!(stats.methods, 3);
+    assert_eq!(stats.properties, 1);
+    assert_eq!(stats.protos, 2);
+    assert_eq!(stats.variants_of("TWMainEnum"), Some(3));
+    assert_eq!(stats.variants_of("UnknownEnum"), None);
+}
+
+#[test]
+fn render_stats_table() {
+    const PREVIOUS_INPUT: &str = include_str!("samples/function_property.input.yaml");
+
+    let mut surface = ApiSurface::default();
+    for fixture in [
+        include_str!("samples/class.input.yaml"),
+        include_str!("samples/enum_extension.input.yaml"),
+        include_str!("samples/proto.input.yaml"),
+    ] {
+        surface.extend(swift_surface(fixture));
+    }
+    let stats = RenderStats::from_surface(&surface);
+    let previous = RenderStats::from_surface(&swift_surface(PREVIOUS_INPUT));
+
+    // Without the previous run.
+    assert_eq!(
+        stats.table(None).to_string(),
+        "\
+| Item          | Count |
+|---------------|-------|
+| Classes       |     1 |
+| Structs       |     0 |
+| Enums         |     1 |
+| Enum variants |     3 |
+| Inits         |     1 |
+| Methods       |     3 |
+| Properties    |     1 |
+| Protos        |     2 |
+"
+    );
+
+    // The previous run rendered one class with four methods.
+    let rows = stats.rows(Some(&previous));
+    assert_eq!(
+        rows[5],
+        StatsRow {
+            label: "Methods",
+            count: 3,
+            delta: Some(-1),
+        }
+    );
+    assert_eq!(
+        stats.table(Some(&previous)).to_string(),
+        "\
+| Item          | Count | Delta |
+|---------------|-------|-------|
+| Classes       |     1 |     0 |
+| Structs       |     0 |     0 |
+| Enums         |     1 |    +1 |
+| Enum variants |     3 |    +3 |
+| Inits         |     1 |    +1 |
+| Methods       |     3 |    -1 |
+| Properties    |     1 |    +1 |
+| Protos        |     2 |    +2 |
+"
+    );
+}
+
+#[test]
+fn stable_sort_ignores_manifest_order() {
+    for fixture in FIXTURES {
+        let input = create_intput(fixture);
+        let mut shuffled = create_intput(fixture);
+        let info = &mut shuffled.file_info;
+        info.structs.reverse();
+        info.inits.reverse();
+        info.enums.reverse();
+        info.functions.reverse();
+        info.properties.reverse();
+        info.protos.reverse();
+
+        assert_eq!(
+            render_to_strings(input).unwrap().into_files(),
+            render_to_strings(shuffled).unwrap().into_files()
+        );
+    }
+
+    // The manifest order is kept without sorting.
+    let mut input = create_intput(include_str!("samples/async.input.yaml"));
+    input.stable_sort = false;
+    let rendered = render_to_strings(input).unwrap();
+    let (_, swift) = &rendered.structs[0];
+    let sign = swift.find("public func sign(").unwrap();
+    let encode = swift.find("public func encode(").unwrap();
+    assert!(sign < encode);
+}
+
+/// Convenience function: creates a dev session of the class and enum extension fixtures.
+fn create_dev_session() -> (SwiftTemplates, DevSession) {
+    let input = create_intput(include_str!("samples/class.input.yaml"));
+    let templates = SwiftTemplates::from(&input);
+    let file_infos = vec![
+        input.file_info,
+        parse_str(include_str!("samples/enum_extension.input.yaml")).unwrap(),
+    ];
+    let session = DevSession::new(
+        templates.clone(),
+        NamingConfig::builtin(),
+        ExclusionConfig::builtin(),
+        ConformanceConfig::builtin(),
+        ProtoNamingConvention::default(),
+        file_infos,
+    )
+    .unwrap();
+    (templates, session)
+}
+
+#[test]
+fn dev_session_reload_templates() {
+    let (templates, mut session) = create_dev_session();
+    let files: Vec<String> = session
+        .files()
+        .keys()
+        .map(|path| path.to_string())
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "Enums/MainEnum.swift",
+            "Extensions/MainEnum+Extension.swift",
+            "Sources/Generated/MainStruct.swift"
+        ]
+    );
+    assert_eq!(
+        session.file("Sources/Generated/MainStruct.swift").unwrap(),
+        include_str!("samples/class.output.swift")
+    );
+
+    // The same templates don't change anything.
+    assert!(session
+        .reload_templates(templates.clone())
+        .unwrap()
+        .is_empty());
+
+    // Only the enums are re-rendered.
+    let mut modified = templates.clone();
+    modified.enum_template = format!("// Modified enum\n{}", templates.enum_template);
+    let changed = session.reload_templates(modified.clone()).unwrap();
+    assert_eq!(changed, ["Enums/MainEnum.swift"]);
+    assert!(session
+        .file("Enums/MainEnum.swift")
+        .unwrap()
+        .starts_with("// Modified enum\n"));
+    assert_eq!(
+        session.file("Sources/Generated/MainStruct.swift").unwrap(),
+        include_str!("samples/class.output.swift")
+    );
+
+    // The partials affect both the structs and the extensions, but not the enums.
+    let mut modified_partial = modified.clone();
+    modified_partial.partial_func_template =
+        format!("// Modified func\n{}", templates.partial_func_template);
+    let mut changed = session.reload_templates(modified_partial).unwrap();
+    changed.sort();
+    assert_eq!(
+        changed,
+        [
+            "Extensions/MainEnum+Extension.swift",
+            "Sources/Generated/MainStruct.swift"
+        ]
+    );
+    assert!(session
+        .file("Sources/Generated/MainStruct.swift")
+        .unwrap()
+        .contains("// Modified func\n"));
+
+    // Invalid templates leave the session unchanged.
+    let before: Vec<(String, String)> = session
+        .files()
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+    let mut invalid = modified;
+    invalid.struct_template = "{{#if}}".to_string();
+    session.reload_templates(invalid).unwrap_err();
+    let after: Vec<(String, String)> = session
+        .files()
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+    assert_eq!(before, after);
+
+    // The original templates restore the original outputs.
+    let mut changed = session.reload_templates(templates).unwrap();
+    changed.sort();
+    assert_eq!(
+        changed,
+        [
+            "Enums/MainEnum.swift",
+            "Extensions/MainEnum+Extension.swift",
+            "Sources/Generated/MainStruct.swift"
+        ]
+    );
+    assert_eq!(
+        session.file("Sources/Generated/MainStruct.swift").unwrap(),
+        include_str!("samples/class.output.swift")
+    );
+}
+
+#[test]
+fn dev_session_reload_manifest() {
+    let (_, mut session) = create_dev_session();
+
+    // The same manifest doesn't change anything.
+    let file_info = parse_str(include_str!("samples/class.input.yaml")).unwrap();
+    assert!(session.reload_manifest(file_info).unwrap().is_empty());
+
+    // The renamed object replaces the previous file.
+    let yaml = include_str!("samples/class.input.yaml").replace("MainStruct", "OtherStruct");
+    let changed = session.reload_manifest(parse_str(&yaml).unwrap()).unwrap();
+    assert_eq!(
+        changed,
+        [
+            "Sources/Generated/MainStruct.swift",
+            "Sources/Generated/OtherStruct.swift"
+        ]
+    );
+    assert!(session.file("Sources/Generated/MainStruct.swift").is_none());
+    assert!(session
+        .file("Sources/Generated/OtherStruct.swift")
+        .is_some());
+    // The other manifest is kept.
+    assert!(session.file("Enums/MainEnum.swift").is_some());
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init(bytes: Data) {
+        let bytes = [UInt8](bytes)
+        let result = TWMainStructCreateWithBytes(bytes,bytes.count)
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func verify(signatures: [Data], keys: [PublicKey]) -> Bool {
+        let obj = self.rawValue
+        let signaturesVector = TWDataVectorCreate()
+        defer {
+            TWDataVectorDelete(signaturesVector)
+        }
+        for item in signatures {
+            let data = TWDataCreateWithNSData(item)
+            defer { TWDataDelete(data) }
+            TWDataVectorAdd(signaturesVector, data)
+        }
+
+        let keys = keys.map { $0.rawValue }
+        let result = TWMainStructVerify(obj,signaturesVector,keys,keys.count)
+        return result
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func encode() -> String? {
+        let obj = self.rawValue
+        guard let result = TWMainStructEncode(obj) else {
+            return nil
+        }
+        return TWStringNSString(result)
+    }
+
+    public func sign(input: Data) -> Data {
+        let obj = self.rawValue
+        let input = TWDataCreateWithNSData(input)
+        defer {
+            TWDataDelete(input)
+        }
+
+        let result = TWMainStructSign(obj,input)
+        return TWDataNSData(result)
+    }
+
+    public func sign(input: Data) async -> Data {
+        return await withCheckedContinuation { continuation in
+            DispatchQueue.global(qos: .userInitiated).async {
+                continuation.resume(returning: self.sign(input: input))
+            }
+        }
+    }
+
+    public static func verify(message: Data, signature: String?) -> Bool {
+        let message = TWDataCreateWithNSData(message)
+        defer {
+            TWDataDelete(message)
+        }
+
+        let ptr: UnsafeRawPointer?
+        if let signature = signature {
+            ptr = TWStringCreateWithNSString(signature)
+        } else {
+            ptr = nil
+        }
+        defer {
+            if let signature  = ptr {
+                TWStringDelete(signature)
+            }
+        }
+        let signature = ptr
+
+        let result = TWMainStructVerify(message,signature)
+        return result
+    }
+
+    public static func verify(message: Data, signature: String?) async -> Bool {
+        return await withCheckedContinuation { continuation in
+            DispatchQueue.global(qos: .userInitiated).async {
+                continuation.resume(returning: self.verify(message: message, signature: signature))
+            }
+        }
+    }
+
+    public var isValid: Bool {
+        let obj = self.rawValue
+        let result = TWMainStructIsValid(obj)
+        return result
+    }
+}
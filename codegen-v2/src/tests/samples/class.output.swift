@@ -5,8 +5,6 @@
 // This is a GENERATED FILE, changes made here WILL BE LOST.
 //
 
-import Foundation
-
 public final class MainStruct {
     let rawValue: OpaquePointer
 
@@ -20,23 +18,23 @@ public final class MainStruct {
             TWStringDelete(string)
         }
 
-        let result = MainStructCreate(string)
+        let result = TWMainStructCreate(string)
 
         self.rawValue = result
     }
 
     deinit {
-        MainStructDelete(self.rawValue)
+        TWMainStructDelete(self.rawValue)
     }
 
     public static func firstFunction(first_param: Int32) -> Bool {
-        let result = MainStructFirstFunction(first_param)
+        let result = TWMainStructFirstFunction(first_param)
         return result
     }
 
     public var firstProperty: Bool {
         let obj = self.rawValue
-        let result = MainStructFirstProperty(obj)
+        let result = TWMainStructFirstProperty(obj)
         return result
     }
 }
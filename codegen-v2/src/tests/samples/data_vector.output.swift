@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public struct TransactionCompiler {
+    init() {}
+
+    public static func compileWithSignatures(coinType: CoinType, txInputData: Data, signatures: [Data], publicKeys: [Data]) -> Data {
+        let coinType = TWCoinType(rawValue: coinType.rawValue)
+        let txInputData = TWDataCreateWithNSData(txInputData)
+        defer {
+            TWDataDelete(txInputData)
+        }
+
+        let signaturesVector = TWDataVectorCreate()
+        defer {
+            TWDataVectorDelete(signaturesVector)
+        }
+        for item in signatures {
+            let data = TWDataCreateWithNSData(item)
+            defer { TWDataDelete(data) }
+            TWDataVectorAdd(signaturesVector, data)
+        }
+
+        let publicKeysVector = TWDataVectorCreate()
+        defer {
+            TWDataVectorDelete(publicKeysVector)
+        }
+        for item in publicKeys {
+            let data = TWDataCreateWithNSData(item)
+            defer { TWDataDelete(data) }
+            TWDataVectorAdd(publicKeysVector, data)
+        }
+
+        let result = TWTransactionCompilerCompileWithSignatures(coinType,txInputData,signaturesVector,publicKeysVector)
+        return TWDataNSData(result)
+    }
+
+    public static func preImageHashesList(coinType: CoinType, txInputData: Data) -> [Data]? {
+        let coinType = TWCoinType(rawValue: coinType.rawValue)
+        let txInputData = TWDataCreateWithNSData(txInputData)
+        defer {
+            TWDataDelete(txInputData)
+        }
+
+        guard let result = TWTransactionCompilerPreImageHashesList(coinType,txInputData) else {
+            return nil
+        }
+        defer {
+            TWDataVectorDelete(result)
+        }
+
+        return (0..<TWDataVectorSize(result)).map { TWDataNSData(TWDataVectorGet(result, $0)!) }
+    }
+}
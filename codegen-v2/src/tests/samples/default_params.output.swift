@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init(name: String, prefix: String? = nil) {
+        let name = TWStringCreateWithNSString(name)
+        defer {
+            TWStringDelete(name)
+        }
+
+        let ptr: UnsafeRawPointer?
+        if let prefix = prefix {
+            ptr = TWStringCreateWithNSString(prefix)
+        } else {
+            ptr = nil
+        }
+        defer {
+            if let prefix  = ptr {
+                TWStringDelete(prefix)
+            }
+        }
+        let prefix = ptr
+
+        let result = TWMainStructCreate(name,prefix)
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func sign(data: Data, curve: Curve = .secp256k1) -> Data {
+        let obj = self.rawValue
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        let curve = TWCurve(rawValue: curve.rawValue)
+        let result = TWMainStructSign(obj,data,curve)
+        return TWDataNSData(result)
+    }
+
+    public var firstProperty: Bool {
+        let obj = self.rawValue
+        let result = TWMainStructFirstProperty(obj)
+        return result
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init(name: String) {
+        let name = TWStringCreateWithNSString(name)
+        defer {
+            TWStringDelete(name)
+        }
+
+        let result = TWMainStructCreate(name)
+
+        self.rawValue = result
+    }
+
+    @available(*, deprecated, message: "Use init(name:) instead")
+    public init?(data: Data) {
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        guard let result = TWMainStructCreateWithData(data) else {
+            return nil
+        }
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func sign(data: Data) -> Data {
+        let obj = self.rawValue
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        let result = TWMainStructSign(obj,data)
+        return TWDataNSData(result)
+    }
+
+    @available(*, deprecated, message: "Use sign(data:) instead")
+    public func signLegacy(data: Data) -> Data {
+        let obj = self.rawValue
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        let result = TWMainStructSignLegacy(obj,data)
+        return TWDataNSData(result)
+    }
+
+    public var firstProperty: Bool {
+        let obj = self.rawValue
+        let result = TWMainStructFirstProperty(obj)
+        return result
+    }
+}
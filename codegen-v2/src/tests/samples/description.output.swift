@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct: CustomStringConvertible, CustomDebugStringConvertible {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public var debugDescription: String {
+        return description
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public var description: String {
+        let obj = self.rawValue
+        let result = TWMainStructDescription(obj)
+        defer {
+            TWStringDelete(result)
+        }
+
+        return String(utf8String: TWStringUTF8Bytes(result))!
+    }
+
+    public var name: String {
+        let obj = self.rawValue
+        let result = TWMainStructName(obj)
+        return TWStringNSString(result)
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+extension CharEnum {
+    init?(rawValue: CChar) {
+        guard let rawValue = UInt8(exactly: rawValue) else {
+            return nil
+        }
+        self.init(rawValue: rawValue)
+    }
+
+    public static func firstFunction(first_param: Int32) -> Bool {
+        let result = TWCharEnumFirstFunction(first_param)
+        return result
+    }
+}
+
+extension TWCharEnum {
+    init(rawValue: UInt8) {
+        self.init(rawValue: CChar(truncatingIfNeeded: rawValue))
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public enum MainEnum: UInt32, CaseIterable, CustomStringConvertible, Codable {
+    case `one` = 0
+    case `two` = 1
+    case `three` = 2
+
+    public var description: String {
+        switch self {
+            case .one: return "one_string"
+            case .two: return "two_string"
+            case .three: return "three_string"
+        }
+    }
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        // The legacy payloads are encoded by the raw values.
+        guard let string = try? container.decode(String.self) else {
+            let rawValue = try container.decode(RawValue.self)
+            guard let value = MainEnum(rawValue: rawValue) else {
+                throw DecodingError.dataCorruptedError(in: container, debugDescription: "Invalid MainEnum raw value: \(rawValue)")
+            }
+            self = value
+            return
+        }
+        switch string {
+            case "one_string": self = .one
+            case "two_string": self = .two
+            case "three_string": self = .three
+            default: throw DecodingError.dataCorruptedError(in: container, debugDescription: "Invalid MainEnum string: \(string)")
+        }
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        try container.encode(description)
+    }
+}
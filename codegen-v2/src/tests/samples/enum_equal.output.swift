@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+extension SomeEnum {
+    public static func == (lhs: SomeEnum, rhs: SomeEnum) -> Bool {
+        return TWSomeEnumEqual(TWSomeEnum(rawValue: lhs.rawValue), TWSomeEnum(rawValue: rhs.rawValue))
+    }
+
+    public var isTestnet: Bool {
+        let obj = TWSomeEnum(rawValue: self.rawValue)
+        let result = TWSomeEnumIsTestnet(obj)
+        return result
+    }
+}
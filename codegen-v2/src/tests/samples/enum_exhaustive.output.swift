@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public var kind: MainEnum {
+        let obj = self.rawValue
+        let result = TWMainStructKind(obj)
+        return MainEnum(rawValue: result.rawValue)!
+    }
+
+    public var optionalKind: OtherEnum? {
+        let obj = self.rawValue
+        guard let result = TWMainStructOptionalKind(obj) else {
+            return nil
+        }
+        return OtherEnum(rawValue: result.rawValue)
+    }
+
+    public var otherKind: OtherEnum {
+        let obj = self.rawValue
+        let result = TWMainStructOtherKind(obj)
+        guard let value = OtherEnum(rawValue: result.rawValue) else {
+            fatalError("unexpected enum raw value")
+        }
+        return value
+    }
+}
@@ -7,13 +7,13 @@
 
 extension MainEnum {
     public static func firstFunction(first_param: Int32) -> Bool {
-        let result = MainEnumFirstFunction(first_param)
+        let result = TWMainEnumFirstFunction(first_param)
         return result
     }
 
     public static func secondFunction(first_param: SomeStruct) -> Bool {
         let first_param = first_param.rawValue
-        let result = MainEnumSecondFunction(first_param)
+        let result = TWMainEnumSecondFunction(first_param)
         return result
     }
 }
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+extension MainEnum {
+    public init(string: String) {
+        let string = TWStringCreateWithNSString(string)
+        defer {
+            TWStringDelete(string)
+        }
+
+        let result = TWMainEnumCreate(string)
+
+        self.init(rawValue: result.rawValue)!
+    }
+
+    public init?(value: Int32) {
+        guard let result = TWMainEnumCreateWithValue(value) else {
+            return nil
+        }
+
+        self.init(rawValue: result.rawValue)
+    }
+
+    public static func firstFunction(first_param: Int32) -> Bool {
+        let result = TWMainEnumFirstFunction(first_param)
+        return result
+    }
+}
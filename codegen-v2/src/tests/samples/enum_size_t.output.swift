@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public enum SizeTEnum: UInt32, CaseIterable {
+    case `one` = 0
+    case `two` = 1
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+extension SizeTEnum {
+    init?(rawValue: Int) {
+        guard let rawValue = UInt32(exactly: rawValue) else {
+            return nil
+        }
+        self.init(rawValue: rawValue)
+    }
+}
+
+extension TWSizeTEnum {
+    init(rawValue: UInt32) {
+        self.init(rawValue: Int(truncatingIfNeeded: rawValue))
+    }
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func hasChildren() -> Bool {
+        let obj = self.rawValue
+        let result = TWMainStructHasChildren(obj)
+        return result
+    }
+
+    public func isNamed(name: String) -> Bool {
+        let obj = self.rawValue
+        let name = TWStringCreateWithNSString(name)
+        defer {
+            TWStringDelete(name)
+        }
+
+        let result = TWMainStructIsNamed(obj,name)
+        return result
+    }
+
+    public var childCount: UInt32 {
+        let obj = self.rawValue
+        let result = TWMainStructChildCount(obj)
+        return result
+    }
+
+    public var isValid: Bool {
+        let obj = self.rawValue
+        let result = TWMainStructIsValid(obj)
+        return result
+    }
+}
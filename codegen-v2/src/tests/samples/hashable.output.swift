@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct: Hashable {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public static func == (lhs: MainStruct, rhs: MainStruct) -> Bool {
+        return TWMainStructEqual(lhs.rawValue, rhs.rawValue)
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        let result = TWMainStructHash(self.rawValue)
+        hasher.combine(TWDataNSData(result))
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func size() -> UInt32 {
+        let obj = self.rawValue
+        let result = TWMainStructSize(obj)
+        return result
+    }
+}
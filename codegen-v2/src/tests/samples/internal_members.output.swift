@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init() {
+        let result = TWMainStructCreate()
+
+        self.rawValue = result
+    }
+
+    internal init(seed: UInt32) {
+        let result = TWMainStructCreateForTesting(seed)
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    internal static func resetCache() -> Bool {
+        let result = TWMainStructResetCache()
+        return result
+    }
+
+    public func sign() -> Bool {
+        let obj = self.rawValue
+        let result = TWMainStructSign(obj)
+        return result
+    }
+
+    internal var internalState: UInt32 {
+        let obj = self.rawValue
+        let result = TWMainStructInternalState(obj)
+        return result
+    }
+}
@@ -15,7 +15,20 @@ public final class MainStruct {
     }
 
     public init?(string: String?) {
-        guard let result = MainStructCreate(string) else {
+        let ptr: UnsafeRawPointer?
+        if let string = string {
+            ptr = TWStringCreateWithNSString(string)
+        } else {
+            ptr = nil
+        }
+        defer {
+            if let string  = ptr {
+                TWStringDelete(string)
+            }
+        }
+        let string = ptr
+
+        guard let result = TWMainStructCreate(string) else {
             return nil
         }
 
@@ -23,19 +36,52 @@ public final class MainStruct {
     }
 
     deinit {
-        MainStructDelete(self.rawValue)
+        TWMainStructDelete(self.rawValue)
     }
 
-    public static func withOptionalInt(first_param: Int32?) -> Bool? {
-        guard let result = MainStructWithOptionalInt(first_param) else {
+    public static func decode(data: Data) -> MainStruct? {
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        guard let result = TWMainStructDecode(data) else {
+            return nil
+        }
+        return MainStruct(rawValue: result)
+    }
+
+    public static func optionalData() -> Data? {
+        guard let result = TWMainStructOptionalData() else {
+            return nil
+        }
+        return TWDataNSData(result)
+    }
+
+    public static func optionalEnum() -> SomeEnum? {
+        guard let result = TWMainStructOptionalEnum() else {
+            return nil
+        }
+        return SomeEnum(rawValue: result.rawValue)
+    }
+
+    public static func optionalStruct() -> SomeStruct? {
+        guard let result = TWMainStructOptionalStruct() else {
+            return nil
+        }
+        return SomeStruct(rawValue: result)
+    }
+
+    public static func withOptionalEnum(first_param: SomeEnum?) -> Bool? {
+        let first_param = TWSomeEnum(rawValue: first_param.rawValue)
+        guard let result = TWMainStructWithOptionalEnum(first_param) else {
             return nil
         }
         return result
     }
 
-    public static func withOptionalStruct(first_param: SomeStruct?) -> Bool? {
-        let first_param = first_param?.rawValue
-        guard let result = MainStructWithOptionalStruct(first_param) else {
+    public static func withOptionalInt(first_param: Int32?) -> Bool? {
+        guard let result = TWMainStructWithOptionalInt(first_param) else {
             return nil
         }
         return result
@@ -55,23 +101,39 @@ public final class MainStruct {
         }
         let first_param = ptr
 
-        guard let result = MainStructWithOptionalString(first_param) else {
+        guard let result = TWMainStructWithOptionalString(first_param) else {
             return nil
         }
         return result
     }
 
-    public static func withOptionalEnum(first_param: SomeEnum?) -> Bool? {
-        let first_param = SomeEnum(rawValue: first_param.rawValue)
-        guard let result = MainStructWithOptionalEnum(first_param) else {
+    public static func withOptionalStruct(first_param: SomeStruct?) -> Bool? {
+        let first_param = first_param?.rawValue
+        guard let result = TWMainStructWithOptionalStruct(first_param) else {
             return nil
         }
         return result
     }
 
+    public var withOptionalData: Data? {
+        let obj = self.rawValue
+        guard let result = TWMainStructWithOptionalData(obj) else {
+            return nil
+        }
+        return TWDataNSData(result)
+    }
+
+    public var withOptionalEnum: SomeEnum? {
+        let obj = self.rawValue
+        guard let result = TWMainStructWithOptionalEnum(obj) else {
+            return nil
+        }
+        return SomeEnum(rawValue: result.rawValue)
+    }
+
     public var withOptionalInt: Int32? {
         let obj = self.rawValue
-        guard let result = MainStructWithOptionalInt(obj) else {
+        guard let result = TWMainStructWithOptionalInt(obj) else {
             return nil
         }
         return result
@@ -79,7 +141,7 @@ public final class MainStruct {
 
     public var withOptionalString: String? {
         let obj = self.rawValue
-        guard let result = MainStructWithOptionalString(obj) else {
+        guard let result = TWMainStructWithOptionalString(obj) else {
             return nil
         }
         return TWStringNSString(result)
@@ -87,17 +149,9 @@ public final class MainStruct {
 
     public var withOptionalStruct: SomeStruct? {
         let obj = self.rawValue
-        guard let result = MainStructWithOptionalStruct(obj) else {
+        guard let result = TWMainStructWithOptionalStruct(obj) else {
             return nil
         }
         return SomeStruct(rawValue: result)
     }
-
-    public var withOptionalEnum: SomeEnum? {
-        let obj = self.rawValue
-        guard let result = MainStructWithOptionalEnum(obj) else {
-            return nil
-        }
-        return SomeEnum(rawValue: result.rawValue)!
-    }
 }
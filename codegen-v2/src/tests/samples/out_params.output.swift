@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func childCount() -> UInt32? {
+        let obj = self.rawValue
+        var count = UInt32()
+        guard TWMainStructChildCount(obj,&count) else {
+            return nil
+        }
+        return count
+    }
+
+    public static func decode(input: String) -> Data? {
+        let input = TWStringCreateWithNSString(input)
+        defer {
+            TWStringDelete(input)
+        }
+
+        let output = TWDataCreateWithSize(0)
+        defer {
+            TWDataDelete(output)
+        }
+
+        guard TWMainStructDecode(input,output) else {
+            return nil
+        }
+        return Data(bytes: TWDataBytes(output), count: TWDataSize(output))
+    }
+
+    public func get() -> Data? {
+        let obj = self.rawValue
+        let result = TWDataCreateWithSize(0)
+        defer {
+            TWDataDelete(result)
+        }
+
+        guard TWMainStructGet(obj,result) else {
+            return nil
+        }
+        return Data(bytes: TWDataBytes(result), count: TWDataSize(result))
+    }
+}
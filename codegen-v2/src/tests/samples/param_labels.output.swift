@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init?(_ string: String) {
+        let string = TWStringCreateWithNSString(string)
+        defer {
+            TWStringDelete(string)
+        }
+
+        guard let result = TWMainStructCreateWithString(string) else {
+            return nil
+        }
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func derive(_ path: String, with data: Data, index: Int32) -> Data {
+        let obj = self.rawValue
+        let path = TWStringCreateWithNSString(path)
+        defer {
+            TWStringDelete(path)
+        }
+
+        let data = TWDataCreateWithNSData(data)
+        defer {
+            TWDataDelete(data)
+        }
+
+        let result = TWMainStructDerive(obj,path,data,index)
+        return TWDataNSData(result)
+    }
+}
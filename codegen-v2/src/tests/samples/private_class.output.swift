@@ -5,8 +5,6 @@
 // This is a GENERATED FILE, changes made here WILL BE LOST.
 //
 
-import Foundation
-
 final class MainStruct {
     let rawValue: OpaquePointer
 
@@ -14,29 +12,29 @@ final class MainStruct {
         self.rawValue = rawValue
     }
 
-    init(string: String) {
+    internal init(string: String) {
         let string = TWStringCreateWithNSString(string)
         defer {
             TWStringDelete(string)
         }
 
-        let result = MainStructCreate(string)
+        let result = TWMainStructCreate(string)
 
         self.rawValue = result
     }
 
     deinit {
-        MainStructDelete(self.rawValue)
+        TWMainStructDelete(self.rawValue)
     }
 
-    static func firstFunction(first_param: Int32) -> Bool {
-        let result = MainStructFirstFunction(first_param)
+    internal static func firstFunction(first_param: Int32) -> Bool {
+        let result = TWMainStructFirstFunction(first_param)
         return result
     }
 
-    var firstProperty: Bool {
+    internal var firstProperty: Bool {
         let obj = self.rawValue
-        let result = MainStructFirstProperty(obj)
+        let result = TWMainStructFirstProperty(obj)
         return result
     }
 }
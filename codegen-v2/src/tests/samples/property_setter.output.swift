@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func setLabel(label: String) -> Void {
+        let obj = self.rawValue
+        let label = TWStringCreateWithNSString(label)
+        defer {
+            TWStringDelete(label)
+        }
+
+        let result = TWMainStructSetLabel(obj,label)
+        return result
+    }
+
+    public var kind: SomeEnum {
+        get {
+            let obj = self.rawValue
+            let result = TWMainStructKind(obj)
+            guard let value = SomeEnum(rawValue: result.rawValue) else {
+                fatalError("unexpected enum raw value")
+            }
+            return value
+        }
+        set {
+            let obj = self.rawValue
+            let newValue = TWSomeEnum(rawValue: newValue.rawValue)
+            TWMainStructSetKind(obj, newValue)
+        }
+    }
+
+    public var name: String {
+        get {
+            let obj = self.rawValue
+            let result = TWMainStructName(obj)
+            return TWStringNSString(result)
+        }
+        set {
+            let obj = self.rawValue
+            let newValue = TWStringCreateWithNSString(newValue)
+            defer {
+                TWStringDelete(newValue)
+            }
+
+            TWMainStructSetName(obj, newValue)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+import SwiftProtobuf
+
+public typealias BitcoinSigningInput = TW_Bitcoin_Proto_SigningInput
+public typealias BitcoinTransaction = TW_Bitcoin_Proto_Transaction
+
+extension BitcoinSigningInput {
+    public static func with(_ builder: (inout BitcoinSigningInput) throws -> Void) rethrows -> BitcoinSigningInput {
+        var message = BitcoinSigningInput()
+        try builder(&message)
+        return message
+    }
+
+    public func serializedDataOrThrow() throws -> Data {
+        do {
+            return try serializedData()
+        } catch {
+            throw ProtoSerializationError(error)
+        }
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public enum ProtoRegistry {
+    public enum Bitcoin {
+        public typealias SigningInput = TW_Bitcoin_Proto_SigningInput
+        public typealias Transaction = TW_Bitcoin_Proto_Transaction
+    }
+
+    public enum EthereumAbi {
+        public typealias ContractCall = TW_EthereumAbi_Proto_ContractCall
+        public typealias ParamValue = TW_EthereumAbi_Proto_Param_Value
+    }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func description() -> String {
+        let obj = self.rawValue
+        let result = TWMainStructDescription(obj)
+        defer {
+            TWStringDelete(result)
+        }
+
+        return String(utf8String: TWStringUTF8Bytes(result))!
+    }
+
+    public func encoded() -> Data? {
+        let obj = self.rawValue
+        guard let result = TWMainStructEncoded(obj) else {
+            return nil
+        }
+        defer {
+            TWDataDelete(result)
+        }
+
+        return Data(bytes: TWDataBytes(result), count: TWDataSize(result))
+    }
+
+    public func rawBytes() -> Data {
+        let obj = self.rawValue
+        let result = TWMainStructRawBytes(obj)
+        return Data(bytes: TWDataBytes(result), count: TWDataSize(result))
+    }
+
+    public var label: String {
+        let obj = self.rawValue
+        let result = TWMainStructLabel(obj)
+        return TWStringNSString(result)
+    }
+
+    public var name: String {
+        let obj = self.rawValue
+        let result = TWMainStructName(obj)
+        defer {
+            TWStringDelete(result)
+        }
+
+        return String(utf8String: TWStringUTF8Bytes(result))!
+    }
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func addingChild(child: UInt32) -> MainStruct {
+        let obj = self.rawValue
+        let result = TWMainStructAddingChild(obj,child)
+        return MainStruct(rawValue: result)
+    }
+
+    public func settingName(name: String) -> MainStruct {
+        let obj = self.rawValue
+        let name = TWStringCreateWithNSString(name)
+        defer {
+            TWStringDelete(name)
+        }
+
+        let _ = TWMainStructSettingName(obj,name)
+        return self
+    }
+
+    public func trimmed() -> MainStruct? {
+        let obj = self.rawValue
+        guard let _ = TWMainStructTrimmed(obj) else {
+            return nil
+        }
+        return self
+    }
+
+    public var childCount: UInt32 {
+        let obj = self.rawValue
+        let result = TWMainStructChildCount(obj)
+        return result
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func combine(other: MainStruct) -> Data {
+        let obj = self.rawValue
+        let other = other.rawValue
+        let result = TWMainStructCombine(obj,other)
+        return TWDataNSData(result)
+    }
+
+    public static func distance(first: MainStruct, second: MainStruct?) -> UInt32 {
+        let first = first.rawValue
+        let second = second?.rawValue
+        let result = TWMainStructDistance(first,second)
+        return result
+    }
+}
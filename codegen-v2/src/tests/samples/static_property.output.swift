@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public var name: String {
+        let obj = self.rawValue
+        let result = TWMainStructName(obj)
+        return TWStringNSString(result)
+    }
+
+    public static var shared: MainStruct {
+        let result = TWMainStructShared()
+        return MainStruct(rawValue: result)
+    }
+
+    public static var version: String? {
+        guard let result = TWMainStructVersion() else {
+            return nil
+        }
+        return TWStringNSString(result)
+    }
+}
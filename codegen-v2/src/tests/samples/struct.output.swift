@@ -5,8 +5,6 @@
 // This is a GENERATED FILE, changes made here WILL BE LOST.
 //
 
-import Foundation
-
 public struct MainStruct {
     init() {}
 
@@ -16,23 +14,23 @@ public struct MainStruct {
             TWStringDelete(string)
         }
 
-        let result = MainStructCreate(string)
+        let result = TWMainStructCreate(string)
 
         self.rawValue = result
     }
 
     deinit {
-        MainStructDelete(self.rawValue)
+        TWMainStructDelete(self.rawValue)
     }
 
     public static func firstFunction(first_param: Int32) -> Bool {
-        let result = MainStructFirstFunction(first_param)
+        let result = TWMainStructFirstFunction(first_param)
         return result
     }
 
     public var firstProperty: Bool {
         let obj = self.rawValue
-        let result = MainStructFirstProperty(obj)
+        let result = TWMainStructFirstProperty(obj)
         return result
     }
 }
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public init(password: Data, name: String?) throws {
+        let password = TWDataCreateWithNSData(password)
+        defer {
+            TWDataDelete(password)
+        }
+
+        let ptr: UnsafeRawPointer?
+        if let name = name {
+            ptr = TWStringCreateWithNSString(name)
+        } else {
+            ptr = nil
+        }
+        defer {
+            if let name  = ptr {
+                TWStringDelete(name)
+            }
+        }
+        let name = ptr
+
+        guard let result = TWMainStructCreate(password,name) else {
+            throw TWError.invalidInput
+        }
+
+        self.rawValue = result
+    }
+
+    public init?(name: String) {
+        let name = TWStringCreateWithNSString(name)
+        defer {
+            TWStringDelete(name)
+        }
+
+        guard let result = TWMainStructCreateWithName(name) else {
+            return nil
+        }
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public var firstProperty: Bool {
+        let obj = self.rawValue
+        let result = TWMainStructFirstProperty(obj)
+        return result
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public final class MainStruct {
+    let rawValue: OpaquePointer
+
+    init(rawValue: OpaquePointer) {
+        self.rawValue = rawValue
+    }
+
+    public static func == (lhs: MainStruct, rhs: MainStruct) -> Bool {
+        return TWMainStructEqual(lhs.rawValue, rhs.rawValue)
+    }
+
+    public init?(string: String) {
+        let string = TWStringCreateWithNSString(string)
+        defer {
+            TWStringDelete(string)
+        }
+
+        guard let result = TWMainStructCreate(string) else {
+            return nil
+        }
+
+        self.rawValue = result
+    }
+
+    deinit {
+        TWMainStructDelete(self.rawValue)
+    }
+
+    public func sign(digest: Data, kind: MainEnum) -> Data? {
+        let obj = self.rawValue
+        let digest = TWDataCreateWithNSData(digest)
+        defer {
+            TWDataDelete(digest)
+        }
+
+        let kind = TWMainEnum(rawValue: kind.rawValue)
+        guard let result = TWMainStructSign(obj,digest,kind) else {
+            return nil
+        }
+        return TWDataNSData(result)
+    }
+
+    public var name: String {
+        let obj = self.rawValue
+        let result = TWMainStructName(obj)
+        return TWStringNSString(result)
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Golden-file tests of the rendered Swift bindings.
+//!
+//! The expected files live in `tests/fixtures/swift/golden`. Run the tests
+//! with `UPDATE_GOLDEN=1` to regenerate them after a deliberate template change,
+//! and review the result with `git diff`.
+
+use libparser::codegen::swift::render_file_info;
+use libparser::manifest::parse_str;
+use similar::TextDiff;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = include_str!("fixtures/swift/golden.input.yaml");
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/swift/golden")
+}
+
+fn update_golden() -> bool {
+    std::env::var("UPDATE_GOLDEN").is_ok_and(|value| value == "1")
+}
+
+/// Collects the golden files under `dir` by their path relative to `root`.
+fn collect_golden_files(root: &Path, dir: &Path, out: &mut BTreeSet<String>) {
+    if !dir.exists() {
+        return;
+    }
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_golden_files(root, &path, out);
+            continue;
+        }
+        let relative = path
+            .strip_prefix(root)
+            .unwrap()
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        out.insert(relative);
+    }
+}
+
+fn unified_diff(path: &str, expected: &str, rendered: &str) -> String {
+    TextDiff::from_lines(expected, rendered)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("golden/{path}"), &format!("rendered/{path}"))
+        .to_string()
+}
+
+#[test]
+fn swift_codegen_matches_golden_files() {
+    let file_info = parse_str(MANIFEST).unwrap();
+    let rendered = render_file_info(file_info).unwrap();
+    assert!(rendered.warnings.is_empty(), "{:?}", rendered.warnings);
+
+    let golden_dir = golden_dir();
+    let mut stale = BTreeSet::new();
+    collect_golden_files(&golden_dir, &golden_dir, &mut stale);
+
+    let mut failures = Vec::new();
+    for (path, content) in rendered.into_files() {
+        stale.remove(&path);
+        let golden_path = golden_dir.join(&path);
+
+        if update_golden() {
+            fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+            fs::write(&golden_path, &content).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&golden_path) {
+            Ok(expected) if expected == content => (),
+            Ok(expected) => failures.push(unified_diff(&path, &expected, &content)),
+            Err(_) => failures.push(format!("Missing golden file: {path}\n")),
+        }
+    }
+
+    for path in stale {
+        if update_golden() {
+            fs::remove_file(golden_dir.join(&path)).unwrap();
+        } else {
+            failures.push(format!("Golden file is not rendered anymore: {path}\n"));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "The rendered Swift bindings differ from the golden files, \
+        run with `UPDATE_GOLDEN=1` to regenerate them:\n\n{}",
+        failures.join("\n")
+    );
+}
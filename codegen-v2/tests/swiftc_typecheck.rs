@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Compiles the Swift bindings rendered from the test manifests with `swiftc`.
+//!
+//! The golden-file tests catch textual drift, this test catches the Swift
+//! errors introduced by a template change, e.g. a nullable struct passed to a
+//! non-nullable C parameter or a value returned from a `Void` function. Each
+//! manifest is rendered into its own directory, together with the stub of its
+//! C FFI declarations, see `render_ffi_stub`, and type-checked with
+//! `swiftc -typecheck`.
+//!
+//! The test is skipped unless `swiftc` is on `PATH`. Set `SWIFTC` to the path
+//! of the compiler to use another one, the test fails if it can't be run.
+
+use libparser::codegen::swift::{render_ffi_stub, render_file_info};
+use libparser::manifest::parse_str;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const STUB_FILE: &str = "FFIStub.swift";
+
+/// The compiler to use, `None` if the test should be skipped.
+fn swiftc() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("SWIFTC") {
+        return Some(PathBuf::from(path));
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join("swiftc"))
+        .find(|path| path.is_file())
+}
+
+/// The manifests of the unit and the golden-file tests.
+fn manifests() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut manifests: Vec<PathBuf> = fs::read_dir(root.join("src/tests/samples"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".input.yaml"))
+        .collect();
+    manifests.sort();
+    manifests.push(root.join("tests/fixtures/swift/golden.input.yaml"));
+    manifests
+}
+
+/// Writes the bindings of the `manifest` and the stub into `dir`, returning
+/// the written paths.
+fn write_bindings(manifest: &Path, dir: &Path) -> Vec<PathBuf> {
+    let file_info = parse_str(&fs::read_to_string(manifest).unwrap()).unwrap();
+    let stub = render_ffi_stub(&file_info);
+    let rendered = render_file_info(file_info).unwrap();
+
+    let files = rendered
+        .into_files()
+        .into_iter()
+        .chain(std::iter::once((STUB_FILE.to_string(), stub)));
+    let mut paths = Vec::new();
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        paths.push(path);
+    }
+    paths
+}
+
+#[test]
+fn swiftc_typechecks_rendered_bindings() {
+    let Some(swiftc) = swiftc() else {
+        eprintln!("`swiftc` is not on PATH and `SWIFTC` is not set, skipping");
+        return;
+    };
+
+    let out_dir = std::env::temp_dir().join(format!("codegen-swiftc-{}", std::process::id()));
+    let mut failures = Vec::new();
+    for manifest in manifests() {
+        let name = manifest.file_name().unwrap().to_string_lossy().to_string();
+        let dir = out_dir.join(name.trim_end_matches(".input.yaml"));
+        let paths = write_bindings(&manifest, &dir);
+
+        let output = Command::new(&swiftc)
+            .arg("-typecheck")
+            .args(&paths)
+            .output()
+            .unwrap_or_else(|err| panic!("Cannot run '{}': {err}", swiftc.display()));
+        if !output.status.success() {
+            failures.push(format!(
+                "{name}:\n{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+    let _ = fs::remove_dir_all(&out_dir);
+
+    assert!(
+        failures.is_empty(),
+        "`swiftc -typecheck` rejected the rendered bindings:\n\n{}",
+        failures.join("\n")
+    );
+}
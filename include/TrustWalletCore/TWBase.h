@@ -42,6 +42,12 @@
 // Marker for discardable result (static) method
 #define TW_METHOD_DISCARDABLE_RESULT
 
+// Marker for nullable constructors to be exposed as throwing initializers
+#define TW_THROWS
+
+// Marker for blocking methods to be exposed with an additional async variant
+#define TW_EXPORT_ASYNC
+
 // Marker for Protobuf types to be serialized across the interface
 #define PROTO(x) TWData *
 
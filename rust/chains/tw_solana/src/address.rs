@@ -121,9 +121,11 @@ impl SolanaAddress {
             .expect("sha256 must return 32 bytes");
 
         // The given hash (aka new public key) must not be on the ed25519 elliptic curve.
-        match ed25519::sha512::PublicKey::try_from(hash.as_slice()) {
-            Ok(_) => Ok(None),
-            Err(_) => Ok(Some(SolanaAddress::with_public_key_bytes(hash))),
+        // Please note that `PublicKey::try_from` rejects non-canonical and small order points too.
+        if ed25519::sha512::PublicKey::is_on_curve(&hash) {
+            Ok(None)
+        } else {
+            Ok(Some(SolanaAddress::with_public_key_bytes(hash)))
         }
     }
 }
@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::str::FromStr;
+use tw_utxo::address::batch::{AddressBatch, AddressTemplate, PARALLEL_THRESHOLD};
+use tw_utxo::chain_params::ChainParams;
+
+/// BIP84 test vector, account `m/84'/0'/0'` of the "abandon ... about" mnemonic.
+const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+const COUNT: u32 = 10_000;
+
+fn p2wpkh_batch() -> AddressBatch {
+    let template = AddressTemplate::from_str("wpkh(0/*)").unwrap();
+    AddressBatch::new(ZPUB, template, ChainParams::bitcoin_mainnet()).unwrap()
+}
+
+/// Derives the range by chunks shorter than [`PARALLEL_THRESHOLD`],
+/// so every chunk is derived on the current thread.
+fn derive_sequentially(batch: &AddressBatch) -> usize {
+    let chunk_size = PARALLEL_THRESHOLD as u32 - 1;
+    (0..COUNT)
+        .step_by(chunk_size as usize)
+        .map(|start| {
+            let end = (start + chunk_size).min(COUNT);
+            batch.derive_range(start..end).unwrap().len()
+        })
+        .sum()
+}
+
+fn address_batch_benchmark(c: &mut Criterion) {
+    let batch = p2wpkh_batch();
+
+    let mut group = c.benchmark_group("address_batch_10000_p2wpkh");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| b.iter(|| derive_sequentially(&batch)));
+    group.bench_function("derive_range", |b| {
+        b.iter(|| batch.derive_range(0..COUNT).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, address_batch_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tw_encoding::hex;
+use tw_keypair::ecdsa;
+use tw_utxo::modules::keys_manager::KeysManager;
+use tw_utxo::modules::sighash_computer::SighashComputer;
+use tw_utxo::modules::signer_metrics::NoopSignerMetrics;
+use tw_utxo::modules::tx_compiler::TxCompiler;
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::test_utils::fixtures::{PRIVATE_KEY, TXID};
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const COUNT: u32 = 500;
+
+/// Builds a transaction sweeping `COUNT` P2WPKH inputs of the same key.
+fn sweep_tx() -> UnsignedTransaction<Transaction> {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let mut builder = TransactionBuilder::new();
+    for index in 0..COUNT {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(index)
+            .amount(10_000)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&public)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder.push_output(OutputBuilder::new(COUNT as u64 * 9_000).p2wpkh(&public));
+    builder.build().unwrap()
+}
+
+/// Signs every input parsing the private key and deriving its public key again,
+/// as if the key was looked up per input.
+fn sign_reparsing_per_input(unsigned_tx: UnsignedTransaction<Transaction>) -> Transaction {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+
+    let claims = unsigned_tx
+        .input_args()
+        .iter()
+        .enumerate()
+        .map(|(input_index, utxo)| {
+            let sighash = SighashComputer::preimage_utxo(&unsigned_tx, input_index, utxo).unwrap();
+
+            let mut keys_manager = KeysManager::default();
+            keys_manager.add_ecdsa_private_bytes(&private_key).unwrap();
+            let signature = TxSigner::sign_sighash(&keys_manager, &sighash).unwrap();
+
+            TxCompiler::utxo_spending_data(utxo, &signature, input_index, &NoopSignerMetrics)
+                .unwrap()
+        })
+        .collect();
+
+    TxCompiler::compile_spending_data(unsigned_tx, claims).unwrap()
+}
+
+fn sweep_benchmark(c: &mut Criterion) {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+
+    let mut group = c.benchmark_group("sign_sweep_500_p2wpkh");
+    group.sample_size(10);
+    group.bench_function("reparsing_per_input", |b| {
+        b.iter_batched(sweep_tx, sign_reparsing_per_input, BatchSize::LargeInput)
+    });
+    group.bench_function("single_key", |b| {
+        b.iter_batched(
+            sweep_tx,
+            |unsigned_tx| TxSigner::sign_with_single_key(unsigned_tx, &private_key).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sweep_benchmark);
+criterion_main!(benches);
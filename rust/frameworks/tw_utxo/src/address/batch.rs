@@ -0,0 +1,423 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Bulk derivation of receive/change addresses from an account extended public key,
+//! e.g. for gap limit scanning on wallet onboarding.
+
+use crate::chain_params::ChainParams;
+use crate::script::standard_script::conditions;
+use crate::script::Script;
+use bitcoin::bip32::{ChildNumber, ExtendedPrivKey, ExtendedPubKey};
+use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::{Secp256k1, VerifyOnly, XOnlyPublicKey};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::ops::Range;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::base58::{self, Alphabet};
+use tw_hash::hasher::{sha256_d, sha256_ripemd};
+use tw_hash::{H160, H256, H264};
+
+/// Ranges of at least this number of indexes are derived in parallel,
+/// if the `parallel` feature is enabled.
+pub const PARALLEL_THRESHOLD: usize = 512;
+/// Non-hardened child indexes are `0..2^31`.
+const NORMAL_INDEX_END: u32 = 1 << 31;
+/// Serialized extended key length excluding the base58 checksum.
+const EXTENDED_KEY_LEN: usize = 78;
+const CHECKSUM_LEN: usize = 4;
+/// Version bytes of a mainnet `xpub`.
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+/// Version bytes of a mainnet `xprv`.
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchScriptType {
+    /// `pkh(...)`, BIP44.
+    P2PKH,
+    /// `sh(wpkh(...))`, BIP49.
+    P2SHP2WPKH,
+    /// `wpkh(...)`, BIP84.
+    P2WPKH,
+    /// `tr(...)` key-path only, BIP86.
+    P2TR,
+}
+
+impl BatchScriptType {
+    /// The purpose of the standard derivation path of the script type, e.g. `84` for P2WPKH.
+    pub fn bip_purpose(&self) -> u32 {
+        match self {
+            BatchScriptType::P2PKH => 44,
+            BatchScriptType::P2SHP2WPKH => 49,
+            BatchScriptType::P2WPKH => 84,
+            BatchScriptType::P2TR => 86,
+        }
+    }
+}
+
+/// A descriptor-like template of the addresses to derive, e.g. `wpkh(0/*)` for receive
+/// or `wpkh(1/*)` for change P2WPKH addresses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AddressTemplate {
+    pub script_type: BatchScriptType,
+    /// Non-hardened chain index relative to the account key, `0` for receive and `1` for change.
+    pub chain: u32,
+}
+
+impl FromStr for AddressTemplate {
+    type Err = SigningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const WRAPPERS: [(&str, &str, BatchScriptType); 4] = [
+            ("pkh(", ")", BatchScriptType::P2PKH),
+            ("sh(wpkh(", "))", BatchScriptType::P2SHP2WPKH),
+            ("wpkh(", ")", BatchScriptType::P2WPKH),
+            ("tr(", ")", BatchScriptType::P2TR),
+        ];
+
+        let (script_type, path) = WRAPPERS
+            .iter()
+            .find_map(|(prefix, suffix, script_type)| {
+                let path = s.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                Some((*script_type, path))
+            })
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Unsupported address template: {s}"))?;
+
+        let chain = path
+            .strip_suffix("/*")
+            .and_then(|chain| u32::from_str(chain).ok())
+            .filter(|chain| *chain < NORMAL_INDEX_END)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Expected a '<chain>/*' non-hardened path, found: {path}"))?;
+
+        Ok(AddressTemplate { script_type, chain })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivedAddress {
+    /// Child index of the address within the chain.
+    pub index: u32,
+    pub public_key: H264,
+    pub script_pubkey: Script,
+    pub address: String,
+}
+
+/// Derives addresses of the same template from an account extended public key.
+pub struct AddressBatch {
+    /// The extended public key of the template chain, e.g. `m/84'/0'/0'/0`.
+    chain_xpub: ExtendedPubKey,
+    script_type: BatchScriptType,
+    chain_params: ChainParams,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl AddressBatch {
+    /// Creates a batch deriver from the account extended public key.
+    ///
+    /// `account_xpub` version bytes are ignored, so SLIP-132 `ypub`, `zpub` and testnet keys
+    /// are accepted too. The address format is defined by the `template` and `chain_params` only.
+    pub fn new(
+        account_xpub: &str,
+        template: AddressTemplate,
+        chain_params: ChainParams,
+    ) -> SigningResult<AddressBatch> {
+        let account_xpub = decode_xpub_any_version(account_xpub)?;
+        AddressBatch::from_account_xpub(account_xpub, template, chain_params)
+    }
+
+    /// Creates a batch deriver from a decoded account extended public key.
+    pub fn from_account_xpub(
+        account_xpub: ExtendedPubKey,
+        template: AddressTemplate,
+        chain_params: ChainParams,
+    ) -> SigningResult<AddressBatch> {
+        let secp = Secp256k1::verification_only();
+
+        let chain = ChildNumber::from_normal_idx(template.chain)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Chain index must be non-hardened")?;
+        let chain_xpub = account_xpub
+            .ckd_pub(&secp, chain)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Error deriving the chain extended public key")?;
+
+        Ok(AddressBatch {
+            chain_xpub,
+            script_type: template.script_type,
+            chain_params,
+            secp,
+        })
+    }
+
+    /// Derives addresses for every index of the given range.
+    ///
+    /// The result is sorted by [`DerivedAddress::index`].
+    /// As per BIP32, an index that results in an invalid child key is skipped,
+    /// so the result can contain less items than the range length.
+    /// Ranges of at least [`PARALLEL_THRESHOLD`] indexes are split between the rayon threads,
+    /// if the `parallel` feature is enabled.
+    pub fn derive_range(&self, indexes: Range<u32>) -> SigningResult<Vec<DerivedAddress>> {
+        if indexes.end > NORMAL_INDEX_END {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Only non-hardened indexes can be derived from an extended public key");
+        }
+
+        if indexes.len() < PARALLEL_THRESHOLD {
+            return self.derive_chunk(indexes);
+        }
+        self.derive_parallel(indexes)
+    }
+
+    /// Derives addresses starting from the `start` index until `gap_limit` consecutive
+    /// addresses are reported as unused by `is_used`.
+    ///
+    /// Returns all the derived addresses, including the trailing unused ones.
+    pub fn scan_with_gap_limit<F>(
+        &self,
+        start: u32,
+        gap_limit: u32,
+        mut is_used: F,
+    ) -> SigningResult<Vec<DerivedAddress>>
+    where
+        F: FnMut(&DerivedAddress) -> bool,
+    {
+        if gap_limit == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Gap limit must be greater than zero");
+        }
+
+        let mut result = Vec::new();
+        let mut unused_in_row = 0;
+        let mut next = start;
+
+        while unused_in_row < gap_limit && next < NORMAL_INDEX_END {
+            // Derive just enough addresses to fill the gap if none of them are used.
+            let end = next
+                .saturating_add(gap_limit - unused_in_row)
+                .min(NORMAL_INDEX_END);
+
+            for address in self.derive_range(next..end)? {
+                if is_used(&address) {
+                    unused_in_row = 0;
+                } else {
+                    unused_in_row += 1;
+                }
+                result.push(address);
+            }
+            next = end;
+        }
+
+        Ok(result)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn derive_parallel(&self, indexes: Range<u32>) -> SigningResult<Vec<DerivedAddress>> {
+        let chunk_size = indexes.len().div_ceil(rayon::current_num_threads()) as u32;
+        let chunk_starts: Vec<u32> = indexes.clone().step_by(chunk_size as usize).collect();
+        let chunks = chunk_starts
+            .into_par_iter()
+            .map(|start| {
+                let end = start.saturating_add(chunk_size).min(indexes.end);
+                self.derive_chunk(start..end)
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        Ok(chunks.into_iter().flatten().collect())
+    }
+
+    /// Threads are not available, so the whole range is derived on the current thread.
+    #[cfg(not(feature = "parallel"))]
+    fn derive_parallel(&self, indexes: Range<u32>) -> SigningResult<Vec<DerivedAddress>> {
+        self.derive_chunk(indexes)
+    }
+
+    fn derive_chunk(&self, indexes: Range<u32>) -> SigningResult<Vec<DerivedAddress>> {
+        let mut encoder = AddressEncoder::new(&self.chain_params);
+
+        let mut result = Vec::with_capacity(indexes.len());
+        for index in indexes {
+            // Panicking implies a bug, the index range has been checked already.
+            let child_number = ChildNumber::from_normal_idx(index).unwrap();
+            // An invalid child key has a probability lower than 1 in 2^127.
+            let Ok(child) = self.chain_xpub.ckd_pub(&self.secp, child_number) else {
+                continue;
+            };
+
+            let public_key = H264::from(child.public_key.serialize());
+            result.push(self.derive_address(&mut encoder, index, public_key)?);
+        }
+        Ok(result)
+    }
+
+    fn derive_address(
+        &self,
+        encoder: &mut AddressEncoder,
+        index: u32,
+        public_key: H264,
+    ) -> SigningResult<DerivedAddress> {
+        let (script_pubkey, address) = match self.script_type {
+            BatchScriptType::P2PKH => {
+                let pubkey_hash = hash160(public_key.as_slice());
+                let address = encoder.base58(self.chain_params.p2pkh_prefix, &pubkey_hash);
+                (conditions::new_p2pkh(&pubkey_hash), address)
+            },
+            BatchScriptType::P2SHP2WPKH => {
+                let pubkey_hash = hash160(public_key.as_slice());
+                let redeem_script = conditions::new_p2wpkh(&pubkey_hash);
+                let script_hash = hash160(redeem_script.as_slice());
+                let address = encoder.base58(self.chain_params.p2sh_prefix, &script_hash);
+                (conditions::new_p2sh(&script_hash), address)
+            },
+            BatchScriptType::P2WPKH => {
+                let pubkey_hash = hash160(public_key.as_slice());
+                let address = encoder.bech32(0, &pubkey_hash, bech32::Variant::Bech32)?;
+                (conditions::new_p2wpkh(&pubkey_hash), address)
+            },
+            BatchScriptType::P2TR => {
+                // Tweak the key with the shared context instead of creating one per address.
+                let internal_key = XOnlyPublicKey::from_slice(&public_key[1..])
+                    .tw_err(|_| SigningErrorType::Error_internal)
+                    .context("Invalid derived public key")?;
+                let (output_key, _parity) = internal_key.tap_tweak(&self.secp, None);
+                let output_key = H256::from(output_key.serialize());
+
+                let address = encoder.bech32(1, output_key.as_slice(), bech32::Variant::Bech32m)?;
+                let script_pubkey = conditions::new_p2tr_dangerous_assume_tweaked(&output_key);
+                (script_pubkey, address)
+            },
+        };
+
+        Ok(DerivedAddress {
+            index,
+            public_key,
+            script_pubkey,
+            address,
+        })
+    }
+}
+
+/// Encodes addresses of a batch reusing the same payload buffer.
+struct AddressEncoder<'a> {
+    chain_params: &'a ChainParams,
+    /// `prefix || hash || checksum` buffer of base58 addresses.
+    base58_payload: Vec<u8>,
+}
+
+impl<'a> AddressEncoder<'a> {
+    fn new(chain_params: &'a ChainParams) -> Self {
+        AddressEncoder {
+            chain_params,
+            base58_payload: Vec::with_capacity(1 + H160::LEN + CHECKSUM_LEN),
+        }
+    }
+
+    fn base58(&mut self, prefix: u8, hash: &H160) -> String {
+        self.base58_payload.clear();
+        self.base58_payload.push(prefix);
+        self.base58_payload.extend_from_slice(hash.as_slice());
+
+        let checksum = sha256_d(&self.base58_payload);
+        self.base58_payload
+            .extend_from_slice(&checksum[..CHECKSUM_LEN]);
+
+        base58::encode(&self.base58_payload, Alphabet::Bitcoin)
+    }
+
+    fn bech32(
+        &mut self,
+        witness_version: u8,
+        witness_program: &[u8],
+        variant: bech32::Variant,
+    ) -> SigningResult<String> {
+        // `hrp` + separator + witness version + program + checksum.
+        let capacity =
+            self.chain_params.hrp.len() + 2 + (witness_program.len() * 8).div_ceil(5) + 6;
+        let mut address = String::with_capacity(capacity);
+
+        let version_u5 =
+            bech32::u5::try_from_u8(witness_version).expect("WitnessVersion must be 0..=16");
+        {
+            let mut writer =
+                bech32::Bech32Writer::new(&self.chain_params.hrp, variant, &mut address)
+                    .tw_err(|_| SigningErrorType::Error_invalid_params)
+                    .context("Invalid bech32 HRP")?;
+            bech32::WriteBase32::write_u5(&mut writer, version_u5)
+                .tw_err(|_| SigningErrorType::Error_internal)?;
+            bech32::ToBase32::write_base32(&witness_program, &mut writer)
+                .tw_err(|_| SigningErrorType::Error_internal)?;
+        }
+
+        Ok(address)
+    }
+}
+
+fn hash160(data: &[u8]) -> H160 {
+    H160::try_from(sha256_ripemd(data).as_slice()).expect("sha256_ripemd returns 20 bytes")
+}
+
+/// Decodes a base58check extended public key ignoring its version bytes.
+fn decode_xpub_any_version(s: &str) -> SigningResult<ExtendedPubKey> {
+    let data = decode_extended_key_any_version(s, XPUB_VERSION)?;
+    ExtendedPubKey::decode(&data)
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid extended public key")
+}
+
+/// Decodes a base58check extended private key ignoring its version bytes.
+pub(crate) fn decode_xprv_any_version(s: &str) -> SigningResult<ExtendedPrivKey> {
+    let data = decode_extended_key_any_version(s, XPRV_VERSION)?;
+    ExtendedPrivKey::decode(&data)
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid extended private key")
+}
+
+/// Checks the base58check extended key and replaces its version bytes with `version`,
+/// so the key can be decoded regardless of SLIP-132 or testnet prefixes.
+fn decode_extended_key_any_version(s: &str, version: [u8; 4]) -> SigningResult<Vec<u8>> {
+    let mut data = base58::decode(s, Alphabet::Bitcoin)
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid base58 extended key")?;
+
+    if data.len() != EXTENDED_KEY_LEN + CHECKSUM_LEN {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Invalid extended key length");
+    }
+    let (payload, checksum) = data.split_at(EXTENDED_KEY_LEN);
+    if sha256_d(payload)[..CHECKSUM_LEN] != *checksum {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Invalid extended key checksum");
+    }
+
+    data.truncate(EXTENDED_KEY_LEN);
+    data[..version.len()].copy_from_slice(&version);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_template_from_str() {
+        let parse = |s: &str| AddressTemplate::from_str(s).ok();
+        let template = |script_type, chain| Some(AddressTemplate { script_type, chain });
+
+        assert_eq!(parse("pkh(0/*)"), template(BatchScriptType::P2PKH, 0));
+        assert_eq!(
+            parse("sh(wpkh(1/*))"),
+            template(BatchScriptType::P2SHP2WPKH, 1)
+        );
+        assert_eq!(parse("wpkh(1/*)"), template(BatchScriptType::P2WPKH, 1));
+        assert_eq!(parse("tr(0/*)"), template(BatchScriptType::P2TR, 0));
+
+        assert_eq!(parse("wpkh(0)"), None);
+        assert_eq!(parse("wpkh(0/1)"), None);
+        assert_eq!(parse("wpkh(2147483648/*)"), None);
+        assert_eq!(parse("wsh(0/*)"), None);
+        assert_eq!(parse("sh(wpkh(0/*)"), None);
+    }
+}
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BIP39 mnemonic validation and seed derivation.
+//! Only the English wordlist is supported.
+
+use bitcoin::hashes::{hmac, sha512, Hash, HashEngine};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::sha2::sha256;
+use zeroize::Zeroizing;
+
+/// The sorted BIP39 English wordlist, one word per line.
+const ENGLISH_WORDLIST: &str = include_str!("bip39_english.txt");
+const BITS_PER_WORD: usize = 11;
+/// Every 32 bits of entropy are followed by a checksum bit.
+const ENTROPY_BITS_PER_CHECKSUM_BIT: usize = 32;
+/// Mnemonic to seed stretching rounds.
+const PBKDF2_ROUNDS: u32 = 2048;
+pub const SEED_LEN: usize = 64;
+
+/// Checks if the mnemonic words are from the English wordlist and the checksum is valid.
+/// Returns the mnemonic with the words separated by a single space.
+pub fn normalize_mnemonic(mnemonic: &str) -> SigningResult<Zeroizing<String>> {
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    if !matches!(words.len(), 12 | 15 | 18 | 21 | 24) {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Mnemonic must have 12, 15, 18, 21 or 24 words");
+    }
+
+    let wordlist: Vec<&str> = ENGLISH_WORDLIST.lines().collect();
+    let mut bits = Zeroizing::new(Vec::with_capacity(words.len() * BITS_PER_WORD));
+    for (word_index, word) in words.iter().enumerate() {
+        // Don't put the word to the error message, as it's secret.
+        let index = wordlist
+            .binary_search(word)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Mnemonic word #{word_index} is not in the BIP39 wordlist"))?;
+        bits.extend(
+            (0..BITS_PER_WORD)
+                .rev()
+                .map(|bit| ((index >> bit) & 1) as u8),
+        );
+    }
+
+    let checksum_len = bits.len() / (ENTROPY_BITS_PER_CHECKSUM_BIT + 1);
+    let (entropy_bits, checksum_bits) = bits.split_at(bits.len() - checksum_len);
+    let entropy = Zeroizing::new(
+        entropy_bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0_u8, |acc, bit| (acc << 1) | bit))
+            .collect::<Vec<_>>(),
+    );
+    let entropy_hash = Zeroizing::new(sha256(&entropy));
+    let expected_checksum = (0..checksum_len).map(|i| (entropy_hash[i / 8] >> (7 - i % 8)) & 1);
+    if !checksum_bits.iter().copied().eq(expected_checksum) {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Invalid mnemonic checksum");
+    }
+
+    Ok(Zeroizing::new(words.join(" ")))
+}
+
+/// BIP39 `PBKDF2-HMAC-SHA512(mnemonic, "mnemonic" || passphrase)`.
+/// The derived key is a single HMAC-SHA512 block long.
+///
+/// The mnemonic must be normalized by [`normalize_mnemonic`].
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Zeroizing<[u8; SEED_LEN]> {
+    let prf = hmac::HmacEngine::<sha512::Hash>::new(mnemonic.as_bytes());
+    let salt = Zeroizing::new(format!("mnemonic{passphrase}"));
+
+    let mut engine = prf.clone();
+    engine.input(salt.as_bytes());
+    engine.input(&1_u32.to_be_bytes());
+
+    let mut block = Zeroizing::new([0; SEED_LEN]);
+    block.copy_from_slice(&hmac::Hmac::from_engine(engine)[..]);
+    let mut seed = block.clone();
+
+    for _ in 1..PBKDF2_ROUNDS {
+        let mut engine = prf.clone();
+        engine.input(block.as_slice());
+        block.copy_from_slice(&hmac::Hmac::from_engine(engine)[..]);
+
+        seed.iter_mut().zip(block.iter()).for_each(|(s, b)| *s ^= b);
+    }
+    seed
+}
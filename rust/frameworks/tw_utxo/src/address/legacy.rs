@@ -71,31 +71,42 @@ impl LegacyAddress {
         s: &str,
         prefix: Option<BitcoinBase58Prefix>,
     ) -> AddressResult<LegacyAddress> {
-        let base58_prefix = match prefix {
-            Some(base58_prefix) => base58_prefix,
+        match prefix {
+            Some(base58_prefix) => {
+                LegacyAddress::from_str_checked(s, base58_prefix.p2pkh, &[base58_prefix.p2sh])
+            },
             None => {
                 let p2pkh = coin.p2pkh_prefix().ok_or(AddressError::InvalidRegistry)?;
                 let p2sh = coin.p2sh_prefix().ok_or(AddressError::InvalidRegistry)?;
-                BitcoinBase58Prefix { p2pkh, p2sh }
-            },
-        };
 
-        LegacyAddress::from_str_checked(s, base58_prefix.p2pkh, base58_prefix.p2sh)
+                // Deprecated P2SH prefixes are still accepted.
+                let mut p2sh_prefixes = vec![p2sh];
+                p2sh_prefixes.extend_from_slice(coin.legacy_p2sh_prefixes());
+                LegacyAddress::from_str_checked(s, p2pkh, &p2sh_prefixes)
+            },
+        }
     }
 
+    /// Parses a `LegacyAddress` and checks if its prefix is either `p2pkh_prefix` or one of `p2sh_prefixes`.
+    /// Some chains accept several P2SH prefixes, e.g. Litecoin still accepts the Bitcoin `0x05` prefix.
     pub fn from_str_checked(
         s: &str,
         p2pkh_prefix: u8,
-        p2sh_prefix: u8,
+        p2sh_prefixes: &[u8],
     ) -> AddressResult<LegacyAddress> {
         let addr = LegacyAddress::from_str(s)?;
-        if addr.prefix() == p2pkh_prefix || addr.prefix() == p2sh_prefix {
+        if addr.prefix() == p2pkh_prefix || p2sh_prefixes.contains(&addr.prefix()) {
             Ok(addr)
         } else {
             Err(AddressError::UnexpectedAddressPrefix)
         }
     }
 
+    /// Returns the same address payload encoded with another `prefix`.
+    pub fn with_prefix(&self, prefix: u8) -> AddressResult<LegacyAddress> {
+        LegacyAddress::new(prefix, self.bytes())
+    }
+
     pub fn prefix(&self) -> u8 {
         self.0.as_ref()[0]
     }
@@ -2,10 +2,13 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+pub mod batch;
+pub mod bip39;
 pub mod derivation;
 pub mod legacy;
 pub mod segwit;
 pub mod standard_bitcoin;
+pub mod standard_derivation;
 pub mod taproot;
 pub mod witness_program;
 
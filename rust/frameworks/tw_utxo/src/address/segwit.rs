@@ -58,9 +58,17 @@ impl SegwitAddress {
         Self::new(hrp, script_hash)
     }
 
-    pub fn from_str_checked(s: &str, expected_hrp: &str) -> AddressResult<SegwitAddress> {
+    /// Parses a `SegwitAddress` and checks if its HRP is one of `expected_hrps`.
+    /// Some chains accept several HRPs, e.g. the canonical one and the deprecated ones.
+    pub fn from_str_checked<H: AsRef<str>>(
+        s: &str,
+        expected_hrps: &[H],
+    ) -> AddressResult<SegwitAddress> {
         let address = Self::from_str(s)?;
-        if address.inner.hrp() != expected_hrp {
+        if !expected_hrps
+            .iter()
+            .any(|hrp| hrp.as_ref() == address.inner.hrp())
+        {
             return Err(AddressError::InvalidHrp);
         }
         Ok(address)
@@ -71,16 +79,26 @@ impl SegwitAddress {
         s: &str,
         prefix: Option<Bech32Prefix>,
     ) -> AddressResult<SegwitAddress> {
-        let hrp = match prefix {
-            Some(Bech32Prefix { hrp }) => hrp,
-            None => coin.hrp().ok_or(AddressError::InvalidRegistry)?,
-        };
-        SegwitAddress::from_str_checked(s, &hrp)
+        match prefix {
+            Some(Bech32Prefix { hrp }) => SegwitAddress::from_str_checked(s, &[hrp]),
+            None => {
+                let hrp = coin.hrp().ok_or(AddressError::InvalidRegistry)?;
+
+                // Deprecated HRPs are still accepted.
+                let mut hrps = vec![hrp];
+                hrps.extend_from_slice(coin.legacy_hrps());
+                SegwitAddress::from_str_checked(s, &hrps)
+            },
+        }
     }
 
     pub fn witness_program(&self) -> &[u8] {
         self.inner.witness_program()
     }
+
+    pub fn hrp(&self) -> &str {
+        self.inner.hrp()
+    }
 }
 
 impl FromStr for SegwitAddress {
@@ -97,6 +97,29 @@ impl StandardBitcoinAddress {
         Err(AddressError::InvalidInput)
     }
 
+    /// Finds the coin the given segwit or taproot address belongs to by its canonical or deprecated HRP.
+    /// Helps to hint that an address of another network is given, e.g. "this looks like a Litecoin address".
+    pub fn detect_network_by_hrp<'a, Coin, I>(s: &str, coins: I) -> Option<Coin>
+    where
+        I: IntoIterator<Item = (Coin, &'a dyn CoinContext)>,
+    {
+        let hrp = match StandardBitcoinAddress::from_str(s).ok()? {
+            StandardBitcoinAddress::Segwit(segwit) => segwit.hrp().to_string(),
+            StandardBitcoinAddress::Taproot(taproot) => taproot.hrp().to_string(),
+            StandardBitcoinAddress::Legacy(_) => return None,
+        };
+
+        coins.into_iter().find_map(|(coin, context)| {
+            let accepted = context.hrp().as_deref() == Some(hrp.as_str())
+                || context.legacy_hrps().contains(&hrp);
+            if accepted {
+                Some(coin)
+            } else {
+                None
+            }
+        })
+    }
+
     /// TrustWallet derivation inherited from:
     /// https://github.com/trustwallet/wallet-core/blob/b65adc4c86e49eb905f659ade025185a62e87ca9/src/Bitcoin/Entry.cpp#L67
     pub fn derive_as_tw(
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Standard BIP44, BIP49, BIP84 and BIP86 derivation of single-key addresses
+//! from a mnemonic, a seed or a master extended private key.
+
+use crate::address::batch::{
+    decode_xprv_any_version, AddressBatch, AddressTemplate, BatchScriptType, DerivedAddress,
+};
+use crate::address::bip39;
+use crate::chain_params::ChainParams;
+use bitcoin::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey};
+use bitcoin::secp256k1::{Secp256k1, SignOnly};
+use bitcoin::Network;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::derivation::{ChildIndex, Derivation};
+use tw_coin_entry::error::prelude::*;
+
+/// BIP32 seed length bounds in bytes.
+const MIN_SEED_LEN: usize = 16;
+const MAX_SEED_LEN: usize = 64;
+
+/// Derives standard addresses along `m/purpose'/coin_type'/account'/change/index`,
+/// where the purpose is defined by the requested [`BatchScriptType`].
+pub struct StandardDerivation {
+    master: ExtendedPrivKey,
+    coin_type: u32,
+    chain_params: ChainParams,
+    secp: Secp256k1<SignOnly>,
+}
+
+impl StandardDerivation {
+    /// Creates a deriver from a BIP39 English mnemonic and an optional passphrase (can be empty).
+    ///
+    /// Only ASCII passphrases are supported, as they don't require Unicode normalization.
+    pub fn from_mnemonic(
+        mnemonic: &str,
+        passphrase: &str,
+        coin_type: u32,
+        chain_params: ChainParams,
+    ) -> SigningResult<StandardDerivation> {
+        if !passphrase.is_ascii() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Only ASCII passphrases are supported");
+        }
+
+        let mnemonic = bip39::normalize_mnemonic(mnemonic).context("Invalid mnemonic")?;
+        let seed = bip39::mnemonic_to_seed(&mnemonic, passphrase);
+        StandardDerivation::from_seed(seed.as_slice(), coin_type, chain_params)
+    }
+
+    /// Creates a deriver from a BIP32 seed.
+    pub fn from_seed(
+        seed: &[u8],
+        coin_type: u32,
+        chain_params: ChainParams,
+    ) -> SigningResult<StandardDerivation> {
+        if !(MIN_SEED_LEN..=MAX_SEED_LEN).contains(&seed.len()) {
+            return SigningError::err(SigningErrorType::Error_invalid_params).with_context(|| {
+                format!("Seed must be {MIN_SEED_LEN} to {MAX_SEED_LEN} bytes long")
+            });
+        }
+
+        // The network affects the serialization of the extended keys only.
+        let master = ExtendedPrivKey::new_master(Network::Bitcoin, seed)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid master key derived from the seed")?;
+        Ok(StandardDerivation::with_master(
+            master,
+            coin_type,
+            chain_params,
+        ))
+    }
+
+    /// Creates a deriver from a master extended private key.
+    ///
+    /// `xprv` version bytes are ignored, so `tprv` and SLIP-132 keys are accepted too.
+    pub fn from_xprv(
+        xprv: &str,
+        coin_type: u32,
+        chain_params: ChainParams,
+    ) -> SigningResult<StandardDerivation> {
+        let master = decode_xprv_any_version(xprv)?;
+        if master.depth != 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Expected a master extended private key");
+        }
+        Ok(StandardDerivation::with_master(
+            master,
+            coin_type,
+            chain_params,
+        ))
+    }
+
+    /// Returns the full derivation path of an address, e.g. `m/84'/0'/0'/0/0`.
+    pub fn path(
+        &self,
+        script_type: BatchScriptType,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> SigningResult<DerivationPath> {
+        let mut path = self.account_path(script_type, account)?;
+
+        let change = ChildNumber::from_normal_idx(change)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Change index must be non-hardened")?;
+        let index = ChildNumber::from_normal_idx(index)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Address index must be non-hardened")?;
+
+        path.push(change);
+        path.push(index);
+        Ok(DerivationPath::from(path))
+    }
+
+    /// Returns the account extended public key, e.g. of `m/84'/0'/0'`.
+    /// The key can be shared with a watch-only wallet.
+    pub fn account_xpub(
+        &self,
+        script_type: BatchScriptType,
+        account: u32,
+    ) -> SigningResult<ExtendedPubKey> {
+        let path = self.account_path(script_type, account)?;
+        let account_xprv = self
+            .master
+            .derive_priv(&self.secp, &path)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Error deriving the account extended private key")?;
+        Ok(ExtendedPubKey::from_priv(&self.secp, &account_xprv))
+    }
+
+    /// Returns a batch deriver of the account receive (`change = 0`) or change (`change = 1`) addresses,
+    /// e.g. for gap limit scanning.
+    pub fn address_batch(
+        &self,
+        script_type: BatchScriptType,
+        account: u32,
+        change: u32,
+    ) -> SigningResult<AddressBatch> {
+        let account_xpub = self.account_xpub(script_type, account)?;
+        let template = AddressTemplate {
+            script_type,
+            chain: change,
+        };
+        AddressBatch::from_account_xpub(account_xpub, template, self.chain_params.clone())
+    }
+
+    /// Derives the address at `m/purpose'/coin_type'/account'/change/index`.
+    pub fn derive_address(
+        &self,
+        script_type: BatchScriptType,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> SigningResult<DerivedAddress> {
+        self.address_batch(script_type, account, change)?
+            .derive_range(index..index.saturating_add(1))?
+            .pop()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Index {index} results in an invalid key, use the next one"))
+    }
+
+    fn with_master(
+        master: ExtendedPrivKey,
+        coin_type: u32,
+        chain_params: ChainParams,
+    ) -> StandardDerivation {
+        StandardDerivation {
+            master,
+            coin_type,
+            chain_params,
+            secp: Secp256k1::signing_only(),
+        }
+    }
+
+    fn account_path(
+        &self,
+        script_type: BatchScriptType,
+        account: u32,
+    ) -> SigningResult<Vec<ChildNumber>> {
+        let hardened = |index: u32, what: &str| {
+            ChildNumber::from_hardened_idx(index)
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .with_context(|| format!("Invalid {what} index: {index}"))
+        };
+
+        Ok(vec![
+            hardened(script_type.bip_purpose(), "purpose")?,
+            hardened(self.coin_type, "coin type")?,
+            hardened(account, "account")?,
+        ])
+    }
+}
+
+/// Returns the coin type of the given registry derivation, e.g. `0` for Bitcoin
+/// or `1` for its `testnet` derivation.
+/// [`Derivation::Default`] stands for the first derivation of the coin.
+pub fn registry_coin_type(coin: &dyn CoinContext, derivation: Derivation) -> SigningResult<u32> {
+    let derivations = coin.derivations();
+    let found = match derivation {
+        Derivation::Default => derivations.first(),
+        _ => derivations.iter().find(|der| der.name == derivation),
+    };
+
+    let coin_type = found
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("The coin doesn't support the requested derivation")?
+        .path
+        .path()
+        .get(1)
+        .copied();
+    match coin_type {
+        Some(ChildIndex::Hardened(coin_type)) => Ok(coin_type),
+        _ => SigningError::err(SigningErrorType::Error_internal)
+            .context("Expected a hardened coin type in the registry derivation path"),
+    }
+}
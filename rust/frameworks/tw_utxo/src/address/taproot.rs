@@ -1,12 +1,11 @@
 use super::Bech32Prefix;
 use crate::address::witness_program::WitnessProgram;
-use bitcoin::key::TapTweak;
 use core::fmt;
 use std::str::FromStr;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::{H256, H264};
-use tw_keypair::tw;
+use tw_hash::H256;
+use tw_keypair::{schnorr, tw};
 use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
 
@@ -43,29 +42,30 @@ impl TaprootAddress {
     ///   possible spending conditions.
     pub fn p2tr_with_public_key(
         hrp: String,
-        internal_pubkey: &H264,
+        internal_pubkey: &schnorr::PublicKey,
         merkle_root: Option<&H256>,
     ) -> AddressResult<TaprootAddress> {
-        // We're relying on the `bitcoin` crate to generate anything Taproot related.
-
-        // Convert the native `H256` to `TapNodeHash` from the `bitcoin` crate.
-        let merkle_root = merkle_root.map(|hash| {
-            let tap_hash =
-                <bitcoin::hashes::sha256t::Hash<_> as bitcoin::hashes::Hash>::from_slice(
-                    hash.as_slice(),
-                )
-                .expect("merkle_root length is 32 bytes");
-
-            bitcoin::taproot::TapNodeHash::from_raw_hash(tap_hash)
-        });
+        let output_key = Self::p2tr_output_key(internal_pubkey, merkle_root);
+        Self::p2tr_with_output_key(hrp, &output_key)
+    }
 
-        // Tweak the public key with the (empty) merkle root.
-        let pubkey = bitcoin::PublicKey::from_slice(internal_pubkey.as_slice()).unwrap();
-        let internal_key = bitcoin::secp256k1::XOnlyPublicKey::from(pubkey.inner);
-        let (output_key, _parity) =
-            internal_key.tap_tweak(&bitcoin::secp256k1::Secp256k1::new(), merkle_root);
+    /// Tweaks the internal public key with an optional merkle root.
+    /// The returned output key keeps its parity that is required later to build script-path control blocks,
+    /// while the address commits to the x-only key only.
+    pub fn p2tr_output_key(
+        internal_pubkey: &schnorr::PublicKey,
+        merkle_root: Option<&H256>,
+    ) -> schnorr::TweakedPublicKey {
+        // The public key is validated on construction, so it's tweaked without parsing it again.
+        internal_pubkey.tweak(merkle_root.copied())
+    }
 
-        Self::new(hrp, output_key.serialize().to_vec())
+    /// Create a Taproot address from an output key tweaked already.
+    pub fn p2tr_with_output_key(
+        hrp: String,
+        output_key: &schnorr::TweakedPublicKey,
+    ) -> AddressResult<TaprootAddress> {
+        Self::new(hrp, output_key.xonly.bytes().to_vec())
     }
 
     /// Create a Taproot address from a public key and an optional merkle root.
@@ -87,17 +87,24 @@ impl TaprootAddress {
             None => coin.hrp().ok_or(AddressError::InvalidRegistry)?,
         };
 
-        let public_key_bytes = public_key
+        let public_key = public_key
             .to_secp256k1()
-            .ok_or(AddressError::PublicKeyTypeMismatch)?
-            .compressed();
+            .ok_or(AddressError::PublicKeyTypeMismatch)?;
 
-        Self::p2tr_with_public_key(hrp, &public_key_bytes, merkle_root)
+        Self::p2tr_with_public_key(hrp, &schnorr::PublicKey::from(public_key), merkle_root)
     }
 
-    pub fn from_str_checked(s: &str, expected_hrp: &str) -> AddressResult<TaprootAddress> {
+    /// Parses a `TaprootAddress` and checks if its HRP is one of `expected_hrps`.
+    /// Some chains accept several HRPs, e.g. the canonical one and the deprecated ones.
+    pub fn from_str_checked<H: AsRef<str>>(
+        s: &str,
+        expected_hrps: &[H],
+    ) -> AddressResult<TaprootAddress> {
         let address = Self::from_str(s)?;
-        if address.inner.hrp() != expected_hrp {
+        if !expected_hrps
+            .iter()
+            .any(|hrp| hrp.as_ref() == address.inner.hrp())
+        {
             return Err(AddressError::InvalidHrp);
         }
         Ok(address)
@@ -108,16 +115,26 @@ impl TaprootAddress {
         s: &str,
         prefix: Option<Bech32Prefix>,
     ) -> AddressResult<TaprootAddress> {
-        let hrp = match prefix {
-            Some(Bech32Prefix { hrp }) => hrp,
-            None => coin.hrp().ok_or(AddressError::InvalidRegistry)?,
-        };
-        TaprootAddress::from_str_checked(s, &hrp)
+        match prefix {
+            Some(Bech32Prefix { hrp }) => TaprootAddress::from_str_checked(s, &[hrp]),
+            None => {
+                let hrp = coin.hrp().ok_or(AddressError::InvalidRegistry)?;
+
+                // Deprecated HRPs are still accepted.
+                let mut hrps = vec![hrp];
+                hrps.extend_from_slice(coin.legacy_hrps());
+                TaprootAddress::from_str_checked(s, &hrps)
+            },
+        }
     }
 
     pub fn witness_program(&self) -> &[u8] {
         self.inner.witness_program()
     }
+
+    pub fn hrp(&self) -> &str {
+        self.inner.hrp()
+    }
 }
 
 impl FromStr for TaprootAddress {
@@ -247,4 +264,62 @@ mod tests {
             "bc1purekytqrqzfzdulufmll8a335jhvw9x4glhzp8fv76yxlsxeyptsfylq9h"
         );
     }
+
+    #[test]
+    fn test_taproot_address_output_key_parity() {
+        let internal_pubkey = "0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0"
+            .decode_hex()
+            .unwrap();
+        let internal_pubkey = schnorr::PublicKey::try_from(internal_pubkey.as_slice()).unwrap();
+        let merkle_root =
+            H256::from("613528a85aaf77444029f6c0b1ad6d736680fdb283b0bfc0c3bdbbb25aee102e");
+
+        let output_key = TaprootAddress::p2tr_output_key(&internal_pubkey, Some(&merkle_root));
+        assert_eq!(output_key.parity, schnorr::Parity::Odd);
+
+        let addr = TaprootAddress::p2tr_with_output_key("bc".to_string(), &output_key).unwrap();
+        assert_eq!(
+            addr,
+            TaprootAddress::p2tr_with_public_key(
+                "bc".to_string(),
+                &internal_pubkey,
+                Some(&merkle_root)
+            )
+            .unwrap()
+        );
+        assert_eq!(addr.witness_program(), output_key.xonly.bytes().as_slice());
+    }
+
+    #[test]
+    fn test_taproot_address_from_str_with_legacy_hrps() {
+        let coin = TestCoinContext {
+            legacy_hrps: vec!["bcrt".to_string()],
+            ..TestCoinContext::default().with_hrp("tb")
+        };
+        let canonical = "tb1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqp3mvzv";
+        let deprecated = "bcrt1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqvg32hk";
+
+        let canonical_addr =
+            TaprootAddress::from_str_with_coin_and_prefix(&coin, canonical, None).unwrap();
+        let deprecated_addr =
+            TaprootAddress::from_str_with_coin_and_prefix(&coin, deprecated, None).unwrap();
+        assert_eq!(deprecated_addr.hrp(), "bcrt");
+        assert_eq!(
+            canonical_addr.witness_program(),
+            deprecated_addr.witness_program()
+        );
+
+        // The explicit prefix doesn't accept the deprecated HRPs.
+        let prefix = Bech32Prefix {
+            hrp: "tb".to_string(),
+        };
+        assert_eq!(
+            TaprootAddress::from_str_with_coin_and_prefix(&coin, deprecated, Some(prefix)),
+            Err(AddressError::InvalidHrp)
+        );
+        assert_eq!(
+            TaprootAddress::from_str_checked(canonical, &["bc"]),
+            Err(AddressError::InvalidHrp)
+        );
+    }
 }
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Matching scripts against BIP158 basic compact block filters.
+//! https://github.com/bitcoin/bips/blob/master/bip-0158.mediawiki
+//!
+//! Only filter decoding and matching is supported, not filter construction.
+
+use crate::script::Script;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+
+/// Golomb-Rice coding parameter of the basic filter.
+pub const FILTER_P: u8 = 19;
+/// False positive rate parameter of the basic filter.
+pub const FILTER_M: u64 = 784_931;
+
+/// A decoded Golomb-coded set of a basic block filter.
+pub struct BlockFilter<'a> {
+    /// Number of items in the filter.
+    n: u64,
+    /// Golomb-Rice encoded sorted deltas.
+    data: &'a [u8],
+    k0: u64,
+    k1: u64,
+}
+
+impl<'a> BlockFilter<'a> {
+    /// Parses the serialized filter (`N` as CompactSize followed by the Golomb-Rice bitstream).
+    ///
+    /// `block_hash` is expected in the internal byte order, i.e. reversed in relation to the hex
+    /// displayed by block explorers. Its first 16 bytes are the SipHash key.
+    pub fn new(filter_bytes: &'a [u8], block_hash: &H256) -> SigningResult<Self> {
+        let (n, n_len) = read_compact_size(filter_bytes)
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Invalid block filter items count")?;
+
+        let (k0, k1) = siphash_keys(block_hash);
+        Ok(BlockFilter {
+            n,
+            data: &filter_bytes[n_len..],
+            k0,
+            k1,
+        })
+    }
+
+    /// Number of items in the filter.
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Whether any of the given scripts is (probably) included in the filter.
+    /// Empty scripts are never included into the filter and therefore skipped.
+    ///
+    /// Query items are hashed and sorted once, then the filter is streamed in a single pass.
+    pub fn matches_any<'s, I>(&self, scripts: I) -> SigningResult<bool>
+    where
+        I: IntoIterator<Item = &'s Script>,
+    {
+        if self.is_empty() {
+            return Ok(false);
+        }
+
+        let f = self
+            .n
+            .checked_mul(FILTER_M)
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Block filter items count is too large")?;
+
+        let mut query: Vec<u64> = scripts
+            .into_iter()
+            .filter(|script| !script.is_empty())
+            .map(|script| self.hash_to_range(script.as_slice(), f))
+            .collect();
+        if query.is_empty() {
+            return Ok(false);
+        }
+        query.sort_unstable();
+
+        let mut reader = BitReader::new(self.data);
+        let mut query_idx = 0;
+        let mut value = 0_u64;
+
+        for _ in 0..self.n {
+            let delta = reader
+                .read_golomb_rice()
+                .or_tw_err(SigningErrorType::Error_input_parse)
+                .context("Block filter is truncated")?;
+            value = value
+                .checked_add(delta)
+                .or_tw_err(SigningErrorType::Error_input_parse)
+                .context("Block filter value overflow")?;
+
+            // Skip the query items that are less than the current filter item.
+            while query_idx < query.len() && query[query_idx] < value {
+                query_idx += 1;
+            }
+            match query.get(query_idx) {
+                Some(item) if *item == value => return Ok(true),
+                Some(_) => (),
+                None => return Ok(false),
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn hash_to_range(&self, item: &[u8], f: u64) -> u64 {
+        let hash = siphash_2_4(self.k0, self.k1, item);
+        ((hash as u128 * f as u128) >> 64) as u64
+    }
+}
+
+/// Whether any of the given scripts is (probably) included in the block filter.
+/// See [`BlockFilter::new`] and [`BlockFilter::matches_any`].
+pub fn matches_any(
+    filter_bytes: &[u8],
+    block_hash: &H256,
+    scripts: &[Script],
+) -> SigningResult<bool> {
+    BlockFilter::new(filter_bytes, block_hash)?.matches_any(scripts)
+}
+
+fn siphash_keys(block_hash: &H256) -> (u64, u64) {
+    let (k0, k1): (&[u8], &[u8]) = (&block_hash[0..8], &block_hash[8..16]);
+    // Panicking implies a bug, both slices are 8 bytes long.
+    (
+        u64::from_le_bytes(k0.try_into().unwrap()),
+        u64::from_le_bytes(k1.try_into().unwrap()),
+    )
+}
+
+/// Reads a CompactSize integer and returns it along with the number of bytes read.
+fn read_compact_size(data: &[u8]) -> Option<(u64, usize)> {
+    let read_le = |len: usize| -> Option<u64> {
+        let bytes = data.get(1..1 + len)?;
+        let mut buf = [0_u8; 8];
+        buf[..len].copy_from_slice(bytes);
+        Some(u64::from_le_bytes(buf))
+    };
+
+    match *data.first()? {
+        0xFD => Some((read_le(2)?, 3)),
+        0xFE => Some((read_le(4)?, 5)),
+        0xFF => Some((read_le(8)?, 9)),
+        n => Some((n as u64, 1)),
+    }
+}
+
+/// Reads bits starting from the most significant one.
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits.
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = *self.data.get(self.position / 8)?;
+        let bit = (byte >> (7 - self.position % 8)) & 1;
+        self.position += 1;
+        Some(bit == 1)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0_u64;
+        for _ in 0..count {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+
+    /// Reads a Golomb-Rice encoded value with the [`FILTER_P`] parameter.
+    fn read_golomb_rice(&mut self) -> Option<u64> {
+        let mut quotient = 0_u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let remainder = self.read_bits(FILTER_P)?;
+        quotient.checked_shl(FILTER_P as u32).map(|q| q | remainder)
+    }
+}
+
+/// SipHash-2-4 as used by BIP158.
+fn siphash_2_4(k0: u64, k1: u64, data: &[u8]) -> u64 {
+    let mut v0 = k0 ^ 0x736f_6d65_7073_6575;
+    let mut v1 = k1 ^ 0x646f_7261_6e64_6f6d;
+    let mut v2 = k0 ^ 0x6c79_6765_6e65_7261;
+    let mut v3 = k1 ^ 0x7465_6462_7974_6573;
+
+    macro_rules! sip_round {
+        () => {
+            v0 = v0.wrapping_add(v1);
+            v1 = v1.rotate_left(13);
+            v1 ^= v0;
+            v0 = v0.rotate_left(32);
+            v2 = v2.wrapping_add(v3);
+            v3 = v3.rotate_left(16);
+            v3 ^= v2;
+            v0 = v0.wrapping_add(v3);
+            v3 = v3.rotate_left(21);
+            v3 ^= v0;
+            v2 = v2.wrapping_add(v1);
+            v1 = v1.rotate_left(17);
+            v1 ^= v2;
+            v2 = v2.rotate_left(32);
+        };
+    }
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in chunks.by_ref() {
+        // Panicking implies a bug, the chunk is exactly 8 bytes long.
+        let m = u64::from_le_bytes(chunk.try_into().unwrap());
+        v3 ^= m;
+        sip_round!();
+        sip_round!();
+        v0 ^= m;
+    }
+
+    let mut last = [0_u8; 8];
+    let remainder = chunks.remainder();
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[7] = data.len() as u8;
+    let m = u64::from_le_bytes(last);
+
+    v3 ^= m;
+    sip_round!();
+    sip_round!();
+    v0 ^= m;
+
+    v2 ^= 0xff;
+    sip_round!();
+    sip_round!();
+    sip_round!();
+    sip_round!();
+
+    v0 ^ v1 ^ v2 ^ v3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    fn script(hex: &str) -> Script {
+        Script::from(hex.decode_hex().unwrap())
+    }
+
+    fn block_hash(hex: &str) -> H256 {
+        H256::from(hex).rev()
+    }
+
+    #[test]
+    fn test_siphash_2_4() {
+        // Reference vectors from the SipHash paper, key = 00..0f.
+        let (k0, k1) = (0x0706_0504_0302_0100, 0x0f0e_0d0c_0b0a_0908);
+        assert_eq!(siphash_2_4(k0, k1, &[]), 0x726f_db47_dd0e_0e31);
+        let data: Vec<u8> = (0..15).collect();
+        assert_eq!(siphash_2_4(k0, k1, &data), 0xa129_ca61_49be_45e5);
+    }
+
+    /// BIP158 test vector: testnet genesis block.
+    #[test]
+    fn test_matches_testnet_genesis() {
+        let filter = "019dfca8".decode_hex().unwrap();
+        let block_hash =
+            block_hash("000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943");
+        let genesis_output = script("4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac");
+        let other = script("00141111111111111111111111111111111111111111");
+
+        let filter = BlockFilter::new(&filter, &block_hash).unwrap();
+        assert_eq!(filter.len(), 1);
+        assert!(filter.matches_any([&genesis_output]).unwrap());
+        assert!(filter.matches_any([&other, &genesis_output]).unwrap());
+        assert!(!filter.matches_any([&other]).unwrap());
+        assert!(!filter.matches_any([&Script::new()]).unwrap());
+    }
+
+    /// Mainnet genesis block filter, as built by rust-bitcoin `BlockFilter::new_script_filter`.
+    #[test]
+    fn test_matches_mainnet_genesis() {
+        let filter = "017fa880".decode_hex().unwrap();
+        let block_hash =
+            block_hash("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        let genesis_output = script("4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac");
+
+        let filter = BlockFilter::new(&filter, &block_hash).unwrap();
+        assert_eq!(filter.len(), 1);
+        assert!(filter.matches_any([&genesis_output]).unwrap());
+        assert!(!filter
+            .matches_any([&script("0014ffffffffffffffffffffffffffffffffffffffff")])
+            .unwrap());
+    }
+
+    #[test]
+    fn test_matches_multiple_items() {
+        // Built with the rust-bitcoin `BlockFilterWriter` from the scripts below,
+        // keyed by the mainnet genesis block hash.
+        let filter = "055b9022bad5f7b76f96aa88407c00".decode_hex().unwrap();
+        let block_hash =
+            block_hash("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        let included = [
+            script("76a914aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa88ac"),
+            script("0014bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            script("a914cccccccccccccccccccccccccccccccccccccccc87"),
+            script("5120dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd"),
+            script("0020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"),
+        ];
+        let other = script("0014ffffffffffffffffffffffffffffffffffffffff");
+
+        let filter = BlockFilter::new(&filter, &block_hash).unwrap();
+        assert_eq!(filter.len(), 5);
+        for item in included.iter() {
+            assert!(filter.matches_any([&other, item]).unwrap());
+        }
+        assert!(filter.matches_any(included.iter()).unwrap());
+        assert!(!filter.matches_any([&other]).unwrap());
+    }
+
+    #[test]
+    fn test_empty_and_truncated_filter() {
+        let block_hash =
+            block_hash("000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943");
+        let item = script("0014bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+
+        assert!(!matches_any(&[0x00], &block_hash, &[item.clone()]).unwrap());
+        // Three items are declared, but the bitstream is missing.
+        matches_any(&[0x03, 0x00], &block_hash, &[item.clone()]).unwrap_err();
+        matches_any(&[], &block_hash, &[item]).unwrap_err();
+    }
+}
@@ -0,0 +1,614 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::address::legacy::LegacyAddress;
+use crate::address::segwit::SegwitAddress;
+use crate::address::standard_bitcoin::StandardBitcoinAddress;
+use crate::address::taproot::TaprootAddress;
+use crate::constants::{BITCOIN_MAX_MONEY, DOGECOIN_MAX_MONEY, LITECOIN_MAX_MONEY};
+use crate::fee_policy::FeePolicy;
+use crate::script::standard_script::conditions::ScriptPubkeyType;
+use crate::script::standard_script::patterns::{ScriptTemplate, TemplateAddress};
+use crate::script::Script;
+use crate::sighash::{AllowedSighashTypes, SighashType};
+use crate::transaction::transaction_parts::Amount;
+use std::fmt;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H160;
+
+/// Bitcoin-like chains with incompatible sighash algorithms, e.g. Bitcoin Cash signatures
+/// commit to the `SIGHASH_FORK_ID` flag. Tags the signing arguments built for a chain,
+/// so they can't be signed by a signer configured for another one.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ChainTag {
+    #[default]
+    Bitcoin,
+    BitcoinCash,
+    Litecoin,
+}
+
+impl fmt::Display for ChainTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChainTag::Bitcoin => "Bitcoin",
+            ChainTag::BitcoinCash => "Bitcoin Cash",
+            ChainTag::Litecoin => "Litecoin",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Address and economic parameters of a Bitcoin-like chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainParams {
+    pub p2pkh_prefix: u8,
+    /// Canonical P2SH prefix. Addresses are always generated with this prefix.
+    pub p2sh_prefix: u8,
+    /// Deprecated P2SH prefixes that are still accepted when parsing addresses.
+    pub legacy_p2sh_prefixes: Vec<u8>,
+    /// Canonical human-readable part of segwit and taproot addresses.
+    /// Addresses are always generated with this HRP.
+    pub hrp: String,
+    /// Deprecated HRPs that are still accepted when parsing addresses, in the order of preference.
+    pub legacy_hrps: Vec<String>,
+    /// Max amount that any UTXO, output or their sum can have.
+    pub max_money: Amount,
+    /// Dust and fee policy of the chain.
+    pub fee_policy: FeePolicy,
+    /// Additional scriptPubkey templates of the chain, in the order of preference.
+    /// They are evaluated after the built-in templates, see [`ChainParams::classify`].
+    pub script_templates: Vec<ScriptTemplate>,
+    /// Whether the chain has activated segwit. Otherwise, transactions are always serialized
+    /// without the segwit marker and flag, and the witness inputs cannot be spent.
+    pub segwit_enabled: bool,
+    /// The sighash types the chain accepts, every defined combination by default.
+    /// The UTXOs signed with another type are rejected before any sighash is computed.
+    pub allowed_sighash_types: AllowedSighashTypes,
+    /// The fork id the chain signatures commit to along with the `SIGHASH_FORK_ID` flag,
+    /// e.g. 0 for Bitcoin Cash and 79 for Bitcoin Gold.
+    /// `None` if the chain doesn't use the fork-id sighash.
+    pub fork_id: Option<u32>,
+}
+
+impl ChainParams {
+    pub fn bitcoin_mainnet() -> Self {
+        ChainParams {
+            p2pkh_prefix: 0,
+            p2sh_prefix: 5,
+            legacy_p2sh_prefixes: Vec::new(),
+            hrp: "bc".to_string(),
+            legacy_hrps: Vec::new(),
+            max_money: BITCOIN_MAX_MONEY,
+            fee_policy: FeePolicy::bitcoin(),
+            script_templates: Vec::new(),
+            segwit_enabled: true,
+            allowed_sighash_types: AllowedSighashTypes::AllDefined,
+            fork_id: None,
+        }
+    }
+
+    pub fn bitcoin_testnet() -> Self {
+        ChainParams {
+            p2pkh_prefix: 111,
+            p2sh_prefix: 196,
+            legacy_p2sh_prefixes: Vec::new(),
+            hrp: "tb".to_string(),
+            legacy_hrps: Vec::new(),
+            max_money: BITCOIN_MAX_MONEY,
+            fee_policy: FeePolicy::bitcoin(),
+            script_templates: Vec::new(),
+            segwit_enabled: true,
+            allowed_sighash_types: AllowedSighashTypes::AllDefined,
+            fork_id: None,
+        }
+    }
+
+    /// Litecoin P2SH addresses used the Bitcoin `0x05` prefix ("3...") before switching to `0x32` ("M...").
+    pub fn litecoin_mainnet() -> Self {
+        ChainParams {
+            p2pkh_prefix: 48,
+            p2sh_prefix: 50,
+            legacy_p2sh_prefixes: vec![5],
+            hrp: "ltc".to_string(),
+            legacy_hrps: Vec::new(),
+            max_money: LITECOIN_MAX_MONEY,
+            fee_policy: FeePolicy::litecoin(),
+            script_templates: Vec::new(),
+            segwit_enabled: true,
+            allowed_sighash_types: AllowedSighashTypes::AllDefined,
+            fork_id: None,
+        }
+    }
+
+    /// Dogecoin has not activated segwit, so there are no bech32 addresses.
+    pub fn dogecoin_mainnet() -> Self {
+        ChainParams {
+            p2pkh_prefix: 30,
+            p2sh_prefix: 22,
+            legacy_p2sh_prefixes: Vec::new(),
+            hrp: String::new(),
+            legacy_hrps: Vec::new(),
+            max_money: DOGECOIN_MAX_MONEY,
+            fee_policy: FeePolicy::dogecoin(),
+            script_templates: Vec::new(),
+            segwit_enabled: false,
+            allowed_sighash_types: AllowedSighashTypes::AllDefined,
+            fork_id: None,
+        }
+    }
+
+    /// Zcash transparent addresses have two-byte prefixes, `0x1CB8` ("t1...") and `0x1CBD` ("t3...").
+    /// The first byte is the registry `staticPrefix`, so only the second one is declared here.
+    /// Zcash has no segwit, and ZIP-244 restricts the sighash types, see [`AllowedSighashTypes::zcash`].
+    pub fn zcash_mainnet() -> Self {
+        ChainParams {
+            p2pkh_prefix: 184,
+            p2sh_prefix: 189,
+            legacy_p2sh_prefixes: Vec::new(),
+            hrp: String::new(),
+            legacy_hrps: Vec::new(),
+            max_money: BITCOIN_MAX_MONEY,
+            fee_policy: FeePolicy::bitcoin(),
+            script_templates: Vec::new(),
+            segwit_enabled: false,
+            allowed_sighash_types: AllowedSighashTypes::zcash(),
+            fork_id: None,
+        }
+    }
+
+    /// Whether the chain signatures commit to the `SIGHASH_FORK_ID` flag.
+    pub fn supports_fork_id(&self) -> bool {
+        self.fork_id.is_some()
+    }
+
+    /// Creates a sighash type strictly, see [`SighashType::from_u32`].
+    /// The `SIGHASH_FORK_ID` flag is accepted only if the chain supports it,
+    /// and the fork id payload must be the [`ChainParams::fork_id`] of the chain.
+    pub fn sighash_type(&self, u: u32) -> SigningResult<SighashType> {
+        let sighash_ty = SighashType::from_u32(u)?;
+        match (sighash_ty.fork_id_value(), self.fork_id) {
+            (None, _) => Ok(sighash_ty),
+            (Some(_), None) => SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                .context(format!(
+                    "Sighash type {u:#x} has SIGHASH_FORK_ID set, but the chain has no fork id"
+                )),
+            (Some(actual), Some(expected)) if actual != expected => {
+                SigningError::err(SigningErrorType::Error_invalid_sighash_type).context(format!(
+                    "Sighash type {u:#x} has fork id {actual}, but the chain expects {expected}"
+                ))
+            },
+            (Some(_), Some(_)) => Ok(sighash_ty),
+        }
+    }
+
+    /// Whether the given prefix is either the canonical or a deprecated P2SH prefix.
+    pub fn is_p2sh_prefix(&self, prefix: u8) -> bool {
+        prefix == self.p2sh_prefix || self.legacy_p2sh_prefixes.contains(&prefix)
+    }
+
+    /// Parses a legacy address of this chain and notes if it uses a deprecated P2SH prefix.
+    pub fn diagnose_legacy_address(&self, s: &str) -> AddressResult<LegacyAddressDiagnostics> {
+        let address = LegacyAddress::from_str(s)?;
+        let prefix = address.prefix();
+
+        let deprecated_prefix = if prefix == self.p2pkh_prefix || prefix == self.p2sh_prefix {
+            None
+        } else if self.legacy_p2sh_prefixes.contains(&prefix) {
+            Some(prefix)
+        } else {
+            return Err(AddressError::UnexpectedAddressPrefix);
+        };
+
+        Ok(LegacyAddressDiagnostics {
+            address,
+            deprecated_prefix,
+        })
+    }
+
+    /// Converts a legacy address to the canonical form,
+    /// i.e. re-encodes P2SH addresses with a deprecated prefix using [`ChainParams::p2sh_prefix`].
+    pub fn to_canonical_legacy_address(
+        &self,
+        address: &LegacyAddress,
+    ) -> AddressResult<LegacyAddress> {
+        let prefix = address.prefix();
+        if prefix == self.p2pkh_prefix || prefix == self.p2sh_prefix {
+            address.with_prefix(prefix)
+        } else if self.legacy_p2sh_prefixes.contains(&prefix) {
+            address.with_prefix(self.p2sh_prefix)
+        } else {
+            Err(AddressError::UnexpectedAddressPrefix)
+        }
+    }
+
+    /// Whether the given HRP is either the canonical or a deprecated one.
+    pub fn is_accepted_hrp(&self, hrp: &str) -> bool {
+        hrp == self.hrp || self.legacy_hrps.iter().any(|legacy| legacy == hrp)
+    }
+
+    /// Parses a segwit or taproot address of this chain and notes if it uses a deprecated HRP.
+    pub fn diagnose_bech32_address(&self, s: &str) -> AddressResult<Bech32AddressDiagnostics> {
+        let address = if let Ok(segwit) = SegwitAddress::from_str(s) {
+            StandardBitcoinAddress::Segwit(segwit)
+        } else {
+            StandardBitcoinAddress::Taproot(TaprootAddress::from_str(s)?)
+        };
+        let hrp = match address {
+            StandardBitcoinAddress::Segwit(ref segwit) => segwit.hrp(),
+            StandardBitcoinAddress::Taproot(ref taproot) => taproot.hrp(),
+            StandardBitcoinAddress::Legacy(_) => return Err(AddressError::InvalidInput),
+        };
+
+        let deprecated_hrp = if hrp == self.hrp {
+            None
+        } else if self.is_accepted_hrp(hrp) {
+            Some(hrp.to_string())
+        } else {
+            return Err(AddressError::InvalidHrp);
+        };
+
+        Ok(Bech32AddressDiagnostics {
+            address,
+            deprecated_hrp,
+        })
+    }
+
+    /// Converts a segwit or taproot address to the canonical form,
+    /// i.e. re-encodes addresses with a deprecated HRP using [`ChainParams::hrp`].
+    pub fn to_canonical_bech32_address(
+        &self,
+        address: &StandardBitcoinAddress,
+    ) -> AddressResult<StandardBitcoinAddress> {
+        match address {
+            StandardBitcoinAddress::Segwit(segwit) if self.is_accepted_hrp(segwit.hrp()) => {
+                SegwitAddress::new(self.hrp.clone(), segwit.witness_program().to_vec())
+                    .map(StandardBitcoinAddress::Segwit)
+            },
+            StandardBitcoinAddress::Taproot(taproot) if self.is_accepted_hrp(taproot.hrp()) => {
+                TaprootAddress::new(self.hrp.clone(), taproot.witness_program().to_vec())
+                    .map(StandardBitcoinAddress::Taproot)
+            },
+            StandardBitcoinAddress::Segwit(_) | StandardBitcoinAddress::Taproot(_) => {
+                Err(AddressError::InvalidHrp)
+            },
+            StandardBitcoinAddress::Legacy(_) => Err(AddressError::InvalidInput),
+        }
+    }
+
+    /// Classifies the scriptPubkey by the built-in templates, see [`ScriptPubkeyType::classify`].
+    /// A non-standard scriptPubkey is then classified by the first matching
+    /// [`ChainParams::script_templates`], if any.
+    pub fn classify(&self, script_pubkey: &Script) -> ScriptPubkeyType {
+        match ScriptPubkeyType::classify(script_pubkey) {
+            ScriptPubkeyType::Custom => self
+                .match_script_template(script_pubkey)
+                .map_or(ScriptPubkeyType::Custom, |(template, _)| {
+                    template.script_type
+                }),
+            script_type => script_type,
+        }
+    }
+
+    /// Returns the address the given scriptPubkey pays to.
+    /// Returns `None` if the scriptPubkey has no address representation, e.g. P2PK or OP_RETURN.
+    pub fn address_of(&self, script_pubkey: &Script) -> Option<String> {
+        let bytes = script_pubkey.as_slice();
+        match ScriptPubkeyType::classify(script_pubkey) {
+            ScriptPubkeyType::P2PKH => LegacyAddress::new(self.p2pkh_prefix, &bytes[3..23])
+                .ok()
+                .map(|addr| addr.to_string()),
+            ScriptPubkeyType::P2SH => LegacyAddress::new(self.p2sh_prefix, &bytes[2..22])
+                .ok()
+                .map(|addr| addr.to_string()),
+            ScriptPubkeyType::P2WPKH | ScriptPubkeyType::P2WSH => {
+                SegwitAddress::new(self.hrp.clone(), bytes[2..].to_vec())
+                    .ok()
+                    .map(|addr| addr.to_string())
+            },
+            ScriptPubkeyType::P2TR => TaprootAddress::new(self.hrp.clone(), bytes[2..].to_vec())
+                .ok()
+                .map(|addr| addr.to_string()),
+            ScriptPubkeyType::Custom => self.template_address_of(script_pubkey),
+            ScriptPubkeyType::P2PK | ScriptPubkeyType::OpReturn | ScriptPubkeyType::Timelocked => {
+                None
+            },
+        }
+    }
+
+    /// Returns the address of a scriptPubkey matched by one of the [`ChainParams::script_templates`].
+    fn template_address_of(&self, script_pubkey: &Script) -> Option<String> {
+        let (template, hash) = self.match_script_template(script_pubkey)?;
+        let prefix = match template.address {
+            TemplateAddress::P2PKH => self.p2pkh_prefix,
+            TemplateAddress::P2SH => self.p2sh_prefix,
+            TemplateAddress::None => return None,
+        };
+        LegacyAddress::new(prefix, hash?.as_slice())
+            .ok()
+            .map(|addr| addr.to_string())
+    }
+
+    fn match_script_template(
+        &self,
+        script_pubkey: &Script,
+    ) -> Option<(&ScriptTemplate, Option<H160>)> {
+        self.script_templates.iter().find_map(|template| {
+            template
+                .match_script(script_pubkey)
+                .map(|hash| (template, hash))
+        })
+    }
+}
+
+/// A parsed segwit or taproot address along with the notes about its encoding.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Bech32AddressDiagnostics {
+    pub address: StandardBitcoinAddress,
+    /// The deprecated HRP the address is encoded with, if any.
+    /// Such an address is still valid, but should be shown in the canonical form.
+    pub deprecated_hrp: Option<String>,
+}
+
+/// A parsed legacy address along with the notes about its encoding.
+#[derive(Debug, Eq, PartialEq)]
+pub struct LegacyAddressDiagnostics {
+    pub address: LegacyAddress,
+    /// The deprecated P2SH prefix the address is encoded with, if any.
+    /// Such an address is still valid, but should be shown in the canonical form.
+    pub deprecated_prefix: Option<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    #[track_caller]
+    fn test_address_of(params: &ChainParams, script_pubkey: &str, expected: Option<&str>) {
+        let script_pubkey = Script::from(script_pubkey.decode_hex().unwrap());
+        assert_eq!(params.address_of(&script_pubkey).as_deref(), expected);
+    }
+
+    #[test]
+    fn test_address_of() {
+        let mainnet = ChainParams::bitcoin_mainnet();
+        let testnet = ChainParams::bitcoin_testnet();
+
+        let p2pkh = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac";
+        test_address_of(&mainnet, p2pkh, Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+        test_address_of(&testnet, p2pkh, Some("mpXwg4jMtRhuSpVq4xS3HFHmCmWp9NyGKt"));
+        test_address_of(
+            &mainnet,
+            "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87",
+            Some("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"),
+        );
+        test_address_of(
+            &testnet,
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            Some("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"),
+        );
+        test_address_of(
+            &mainnet,
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            Some("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"),
+        );
+        // P2PK and OP_RETURN have no address.
+        test_address_of(
+            &mainnet,
+            "21025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22fac",
+            None,
+        );
+        test_address_of(&mainnet, "6a0401020304", None);
+    }
+
+    #[test]
+    fn test_script_templates() {
+        // A partner chain ends its P2PKH scripts with `OP_CHECKSIGVERIFY OP_1`.
+        let template = ScriptTemplate::new(
+            "OP_DUP OP_HASH160 <20> OP_EQUALVERIFY OP_CHECKSIGVERIFY OP_1",
+            ScriptPubkeyType::P2PKH,
+            TemplateAddress::P2PKH,
+        )
+        .unwrap();
+        let partner = ChainParams {
+            script_templates: vec![template],
+            ..ChainParams::bitcoin_mainnet()
+        };
+        let bitcoin = ChainParams::bitcoin_mainnet();
+
+        let p2pkh = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac";
+        let p2pkh_variant = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ad51";
+        let address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+        let script = |hex: &str| Script::from(hex.decode_hex().unwrap());
+
+        // Both variants are classified as P2PKH of the same address.
+        for params in [&partner, &bitcoin] {
+            assert_eq!(params.classify(&script(p2pkh)), ScriptPubkeyType::P2PKH);
+            test_address_of(params, p2pkh, Some(address));
+        }
+        assert_eq!(
+            partner.classify(&script(p2pkh_variant)),
+            ScriptPubkeyType::P2PKH
+        );
+        test_address_of(&partner, p2pkh_variant, Some(address));
+
+        // The variant is non-standard without the template.
+        assert_eq!(
+            bitcoin.classify(&script(p2pkh_variant)),
+            ScriptPubkeyType::Custom
+        );
+        test_address_of(&bitcoin, p2pkh_variant, None);
+
+        // The whole script must match.
+        let trailing = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ad5151";
+        assert_eq!(
+            partner.classify(&script(trailing)),
+            ScriptPubkeyType::Custom
+        );
+        test_address_of(&partner, trailing, None);
+
+        // A legacy address form requires a public key hash push.
+        ScriptTemplate::new(
+            "<33> OP_CHECKSIGVERIFY OP_1",
+            ScriptPubkeyType::P2PK,
+            TemplateAddress::P2PKH,
+        )
+        .unwrap_err();
+        let p2pk = ScriptTemplate::new(
+            "<33> OP_CHECKSIGVERIFY OP_1",
+            ScriptPubkeyType::P2PK,
+            TemplateAddress::None,
+        )
+        .unwrap();
+        let partner = ChainParams {
+            script_templates: vec![p2pk],
+            ..ChainParams::bitcoin_mainnet()
+        };
+        let p2pk_variant =
+            "21025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22fad51";
+        assert_eq!(
+            partner.classify(&script(p2pk_variant)),
+            ScriptPubkeyType::P2PK
+        );
+        test_address_of(&partner, p2pk_variant, None);
+    }
+
+    #[test]
+    fn test_sighash_type_fork_id() {
+        let bitcoin = ChainParams::bitcoin_mainnet();
+        let bitcoin_gold = ChainParams {
+            fork_id: Some(79),
+            ..ChainParams::bitcoin_mainnet()
+        };
+        assert!(!bitcoin.supports_fork_id());
+        assert!(bitcoin_gold.supports_fork_id());
+
+        // The fork-id sighash is only accepted by the chains that support it.
+        assert_eq!(bitcoin.sighash_type(0x01).unwrap().raw_sighash(), 0x01);
+        let err = bitcoin.sighash_type(0x41).unwrap_err();
+        assert_eq!(
+            *err.error_type(),
+            SigningErrorType::Error_invalid_sighash_type
+        );
+
+        // The fork id payload must match the chain.
+        assert_eq!(
+            bitcoin_gold.sighash_type(0x4f41).unwrap().fork_id_value(),
+            Some(79)
+        );
+        let err = bitcoin_gold.sighash_type(0x41).unwrap_err();
+        assert_eq!(
+            *err.error_type(),
+            SigningErrorType::Error_invalid_sighash_type
+        );
+        bitcoin_gold.sighash_type(0x01).unwrap();
+
+        // Reserved bits are rejected regardless of the chain.
+        bitcoin_gold.sighash_type(0x4f45).unwrap_err();
+    }
+
+    #[test]
+    fn test_litecoin_p2sh_prefixes() {
+        let litecoin = ChainParams::litecoin_mainnet();
+        let p2sh = "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87";
+        let canonical = "MQMHBtvnBfxTzt3K2bdxgSE7qZPHSXWsGM";
+        let deprecated = "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy";
+
+        // Only the canonical form is generated.
+        test_address_of(&litecoin, p2sh, Some(canonical));
+
+        // Both forms are accepted.
+        let canonical_addr = LegacyAddress::from_str_checked(canonical, 48, &[50, 5]).unwrap();
+        let deprecated_addr = LegacyAddress::from_str_checked(deprecated, 48, &[50, 5]).unwrap();
+        assert_eq!(canonical_addr.payload(), deprecated_addr.payload());
+        assert!(litecoin.is_p2sh_prefix(canonical_addr.prefix()));
+        assert!(litecoin.is_p2sh_prefix(deprecated_addr.prefix()));
+
+        let diagnostics = litecoin.diagnose_legacy_address(canonical).unwrap();
+        assert_eq!(diagnostics.address, canonical_addr);
+        assert_eq!(diagnostics.deprecated_prefix, None);
+
+        let diagnostics = litecoin.diagnose_legacy_address(deprecated).unwrap();
+        assert_eq!(diagnostics.address, deprecated_addr);
+        assert_eq!(diagnostics.deprecated_prefix, Some(5));
+        assert_eq!(
+            litecoin
+                .to_canonical_legacy_address(&diagnostics.address)
+                .unwrap()
+                .to_string(),
+            canonical
+        );
+
+        // P2PKH addresses are never deprecated.
+        let p2pkh = "Lbg5DgLCmK2iN1m8ykyKiBgYaYsQ3S2BCr";
+        let diagnostics = litecoin.diagnose_legacy_address(p2pkh).unwrap();
+        assert_eq!(diagnostics.deprecated_prefix, None);
+
+        // Bitcoin doesn't accept the Litecoin prefix and vice versa.
+        let bitcoin = ChainParams::bitcoin_mainnet();
+        assert_eq!(
+            bitcoin.diagnose_legacy_address(canonical),
+            Err(AddressError::UnexpectedAddressPrefix)
+        );
+        assert_eq!(
+            litecoin.diagnose_legacy_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            Err(AddressError::UnexpectedAddressPrefix)
+        );
+        LegacyAddress::from_str_checked(canonical, 0, &[5]).unwrap_err();
+    }
+
+    #[test]
+    fn test_multiple_hrps() {
+        // Testnet that still accepts the regtest HRP.
+        let params = ChainParams {
+            legacy_hrps: vec!["bcrt".to_string()],
+            ..ChainParams::bitcoin_testnet()
+        };
+        let p2wpkh = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+        let canonical = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+        let deprecated = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+        // Only the canonical HRP is used to generate addresses.
+        test_address_of(&params, p2wpkh, Some(canonical));
+        assert!(params.is_accepted_hrp("tb"));
+        assert!(params.is_accepted_hrp("bcrt"));
+        assert!(!params.is_accepted_hrp("bc"));
+
+        let diagnostics = params.diagnose_bech32_address(canonical).unwrap();
+        assert_eq!(diagnostics.deprecated_hrp, None);
+        assert_eq!(diagnostics.address.to_string(), canonical);
+
+        let diagnostics = params.diagnose_bech32_address(deprecated).unwrap();
+        assert_eq!(diagnostics.deprecated_hrp.as_deref(), Some("bcrt"));
+        assert_eq!(diagnostics.address.to_string(), deprecated);
+        assert_eq!(
+            params
+                .to_canonical_bech32_address(&diagnostics.address)
+                .unwrap()
+                .to_string(),
+            canonical
+        );
+
+        // Taproot addresses are diagnosed too.
+        let taproot = "bcrt1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqvg32hk";
+        let diagnostics = params.diagnose_bech32_address(taproot).unwrap();
+        assert!(matches!(
+            diagnostics.address,
+            StandardBitcoinAddress::Taproot(_)
+        ));
+        assert_eq!(diagnostics.deprecated_hrp.as_deref(), Some("bcrt"));
+
+        // Mainnet HRP is not accepted.
+        assert_eq!(
+            params.diagnose_bech32_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(AddressError::InvalidHrp)
+        );
+        let testnet = ChainParams::bitcoin_testnet();
+        assert_eq!(
+            testnet.diagnose_bech32_address(deprecated),
+            Err(AddressError::InvalidHrp)
+        );
+    }
+}
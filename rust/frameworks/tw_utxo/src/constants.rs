@@ -2,6 +2,58 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::transaction::transaction_parts::Amount;
+
 /// A standard transaction is limited to 400k weight units (WU).
 /// https://bitcoin.stackexchange.com/questions/35570/what-is-the-maximum-number-of-inputs-outputs-a-transaction-can-have
 pub const MAX_TRANSACTION_WEIGHT: usize = 400_000;
+
+/// Maximum number of bytes of a script that can be executed.
+/// Outputs with larger scriptPubkey are unspendable.
+/// https://github.com/bitcoin/bitcoin/blob/v26.0/src/script/script.h#L39
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// A standard transaction is limited to 16k sigops cost (a fifth of the block limit).
+/// https://github.com/bitcoin/bitcoin/blob/v26.0/src/policy/policy.h
+pub const MAX_STANDARD_TX_SIGOPS_COST: usize = 16_000;
+
+/// The number of satoshis in one BTC.
+pub const COIN: Amount = 100_000_000;
+
+/// No amount larger than this (in satoshi) is valid on Bitcoin.
+/// https://github.com/bitcoin/bitcoin/blob/v26.0/src/consensus/amount.h#L26
+pub const BITCOIN_MAX_MONEY: Amount = 21_000_000 * COIN;
+
+/// No amount larger than this (in litoshi) is valid on Litecoin.
+/// https://github.com/litecoin-project/litecoin/blob/v0.21.2.2/src/amount.h#L26
+pub const LITECOIN_MAX_MONEY: Amount = 84_000_000 * COIN;
+
+/// No amount larger than this (in koinu) is valid on Dogecoin.
+/// https://github.com/dogecoin/dogecoin/blob/v1.14.6/src/amount.h#L29
+pub const DOGECOIN_MAX_MONEY: Amount = 10_000_000_000 * COIN;
+
+/// `nLockTime` values below the threshold are block heights, otherwise UNIX timestamps.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+/// The input sequence that disables both Replace-By-Fee signaling and `nLockTime`.
+pub const SEQUENCE_FINAL: u32 = 0xffffffff;
+
+/// The input sequence that enables `nLockTime` without signaling Replace-By-Fee.
+pub const SEQUENCE_ENABLE_LOCKTIME_NO_RBF: u32 = 0xfffffffe;
+
+/// The maximum input sequence that signals opt-in Replace-By-Fee.
+/// It enables `nLockTime`, but doesn't enforce a relative timelock.
+/// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
+pub const MAX_BIP125_RBF_SEQUENCE: u32 = 0xfffffffd;
+
+/// The version of the Topologically Restricted Until Confirmation (TRUC) transactions.
+/// https://github.com/bitcoin/bips/blob/master/bip-0431.mediawiki
+pub const TRUC_VERSION: i32 = 3;
+
+/// A TRUC transaction is limited to 10k vbytes.
+/// https://github.com/bitcoin/bitcoin/blob/v28.0/src/policy/truc_policy.h#L27
+pub const TRUC_MAX_VSIZE: usize = 10_000;
+
+/// A TRUC transaction spending an unconfirmed TRUC transaction is limited to 1k vbytes.
+/// https://github.com/bitcoin/bitcoin/blob/v28.0/src/policy/truc_policy.h#L30
+pub const TRUC_CHILD_MAX_VSIZE: usize = 1_000;
@@ -4,8 +4,10 @@
 
 use crate::dust::DustPolicy;
 use crate::script::standard_script::conditions;
+use crate::spec_location::SpecLocation;
 use crate::transaction::transaction_interface::{TransactionInterface, TxOutputInterface};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use crate::transaction::UtxoToSign;
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
 
@@ -22,15 +24,18 @@ impl<Transaction: TransactionInterface> DustFilter<Transaction> {
         }
     }
 
+    /// Whether the UTXO amount is less than the dust threshold.
+    pub fn is_dust_input(&self, utxo_args: &UtxoToSign) -> bool {
+        utxo_args.amount < self.dust_policy.dust_threshold()
+    }
+
     /// Filter dust UTXOs out.
     /// Returns an error if there are no valid UTXOs.
     pub fn filter_inputs(
         &self,
         mut transaction: UnsignedTransaction<Transaction>,
     ) -> SigningResult<UnsignedTransaction<Transaction>> {
-        let dust_threshold = self.dust_policy.dust_threshold();
-
-        transaction.retain_inputs(|_utxo, utxo_args| utxo_args.amount >= dust_threshold)?;
+        transaction.retain_inputs(|_utxo, utxo_args| !self.is_dust_input(utxo_args))?;
 
         Ok(transaction)
     }
@@ -42,7 +47,8 @@ impl<Transaction: TransactionInterface> DustFilter<Transaction> {
     ) -> SigningResult<()> {
         let dust_threshold = self.dust_policy.dust_threshold();
 
-        let has_dust_output = transaction.transaction().outputs().iter().any(|output| {
+        let outputs = transaction.transaction().outputs();
+        let dust_output = outputs.iter().enumerate().find(|(_, output)| {
             if conditions::is_op_return(output.script_pubkey()) {
                 // Ignore the OP_RETURN output value. It can (or even should) be 0.
                 return false;
@@ -50,8 +56,13 @@ impl<Transaction: TransactionInterface> DustFilter<Transaction> {
             output.value() < dust_threshold
         });
 
-        if has_dust_output {
-            return SigningError::err(SigningErrorType::Error_dust_amount_requested);
+        if let Some((output_index, output)) = dust_output {
+            return SigningError::err(SigningErrorType::Error_dust_amount_requested)
+                .context(format!(
+                    "Output #{output_index} amount {} is below dust {dust_threshold}",
+                    output.value()
+                ))
+                .context(SpecLocation::of_output(output_index, output));
         }
         Ok(())
     }
@@ -8,7 +8,7 @@ pub mod dust_filter;
 
 /// Transaction dust amount calculator.
 /// Later, we plan to add support for `DynamicDust` policy with a `min_relay_fee` amount.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DustPolicy {
     FixedAmount(Amount),
 }
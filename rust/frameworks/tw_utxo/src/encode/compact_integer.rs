@@ -2,9 +2,12 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
-use crate::encode::Encodable;
+use crate::encode::{Decodable, Encodable};
+use crate::weights;
 use std::ops::RangeInclusive;
+use tw_coin_entry::error::prelude::*;
 
 const ONE_BYTE_RANGE: RangeInclusive<u64> = 0..=0xFC;
 const TWO_BYTES_RANGE: RangeInclusive<u64> = 0xFD..=0xFFFF;
@@ -18,6 +21,12 @@ const EIGHT_BYTES_FLAG: u8 = 0xFF_u8;
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct CompactInteger(u64);
 
+impl CompactInteger {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
 impl From<usize> for CompactInteger {
     fn from(value: usize) -> Self {
         CompactInteger(value as u64)
@@ -40,18 +49,28 @@ impl Encodable for CompactInteger {
     }
 
     fn encoded_size(&self) -> usize {
-        const BYTE_FLAG: usize = 1;
+        weights::compact_size_len(self.0)
+    }
+}
 
-        let v = self.0;
-        if ONE_BYTE_RANGE.contains(&v) {
-            BYTE_FLAG
-        } else if TWO_BYTES_RANGE.contains(&v) {
-            BYTE_FLAG + 2
-        } else if FOUR_BYTES_RANGE.contains(&v) {
-            BYTE_FLAG + 4
-        } else {
-            BYTE_FLAG + 8
+impl Decodable for CompactInteger {
+    /// Decodes the integer rejecting non-minimal encodings, as Bitcoin Core does.
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let (v, min_range) = match reader.read::<u8>()? {
+            TWO_BYTES_FLAG => (reader.read::<u16>()? as u64, TWO_BYTES_RANGE),
+            FOUR_BYTES_FLAG => (reader.read::<u32>()? as u64, FOUR_BYTES_RANGE),
+            EIGHT_BYTES_FLAG => (
+                reader.read::<u64>()?,
+                *FOUR_BYTES_RANGE.end() + 1..=u64::MAX,
+            ),
+            byte => return Ok(CompactInteger(byte as u64)),
+        };
+
+        if v < *min_range.start() {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context(format!("Non-minimal compact integer encoding of '{v}'"));
         }
+        Ok(CompactInteger(v))
     }
 }
 
@@ -79,4 +98,35 @@ mod tests {
 
         assert_eq!(stream.out(), expected);
     }
+
+    #[test]
+    fn test_compact_integer_decode() {
+        let values = [
+            0_u64,
+            0xfc,
+            0xfd,
+            0xffff,
+            0x10000,
+            0xffff_ffff,
+            0x1_0000_0000,
+        ];
+        for v in values {
+            let mut stream = Stream::default();
+            stream.append(&CompactInteger(v));
+            let encoded = stream.out();
+
+            let mut reader = Reader::new(&encoded);
+            assert_eq!(reader.read::<CompactInteger>().unwrap(), CompactInteger(v));
+            assert!(reader.is_finished());
+        }
+
+        // Non-minimal encodings.
+        for encoded in [
+            vec![0xfd, 0xfc, 0x00],
+            vec![0xfe, 0xff, 0xff, 0x00, 0x00],
+            vec![0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00],
+        ] {
+            Reader::new(&encoded).read::<CompactInteger>().unwrap_err();
+        }
+    }
 }
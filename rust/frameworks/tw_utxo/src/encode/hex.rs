@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::encode::stream::Stream;
+use crate::encode::Encodable;
+use std::fmt;
+use std::io;
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+/// Size of the hex buffer on the stack. Hex is passed to the formatter in chunks of this size.
+const HEX_BUFFER_SIZE: usize = 256;
+
+/// Displays the serialized struct as a lowercase hex string.
+/// The struct is encoded on the fly, without allocating the serialized bytes or the hex string.
+///
+/// Created by [`Encodable::display_hex`].
+pub struct DisplayHex<'a, T> {
+    inner: &'a T,
+}
+
+impl<'a, T: Encodable> DisplayHex<'a, T> {
+    pub fn new(inner: &'a T) -> Self {
+        DisplayHex { inner }
+    }
+}
+
+impl<T: Encodable> fmt::Display for DisplayHex<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex_to(self.inner, f)
+    }
+}
+
+/// Encodes the struct and writes it to `out` as a lowercase hex string.
+pub fn write_hex_to<T, W>(t: &T, out: &mut W) -> fmt::Result
+where
+    T: Encodable,
+    W: fmt::Write + ?Sized,
+{
+    let mut hex_writer = HexWriter {
+        out,
+        buffer: [0; HEX_BUFFER_SIZE],
+        len: 0,
+    };
+
+    let mut stream = Stream::from_writer(&mut hex_writer);
+    stream.append(t);
+    stream.finish().map_err(|_| fmt::Error)
+}
+
+/// Adapts [`fmt::Write`] to [`io::Write`] hex encoding the written bytes.
+struct HexWriter<'a, W: ?Sized> {
+    out: &'a mut W,
+    buffer: [u8; HEX_BUFFER_SIZE],
+    len: usize,
+}
+
+impl<W: fmt::Write + ?Sized> HexWriter<'_, W> {
+    fn flush_buffer(&mut self) -> io::Result<()> {
+        let hex = std::str::from_utf8(&self.buffer[..self.len])
+            .expect("Buffer must contain hex characters only");
+        self.len = 0;
+        self.out
+            .write_str(hex)
+            .map_err(|_| io::Error::other("Error formatting hex"))
+    }
+}
+
+impl<W: fmt::Write + ?Sized> io::Write for HexWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            if self.len + 2 > HEX_BUFFER_SIZE {
+                self.flush_buffer()?;
+            }
+            self.buffer[self.len] = HEX_CHARS[(byte >> 4) as usize];
+            self.buffer[self.len + 1] = HEX_CHARS[(byte & 0x0f) as usize];
+            self.len += 2;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.len > 0 {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::compact_integer::CompactInteger;
+    use crate::encode::encode;
+    use tw_encoding::hex::ToHex;
+    use tw_memory::Data;
+
+    #[test]
+    fn test_display_hex() {
+        let data: Data = (0..=255).collect();
+        assert_eq!(data.display_hex().to_string(), encode(&data).to_hex());
+
+        // Longer than the hex buffer, flushed in several chunks.
+        let data: Data = (0..10_000).map(|i| (i % 251) as u8).collect();
+        assert_eq!(data.display_hex().to_string(), encode(&data).to_hex());
+
+        assert_eq!(
+            CompactInteger::from(0xfd_usize).display_hex().to_string(),
+            "fdfd00"
+        );
+        assert_eq!(format!("tx: {}", 1_u16.display_hex()), "tx: 0100");
+    }
+
+    #[test]
+    fn test_write_hex_to_error() {
+        struct FailingWriter;
+
+        impl fmt::Write for FailingWriter {
+            fn write_str(&mut self, _s: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let data: Data = vec![1, 2, 3];
+        assert_eq!(data.write_hex_to(&mut FailingWriter), Err(fmt::Error));
+    }
+}
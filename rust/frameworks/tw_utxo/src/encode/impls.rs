@@ -3,9 +3,11 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
-use crate::encode::Encodable;
-use byteorder::{LittleEndian, WriteBytesExt};
+use crate::encode::{Decodable, Encodable};
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use tw_coin_entry::error::prelude::*;
 use tw_hash::Hash;
 use tw_memory::Data;
 
@@ -21,6 +23,13 @@ impl Encodable for Data {
     }
 }
 
+impl Decodable for Data {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let len = reader.read_len(1)?;
+        reader.read_slice(len).map(<[u8]>::to_vec)
+    }
+}
+
 impl<const N: usize> Encodable for Hash<N> {
     #[inline]
     fn encode(&self, stream: &mut Stream) {
@@ -33,6 +42,15 @@ impl<const N: usize> Encodable for Hash<N> {
     }
 }
 
+impl<const N: usize> Decodable for Hash<N> {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let bytes = reader.read_slice(N)?;
+        Hash::try_from(bytes)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Slice length must be equal to the hash length")
+    }
+}
+
 impl Encodable for u8 {
     #[inline]
     fn encode(&self, s: &mut Stream) {
@@ -45,6 +63,13 @@ impl Encodable for u8 {
     }
 }
 
+impl Decodable for u8 {
+    #[inline]
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        Ok(reader.read_slice(1)?[0])
+    }
+}
+
 macro_rules! impl_encodable_for_int {
     ($int:ty, $size:literal, $write_fn:tt) => {
         impl Encodable for $int {
@@ -61,16 +86,33 @@ macro_rules! impl_encodable_for_int {
     };
 }
 
+macro_rules! impl_decodable_for_int {
+    ($int:ty, $size:literal, $read_fn:tt) => {
+        impl Decodable for $int {
+            #[inline]
+            fn decode(reader: &mut Reader) -> SigningResult<Self> {
+                Ok(LittleEndian::$read_fn(reader.read_slice($size)?))
+            }
+        }
+    };
+}
+
 impl_encodable_for_int!(i32, 4, write_i32);
 impl_encodable_for_int!(i64, 8, write_i64);
 impl_encodable_for_int!(u16, 2, write_u16);
 impl_encodable_for_int!(u32, 4, write_u32);
 impl_encodable_for_int!(u64, 8, write_u64);
 
+impl_decodable_for_int!(i32, 4, read_i32);
+impl_decodable_for_int!(i64, 8, read_i64);
+impl_decodable_for_int!(u16, 2, read_u16);
+impl_decodable_for_int!(u32, 4, read_u32);
+impl_decodable_for_int!(u64, 8, read_u64);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::encode::encode;
+    use crate::encode::{decode, encode};
     use tw_encoding::hex::{DecodeHex, ToHex};
 
     #[test]
@@ -102,4 +144,22 @@ mod tests {
         stream.append_raw_slice(&slice);
         assert_eq!(stream.out().to_hex(), "64000000");
     }
+
+    #[test]
+    fn test_decode_primitives() {
+        let bytes = "0102000300000004000000000000000145".decode_hex().unwrap();
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read::<u8>().unwrap(), 1);
+        assert_eq!(reader.read::<u16>().unwrap(), 2);
+        assert_eq!(reader.read::<u32>().unwrap(), 3);
+        assert_eq!(reader.read::<u64>().unwrap(), 4);
+        assert_eq!(reader.read::<Data>().unwrap(), vec![0x45]);
+        assert!(reader.is_finished());
+
+        assert_eq!(decode::<u32>(&[1, 0, 0, 0]).unwrap(), 1);
+        // Trailing bytes are not allowed.
+        decode::<u16>(&[1, 0, 0]).unwrap_err();
+        // Not enough bytes.
+        decode::<Data>(&[2, 0x45]).unwrap_err();
+    }
 }
@@ -2,11 +2,17 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::encode::hex::DisplayHex;
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
+use std::fmt;
+use tw_coin_entry::error::prelude::*;
 use tw_memory::Data;
 
 pub mod compact_integer;
+pub mod hex;
 pub mod impls;
+pub mod reader;
 pub mod stream;
 
 pub fn encode<T>(t: &T) -> Data
@@ -18,10 +24,50 @@ where
     stream.out()
 }
 
+/// Deserializes the struct from the given bytes.
+/// Returns an error if there are bytes left after the struct.
+pub fn decode<T>(bytes: &[u8]) -> SigningResult<T>
+where
+    T: Decodable,
+{
+    let mut reader = Reader::new(bytes);
+    let t = reader.read()?;
+    if !reader.is_finished() {
+        return SigningError::err(SigningErrorType::Error_input_parse).context(format!(
+            "Unexpected {} trailing bytes",
+            reader.remaining().len()
+        ));
+    }
+    Ok(t)
+}
+
 pub trait Encodable {
     /// Serialize the struct and appends it to the end of stream.
     fn encode(&self, stream: &mut Stream);
 
     /// Hint about the size of serialized struct.
     fn encoded_size(&self) -> usize;
+
+    /// Returns an object that displays the serialized struct as a hex string,
+    /// without allocating the serialized bytes. Useful for logging large transactions.
+    fn display_hex(&self) -> DisplayHex<'_, Self>
+    where
+        Self: Sized,
+    {
+        DisplayHex::new(self)
+    }
+
+    /// Serializes the struct and writes it to `out` as a hex string,
+    /// without allocating the serialized bytes.
+    fn write_hex_to<W: fmt::Write>(&self, out: &mut W) -> fmt::Result
+    where
+        Self: Sized,
+    {
+        hex::write_hex_to(self, out)
+    }
+}
+
+pub trait Decodable: Sized {
+    /// Deserializes the struct from the current position of the reader.
+    fn decode(reader: &mut Reader) -> SigningResult<Self>;
 }
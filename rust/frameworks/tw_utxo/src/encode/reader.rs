@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::encode::compact_integer::CompactInteger;
+use crate::encode::Decodable;
+use tw_coin_entry::error::prelude::*;
+
+/// Reader used for deserialization of Bitcoin structures.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data, position: 0 }
+    }
+
+    /// Deserializes the struct from the current position.
+    pub fn read<T>(&mut self) -> SigningResult<T>
+    where
+        T: Decodable,
+    {
+        T::decode(self)
+    }
+
+    /// Reads exactly `len` raw bytes.
+    pub fn read_slice(&mut self, len: usize) -> SigningResult<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .with_context(|| {
+                format!(
+                    "Unexpected end of data: expected {len} bytes at offset {}",
+                    self.position
+                )
+            })?;
+
+        let slice = &self.data[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    /// Reads a length prefix and checks that there are at least `len * min_item_size` bytes left,
+    /// so a malformed prefix can't cause a huge allocation.
+    pub fn read_len(&mut self, min_item_size: usize) -> SigningResult<usize> {
+        let len = self.read::<CompactInteger>()?.value();
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| len.saturating_mul(min_item_size) <= self.remaining().len())
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .with_context(|| format!("Length prefix '{len}' exceeds the remaining data"))?;
+        Ok(len)
+    }
+
+    /// Reads a list of deserializable structs prefixed with their number.
+    pub fn read_list<T>(&mut self, min_item_size: usize) -> SigningResult<Vec<T>>
+    where
+        T: Decodable,
+    {
+        let len = self.read_len(min_item_size)?;
+        (0..len).map(|_| self.read()).collect()
+    }
+
+    /// Returns the next byte without advancing the position.
+    pub fn peek_u8(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    /// The bytes that haven't been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_read_slice() {
+        let mut reader = Reader::new(&[1, 2, 3]);
+        assert_eq!(reader.read_slice(2).unwrap(), &[1, 2]);
+        assert_eq!(reader.peek_u8(), Some(3));
+        reader.read_slice(2).unwrap_err();
+        assert_eq!(reader.remaining(), &[3]);
+        assert_eq!(reader.read::<u8>().unwrap(), 3);
+        assert!(reader.is_finished());
+        assert_eq!(reader.peek_u8(), None);
+    }
+
+    #[test]
+    fn test_reader_read_len_exceeds_remaining() {
+        // The length prefix is `0xffffffff`, but there is a single byte left.
+        let mut reader = Reader::new(&[0xfe, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        reader.read_len(1).unwrap_err();
+    }
+}
@@ -8,17 +8,45 @@ use std::io;
 use std::io::Write;
 use tw_memory::Data;
 
-/// Stream used for serialization of Bitcoin structures
-#[derive(Default)]
-pub struct Stream {
-    buffer: Data,
+/// Stream used for serialization of Bitcoin structures.
+///
+/// Writes either to an in-memory buffer (see [`Stream::new`]) or directly to a writer
+/// (see [`Stream::from_writer`]) without materializing the serialized bytes.
+pub struct Stream<'a> {
+    backend: Backend<'a>,
 }
 
-impl Stream {
+enum Backend<'a> {
+    Buffer(Data),
+    Writer {
+        writer: &'a mut dyn Write,
+        /// The first error returned by the writer. Nothing is written after an error.
+        error: Option<io::Error>,
+    },
+}
+
+impl Default for Stream<'_> {
+    fn default() -> Self {
+        Stream::new()
+    }
+}
+
+impl<'a> Stream<'a> {
     /// New stream
     pub fn new() -> Self {
         Stream {
-            buffer: Data::default(),
+            backend: Backend::Buffer(Data::default()),
+        }
+    }
+
+    /// New stream that writes the serialized bytes to the given `writer`.
+    /// Use [`Stream::finish`] to get the result of writing.
+    pub fn from_writer(writer: &'a mut dyn Write) -> Self {
+        Stream {
+            backend: Backend::Writer {
+                writer,
+                error: None,
+            },
         }
     }
 
@@ -33,8 +61,17 @@ impl Stream {
 
     /// Appends raw bytes to the end of the stream.
     pub fn append_raw_slice(&mut self, bytes: &[u8]) -> &mut Self {
-        // discard error for now, since we write to simple vector
-        self.buffer.write_all(bytes).unwrap();
+        match self.backend {
+            Backend::Buffer(ref mut buffer) => buffer.extend_from_slice(bytes),
+            Backend::Writer {
+                ref mut writer,
+                ref mut error,
+            } => {
+                if error.is_none() {
+                    *error = writer.write_all(bytes).err();
+                }
+            },
+        }
         self
     }
 
@@ -48,19 +85,36 @@ impl Stream {
     }
 
     /// Full stream.
+    /// Returns an empty buffer if the stream writes to a writer.
     pub fn out(self) -> Data {
-        self.buffer
+        match self.backend {
+            Backend::Buffer(buffer) => buffer,
+            Backend::Writer { .. } => Data::default(),
+        }
+    }
+
+    /// Returns the first error occurred while writing, if any. Otherwise, flushes the writer.
+    pub fn finish(self) -> io::Result<()> {
+        match self.backend {
+            Backend::Buffer(_) => Ok(()),
+            Backend::Writer { writer, error } => match error {
+                Some(error) => Err(error),
+                None => writer.flush(),
+            },
+        }
     }
 }
 
-impl Write for Stream {
+impl Write for Stream<'_> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
-        self.buffer.write(buf)
+        // Errors of the writer are reported by `Stream::finish`.
+        self.append_raw_slice(buf);
+        Ok(buf.len())
     }
 
     #[inline]
     fn flush(&mut self) -> Result<(), io::Error> {
-        self.buffer.flush()
+        Ok(())
     }
 }
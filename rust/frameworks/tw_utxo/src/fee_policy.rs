@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Economic policy of a Bitcoin-like chain: dust, relay fee and fee sanity bounds.
+//! Forks differ enough (e.g. Dogecoin fees are about 1 DOGE/kB) that Bitcoin numbers must not be hardcoded.
+
+use crate::dust::DustPolicy;
+use crate::transaction::transaction_parts::Amount;
+use tw_coin_entry::error::prelude::*;
+
+/// Multiplier of the minimum relay fee that Bitcoin Core used to consider absurd.
+pub const DEFAULT_ABSURD_FEE_MULTIPLIER: Amount = 10_000;
+
+/// The fee rates are in the smallest units of the chain per vbyte.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeePolicy {
+    /// Computes the amount below which outputs (and UTXOs) are dust.
+    pub dust_policy: DustPolicy,
+    /// Transactions paying a lower fee rate are not relayed.
+    pub min_relay_fee_rate: Amount,
+    /// The highest fee rate a caller may request.
+    pub max_fee_rate: Amount,
+    /// The fee is absurd if it exceeds the minimum relay fee of the transaction multiplied by this.
+    /// `None` disables the check.
+    pub absurd_fee_multiplier: Option<Amount>,
+}
+
+impl FeePolicy {
+    /// Bitcoin Core defaults: 546 sats dust of a P2PKH output and 1 sat/vB minimum relay fee.
+    pub fn bitcoin() -> Self {
+        FeePolicy {
+            dust_policy: DustPolicy::FixedAmount(546),
+            min_relay_fee_rate: 1,
+            max_fee_rate: 1_000,
+            absurd_fee_multiplier: Some(DEFAULT_ABSURD_FEE_MULTIPLIER),
+        }
+    }
+
+    /// Litecoin Core defaults: ten times higher dust relay and minimum relay fees than Bitcoin.
+    pub fn litecoin() -> Self {
+        FeePolicy {
+            dust_policy: DustPolicy::FixedAmount(5_460),
+            min_relay_fee_rate: 10,
+            max_fee_rate: 10_000,
+            absurd_fee_multiplier: Some(DEFAULT_ABSURD_FEE_MULTIPLIER),
+        }
+    }
+
+    /// Dogecoin recommended policy: 1 DOGE/kB fee and 1 DOGE dust limit.
+    pub fn dogecoin() -> Self {
+        const KOINU_PER_DOGE: Amount = 100_000_000;
+
+        FeePolicy {
+            dust_policy: DustPolicy::FixedAmount(KOINU_PER_DOGE),
+            min_relay_fee_rate: KOINU_PER_DOGE / 1_000,
+            max_fee_rate: KOINU_PER_DOGE / 10,
+            absurd_fee_multiplier: Some(DEFAULT_ABSURD_FEE_MULTIPLIER),
+        }
+    }
+
+    /// Only the dust policy is applied, the fee rate and the fee are not checked.
+    pub fn unbounded(dust_policy: DustPolicy) -> Self {
+        FeePolicy {
+            dust_policy,
+            min_relay_fee_rate: 0,
+            max_fee_rate: Amount::MAX,
+            absurd_fee_multiplier: None,
+        }
+    }
+
+    /// Overrides the dust policy, e.g. with the dust threshold provided by the caller.
+    /// The fee rate and the fee bounds are kept.
+    pub fn with_dust_policy(self, dust_policy: DustPolicy) -> Self {
+        FeePolicy {
+            dust_policy,
+            ..self
+        }
+    }
+
+    pub fn dust_threshold(&self) -> Amount {
+        self.dust_policy.dust_threshold()
+    }
+
+    /// Checks if the requested fee rate is between the minimum relay and the maximum fee rates.
+    pub fn check_fee_rate(&self, fee_rate: Amount) -> SigningResult<()> {
+        if fee_rate < self.min_relay_fee_rate {
+            return SigningError::err(SigningErrorType::Error_wrong_fee).context(format!(
+                "Fee rate {fee_rate} is below the minimum relay fee rate {}",
+                self.min_relay_fee_rate
+            ));
+        }
+        if fee_rate > self.max_fee_rate {
+            return SigningError::err(SigningErrorType::Error_wrong_fee).context(format!(
+                "Fee rate {fee_rate} exceeds the maximum fee rate {}",
+                self.max_fee_rate
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks if the `fee` of a transaction of the given `vsize` is not absurd.
+    pub fn check_absurd_fee(&self, fee: Amount, vsize: usize) -> SigningResult<()> {
+        let Some(multiplier) = self.absurd_fee_multiplier else {
+            return Ok(());
+        };
+
+        let absurd_fee = (vsize as Amount)
+            .saturating_mul(self.min_relay_fee_rate)
+            .saturating_mul(multiplier);
+        if fee > absurd_fee {
+            return SigningError::err(SigningErrorType::Error_wrong_fee).context(format!(
+                "Fee {fee} is absurdly high: exceeds {absurd_fee} for a transaction of {vsize} vbytes"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rate_bounds() {
+        let bitcoin = FeePolicy::bitcoin();
+        bitcoin.check_fee_rate(1).unwrap();
+        bitcoin.check_fee_rate(1_000).unwrap();
+        bitcoin.check_fee_rate(0).unwrap_err();
+        bitcoin.check_fee_rate(1_001).unwrap_err();
+
+        // Bitcoin fee rate is too low for Dogecoin.
+        let dogecoin = FeePolicy::dogecoin();
+        dogecoin.check_fee_rate(100).unwrap_err();
+        dogecoin.check_fee_rate(100_000).unwrap();
+
+        let no_bounds = FeePolicy::unbounded(DustPolicy::FixedAmount(546));
+        no_bounds.check_fee_rate(0).unwrap();
+        no_bounds.check_fee_rate(Amount::MAX).unwrap();
+
+        // Overriding the dust policy keeps the fee rate bounds.
+        let dogecoin = FeePolicy::dogecoin().with_dust_policy(DustPolicy::FixedAmount(546));
+        assert_eq!(dogecoin.dust_threshold(), 546);
+        dogecoin.check_fee_rate(100).unwrap_err();
+        dogecoin.check_fee_rate(100_000).unwrap();
+    }
+
+    #[test]
+    fn test_absurd_fee() {
+        let bitcoin = FeePolicy::bitcoin();
+        bitcoin.check_absurd_fee(2_000_000, 200).unwrap();
+        bitcoin.check_absurd_fee(2_000_001, 200).unwrap_err();
+
+        let no_check = FeePolicy::unbounded(DustPolicy::FixedAmount(546));
+        no_check.check_absurd_fee(Amount::MAX, 200).unwrap();
+    }
+}
@@ -3,13 +3,25 @@
 // Copyright © 2017 Trust Wallet.
 
 pub mod address;
+pub mod bip158;
+pub mod chain_params;
 pub mod constants;
 pub mod dust;
 pub mod encode;
+pub mod fee_policy;
+pub mod merkle;
 pub mod modules;
+pub mod psbt;
+pub mod replay_protection;
 pub mod script;
+pub mod shuffle;
 pub mod sighash;
 pub mod signature;
 pub mod signing_mode;
+pub mod spec_location;
 pub mod spending_data;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod transaction;
+pub mod truc;
+pub mod weights;
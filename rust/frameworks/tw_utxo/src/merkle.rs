@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Compact proofs of payment: a transaction merkle branch checked against a block header merkle root.
+//!
+//! All hashes are expected in the internal byte order,
+//! i.e. reversed in relation to the hex displayed by block explorers.
+
+use crate::transaction::transaction_parts::Txid;
+use tw_hash::hasher::sha256_d;
+use tw_hash::H256;
+
+/// Computes the merkle root of a block transactions.
+/// Returns `None` if `txids` is empty.
+///
+/// The last node of a level with an odd number of nodes is paired with itself.
+pub fn compute_merkle_root(txids: &[Txid]) -> Option<H256> {
+    let mut level: Vec<H256> = txids.iter().map(|txid| txid.0).collect();
+    if level.is_empty() {
+        return None;
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let left = &pair[0];
+                let right = pair.get(1).unwrap_or(left);
+                hash_nodes(left, right)
+            })
+            .collect();
+    }
+    level.pop()
+}
+
+/// Checks if the transaction at the given `position` in a block is committed to the `merkle_root`.
+/// The `branch` contains the sibling nodes from the transaction level up to the root.
+///
+/// The last node of a level with an odd number of nodes is paired with itself,
+/// which makes different transaction lists result in the same root (CVE-2012-2459).
+/// So the branch is rejected if the claimed position relies on a node paired with itself:
+/// * a right node can never be a duplicate of its left sibling;
+/// * once the node is paired with itself, it's the last at its level,
+///   so it can't be followed by another sibling to its right at the upper levels.
+pub fn verify_merkle_branch(
+    txid: &Txid,
+    branch: &[H256],
+    position: u64,
+    merkle_root: &H256,
+) -> bool {
+    // The position doesn't fit the tree of the given height.
+    if branch.len() < u64::BITS as usize && position >> branch.len() != 0 {
+        return false;
+    }
+
+    let mut node = txid.0;
+    let mut is_last_at_level = false;
+    for (level, sibling) in branch.iter().enumerate() {
+        let is_right = level < u64::BITS as usize && (position >> level) & 1 == 1;
+        let is_duplicate = *sibling == node;
+
+        node = match (is_right, is_duplicate) {
+            // The node pretends to be a copy of the last node.
+            (true, true) => return false,
+            (true, false) => hash_nodes(sibling, &node),
+            (false, true) => {
+                is_last_at_level = true;
+                hash_nodes(&node, sibling)
+            },
+            // A node paired with itself at a lower level can't have a right sibling.
+            (false, false) if is_last_at_level => return false,
+            (false, false) => hash_nodes(&node, sibling),
+        };
+    }
+    node == *merkle_root
+}
+
+fn hash_nodes(left: &H256, right: &H256) -> H256 {
+    let mut data = Vec::with_capacity(H256::LEN * 2);
+    data.extend_from_slice(left.as_slice());
+    data.extend_from_slice(right.as_slice());
+    H256::try_from(sha256_d(&data).as_slice()).expect("sha256d must return 32 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txid(display_hex: &str) -> Txid {
+        Txid::from_display_hex(display_hex).unwrap()
+    }
+
+    fn node(display_hex: &str) -> H256 {
+        txid(display_hex).0
+    }
+
+    /// Bitcoin block #100000.
+    fn block_100000() -> (Vec<Txid>, H256) {
+        let txids = [
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ]
+        .map(txid)
+        .to_vec();
+        let merkle_root = node("f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766");
+        (txids, merkle_root)
+    }
+
+    /// Builds the merkle branch of the transaction at `position`.
+    fn merkle_branch(txids: &[Txid], mut position: usize) -> Vec<H256> {
+        let mut level: Vec<H256> = txids.iter().map(|txid| txid.0).collect();
+        let mut branch = Vec::new();
+        while level.len() > 1 {
+            let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+            branch.push(*sibling);
+            level = level
+                .chunks(2)
+                .map(|pair| hash_nodes(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            position /= 2;
+        }
+        branch
+    }
+
+    #[test]
+    fn test_compute_merkle_root_block_100000() {
+        let (txids, merkle_root) = block_100000();
+        assert_eq!(compute_merkle_root(&txids), Some(merkle_root));
+        assert_eq!(compute_merkle_root(&[]), None);
+
+        // The merkle root of a single transaction block is the txid itself.
+        assert_eq!(compute_merkle_root(&txids[..1]), Some(txids[0].0));
+    }
+
+    #[test]
+    fn test_verify_merkle_branch_block_100000() {
+        let (txids, merkle_root) = block_100000();
+
+        let branch = [
+            txids[3].0,
+            node("ccdafb73d8dcd0173d5d5c3c9a0770d0b3953db889dab99ef05b1907518cb815"),
+        ];
+        assert!(verify_merkle_branch(&txids[2], &branch, 2, &merkle_root));
+        assert_eq!(merkle_branch(&txids, 2), branch);
+
+        for (position, txid) in txids.iter().enumerate() {
+            let branch = merkle_branch(&txids, position);
+            assert!(verify_merkle_branch(
+                txid,
+                &branch,
+                position as u64,
+                &merkle_root
+            ));
+        }
+
+        // Wrong position, transaction, root or out of the tree.
+        assert!(!verify_merkle_branch(&txids[2], &branch, 3, &merkle_root));
+        assert!(!verify_merkle_branch(&txids[1], &branch, 2, &merkle_root));
+        assert!(!verify_merkle_branch(&txids[2], &branch, 2, &txids[0].0));
+        assert!(!verify_merkle_branch(&txids[2], &branch, 6, &merkle_root));
+    }
+
+    #[test]
+    fn test_verify_merkle_branch_odd_number_of_transactions() {
+        let (txids, _) = block_100000();
+        let txids = &txids[..3];
+        let merkle_root = compute_merkle_root(txids).unwrap();
+
+        // The last transaction is legitimately paired with itself.
+        let branch = merkle_branch(txids, 2);
+        assert_eq!(branch[0], txids[2].0);
+        assert!(verify_merkle_branch(&txids[2], &branch, 2, &merkle_root));
+    }
+
+    /// CVE-2012-2459: `[tx0, tx1, tx2]` and `[tx0, tx1, tx2, tx2]` have the same merkle root,
+    /// so the duplicated copy of `tx2` must not be provable at position 3.
+    #[test]
+    fn test_verify_merkle_branch_duplicate_node() {
+        let (txids, _) = block_100000();
+        let txids = &txids[..3];
+        let merkle_root = compute_merkle_root(txids).unwrap();
+
+        let mutated: Vec<Txid> = [txids, &txids[2..]].concat();
+        assert_eq!(compute_merkle_root(&mutated), Some(merkle_root));
+
+        let branch = merkle_branch(&mutated, 3);
+        assert!(!verify_merkle_branch(&txids[2], &branch, 3, &merkle_root));
+
+        // Five transactions: the 5th is paired with itself at two levels.
+        // Claiming it at position 4 is fine, but a subtree duplicated at the upper level is not.
+        let (mut txids, _) = block_100000();
+        txids.push(txid(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        ));
+        let merkle_root = compute_merkle_root(&txids).unwrap();
+        let branch = merkle_branch(&txids, 4);
+        assert!(verify_merkle_branch(&txids[4], &branch, 4, &merkle_root));
+
+        let mutated: Vec<Txid> = [&txids[..], &txids[4..], &txids[4..], &txids[4..]].concat();
+        assert_eq!(compute_merkle_root(&mutated), Some(merkle_root));
+        for position in 5..8 {
+            let branch = merkle_branch(&mutated, position);
+            assert!(!verify_merkle_branch(
+                &txids[4],
+                &branch,
+                position as u64,
+                &merkle_root
+            ));
+        }
+    }
+}
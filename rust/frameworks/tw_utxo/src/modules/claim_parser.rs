@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Recovers signatures and public keys from the scriptSig and witness of existing transactions.
+
+use crate::script::standard_script::conditions;
+use crate::script::standard_script::opcodes::OP_PUSHDATA4;
+use crate::script::{Script, Witness};
+use crate::signature::{is_valid_signature_encoding, parse_der_lax};
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::{H160, H256};
+use tw_memory::Data;
+
+/// Which ECDSA signature encodings are recognized.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DerMode {
+    /// Only strict DER signatures are recognized (BIP66).
+    Strict,
+    /// Non-canonical signatures, that were accepted before BIP66, are recognized too.
+    /// Such signatures are flagged with [`ParsedSignature::is_strict_der`] unset.
+    #[default]
+    Lax,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimType {
+    P2PK,
+    P2PKH,
+    P2SHMultisig,
+    P2SHP2WPKH,
+    P2WPKH,
+    /// The claim doesn't match the spent scriptPubkey, or is not supported.
+    Unknown,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsedSignature {
+    pub r: H256,
+    pub s: H256,
+    /// The sighash type byte as is, it's not validated.
+    pub sighash_type: u8,
+    /// Whether the signature is encoded as strict DER (BIP66).
+    pub is_strict_der: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsedClaim {
+    pub claim_type: ClaimType,
+    /// Signatures in the order they appear in the claim.
+    pub signatures: Vec<ParsedSignature>,
+    /// Public keys of the claim, or all the public keys of the multisig redeem script.
+    pub public_keys: Vec<Data>,
+    /// P2SH redeem script.
+    pub redeem_script: Option<Script>,
+}
+
+impl ParsedClaim {
+    pub fn unknown() -> ParsedClaim {
+        ParsedClaim {
+            claim_type: ClaimType::Unknown,
+            signatures: Vec::new(),
+            public_keys: Vec::new(),
+            redeem_script: None,
+        }
+    }
+
+    pub fn has_non_canonical_signatures(&self) -> bool {
+        self.signatures.iter().any(|sig| !sig.is_strict_der)
+    }
+}
+
+/// Parses P2PK, P2PKH, P2SH multisig, P2SH-P2WPKH and P2WPKH claims of existing transactions.
+/// Claims that don't match any of these patterns are classified as [`ClaimType::Unknown`].
+///
+/// Please note that neither signatures nor public keys are verified.
+pub struct ClaimParser;
+
+impl ClaimParser {
+    /// Parses the claim of the `script_pubkey` output, recognizing non-canonical signatures too.
+    pub fn parse(script_sig: &Script, witness: &Witness, script_pubkey: &Script) -> ParsedClaim {
+        Self::parse_with_mode(script_sig, witness, script_pubkey, DerMode::Lax)
+    }
+
+    pub fn parse_with_mode(
+        script_sig: &Script,
+        witness: &Witness,
+        script_pubkey: &Script,
+        mode: DerMode,
+    ) -> ParsedClaim {
+        let parsed = if let Some(pubkey) = conditions::match_p2pk(script_pubkey) {
+            Self::parse_p2pk(script_sig, witness, pubkey, mode)
+        } else if let Some(pubkey_hash) = conditions::match_p2pkh(script_pubkey) {
+            Self::parse_p2pkh(script_sig, witness, &pubkey_hash, mode)
+        } else if let Some(script_hash) = conditions::match_p2sh(script_pubkey) {
+            Self::parse_p2sh(script_sig, witness, &script_hash, mode)
+        } else if let Some(pubkey_hash) = conditions::match_p2wpkh(script_pubkey) {
+            if script_sig.is_empty() {
+                Self::parse_p2wpkh_witness(witness, &pubkey_hash, mode)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        parsed.unwrap_or_else(ParsedClaim::unknown)
+    }
+
+    /// `<sig>`
+    fn parse_p2pk(
+        script_sig: &Script,
+        witness: &Witness,
+        pubkey: Data,
+        mode: DerMode,
+    ) -> Option<ParsedClaim> {
+        if !witness.is_empty() {
+            return None;
+        }
+        let items = push_items(script_sig)?;
+        let [sig] = items[..] else {
+            return None;
+        };
+
+        Some(ParsedClaim {
+            claim_type: ClaimType::P2PK,
+            signatures: vec![parse_signature(sig, mode)?],
+            public_keys: vec![pubkey],
+            redeem_script: None,
+        })
+    }
+
+    /// `<sig> <pubkey>`
+    fn parse_p2pkh(
+        script_sig: &Script,
+        witness: &Witness,
+        pubkey_hash: &H160,
+        mode: DerMode,
+    ) -> Option<ParsedClaim> {
+        if !witness.is_empty() {
+            return None;
+        }
+        let items = push_items(script_sig)?;
+        let [sig, pubkey] = items[..] else {
+            return None;
+        };
+        if hash160(pubkey) != *pubkey_hash {
+            return None;
+        }
+
+        Some(ParsedClaim {
+            claim_type: ClaimType::P2PKH,
+            signatures: vec![parse_signature(sig, mode)?],
+            public_keys: vec![pubkey.to_vec()],
+            redeem_script: None,
+        })
+    }
+
+    /// `OP_0 <sig_1> ... <sig_m> <redeem_script>` or `<p2wpkh_redeem_script>`.
+    fn parse_p2sh(
+        script_sig: &Script,
+        witness: &Witness,
+        script_hash: &H160,
+        mode: DerMode,
+    ) -> Option<ParsedClaim> {
+        let items = push_items(script_sig)?;
+        let (redeem_script, items) = items.split_last()?;
+        if hash160(redeem_script) != *script_hash {
+            return None;
+        }
+        let redeem_script = Script::from(redeem_script.to_vec());
+
+        if let Some(pubkey_hash) = conditions::match_p2wpkh(&redeem_script) {
+            if !items.is_empty() {
+                return None;
+            }
+            let parsed = Self::parse_p2wpkh_witness(witness, &pubkey_hash, mode)?;
+            return Some(ParsedClaim {
+                claim_type: ClaimType::P2SHP2WPKH,
+                redeem_script: Some(redeem_script),
+                ..parsed
+            });
+        }
+
+        let (required, public_keys) = conditions::match_multisig(&redeem_script)?;
+        // The first item is consumed by `OP_CHECKMULTISIG` due to the off-by-one bug.
+        let (_dummy, sigs) = items.split_first()?;
+        if !witness.is_empty() || sigs.len() != required {
+            return None;
+        }
+
+        let signatures = sigs
+            .iter()
+            .map(|sig| parse_signature(sig, mode))
+            .collect::<Option<Vec<_>>>()?;
+        Some(ParsedClaim {
+            claim_type: ClaimType::P2SHMultisig,
+            signatures,
+            public_keys,
+            redeem_script: Some(redeem_script),
+        })
+    }
+
+    /// `<sig> <pubkey>` witness items.
+    fn parse_p2wpkh_witness(
+        witness: &Witness,
+        pubkey_hash: &H160,
+        mode: DerMode,
+    ) -> Option<ParsedClaim> {
+        let [sig, pubkey] = witness.as_items() else {
+            return None;
+        };
+        if hash160(pubkey.as_slice()) != *pubkey_hash {
+            return None;
+        }
+
+        Some(ParsedClaim {
+            claim_type: ClaimType::P2WPKH,
+            signatures: vec![parse_signature(sig.as_slice(), mode)?],
+            public_keys: vec![pubkey.to_vec()],
+            redeem_script: None,
+        })
+    }
+}
+
+/// Returns the pushed data items if the script contains data pushes only.
+fn push_items(script: &Script) -> Option<Vec<&[u8]>> {
+    script
+        .push_only_ops()?
+        .into_iter()
+        .map(|(opcode, data)| {
+            if opcode <= OP_PUSHDATA4 {
+                Some(data)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parses a signature followed by the sighash type byte.
+fn parse_signature(sig: &[u8], mode: DerMode) -> Option<ParsedSignature> {
+    let (sighash_type, der) = sig.split_last()?;
+    let is_strict_der = is_valid_signature_encoding(sig);
+    if mode == DerMode::Strict && !is_strict_der {
+        return None;
+    }
+
+    let (r, s) = parse_der_lax(der)?;
+    Some(ParsedSignature {
+        r,
+        s,
+        sighash_type: *sighash_type,
+        is_strict_der,
+    })
+}
+
+fn hash160(data: &[u8]) -> H160 {
+    H160::try_from(sha256_ripemd(data).as_slice()).expect("sha256_ripemd must return 20 bytes")
+}
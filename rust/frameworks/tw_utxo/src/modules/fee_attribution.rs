@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Attribution of the network fee of a planned transaction to its outputs,
+//! e.g. for cost accounting of batched payouts.
+
+use crate::encode::Encodable;
+use crate::modules::utxo_selector::SelectResult;
+use crate::transaction::transaction_interface::{TransactionInterface, TxOutputInterface};
+use crate::transaction::transaction_parts::Amount;
+use serde_json::{json, Value as Json};
+use tw_coin_entry::error::prelude::*;
+
+/// The share of the network fee attributable to every output of a transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeAttribution {
+    /// `(output_index, attributed_fee)` in the outputs order.
+    /// The attributed fees sum exactly to the total fee.
+    pub fees: Vec<(usize, Amount)>,
+}
+
+impl FeeAttribution {
+    /// Attributes the estimated fee of the plan to the outputs of the planned transaction.
+    pub fn from_plan<Transaction: TransactionInterface>(
+        result: &SelectResult<Transaction>,
+    ) -> SigningResult<FeeAttribution> {
+        let outputs: Vec<_> = result
+            .unsigned_tx
+            .transaction()
+            .outputs()
+            .iter()
+            .map(|output| (output.encoded_size(), output.value()))
+            .collect();
+
+        Self::attribute(
+            result.plan.fee_estimate,
+            result.plan.vsize_estimate,
+            &outputs,
+        )
+    }
+
+    /// Distributes the `total_fee` across the `outputs` given as `(size, value)` pairs.
+    ///
+    /// Every output is weighted by its size plus an equal share of the fixed overhead,
+    /// i.e. the part of `vsize` not taken by the outputs (version, inputs, witnesses etc).
+    /// The shares are rounded down, and the remainder is assigned to the output
+    /// with the largest value, the first one if there are several.
+    pub fn attribute(
+        total_fee: Amount,
+        vsize: usize,
+        outputs: &[(usize, Amount)],
+    ) -> SigningResult<FeeAttribution> {
+        let Some(largest_idx) = largest_output(outputs) else {
+            if total_fee == 0 {
+                return Ok(FeeAttribution::default());
+            }
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("There are no outputs to attribute the fee to");
+        };
+
+        let outputs_size: usize = outputs.iter().map(|(size, _)| size).sum();
+        let overhead = vsize.saturating_sub(outputs_size) as u128;
+
+        // Scale the weights by the number of outputs to keep the overhead shares integer.
+        let outputs_count = outputs.len() as u128;
+        let total_weight = (outputs_size as u128 + overhead) * outputs_count;
+
+        let mut fees: Vec<(usize, Amount)> = outputs
+            .iter()
+            .enumerate()
+            .map(|(output_idx, (size, _))| {
+                let weight = *size as u128 * outputs_count + overhead;
+                let fee = (total_fee as u128 * weight)
+                    .checked_div(total_weight)
+                    .unwrap_or_default();
+                // The share doesn't exceed `total_fee`, so it fits.
+                (output_idx, fee as Amount)
+            })
+            .collect();
+
+        let attributed: Amount = fees.iter().map(|(_, fee)| fee).sum();
+        fees[largest_idx].1 += total_fee - attributed;
+
+        Ok(FeeAttribution { fees })
+    }
+
+    /// The sum of the attributed fees.
+    pub fn total(&self) -> Amount {
+        self.fees.iter().map(|(_, fee)| fee).sum()
+    }
+
+    /// Returns the fees as a list of `{ "output_index", "fee" }` objects.
+    pub fn to_json(&self) -> Json {
+        let fees: Vec<_> = self
+            .fees
+            .iter()
+            .map(|(output_index, fee)| {
+                json!({
+                    "output_index": output_index,
+                    "fee": fee,
+                })
+            })
+            .collect();
+        Json::Array(fees)
+    }
+}
+
+// Convenience function: returns the index of the first output with the largest value.
+fn largest_output(outputs: &[(usize, Amount)]) -> Option<usize> {
+    let mut largest: Option<usize> = None;
+    for (output_idx, (_, value)) in outputs.iter().enumerate() {
+        match largest {
+            Some(largest_idx) if outputs[largest_idx].1 >= *value => (),
+            _ => largest = Some(output_idx),
+        }
+    }
+    largest
+}
@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Replace-By-Fee (BIP125) fee bumping of a transaction that has no change output
+//! to shave the fee from, by adding extra inputs.
+
+use crate::constants::MAX_TRANSACTION_WEIGHT;
+use crate::dust::DustPolicy;
+use crate::modules::fee_estimator::FeeEstimator;
+use crate::modules::utxo_selector::{ExcludedUtxo, InputSelector, SelectPlan, SelectionPolicy};
+use crate::script::{Script, Witness};
+use crate::sighash::{SighashBase, SighashType};
+use crate::transaction::transaction_interface::{
+    TransactionInterface, TxInputInterface, TxOutputInterface,
+};
+use crate::transaction::transaction_parts::Amount;
+use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use crate::transaction::UtxoToSign;
+use std::marker::PhantomData;
+use tw_coin_entry::error::prelude::*;
+
+/// Bitcoin Core default `-incrementalrelayfee` in satoshis per vbyte.
+pub const DEFAULT_INCREMENTAL_RELAY_FEE: Amount = 1;
+
+/// The selection policy of the candidates if not set in the request.
+/// BIP125 rule 2 forbids spending new unconfirmed UTXOs in the replacement.
+pub const DEFAULT_BUMP_SELECTION_POLICY: SelectionPolicy = SelectionPolicy::OnlyConfirmed;
+
+/// A UTXO that can be added to the replacement transaction.
+/// Its confirmation metadata is taken from [`UtxoToSign::confirmations`].
+pub struct BumpCandidate<Transaction: TransactionInterface> {
+    pub input: Transaction::Input,
+    pub arg: UtxoToSign,
+}
+
+/// Add-input fee bump request.
+pub struct AddInputsRequest<Transaction: TransactionInterface> {
+    /// The transaction to be replaced. It must signal Replace-By-Fee.
+    pub original: UnsignedTransaction<Transaction>,
+    pub candidates: Vec<BumpCandidate<Transaction>>,
+    pub input_selector: InputSelector,
+    /// The fee rate of the replacement transaction.
+    pub fee_per_vbyte: Amount,
+    /// The replacement must pay at least `original_fee + incremental_relay_fee * vsize` (BIP125 rule 4).
+    pub incremental_relay_fee: Amount,
+    pub dust_policy: DustPolicy,
+    /// If set, the surplus above dust is sent to the change output.
+    /// The surplus is absorbed into the fee only if it's dust,
+    /// so the request fails if there is no change output to send a larger surplus to.
+    pub change_output: Option<Transaction::Output>,
+    /// Which candidates can be added, [`DEFAULT_BUMP_SELECTION_POLICY`] if not set.
+    /// Allowing unconfirmed candidates breaks BIP125 rule 2.
+    pub selection_policy: Option<SelectionPolicy>,
+}
+
+pub struct FeeBumpResult<Transaction> {
+    /// The replacement transaction with the original inputs and outputs followed by
+    /// the added inputs and the change output if any.
+    pub unsigned_tx: UnsignedTransaction<Transaction>,
+    pub plan: SelectPlan,
+    /// The fee paid by the original transaction.
+    pub original_fee: Amount,
+    /// Indexes of the original inputs whose signatures remain valid in the replacement,
+    /// i.e. signed with `SIGHASH_ANYONECANPAY` that doesn't commit to the changed outputs.
+    pub reusable_signatures: Vec<usize>,
+    /// Indexes of the inputs that must be signed, including all the added inputs.
+    pub inputs_to_sign: Vec<usize>,
+}
+
+pub struct FeeBumper<Transaction> {
+    _phantom: PhantomData<Transaction>,
+}
+
+impl<Transaction> FeeBumper<Transaction>
+where
+    Transaction: TransactionInterface,
+{
+    /// * Checks if the original transaction signals Replace-By-Fee
+    /// * Selects enough candidates to cover the new fee, skipping the ones not allowed by the selection policy
+    /// * Pushes the change output if the surplus is not dust, or fails if there is no change output
+    /// * Finds out which signatures of the original transaction remain valid
+    pub fn add_inputs(
+        request: AddInputsRequest<Transaction>,
+    ) -> SigningResult<FeeBumpResult<Transaction>> {
+        let original = request.original;
+        if !original.transaction().signals_rbf() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The original transaction doesn't signal Replace-By-Fee");
+        }
+
+        let total_out = original.total_output()?;
+        let original_total_in = original.total_input()?;
+        let original_fee = original_total_in
+            .checked_sub(total_out)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("The original transaction outputs exceed its inputs")?;
+        let original_inputs_len = original.inputs().len();
+        let original_outputs_len = original.transaction().outputs().len();
+
+        let policy = request
+            .selection_policy
+            .unwrap_or(DEFAULT_BUMP_SELECTION_POLICY);
+        let (mut candidates, excluded) =
+            Self::usable_candidates(&original, request.candidates, policy)?;
+        match request.input_selector {
+            InputSelector::UseAll | InputSelector::InOrder => (),
+            InputSelector::Ascending => {
+                candidates.sort_by(|a, b| a.arg.amount.cmp(&b.arg.amount));
+            },
+            InputSelector::Descending => {
+                candidates.sort_by(|a, b| b.arg.amount.cmp(&a.arg.amount));
+            },
+        }
+
+        let mut inputs = original.inputs().to_vec();
+        let mut input_args = original.input_args().to_vec();
+        let mut replacement = original;
+
+        let mut total_in = original_total_in;
+        let mut tx_fee = 0;
+        let mut vsize_estimate = 0;
+        let mut total_covered = false;
+        for candidate in candidates {
+            total_in = total_in
+                .checked_add(candidate.arg.amount)
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction UTXO amounts is too big")?;
+            inputs.push(candidate.input);
+            input_args.push(candidate.arg);
+            replacement.set_inputs(inputs.clone(), input_args.clone())?;
+
+            let mut estimated_tx = replacement.estimate_transaction();
+            if let Some(ref change_output) = request.change_output {
+                // Take the change output into account to get the most max possible fee.
+                estimated_tx.push_output(change_output.clone());
+            }
+
+            if estimated_tx.weight() > MAX_TRANSACTION_WEIGHT {
+                return SigningError::err(SigningErrorType::Error_tx_too_big)
+                    .context("Too many UTXOs required to bump the fee");
+            }
+
+            vsize_estimate = estimated_tx.vsize();
+            tx_fee = Self::required_fee(
+                &estimated_tx,
+                request.fee_per_vbyte,
+                request.incremental_relay_fee,
+                original_fee,
+            )?;
+            let total_required = total_out
+                .checked_add(tx_fee)
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction output amounts and fee is too big")?;
+
+            if total_in >= total_required {
+                total_covered = true;
+                if request.input_selector != InputSelector::UseAll {
+                    break;
+                }
+            }
+        }
+
+        if !total_covered {
+            return SigningError::err(SigningErrorType::Error_not_enough_utxos).context(format!(
+                "Insufficient confirmed UTXOs to bump the fee. Available '{total_in}', required '{total_out}' + fee '{tx_fee}'"
+            ));
+        }
+
+        // Amounts are checked already, but use checked math to avoid panicking on underflow.
+        let mut change = total_in
+            .checked_sub(total_out)
+            .and_then(|remaining| remaining.checked_sub(tx_fee))
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Total input amount must cover the output amounts and fee")?;
+
+        let dust_threshold = request.dust_policy.dust_threshold();
+        let mut total_send = total_out;
+        match request.change_output {
+            Some(mut change_output) if change >= dust_threshold => {
+                change_output.set_value(change);
+                replacement.push_output(change_output);
+                total_send += change;
+            },
+            None if change >= dust_threshold => {
+                return SigningError::err(SigningErrorType::Error_wrong_fee).context(format!(
+                    "The surplus '{change}' would be burned as fee, specify a change output to receive it"
+                ));
+            },
+            // The change amount is dust, so it's absorbed into the fee.
+            _ => change = 0,
+        }
+
+        // Clear script_sig's and witnesses, as the replacement must be signed again.
+        for input in inputs.iter_mut() {
+            input.set_script_sig(Script::default());
+            input.set_witness(Witness::default());
+        }
+        replacement.set_inputs(inputs, input_args)?;
+
+        let outputs_changed = replacement.transaction().outputs().len() != original_outputs_len;
+        let (reusable_signatures, inputs_to_sign) =
+            (0..replacement.input_args().len()).partition(|index| {
+                *index < original_inputs_len
+                    && signature_remains_valid(
+                        replacement.input_args()[*index].sighash_ty,
+                        *index,
+                        original_outputs_len,
+                        outputs_changed,
+                    )
+            });
+
+        debug_assert!(total_send <= total_in);
+        let plan = SelectPlan {
+            total_spend: total_in,
+            total_send,
+            vsize_estimate,
+            fee_estimate: total_in - total_send,
+            change,
+            signals_rbf: replacement.transaction().signals_rbf(),
+            excluded,
+            replay_protection: replacement.replay_protection().cloned(),
+        };
+        Ok(FeeBumpResult {
+            unsigned_tx: replacement,
+            plan,
+            original_fee,
+            reusable_signatures,
+            inputs_to_sign,
+        })
+    }
+
+    /// Filters out the candidates not allowed by the `policy`, returning them as excluded,
+    /// and checks if the candidates are not spent by the original transaction already.
+    fn usable_candidates(
+        original: &UnsignedTransaction<Transaction>,
+        candidates: Vec<BumpCandidate<Transaction>>,
+        policy: SelectionPolicy,
+    ) -> SigningResult<(Vec<BumpCandidate<Transaction>>, Vec<ExcludedUtxo>)> {
+        let mut usable = Vec::with_capacity(candidates.len());
+        let mut excluded = Vec::new();
+        for (index, candidate) in candidates.into_iter().enumerate() {
+            let outpoint = candidate.input.previous_output();
+            if original
+                .inputs()
+                .iter()
+                .any(|input| input.previous_output() == outpoint)
+            {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Candidate #{index} is spent by the original transaction already"
+                ));
+            }
+
+            match policy.exclusion_reason(&candidate.arg) {
+                Some(reason) => excluded.push(ExcludedUtxo {
+                    index,
+                    previous_output: *outpoint,
+                    reason,
+                }),
+                None => usable.push(candidate),
+            }
+        }
+        Ok((usable, excluded))
+    }
+
+    /// The replacement must pay at least the given fee rate,
+    /// and more than the original transaction by the incremental relay fee (BIP125 rules 3 and 4).
+    fn required_fee(
+        estimated_tx: &Transaction,
+        fee_per_vbyte: Amount,
+        incremental_relay_fee: Amount,
+        original_fee: Amount,
+    ) -> SigningResult<Amount> {
+        let fee = FeeEstimator::estimate_fee(estimated_tx, fee_per_vbyte)?;
+        let min_fee = FeeEstimator::estimate_fee(estimated_tx, incremental_relay_fee)?
+            .checked_add(original_fee)
+            .or_tw_err(SigningErrorType::Error_wrong_fee)
+            .context("The replacement fee is too large")?;
+        Ok(fee.max(min_fee))
+    }
+}
+
+/// Whether the signature of an original input remains valid after adding inputs,
+/// and possibly the change output at the end of the outputs.
+fn signature_remains_valid(
+    sighash_ty: SighashType,
+    input_index: usize,
+    original_outputs_len: usize,
+    outputs_changed: bool,
+) -> bool {
+    // Commits to all the inputs.
+    if !sighash_ty.anyone_can_pay() {
+        return false;
+    }
+
+    match sighash_ty.base_type() {
+        SighashBase::All => !outputs_changed,
+        SighashBase::None => true,
+        // The original outputs are kept in place.
+        SighashBase::Single => input_index < original_outputs_len,
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::transaction::transaction_interface::TransactionInterface;
 use crate::transaction::transaction_parts::Amount;
+use crate::weights;
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
 
@@ -14,9 +15,7 @@ pub struct FeeEstimator<Transaction> {
 impl<Transaction: TransactionInterface> FeeEstimator<Transaction> {
     pub fn estimate_fee(tx: &Transaction, fee_rate: Amount) -> SigningResult<Amount> {
         let vsize = tx.vsize();
-        Amount::try_from(vsize)
-            .ok()
-            .and_then(|vsize| vsize.checked_mul(fee_rate))
+        weights::fee_for_vsize(vsize, fee_rate)
             .or_tw_err(SigningErrorType::Error_wrong_fee)
             .with_context(|| format!("feePerVByte is too large: '{vsize} * {fee_rate}' overflow"))
     }
@@ -3,47 +3,193 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::modules::sighash_computer::TaprootTweak;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::H264;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::sha2::sha256;
+use tw_hash::{H160, H256, H264};
 use tw_keypair::{ecdsa, schnorr};
+use tw_misc::traits::ToBytesZeroizing;
 
 /// Standard Bitcoin keys manager.
 /// Supports ecdsa and schnorr private keys.
-#[derive(Default)]
+///
+/// Every distinct private key is parsed once, and its public keys are derived once when the key is added,
+/// so that signing many inputs with the same key doesn't repeat the elliptic curve operations.
+///
+/// The manager doesn't rely on any process-global state: the schnorr keys are parsed, tweaked and signed
+/// with the secp256k1 context held by the manager. By default, it's the immutable
+/// [`schnorr::Secp256k1Context::shared`] context, see [`KeysManager::with_schnorr_context`] to use another one.
 pub struct KeysManager {
+    /// The secp256k1 context used for schnorr keys.
+    schnorr_context: Arc<schnorr::Secp256k1Context>,
+    /// Hashes of the added ecdsa private keys.
+    ecdsa_key_ids: HashSet<H256>,
     /// Ecdsa public to private keys.
     ecdsa_public_private_map: HashMap<H264, ecdsa::secp256k1::PrivateKey>,
+    /// Ecdsa public key hashes (`HASH160(compressed_pubkey)`) to public keys.
+    ecdsa_pubkey_hash_map: HashMap<H160, H264>,
+    /// Hashes of the added schnorr private keys.
+    schnorr_key_ids: HashSet<H256>,
     /// Schnorr private keys.
     schnorr_private_keys: Vec<schnorr::PrivateKey>,
+    /// Untweaked x-only public to private keys.
+    schnorr_public_private_map: HashMap<H256, schnorr::PrivateKey>,
+    /// P2TR key-path (tweaked without a merkle root) x-only public to tweaked private keys.
+    schnorr_key_path_map: HashMap<H256, schnorr::PrivateKey>,
+}
+
+impl Default for KeysManager {
+    fn default() -> Self {
+        KeysManager {
+            schnorr_context: Arc::clone(schnorr::Secp256k1Context::shared()),
+            ecdsa_key_ids: HashSet::default(),
+            ecdsa_public_private_map: HashMap::default(),
+            ecdsa_pubkey_hash_map: HashMap::default(),
+            schnorr_key_ids: HashSet::default(),
+            schnorr_private_keys: Vec::default(),
+            schnorr_public_private_map: HashMap::default(),
+            schnorr_key_path_map: HashMap::default(),
+        }
+    }
 }
 
 impl KeysManager {
+    /// Creates an empty keys manager using the given shared secp256k1 context for schnorr keys.
+    pub fn with_schnorr_context(schnorr_context: Arc<schnorr::Secp256k1Context>) -> KeysManager {
+        KeysManager {
+            schnorr_context,
+            ..KeysManager::default()
+        }
+    }
+
+    pub fn schnorr_context(&self) -> &schnorr::Secp256k1Context {
+        &self.schnorr_context
+    }
+
+    /// Adds an ecdsa private key. Does nothing if the same key has been added already.
     pub fn add_ecdsa_private(&mut self, private: ecdsa::secp256k1::PrivateKey) -> &mut Self {
-        self.ecdsa_public_private_map
-            .insert(private.public().compressed(), private);
+        if !self
+            .ecdsa_key_ids
+            .insert(key_id(&private.to_zeroizing_vec()))
+        {
+            return self;
+        }
+
+        let public = private.public().compressed();
+        self.ecdsa_pubkey_hash_map
+            .insert(pubkey_hash(&public), public);
+        self.ecdsa_public_private_map.insert(public, private);
         self
     }
 
+    /// Parses and adds an ecdsa private key.
+    /// The key is not parsed again if the same key has been added already.
+    pub fn add_ecdsa_private_bytes(&mut self, private: &[u8]) -> SigningResult<&mut Self> {
+        if self.ecdsa_key_ids.contains(&key_id(private)) {
+            return Ok(self);
+        }
+
+        let private = ecdsa::secp256k1::PrivateKey::try_from(private)
+            .into_tw()
+            .context("Invalid ecdsa secp256k1 private key")?;
+        Ok(self.add_ecdsa_private(private))
+    }
+
+    /// Adds a schnorr private key. Does nothing if the same key has been added already.
     pub fn add_schnorr_private(&mut self, private: schnorr::PrivateKey) -> &mut Self {
+        if !self
+            .schnorr_key_ids
+            .insert(key_id(&private.to_zeroizing_vec()))
+        {
+            return self;
+        }
+
+        let tweaked = private
+            .clone()
+            .tweak_with_context(&self.schnorr_context, None);
+        self.schnorr_key_path_map
+            .insert(tweaked.public().x_only().bytes(), tweaked);
+        self.schnorr_public_private_map
+            .insert(private.public().x_only().bytes(), private.clone());
         self.schnorr_private_keys.push(private);
         self
     }
 
+    /// Parses and adds a schnorr private key.
+    /// The key is not parsed again if the same key has been added already.
+    ///
+    /// `no_aux_rand` disables auxiliary random data when signing. ONLY recommended for testing.
+    pub fn add_schnorr_private_bytes(
+        &mut self,
+        private: &[u8],
+        no_aux_rand: bool,
+    ) -> SigningResult<&mut Self> {
+        if self.schnorr_key_ids.contains(&key_id(private)) {
+            return Ok(self);
+        }
+
+        let private =
+            schnorr::PrivateKey::try_from_slice_with_context(&self.schnorr_context, private)
+                .into_tw()
+                .context("Invalid schnorr private key")?;
+        if no_aux_rand {
+            Ok(self.add_schnorr_private(private.no_aux_rand()))
+        } else {
+            Ok(self.add_schnorr_private(private))
+        }
+    }
+
     pub fn get_ecdsa_private(
         &self,
         public: &ecdsa::secp256k1::PublicKey,
     ) -> SigningResult<&ecdsa::secp256k1::PrivateKey> {
-        let pubkey_bytes = public.compressed();
+        self.get_ecdsa_private_by_compressed(&public.compressed())
+    }
+
+    /// Gets an ecdsa private key by a serialized public key.
+    /// Compressed public keys are looked up as is, without being parsed.
+    pub fn get_ecdsa_private_by_bytes(
+        &self,
+        public: &[u8],
+    ) -> SigningResult<&ecdsa::secp256k1::PrivateKey> {
+        if let Ok(compressed) = H264::try_from(public) {
+            return self.get_ecdsa_private_by_compressed(&compressed);
+        }
 
+        let public = ecdsa::secp256k1::PublicKey::try_from(public)
+            .into_tw()
+            .context("Signing error: expected a valid signer ecdsa secp256k1 public key")?;
+        self.get_ecdsa_private(&public)
+    }
+
+    /// Gets an ecdsa private key by `HASH160` of its compressed public key.
+    pub fn get_ecdsa_private_by_pubkey_hash(
+        &self,
+        pubkey_hash: &H160,
+    ) -> SigningResult<&ecdsa::secp256k1::PrivateKey> {
+        let public = self
+            .ecdsa_pubkey_hash_map
+            .get(pubkey_hash)
+            .or_tw_err(SigningErrorType::Error_missing_private_key)
+            .with_context(|| format!("Cannot find a private key corresponding to the ecdsa public key hash: {pubkey_hash}"))?;
+        self.get_ecdsa_private_by_compressed(public)
+    }
+
+    fn get_ecdsa_private_by_compressed(
+        &self,
+        pubkey_bytes: &H264,
+    ) -> SigningResult<&ecdsa::secp256k1::PrivateKey> {
         self.ecdsa_public_private_map
-            .get(&pubkey_bytes)
+            .get(pubkey_bytes)
             .or_tw_err(SigningErrorType::Error_missing_private_key)
             .with_context(|| format!("Cannot find a private key corresponding to the ecdsa public key: {pubkey_bytes}"))
     }
 
     /// Gets a schnorr private key by an either tweaked or untweaked x-only public key.
-    /// The function iterates over the private keys, tweaks them if specified in `taproot_tweak`,
+    /// Untweaked and P2TR key-path keys are looked up in the cache. Otherwise, the function iterates
+    /// over the private keys, tweaks them with the merkle root specified in `taproot_tweak`,
     /// and returns `Ok(schnorr::PrivateKey)` if found.
     pub fn get_schnorr_private(
         &self,
@@ -52,25 +198,36 @@ impl KeysManager {
     ) -> SigningResult<schnorr::PrivateKey> {
         let pubkey_bytes = public.bytes();
 
-        for private_key in self.schnorr_private_keys.iter() {
-            match taproot_tweak {
-                Some(ref tweak) => {
-                    let tweaked_private = private_key.clone().tweak(tweak.merkle_root);
-                    if tweaked_private.public().x_only().bytes() == pubkey_bytes {
-                        return Ok(tweaked_private);
-                    }
-                    // Otherwise, continue searching for a private key.
-                },
-                None => {
-                    if private_key.public().x_only().bytes() == pubkey_bytes {
-                        return Ok(private_key.clone());
-                    }
-                    // Otherwise, continue searching for a private key.
-                },
-            }
-        }
+        let found = match taproot_tweak {
+            None => self.schnorr_public_private_map.get(&pubkey_bytes).cloned(),
+            Some(TaprootTweak { merkle_root: None }) => {
+                self.schnorr_key_path_map.get(&pubkey_bytes).cloned()
+            },
+            Some(TaprootTweak {
+                merkle_root: Some(merkle_root),
+            }) => self
+                .schnorr_private_keys
+                .iter()
+                .map(|private_key| {
+                    private_key
+                        .clone()
+                        .tweak_with_context(&self.schnorr_context, Some(*merkle_root))
+                })
+                .find(|tweaked_private| tweaked_private.public().x_only().bytes() == pubkey_bytes),
+        };
 
-        SigningError::err(SigningErrorType::Error_missing_private_key)
-            .context(format!("Cannot find a private key corresponding to the x-only schnorr public key: {pubkey_bytes}"))
+        found
+            .or_tw_err(SigningErrorType::Error_missing_private_key)
+            .with_context(|| format!("Cannot find a private key corresponding to the x-only schnorr public key: {pubkey_bytes}"))
     }
 }
+
+/// Identifies a private key without keeping a copy of its bytes.
+fn key_id(private: &[u8]) -> H256 {
+    H256::try_from(sha256(private).as_slice()).expect("sha256 must return 32 bytes")
+}
+
+fn pubkey_hash(public: &H264) -> H160 {
+    H160::try_from(sha256_ripemd(public.as_slice()).as_slice())
+        .expect("sha256_ripemd must return 20 bytes")
+}
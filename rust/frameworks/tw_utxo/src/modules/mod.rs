@@ -2,11 +2,20 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+pub mod claim_parser;
+pub mod fee_attribution;
+pub mod fee_bumper;
 pub mod fee_estimator;
 pub mod keys_manager;
+pub mod output_batcher;
 pub mod sighash_computer;
 pub mod sighash_verifier;
+pub mod signer_metrics;
+pub mod signing_session;
+pub mod taproot_spend_plan;
 pub mod tx_compiler;
+pub mod tx_diff;
 pub mod tx_planner;
 pub mod tx_signer;
+pub mod tx_summary;
 pub mod utxo_selector;
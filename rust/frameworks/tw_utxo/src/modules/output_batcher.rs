@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Splits a large payout into several transactions, each of them fitting the given
+//! weight budget and "max outputs" policy limits.
+
+use crate::script::Script;
+use crate::transaction::standard_transaction::TransactionOutput;
+use crate::transaction::transaction_parts::Amount;
+use crate::weights::{self, FeeRounding};
+use tw_coin_entry::error::prelude::*;
+
+/// Who pays the fee of every batch transaction.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FeeAllocation {
+    /// The fee is deducted from the recipient amounts proportionally to the amounts.
+    #[default]
+    Proportional,
+    /// The recipients receive the requested amounts, the fee is paid on top of them.
+    SenderPays,
+}
+
+/// Describes how every batch transaction is funded.
+/// All the batches are assumed to be funded the same way.
+#[derive(Clone, Debug)]
+pub struct FundingDescriptor {
+    /// Number of inputs spent by every batch transaction.
+    pub input_count: usize,
+    /// Weight of a single signed input including its witness,
+    /// e.g. [`InputClaim::weight`](crate::weights::InputClaim::weight).
+    pub input_weight: usize,
+    /// Whether the inputs have witness data.
+    pub segwit: bool,
+    /// If set, every batch transaction reserves space for a change output to the script.
+    pub change_script_pubkey: Option<Script>,
+    pub fee_per_vbyte: Amount,
+}
+
+/// A payout recipient.
+#[derive(Clone, Debug)]
+pub struct Recipient {
+    pub script_pubkey: Script,
+    pub amount: Amount,
+}
+
+/// A single batch transaction plan.
+#[derive(Clone, Debug)]
+pub struct BatchPlan {
+    /// Recipient outputs with the fee share deducted if [`FeeAllocation::Proportional`] is used.
+    /// The change output is not included as its amount depends on the UTXOs to be selected.
+    pub outputs: Vec<TransactionOutput>,
+    /// Indexes of the recipients in the original list, in the same order as [`BatchPlan::outputs`].
+    pub recipient_indexes: Vec<usize>,
+    /// The estimated transaction weight including the change output if requested.
+    pub weight_estimate: usize,
+    /// The estimated `vsize` in `vbytes`.
+    pub vsize_estimate: usize,
+    /// The estimated fee of the transaction.
+    pub fee_estimate: Amount,
+    /// Sum of the [`BatchPlan::outputs`] amounts.
+    pub total_send: Amount,
+    /// Amount to be spent from the funding inputs excluding change, i.e. `total_send + fee_estimate`.
+    pub total_spend: Amount,
+}
+
+/// Splits recipients into batch transactions.
+///
+/// Recipients are taken in the given order, and every batch is filled until either
+/// the weight budget or the maximum number of outputs is reached.
+/// No recipient is dropped: if a recipient can't fit into an empty batch, an error is returned.
+pub struct OutputBatcher {
+    funding: FundingDescriptor,
+    weight_budget: usize,
+    max_outputs: Option<usize>,
+    fee_allocation: FeeAllocation,
+}
+
+impl OutputBatcher {
+    pub fn new(funding: FundingDescriptor, weight_budget: usize) -> Self {
+        OutputBatcher {
+            funding,
+            weight_budget,
+            max_outputs: None,
+            fee_allocation: FeeAllocation::default(),
+        }
+    }
+
+    /// Limits the number of outputs of every batch transaction including the change output.
+    pub fn max_outputs(mut self, max_outputs: usize) -> Self {
+        self.max_outputs = Some(max_outputs);
+        self
+    }
+
+    pub fn fee_allocation(mut self, fee_allocation: FeeAllocation) -> Self {
+        self.fee_allocation = fee_allocation;
+        self
+    }
+
+    pub fn batch(&self, recipients: &[Recipient]) -> SigningResult<Vec<BatchPlan>> {
+        self.check_params(recipients)?;
+
+        let mut plans = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+
+        for (index, recipient) in recipients.iter().enumerate() {
+            if !current.is_empty() && !self.fits(recipients, &current, recipient) {
+                let batch = std::mem::take(&mut current);
+                self.push_plan(&mut plans, recipients, batch)?;
+            }
+
+            if !self.fits(recipients, &current, recipient) {
+                return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+                    "Recipient #{index} doesn't fit into a transaction with the weight budget '{}'",
+                    self.weight_budget
+                ));
+            }
+            current.push(index);
+        }
+
+        if !current.is_empty() {
+            self.push_plan(&mut plans, recipients, current)?;
+        }
+
+        Ok(plans)
+    }
+
+    fn push_plan(
+        &self,
+        plans: &mut Vec<BatchPlan>,
+        recipients: &[Recipient],
+        batch: Vec<usize>,
+    ) -> SigningResult<()> {
+        let batch_index = plans.len();
+        let plan = self
+            .plan_batch(recipients, batch)
+            .with_context(|| format!("Error planning batch #{batch_index}"))?;
+        plans.push(plan);
+        Ok(())
+    }
+
+    fn check_params(&self, recipients: &[Recipient]) -> SigningResult<()> {
+        if recipients.is_empty() {
+            return SigningError::err(SigningErrorType::Error_missing_input_utxos)
+                .context("No recipients provided");
+        }
+        if self.funding.input_count == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Every batch must be funded by at least one input");
+        }
+        let min_outputs = 1 + self.change_outputs();
+        if matches!(self.max_outputs, Some(max) if max < min_outputs) {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Max outputs must allow at least {min_outputs} output(s) per transaction"
+            ));
+        }
+        if let Some(index) = recipients.iter().position(|r| r.amount == 0) {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Recipient #{index} amount is zero"));
+        }
+        Ok(())
+    }
+
+    fn change_outputs(&self) -> usize {
+        usize::from(self.funding.change_script_pubkey.is_some())
+    }
+
+    /// Whether the recipient can be added to the batch without exceeding the limits.
+    fn fits(&self, recipients: &[Recipient], batch: &[usize], recipient: &Recipient) -> bool {
+        let outputs_count = batch.len() + 1 + self.change_outputs();
+        if matches!(self.max_outputs, Some(max) if outputs_count > max) {
+            return false;
+        }
+
+        let scripts = batch
+            .iter()
+            .map(|i| &recipients[*i].script_pubkey)
+            .chain(std::iter::once(&recipient.script_pubkey));
+        self.estimate_weight(scripts) <= self.weight_budget
+    }
+
+    /// Estimates the weight of a batch transaction with the given recipient scripts.
+    fn estimate_weight<'a, I>(&self, recipient_scripts: I) -> usize
+    where
+        I: Iterator<Item = &'a Script>,
+    {
+        let funding = &self.funding;
+
+        let mut outputs_count = 0;
+        let mut outputs_weight = 0;
+        for script in recipient_scripts.chain(funding.change_script_pubkey.iter()) {
+            outputs_count += 1;
+            outputs_weight += weights::output_weight(script.len());
+        }
+
+        weights::tx_overhead_weight(funding.input_count, outputs_count, funding.segwit)
+            + outputs_weight
+            + funding.input_count.saturating_mul(funding.input_weight)
+    }
+
+    fn plan_batch(&self, recipients: &[Recipient], batch: Vec<usize>) -> SigningResult<BatchPlan> {
+        let weight_estimate =
+            self.estimate_weight(batch.iter().map(|i| &recipients[*i].script_pubkey));
+        let vsize_estimate = weights::vsize_from_weight(weight_estimate);
+
+        let fee_per_vbyte = self.funding.fee_per_vbyte;
+        let fee_estimate =
+            weights::fee_for_weight(weight_estimate, fee_per_vbyte, FeeRounding::VsizeUp)
+                .or_tw_err(SigningErrorType::Error_wrong_fee)
+                .with_context(|| {
+                    format!(
+                        "feePerVByte is too large: '{vsize_estimate} * {fee_per_vbyte}' overflow"
+                    )
+                })?;
+
+        let amounts: Vec<Amount> = batch.iter().map(|i| recipients[*i].amount).collect();
+        let gross_total = checked_sum(&amounts)?;
+
+        let amounts = match self.fee_allocation {
+            FeeAllocation::SenderPays => amounts,
+            FeeAllocation::Proportional => {
+                let shares = allocate_proportionally(fee_estimate, &amounts, gross_total)?;
+                amounts
+                    .iter()
+                    .zip(shares)
+                    .zip(batch.iter())
+                    .map(|((amount, share), index)| match amount.checked_sub(share) {
+                        Some(net) if net > 0 => Ok(net),
+                        _ => SigningError::err(SigningErrorType::Error_not_enough_utxos)
+                            .context(format!(
+                                "Recipient #{index} amount '{amount}' doesn't cover its fee share '{share}'"
+                            )),
+                    })
+                    .collect::<SigningResult<Vec<_>>>()?
+            },
+        };
+
+        let outputs: Vec<_> = batch
+            .iter()
+            .zip(amounts.iter())
+            .map(|(index, value)| TransactionOutput {
+                value: *value,
+                script_pubkey: recipients[*index].script_pubkey.clone(),
+            })
+            .collect();
+
+        let total_send = checked_sum(&amounts)?;
+        let total_spend = total_send
+            .checked_add(fee_estimate)
+            .or_tw_err(SigningErrorType::Error_wrong_fee)
+            .context("Total spend amount overflow")?;
+
+        Ok(BatchPlan {
+            outputs,
+            recipient_indexes: batch,
+            weight_estimate,
+            vsize_estimate,
+            fee_estimate,
+            total_send,
+            total_spend,
+        })
+    }
+}
+
+fn checked_sum(amounts: &[Amount]) -> SigningResult<Amount> {
+    amounts
+        .iter()
+        .try_fold(0 as Amount, |acc, amount| acc.checked_add(*amount))
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("Total amount overflow")
+}
+
+/// Splits the `fee` into shares proportional to the `amounts` using the largest remainder method.
+///
+/// Every share is rounded down first, then the remaining satoshis are given one by one
+/// to the amounts with the largest remainders, the lowest index first on ties.
+/// The sum of the shares is always exactly equal to the `fee`.
+fn allocate_proportionally(
+    fee: Amount,
+    amounts: &[Amount],
+    total: Amount,
+) -> SigningResult<Vec<Amount>> {
+    if total == 0 {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Cannot allocate the fee among zero amounts");
+    }
+
+    let fee = u128::from(fee);
+    let total = u128::from(total);
+
+    let mut shares = Vec::with_capacity(amounts.len());
+    let mut remainders = Vec::with_capacity(amounts.len());
+    for (index, amount) in amounts.iter().enumerate() {
+        // Can't overflow as both `fee` and `amount` fit into `u64`.
+        let scaled = fee * u128::from(*amount);
+        shares.push(scaled / total);
+        remainders.push((scaled % total, index));
+    }
+
+    let allocated: u128 = shares.iter().sum();
+    // The sum of the rounded down shares is less than the fee by at most `amounts.len() - 1`.
+    let leftover = (fee - allocated) as usize;
+
+    remainders
+        .sort_by(|(rem_a, index_a), (rem_b, index_b)| rem_b.cmp(rem_a).then(index_a.cmp(index_b)));
+    for (_, index) in remainders.into_iter().take(leftover) {
+        shares[index] += 1;
+    }
+
+    // Every share is not greater than the `fee` that fits into `u64`.
+    Ok(shares.into_iter().map(|share| share as Amount).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_proportionally() {
+        assert_eq!(
+            allocate_proportionally(100, &[1, 1, 1], 3).unwrap(),
+            vec![34, 33, 33]
+        );
+        assert_eq!(
+            allocate_proportionally(10, &[1_000, 3_000], 4_000).unwrap(),
+            vec![3, 7]
+        );
+        assert_eq!(
+            allocate_proportionally(Amount::MAX, &[Amount::MAX], Amount::MAX).unwrap(),
+            vec![Amount::MAX]
+        );
+        assert_eq!(allocate_proportionally(0, &[5, 7], 12).unwrap(), vec![0, 0]);
+    }
+}
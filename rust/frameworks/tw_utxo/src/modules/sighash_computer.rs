@@ -4,6 +4,7 @@
 
 use crate::script::Script;
 use crate::signing_mode::SigningMethod;
+use crate::spec_location::SpecLocation;
 use crate::transaction::transaction_interface::TransactionInterface;
 use crate::transaction::transaction_parts::Amount;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
@@ -12,7 +13,7 @@ use crate::transaction::{
 };
 use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::PublicKeyBytes;
-use tw_coin_entry::error::prelude::SigningResult;
+use tw_coin_entry::error::prelude::*;
 use tw_hash::H256;
 
 #[derive(Debug, Clone)]
@@ -60,77 +61,83 @@ where
             .input_args()
             .iter()
             .enumerate()
-            .map(|(input_index, utxo)| {
-                let signing_method = utxo.signing_method;
-
-                let utxo_args = UtxoPreimageArgs {
-                    input_index,
-                    script_pubkey: utxo.script_pubkey.clone(),
-                    amount: utxo.amount,
-                    // TODO move `leaf_hash_code_separator` to `UtxoTaprootPreimageArgs`.
-                    leaf_hash_code_separator: utxo.leaf_hash_code_separator,
-                    sighash_ty: utxo.sighash_ty,
-                    tx_hasher: utxo.tx_hasher,
-                    signing_method,
-                };
+            .map(|(input_index, utxo)| Self::preimage_utxo(unsigned_tx, input_index, utxo))
+            // Collect the results as [`SigningResult<Vec<UtxoSighash>>`].
+            .collect::<SigningResult<Vec<_>>>()
+            .map(|sighashes: Vec<UtxoSighash>| TxPreimage { sighashes })
+    }
 
-                let (sighash, taproot_tweak) = match signing_method {
-                    SigningMethod::Legacy | SigningMethod::Segwit => {
-                        let sighash = unsigned_tx.transaction().preimage_tx(&utxo_args)?;
-                        (sighash, None)
-                    },
-                    SigningMethod::Taproot => {
-                        // TODO Move `tr_spent_amounts` and `tr_spent_script_pubkeys` logic to `Transaction::preimage_taproot_tx()`.
-                        let tr_spent_amounts: Vec<Amount> = unsigned_tx
-                            .input_args()
-                            .iter()
-                            .map(|utxo| utxo.amount)
-                            .collect();
+    /// Computes the sighash of the `input_index` UTXO.
+    pub fn preimage_utxo(
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+        input_index: usize,
+        utxo: &UtxoToSign,
+    ) -> SigningResult<UtxoSighash> {
+        let request_index = unsigned_tx.request_index(input_index);
+        utxo.check_amount()
+            .with_context(|| format!("Invalid amount of the input #{request_index}"))
+            .with_context(|| SpecLocation::of_utxo(request_index, utxo))?;
+        let signing_method = utxo.signing_method;
 
-                        let tr_spent_script_pubkeys: Vec<Script> = unsigned_tx
-                            .input_args()
-                            .iter()
-                            .map(|utxo| {
-                                if utxo.signing_method == SigningMethod::Taproot {
-                                    // Taproot UTXOs scriptPubkeys should be signed as is.
-                                    utxo.script_pubkey.clone()
-                                } else {
-                                    // Use the original scriptPubkey declared in the unspent output.
-                                    utxo.prevout_script_pubkey.clone()
-                                }
-                            })
-                            .collect();
+        let utxo_args = UtxoPreimageArgs {
+            input_index,
+            script_code: utxo.script_code.clone(),
+            amount: utxo.amount,
+            // TODO move `leaf_hash_code_separator` to `UtxoTaprootPreimageArgs`.
+            leaf_hash_code_separator: utxo.leaf_hash_code_separator,
+            sighash_ty: utxo.sighash_ty,
+            tx_hasher: utxo.tx_hasher,
+            signing_method,
+        };
 
-                        let tr = UtxoTaprootPreimageArgs {
-                            args: utxo_args,
-                            spent_amounts: tr_spent_amounts,
-                            spent_script_pubkeys: tr_spent_script_pubkeys.clone(),
-                        };
+        let (sighash, taproot_tweak) = match signing_method {
+            SigningMethod::Legacy | SigningMethod::Segwit => {
+                let sighash = unsigned_tx.transaction().preimage_tx(&utxo_args)?;
+                (sighash, None)
+            },
+            SigningMethod::Taproot => {
+                // TODO Move `tr_spent_amounts` and `tr_spent_script_pubkeys` logic to `Transaction::preimage_taproot_tx()`.
+                let tr_spent_amounts: Vec<Amount> = unsigned_tx
+                    .input_args()
+                    .iter()
+                    .map(|utxo| utxo.amount)
+                    .collect();
 
-                        let sighash = unsigned_tx.transaction().preimage_taproot_tx(&tr)?;
-                        let taproot_tweak = Self::get_taproot_tweak(utxo);
+                // BIP-341 commits to the original scriptPubkeys declared in the unspent outputs.
+                // Note that Taproot script-path UTXOs have the revealed leaf script as `script_code`,
+                // but the leaf itself is committed via `leaf_hash_code_separator`.
+                let tr_spent_script_pubkeys: Vec<Script> = unsigned_tx
+                    .input_args()
+                    .iter()
+                    .map(|utxo| utxo.prevout_script_pubkey.clone())
+                    .collect();
 
-                        (sighash, taproot_tweak)
-                    },
+                let tr = UtxoTaprootPreimageArgs {
+                    args: utxo_args,
+                    spent_amounts: tr_spent_amounts,
+                    spent_script_pubkeys: tr_spent_script_pubkeys.clone(),
                 };
 
-                Ok(UtxoSighash {
-                    signing_method,
-                    sighash,
-                    signer_pubkey: utxo.spender_public_key.clone(),
-                    taproot_tweak,
-                })
-            })
-            // Collect the results as [`SigningResult<Vec<UtxoSighash>>`].
-            .collect::<SigningResult<Vec<_>>>()
-            .map(|sighashes: Vec<UtxoSighash>| TxPreimage { sighashes })
+                let sighash = unsigned_tx.transaction().preimage_taproot_tx(&tr)?;
+                let taproot_tweak = Self::get_taproot_tweak(utxo);
+
+                (sighash, taproot_tweak)
+            },
+        };
+
+        Ok(UtxoSighash {
+            signing_method,
+            sighash,
+            signer_pubkey: utxo.spender_public_key.clone(),
+            taproot_tweak,
+        })
     }
 
     pub fn get_taproot_tweak(utxo: &UtxoToSign) -> Option<TaprootTweak> {
         // Any empty leaf hash implies P2TR key-path (balance transfer)
         if utxo.leaf_hash_code_separator.is_none() {
-            // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
-            let merkle_root = None;
+            // Tweak keypair for P2TR key-path, with a zeroed Merkle root if there are no scripts.
+            let merkle_root = utxo.taproot_merkle_root;
             Some(TaprootTweak { merkle_root })
         } else {
             None
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use std::time::{Duration, Instant};
+
+/// A phase of signing a transaction input.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SigningPhase {
+    /// Computing the sighash of the input.
+    Preimage,
+    /// Signing the sighash with an ECDSA or Schnorr private key.
+    Signing,
+    /// Serializing the signature along with the sighash type.
+    Serialization,
+    /// Assembling the scriptSig or witness of the input.
+    ScriptAssembly,
+}
+
+impl SigningPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningPhase::Preimage => "preimage",
+            SigningPhase::Signing => "signing",
+            SigningPhase::Serialization => "serialization",
+            SigningPhase::ScriptAssembly => "script_assembly",
+        }
+    }
+}
+
+/// Telemetry hooks invoked by [`TxSigner`] and [`TxCompiler`].
+/// Allows integrators to bridge the signing timings to their metrics stack.
+///
+/// [`TxSigner`]: crate::modules::tx_signer::TxSigner
+/// [`TxCompiler`]: crate::modules::tx_compiler::TxCompiler
+pub trait SignerMetrics {
+    /// Called once the `phase` of signing the `input_index` input is finished.
+    fn on_phase(&self, _phase: SigningPhase, _input_index: usize, _duration: Duration) {}
+}
+
+/// Metrics that ignore all the events.
+pub struct NoopSignerMetrics;
+
+impl SignerMetrics for NoopSignerMetrics {}
+
+/// Runs `f` and reports its duration as the `phase` of the `input_index` input.
+pub(crate) fn measure<T, F>(
+    metrics: &dyn SignerMetrics,
+    phase: SigningPhase,
+    input_index: usize,
+    f: F,
+) -> T
+where
+    F: FnOnce() -> T,
+{
+    let start = Instant::now();
+    let result = f();
+    metrics.on_phase(phase, input_index, start.elapsed());
+    result
+}
@@ -0,0 +1,460 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! An in-progress signing session that can be saved and resumed later,
+//! e.g. when the inputs are signed by different devices or in different app launches.
+//!
+//! A saved session contains the unsigned transaction, the UTXO arguments, the signatures produced so far
+//! and the session content digest. The replay protection the transaction was built with is not saved,
+//! as the UTXO sighash types carry the fork id already.
+//! Only the UTXOs with a [`StandardConstructor`] can be saved.
+
+use crate::chain_params::ChainTag;
+use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
+use crate::encode::stream::Stream;
+use crate::encode::{Decodable, Encodable};
+use crate::modules::keys_manager::KeysManager;
+use crate::modules::sighash_computer::{SighashComputer, UtxoSighash};
+use crate::modules::sighash_verifier::SighashVerifier;
+use crate::modules::signer_metrics::NoopSignerMetrics;
+use crate::modules::tx_compiler::TxCompiler;
+use crate::modules::tx_signer::{NonceReuseGuard, TxSigner};
+use crate::script::script_code::ScriptCode;
+use crate::sighash::SighashType;
+use crate::signing_mode::SigningMethod;
+use crate::spending_data::standard_constructor::StandardConstructor;
+use crate::transaction::transaction_interface::TransactionInterface;
+use crate::transaction::transaction_parts::LockTime;
+use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use crate::transaction::{TransactionPreimage, UtxoToSign};
+use tw_coin_entry::coin_entry::SignatureBytes;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::hasher::{sha256_d, Hasher};
+use tw_hash::H256;
+use tw_memory::Data;
+
+/// The version of the serialized session format.
+const SESSION_FORMAT_VERSION: u8 = 2;
+
+pub struct SigningSession<Transaction> {
+    unsigned_tx: UnsignedTransaction<Transaction>,
+    /// The sighashes in the same order as the transaction UTXOs.
+    sighashes: Vec<UtxoSighash>,
+    /// The signatures produced so far in the same order as the transaction UTXOs.
+    signatures: Vec<Option<SignatureBytes>>,
+    digest: H256,
+    nonce_guard: NonceReuseGuard,
+}
+
+impl<Transaction> SigningSession<Transaction>
+where
+    Transaction: TransactionPreimage + TransactionInterface,
+{
+    /// Starts a session without any signature.
+    pub fn new(unsigned_tx: UnsignedTransaction<Transaction>) -> SigningResult<Self> {
+        let sighashes = SighashComputer::preimage_tx(&unsigned_tx)
+            .context("Error sighash pre-imaging")?
+            .sighashes;
+        let digest = content_digest(unsigned_tx.transaction(), &sighashes);
+
+        Ok(SigningSession {
+            signatures: vec![None; sighashes.len()],
+            unsigned_tx,
+            sighashes,
+            digest,
+            nonce_guard: NonceReuseGuard::default(),
+        })
+    }
+
+    /// Resumes a session serialized with [`SigningSession::to_bytes`].
+    ///
+    /// The content digest is computed again and compared with the saved one,
+    /// and every restored signature is verified against the sighash of its input.
+    pub fn resume(bytes: &[u8]) -> SigningResult<Self>
+    where
+        Transaction: Decodable,
+    {
+        let mut reader = Reader::new(bytes);
+
+        let version: u8 = reader.read()?;
+        if version != SESSION_FORMAT_VERSION {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context(format!("Unsupported signing session version: {version}"));
+        }
+
+        let digest: H256 = reader.read()?;
+        let transaction: Transaction = reader.read().context("Invalid signing session")?;
+
+        // Every UTXO takes more than one byte.
+        let utxo_args = (0..reader.read_len(1)?)
+            .map(|input_index| {
+                read_utxo_args(&mut reader)
+                    .with_context(|| format!("Invalid signing session input #{input_index}"))
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        // Every signature slot takes at least the one byte flag.
+        let signatures = (0..reader.read_len(1)?)
+            .map(|_| read_optional::<SignatureBytes>(&mut reader))
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        if !reader.is_finished() {
+            return SigningError::err(SigningErrorType::Error_input_parse).context(format!(
+                "Unexpected {} trailing bytes of the signing session",
+                reader.remaining().len()
+            ));
+        }
+
+        let unsigned_tx = UnsignedTransaction::new(transaction, utxo_args)?;
+        let mut session = Self::new(unsigned_tx)?;
+
+        if digest != session.digest {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("The signing session content digest doesn't match");
+        }
+        if signatures.len() != session.signatures.len() {
+            return SigningError::err(SigningErrorType::Error_signatures_count).context(format!(
+                "The signing session has {} signature slots, but the transaction has {} inputs",
+                signatures.len(),
+                session.signatures.len()
+            ));
+        }
+
+        for (input_index, signature) in signatures.into_iter().enumerate() {
+            if let Some(signature) = signature {
+                SighashVerifier::<Transaction>::verify_signature(
+                    &session.sighashes[input_index],
+                    &session.unsigned_tx.input_args()[input_index],
+                    &signature,
+                )
+                .with_context(|| format!("Invalid signature of the input #{input_index}"))?;
+                session.insert_signature(input_index, signature)?;
+            }
+        }
+        Ok(session)
+    }
+
+    /// Serializes the session: the unsigned transaction, the UTXO arguments,
+    /// the content digest and the signatures produced so far.
+    ///
+    /// Returns [`SigningErrorType::Error_not_supported`] if any of the UTXOs has a custom spending data constructor.
+    pub fn to_bytes(&self) -> SigningResult<Data> {
+        let mut stream = Stream::default();
+        stream
+            .append(&SESSION_FORMAT_VERSION)
+            .append(&self.digest)
+            .append(self.unsigned_tx.transaction())
+            .append(&CompactInteger::from(self.unsigned_tx.input_args().len()));
+
+        for (input_index, utxo) in self.unsigned_tx.input_args().iter().enumerate() {
+            append_utxo_args(&mut stream, utxo)
+                .with_context(|| format!("Cannot save the input #{input_index}"))?;
+        }
+
+        stream.append(&CompactInteger::from(self.signatures.len()));
+        for signature in self.signatures.iter() {
+            append_optional(&mut stream, signature.as_ref());
+        }
+        Ok(stream.out())
+    }
+
+    /// The digest of the transaction and the sighashes of all its inputs.
+    pub fn digest(&self) -> H256 {
+        self.digest
+    }
+
+    /// The sighashes in the same order as the transaction UTXOs.
+    pub fn sighashes(&self) -> &[UtxoSighash] {
+        &self.sighashes
+    }
+
+    pub fn is_signed(&self, input_index: usize) -> bool {
+        matches!(self.signatures.get(input_index), Some(Some(_)))
+    }
+
+    /// The indexes of the inputs that don't have a signature yet.
+    pub fn remaining_inputs(&self) -> Vec<usize> {
+        (0..self.signatures.len())
+            .filter(|input_index| !self.is_signed(*input_index))
+            .collect()
+    }
+
+    /// Signs the `input_index` UTXO with the given sighash signer, e.g. an external one.
+    /// Does nothing if the input has been signed already, so its signature is preserved.
+    pub fn sign_input_with_signer<F>(
+        &mut self,
+        input_index: usize,
+        sign_sighash: F,
+    ) -> SigningResult<()>
+    where
+        F: FnOnce(&UtxoSighash) -> SigningResult<SignatureBytes>,
+    {
+        if self.is_signed(input_index) {
+            return Ok(());
+        }
+
+        let sighash = self
+            .sighashes
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("There is no input #{input_index}"))?;
+        let signature = sign_sighash(sighash)?;
+        self.insert_signature(input_index, signature)
+    }
+
+    /// Signs the `input_index` UTXO with a key of the `keys_manager`.
+    pub fn sign_input(
+        &mut self,
+        input_index: usize,
+        keys_manager: &KeysManager,
+    ) -> SigningResult<()> {
+        self.sign_input_with_signer(input_index, |sighash| {
+            TxSigner::<Transaction>::sign_sighash(keys_manager, sighash)
+        })
+    }
+
+    /// Signs all the inputs that don't have a signature yet.
+    pub fn sign_remaining(&mut self, keys_manager: &KeysManager) -> SigningResult<()> {
+        for input_index in self.remaining_inputs() {
+            self.sign_input(input_index, keys_manager)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles the signed transaction.
+    /// Returns an error if any of the inputs has not been signed yet.
+    pub fn finish(self) -> SigningResult<Transaction> {
+        let remaining = self.remaining_inputs();
+        if !remaining.is_empty() {
+            let remaining: Vec<_> = remaining.iter().map(ToString::to_string).collect();
+            return SigningError::err(SigningErrorType::Error_signatures_count).context(format!(
+                "The inputs have not been signed yet: {}",
+                remaining.join(", ")
+            ));
+        }
+
+        let claims = self
+            .unsigned_tx
+            .input_args()
+            .iter()
+            .zip(self.signatures.iter().flatten())
+            .enumerate()
+            .map(|(input_index, (utxo, signature))| {
+                TxCompiler::<Transaction>::utxo_spending_data(
+                    utxo,
+                    signature,
+                    input_index,
+                    &NoopSignerMetrics,
+                )
+            })
+            .collect::<SigningResult<_>>()?;
+
+        TxCompiler::compile_spending_data(self.unsigned_tx, claims)
+    }
+
+    fn insert_signature(
+        &mut self,
+        input_index: usize,
+        signature: SignatureBytes,
+    ) -> SigningResult<()> {
+        self.nonce_guard.check(
+            input_index,
+            &self.sighashes[input_index].sighash,
+            &signature,
+        )?;
+        self.signatures[input_index] = Some(signature);
+        Ok(())
+    }
+}
+
+/// Computes the digest of the transaction and the sighashes (and signers) of all its inputs.
+/// The sighashes commit to the UTXO arguments the transaction is signed with,
+/// e.g. the amounts, script codes and sighash types.
+fn content_digest<Transaction: Encodable>(
+    transaction: &Transaction,
+    sighashes: &[UtxoSighash],
+) -> H256 {
+    let mut stream = Stream::default();
+    stream.append(transaction);
+    for sighash in sighashes {
+        stream
+            .append(&sighash.sighash)
+            .append(&sighash.signer_pubkey);
+        // The merkle root the private key is tweaked with, if any.
+        let merkle_root = sighash
+            .taproot_tweak
+            .as_ref()
+            .and_then(|tweak| tweak.merkle_root);
+        append_optional(&mut stream, merkle_root.as_ref());
+    }
+    H256::try_from(sha256_d(&stream.out()).as_slice()).expect("sha256d must return 32 bytes")
+}
+
+fn append_bool(stream: &mut Stream, value: bool) {
+    stream.append(&u8::from(value));
+}
+
+fn read_bool(reader: &mut Reader) -> SigningResult<bool> {
+    match reader.read::<u8>()? {
+        0 => Ok(false),
+        1 => Ok(true),
+        flag => SigningError::err(SigningErrorType::Error_input_parse)
+            .context(format!("Invalid boolean flag: {flag}")),
+    }
+}
+
+fn append_optional<T: Encodable>(stream: &mut Stream, value: Option<&T>) {
+    append_bool(stream, value.is_some());
+    if let Some(value) = value {
+        stream.append(value);
+    }
+}
+
+fn read_optional<T: Decodable>(reader: &mut Reader) -> SigningResult<Option<T>> {
+    if read_bool(reader)? {
+        Ok(Some(reader.read()?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn append_utxo_args(stream: &mut Stream, utxo: &UtxoToSign) -> SigningResult<()> {
+    let constructor = utxo
+        .spending_data_constructor
+        .to_standard()
+        .or_tw_err(SigningErrorType::Error_not_supported)
+        .context("Only the standard spending data constructors can be saved")?;
+
+    stream
+        .append(&utxo.prevout_script_pubkey)
+        .append(utxo.script_code.as_script())
+        .append(&signing_method_tag(utxo.signing_method))
+        .append(&constructor)
+        .append(&utxo.spender_public_key)
+        .append(&utxo.amount);
+
+    append_bool(stream, utxo.leaf_hash_code_separator.is_some());
+    if let Some((leaf_hash, code_separator)) = utxo.leaf_hash_code_separator {
+        stream.append(&leaf_hash).append(&code_separator);
+    }
+    append_optional(stream, utxo.taproot_merkle_root.as_ref());
+
+    stream
+        .append(&hasher_tag(utxo.tx_hasher))
+        .append(&utxo.sighash_ty.raw_sighash());
+    append_bool(stream, utxo.explicit_sequence);
+    append_bool(stream, utxo.allow_zero_amount);
+
+    let required_lock_time = utxo.required_lock_time.map(LockTime::to_consensus_u32);
+    append_optional(stream, required_lock_time.as_ref());
+    stream.append(&chain_tag(utxo.chain));
+    append_optional(stream, utxo.confirmations.as_ref());
+    append_bool(stream, utxo.is_change_from_self);
+    Ok(())
+}
+
+fn read_utxo_args(reader: &mut Reader) -> SigningResult<UtxoToSign> {
+    let prevout_script_pubkey = reader.read()?;
+    // The script code has been checked when the session was started.
+    let script_code = ScriptCode::restore(reader.read()?);
+    let signing_method = signing_method_from_tag(reader.read()?)?;
+    let spending_data_constructor = reader.read::<StandardConstructor>()?.into();
+    let spender_public_key = reader.read()?;
+    let amount = reader.read()?;
+
+    let leaf_hash_code_separator = if read_bool(reader)? {
+        Some((reader.read()?, reader.read()?))
+    } else {
+        None
+    };
+    let taproot_merkle_root = read_optional(reader)?;
+
+    let tx_hasher = hasher_from_tag(reader.read()?)?;
+    let sighash_ty = SighashType::from_u32_lenient(reader.read()?)?;
+    let explicit_sequence = read_bool(reader)?;
+    let allow_zero_amount = read_bool(reader)?;
+
+    let required_lock_time = read_optional(reader)?.map(LockTime::from_consensus);
+    let chain = chain_from_tag(reader.read()?)?;
+    let confirmations = read_optional(reader)?;
+    let is_change_from_self = read_bool(reader)?;
+
+    Ok(UtxoToSign {
+        prevout_script_pubkey,
+        script_code,
+        signing_method,
+        spending_data_constructor,
+        spender_public_key,
+        amount,
+        leaf_hash_code_separator,
+        taproot_merkle_root,
+        tx_hasher,
+        sighash_ty,
+        explicit_sequence,
+        allow_zero_amount,
+        required_lock_time,
+        chain,
+        confirmations,
+        is_change_from_self,
+    })
+}
+
+fn signing_method_tag(signing_method: SigningMethod) -> u8 {
+    match signing_method {
+        SigningMethod::Legacy => 0,
+        SigningMethod::Segwit => 1,
+        SigningMethod::Taproot => 2,
+    }
+}
+
+fn signing_method_from_tag(tag: u8) -> SigningResult<SigningMethod> {
+    match tag {
+        0 => Ok(SigningMethod::Legacy),
+        1 => Ok(SigningMethod::Segwit),
+        2 => Ok(SigningMethod::Taproot),
+        _ => SigningError::err(SigningErrorType::Error_input_parse)
+            .context(format!("Unknown signing method: {tag}")),
+    }
+}
+
+fn hasher_tag(hasher: Hasher) -> u8 {
+    match hasher {
+        Hasher::Sha256 => 0,
+        Hasher::Keccak256 => 1,
+        Hasher::Sha256d => 2,
+        Hasher::Sha256ripemd => 3,
+        Hasher::TapSighash => 4,
+    }
+}
+
+fn hasher_from_tag(tag: u8) -> SigningResult<Hasher> {
+    match tag {
+        0 => Ok(Hasher::Sha256),
+        1 => Ok(Hasher::Keccak256),
+        2 => Ok(Hasher::Sha256d),
+        3 => Ok(Hasher::Sha256ripemd),
+        4 => Ok(Hasher::TapSighash),
+        _ => SigningError::err(SigningErrorType::Error_input_parse)
+            .context(format!("Unknown transaction hasher: {tag}")),
+    }
+}
+
+fn chain_tag(chain: ChainTag) -> u8 {
+    match chain {
+        ChainTag::Bitcoin => 0,
+        ChainTag::BitcoinCash => 1,
+        ChainTag::Litecoin => 2,
+    }
+}
+
+fn chain_from_tag(tag: u8) -> SigningResult<ChainTag> {
+    match tag {
+        0 => Ok(ChainTag::Bitcoin),
+        1 => Ok(ChainTag::BitcoinCash),
+        2 => Ok(ChainTag::Litecoin),
+        _ => SigningError::err(SigningErrorType::Error_input_parse)
+            .context(format!("Unknown chain: {tag}")),
+    }
+}
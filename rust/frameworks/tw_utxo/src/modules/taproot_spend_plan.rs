@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Chooses how to spend a taproot output that can be claimed either via the key path
+//! or via one of several script leaves, depending on the keys that are available.
+
+use crate::script::Script;
+use crate::transaction::standard_transaction::builder::{tapscript_merkle_root, UtxoBuilder};
+use crate::transaction::standard_transaction::TransactionInput;
+use crate::transaction::UtxoToSign;
+use crate::weights::InputClaim;
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_hash::H256;
+use tw_keypair::schnorr;
+
+/// A script leaf of the taproot output signed by a single key, e.g. `<signer> OP_CHECKSIG`.
+#[derive(Clone, Debug)]
+pub struct TaprootLeaf {
+    /// The leaf script revealed by the script path spend.
+    pub script: Script,
+    /// The sibling hashes from the leaf to the merkle root.
+    pub merkle_branch: Vec<H256>,
+    /// The key that signs the script path spend.
+    pub signer: schnorr::PublicKey,
+    /// Whether the private key of the [`TaprootLeaf::signer`] is available.
+    pub signer_available: bool,
+}
+
+impl TaprootLeaf {
+    /// The witness items of the script path spend, see [`InputClaim::P2trScriptPath`].
+    pub fn claim(&self) -> InputClaim {
+        InputClaim::P2trScriptPath {
+            script_len: self.script.len(),
+            // The leaf version, the internal key and the merkle branch.
+            control_block_len: 1 + H256::LEN * (1 + self.merkle_branch.len()),
+        }
+    }
+}
+
+/// The ways a taproot UTXO can be claimed.
+#[derive(Clone, Debug)]
+pub struct TaprootClaimDescriptor {
+    pub internal_pubkey: schnorr::PublicKey,
+    /// Whether the private key of the [`TaprootClaimDescriptor::internal_pubkey`] is available,
+    /// so the output can be spent via the key path.
+    pub internal_key_available: bool,
+    /// The known leaves of the script tree. Every leaf must commit to the same merkle root.
+    pub leaves: Vec<TaprootLeaf>,
+}
+
+impl TaprootClaimDescriptor {
+    /// Returns the merkle root the leaves commit to, `None` if there are no leaves.
+    pub fn merkle_root(&self) -> SigningResult<Option<H256>> {
+        let mut roots = self
+            .leaves
+            .iter()
+            .map(|leaf| tapscript_merkle_root(&leaf.script, &leaf.merkle_branch));
+        let Some(merkle_root) = roots.next() else {
+            return Ok(None);
+        };
+        if let Some(leaf_index) = roots.position(|root| root != merkle_root) {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Taproot leaf #{} commits to a different merkle root than leaf #0",
+                leaf_index + 1
+            ));
+        }
+        Ok(Some(merkle_root))
+    }
+}
+
+/// The path the taproot output is spent via.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TaprootSpendPath {
+    KeyPath,
+    /// The index of the leaf in [`TaprootClaimDescriptor::leaves`].
+    ScriptPath {
+        leaf_index: usize,
+    },
+}
+
+/// The cheapest satisfiable way to spend a taproot output, see [`TaprootSpendPlan::resolve`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TaprootSpendPlan {
+    pub path: TaprootSpendPath,
+    /// The witness the input is claimed with, to estimate the fee.
+    pub claim: InputClaim,
+}
+
+impl TaprootSpendPlan {
+    /// Chooses the spend path with the smallest witness among the ones whose keys are available.
+    /// The key path is the cheapest one, the leaves of the same cost are chosen in their order.
+    ///
+    /// Returns an error listing the missing keys if no path is satisfiable.
+    pub fn resolve(descriptor: &TaprootClaimDescriptor) -> SigningResult<TaprootSpendPlan> {
+        // Validate the script tree even if the key path is chosen, as it's tweaked with the root.
+        descriptor.merkle_root()?;
+
+        if descriptor.internal_key_available {
+            return Ok(TaprootSpendPlan {
+                path: TaprootSpendPath::KeyPath,
+                claim: InputClaim::P2trKeyPath,
+            });
+        }
+
+        let cheapest = descriptor
+            .leaves
+            .iter()
+            .enumerate()
+            .filter(|(_, leaf)| leaf.signer_available)
+            .min_by_key(|(leaf_index, leaf)| (leaf.claim().weight(true), *leaf_index));
+        if let Some((leaf_index, leaf)) = cheapest {
+            return Ok(TaprootSpendPlan {
+                path: TaprootSpendPath::ScriptPath { leaf_index },
+                claim: leaf.claim(),
+            });
+        }
+
+        let mut missing = vec![format!(
+            "the internal key {}",
+            hex::encode(descriptor.internal_pubkey.x_only().bytes(), false)
+        )];
+        missing.extend(
+            descriptor
+                .leaves
+                .iter()
+                .enumerate()
+                .map(|(leaf_index, leaf)| {
+                    format!(
+                        "the {} key of leaf #{leaf_index}",
+                        hex::encode(leaf.signer.x_only().bytes(), false)
+                    )
+                }),
+        );
+        SigningError::err(SigningErrorType::Error_missing_private_key).context(format!(
+            "No spend path of the taproot output is satisfiable. Missing {}",
+            missing.join(", ")
+        ))
+    }
+
+    /// The serialized witness size, which is also its weight, as estimated by the planner.
+    pub fn witness_weight(&self) -> usize {
+        self.claim.witness_size(self.claim.max_sig_len())
+    }
+
+    /// The weight of the input including the witness, as estimated by the planner.
+    pub fn input_weight(&self) -> usize {
+        self.claim.weight(true)
+    }
+
+    /// Configures the UTXO to be claimed via the planned path.
+    pub fn claim_utxo(
+        &self,
+        descriptor: &TaprootClaimDescriptor,
+        utxo: UtxoBuilder,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        match self.path {
+            TaprootSpendPath::KeyPath => match descriptor.merkle_root()? {
+                Some(merkle_root) => {
+                    utxo.p2tr_key_path_with_merkle_root(&descriptor.internal_pubkey, &merkle_root)
+                },
+                None => utxo.p2tr_key_path(&descriptor.internal_pubkey),
+            },
+            TaprootSpendPath::ScriptPath { leaf_index } => {
+                let leaf = descriptor
+                    .leaves
+                    .get(leaf_index)
+                    .or_tw_err(SigningErrorType::Error_invalid_params)
+                    .with_context(|| format!("There is no taproot leaf #{leaf_index}"))?;
+                utxo.p2tr_script_path_signed_by(
+                    &descriptor.internal_pubkey,
+                    &leaf.signer,
+                    leaf.script.clone(),
+                    &leaf.merkle_branch,
+                )
+            },
+        }
+    }
+}
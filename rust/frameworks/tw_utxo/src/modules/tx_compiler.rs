@@ -3,15 +3,13 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::modules::sighash_verifier::SighashVerifier;
+use crate::modules::signer_metrics::{measure, NoopSignerMetrics, SignerMetrics, SigningPhase};
 use crate::sighash::SighashType;
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature, FromRawOrDerBytes};
-use crate::spending_data::{
-    EcdsaSpendingDataConstructor, SchnorrSpendingDataConstructor, SpendingData,
-    SpendingDataConstructor,
-};
+use crate::spending_data::{SpendingData, SpendingDataConstructor};
 use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
-use crate::transaction::TransactionPreimage;
+use crate::transaction::{TransactionPreimage, UtxoToSign};
 use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_coin_entry::error::prelude::*;
@@ -49,23 +47,49 @@ where
             .input_args()
             .iter()
             .zip(signatures)
-            .map(|(utxo, sign)| match utxo.spending_data_constructor {
-                SpendingDataConstructor::Ecdsa(ref ecdsa_constructor) => {
-                    Self::get_ecdsa_spending_data(ecdsa_constructor.as_ref(), sign, utxo.sighash_ty)
-                },
-                SpendingDataConstructor::Schnorr(ref schnorr_constructor) => {
-                    Self::get_schnorr_spending_data(
-                        schnorr_constructor.as_ref(),
-                        sign,
-                        utxo.sighash_ty,
-                    )
-                },
+            .enumerate()
+            .map(|(input_index, (utxo, sign))| {
+                Self::utxo_spending_data(utxo, sign, input_index, &NoopSignerMetrics)
             })
             .collect::<SigningResult<_>>()?;
 
         Self::compile_spending_data(unsigned_tx, claims)
     }
 
+    /// Constructs the spending data (scriptSig or witness) of the `input_index` UTXO with the given signature.
+    /// Reports the [`SigningPhase::Serialization`] and [`SigningPhase::ScriptAssembly`] phases to `metrics`.
+    pub fn utxo_spending_data(
+        utxo: &UtxoToSign,
+        sign: &[u8],
+        input_index: usize,
+        metrics: &dyn SignerMetrics,
+    ) -> SigningResult<SpendingData> {
+        match utxo.spending_data_constructor {
+            SpendingDataConstructor::Ecdsa(ref ecdsa_constructor) => {
+                let signature = measure(metrics, SigningPhase::Serialization, input_index, || {
+                    Self::get_ecdsa_signature(sign, utxo.sighash_ty)
+                })?;
+                Ok(measure(
+                    metrics,
+                    SigningPhase::ScriptAssembly,
+                    input_index,
+                    || ecdsa_constructor.get_spending_data(&signature),
+                ))
+            },
+            SpendingDataConstructor::Schnorr(ref schnorr_constructor) => {
+                let signature = measure(metrics, SigningPhase::Serialization, input_index, || {
+                    Self::get_schnorr_signature(sign, utxo.sighash_ty)
+                })?;
+                Ok(measure(
+                    metrics,
+                    SigningPhase::ScriptAssembly,
+                    input_index,
+                    || schnorr_constructor.get_spending_data(&signature),
+                ))
+            },
+        }
+    }
+
     /// Compiles the transaction with the given spending data.
     pub fn compile_spending_data(
         unsigned_tx: UnsignedTransaction<Transaction>,
@@ -93,31 +117,27 @@ where
         Ok(signed_tx)
     }
 
-    fn get_ecdsa_spending_data(
-        ecdsa_constructor: &dyn EcdsaSpendingDataConstructor,
+    fn get_ecdsa_signature(
         sign: &[u8],
         sighash_ty: SighashType,
-    ) -> SigningResult<SpendingData> {
+    ) -> SigningResult<BitcoinEcdsaSignature> {
         let ecdsa_sign = ecdsa::secp256k1::VerifySignature::from_raw_or_der_bytes(sign)?;
         let der_sign = ecdsa_sign
             .to_der()
             .into_tw()
             .context("Error getting ASN.1 DER-encoded signature")?;
 
-        let signature = BitcoinEcdsaSignature::new(der_sign, sighash_ty)?;
-        Ok(ecdsa_constructor.get_spending_data(&signature))
+        BitcoinEcdsaSignature::new(der_sign, sighash_ty)
     }
 
-    fn get_schnorr_spending_data(
-        schnorr_constructor: &dyn SchnorrSpendingDataConstructor,
+    fn get_schnorr_signature(
         sign: &[u8],
         sighash_ty: SighashType,
-    ) -> SigningResult<SpendingData> {
+    ) -> SigningResult<BitcoinSchnorrSignature> {
         let schnorr_sign = schnorr::Signature::try_from(sign)
             .into_tw()
             .context("Invalid schnorr signature")?;
 
-        let signature = BitcoinSchnorrSignature::new(schnorr_sign, sighash_ty)?;
-        Ok(schnorr_constructor.get_spending_data(&signature))
+        BitcoinSchnorrSignature::new(schnorr_sign, sighash_ty)
     }
 }
@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Structural comparison of two transactions, e.g. the outputs of two signers
+//! of the same transaction.
+
+use crate::script::{Script, Witness};
+use crate::signature::is_valid_signature_encoding;
+use crate::transaction::standard_transaction::{Transaction, TransactionInput, TransactionOutput};
+use crate::transaction::transaction_parts::OutPoint;
+use serde_json::{json, Value as Json};
+use std::fmt;
+use tw_encoding::hex;
+
+/// A field that differs between two transactions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// The path of the field, e.g. `version` or `inputs[0].script_sig`.
+    pub field: String,
+    /// The field of the first transaction. Scripts are rendered as ASM.
+    pub left: String,
+    /// The field of the second transaction. Scripts are rendered as ASM.
+    pub right: String,
+    /// Whether the field has the same structure, but different signatures,
+    /// which is expected if the signers use different nonces.
+    pub signature_only: bool,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.field, self.left, self.right)?;
+        if self.signature_only {
+            write!(f, " [signature only]")?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two transactions, see [`tx_diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxDiff {
+    /// The differing fields in the serialization order.
+    pub differences: Vec<FieldDiff>,
+}
+
+impl TxDiff {
+    /// Whether the transactions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Whether the transactions differ by the signatures only.
+    pub fn is_signature_only(&self) -> bool {
+        !self.is_empty() && self.differences.iter().all(|diff| diff.signature_only)
+    }
+
+    /// The differences other than the signatures.
+    pub fn structural(&self) -> impl Iterator<Item = &FieldDiff> {
+        self.differences.iter().filter(|diff| !diff.signature_only)
+    }
+
+    /// Returns the diff as `{ "equal", "signature_only", "differences" }`,
+    /// where every difference is a `{ "field", "left", "right", "signature_only" }` object.
+    pub fn to_json(&self) -> Json {
+        let differences: Vec<_> = self
+            .differences
+            .iter()
+            .map(|diff| {
+                json!({
+                    "field": diff.field,
+                    "left": diff.left,
+                    "right": diff.right,
+                    "signature_only": diff.signature_only,
+                })
+            })
+            .collect();
+        json!({
+            "equal": self.is_empty(),
+            "signature_only": self.is_signature_only(),
+            "differences": differences,
+        })
+    }
+}
+
+/// One difference per line, the transactions are the same if empty.
+impl fmt::Display for TxDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in &self.differences {
+            writeln!(f, "{diff}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the transactions field by field.
+///
+/// The signatures are recognized by their encoding: strict DER with the same sighash type,
+/// or 64 and 65 bytes long Schnorr signatures (the latter with the same sighash type).
+/// A scriptSig or witness that differs in such signatures only is flagged as
+/// [`FieldDiff::signature_only`].
+pub fn tx_diff(a: &Transaction, b: &Transaction) -> TxDiff {
+    let mut differences = Vec::new();
+    let mut push = |field: String, left: String, right: String, signature_only: bool| {
+        differences.push(FieldDiff {
+            field,
+            left,
+            right,
+            signature_only,
+        })
+    };
+
+    if a.version != b.version {
+        push(
+            "version".to_string(),
+            a.version.to_string(),
+            b.version.to_string(),
+            false,
+        );
+    }
+
+    if a.inputs.len() != b.inputs.len() {
+        push(
+            "inputs.len".to_string(),
+            a.inputs.len().to_string(),
+            b.inputs.len().to_string(),
+            false,
+        );
+    }
+    for (i, (a, b)) in a.inputs.iter().zip(b.inputs.iter()).enumerate() {
+        diff_input(a, b, |field, left, right, signature_only| {
+            push(format!("inputs[{i}].{field}"), left, right, signature_only)
+        });
+    }
+
+    if a.outputs.len() != b.outputs.len() {
+        push(
+            "outputs.len".to_string(),
+            a.outputs.len().to_string(),
+            b.outputs.len().to_string(),
+            false,
+        );
+    }
+    for (i, (a, b)) in a.outputs.iter().zip(b.outputs.iter()).enumerate() {
+        diff_output(a, b, |field, left, right| {
+            push(format!("outputs[{i}].{field}"), left, right, false)
+        });
+    }
+
+    if a.locktime != b.locktime {
+        push(
+            "lock_time".to_string(),
+            a.locktime.to_string(),
+            b.locktime.to_string(),
+            false,
+        );
+    }
+
+    TxDiff { differences }
+}
+
+fn diff_input<F>(a: &TransactionInput, b: &TransactionInput, mut push: F)
+where
+    F: FnMut(&str, String, String, bool),
+{
+    if a.previous_output != b.previous_output {
+        push(
+            "outpoint",
+            outpoint_to_string(&a.previous_output),
+            outpoint_to_string(&b.previous_output),
+            false,
+        );
+    }
+    if a.sequence != b.sequence {
+        push(
+            "sequence",
+            a.sequence.to_string(),
+            b.sequence.to_string(),
+            false,
+        );
+    }
+    if a.script_sig != b.script_sig {
+        push(
+            "script_sig",
+            a.script_sig.to_asm(true),
+            b.script_sig.to_asm(true),
+            script_sig_signature_only(&a.script_sig, &b.script_sig),
+        );
+    }
+    if a.witness != b.witness {
+        push(
+            "witness",
+            witness_to_string(&a.witness),
+            witness_to_string(&b.witness),
+            witness_signature_only(&a.witness, &b.witness),
+        );
+    }
+}
+
+fn diff_output<F>(a: &TransactionOutput, b: &TransactionOutput, mut push: F)
+where
+    F: FnMut(&str, String, String),
+{
+    if a.value != b.value {
+        push("value", a.value.to_string(), b.value.to_string());
+    }
+    if a.script_pubkey != b.script_pubkey {
+        push(
+            "script_pubkey",
+            a.script_pubkey.to_asm(false),
+            b.script_pubkey.to_asm(false),
+        );
+    }
+}
+
+/// Whether the push-only scriptSig's have the same operations except for the pushed signatures.
+fn script_sig_signature_only(a: &Script, b: &Script) -> bool {
+    let (Some(a_ops), Some(b_ops)) = (a.push_only_ops(), b.push_only_ops()) else {
+        return false;
+    };
+    a_ops.len() == b_ops.len()
+        && a_ops
+            .iter()
+            .zip(b_ops.iter())
+            .all(|(a_op, b_op)| a_op == b_op || is_signature_pair(a_op.1, b_op.1))
+}
+
+/// Whether the witnesses have the same items except for the signatures.
+fn witness_signature_only(a: &Witness, b: &Witness) -> bool {
+    let (a_items, b_items) = (a.as_items(), b.as_items());
+    a_items.len() == b_items.len()
+        && a_items.iter().zip(b_items.iter()).all(|(a_item, b_item)| {
+            a_item == b_item || is_signature_pair(a_item.as_slice(), b_item.as_slice())
+        })
+}
+
+/// Whether both items are signatures of the same kind and sighash type.
+fn is_signature_pair(a: &[u8], b: &[u8]) -> bool {
+    let same_sighash = a.last() == b.last();
+    let ecdsa = is_valid_signature_encoding(a) && is_valid_signature_encoding(b);
+    // `SIGHASH_DEFAULT` signatures are 64 bytes long, the others have the sighash type appended.
+    let schnorr = a.len() == b.len() && matches!(a.len(), 64 | 65);
+    (ecdsa && same_sighash) || (schnorr && (a.len() == 64 || same_sighash))
+}
+
+/// Returns the outpoint as `txid:vout`, where `txid` is displayed as by block explorers.
+fn outpoint_to_string(outpoint: &OutPoint) -> String {
+    format!(
+        "{}:{}",
+        hex::encode(outpoint.hash.rev().as_slice(), false),
+        outpoint.index
+    )
+}
+
+/// Returns the hex encoded witness items separated by spaces, e.g. `[<sig> <pubkey>]`.
+fn witness_to_string(witness: &Witness) -> String {
+    let items: Vec<_> = witness
+        .as_items()
+        .iter()
+        .map(|item| hex::encode(item.as_slice(), false))
+        .collect();
+    format!("[{}]", items.join(" "))
+}
@@ -2,14 +2,22 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::constants::MAX_STANDARD_TX_SIGOPS_COST;
 use crate::dust::dust_filter::DustFilter;
-use crate::dust::DustPolicy;
+use crate::fee_policy::FeePolicy;
 use crate::modules::utxo_selector::exact_selector::ExactInputSelector;
 use crate::modules::utxo_selector::max_selector::MaxInputSelector;
-use crate::modules::utxo_selector::{InputSelector, SelectResult};
-use crate::transaction::transaction_interface::TransactionInterface;
+use crate::modules::utxo_selector::{
+    ExcludedUtxo, ExclusionReason, InputSelector, SelectResult, SelectionPolicy,
+};
+use crate::shuffle::ChaChaShuffleRng;
+use crate::sighash::SighashBase;
+use crate::transaction::transaction_interface::{
+    TransactionInterface, TxInputInterface, TxOutputInterface,
+};
 use crate::transaction::transaction_parts::Amount;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use crate::truc::{self, UnconfirmedParent};
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
 
@@ -23,8 +31,25 @@ pub const MAX_TRANSACTION_SIZE: usize = 100 * 1024;
 /// If needed to add chain specific parameters, consider adding a different Request struct.
 pub struct PlanRequest<Transaction: TransactionInterface> {
     pub ty: RequestType<Transaction>,
-    pub dust_policy: DustPolicy,
+    /// Dust and fee policy of the chain, see [`crate::chain_params::ChainParams::fee_policy`].
+    pub fee_policy: FeePolicy,
     pub fee_per_vbyte: Amount,
+    /// Some chains require the fee to be declared as an explicit output to a well-known script.
+    /// If set, the planner pushes the output with the computed fee amount,
+    /// so the sum of UTXO amounts is exactly equal to the sum of output amounts.
+    ///
+    /// The output value is ignored and overridden.
+    pub explicit_fee_output: Option<Transaction::Output>,
+    /// Which UTXOs can be selected by their confirmation metadata.
+    pub selection_policy: SelectionPolicy,
+    /// If set, the outputs of the planned transaction, including change and the fee output,
+    /// are shuffled by the [`ChaChaShuffleRng`] of the seed. The same seed gives the same order.
+    /// Not allowed if any of the selected inputs is signed with `SIGHASH_SINGLE`.
+    pub output_shuffle_seed: Option<[u8; 32]>,
+    /// The unconfirmed transactions spent by the planned one.
+    /// The planned transaction is checked against the TRUC limits along with them,
+    /// see [`truc::check_topology`].
+    pub unconfirmed_parents: Vec<UnconfirmedParent>,
 }
 
 pub enum RequestType<Transaction: TransactionInterface> {
@@ -51,42 +76,177 @@ impl<Transaction> TxPlanner<Transaction>
 where
     Transaction: TransactionInterface,
 {
-    /// * Filters dust UTXOs
+    /// * Checks if the fee rate is within the chain fee policy bounds
+    /// * Filters dust UTXOs and the ones not allowed by the selection policy
     /// * Checks if all outputs are not dust
     /// * Select UTXOs as specified in the request
+    /// * Checks if the fee is not absurd
+    /// * Pushes the explicit fee output if required
+    /// * Shuffles the outputs if requested, unless an input is signed with `SIGHASH_SINGLE`
+    /// * Checks if the transaction sigops cost is standard
+    /// * Checks if the transaction and its unconfirmed parents are within the TRUC limits
     pub fn plan(request: PlanRequest<Transaction>) -> SigningResult<SelectResult<Transaction>> {
-        let dust_filter = DustFilter::new(request.dust_policy);
+        let fee_policy = request.fee_policy;
+        fee_policy
+            .check_fee_rate(request.fee_per_vbyte)
+            .context("Invalid fee rate")?;
 
-        let select_result = match request.ty {
+        let dust_policy = fee_policy.dust_policy;
+        let dust_filter = DustFilter::new(dust_policy);
+        let policy = request.selection_policy;
+
+        let mut excluded = Vec::new();
+        let mut select_result = match request.ty {
             RequestType::SendMax { unsigned_tx } => {
-                let unsigned_tx = dust_filter
-                    .filter_inputs(unsigned_tx)
-                    .context("Error filtering dust UTXOs")?;
+                let unsigned_tx =
+                    Self::exclude_inputs(unsigned_tx, &dust_filter, policy, &mut excluded)
+                        .context("Error filtering UTXOs")?;
 
                 MaxInputSelector::new(unsigned_tx)
-                    .select_max(request.fee_per_vbyte, request.dust_policy)
+                    .maybe_fee_output(request.explicit_fee_output.clone())
+                    .select_max(request.fee_per_vbyte, dust_policy)
             },
             RequestType::SendExact {
                 unsigned_tx,
                 change_output,
                 input_selector,
             } => {
-                let unsigned_tx = dust_filter
-                    .filter_inputs(unsigned_tx)
-                    .context("Error filtering dust UTXOs")?;
+                let unsigned_tx =
+                    Self::exclude_inputs(unsigned_tx, &dust_filter, policy, &mut excluded)
+                        .context("Error filtering UTXOs")?;
 
                 ExactInputSelector::new(unsigned_tx)
                     .maybe_change_output(change_output)
-                    .select_inputs(request.dust_policy, input_selector, request.fee_per_vbyte)
+                    .maybe_fee_output(request.explicit_fee_output.clone())
+                    .select_inputs(dust_policy, input_selector, request.fee_per_vbyte)
             },
         }
         .context("Error selecting UTXOs")?;
+        select_result.plan.excluded = excluded;
 
         // Check outputs after all manipulations are done, as there could `change` or `max` amounts be less than `dust`.
         dust_filter
             .check_outputs(&select_result.unsigned_tx)
             .context("There are dust output amounts")?;
 
+        fee_policy.check_absurd_fee(
+            select_result.plan.fee_estimate,
+            select_result.plan.vsize_estimate,
+        )?;
+
+        let mut select_result = match request.explicit_fee_output {
+            Some(fee_output) => Self::push_fee_output(select_result, fee_output)?,
+            None => select_result,
+        };
+
+        if let Some(seed) = request.output_shuffle_seed {
+            Self::check_no_sighash_single(&select_result.unsigned_tx)
+                .context("Outputs cannot be shuffled")?;
+            select_result
+                .unsigned_tx
+                .shuffle_outputs(&mut ChaChaShuffleRng::from_seed(seed));
+        }
+
+        Self::check_sigop_cost(&select_result.unsigned_tx)?;
+        truc::check_topology(
+            select_result.unsigned_tx.transaction(),
+            select_result.plan.vsize_estimate,
+            &request.unconfirmed_parents,
+        )
+        .context("The transaction violates the TRUC topology limits")?;
+        Ok(select_result)
+    }
+
+    /// `SIGHASH_SINGLE` signs the output at the index of the input,
+    /// so the outputs must keep their order if any of the selected inputs uses it.
+    fn check_no_sighash_single(
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+    ) -> SigningResult<()> {
+        let single = unsigned_tx
+            .input_args()
+            .iter()
+            .position(|utxo| utxo.sighash_ty.base_type() == SighashBase::Single);
+        if let Some(input_index) = single {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Input #{} is signed with SIGHASH_SINGLE",
+                unsigned_tx.request_index(input_index)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Filters out the dust UTXOs and the ones not allowed by the `policy`,
+    /// and reports them to `excluded`.
+    fn exclude_inputs(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        dust_filter: &DustFilter<Transaction>,
+        policy: SelectionPolicy,
+        excluded: &mut Vec<ExcludedUtxo>,
+    ) -> SigningResult<UnsignedTransaction<Transaction>> {
+        // Report the UTXOs before filtering, while they have their original indexes.
+        for (index, (input, arg)) in unsigned_tx
+            .inputs()
+            .iter()
+            .zip(unsigned_tx.input_args())
+            .enumerate()
+        {
+            let reason = if dust_filter.is_dust_input(arg) {
+                Some(ExclusionReason::Dust)
+            } else {
+                policy.exclusion_reason(arg)
+            };
+
+            if let Some(reason) = reason {
+                excluded.push(ExcludedUtxo {
+                    index,
+                    previous_output: *input.previous_output(),
+                    reason,
+                });
+            }
+        }
+
+        let mut unsigned_tx = dust_filter.filter_inputs(unsigned_tx)?;
+        unsigned_tx.retain_inputs(|_, arg| policy.exclusion_reason(arg).is_none())?;
+        Ok(unsigned_tx)
+    }
+
+    /// Checks if the transaction with estimated signatures doesn't exceed the standard sigops cost.
+    fn check_sigop_cost(unsigned_tx: &UnsignedTransaction<Transaction>) -> SigningResult<()> {
+        let prevout_script_pubkeys = unsigned_tx
+            .input_args()
+            .iter()
+            .map(|arg| &arg.prevout_script_pubkey);
+        let sigop_cost = unsigned_tx
+            .estimate_transaction()
+            .total_sigop_cost(prevout_script_pubkeys)?;
+
+        if sigop_cost > MAX_STANDARD_TX_SIGOPS_COST {
+            return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+                "Transaction sigops cost '{sigop_cost}' exceeds the standard limit '{MAX_STANDARD_TX_SIGOPS_COST}'"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Declares the transaction fee as an explicit output.
+    ///
+    /// The fee output is taken into account on fee estimation by the UTXO selectors,
+    /// so the fee covers the output size. It's never checked for dust though.
+    fn push_fee_output(
+        mut select_result: SelectResult<Transaction>,
+        mut fee_output: Transaction::Output,
+    ) -> SigningResult<SelectResult<Transaction>> {
+        fee_output.set_value(select_result.plan.fee_estimate);
+        select_result.unsigned_tx.push_output(fee_output);
+
+        let total_in = select_result.unsigned_tx.total_input()?;
+        let total_out = select_result.unsigned_tx.total_output()?;
+        if total_in != total_out {
+            return SigningError::err(SigningErrorType::Error_internal).context(format!(
+                "Sum of UTXO amounts '{total_in}' must be equal to the sum of output amounts '{total_out}' including the fee output"
+            ));
+        }
+
         Ok(select_result)
     }
 }
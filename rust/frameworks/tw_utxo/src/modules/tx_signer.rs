@@ -2,25 +2,40 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::encode::Decodable;
 use crate::modules::keys_manager::KeysManager;
-use crate::modules::sighash_computer::{SighashComputer, TxPreimage, UtxoSighash};
+use crate::modules::sighash_computer::{SighashComputer, UtxoSighash};
+use crate::modules::signer_metrics::{measure, NoopSignerMetrics, SignerMetrics, SigningPhase};
+use crate::modules::signing_session::SigningSession;
 use crate::modules::tx_compiler::TxCompiler;
 use crate::signing_mode::SigningMethod;
 use crate::transaction::transaction_interface::TransactionInterface;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::TransactionPreimage;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+use tw_keypair::schnorr;
 use tw_keypair::traits::SigningKeyTrait;
-use tw_keypair::{ecdsa, schnorr};
+use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
 
+/// Both ECDSA and Schnorr signatures start with the 32 bytes R value derived from the nonce.
+const SIGNATURE_R_LEN: usize = 32;
+
 /// Transaction Signer with a standard Bitcoin behaviour.
 ///
 /// # Important
 ///
 /// If needed to implement a custom logic, consider adding a different Transaction Signer.
+///
+/// # Thread safety
+///
+/// The signer doesn't use any process-global mutable state: caches and the secp256k1 context
+/// are owned by the given [`KeysManager`], so different transactions can be signed concurrently.
 pub struct TxSigner<Transaction> {
     _phantom: PhantomData<Transaction>,
 }
@@ -33,31 +48,146 @@ where
         unsigned_tx: UnsignedTransaction<Transaction>,
         keys_manager: &KeysManager,
     ) -> SigningResult<Transaction> {
-        let TxPreimage { sighashes } =
-            SighashComputer::preimage_tx(&unsigned_tx).context("Error sighash pre-imaging")?;
+        Self::sign_tx_with_metrics(unsigned_tx, keys_manager, &NoopSignerMetrics)
+    }
+
+    /// Signs the transaction reporting every [`SigningPhase`] of every input to `metrics`.
+    pub fn sign_tx_with_metrics(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        keys_manager: &KeysManager,
+        metrics: &dyn SignerMetrics,
+    ) -> SigningResult<Transaction> {
+        Self::sign_tx_with_signer(unsigned_tx, metrics, |sighash| {
+            Self::sign_sighash(keys_manager, sighash)
+        })
+    }
 
-        let signatures: Vec<_> = sighashes
+    /// Signs the transaction with the given sighash signer, e.g. an external one.
+    ///
+    /// Returns [`SigningErrorType::Error_nonce_reuse_detected`] if two signatures share the same
+    /// nonce while signing different sighashes, as that leaks the private key.
+    pub fn sign_tx_with_signer<F>(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        metrics: &dyn SignerMetrics,
+        sign_sighash: F,
+    ) -> SigningResult<Transaction>
+    where
+        F: Fn(&UtxoSighash) -> SigningResult<SignatureBytes>,
+    {
+        let mut nonce_guard = NonceReuseGuard::default();
+        let claims = unsigned_tx
+            .input_args()
             .iter()
-            .map(|sighash| match sighash.signing_method {
-                SigningMethod::Legacy | SigningMethod::Segwit => {
-                    Self::sign_legacy_sighash(keys_manager, sighash)
-                },
-                SigningMethod::Taproot => Self::sign_taproot_sighash(keys_manager, sighash),
+            .enumerate()
+            .map(|(input_index, utxo)| {
+                let sighash = measure(metrics, SigningPhase::Preimage, input_index, || {
+                    SighashComputer::preimage_utxo(&unsigned_tx, input_index, utxo)
+                })
+                .context("Error sighash pre-imaging")?;
+
+                let signature = measure(metrics, SigningPhase::Signing, input_index, || {
+                    sign_sighash(&sighash)
+                })?;
+                nonce_guard.check(input_index, &sighash.sighash, &signature)?;
+
+                TxCompiler::utxo_spending_data(utxo, &signature, input_index, metrics)
             })
             .collect::<SigningResult<_>>()?;
 
-        TxCompiler::compile(unsigned_tx, &signatures)
+        TxCompiler::compile_spending_data(unsigned_tx, claims)
     }
 
-    pub fn sign_legacy_sighash(
+    /// Signs every input that needs a signature with the same `private_key`,
+    /// e.g. when sweeping a single address.
+    ///
+    /// The key is parsed and its public keys are derived once for all inputs.
+    /// Returns an error listing all the inputs that cannot be signed with the key.
+    pub fn sign_with_single_key(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        private_key: &[u8],
+    ) -> SigningResult<Transaction> {
+        let has_taproot = unsigned_tx
+            .input_args()
+            .iter()
+            .any(|utxo| utxo.signing_method == SigningMethod::Taproot);
+
+        let mut keys_manager = KeysManager::default();
+        keys_manager.add_ecdsa_private_bytes(private_key)?;
+        if has_taproot {
+            keys_manager.add_schnorr_private_bytes(private_key, false)?;
+        }
+
+        let mut claims = Vec::with_capacity(unsigned_tx.input_args().len());
+        let mut unsatisfied = Vec::new();
+        let mut nonce_guard = NonceReuseGuard::default();
+        for (input_index, utxo) in unsigned_tx.input_args().iter().enumerate() {
+            let sighash = SighashComputer::preimage_utxo(&unsigned_tx, input_index, utxo)
+                .context("Error sighash pre-imaging")?;
+
+            match Self::sign_sighash(&keys_manager, &sighash) {
+                Ok(signature) => {
+                    nonce_guard.check(input_index, &sighash.sighash, &signature)?;
+                    claims.push(TxCompiler::utxo_spending_data(
+                        utxo,
+                        &signature,
+                        input_index,
+                        &NoopSignerMetrics,
+                    )?)
+                },
+                // Keep checking the other inputs to report all of them at once.
+                Err(e) if e.error_type() == &SigningErrorType::Error_missing_private_key => {
+                    unsatisfied.push(input_index.to_string())
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        if !unsatisfied.is_empty() {
+            return SigningError::err(SigningErrorType::Error_missing_private_key).context(
+                format!(
+                    "The private key cannot sign the inputs: {}",
+                    unsatisfied.join(", ")
+                ),
+            );
+        }
+
+        TxCompiler::compile_spending_data(unsigned_tx, claims)
+    }
+
+    /// Starts a signing session that can be saved with [`SigningSession::to_bytes`]
+    /// and resumed later with [`TxSigner::resume`].
+    pub fn start_session(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+    ) -> SigningResult<SigningSession<Transaction>> {
+        SigningSession::new(unsigned_tx)
+    }
+
+    /// Resumes a saved signing session to sign the remaining inputs only.
+    /// The signatures produced before are verified and preserved exactly.
+    pub fn resume(session: &[u8]) -> SigningResult<SigningSession<Transaction>>
+    where
+        Transaction: Decodable,
+    {
+        SigningSession::resume(session)
+    }
+
+    pub fn sign_sighash(
         keys_manager: &KeysManager,
         sighash: &UtxoSighash,
     ) -> SigningResult<SignatureBytes> {
-        let public_key = ecdsa::secp256k1::PublicKey::try_from(sighash.signer_pubkey.as_slice())
-            .into_tw()
-            .context("Signing error: expected a valid signer ecdsa secp256k1 public key")?;
+        match sighash.signing_method {
+            SigningMethod::Legacy | SigningMethod::Segwit => {
+                Self::sign_legacy_sighash(keys_manager, sighash)
+            },
+            SigningMethod::Taproot => Self::sign_taproot_sighash(keys_manager, sighash),
+        }
+    }
 
-        let private_key = keys_manager.get_ecdsa_private(&public_key)?;
+    pub fn sign_legacy_sighash(
+        keys_manager: &KeysManager,
+        sighash: &UtxoSighash,
+    ) -> SigningResult<SignatureBytes> {
+        let private_key = keys_manager.get_ecdsa_private_by_bytes(&sighash.signer_pubkey)?;
         let signature = private_key
             .sign(sighash.sighash)
             .into_tw()
@@ -77,10 +207,47 @@ where
         let private_key =
             keys_manager.get_schnorr_private(&x_only_pubkey, &sighash.taproot_tweak)?;
         let signature = private_key
-            .sign(sighash.sighash)
+            .sign_with_context(keys_manager.schnorr_context(), sighash.sighash)
             .into_tw()
             .context("Error signing sighash with a schnorr private key")?;
 
         Ok(signature.to_vec())
     }
 }
+
+/// A safety net against the nonce reuse across the inputs of a transaction.
+///
+/// The nonces are deterministic (RFC6979 and BIP340), so the same nonce can only be used
+/// to sign the same sighash, unless there is a bug in the nonce generation.
+#[derive(Default)]
+pub(crate) struct NonceReuseGuard {
+    /// The sighashes by the R value of their signatures.
+    signed: HashMap<Data, H256>,
+}
+
+impl NonceReuseGuard {
+    pub(crate) fn check(
+        &mut self,
+        input_index: usize,
+        sighash: &H256,
+        signature: &[u8],
+    ) -> SigningResult<()> {
+        let r = signature
+            .get(..SIGNATURE_R_LEN)
+            .or_tw_err(SigningErrorType::Error_signing)
+            .with_context(|| format!("Invalid signature of input #{input_index}"))?;
+
+        match self.signed.entry(r.to_vec()) {
+            Entry::Occupied(signed) if signed.get() != sighash => {
+                SigningError::err(SigningErrorType::Error_nonce_reuse_detected).context(format!(
+                    "The signature of input #{input_index} reuses the nonce of another input"
+                ))
+            },
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(*sighash);
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::replay_protection::ReplayProtection;
+use crate::script::standard_script::conditions::ScriptPubkeyType;
+use crate::script::Script;
+use crate::transaction::transaction_interface::{
+    TransactionInterface, TxInputInterface, TxOutputInterface,
+};
+use crate::transaction::transaction_parts::Amount;
+use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use std::fmt;
+use std::marker::PhantomData;
+use tw_encoding::hex;
+
+/// A human-readable summary of a transaction output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputSummary {
+    pub value: Amount,
+    pub script_type: ScriptPubkeyType,
+    /// Hex encoded scriptPubkey.
+    pub script_pubkey: String,
+}
+
+impl fmt::Display for OutputSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.value,
+            self.script_type.as_str(),
+            self.script_pubkey
+        )
+    }
+}
+
+/// A human-readable summary of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxSummary {
+    /// Payment outputs, including change.
+    pub outputs: Vec<OutputSummary>,
+    /// The fee declared as an explicit output, if any.
+    /// See [`TxSummaryBuilder::summarize_with_fee_output`].
+    pub fee: Option<Amount>,
+    pub vsize: usize,
+    pub weight: usize,
+    /// Whether the transaction signals opt-in Replace-By-Fee.
+    pub signals_rbf: bool,
+    /// The replay protection applied by the builder, if any, e.g. `fork-id sighash`.
+    /// See [`TxSummaryBuilder::summarize_unsigned`].
+    pub replay_protection: Option<String>,
+    /// Non-critical issues, e.g. non-standard outputs or scriptSig's that may not be relayed by nodes.
+    pub warnings: Vec<String>,
+}
+
+pub struct TxSummaryBuilder<Transaction> {
+    _phantom: PhantomData<Transaction>,
+}
+
+impl<Transaction: TransactionInterface> TxSummaryBuilder<Transaction> {
+    pub fn summarize(tx: &Transaction) -> TxSummary {
+        Self::summarize_impl(tx, None, &[])
+    }
+
+    /// Summarizes a transaction, classifying the P2WSH outputs by the given witness scripts
+    /// if known, e.g. [`ScriptPubkeyType::Timelocked`] vaults.
+    pub fn summarize_with_witness_scripts(
+        tx: &Transaction,
+        witness_scripts: &[Script],
+    ) -> TxSummary {
+        Self::summarize_impl(tx, None, witness_scripts)
+    }
+
+    /// Summarizes an unsigned transaction along with the replay protection applied by the builder.
+    pub fn summarize_unsigned(unsigned_tx: &UnsignedTransaction<Transaction>) -> TxSummary {
+        let mut summary = Self::summarize(unsigned_tx.transaction());
+        summary.replay_protection = unsigned_tx
+            .replay_protection()
+            .map(ReplayProtection::to_string);
+        summary
+    }
+
+    /// Summarizes a transaction of a chain that requires the fee to be declared
+    /// as an explicit output to the `fee_script_pubkey`.
+    /// The last output to the script is reported as the fee rather than a payment.
+    pub fn summarize_with_fee_output(tx: &Transaction, fee_script_pubkey: &Script) -> TxSummary {
+        let fee_output_idx = tx
+            .outputs()
+            .iter()
+            .rposition(|output| output.script_pubkey() == fee_script_pubkey);
+        Self::summarize_impl(tx, fee_output_idx, &[])
+    }
+
+    fn summarize_impl(
+        tx: &Transaction,
+        fee_output_idx: Option<usize>,
+        witness_scripts: &[Script],
+    ) -> TxSummary {
+        let mut warnings = Vec::new();
+
+        // Non-minimal pushes make the transaction non-standard (`SCRIPT_VERIFY_MINIMALDATA`).
+        for (i, input) in tx.inputs().iter().enumerate() {
+            if let Err(e) = input.script_sig().instructions_minimal() {
+                warnings.push(format!(
+                    "Input #{i} has a non-standard scriptSig and may not be relayed: {e}"
+                ));
+            }
+        }
+
+        let fee = fee_output_idx
+            .and_then(|idx| tx.outputs().get(idx))
+            .map(|output| output.value());
+
+        let outputs = tx
+            .outputs()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != fee_output_idx)
+            .map(|(i, output)| {
+                let script_type = ScriptPubkeyType::classify(output.script_pubkey());
+                if !script_type.is_standard() {
+                    warnings.push(format!(
+                        "Output #{i} has a non-standard scriptPubkey and may not be relayed"
+                    ));
+                }
+                let script_type =
+                    resolve_witness_script(output.script_pubkey(), script_type, witness_scripts);
+
+                OutputSummary {
+                    value: output.value(),
+                    script_type,
+                    script_pubkey: hex::encode(output.script_pubkey().as_slice(), false),
+                }
+            })
+            .collect();
+
+        TxSummary {
+            outputs,
+            fee,
+            vsize: tx.vsize(),
+            weight: tx.weight(),
+            signals_rbf: tx.signals_rbf(),
+            replay_protection: None,
+            warnings,
+        }
+    }
+}
+
+/// Classifies a P2WSH output by its witness script if it's among the `witness_scripts`.
+fn resolve_witness_script(
+    script_pubkey: &Script,
+    script_type: ScriptPubkeyType,
+    witness_scripts: &[Script],
+) -> ScriptPubkeyType {
+    if script_type != ScriptPubkeyType::P2WSH {
+        return script_type;
+    }
+
+    witness_scripts
+        .iter()
+        .map(|witness_script| {
+            ScriptPubkeyType::classify_with_witness_script(script_pubkey, witness_script)
+        })
+        .find(|ty| *ty != ScriptPubkeyType::P2WSH)
+        .unwrap_or(script_type)
+}
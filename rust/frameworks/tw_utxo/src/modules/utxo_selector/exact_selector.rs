@@ -7,6 +7,7 @@ use crate::dust::DustPolicy;
 use crate::modules::fee_estimator::FeeEstimator;
 use crate::modules::utxo_selector::{InputSelector, SelectPlan, SelectResult};
 use crate::script::{Script, Witness};
+use crate::spec_location::SpecLocation;
 use crate::transaction::transaction_interface::{
     TransactionInterface, TxInputInterface, TxOutputInterface,
 };
@@ -19,6 +20,7 @@ use tw_coin_entry::error::prelude::*;
 pub struct ExactInputSelector<Transaction: TransactionInterface> {
     unsigned_tx: UnsignedTransaction<Transaction>,
     change_output: Option<Transaction::Output>,
+    fee_output: Option<Transaction::Output>,
 }
 
 impl<Transaction> ExactInputSelector<Transaction>
@@ -29,6 +31,7 @@ where
         ExactInputSelector {
             unsigned_tx,
             change_output: None,
+            fee_output: None,
         }
     }
 
@@ -37,6 +40,13 @@ where
         self
     }
 
+    /// The explicit fee output is only taken into account on fee estimation,
+    /// the caller pushes it with the computed fee amount.
+    pub fn maybe_fee_output(mut self, output: Option<Transaction::Output>) -> Self {
+        self.fee_output = output;
+        self
+    }
+
     pub fn select_inputs(
         mut self,
         dust_policy: DustPolicy,
@@ -54,14 +64,21 @@ where
             // the change output will be pushed to the result unsigned transaction.
             estimated_tx.push_output(change_output.clone());
         }
+        if let Some(ref fee_output) = self.fee_output {
+            estimated_tx.push_output(fee_output.clone());
+        }
 
         // Prepare the available UTXOs.
         // Please note all the Transaction inputs contain estimated script sigs.
-        let mut utxos: Vec<(Transaction::Input, &UtxoToSign)> = estimated_tx
-            .inputs()
+        // The UTXOs keep their index in the signing request, so the errors can locate them.
+        let mut utxos: Vec<(usize, Transaction::Input, &UtxoToSign)> = self
+            .unsigned_tx
+            .request_indices()
             .iter()
-            .cloned()
+            .copied()
+            .zip(estimated_tx.inputs().iter().cloned())
             .zip(self.unsigned_tx.input_args())
+            .map(|((request_index, input), arg)| (request_index, input, arg))
             .collect();
 
         // Sort the UTXOs.
@@ -69,30 +86,36 @@ where
             // Nothing to do.
             InputSelector::UseAll | InputSelector::InOrder => (),
             InputSelector::Ascending => {
-                utxos.sort_by(|(_, a), (_, b)| a.amount.cmp(&b.amount));
+                utxos.sort_by(|(_, _, a), (_, _, b)| a.amount.cmp(&b.amount));
             },
             InputSelector::Descending => {
-                utxos.sort_by(|(_, a), (_, b)| b.amount.cmp(&a.amount));
+                utxos.sort_by(|(_, _, a), (_, _, b)| b.amount.cmp(&a.amount));
             },
         }
 
         // Select the UTXOs to cover all the outputs and the fee.
-        let mut total_in = 0;
-        let mut tx_fee = 0;
+        let mut total_in: Amount = 0;
+        let mut tx_fee: Amount = 0;
         let mut selected_utxos = Vec::with_capacity(utxos.len());
         let mut selected_utxo_args = Vec::with_capacity(utxos.len());
+        let mut selected_request_indices = Vec::with_capacity(utxos.len());
 
         let mut total_covered = false;
-        for (input, arg) in utxos {
+        for (request_index, input, arg) in utxos {
             // Estimated script_sig or witness MUST be set.
             debug_assert!(input.has_witness() || input.has_script_sig());
 
             // Update total input amount.
-            total_in += arg.amount;
+            total_in = total_in
+                .checked_add(arg.amount)
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction UTXO amounts is too big")
+                .with_context(|| SpecLocation::of_utxo(request_index, arg))?;
 
             // Track the selected UTXOs.
             selected_utxos.push(input.clone());
             selected_utxo_args.push(arg.clone());
+            selected_request_indices.push(request_index);
 
             // Update the transaction with (all) the selected in UTXOs.
             estimated_tx.replace_inputs(selected_utxos.clone());
@@ -100,15 +123,20 @@ where
             // Check the transaction weight.
             if estimated_tx.weight() > MAX_TRANSACTION_WEIGHT {
                 return SigningError::err(SigningErrorType::Error_tx_too_big)
-                    .context("Too many UTXOs required to send the requested amounts");
+                    .context("Too many UTXOs required to send the requested amounts")
+                    .context(SpecLocation::of_utxo(request_index, arg));
             }
 
             // Estimate the transaction fee.
             tx_fee = FeeEstimator::estimate_fee(&estimated_tx, fee_rate)?;
+            let total_required = total_out
+                .checked_add(tx_fee)
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction output amounts and fee is too big")?;
 
             // Check if the total input amount covers the total output amount
             // and the fee.
-            if total_in >= total_out + tx_fee {
+            if total_in >= total_required {
                 total_covered = true;
 
                 // Unless we're told to use all inputs, we can stop here.
@@ -125,11 +153,12 @@ where
         }
 
         // Calculate the change amount.
-        debug_assert!(
-            total_in >= total_out + tx_fee,
-            "All amounts must be checked already"
-        );
-        let mut change = total_in - total_out - tx_fee;
+        // Amounts are checked already, but use checked math to avoid panicking on underflow.
+        let mut change = total_in
+            .checked_sub(total_out)
+            .and_then(|remaining| remaining.checked_sub(tx_fee))
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Total input amount must cover the output amounts and fee")?;
 
         let dust_threshold = dust_policy.dust_threshold();
         match self.change_output {
@@ -148,8 +177,11 @@ where
             utxo.set_witness(Witness::default());
         }
 
-        self.unsigned_tx
-            .set_inputs(selected_utxos, selected_utxo_args)?;
+        self.unsigned_tx.set_selected_inputs(
+            selected_utxos,
+            selected_utxo_args,
+            selected_request_indices,
+        )?;
 
         debug_assert!(total_out <= total_in);
         let fee_estimate = total_in - total_out;
@@ -162,6 +194,10 @@ where
             vsize_estimate: estimated_tx.vsize(),
             fee_estimate,
             change,
+            signals_rbf: self.unsigned_tx.transaction().signals_rbf(),
+            // Filled by the planner.
+            excluded: Vec::new(),
+            replay_protection: self.unsigned_tx.replay_protection().cloned(),
         };
         Ok(SelectResult {
             unsigned_tx: self.unsigned_tx,
@@ -16,8 +16,9 @@ use crate::transaction::UtxoToSign;
 use itertools::Itertools;
 use tw_coin_entry::error::prelude::*;
 
-pub struct MaxInputSelector<Transaction> {
+pub struct MaxInputSelector<Transaction: TransactionInterface> {
     unsigned_tx: UnsignedTransaction<Transaction>,
+    fee_output: Option<Transaction::Output>,
 }
 
 impl<Transaction> MaxInputSelector<Transaction>
@@ -25,7 +26,17 @@ where
     Transaction: TransactionInterface,
 {
     pub fn new(unsigned_tx: UnsignedTransaction<Transaction>) -> Self {
-        MaxInputSelector { unsigned_tx }
+        MaxInputSelector {
+            unsigned_tx,
+            fee_output: None,
+        }
+    }
+
+    /// The explicit fee output is only taken into account on fee estimation,
+    /// the caller pushes it with the computed fee amount.
+    pub fn maybe_fee_output(mut self, output: Option<Transaction::Output>) -> Self {
+        self.fee_output = output;
+        self
     }
 
     pub fn select_max(
@@ -39,27 +50,29 @@ where
         }
 
         let mut estimated_tx = self.unsigned_tx.estimate_transaction();
+        if let Some(ref fee_output) = self.fee_output {
+            estimated_tx.push_output(fee_output.clone());
+        }
         let mut total_in = self.unsigned_tx.total_input()?;
 
-        // Sort the transaction inputs and their arguments in descending order.
+        // Sort the transaction inputs, their arguments and request indices in descending order.
         // Please note all the Transaction inputs contain estimated script sigs.
-        let (mut selected_utxos, mut selected_utxo_args): (
-            Vec<Transaction::Input>,
-            Vec<UtxoToSign>,
-        ) = estimated_tx
+        let mut selected: Vec<(Transaction::Input, UtxoToSign, usize)> = estimated_tx
             .inputs()
             .iter()
             .cloned()
             .zip(self.unsigned_tx.input_args().iter().cloned())
+            .zip(self.unsigned_tx.request_indices().iter().copied())
+            .map(|((utxo, arg), request_index)| (utxo, arg, request_index))
             // Sort the UTXOs in the descending order (largest first).
-            .sorted_by(|(_, a), (_, b)| b.amount.cmp(&a.amount))
-            .unzip();
+            .sorted_by(|(_, a, _), (_, b, _)| b.amount.cmp(&a.amount))
+            .collect();
 
         // Remove transaction inputs from lower to higher until we have a transaction
         // with a normal weight (less or equal to `MAX_TRANSACTION_WEIGHT`).
-        while !selected_utxos.is_empty() {
+        while !selected.is_empty() {
             // Put all the utxos to the estimated transaction in the descending order.
-            estimated_tx.replace_inputs(selected_utxos.clone());
+            estimated_tx.replace_inputs(selected.iter().map(|(utxo, _, _)| utxo.clone()).collect());
 
             // Check the transaction weight.
             if estimated_tx.weight() <= MAX_TRANSACTION_WEIGHT {
@@ -67,32 +80,45 @@ where
             }
 
             // Remove the most lower UTXO.
-            selected_utxos.pop();
-            let removed_arg = selected_utxo_args
+            let (_, removed_arg, _) = selected
                 .pop()
-                .expect("!selected_utxos.is_empty() must be checked already");
+                .expect("!selected.is_empty() must be checked already");
             debug_assert!(total_in >= removed_arg.amount);
             total_in -= removed_arg.amount;
         }
 
         // At this moment we have either a transaction with a normal weight, or an empty list of UTXOs.
-        // If the list of UTXOs is empty, [`UnsignedTransaction::set_inputs`] will return an error.
+        // If the list of UTXOs is empty, [`UnsignedTransaction::set_selected_inputs`] will return an error.
 
-        // Clear script_sig's and witnesses before updating the result transaction.
-        for utxo in selected_utxos.iter_mut() {
+        let mut selected_utxos = Vec::with_capacity(selected.len());
+        let mut selected_utxo_args = Vec::with_capacity(selected.len());
+        let mut selected_request_indices = Vec::with_capacity(selected.len());
+        for (mut utxo, arg, request_index) in selected {
+            // Clear script_sig's and witnesses before updating the result transaction.
             utxo.set_script_sig(Script::default());
             utxo.set_witness(Witness::default());
+
+            selected_utxos.push(utxo);
+            selected_utxo_args.push(arg);
+            selected_request_indices.push(request_index);
         }
 
         // Update the result transaction.
-        self.unsigned_tx
-            .set_inputs(selected_utxos, selected_utxo_args)?;
+        self.unsigned_tx.set_selected_inputs(
+            selected_utxos,
+            selected_utxo_args,
+            selected_request_indices,
+        )?;
 
         let tx_fee = FeeEstimator::estimate_fee(&estimated_tx, fee_rate)?;
         let dust_threshold = dust_policy.dust_threshold();
 
         // Check if the total input amount covers the fee, and the remaining amount is not dust.
-        if total_in < tx_fee + dust_threshold {
+        let min_required = tx_fee
+            .checked_add(dust_threshold)
+            .or_tw_err(SigningErrorType::Error_wrong_fee)
+            .context("Sum of the transaction fee and dust threshold is too big")?;
+        if total_in < min_required {
             // Insufficient funds.
             return SigningError::err(SigningErrorType::Error_not_enough_utxos).context(format!(
                 "Insufficient funds to generate a transaction. Available '{total_in}' amount is not enough to cover transaction fee '{tx_fee}', or the remaining amount is dust"
@@ -115,6 +141,10 @@ where
             vsize_estimate: estimated_tx.vsize(),
             fee_estimate: tx_fee,
             change: 0,
+            signals_rbf: self.unsigned_tx.transaction().signals_rbf(),
+            // Filled by the planner.
+            excluded: Vec::new(),
+            replay_protection: self.unsigned_tx.replay_protection().cloned(),
         };
         Ok(SelectResult {
             unsigned_tx: self.unsigned_tx,
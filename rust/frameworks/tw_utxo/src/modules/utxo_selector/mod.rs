@@ -2,8 +2,10 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::transaction::transaction_parts::Amount;
+use crate::replay_protection::ReplayProtection;
+use crate::transaction::transaction_parts::{Amount, OutPoint};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
+use crate::transaction::UtxoToSign;
 
 pub mod exact_selector;
 pub mod max_selector;
@@ -24,6 +26,65 @@ pub enum InputSelector {
     InOrder,
 }
 
+/// Which UTXOs can be selected by their confirmation metadata,
+/// see [`UtxoToSign::confirmations`] and [`UtxoToSign::is_change_from_self`].
+/// A UTXO with an unknown number of confirmations is considered unconfirmed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionPolicy {
+    /// Any UTXO can be selected.
+    #[default]
+    Any,
+    /// Only the UTXOs with at least one confirmation.
+    OnlyConfirmed,
+    /// Only the UTXOs with at least the given number of confirmations.
+    MinConfirmations(u32),
+    /// The confirmed UTXOs and the unconfirmed change of the wallet's own transactions,
+    /// but not the unconfirmed UTXOs received from others.
+    AllowOwnUnconfirmedChange,
+}
+
+impl SelectionPolicy {
+    /// Returns the reason why the UTXO cannot be selected, if any.
+    pub fn exclusion_reason(&self, utxo: &UtxoToSign) -> Option<ExclusionReason> {
+        let confirmations = utxo.confirmations.unwrap_or_default();
+        let required = match self {
+            SelectionPolicy::Any => return None,
+            SelectionPolicy::OnlyConfirmed => 1,
+            SelectionPolicy::MinConfirmations(required) => *required,
+            SelectionPolicy::AllowOwnUnconfirmedChange if utxo.is_change_from_self => return None,
+            SelectionPolicy::AllowOwnUnconfirmedChange => 1,
+        };
+
+        match confirmations {
+            confirmations if confirmations >= required => None,
+            0 => Some(ExclusionReason::Unconfirmed),
+            _ => Some(ExclusionReason::NotEnoughConfirmations {
+                confirmations,
+                required,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExclusionReason {
+    /// The UTXO amount is less than the dust threshold.
+    Dust,
+    /// The UTXO is unconfirmed, or the number of its confirmations is unknown.
+    Unconfirmed,
+    /// The UTXO has fewer confirmations than required by [`SelectionPolicy::MinConfirmations`].
+    NotEnoughConfirmations { confirmations: u32, required: u32 },
+}
+
+/// A UTXO that has not been considered for the selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExcludedUtxo {
+    /// The index of the UTXO in the request, i.e. of the transaction input or the fee bump candidate.
+    pub index: usize,
+    pub previous_output: OutPoint,
+    pub reason: ExclusionReason,
+}
+
 pub struct SelectPlan {
     // Maximum available amount in all the transaction input UTXOs.
     // That is an amount that will be spent by the transaction.
@@ -40,6 +101,12 @@ pub struct SelectPlan {
     // Remaining change.
     // Zero if not applied.
     pub change: Amount,
+    /// Whether the transaction signals opt-in Replace-By-Fee.
+    pub signals_rbf: bool,
+    /// The UTXOs that have not been considered for the selection, and why.
+    pub excluded: Vec<ExcludedUtxo>,
+    /// The replay protection applied by the builder, if any.
+    pub replay_protection: Option<ReplayProtection>,
 }
 
 pub struct SelectResult<Transaction> {
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! The Creator and Constructor roles of BIP370, i.e. building a v2 PSBT incrementally.
+//! https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki#roles
+
+use crate::psbt::{
+    check_tx_version, compute_locktime, KeyValueMap, Psbt, PsbtInput, PsbtOutput, PsbtVersion,
+    TX_MODIFIABLE_INPUTS, TX_MODIFIABLE_OUTPUTS,
+};
+use tw_coin_entry::error::prelude::*;
+
+impl Psbt {
+    /// Creator: a v2 PSBT without inputs and outputs, that can be added by [`Psbt::add_input`]
+    /// and [`Psbt::add_output`].
+    pub fn new_v2(tx_version: i32, fallback_locktime: Option<u32>) -> SigningResult<Psbt> {
+        check_tx_version(tx_version, SigningErrorType::Error_invalid_params)?;
+
+        Ok(Psbt {
+            version: PsbtVersion::V2,
+            tx_version,
+            fallback_locktime,
+            tx_modifiable: Some(TX_MODIFIABLE_INPUTS | TX_MODIFIABLE_OUTPUTS),
+            inputs: Vec::default(),
+            outputs: Vec::default(),
+            unknown: KeyValueMap::new(),
+        })
+    }
+
+    /// Constructor: adds an input if the inputs are modifiable.
+    ///
+    /// Returns an error if the input is spent already, or its required lock time conflicts
+    /// with the lock times required by the other inputs.
+    pub fn add_input(&mut self, input: PsbtInput) -> SigningResult<()> {
+        self.check_modifiable(TX_MODIFIABLE_INPUTS, "inputs")?;
+        input.validate_locktimes(SigningErrorType::Error_invalid_params)?;
+
+        if self
+            .inputs
+            .iter()
+            .any(|existing| existing.previous_output == input.previous_output)
+        {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The PSBT spends the input already");
+        }
+
+        self.inputs.push(input);
+        if let Err(err) = compute_locktime(&self.inputs, self.fallback_locktime) {
+            self.inputs.pop();
+            return Err(err).context("The input lock time conflicts with the other inputs");
+        }
+        Ok(())
+    }
+
+    /// Constructor: adds an output if the outputs are modifiable.
+    pub fn add_output(&mut self, output: PsbtOutput) -> SigningResult<()> {
+        self.check_modifiable(TX_MODIFIABLE_OUTPUTS, "outputs")?;
+        self.outputs.push(output);
+        Ok(())
+    }
+
+    /// Constructor: marks the inputs and outputs as not modifiable once the construction is done.
+    pub fn finish_construction(&mut self) {
+        if let Some(tx_modifiable) = self.tx_modifiable.as_mut() {
+            *tx_modifiable &= !(TX_MODIFIABLE_INPUTS | TX_MODIFIABLE_OUTPUTS);
+        }
+    }
+
+    fn check_modifiable(&self, flag: u8, what: &str) -> SigningResult<()> {
+        if self.version != PsbtVersion::V2 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Only PSBT v2 {what} can be added"));
+        }
+        if self.tx_modifiable.unwrap_or_default() & flag == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("The PSBT {what} are not modifiable"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! The key-value maps a PSBT consists of.
+//! https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki#specification
+
+use crate::encode::reader::Reader;
+use crate::encode::stream::Stream;
+use crate::encode::{self, Decodable};
+use std::collections::BTreeMap;
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_memory::Data;
+
+/// Terminates a map, i.e. a zero length key.
+const SEPARATOR: u8 = 0x00;
+
+/// The key-value pairs of a map keyed by the whole key, i.e. the key type followed by the key data.
+/// The pairs are serialized in the order of their keys.
+pub type KeyValueMap = BTreeMap<Data, Data>;
+
+/// Reads the key-value pairs up to the separator.
+pub(crate) fn read_map(reader: &mut Reader) -> SigningResult<KeyValueMap> {
+    let mut map = KeyValueMap::new();
+    loop {
+        let key: Data = reader.read()?;
+        if key.is_empty() {
+            return Ok(map);
+        }
+
+        if map.contains_key(&key) {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context(format!("Duplicate PSBT key '{}'", hex::encode(&key, false)));
+        }
+        let value = reader.read()?;
+        map.insert(key, value);
+    }
+}
+
+/// Writes the key-value pairs followed by the separator.
+pub(crate) fn write_map(stream: &mut Stream, map: &KeyValueMap) {
+    for (key, value) in map {
+        stream.append(key).append(value);
+    }
+    stream.append(&SEPARATOR);
+}
+
+/// Returns an error if the map contains any of the given key types.
+pub(crate) fn forbid_fields(
+    map: &KeyValueMap,
+    key_types: &[u8],
+    map_name: &str,
+) -> SigningResult<()> {
+    // The key types are single bytes, as all of them are below the compact integer flags.
+    match map.keys().find(|key| key_types.contains(&key[0])) {
+        Some(key) => SigningError::err(SigningErrorType::Error_input_parse).context(format!(
+            "Key type '0x{:02x}' is not allowed in the {map_name} map",
+            key[0]
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Removes the field of the given key type and decodes its value, if present.
+/// The field must not have key data.
+pub(crate) fn take_field<T>(
+    map: &mut KeyValueMap,
+    key_type: u8,
+    name: &str,
+) -> SigningResult<Option<T>>
+where
+    T: Decodable,
+{
+    take_raw_field(map, key_type, name)?
+        .map(|value| encode::decode(&value).with_context(|| format!("Invalid {name} value")))
+        .transpose()
+}
+
+/// Removes the field of the given key type and decodes its value.
+/// Returns an error if the field is missing.
+pub(crate) fn require_field<T>(map: &mut KeyValueMap, key_type: u8, name: &str) -> SigningResult<T>
+where
+    T: Decodable,
+{
+    take_field(map, key_type, name)?
+        .or_tw_err(SigningErrorType::Error_input_parse)
+        .with_context(|| format!("Missing required {name} field"))
+}
+
+/// Removes the field of the given key type and returns its raw value, if present.
+/// The field must not have key data.
+pub(crate) fn take_raw_field(
+    map: &mut KeyValueMap,
+    key_type: u8,
+    name: &str,
+) -> SigningResult<Option<Data>> {
+    if map.keys().any(|key| key[0] == key_type && key.len() > 1) {
+        return SigningError::err(SigningErrorType::Error_input_parse)
+            .context(format!("{name} key must not have key data"));
+    }
+    Ok(map.remove([key_type].as_slice()))
+}
+
+/// Inserts the field of the given key type with the serialized value.
+pub(crate) fn insert_field<T>(map: &mut KeyValueMap, key_type: u8, value: &T)
+where
+    T: encode::Encodable,
+{
+    map.insert(vec![key_type], encode::encode(value));
+}
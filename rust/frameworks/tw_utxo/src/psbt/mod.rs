@@ -0,0 +1,550 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Partially Signed Bitcoin Transactions of version 0 (BIP174) and version 2 (BIP370).
+//!
+//! A v0 PSBT embeds the unsigned transaction in the global map, while a v2 PSBT stores
+//! the transaction fields in the global, input and output maps instead. [`Psbt`] holds the fields
+//! regardless of the version, which is detected automatically on decoding.
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki
+
+use crate::constants::LOCKTIME_THRESHOLD;
+use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
+use crate::encode::stream::Stream;
+use crate::encode::{self, Decodable, Encodable};
+use crate::psbt::map::{
+    forbid_fields, insert_field, read_map, require_field, take_field, take_raw_field, write_map,
+};
+use crate::script::{Script, Witness};
+use crate::transaction::standard_transaction::{Transaction, TransactionInput, TransactionOutput};
+use crate::transaction::transaction_parts::{Amount, OutPoint};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+
+pub mod constructor;
+pub mod map;
+
+pub use map::KeyValueMap;
+
+const PSBT_MAGIC: [u8; 5] = *b"psbt\xff";
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+/// The global fields that must be excluded from v0.
+const V0_EXCLUDED_GLOBAL_FIELDS: &[u8] = &[
+    PSBT_GLOBAL_TX_VERSION,
+    PSBT_GLOBAL_FALLBACK_LOCKTIME,
+    PSBT_GLOBAL_INPUT_COUNT,
+    PSBT_GLOBAL_OUTPUT_COUNT,
+    PSBT_GLOBAL_TX_MODIFIABLE,
+];
+/// The input fields that must be excluded from v0.
+const V0_EXCLUDED_INPUT_FIELDS: &[u8] = &[
+    PSBT_IN_PREVIOUS_TXID,
+    PSBT_IN_OUTPUT_INDEX,
+    PSBT_IN_SEQUENCE,
+    PSBT_IN_REQUIRED_TIME_LOCKTIME,
+    PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+];
+/// The output fields that must be excluded from v0.
+const V0_EXCLUDED_OUTPUT_FIELDS: &[u8] = &[PSBT_OUT_AMOUNT, PSBT_OUT_SCRIPT];
+
+/// The minimum transaction version of a v2 PSBT, `PSBT_GLOBAL_TX_VERSION`.
+const MIN_TX_VERSION: i32 = 2;
+
+/// Bit of [`Psbt::tx_modifiable`]: inputs can be added or removed.
+pub const TX_MODIFIABLE_INPUTS: u8 = 0x01;
+/// Bit of [`Psbt::tx_modifiable`]: outputs can be added or removed.
+pub const TX_MODIFIABLE_OUTPUTS: u8 = 0x02;
+/// Bit of [`Psbt::tx_modifiable`]: the transaction has a `SIGHASH_SINGLE` signature,
+/// so the inputs and outputs must be added in pairs.
+pub const TX_MODIFIABLE_SIGHASH_SINGLE: u8 = 0x04;
+
+/// The PSBT version, `PSBT_GLOBAL_VERSION`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PsbtVersion {
+    V0,
+    V2,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Psbt {
+    /// The version the PSBT is serialized with.
+    pub version: PsbtVersion,
+    /// The transaction version, `PSBT_GLOBAL_TX_VERSION`.
+    pub tx_version: i32,
+    /// The transaction `nLockTime` if no input requires a lock time, `PSBT_GLOBAL_FALLBACK_LOCKTIME`.
+    /// For v0, this is the `nLockTime` of the unsigned transaction.
+    pub fallback_locktime: Option<u32>,
+    /// Whether inputs and outputs can be added, `PSBT_GLOBAL_TX_MODIFIABLE`. Not used by v0.
+    /// See [`TX_MODIFIABLE_INPUTS`], [`TX_MODIFIABLE_OUTPUTS`] and [`TX_MODIFIABLE_SIGHASH_SINGLE`].
+    pub tx_modifiable: Option<u8>,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    /// The other global fields (e.g. xpubs), kept as is.
+    pub unknown: KeyValueMap,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PsbtInput {
+    /// `PSBT_IN_PREVIOUS_TXID` and `PSBT_IN_OUTPUT_INDEX`.
+    pub previous_output: OutPoint,
+    /// `PSBT_IN_SEQUENCE`, final (`0xffffffff`) if not set.
+    pub sequence: Option<u32>,
+    /// The minimum UNIX timestamp lock time the input requires, `PSBT_IN_REQUIRED_TIME_LOCKTIME`.
+    pub required_time_locktime: Option<u32>,
+    /// The minimum block height lock time the input requires, `PSBT_IN_REQUIRED_HEIGHT_LOCKTIME`.
+    pub required_height_locktime: Option<u32>,
+    /// The other input fields (e.g. UTXOs and partial signatures), kept as is.
+    pub unknown: KeyValueMap,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PsbtOutput {
+    /// `PSBT_OUT_AMOUNT`.
+    pub amount: Amount,
+    /// `PSBT_OUT_SCRIPT`.
+    pub script_pubkey: Script,
+    /// The other output fields (e.g. BIP32 derivation paths), kept as is.
+    pub unknown: KeyValueMap,
+}
+
+impl Psbt {
+    /// Decodes a PSBT of either version strictly, i.e. returns an error if there is any data after it.
+    pub fn from_bytes(bytes: &[u8]) -> SigningResult<Psbt> {
+        encode::decode(bytes)
+    }
+
+    pub fn encode_out(&self) -> Vec<u8> {
+        encode::encode(self)
+    }
+
+    /// Converts the PSBT to v2. The conversion is lossless.
+    ///
+    /// Returns an error if the transaction version is below 2, as v2 PSBTs don't support it.
+    pub fn to_v2(&self) -> SigningResult<Psbt> {
+        check_tx_version(self.tx_version, SigningErrorType::Error_invalid_params)?;
+        Ok(Psbt {
+            version: PsbtVersion::V2,
+            ..self.clone()
+        })
+    }
+
+    /// Converts the PSBT to v0.
+    ///
+    /// Returns an error if the conversion would be lossy, i.e. if the transaction is still modifiable,
+    /// or any input requires a lock time. A v0 PSBT must spend at least one input too,
+    /// otherwise the unsigned transaction can't be decoded unambiguously.
+    pub fn to_v0(&self) -> SigningResult<Psbt> {
+        if self.tx_modifiable.unwrap_or_default() != 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("PSBT_GLOBAL_TX_MODIFIABLE can't be converted to PSBT v0");
+        }
+        if self.inputs.iter().any(PsbtInput::requires_locktime) {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Required input lock times can't be converted to PSBT v0");
+        }
+        if self.inputs.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("PSBT v0 must spend at least one input");
+        }
+
+        Ok(Psbt {
+            version: PsbtVersion::V0,
+            tx_modifiable: None,
+            ..self.clone()
+        })
+    }
+
+    /// Determines the transaction `nLockTime` as BIP370 specifies:
+    /// * The fallback lock time (or zero) if no input requires a lock time.
+    /// * Otherwise, the maximum of the required block heights if every such input supports a height lock time,
+    ///   or the maximum of the required timestamps if every such input supports a time lock time.
+    ///
+    /// Returns an error if the inputs require both height and time based lock times.
+    pub fn compute_locktime(&self) -> SigningResult<u32> {
+        compute_locktime(&self.inputs, self.fallback_locktime)
+    }
+
+    /// Returns the unsigned transaction, i.e. with empty scriptSigs and witnesses.
+    pub fn unsigned_tx(&self) -> SigningResult<Transaction> {
+        Ok(self.unsigned_tx_with_locktime(self.compute_locktime()?))
+    }
+
+    fn decode_v0(reader: &mut Reader, mut global: KeyValueMap) -> SigningResult<Psbt> {
+        forbid_fields(&global, V0_EXCLUDED_GLOBAL_FIELDS, "PSBT v0 global")?;
+
+        let tx_bytes = take_raw_field(
+            &mut global,
+            PSBT_GLOBAL_UNSIGNED_TX,
+            "PSBT_GLOBAL_UNSIGNED_TX",
+        )?
+        .or_tw_err(SigningErrorType::Error_input_parse)
+        .context("Missing required PSBT_GLOBAL_UNSIGNED_TX field")?;
+        let tx =
+            Transaction::from_bytes(&tx_bytes).context("Invalid PSBT_GLOBAL_UNSIGNED_TX value")?;
+        if tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("PSBT_GLOBAL_UNSIGNED_TX must have empty scriptSigs and witnesses");
+        }
+
+        let mut inputs = Vec::with_capacity(tx.inputs.len());
+        for tx_input in tx.inputs {
+            let unknown = read_map(reader)?;
+            forbid_fields(&unknown, V0_EXCLUDED_INPUT_FIELDS, "PSBT v0 input")?;
+
+            inputs.push(PsbtInput {
+                previous_output: tx_input.previous_output,
+                sequence: Some(tx_input.sequence),
+                required_time_locktime: None,
+                required_height_locktime: None,
+                unknown,
+            });
+        }
+
+        let mut outputs = Vec::with_capacity(tx.outputs.len());
+        for tx_output in tx.outputs {
+            let unknown = read_map(reader)?;
+            forbid_fields(&unknown, V0_EXCLUDED_OUTPUT_FIELDS, "PSBT v0 output")?;
+
+            outputs.push(PsbtOutput {
+                amount: tx_output.value,
+                script_pubkey: tx_output.script_pubkey,
+                unknown,
+            });
+        }
+
+        Ok(Psbt {
+            version: PsbtVersion::V0,
+            tx_version: tx.version,
+            fallback_locktime: Some(tx.locktime),
+            tx_modifiable: None,
+            inputs,
+            outputs,
+            unknown: global,
+        })
+    }
+
+    fn decode_v2(reader: &mut Reader, mut global: KeyValueMap) -> SigningResult<Psbt> {
+        forbid_fields(&global, &[PSBT_GLOBAL_UNSIGNED_TX], "PSBT v2 global")?;
+
+        let tx_version = require_field(
+            &mut global,
+            PSBT_GLOBAL_TX_VERSION,
+            "PSBT_GLOBAL_TX_VERSION",
+        )?;
+        check_tx_version(tx_version, SigningErrorType::Error_input_parse)?;
+        let fallback_locktime = take_field(
+            &mut global,
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            "PSBT_GLOBAL_FALLBACK_LOCKTIME",
+        )?;
+        let input_count: CompactInteger = require_field(
+            &mut global,
+            PSBT_GLOBAL_INPUT_COUNT,
+            "PSBT_GLOBAL_INPUT_COUNT",
+        )?;
+        let output_count: CompactInteger = require_field(
+            &mut global,
+            PSBT_GLOBAL_OUTPUT_COUNT,
+            "PSBT_GLOBAL_OUTPUT_COUNT",
+        )?;
+        let tx_modifiable = take_field(
+            &mut global,
+            PSBT_GLOBAL_TX_MODIFIABLE,
+            "PSBT_GLOBAL_TX_MODIFIABLE",
+        )?;
+
+        // Each map takes one byte at least, so a malformed count fails at the end of data.
+        let inputs = (0..input_count.value())
+            .map(|_| PsbtInput::decode_v2(read_map(reader)?))
+            .collect::<SigningResult<Vec<_>>>()?;
+        let outputs = (0..output_count.value())
+            .map(|_| PsbtOutput::decode_v2(read_map(reader)?))
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        Ok(Psbt {
+            version: PsbtVersion::V2,
+            tx_version,
+            fallback_locktime,
+            tx_modifiable,
+            inputs,
+            outputs,
+            unknown: global,
+        })
+    }
+
+    fn global_map(&self) -> KeyValueMap {
+        let mut global = self.unknown.clone();
+        match self.version {
+            PsbtVersion::V0 => {
+                // A v0 PSBT doesn't contain required lock times, see `Psbt::to_v0`.
+                let tx = self.unsigned_tx_with_locktime(self.fallback_locktime.unwrap_or_default());
+                global.insert(vec![PSBT_GLOBAL_UNSIGNED_TX], tx.encode_out());
+            },
+            PsbtVersion::V2 => {
+                insert_field(&mut global, PSBT_GLOBAL_TX_VERSION, &self.tx_version);
+                if let Some(fallback_locktime) = self.fallback_locktime {
+                    insert_field(
+                        &mut global,
+                        PSBT_GLOBAL_FALLBACK_LOCKTIME,
+                        &fallback_locktime,
+                    );
+                }
+                insert_field(
+                    &mut global,
+                    PSBT_GLOBAL_INPUT_COUNT,
+                    &CompactInteger::from(self.inputs.len()),
+                );
+                insert_field(
+                    &mut global,
+                    PSBT_GLOBAL_OUTPUT_COUNT,
+                    &CompactInteger::from(self.outputs.len()),
+                );
+                if let Some(tx_modifiable) = self.tx_modifiable {
+                    insert_field(&mut global, PSBT_GLOBAL_TX_MODIFIABLE, &tx_modifiable);
+                }
+                insert_field(&mut global, PSBT_GLOBAL_VERSION, &2_u32);
+            },
+        }
+        global
+    }
+
+    fn unsigned_tx_with_locktime(&self, locktime: u32) -> Transaction {
+        Transaction {
+            version: self.tx_version,
+            inputs: self.inputs.iter().map(PsbtInput::to_tx_input).collect(),
+            outputs: self.outputs.iter().map(PsbtOutput::to_tx_output).collect(),
+            locktime,
+        }
+    }
+}
+
+impl Encodable for Psbt {
+    fn encode(&self, stream: &mut Stream) {
+        stream.append_raw_slice(&PSBT_MAGIC);
+        write_map(stream, &self.global_map());
+        for input in self.inputs.iter() {
+            write_map(stream, &input.map(self.version));
+        }
+        for output in self.outputs.iter() {
+            write_map(stream, &output.map(self.version));
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        self.encode_out().len()
+    }
+}
+
+impl Decodable for Psbt {
+    /// Decodes a PSBT, detecting the version by `PSBT_GLOBAL_VERSION`.
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        if reader.read_slice(PSBT_MAGIC.len())? != PSBT_MAGIC {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Invalid PSBT magic bytes");
+        }
+
+        let mut global = read_map(reader)?;
+        let version: Option<u32> =
+            take_field(&mut global, PSBT_GLOBAL_VERSION, "PSBT_GLOBAL_VERSION")?;
+        match version.unwrap_or_default() {
+            0 => Psbt::decode_v0(reader, global),
+            2 => Psbt::decode_v2(reader, global),
+            version => SigningError::err(SigningErrorType::Error_input_parse)
+                .context(format!("Unsupported PSBT version '{version}'")),
+        }
+    }
+}
+
+impl PsbtInput {
+    /// An input spending the given outpoint, with no other fields.
+    pub fn new(previous_output: OutPoint) -> PsbtInput {
+        PsbtInput {
+            previous_output,
+            sequence: None,
+            required_time_locktime: None,
+            required_height_locktime: None,
+            unknown: KeyValueMap::new(),
+        }
+    }
+
+    pub fn requires_locktime(&self) -> bool {
+        self.required_time_locktime.is_some() || self.required_height_locktime.is_some()
+    }
+
+    /// Checks that the required lock times are a timestamp and a (positive) block height respectively.
+    fn validate_locktimes(&self, error_type: SigningErrorType) -> SigningResult<()> {
+        if let Some(time) = self.required_time_locktime {
+            if time < LOCKTIME_THRESHOLD {
+                return SigningError::err(error_type).context(format!(
+                    "PSBT_IN_REQUIRED_TIME_LOCKTIME '{time}' is a block height"
+                ));
+            }
+        }
+        if let Some(height) = self.required_height_locktime {
+            if height == 0 || height >= LOCKTIME_THRESHOLD {
+                return SigningError::err(error_type).context(format!(
+                    "PSBT_IN_REQUIRED_HEIGHT_LOCKTIME '{height}' is not a block height"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_v2(mut unknown: KeyValueMap) -> SigningResult<PsbtInput> {
+        let hash: H256 =
+            require_field(&mut unknown, PSBT_IN_PREVIOUS_TXID, "PSBT_IN_PREVIOUS_TXID")?;
+        let index = require_field(&mut unknown, PSBT_IN_OUTPUT_INDEX, "PSBT_IN_OUTPUT_INDEX")?;
+
+        let input = PsbtInput {
+            previous_output: OutPoint { hash, index },
+            sequence: take_field(&mut unknown, PSBT_IN_SEQUENCE, "PSBT_IN_SEQUENCE")?,
+            required_time_locktime: take_field(
+                &mut unknown,
+                PSBT_IN_REQUIRED_TIME_LOCKTIME,
+                "PSBT_IN_REQUIRED_TIME_LOCKTIME",
+            )?,
+            required_height_locktime: take_field(
+                &mut unknown,
+                PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+                "PSBT_IN_REQUIRED_HEIGHT_LOCKTIME",
+            )?,
+            unknown,
+        };
+        input.validate_locktimes(SigningErrorType::Error_input_parse)?;
+        Ok(input)
+    }
+
+    fn map(&self, version: PsbtVersion) -> KeyValueMap {
+        let mut map = self.unknown.clone();
+        if version == PsbtVersion::V2 {
+            insert_field(&mut map, PSBT_IN_PREVIOUS_TXID, &self.previous_output.hash);
+            insert_field(&mut map, PSBT_IN_OUTPUT_INDEX, &self.previous_output.index);
+            if let Some(sequence) = self.sequence {
+                insert_field(&mut map, PSBT_IN_SEQUENCE, &sequence);
+            }
+            if let Some(time) = self.required_time_locktime {
+                insert_field(&mut map, PSBT_IN_REQUIRED_TIME_LOCKTIME, &time);
+            }
+            if let Some(height) = self.required_height_locktime {
+                insert_field(&mut map, PSBT_IN_REQUIRED_HEIGHT_LOCKTIME, &height);
+            }
+        }
+        map
+    }
+
+    fn to_tx_input(&self) -> TransactionInput {
+        TransactionInput {
+            previous_output: self.previous_output,
+            script_sig: Script::default(),
+            sequence: self.sequence.unwrap_or(u32::MAX),
+            witness: Witness::default(),
+        }
+    }
+}
+
+impl PsbtOutput {
+    /// An output with no other fields.
+    pub fn new(amount: Amount, script_pubkey: Script) -> PsbtOutput {
+        PsbtOutput {
+            amount,
+            script_pubkey,
+            unknown: KeyValueMap::new(),
+        }
+    }
+
+    fn decode_v2(mut unknown: KeyValueMap) -> SigningResult<PsbtOutput> {
+        let amount: i64 = require_field(&mut unknown, PSBT_OUT_AMOUNT, "PSBT_OUT_AMOUNT")?;
+        let amount = Amount::try_from(amount)
+            .tw_err(|_| SigningErrorType::Error_input_parse)
+            .with_context(|| format!("PSBT_OUT_AMOUNT '{amount}' is negative"))?;
+        let script_pubkey = take_raw_field(&mut unknown, PSBT_OUT_SCRIPT, "PSBT_OUT_SCRIPT")?
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Missing required PSBT_OUT_SCRIPT field")?;
+
+        Ok(PsbtOutput {
+            amount,
+            script_pubkey: Script::from(script_pubkey),
+            unknown,
+        })
+    }
+
+    fn map(&self, version: PsbtVersion) -> KeyValueMap {
+        let mut map = self.unknown.clone();
+        if version == PsbtVersion::V2 {
+            insert_field(&mut map, PSBT_OUT_AMOUNT, &self.amount);
+            map.insert(
+                vec![PSBT_OUT_SCRIPT],
+                self.script_pubkey.as_slice().to_vec(),
+            );
+        }
+        map
+    }
+
+    fn to_tx_output(&self) -> TransactionOutput {
+        TransactionOutput {
+            value: self.amount,
+            script_pubkey: self.script_pubkey.clone(),
+        }
+    }
+}
+
+fn check_tx_version(tx_version: i32, error_type: SigningErrorType) -> SigningResult<()> {
+    if tx_version < MIN_TX_VERSION {
+        return SigningError::err(error_type).context(format!(
+            "PSBT v2 transaction version must be at least {MIN_TX_VERSION}, got {tx_version}"
+        ));
+    }
+    Ok(())
+}
+
+fn compute_locktime(inputs: &[PsbtInput], fallback_locktime: Option<u32>) -> SigningResult<u32> {
+    let locking_inputs: Vec<_> = inputs
+        .iter()
+        .filter(|input| input.requires_locktime())
+        .collect();
+    if locking_inputs.is_empty() {
+        return Ok(fallback_locktime.unwrap_or_default());
+    }
+
+    // A height lock time is preferred if every input supports both.
+    let max_height = locking_inputs
+        .iter()
+        .map(|input| input.required_height_locktime)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|heights| heights.into_iter().max());
+    let max_time = locking_inputs
+        .iter()
+        .map(|input| input.required_time_locktime)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|times| times.into_iter().max());
+
+    max_height
+        .or(max_time)
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("The inputs require both height and time based lock times")
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Replay protection of the transactions signed after a chain split.
+//! Without it, a transaction signed for one chain is valid on the other chain as well.
+
+use crate::script::standard_script::conditions;
+use crate::transaction::standard_transaction::TransactionOutput;
+use crate::transaction::UtxoToSign;
+use std::fmt;
+use tw_coin_entry::error::prelude::*;
+
+/// Makes the transaction valid on one side of a chain split only.
+#[derive(Clone, Debug)]
+pub enum ReplayProtection {
+    /// Requires the `SIGHASH_FORK_ID` flag on every input,
+    /// so the signatures are only valid on the chains with the fork-id sighash, e.g. Bitcoin Cash.
+    ForkIdSighash,
+    /// Appends the chain-specific output that only relays on one chain,
+    /// e.g. an `OP_RETURN` output built by [`OutputBuilder::op_return`] or a dust output.
+    ///
+    /// [`OutputBuilder::op_return`]: crate::transaction::standard_transaction::builder::OutputBuilder::op_return
+    ProtectionOutput(TransactionOutput),
+}
+
+impl ReplayProtection {
+    /// Checks if the protection can be applied to the UTXOs signed for the chain of the given fork id,
+    /// see [`crate::chain_params::ChainParams::fork_id`].
+    pub fn check(&self, fork_id: Option<u32>, utxo_args: &[UtxoToSign]) -> SigningResult<()> {
+        match self {
+            ReplayProtection::ForkIdSighash => {
+                let Some(fork_id) = fork_id else {
+                    return SigningError::err(SigningErrorType::Error_not_supported).context(
+                        "The chain does not support the fork-id sighash replay protection",
+                    );
+                };
+
+                for (input_index, utxo) in utxo_args.iter().enumerate() {
+                    if utxo.sighash_ty.fork_id_value() != Some(fork_id) {
+                        return SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                            .context(format!(
+                                "Input #{input_index} must be signed with SIGHASH_FORK_ID of fork id {fork_id} for replay protection"
+                            ));
+                    }
+                }
+                Ok(())
+            },
+            ReplayProtection::ProtectionOutput(output) => {
+                if output.script_pubkey.is_empty() {
+                    return SigningError::err(SigningErrorType::Error_script_output)
+                        .context("Replay protection output must have a scriptPubkey");
+                }
+                if output.value == 0 && !conditions::is_op_return(&output.script_pubkey) {
+                    return SigningError::err(SigningErrorType::Error_dust_amount_requested)
+                        .context("Replay protection output must be either OP_RETURN or non-zero");
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Display for ReplayProtection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayProtection::ForkIdSighash => f.write_str("fork-id sighash"),
+            ReplayProtection::ProtectionOutput(_) => f.write_str("protection output"),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Script disassembly in the same format as Bitcoin Core `ScriptToAsmStr`.
+
+use super::{next_op, Script};
+use crate::constants::MAX_SCRIPT_SIZE;
+use crate::script::standard_script::opcodes::*;
+use crate::signature::is_valid_signature_encoding;
+use tw_encoding::hex;
+
+/// Bitcoin Core names of the opcodes in the `OP_NOP..=OP_CHECKSIGADD` range.
+#[rustfmt::skip]
+const OPCODE_NAMES: [&str; (OP_CHECKSIGADD - OP_NOP + 1) as usize] = [
+    "OP_NOP", "OP_VER", "OP_IF", "OP_NOTIF", "OP_VERIF", "OP_VERNOTIF", "OP_ELSE", "OP_ENDIF",
+    "OP_VERIFY", "OP_RETURN", "OP_TOALTSTACK", "OP_FROMALTSTACK", "OP_2DROP", "OP_2DUP",
+    "OP_3DUP", "OP_2OVER", "OP_2ROT", "OP_2SWAP", "OP_IFDUP", "OP_DEPTH", "OP_DROP", "OP_DUP",
+    "OP_NIP", "OP_OVER", "OP_PICK", "OP_ROLL", "OP_ROT", "OP_SWAP", "OP_TUCK", "OP_CAT",
+    "OP_SUBSTR", "OP_LEFT", "OP_RIGHT", "OP_SIZE", "OP_INVERT", "OP_AND", "OP_OR", "OP_XOR",
+    "OP_EQUAL", "OP_EQUALVERIFY", "OP_RESERVED1", "OP_RESERVED2", "OP_1ADD", "OP_1SUB",
+    "OP_2MUL", "OP_2DIV", "OP_NEGATE", "OP_ABS", "OP_NOT", "OP_0NOTEQUAL", "OP_ADD", "OP_SUB",
+    "OP_MUL", "OP_DIV", "OP_MOD", "OP_LSHIFT", "OP_RSHIFT", "OP_BOOLAND", "OP_BOOLOR",
+    "OP_NUMEQUAL", "OP_NUMEQUALVERIFY", "OP_NUMNOTEQUAL", "OP_LESSTHAN", "OP_GREATERTHAN",
+    "OP_LESSTHANOREQUAL", "OP_GREATERTHANOREQUAL", "OP_MIN", "OP_MAX", "OP_WITHIN",
+    "OP_RIPEMD160", "OP_SHA1", "OP_SHA256", "OP_HASH160", "OP_HASH256", "OP_CODESEPARATOR",
+    "OP_CHECKSIG", "OP_CHECKSIGVERIFY", "OP_CHECKMULTISIG", "OP_CHECKMULTISIGVERIFY", "OP_NOP1",
+    "OP_CHECKLOCKTIMEVERIFY", "OP_CHECKSEQUENCEVERIFY", "OP_NOP4", "OP_NOP5", "OP_NOP6",
+    "OP_NOP7", "OP_NOP8", "OP_NOP9", "OP_NOP10", "OP_CHECKSIGADD",
+];
+
+impl Script {
+    /// Disassembles the script, e.g. `OP_DUP OP_HASH160 <hex> OP_EQUALVERIFY OP_CHECKSIG`.
+    ///
+    /// Pushes of up to 4 bytes are displayed as numbers, longer pushes as hex.
+    /// If `attempt_sighash_decode` is set, the sighash type of pushed signatures is decoded,
+    /// e.g. `<DER hex>[ALL]`. It's meant to be used for scriptSig's only.
+    pub fn to_asm(&self, attempt_sighash_decode: bool) -> String {
+        let bytes = self.as_slice();
+        // OP_RETURN data may look like a signature.
+        let attempt_sighash_decode = attempt_sighash_decode && !self.is_unspendable();
+
+        let mut items = Vec::new();
+        let mut pc = 0;
+        while pc < bytes.len() {
+            let (opcode, data) = match next_op(bytes, &mut pc) {
+                Some(op) => op,
+                None => {
+                    items.push("[error]".to_string());
+                    break;
+                },
+            };
+
+            let item = if opcode > OP_PUSHDATA4 {
+                opcode_name(opcode)
+            } else if data.len() <= 4 {
+                decode_script_num(data).to_string()
+            } else if attempt_sighash_decode {
+                push_with_sighash_decoded(data)
+            } else {
+                hex::encode(data, false)
+            };
+            items.push(item);
+        }
+
+        items.join(" ")
+    }
+
+    /// Whether the script is provably unspendable.
+    fn is_unspendable(&self) -> bool {
+        self.as_slice().first() == Some(&OP_RETURN) || self.len() > MAX_SCRIPT_SIZE
+    }
+}
+
+fn opcode_name(opcode: u8) -> String {
+    match opcode {
+        OP_PUSHNUM_NEG1 => "-1".to_string(),
+        OP_RESERVED => "OP_RESERVED".to_string(),
+        OP_PUSHNUM_1..=OP_PUSHNUM_16 => (opcode - OP_PUSHNUM_1 + 1).to_string(),
+        OP_NOP..=OP_CHECKSIGADD => OPCODE_NAMES[(opcode - OP_NOP) as usize].to_string(),
+        OP_INVALIDOPCODE => "OP_INVALIDOPCODE".to_string(),
+        _ => "OP_UNKNOWN".to_string(),
+    }
+}
+
+/// Parses an opcode by its Bitcoin Core name, e.g. `OP_CHECKSIG`.
+/// The small number opcodes are named `OP_0`, `OP_1NEGATE` and `OP_1..=OP_16`.
+pub(crate) fn opcode_from_name(name: &str) -> Option<u8> {
+    match name {
+        "OP_0" | "OP_FALSE" => return Some(OP_PUSHBYTES_0),
+        "OP_1NEGATE" => return Some(OP_PUSHNUM_NEG1),
+        "OP_TRUE" => return Some(OP_PUSHNUM_1),
+        "OP_RESERVED" => return Some(OP_RESERVED),
+        _ => (),
+    }
+
+    if let Some(n) = name.strip_prefix("OP_").and_then(|n| n.parse::<u8>().ok()) {
+        return match n {
+            1..=16 => Some(OP_PUSHNUM_1 + n - 1),
+            _ => None,
+        };
+    }
+    OPCODE_NAMES
+        .iter()
+        .position(|opcode_name| *opcode_name == name)
+        .map(|i| OP_NOP + i as u8)
+}
+
+/// Decodes a little-endian sign-magnitude number of up to 4 bytes.
+fn decode_script_num(data: &[u8]) -> i64 {
+    let Some(last) = data.last() else {
+        return 0;
+    };
+
+    let mut result = data
+        .iter()
+        .enumerate()
+        .fold(0_i64, |acc, (i, byte)| acc | ((*byte as i64) << (8 * i)));
+
+    // The most significant bit of the last byte is the sign bit.
+    if last & 0x80 != 0 {
+        result &= !(0x80_i64 << (8 * (data.len() - 1)));
+        result = -result;
+    }
+    result
+}
+
+fn push_with_sighash_decoded(data: &[u8]) -> String {
+    let sighash_name = data
+        .split_last()
+        .filter(|_| is_valid_signature_encoding(data))
+        .and_then(|(sighash_ty, sig)| Some((sighash_type_name(*sighash_ty)?, sig)));
+
+    match sighash_name {
+        Some((name, sig)) => format!("{}[{name}]", hex::encode(sig, false)),
+        None => hex::encode(data, false),
+    }
+}
+
+fn sighash_type_name(sighash_ty: u8) -> Option<&'static str> {
+    match sighash_ty {
+        0x01 => Some("ALL"),
+        0x02 => Some("NONE"),
+        0x03 => Some("SINGLE"),
+        0x81 => Some("ALL|ANYONECANPAY"),
+        0x82 => Some("NONE|ANYONECANPAY"),
+        0x83 => Some("SINGLE|ANYONECANPAY"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    #[track_caller]
+    fn test_asm(script: &str, attempt_sighash_decode: bool, expected: &str) {
+        let script = Script::from(script.decode_hex().unwrap());
+        assert_eq!(script.to_asm(attempt_sighash_decode), expected);
+    }
+
+    #[test]
+    fn test_script_to_asm() {
+        test_asm(
+            "76a914aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa88ac",
+            false,
+            "OP_DUP OP_HASH160 aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa OP_EQUALVERIFY OP_CHECKSIG",
+        );
+        test_asm(
+            "0014bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            false,
+            "0 bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+        );
+        // Numbers and small pushes.
+        test_asm("4f5160010081028000", false, "-1 1 16 0 -1 128 0");
+        test_asm(
+            "b1b2baff",
+            false,
+            "OP_CHECKLOCKTIMEVERIFY OP_CHECKSEQUENCEVERIFY OP_CHECKSIGADD OP_INVALIDOPCODE",
+        );
+        // Truncated push.
+        test_asm("ac4c", false, "OP_CHECKSIG [error]");
+    }
+
+    #[test]
+    fn test_script_to_asm_sighash_decode() {
+        let sig = "304402201857bc6e6e48b46046a4bd204136fc77e24c240943fb5a1f0e86387aae59b34902200a7f31478784e51c49f46ef072745a4f263d7efdbc9c6784aa2571ff4f6f2a40";
+        let script_sig = format!(
+            "47{sig}8121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f"
+        );
+
+        test_asm(
+            &script_sig,
+            true,
+            &format!("{sig}[ALL|ANYONECANPAY] 025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f"),
+        );
+        test_asm(
+            &script_sig,
+            false,
+            &format!("{sig}81 025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f"),
+        );
+        // OP_RETURN data is never decoded.
+        test_asm(&format!("6a47{sig}01"), true, &format!("OP_RETURN {sig}01"));
+    }
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Minimal push validation as Bitcoin Core `CheckMinimalPush` does (`SCRIPT_VERIFY_MINIMALDATA`).
+
+use super::{next_op, Script};
+use crate::script::standard_script::opcodes::*;
+use std::fmt;
+
+/// A script instruction that would make a transaction non-standard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MinimalPushError {
+    /// The push operation at the given instruction index is truncated.
+    Malformed { index: usize },
+    /// The push operation at the given instruction index should have been encoded with the `minimal` opcode.
+    NonMinimal {
+        index: usize,
+        opcode: u8,
+        minimal: u8,
+    },
+}
+
+impl fmt::Display for MinimalPushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinimalPushError::Malformed { index } => {
+                write!(f, "Instruction #{index} is a malformed push operation")
+            },
+            MinimalPushError::NonMinimal {
+                index,
+                opcode,
+                minimal,
+            } => write!(
+                f,
+                "Instruction #{index} is pushed with {}, but the minimal encoding is {}",
+                push_opcode_name(*opcode),
+                push_opcode_name(*minimal)
+            ),
+        }
+    }
+}
+
+impl Script {
+    /// Returns the script operations (opcodes and the pushed data),
+    /// or an error at the first malformed or non-minimal push operation.
+    ///
+    /// For example, `OP_PUSHDATA1` used for 20 bytes, or `OP_1` encoded as a push of `0x01`.
+    pub fn instructions_minimal(&self) -> Result<Vec<(u8, &[u8])>, MinimalPushError> {
+        let bytes = self.as_slice();
+
+        let mut ops = Vec::new();
+        let mut pc = 0;
+        while pc < bytes.len() {
+            let index = ops.len();
+            let (opcode, data) =
+                next_op(bytes, &mut pc).ok_or(MinimalPushError::Malformed { index })?;
+
+            match minimal_push_opcode(opcode, data) {
+                Some(minimal) if minimal != opcode => {
+                    return Err(MinimalPushError::NonMinimal {
+                        index,
+                        opcode,
+                        minimal,
+                    });
+                },
+                _ => ops.push((opcode, data)),
+            }
+        }
+        Ok(ops)
+    }
+}
+
+/// Returns the opcode that pushes the `data` minimally if `opcode` is a push operation.
+fn minimal_push_opcode(opcode: u8, data: &[u8]) -> Option<u8> {
+    if opcode > OP_PUSHDATA4 {
+        return None;
+    }
+
+    let minimal = match data {
+        [] => OP_PUSHBYTES_0,
+        [n @ 1..=16] => OP_PUSHNUM_1 + n - 1,
+        [0x81] => OP_PUSHNUM_NEG1,
+        _ => match data.len() {
+            n if n <= OP_PUSHBYTES_75 as usize => n as u8,
+            n if n <= 0xff => OP_PUSHDATA1,
+            n if n <= 0xffff => OP_PUSHDATA2,
+            _ => OP_PUSHDATA4,
+        },
+    };
+    Some(minimal)
+}
+
+fn push_opcode_name(opcode: u8) -> String {
+    match opcode {
+        OP_PUSHBYTES_0 => "OP_0".to_string(),
+        OP_PUSHDATA1 => "OP_PUSHDATA1".to_string(),
+        OP_PUSHDATA2 => "OP_PUSHDATA2".to_string(),
+        OP_PUSHDATA4 => "OP_PUSHDATA4".to_string(),
+        OP_PUSHNUM_NEG1 => "OP_1NEGATE".to_string(),
+        OP_PUSHNUM_1..=OP_PUSHNUM_16 => format!("OP_{}", opcode - OP_PUSHNUM_1 + 1),
+        n if n <= OP_PUSHBYTES_75 => format!("OP_PUSHBYTES_{n}"),
+        _ => format!("0x{opcode:02x}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    #[track_caller]
+    fn test_non_minimal(script: &[u8], index: usize, opcode: u8, minimal: u8) {
+        let script = Script::from(script.to_vec());
+        assert_eq!(
+            script.instructions_minimal(),
+            Err(MinimalPushError::NonMinimal {
+                index,
+                opcode,
+                minimal
+            })
+        );
+    }
+
+    #[test]
+    fn test_instructions_minimal() {
+        // P2PKH scriptSig-like script.
+        let script = Script::from(
+            "4830450221008888888888888888888888888888888888888888888888888888888888888888022077777777777777777777777777777777777777777777777777777777777777770121021111111111111111111111111111111111111111111111111111111111111111"
+                .decode_hex()
+                .unwrap(),
+        );
+        assert_eq!(script.instructions_minimal().unwrap().len(), 2);
+
+        // `OP_0 OP_1NEGATE OP_16 <00> <82> OP_1 OP_CHECKSIG`.
+        let script = Script::from("004f600100018251ac".decode_hex().unwrap());
+        let ops = script.instructions_minimal().unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                (OP_PUSHBYTES_0, [].as_slice()),
+                (OP_PUSHNUM_NEG1, [].as_slice()),
+                (OP_PUSHNUM_16, [].as_slice()),
+                (0x01, [0x00].as_slice()),
+                (0x01, [0x82].as_slice()),
+                (OP_PUSHNUM_1, [].as_slice()),
+                (OP_CHECKSIG, [].as_slice()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_instructions_minimal_empty_push() {
+        test_non_minimal(&[OP_PUSHDATA1, 0x00], 0, OP_PUSHDATA1, OP_PUSHBYTES_0);
+        test_non_minimal(&[OP_PUSHDATA2, 0x00, 0x00], 0, OP_PUSHDATA2, OP_PUSHBYTES_0);
+    }
+
+    #[test]
+    fn test_instructions_minimal_small_integer() {
+        test_non_minimal(&[0x01, 0x01], 0, 0x01, OP_PUSHNUM_1);
+        test_non_minimal(&[0x01, 0x10], 0, 0x01, OP_PUSHNUM_16);
+        test_non_minimal(&[OP_PUSHDATA1, 0x01, 0x05], 0, OP_PUSHDATA1, OP_PUSHNUM_5);
+        test_non_minimal(&[0x01, 0x81], 0, 0x01, OP_PUSHNUM_NEG1);
+    }
+
+    #[test]
+    fn test_instructions_minimal_pushdata() {
+        let mut script = vec![OP_PUSHDATA1, 20];
+        script.extend_from_slice(&[0xaa; 20]);
+        test_non_minimal(&script, 0, OP_PUSHDATA1, 20);
+
+        let mut script = vec![OP_PUSHDATA2, 76, 0];
+        script.extend_from_slice(&[0xaa; 76]);
+        test_non_minimal(&script, 0, OP_PUSHDATA2, OP_PUSHDATA1);
+
+        let mut script = vec![OP_PUSHDATA4, 0, 1, 0, 0];
+        script.extend_from_slice(&[0xaa; 256]);
+        test_non_minimal(&script, 0, OP_PUSHDATA4, OP_PUSHDATA2);
+    }
+
+    #[test]
+    fn test_instructions_minimal_error_index() {
+        // `OP_0 OP_DUP <01>`.
+        test_non_minimal(&[OP_PUSHBYTES_0, OP_DUP, 0x01, 0x01], 2, 0x01, OP_PUSHNUM_1);
+
+        // `OP_DUP <truncated>`.
+        let script = Script::from(vec![OP_DUP, 0x02, 0xaa]);
+        assert_eq!(
+            script.instructions_minimal(),
+            Err(MinimalPushError::Malformed { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_minimal_push_error_display() {
+        let mut script = vec![OP_PUSHBYTES_0, OP_PUSHDATA1, 20];
+        script.extend_from_slice(&[0xaa; 20]);
+        let err = Script::from(script).instructions_minimal().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Instruction #1 is pushed with OP_PUSHDATA1, but the minimal encoding is OP_PUSHBYTES_20"
+        );
+
+        let err = Script::from(vec![0x01, 0x01])
+            .instructions_minimal()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Instruction #0 is pushed with OP_PUSHBYTES_1, but the minimal encoding is OP_1"
+        );
+    }
+}
@@ -3,13 +3,24 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
-use crate::encode::Encodable;
+use crate::encode::{Decodable, Encodable};
 use standard_script::opcodes::*;
+use tw_coin_entry::error::prelude::SigningResult;
 use tw_memory::Data;
 
+mod asm;
+mod minimal_push;
+pub mod script_code;
 pub mod standard_script;
 
+pub use minimal_push::MinimalPushError;
+
+/// Max number of public keys in a legacy `OP_CHECKMULTISIG` operation.
+/// It's assumed as the number of sigops of `OP_CHECKMULTISIG` if it can't be counted accurately.
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Script {
     bytes: Data,
@@ -28,6 +39,12 @@ impl Encodable for Script {
     }
 }
 
+impl Decodable for Script {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        reader.read::<Data>().map(Script::from)
+    }
+}
+
 impl Script {
     pub fn new() -> Self {
         Self::default()
@@ -89,6 +106,17 @@ impl Script {
         self.bytes.extend_from_slice(data);
     }
 
+    /// Pushes the given integer using the shortest possible encoding:
+    /// `OP_0`, `OP_1NEGATE`, `OP_1..=OP_16` or a minimally encoded script number.
+    pub fn push_int(&mut self, n: i64) {
+        match n {
+            0 => self.push(OP_PUSHBYTES_0),
+            -1 => self.push(OP_PUSHNUM_NEG1),
+            1..=16 => self.push(OP_PUSHNUM_1 + (n as u8) - 1),
+            _ => self.push_slice(&encode_script_num(n)),
+        }
+    }
+
     /// Appends the given data to the end of the script as-is.
     pub fn append(&mut self, data: &[u8]) {
         self.bytes.extend_from_slice(data);
@@ -101,6 +129,100 @@ impl Script {
     pub fn to_vec(&self) -> Data {
         self.bytes.clone()
     }
+
+    /// Counts signature operations as Bitcoin Core's `CScript::GetSigOpCount` does.
+    ///
+    /// If `accurate` is false, every `OP_CHECKMULTISIG` is counted as [`MAX_PUBKEYS_PER_MULTISIG`] sigops (legacy rule).
+    /// Otherwise, the number of public keys is taken from the preceding `OP_1..=OP_16` opcode if any.
+    /// The counting stops at the first malformed push operation.
+    pub fn sigop_count(&self, accurate: bool) -> usize {
+        let mut count = 0;
+        let mut last_opcode = None;
+        for (opcode, _) in self.ops() {
+            match opcode {
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => count += 1,
+                OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => match last_opcode {
+                    Some(n @ OP_PUSHNUM_1..=OP_PUSHNUM_16) if accurate => {
+                        count += (n - OP_PUSHNUM_1 + 1) as usize
+                    },
+                    _ => count += MAX_PUBKEYS_PER_MULTISIG,
+                },
+                _ => (),
+            }
+            last_opcode = Some(opcode);
+        }
+        count
+    }
+
+    /// Returns the data pushed by the last operation if the script contains push operations only
+    /// (including `OP_1NEGATE` and `OP_1..=OP_16`). The data is empty if the last operation is not a data push.
+    ///
+    /// For example, it's a redeem script if the script is a P2SH scriptSig.
+    pub fn last_push_if_push_only(&self) -> Option<&[u8]> {
+        let ops = self.push_only_ops()?;
+        Some(ops.last().map_or(&[][..], |(_, data)| *data))
+    }
+
+    /// Returns the operations if the script contains push operations only
+    /// (including `OP_1NEGATE` and `OP_1..=OP_16`), or `None` if there is a malformed push operation.
+    pub(crate) fn push_only_ops(&self) -> Option<Vec<(u8, &[u8])>> {
+        let mut ops = Vec::new();
+        let mut pc = 0;
+        while pc < self.bytes.len() {
+            let (opcode, data) = next_op(&self.bytes, &mut pc)?;
+            if opcode > OP_PUSHNUM_16 {
+                return None;
+            }
+            ops.push((opcode, data));
+        }
+        Some(ops)
+    }
+
+    /// Whether every push operation of the script is well-formed,
+    /// i.e. the pushed data doesn't exceed the script.
+    pub(crate) fn is_well_formed(&self) -> bool {
+        let mut pc = 0;
+        while pc < self.bytes.len() {
+            if next_op(&self.bytes, &mut pc).is_none() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Iterates over the script operations yielding opcodes and the pushed data.
+    /// Stops at the first malformed push operation.
+    fn ops(&self) -> impl Iterator<Item = (u8, &[u8])> + '_ {
+        let mut pc = 0;
+        std::iter::from_fn(move || next_op(&self.bytes, &mut pc))
+    }
+}
+
+/// Reads an operation starting at `pc`, and moves `pc` to the next operation.
+/// Returns `None` if there are no operations left, or the push operation is malformed.
+fn next_op<'a>(bytes: &'a [u8], pc: &mut usize) -> Option<(u8, &'a [u8])> {
+    let opcode = *bytes.get(*pc)?;
+    let mut cursor = *pc + 1;
+
+    let read_len = |cursor: &mut usize, len_size: usize| -> Option<usize> {
+        let len_bytes = bytes.get(*cursor..*cursor + len_size)?;
+        *cursor += len_size;
+        let mut buf = [0_u8; 4];
+        buf[..len_size].copy_from_slice(len_bytes);
+        Some(u32::from_le_bytes(buf) as usize)
+    };
+
+    let data_len = match opcode {
+        n if n <= OP_PUSHBYTES_75 => n as usize,
+        OP_PUSHDATA1 => read_len(&mut cursor, 1)?,
+        OP_PUSHDATA2 => read_len(&mut cursor, 2)?,
+        OP_PUSHDATA4 => read_len(&mut cursor, 4)?,
+        _ => 0,
+    };
+
+    let data = bytes.get(cursor..cursor.checked_add(data_len)?)?;
+    *pc = cursor + data_len;
+    Some((opcode, data))
 }
 
 impl From<Data> for Script {
@@ -109,6 +231,26 @@ impl From<Data> for Script {
     }
 }
 
+/// Encodes the given integer as a minimal little-endian script number,
+/// where the most significant bit of the last byte is a sign bit.
+fn encode_script_num(n: i64) -> Data {
+    let mut abs = n.unsigned_abs();
+    let mut result = Data::with_capacity(9);
+    while abs > 0 {
+        result.push((abs & 0xff) as u8);
+        abs >>= 8;
+    }
+
+    let sign_bit = if n < 0 { 0x80 } else { 0 };
+    match result.last_mut() {
+        // The sign bit is occupied, push an extra byte.
+        Some(last) if *last & 0x80 != 0 => result.push(sign_bit),
+        Some(last) => *last |= sign_bit,
+        None => (),
+    }
+    result
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Witness {
     items: Vec<Script>,
@@ -125,6 +267,14 @@ impl Encodable for Witness {
     }
 }
 
+impl Decodable for Witness {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        // Every item has at least a length prefix.
+        let items = reader.read_list(1)?;
+        Ok(Witness { items })
+    }
+}
+
 impl Witness {
     pub fn new() -> Self {
         Self::default()
@@ -202,4 +352,68 @@ mod tests {
 
         assert_eq!(script.bytes.to_hex(), bitcoin_script.to_hex());
     }
+
+    #[test]
+    fn test_script_sigop_count() {
+        #[track_caller]
+        fn test_impl(script: &str, legacy: usize, accurate: usize) {
+            let script = Script::from(script.decode_hex().unwrap());
+            assert_eq!(script.sigop_count(false), legacy);
+            assert_eq!(script.sigop_count(true), accurate);
+        }
+
+        // P2PKH.
+        test_impl("76a914aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa88ac", 1, 1);
+        // 2-of-3 multisig.
+        test_impl(
+            "52\
+            21021111111111111111111111111111111111111111111111111111111111111111\
+            21022222222222222222222222222222222222222222222222222222222222222222\
+            21023333333333333333333333333333333333333333333333333333333333333333\
+            53ae",
+            20,
+            3,
+        );
+        // `OP_16 OP_CHECKMULTISIGVERIFY OP_CHECKSIGVERIFY`.
+        test_impl("60afad", 21, 17);
+        // The number of public keys isn't known.
+        test_impl("ae", 20, 20);
+        test_impl("0103ae", 20, 20);
+        // Pushed data is not counted.
+        test_impl("02acac", 0, 0);
+        // The counting stops at the first malformed push.
+        test_impl("ac4c", 1, 1);
+        test_impl("ac05acacac", 1, 1);
+    }
+
+    #[test]
+    fn test_script_last_push_if_push_only() {
+        let script = Script::from("0002aabb4c02ccdd".decode_hex().unwrap());
+        assert_eq!(
+            script.last_push_if_push_only(),
+            Some([0xcc, 0xdd].as_slice())
+        );
+
+        let script = Script::from("02aabb51".decode_hex().unwrap());
+        assert_eq!(script.last_push_if_push_only(), Some([].as_slice()));
+
+        // Contains `OP_CHECKSIG`.
+        let script = Script::from("02aabbac".decode_hex().unwrap());
+        assert_eq!(script.last_push_if_push_only(), None);
+
+        // Malformed push.
+        let script = Script::from("03aabb".decode_hex().unwrap());
+        assert_eq!(script.last_push_if_push_only(), None);
+    }
+
+    #[test]
+    fn test_script_push_int() {
+        for n in [0, -1, 1, 16, 17, -17, 128, -128, 500_000, i64::MAX] {
+            let mut script = Script::new();
+            script.push_int(n);
+
+            let bitcoin_script = bitcoin::script::Script::builder().push_int(n).into_bytes();
+            assert_eq!(script.bytes.to_hex(), bitcoin_script.to_hex(), "n = {n}");
+        }
+    }
 }
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::encode::stream::Stream;
+use crate::encode::Encodable;
+use crate::script::standard_script::conditions;
+use crate::script::Script;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H160;
+
+/// The script committed by the signature of an input, `scriptCode` in BIP143 terms.
+///
+/// The script code differs from the scriptPubkey of the spent output for the witness outputs,
+/// so it can only be constructed for a specific output type. For example, a P2WPKH input
+/// is signed with the implied P2PKH script, but never with the witness program itself:
+///
+/// ```compile_fail
+/// use tw_hash::H160;
+/// use tw_utxo::script::standard_script::conditions;
+/// use tw_utxo::script::script_code::ScriptCode;
+///
+/// let pubkey_hash = H160::default();
+/// // `for_p2wpkh` expects the public key hash, not the v0 witness program.
+/// let script_code = ScriptCode::for_p2wpkh(conditions::new_p2wpkh(&pubkey_hash));
+/// ```
+///
+/// ```compile_fail
+/// use tw_hash::H160;
+/// use tw_utxo::script::standard_script::conditions;
+/// use tw_utxo::script::script_code::ScriptCode;
+///
+/// // A script can't be used as a script code without naming the output type.
+/// let script_code: ScriptCode = conditions::new_p2wpkh(&H160::default()).into();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptCode(Script);
+
+impl ScriptCode {
+    /// The script code of a legacy output, e.g. P2PKH or P2PK, is its scriptPubkey.
+    /// Returns an error if the script is neither a P2PKH nor a P2PK scriptPubkey.
+    pub fn for_p2pkh(script_pubkey: Script) -> SigningResult<ScriptCode> {
+        if conditions::match_p2pkh(&script_pubkey).is_none()
+            && conditions::match_p2pk(&script_pubkey).is_none()
+        {
+            return SigningError::err(SigningErrorType::Error_script_output)
+                .context("Expected a P2PKH or P2PK scriptPubkey as the legacy script code");
+        }
+        Ok(ScriptCode(script_pubkey))
+    }
+
+    /// The script code of a P2WPKH output is the implied P2PKH script (BIP143).
+    pub fn for_p2wpkh(pubkey_hash: H160) -> ScriptCode {
+        ScriptCode(Script::p2pkh_from_hash(pubkey_hash))
+    }
+
+    /// The script code of a P2WSH output is the witness script itself (BIP143).
+    pub fn for_p2wsh(witness_script: &Script) -> ScriptCode {
+        ScriptCode(witness_script.clone())
+    }
+
+    /// Taproot signatures don't commit to a script code (BIP341). Keeps the output key script
+    /// of a key-path spending. Returns an error if the script is not a P2TR scriptPubkey.
+    pub fn for_p2tr_key_path(script_pubkey: Script) -> SigningResult<ScriptCode> {
+        if !conditions::is_p2tr(&script_pubkey) {
+            return SigningError::err(SigningErrorType::Error_script_output)
+                .context("Expected a P2TR scriptPubkey as the key-path script code");
+        }
+        Ok(ScriptCode(script_pubkey))
+    }
+
+    /// The leaf script of a script-path spending is committed via its leaf hash (BIP341).
+    /// Keeps the revealed leaf script. Returns an error if the leaf is empty or has a malformed push.
+    pub fn for_tapscript(leaf_script: Script) -> SigningResult<ScriptCode> {
+        if leaf_script.is_empty() || !leaf_script.is_well_formed() {
+            return SigningError::err(SigningErrorType::Error_script_output)
+                .context("Expected a non-empty well-formed tapscript leaf as the script code");
+        }
+        Ok(ScriptCode(leaf_script))
+    }
+
+    pub fn as_script(&self) -> &Script {
+        &self.0
+    }
+
+    /// Restores a script code saved via [`ScriptCode::as_script`].
+    /// The template is not checked again, as it has been checked when the script code was created.
+    pub(crate) fn restore(script: Script) -> ScriptCode {
+        ScriptCode(script)
+    }
+}
+
+impl Encodable for ScriptCode {
+    fn encode(&self, stream: &mut Stream) {
+        self.0.encode(stream)
+    }
+
+    fn encoded_size(&self) -> usize {
+        self.0.encoded_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    const PUBKEY_HASH: &str = "60cda7b50f14c152d7401c28ae773c698db92373";
+
+    #[test]
+    fn test_script_code_p2wpkh() {
+        let pubkey_hash = H160::from(PUBKEY_HASH);
+
+        // The implied P2PKH script, not the v0 witness program.
+        let script_code = ScriptCode::for_p2wpkh(pubkey_hash);
+        assert_eq!(
+            *script_code.as_script(),
+            Script::p2pkh_from_hash(pubkey_hash)
+        );
+        assert_ne!(
+            *script_code.as_script(),
+            conditions::new_p2wpkh(&pubkey_hash)
+        );
+        assert_eq!(
+            script_code,
+            ScriptCode::for_p2pkh(Script::p2pkh_from_hash(pubkey_hash)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_script_code_template_mismatch() {
+        let pubkey_hash = H160::from(PUBKEY_HASH);
+        let p2wpkh = conditions::new_p2wpkh(&pubkey_hash);
+        let p2tr = Script::from(
+            "51205ee16f6144e2d46edea1427e1222ea879377e029b0c5d2e252517aee85948ec7"
+                .decode_hex()
+                .unwrap(),
+        );
+
+        // The v0 witness program is not a legacy script code.
+        ScriptCode::for_p2pkh(p2wpkh.clone()).unwrap_err();
+        ScriptCode::for_p2pkh(p2tr.clone()).unwrap_err();
+        ScriptCode::for_p2tr_key_path(p2tr.clone()).unwrap();
+        ScriptCode::for_p2tr_key_path(p2wpkh).unwrap_err();
+
+        ScriptCode::for_tapscript(p2tr).unwrap();
+        ScriptCode::for_tapscript(Script::default()).unwrap_err();
+        // `OP_PUSHBYTES_32` with a single byte.
+        ScriptCode::for_tapscript(Script::from(vec![0x20, 0x01])).unwrap_err();
+    }
+}
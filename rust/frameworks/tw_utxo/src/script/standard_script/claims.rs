@@ -1,4 +1,6 @@
-use tw_hash::H264;
+use tw_hash::{H256, H264};
+use tw_keypair::schnorr;
+use tw_memory::Data;
 
 use crate::script::Witness;
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature};
@@ -78,6 +80,26 @@ pub fn new_p2wpkh(sig: &BitcoinEcdsaSignature, pubkey: H264) -> Witness {
     w
 }
 
+/// Creates witness script items to claim a P2WSH output of a timelocked P2PKH
+/// witness script, see [`super::conditions::new_cltv_p2pkh`] (_witness_).
+///
+/// ```txt
+/// <sig>
+/// <pubkey>
+/// <witness_script>
+/// ```
+pub fn new_p2wsh_cltv_p2pkh(
+    sig: &BitcoinEcdsaSignature,
+    pubkey: H264,
+    witness_script: Script,
+) -> Witness {
+    let mut w = Witness::new();
+    w.push_item(Script::from(sig.serialize()));
+    w.push_item(Script::from(pubkey.into_vec()));
+    w.push_item(witness_script);
+    w
+}
+
 /// Creates witness script items to claim a P2TR spending condition
 /// (_witness_).
 ///
@@ -109,3 +131,58 @@ pub fn new_p2tr_script_path(
     w.push_item(Script::from(control_block));
     w
 }
+
+/// Leaf version of a Tapscript (BIP-342) leaf.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Creates a control block to claim a P2TR script-path spending condition.
+/// The parity bit of the first byte is taken from the tweaked `output_key`.
+///
+/// ```txt
+/// <leaf_version | output_key_parity>
+/// <internal_key>
+/// <merkle_branch...>
+/// ```
+pub fn new_tapscript_control_block(
+    internal_key: &schnorr::XOnlyPublicKey,
+    output_key: &schnorr::TweakedPublicKey,
+    merkle_branch: &[H256],
+) -> Data {
+    let mut control_block = Vec::with_capacity(1 + H256::LEN * (merkle_branch.len() + 1));
+    control_block.push(TAPSCRIPT_LEAF_VERSION | output_key.parity.to_u8());
+    control_block.extend_from_slice(internal_key.bytes().as_slice());
+    for node in merkle_branch {
+        control_block.extend_from_slice(node.as_slice());
+    }
+    control_block
+}
+
+/// Creates witness script items to claim a Tapscript multisig spending condition
+/// (_witness_).
+/// The `sigs` are expected in the same order as the public keys in the `payload` script,
+/// where `None` corresponds to a public key that does not sign.
+///
+/// The script consumes the signatures from the top of the stack,
+/// so they are placed in reverse order:
+///
+/// ```txt
+/// <sig_n or empty>
+/// ...
+/// <sig_1 or empty>
+/// <payload>
+/// <control_block>
+/// ```
+pub fn new_tapscript_multisig(
+    sigs: &[Option<BitcoinSchnorrSignature>],
+    payload: Script,
+    control_block: Vec<u8>,
+) -> Witness {
+    let mut w = Witness::new();
+    for sig in sigs.iter().rev() {
+        let item = sig.as_ref().map(BitcoinSchnorrSignature::serialize);
+        w.push_item(Script::from(item.unwrap_or_default()));
+    }
+    w.push_item(payload);
+    w.push_item(Script::from(control_block));
+    w
+}
@@ -1,15 +1,17 @@
-use bitcoin::hashes::Hash;
 use bitcoin::key::TweakedPublicKey;
 use secp256k1::XOnlyPublicKey;
+use tw_coin_entry::error::prelude::*;
 use tw_hash::H160;
 use tw_hash::H256;
 use tw_hash::H264;
+use tw_keypair::schnorr;
 use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
 
 use super::opcodes::*;
 use super::Script;
 use super::SEGWIT_VERSION;
+use crate::transaction::transaction_parts::LockTime;
 
 /// Creates a P2SH spending condition (_scriptPubkey_).
 ///
@@ -75,13 +77,24 @@ pub fn new_p2wpkh(pubkey_hash: &H160) -> Script {
     s
 }
 
-pub fn new_p2tr_key_path(pubkey: &H264) -> Script {
-    // We're relying on the `bitcoin` crate to generate anything Taproot related.
-    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_slice()).unwrap();
-    let internal_key = XOnlyPublicKey::from(pubkey.inner);
+/// Creates a timelocked P2PKH script, usually used as a P2WSH witness script.
+/// The output can't be spent until the transaction `nLockTime` reaches the `lock_time`.
+///
+/// ```txt
+/// <lock_time> OP_CHECKLOCKTIMEVERIFY OP_DROP OP_DUP OP_HASH160 <push><pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG
+/// ```
+pub fn new_cltv_p2pkh(lock_time: LockTime, pubkey_hash: &H160) -> Script {
+    let mut s = Script::with_capacity(33);
+    s.push_int(lock_time.to_consensus_u32() as i64);
+    s.push(OP_CLTV);
+    s.push(OP_DROP);
+    s.append(new_p2pkh(pubkey_hash).as_slice());
+    s
+}
 
-    let script = bitcoin::ScriptBuf::new_v1_p2tr(&secp256k1::Secp256k1::new(), internal_key, None);
-    Script::from(script.to_vec())
+/// The public key is validated on construction, so it's tweaked without parsing it again.
+pub fn new_p2tr_key_path(pubkey: &schnorr::PublicKey) -> Script {
+    new_p2tr_dangerous_assume_tweaked(&pubkey.tweak(None).xonly.bytes())
 }
 
 pub fn new_p2tr_dangerous_assume_tweaked(xonly: &H256) -> Script {
@@ -93,20 +106,45 @@ pub fn new_p2tr_dangerous_assume_tweaked(xonly: &H256) -> Script {
     Script::from(script.to_vec())
 }
 
-pub fn new_p2tr_script_path(pubkey: &H264, merkle_root: &H256) -> Script {
-    // We're relying on the `bitcoin` crate to generate anything Taproot related.
-    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_slice()).unwrap();
-    let internal_key = XOnlyPublicKey::from(pubkey.inner);
+pub fn new_p2tr_script_path(pubkey: &schnorr::PublicKey, merkle_root: &H256) -> Script {
+    new_p2tr_dangerous_assume_tweaked(&pubkey.tweak(Some(*merkle_root)).xonly.bytes())
+}
 
-    let hash = bitcoin::hashes::sha256t::Hash::<_>::from_slice(merkle_root.as_slice()).unwrap();
-    let merkle_root = bitcoin::taproot::TapNodeHash::from_raw_hash(hash);
+/// Max number of public keys in a Tapscript multisig.
+/// Each key requires a signature or an empty item on the stack that is limited to 1000 elements.
+pub const TAPSCRIPT_MULTISIG_MAX_KEYS: usize = 999;
 
-    let script = bitcoin::ScriptBuf::new_v1_p2tr(
-        &secp256k1::Secp256k1::new(),
-        internal_key,
-        Some(merkle_root),
-    );
-    Script::from(script.to_vec())
+/// Creates a Tapscript `k-of-n` multisig spending condition (_leaf script_).
+/// See [BIP-342](https://github.com/bitcoin/bips/blob/master/bip-0342.mediawiki#cite_note-5).
+///
+/// ```txt
+/// <pubkey_1> OP_CHECKSIG <pubkey_2> OP_CHECKSIGADD ... <pubkey_n> OP_CHECKSIGADD <k> OP_NUMEQUAL
+/// ```
+pub fn new_tapscript_multisig(threshold: usize, pubkeys: &[H256]) -> SigningResult<Script> {
+    if pubkeys.is_empty() || pubkeys.len() > TAPSCRIPT_MULTISIG_MAX_KEYS {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "Tapscript multisig must contain 1..={TAPSCRIPT_MULTISIG_MAX_KEYS} public keys"
+        ));
+    }
+    if threshold == 0 || threshold > pubkeys.len() {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "Tapscript multisig threshold must be in 1..={} range",
+            pubkeys.len()
+        ));
+    }
+
+    let mut s = Script::with_capacity(pubkeys.len() * 34 + 4);
+    for (i, pubkey) in pubkeys.iter().enumerate() {
+        s.push_slice(pubkey.as_slice());
+        if i == 0 {
+            s.push(OP_CHECKSIG);
+        } else {
+            s.push(OP_CHECKSIGADD);
+        }
+    }
+    s.push_int(threshold as i64);
+    s.push(OP_NUMEQUAL);
+    Ok(s)
 }
 
 pub fn new_op_return(data: &[u8]) -> Script {
@@ -171,6 +209,79 @@ pub fn match_p2pkh(s: &Script) -> Option<H160> {
     }
 }
 
+/// Returns a script hash if matched.
+pub fn match_p2sh(s: &Script) -> Option<H160> {
+    if is_p2sh(s) {
+        Some(H160::try_from(&s.as_slice()[2..22]).expect("is_p2sh checks the length"))
+    } else {
+        None
+    }
+}
+
+/// Returns the number of required signatures and the public keys if matched a bare
+/// `OP_m <pubkey_1> ... <pubkey_n> OP_n OP_CHECKMULTISIG` script.
+pub fn match_multisig(s: &Script) -> Option<(usize, Vec<Data>)> {
+    let (last_opcode, body) = s.as_slice().split_last()?;
+    if *last_opcode != OP_CHECKMULTISIG {
+        return None;
+    }
+
+    let body = Script::from(body.to_vec());
+    let ops = body.push_only_ops()?;
+    let ((first_opcode, _), rest) = ops.split_first()?;
+    let ((n_opcode, _), pubkeys) = rest.split_last()?;
+
+    let small_int = |opcode: u8| match opcode {
+        OP_PUSHNUM_1..=OP_PUSHNUM_16 => Some((opcode - OP_PUSHNUM_1 + 1) as usize),
+        _ => None,
+    };
+    let required = small_int(*first_opcode)?;
+    let total = small_int(*n_opcode)?;
+    if total != pubkeys.len() || required > total {
+        return None;
+    }
+
+    pubkeys
+        .iter()
+        .map(|(opcode, pubkey)| match (*opcode, pubkey.len()) {
+            (OP_PUSHBYTES_33, 33) | (OP_PUSHBYTES_65, 65) => Some(pubkey.to_vec()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|pubkeys| (required, pubkeys))
+}
+
+/// Returns the lock time and the public key hash if matched a [`new_cltv_p2pkh`] script.
+/// The lock time must be minimally encoded.
+pub fn match_cltv_p2pkh(s: &Script) -> Option<(LockTime, H160)> {
+    let b = s.as_slice();
+    // `OP_CLTV OP_DROP` followed by a P2PKH script.
+    let (prefix, p2pkh) = b.split_at(b.len().checked_sub(27)?);
+    let pubkey_hash = match_p2pkh(&Script::from(p2pkh[2..].to_vec()))?;
+    if p2pkh[0] != OP_CLTV || p2pkh[1] != OP_DROP {
+        return None;
+    }
+
+    let lock_time = match *prefix.first()? {
+        OP_PUSHBYTES_0 => 0,
+        opcode @ OP_PUSHNUM_1..=OP_PUSHNUM_16 => (opcode - OP_PUSHNUM_1 + 1) as u32,
+        // Script numbers are up to 5 bytes long, the sign bit must not be set.
+        len @ 1..=5 if prefix.len() == len as usize + 1 && prefix[len as usize] & 0x80 == 0 => {
+            let mut bytes = [0; 8];
+            bytes[..len as usize].copy_from_slice(&prefix[1..]);
+            u32::try_from(u64::from_le_bytes(bytes)).ok()?
+        },
+        _ => return None,
+    };
+
+    // Check if the lock time is encoded minimally.
+    let lock_time = LockTime::from_consensus(lock_time);
+    if new_cltv_p2pkh(lock_time, &pubkey_hash) != *s {
+        return None;
+    }
+    Some((lock_time, pubkey_hash))
+}
+
 /// Returns a public key hash if matched.
 pub fn match_p2wpkh(s: &Script) -> Option<H160> {
     if is_p2wpkh(s) {
@@ -188,3 +299,79 @@ pub fn match_p2tr(s: &Script) -> Option<H256> {
         None
     }
 }
+
+/// Standard scriptPubkey classification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptPubkeyType {
+    P2PK,
+    P2PKH,
+    P2SH,
+    P2WPKH,
+    P2WSH,
+    P2TR,
+    OpReturn,
+    /// A [`new_cltv_p2pkh`] script. It's non-standard as a bare scriptPubkey,
+    /// but it's reported for P2WSH outputs with a known witness script.
+    Timelocked,
+    /// Non-standard scriptPubkey.
+    Custom,
+}
+
+impl ScriptPubkeyType {
+    pub fn classify(s: &Script) -> Self {
+        if match_p2pk(s).is_some() {
+            ScriptPubkeyType::P2PK
+        } else if is_p2pkh(s) {
+            ScriptPubkeyType::P2PKH
+        } else if is_p2sh(s) {
+            ScriptPubkeyType::P2SH
+        } else if is_p2wpkh(s) {
+            ScriptPubkeyType::P2WPKH
+        } else if is_p2wsh(s) {
+            ScriptPubkeyType::P2WSH
+        } else if is_p2tr(s) {
+            ScriptPubkeyType::P2TR
+        } else if is_op_return(s) {
+            ScriptPubkeyType::OpReturn
+        } else if match_cltv_p2pkh(s).is_some() {
+            ScriptPubkeyType::Timelocked
+        } else {
+            ScriptPubkeyType::Custom
+        }
+    }
+
+    /// Classifies a P2WSH output by its witness script if the script hashes to the output,
+    /// e.g. a [`ScriptPubkeyType::Timelocked`] vault. Otherwise, classifies the scriptPubkey as is.
+    pub fn classify_with_witness_script(s: &Script, witness_script: &Script) -> Self {
+        if *s == Script::p2wsh_from_script(witness_script) {
+            match ScriptPubkeyType::classify(witness_script) {
+                ScriptPubkeyType::Timelocked => ScriptPubkeyType::Timelocked,
+                _ => ScriptPubkeyType::P2WSH,
+            }
+        } else {
+            ScriptPubkeyType::classify(s)
+        }
+    }
+
+    /// Whether the script is standard as a bare scriptPubkey.
+    pub fn is_standard(&self) -> bool {
+        !matches!(
+            self,
+            ScriptPubkeyType::Custom | ScriptPubkeyType::Timelocked
+        )
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptPubkeyType::P2PK => "p2pk",
+            ScriptPubkeyType::P2PKH => "p2pkh",
+            ScriptPubkeyType::P2SH => "p2sh",
+            ScriptPubkeyType::P2WPKH => "p2wpkh",
+            ScriptPubkeyType::P2WSH => "p2wsh",
+            ScriptPubkeyType::P2TR => "p2tr",
+            ScriptPubkeyType::OpReturn => "op_return",
+            ScriptPubkeyType::Timelocked => "timelocked",
+            ScriptPubkeyType::Custom => "custom",
+        }
+    }
+}
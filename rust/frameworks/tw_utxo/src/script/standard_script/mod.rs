@@ -3,5 +3,7 @@ use super::Script;
 pub mod claims;
 pub mod conditions;
 pub mod opcodes;
+pub mod patterns;
+pub(crate) mod templates;
 
-const SEGWIT_VERSION: u8 = 0;
+pub(crate) const SEGWIT_VERSION: u8 = 0;
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Additional scriptPubkey templates registered by the chain params, e.g. the variants
+//! of the standard scripts used by partner chains.
+//!
+//! A template is described as a sequence of whitespace-separated items:
+//! - an opcode by its Bitcoin Core name, e.g. `OP_CHECKSIGVERIFY` or `OP_1`;
+//! - `<N>`, a push of exactly `N` bytes of any content, e.g. `<20>` for a public key hash.
+//!
+//! For example, `OP_DUP OP_HASH160 <20> OP_EQUALVERIFY OP_CHECKSIGVERIFY OP_1`.
+
+use super::conditions::ScriptPubkeyType;
+use super::opcodes::*;
+use crate::script::asm::opcode_from_name;
+use crate::script::{next_op, Script};
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H160;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatternItem {
+    /// The exact opcode.
+    Opcode(u8),
+    /// A minimal push of exactly the given number of bytes.
+    Push(usize),
+}
+
+/// A scriptPubkey pattern, see the module docs for the syntax.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptPattern {
+    items: Vec<PatternItem>,
+}
+
+impl ScriptPattern {
+    pub fn new(items: Vec<PatternItem>) -> SigningResult<Self> {
+        if items.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Script pattern must not be empty");
+        }
+        let data_push = items.iter().find_map(|item| match item {
+            PatternItem::Opcode(opcode @ OP_PUSHBYTES_1..=OP_PUSHDATA4) => Some(*opcode),
+            _ => None,
+        });
+        if let Some(opcode) = data_push {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Script pattern must use `<N>` for data pushes, found opcode {opcode:#04x}"
+            ));
+        }
+        Ok(ScriptPattern { items })
+    }
+
+    pub fn items(&self) -> &[PatternItem] {
+        &self.items
+    }
+
+    /// Returns the data of the `<N>` pushes in the order of the pattern if the script matches.
+    pub fn match_script<'a>(&self, s: &'a Script) -> Option<Vec<&'a [u8]>> {
+        let bytes = s.as_slice();
+        let mut pushes = Vec::new();
+        let mut pc = 0;
+        for item in self.items.iter() {
+            let (opcode, data) = next_op(bytes, &mut pc)?;
+            match *item {
+                PatternItem::Opcode(expected) if opcode == expected => (),
+                // Only the direct pushes are minimal for the data of up to 75 bytes.
+                PatternItem::Push(len) if data.len() == len && is_minimal_push(opcode, len) => {
+                    pushes.push(data)
+                },
+                _ => return None,
+            }
+        }
+        // The whole script must be matched.
+        if pc != bytes.len() {
+            return None;
+        }
+        Some(pushes)
+    }
+}
+
+impl FromStr for ScriptPattern {
+    type Err = SigningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let items = s
+            .split_whitespace()
+            .map(|item| {
+                let push_len = item
+                    .strip_prefix('<')
+                    .and_then(|item| item.strip_suffix('>'));
+                if let Some(push_len) = push_len {
+                    return push_len
+                        .parse()
+                        .ok()
+                        .filter(|len| *len > 0)
+                        .map(PatternItem::Push)
+                        .or_tw_err(SigningErrorType::Error_invalid_params)
+                        .with_context(|| format!("Invalid push length: {item}"));
+                }
+                opcode_from_name(item)
+                    .map(PatternItem::Opcode)
+                    .or_tw_err(SigningErrorType::Error_invalid_params)
+                    .with_context(|| format!("Unknown opcode: {item}"))
+            })
+            .collect::<SigningResult<_>>()?;
+        ScriptPattern::new(items)
+    }
+}
+
+fn is_minimal_push(opcode: u8, len: usize) -> bool {
+    match len {
+        0..=75 => opcode as usize == len,
+        76..=0xFF => opcode == OP_PUSHDATA1,
+        0x100..=0xFFFF => opcode == OP_PUSHDATA2,
+        _ => opcode == OP_PUSHDATA4,
+    }
+}
+
+/// How the address of a script matched by a [`ScriptTemplate`] is displayed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TemplateAddress {
+    /// The script has no address representation.
+    None,
+    /// The first `<20>` push is encoded as a P2PKH address.
+    P2PKH,
+    /// The first `<20>` push is encoded as a P2SH address.
+    P2SH,
+}
+
+/// An additional scriptPubkey template mapped to a classification and an address form.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptTemplate {
+    pub pattern: ScriptPattern,
+    pub script_type: ScriptPubkeyType,
+    pub address: TemplateAddress,
+}
+
+impl ScriptTemplate {
+    /// Parses the `pattern` and checks that it can be displayed with the `address` form.
+    pub fn new(
+        pattern: &str,
+        script_type: ScriptPubkeyType,
+        address: TemplateAddress,
+    ) -> SigningResult<Self> {
+        let pattern = ScriptPattern::from_str(pattern)
+            .with_context(|| format!("Invalid script pattern: {pattern}"))?;
+
+        let has_hash = pattern.items().contains(&PatternItem::Push(H160::LEN));
+        if address != TemplateAddress::None && !has_hash {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Script pattern of a legacy address must contain a `<20>` push");
+        }
+        Ok(ScriptTemplate {
+            pattern,
+            script_type,
+            address,
+        })
+    }
+
+    /// Returns the hash of the address if the script matches the template.
+    /// The hash is `None` if the template has no address representation.
+    pub fn match_script(&self, s: &Script) -> Option<Option<H160>> {
+        let pushes = self.pattern.match_script(s)?;
+        if self.address == TemplateAddress::None {
+            return Some(None);
+        }
+        let hash = pushes
+            .into_iter()
+            .find(|data| data.len() == H160::LEN)
+            .expect("ScriptTemplate::new checks the pattern contains a hash push");
+        Some(Some(
+            H160::try_from(hash).expect("Checked the length above"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    fn script(hex: &str) -> Script {
+        Script::from(hex.decode_hex().unwrap())
+    }
+
+    #[test]
+    fn test_script_pattern_parse() {
+        let pattern =
+            ScriptPattern::from_str("OP_DUP OP_HASH160 <20> OP_EQUALVERIFY OP_CHECKSIGVERIFY OP_1")
+                .unwrap();
+        assert_eq!(
+            pattern.items(),
+            &[
+                PatternItem::Opcode(OP_DUP),
+                PatternItem::Opcode(OP_HASH160),
+                PatternItem::Push(20),
+                PatternItem::Opcode(OP_EQUALVERIFY),
+                PatternItem::Opcode(OP_CHECKSIGVERIFY),
+                PatternItem::Opcode(OP_PUSHNUM_1),
+            ]
+        );
+
+        ScriptPattern::from_str("").unwrap_err();
+        ScriptPattern::from_str("OP_DUP OP_UNKNOWN").unwrap_err();
+        ScriptPattern::from_str("OP_DUP <0>").unwrap_err();
+        ScriptPattern::from_str("OP_DUP <20").unwrap_err();
+        ScriptPattern::from_str("OP_17").unwrap_err();
+    }
+
+    #[test]
+    fn test_script_pattern_match() {
+        let pattern = ScriptPattern::from_str("OP_1 <2> OP_CHECKSIG").unwrap();
+
+        let matched = script("51020102ac");
+        assert_eq!(pattern.match_script(&matched), Some(vec![&[1_u8, 2][..]]));
+        // Non-minimal push.
+        assert_eq!(pattern.match_script(&script("514c020102ac")), None);
+        // Wrong push length.
+        assert_eq!(pattern.match_script(&script("5103010203ac")), None);
+        // Trailing opcodes.
+        assert_eq!(pattern.match_script(&script("51020102acac")), None);
+        // Truncated script.
+        assert_eq!(pattern.match_script(&script("510201")), None);
+    }
+}
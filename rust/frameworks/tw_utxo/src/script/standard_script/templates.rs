@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Standard scriptPubkey templates built from public keys and scripts,
+//! so callers don't need to hash them themselves.
+
+use super::conditions;
+use super::Script;
+use crate::transaction::transaction_parts::LockTime;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::sha2::sha256;
+use tw_hash::{H160, H256};
+use tw_keypair::{ecdsa, schnorr, tw};
+
+impl Script {
+    /// Creates a P2PKH scriptPubkey of the compressed secp256k1 public key.
+    pub fn p2pkh_from_pubkey(pubkey: &tw::PublicKey) -> SigningResult<Script> {
+        let pubkey = secp256k1_pubkey(pubkey)?;
+        Ok(Script::p2pkh_from_hash(pubkey_hash(pubkey)))
+    }
+
+    /// `OP_DUP OP_HASH160 <push><pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG`
+    pub fn p2pkh_from_hash(pubkey_hash: H160) -> Script {
+        conditions::new_p2pkh(&pubkey_hash)
+    }
+
+    /// `OP_HASH160 <push><script_hash> OP_EQUAL`
+    pub fn p2sh_from_hash(script_hash: H160) -> Script {
+        conditions::new_p2sh(&script_hash)
+    }
+
+    /// Creates a P2WPKH scriptPubkey of the compressed secp256k1 public key.
+    pub fn p2wpkh_from_pubkey(pubkey: &tw::PublicKey) -> SigningResult<Script> {
+        let pubkey = secp256k1_pubkey(pubkey)?;
+        Ok(conditions::new_p2wpkh(&pubkey_hash(pubkey)))
+    }
+
+    /// `0 <push><sha256(witness_script)>`
+    pub fn p2wsh_from_script(witness_script: &Script) -> Script {
+        let script_hash = H256::try_from(sha256(witness_script.as_slice()).as_slice())
+            .expect("sha256 must return 32 bytes");
+        conditions::new_p2wsh(&script_hash)
+    }
+
+    /// `<lock_time> OP_CHECKLOCKTIMEVERIFY OP_DROP OP_DUP OP_HASH160 <push><pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG`
+    ///
+    /// The script is meant to be used as a P2WSH witness script, see [`Script::p2wsh_from_script`].
+    pub fn cltv_p2pkh(lock_time: LockTime, pubkey: &ecdsa::secp256k1::PublicKey) -> Script {
+        conditions::new_cltv_p2pkh(lock_time, &pubkey_hash(pubkey))
+    }
+
+    /// `1 <push><output_key>`, where the output key is tweaked already.
+    pub fn p2tr_from_output_key(output_key: &schnorr::XOnlyPublicKey) -> Script {
+        conditions::new_p2tr_dangerous_assume_tweaked(&output_key.bytes())
+    }
+}
+
+fn secp256k1_pubkey(pubkey: &tw::PublicKey) -> SigningResult<&ecdsa::secp256k1::PublicKey> {
+    pubkey
+        .to_secp256k1()
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("Expected a secp256k1 public key")
+}
+
+pub(crate) fn pubkey_hash(pubkey: &ecdsa::secp256k1::PublicKey) -> H160 {
+    H160::try_from(sha256_ripemd(pubkey.compressed().as_slice()).as_slice())
+        .expect("sha256_ripemd must return 20 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::script_code::ScriptCode;
+    use tw_encoding::hex::{self, DecodeHex};
+    use tw_keypair::tw::PublicKeyType;
+
+    const PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+    const PUBKEY_HASH: &str = "60cda7b50f14c152d7401c28ae773c698db92373";
+
+    fn tw_pubkey(ty: PublicKeyType) -> tw::PublicKey {
+        tw::PublicKey::new(PUBKEY.decode_hex().unwrap(), ty).unwrap()
+    }
+
+    fn pubkey_hash() -> H160 {
+        H160::from(PUBKEY_HASH)
+    }
+
+    #[track_caller]
+    fn assert_script(script: Script, expected: &str) {
+        assert_eq!(hex::encode(script.as_slice(), false), expected);
+    }
+
+    #[test]
+    fn test_p2pkh() {
+        let expected = "76a91460cda7b50f14c152d7401c28ae773c698db9237388ac";
+        assert_script(
+            Script::p2pkh_from_pubkey(&tw_pubkey(PublicKeyType::Secp256k1)).unwrap(),
+            expected,
+        );
+        assert_script(Script::p2pkh_from_hash(pubkey_hash()), expected);
+        assert_script(
+            ScriptCode::for_p2pkh(Script::p2pkh_from_hash(pubkey_hash()))
+                .unwrap()
+                .as_script()
+                .clone(),
+            expected,
+        );
+    }
+
+    #[test]
+    fn test_p2sh() {
+        // P2SH-P2WPKH of the public key.
+        let script_hash = H160::from("5e6038d009952ae0946d269cbb665fbe66299a58");
+        assert_script(
+            Script::p2sh_from_hash(script_hash),
+            "a9145e6038d009952ae0946d269cbb665fbe66299a5887",
+        );
+    }
+
+    #[test]
+    fn test_p2wpkh() {
+        assert_script(
+            Script::p2wpkh_from_pubkey(&tw_pubkey(PublicKeyType::Secp256k1)).unwrap(),
+            "001460cda7b50f14c152d7401c28ae773c698db92373",
+        );
+        // The implied P2PKH script.
+        assert_script(
+            ScriptCode::for_p2wpkh(pubkey_hash()).as_script().clone(),
+            "76a91460cda7b50f14c152d7401c28ae773c698db9237388ac",
+        );
+    }
+
+    #[test]
+    fn test_p2wsh() {
+        // 1-of-1 multisig.
+        let witness_script = Script::from(format!("5121{PUBKEY}51ae").decode_hex().unwrap());
+        assert_script(
+            Script::p2wsh_from_script(&witness_script),
+            "0020a35eb7c13fe91c718bbd90bf0017be275db018e7c7e4621aa6ac058947c10a03",
+        );
+        assert_eq!(
+            *ScriptCode::for_p2wsh(&witness_script).as_script(),
+            witness_script
+        );
+    }
+
+    #[test]
+    fn test_cltv_p2pkh() {
+        let pubkey = ecdsa::secp256k1::PublicKey::try_from(PUBKEY).unwrap();
+
+        // Block height 840000.
+        let script = Script::cltv_p2pkh(LockTime::Height(840_000), &pubkey);
+        assert_script(
+            script.clone(),
+            "0340d10cb17576a91460cda7b50f14c152d7401c28ae773c698db9237388ac",
+        );
+        assert_eq!(
+            conditions::match_cltv_p2pkh(&script),
+            Some((LockTime::Height(840_000), pubkey_hash()))
+        );
+
+        // Small numbers are pushed with `OP_1..OP_16`.
+        let script = Script::cltv_p2pkh(LockTime::Height(16), &pubkey);
+        assert_script(
+            script.clone(),
+            "60b17576a91460cda7b50f14c152d7401c28ae773c698db9237388ac",
+        );
+        assert_eq!(
+            conditions::match_cltv_p2pkh(&script),
+            Some((LockTime::Height(16), pubkey_hash()))
+        );
+
+        // 5-byte script number, as the sign bit of the 4th byte is set.
+        let script = Script::cltv_p2pkh(LockTime::Time(0x8000_0000), &pubkey);
+        assert_script(
+            script.clone(),
+            "050000008000b17576a91460cda7b50f14c152d7401c28ae773c698db9237388ac",
+        );
+        assert_eq!(
+            conditions::match_cltv_p2pkh(&script),
+            Some((LockTime::Time(0x8000_0000), pubkey_hash()))
+        );
+
+        // Non-minimal encoding of 16.
+        let script = Script::from(
+            "0110b17576a91460cda7b50f14c152d7401c28ae773c698db9237388ac"
+                .decode_hex()
+                .unwrap(),
+        );
+        assert_eq!(conditions::match_cltv_p2pkh(&script), None);
+        // Negative lock time.
+        let script = Script::from(
+            "0181b17576a91460cda7b50f14c152d7401c28ae773c698db9237388ac"
+                .decode_hex()
+                .unwrap(),
+        );
+        assert_eq!(conditions::match_cltv_p2pkh(&script), None);
+        // Plain P2PKH.
+        assert_eq!(
+            conditions::match_cltv_p2pkh(&Script::p2pkh_from_hash(pubkey_hash())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_p2tr() {
+        let output_key = schnorr::XOnlyPublicKey::try_from(
+            "e8b706a97732e705e22ae7710703e7f589ed13c636324461afa443016134cc05"
+                .decode_hex()
+                .unwrap()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_script(
+            Script::p2tr_from_output_key(&output_key),
+            "5120e8b706a97732e705e22ae7710703e7f589ed13c636324461afa443016134cc05",
+        );
+    }
+
+    #[test]
+    fn test_non_secp256k1_pubkey() {
+        let pubkey = tw::PublicKey::new(
+            "4870d56d074c50e891506d78faa4fb69ca039cc5f131eb491e166b975880e867"
+                .decode_hex()
+                .unwrap(),
+            PublicKeyType::Ed25519,
+        )
+        .unwrap();
+        let err = Script::p2pkh_from_pubkey(&pubkey).unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Deterministic shuffling of transaction inputs and outputs.
+//! The order is randomized for privacy, but reproducible given the same random source (e.g. a seed) for auditing.
+
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// A source of random numbers for shuffling.
+/// Implemented by the caller, as Rust RNG types cannot be passed through FFI.
+pub trait ShuffleRng {
+    fn next_u64(&mut self) -> u64;
+}
+
+/// The default [`ShuffleRng`] producing the ChaCha20 keystream of the given seed.
+pub struct ChaChaShuffleRng(ChaCha20Rng);
+
+impl ChaChaShuffleRng {
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        ChaChaShuffleRng(ChaCha20Rng::from_seed(seed))
+    }
+}
+
+impl ShuffleRng for ChaChaShuffleRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+/// Shuffles the items using the Fisher–Yates algorithm.
+pub fn shuffle<T, R>(items: &mut [T], rng: &mut R)
+where
+    R: ShuffleRng + ?Sized,
+{
+    for i in (1..items.len()).rev() {
+        let j = uniform(rng, i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Returns a uniformly distributed number in the `0..bound` range.
+/// Numbers below `2^64 % bound` are rejected to avoid the modulo bias.
+fn uniform<R>(rng: &mut R, bound: u64) -> u64
+where
+    R: ShuffleRng + ?Sized,
+{
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let n = rng.next_u64();
+        if n >= threshold {
+            return n % bound;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SequenceRng(Vec<u64>);
+
+    impl ShuffleRng for SequenceRng {
+        fn next_u64(&mut self) -> u64 {
+            self.0.remove(0)
+        }
+    }
+
+    #[test]
+    fn test_chacha_shuffle_rng() {
+        // ChaCha20 keystream of the zero key and nonce.
+        let mut rng = ChaChaShuffleRng::from_seed([0; 32]);
+        assert_eq!(rng.next_u64(), 0x903df1a0ade0b876);
+        assert_eq!(rng.next_u64(), 0x28bd8653e56a5d40);
+    }
+
+    #[test]
+    fn test_uniform_rejects_biased() {
+        // `2^64 % 3 == 1`, so `0` is rejected.
+        let mut rng = SequenceRng(vec![0, 5]);
+        assert_eq!(uniform(&mut rng, 3), 2);
+        assert!(rng.0.is_empty());
+
+        let mut rng = SequenceRng(vec![u64::MAX]);
+        assert_eq!(uniform(&mut rng, 1), 0);
+    }
+
+    #[test]
+    fn test_shuffle_fisher_yates() {
+        // `j` is chosen for `i = 3, 2, 1`.
+        let mut rng = SequenceRng(vec![0, 2, 0]);
+        let mut items = [0, 1, 2, 3];
+        shuffle(&mut items, &mut rng);
+        assert_eq!(items, [1, 3, 2, 0]);
+
+        // Nothing to shuffle.
+        let mut rng = SequenceRng(vec![]);
+        shuffle(&mut [0], &mut rng);
+        shuffle::<u8, _>(&mut [], &mut rng);
+    }
+}
@@ -2,12 +2,19 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::signing_mode::SigningMethod;
 use tw_coin_entry::error::prelude::*;
 
 const ANYONE_CAN_PAY_FLAG: u32 = 0x80;
 const FORK_ID_FLAG: u32 = 0x40;
 const BASE_FLAG: u32 = 0x1f;
-const DEFAULT_TAPROOT_SIGHASH_TYPE: u8 = 0;
+/// Flags that are allowed to be set in the lower byte of the sighash type.
+/// Note `0x20` and `0x04..=0x1c` bits are undefined.
+const DEFINED_FLAGS: u32 = ANYONE_CAN_PAY_FLAG | FORK_ID_FLAG | 0x03;
+/// Fork id value is stored in the upper 24 bits of the sighash type.
+const FORK_ID_SHIFT: u32 = 8;
+const MAX_FORK_ID: u32 = 0x00ff_ffff;
+pub(crate) const DEFAULT_TAPROOT_SIGHASH_TYPE: u8 = 0;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -31,16 +38,60 @@ pub struct SighashType {
 }
 
 impl SighashType {
-    /// Creates Sighash from any u32.
+    /// Creates Sighash from u32 strictly.
+    ///
+    /// Only the base type, `SIGHASH_ANYONECANPAY` and `SIGHASH_FORK_ID` bits may be set.
+    /// The upper 24 bits are allowed only if `SIGHASH_FORK_ID` is set, as they carry the fork id value.
+    /// Use [`SighashType::from_u32_lenient`] to decode historical chain data.
     pub fn from_u32(u: u32) -> SigningResult<Self> {
+        let fork_id_bits = if u & FORK_ID_FLAG == FORK_ID_FLAG {
+            MAX_FORK_ID << FORK_ID_SHIFT
+        } else {
+            0
+        };
+
+        if u & !(DEFINED_FLAGS | fork_id_bits) != 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                .context(format!("Sighash type has reserved bits set: {u:#x}"));
+        }
+        // Only `SIGHASH_DEFAULT` may have no base type, the flags cannot be combined with it.
+        if u != DEFAULT_TAPROOT_SIGHASH_TYPE as u32 && !(1..=3).contains(&(u & BASE_FLAG)) {
+            return SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                .context(format!("Sighash type has no base type: {u:#x}"));
+        }
+        Self::from_u32_lenient(u)
+    }
+
+    /// Creates the Taproot `SIGHASH_DEFAULT` (0x00) sighash type.
+    /// It has the `SIGHASH_ALL` semantics, but is not allowed in Legacy or Segwit transactions.
+    pub fn sighash_default() -> Self {
+        SighashType {
+            raw_sighash: DEFAULT_TAPROOT_SIGHASH_TYPE as u32,
+            base: SighashBase::All,
+        }
+    }
+
+    /// Returns the sighash type used if the caller doesn't specify one:
+    /// `SIGHASH_DEFAULT` for Taproot inputs, as BIP341 recommends, and `SIGHASH_ALL` otherwise.
+    pub fn default_for(signing_method: SigningMethod) -> Self {
+        match signing_method {
+            SigningMethod::Taproot => Self::sighash_default(),
+            SigningMethod::Legacy | SigningMethod::Segwit => Self::default(),
+        }
+    }
+
+    /// Creates Sighash from any u32, ignoring undefined bits.
+    /// Undefined base types are treated as `SIGHASH_ALL`, as Bitcoin Core does in `SignatureHash`:
+    /// only `SIGHASH_NONE` and `SIGHASH_SINGLE` change which outputs are signed.
+    pub fn from_u32_lenient(u: u32) -> SigningResult<Self> {
+        if u == DEFAULT_TAPROOT_SIGHASH_TYPE as u32 {
+            return Ok(Self::sighash_default());
+        }
+
         let base = match u & BASE_FLAG {
-            1 => SighashBase::All,
             2 => SighashBase::None,
             3 => SighashBase::Single,
-            _ => {
-                return SigningError::err(SigningErrorType::Error_invalid_params)
-                    .context("Invalid sighash uint32 representation")
-            },
+            _ => SighashBase::All,
         };
         Ok(SighashType {
             raw_sighash: u,
@@ -48,20 +99,44 @@ impl SighashType {
         })
     }
 
+    /// Creates a Sighash with the `SIGHASH_FORK_ID` flag and the given fork id value.
+    /// The result is composed as `(fork_id << 8) | FORK_ID_FLAG | base`,
+    /// for example, 0 for BitcoinCash and 79 for BitcoinGold.
+    pub fn with_fork_id(base: SighashBase, fork_id: u32) -> SigningResult<Self> {
+        if fork_id > MAX_FORK_ID {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Fork id must fit 24 bits, but {fork_id} given"));
+        }
+        Ok(SighashType {
+            raw_sighash: (fork_id << FORK_ID_SHIFT) | FORK_ID_FLAG | base as u32,
+            base,
+        })
+    }
+
     /// Returns a raw sighash type.
     pub fn raw_sighash(&self) -> u32 {
         self.raw_sighash
     }
 
     /// Returns a raw sighash type as u8 if possible.
+    /// If the sighash has the `SIGHASH_FORK_ID` flag, the fork id value is omitted,
+    /// as it is committed in the preimage only.
     pub fn serialize(&self) -> SigningResult<u8> {
+        if self.is_sighash_default() {
+            return SigningError::err(SigningErrorType::Error_sighash_default_not_allowed)
+                .context("SIGHASH_DEFAULT can be serialized in Taproot transactions only");
+        }
+        if self.fork_id() {
+            return Ok((self.raw_sighash & 0xff) as u8);
+        }
         self.raw_sighash
             .try_into()
             .tw_err(|_| SigningErrorType::Error_invalid_params)
             .context("sighashType must fit uint8")
     }
 
-    /// Returns a serialized raw sighash type, where `All` sighash is serialized as 0x00 (`Default`).
+    /// Returns a serialized raw sighash type as u8.
+    /// An explicit `SIGHASH_ALL` is kept as 0x01, as it commits to a different digest than 0x00 (`Default`).
     /// The 0x00 variant is only supported in Taproot transactions,
     /// not in Legacy or Segwit transactions.
     pub fn serialize_as_taproot(&self) -> SigningResult<u8> {
@@ -80,9 +155,14 @@ impl SighashType {
     /// not in Legacy or Segwit transactions.
     pub fn is_default_taproot_sighash(&self) -> bool {
         self.raw_sighash == DEFAULT_TAPROOT_SIGHASH_TYPE as u32
-            || self.raw_sighash == SighashBase::All as u32
     }
 
+    /// Whether the sighash type is exactly `SIGHASH_DEFAULT` (0x00).
+    pub fn is_sighash_default(&self) -> bool {
+        self.raw_sighash == DEFAULT_TAPROOT_SIGHASH_TYPE as u32
+    }
+
+    /// Returns the base type. `SIGHASH_DEFAULT` has the `SIGHASH_ALL` semantics.
     pub fn base_type(&self) -> SighashBase {
         self.base
     }
@@ -96,8 +176,15 @@ impl SighashType {
     pub fn fork_id(&self) -> bool {
         (self.raw_sighash & FORK_ID_FLAG) == FORK_ID_FLAG
     }
+
+    /// Returns a fork id value if the `SIGHASH_FORK_ID` flag is set.
+    pub fn fork_id_value(&self) -> Option<u32> {
+        self.fork_id().then_some(self.raw_sighash >> FORK_ID_SHIFT)
+    }
 }
 
+/// `SIGHASH_ALL` is the only sighash type valid for every signing method.
+/// Use [`SighashType::default_for`] to get `SIGHASH_DEFAULT` for Taproot inputs.
 impl Default for SighashType {
     fn default() -> Self {
         SighashType {
@@ -106,3 +193,181 @@ impl Default for SighashType {
         }
     }
 }
+
+/// The sighash types a chain accepts, see [`crate::chain_params::ChainParams::allowed_sighash_types`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AllowedSighashTypes {
+    /// Every combination accepted by [`SighashType::from_u32`].
+    #[default]
+    AllDefined,
+    /// The listed sighash types only.
+    Only(Vec<SighashType>),
+}
+
+impl AllowedSighashTypes {
+    /// Zcash v5 (ZIP-244) requires `SIGHASH_ALL` for the transactions with shielded components,
+    /// so the other types are rejected regardless of the transaction.
+    /// https://zips.z.cash/zip-0244#signature-digest
+    pub fn zcash() -> Self {
+        AllowedSighashTypes::Only(vec![SighashType::default()])
+    }
+
+    /// Whether the chain accepts the sighash type.
+    pub fn contains(&self, sighash_ty: SighashType) -> bool {
+        match self {
+            AllowedSighashTypes::AllDefined => true,
+            AllowedSighashTypes::Only(allowed) => allowed.contains(&sighash_ty),
+        }
+    }
+
+    /// Checks if the chain accepts the sighash type.
+    pub fn check(&self, sighash_ty: SighashType) -> SigningResult<()> {
+        if !self.contains(sighash_ty) {
+            return SigningError::err(SigningErrorType::Error_sighash_type_not_allowed).context(
+                format!(
+                    "Sighash type {:#x} is not allowed by the chain",
+                    sighash_ty.raw_sighash()
+                ),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sighash_with_fork_id() {
+        // BitcoinCash.
+        let bch = SighashType::with_fork_id(SighashBase::All, 0).unwrap();
+        assert_eq!(bch.raw_sighash(), 0x41);
+        assert_eq!(bch.serialize().unwrap(), 0x41);
+        assert_eq!(bch.fork_id_value(), Some(0));
+        assert_eq!(bch, SighashType::from_u32(0x41).unwrap());
+
+        // BitcoinGold.
+        let btg = SighashType::with_fork_id(SighashBase::All, 79).unwrap();
+        assert_eq!(btg.raw_sighash(), 0x4f41);
+        assert_eq!(btg.serialize().unwrap(), 0x41);
+        assert_eq!(btg.fork_id_value(), Some(79));
+        assert_eq!(btg, SighashType::from_u32(0x4f41).unwrap());
+
+        assert_eq!(SighashType::default().fork_id_value(), None);
+        SighashType::with_fork_id(SighashBase::All, 0x0100_0000).unwrap_err();
+    }
+
+    #[test]
+    fn test_sighash_from_u32_strict() {
+        #[rustfmt::skip]
+        let expected_accepted: Vec<u32> = vec![
+            0x00,
+            0x01, 0x02, 0x03,
+            0x41, 0x42, 0x43,
+            0x81, 0x82, 0x83,
+            0xc1, 0xc2, 0xc3,
+        ];
+
+        let mut accepted = Vec::new();
+        for u in 0..=u8::MAX as u32 {
+            match SighashType::from_u32(u) {
+                Ok(sighash) => {
+                    assert_eq!(sighash.raw_sighash(), u);
+                    accepted.push(u);
+                },
+                Err(e) => {
+                    assert_eq!(
+                        *e.error_type(),
+                        SigningErrorType::Error_invalid_sighash_type
+                    )
+                },
+            }
+
+            // Undefined bits are ignored, and the undefined base types are `SIGHASH_ALL`-like.
+            let lenient = SighashType::from_u32_lenient(u).unwrap();
+            assert_eq!(lenient.raw_sighash(), u);
+            let expected_base = match u & BASE_FLAG {
+                2 => SighashBase::None,
+                3 => SighashBase::Single,
+                _ => SighashBase::All,
+            };
+            assert_eq!(lenient.base_type(), expected_base);
+        }
+        assert_eq!(accepted, expected_accepted);
+
+        // Fork id payload bits are allowed with `SIGHASH_FORK_ID` only.
+        SighashType::from_u32(0x4f41).unwrap();
+        SighashType::from_u32(0x4f01).unwrap_err();
+        SighashType::from_u32_lenient(0x4f01).unwrap();
+        // But not without a base type.
+        SighashType::from_u32(0x4f40).unwrap_err();
+    }
+
+    #[test]
+    fn test_sighash_from_u32_lenient_undefined_base() {
+        // Bitcoin Core treats the undefined base types as `SIGHASH_ALL`,
+        // e.g. 0x04 commits to every input and output, but keeps the raw value in the preimage.
+        for raw in [0x04, 0x1f, 0x20, 0x84] {
+            let sighash = SighashType::from_u32_lenient(raw).unwrap();
+            assert_eq!(sighash.base_type(), SighashBase::All);
+            assert_eq!(sighash.raw_sighash(), raw);
+            assert_eq!(sighash.anyone_can_pay(), raw & ANYONE_CAN_PAY_FLAG != 0);
+            SighashType::from_u32(raw).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_sighash_default() {
+        let default = SighashType::from_u32(0).unwrap();
+        assert_eq!(default, SighashType::sighash_default());
+        assert!(default.is_sighash_default());
+        assert_eq!(default.base_type(), SighashBase::All);
+        assert!(!default.anyone_can_pay());
+        assert_eq!(default.serialize_as_taproot().unwrap(), 0x00);
+
+        let err = default.serialize().unwrap_err();
+        assert_eq!(
+            *err.error_type(),
+            SigningErrorType::Error_sighash_default_not_allowed
+        );
+
+        // `SIGHASH_ALL` is a different sighash type, and it keeps its explicit byte in Taproot.
+        let all = SighashType::default();
+        assert_ne!(all, default);
+        assert!(!all.is_sighash_default());
+        assert!(!all.is_default_taproot_sighash());
+        assert_eq!(all.serialize().unwrap(), 0x01);
+        assert_eq!(all.serialize_as_taproot().unwrap(), 0x01);
+    }
+
+    #[test]
+    fn test_sighash_default_for() {
+        let taproot = SighashType::default_for(SigningMethod::Taproot);
+        assert!(taproot.is_sighash_default());
+        assert_eq!(taproot.serialize_as_taproot().unwrap(), 0x00);
+
+        for signing_method in [SigningMethod::Legacy, SigningMethod::Segwit] {
+            let sighash = SighashType::default_for(signing_method);
+            assert_eq!(sighash, SighashType::default());
+            assert_eq!(sighash.serialize().unwrap(), 0x01);
+        }
+    }
+
+    #[test]
+    fn test_allowed_sighash_types() {
+        let zcash = AllowedSighashTypes::zcash();
+        zcash.check(SighashType::default()).unwrap();
+
+        for raw in [0x00, 0x02, 0x03, 0x81, 0x41] {
+            let sighash = SighashType::from_u32(raw).unwrap();
+            AllowedSighashTypes::AllDefined.check(sighash).unwrap();
+
+            let err = zcash.check(sighash).unwrap_err();
+            assert_eq!(
+                *err.error_type(),
+                SigningErrorType::Error_sighash_type_not_allowed
+            );
+        }
+    }
+}
@@ -2,9 +2,9 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::sighash::SighashType;
+use crate::sighash::{SighashType, DEFAULT_TAPROOT_SIGHASH_TYPE};
 use tw_coin_entry::error::prelude::*;
-use tw_hash::H512;
+use tw_hash::{H256, H512};
 use tw_keypair::ecdsa::der;
 use tw_keypair::{ecdsa, schnorr};
 use tw_memory::Data;
@@ -12,6 +12,9 @@ use tw_memory::Data;
 /// Use the max value of the u8 type to avoid potential serialization optimizations (like ASN.1 DER).
 const ESTIMATION_DEFAULT_BYTE: u8 = u8::MAX;
 
+/// Sighash types allowed in Taproot signatures besides `SIGHASH_DEFAULT`.
+const TAPROOT_SIGHASH_TYPES: [u8; 6] = [0x01, 0x02, 0x03, 0x81, 0x82, 0x83];
+
 /// A Bitcoin ECDSA signature with a sighash type, which must be serialzed
 /// occordingly in the scriptSig/Witness data to spend an output.
 pub struct BitcoinEcdsaSignature {
@@ -49,12 +52,21 @@ impl BitcoinEcdsaSignature {
     }
 }
 
+/// A Bitcoin Schnorr signature with a sighash type, which must be serialized
+/// accordingly in the Taproot witness to spend an output.
+///
+/// Unlike [`BitcoinEcdsaSignature`], the sighash type byte is omitted if it's `SIGHASH_DEFAULT` (0x00),
+/// i.e. the signature is either 64 or 65 bytes long.
+/// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message
 pub struct BitcoinSchnorrSignature {
     sig: H512,
     sighash_ty: u8,
 }
 
 impl BitcoinSchnorrSignature {
+    // The max size of the serialized signature including sighash type.
+    const SER_SIZE: usize = 65;
+
     pub fn new(
         sig: schnorr::Signature,
         sighash_ty: SighashType,
@@ -72,10 +84,47 @@ impl BitcoinSchnorrSignature {
         }
     }
 
+    /// Parses a 64 byte signature with the `SIGHASH_DEFAULT` type,
+    /// or a 65 byte signature followed by an explicit sighash type.
+    ///
+    /// As per BIP341, an explicit 0x00 sighash type byte is rejected.
+    pub fn from_bytes(bytes: &[u8]) -> SigningResult<BitcoinSchnorrSignature> {
+        let (sig, sighash_ty) = match bytes.len() {
+            H512::LEN => (bytes, DEFAULT_TAPROOT_SIGHASH_TYPE),
+            Self::SER_SIZE => {
+                let (sig, sighash_ty) = bytes.split_at(H512::LEN);
+                (sig, sighash_ty[0])
+            },
+            len => {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Schnorr signature must be 64 or 65 bytes, found {len}"
+                ))
+            },
+        };
+
+        if bytes.len() == Self::SER_SIZE && sighash_ty == DEFAULT_TAPROOT_SIGHASH_TYPE {
+            return SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                .context("SIGHASH_DEFAULT must be omitted in a 65 byte Schnorr signature");
+        }
+        if !TAPROOT_SIGHASH_TYPES.contains(&sighash_ty) {
+            return SigningError::err(SigningErrorType::Error_invalid_sighash_type)
+                .context(format!("Invalid Taproot sighash type: {sighash_ty:#x}"));
+        }
+
+        // Panicking implies a bug, the length is checked above.
+        let sig = H512::try_from(sig).expect("Expected exactly 64 bytes");
+        Ok(BitcoinSchnorrSignature { sig, sighash_ty })
+    }
+
+    pub fn sighash_type(&self) -> SigningResult<SighashType> {
+        SighashType::from_u32(self.sighash_ty as u32)
+    }
+
+    /// Serializes the signature, omitting the sighash type byte if it's `SIGHASH_DEFAULT`.
     pub fn serialize(&self) -> Vec<u8> {
-        let mut ser = Vec::with_capacity(65);
+        let mut ser = Vec::with_capacity(Self::SER_SIZE);
         ser.extend_from_slice(self.sig.as_slice());
-        if self.sighash_ty != 0 {
+        if self.sighash_ty != DEFAULT_TAPROOT_SIGHASH_TYPE {
             ser.push(self.sighash_ty)
         }
         ser
@@ -103,6 +152,131 @@ impl FromRawOrDerBytes for ecdsa::secp256k1::VerifySignature {
     }
 }
 
+/// Checks if the signature, including the trailing sighash type byte, is encoded as strict DER.
+/// https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+pub fn is_valid_signature_encoding(sig: &[u8]) -> bool {
+    // Format: 0x30 [total-length] 0x02 [R-length] [R] 0x02 [S-length] [S] [sighash]
+    if sig.len() < 9 || sig.len() > BitcoinEcdsaSignature::SER_SIZE {
+        return false;
+    }
+    if sig[0] != 0x30 || sig[1] as usize != sig.len() - 3 {
+        return false;
+    }
+
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return false;
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != sig.len() {
+        return false;
+    }
+
+    // R must be a non-empty, non-negative and minimally encoded integer.
+    if sig[2] != 0x02 || len_r == 0 || sig[4] & 0x80 != 0 {
+        return false;
+    }
+    if len_r > 1 && sig[4] == 0x00 && sig[5] & 0x80 == 0 {
+        return false;
+    }
+
+    // Same for S.
+    if sig[len_r + 4] != 0x02 || len_s == 0 || sig[len_r + 6] & 0x80 != 0 {
+        return false;
+    }
+    if len_s > 1 && sig[len_r + 6] == 0x00 && sig[len_r + 7] & 0x80 == 0 {
+        return false;
+    }
+
+    true
+}
+
+/// Parses an ECDSA signature, excluding the sighash type byte, as leniently as Bitcoin Core's
+/// `ecdsa_signature_parse_der_lax` does, i.e. as the signatures were accepted before BIP66.
+/// Returns `(R, S)` or `None` if the signature cannot be parsed, or if `R` or `S` exceeds 32 bytes.
+///
+/// https://github.com/bitcoin/bitcoin/blob/v26.0/src/pubkey.cpp#L31-L169
+pub fn parse_der_lax(sig: &[u8]) -> Option<(H256, H256)> {
+    let mut pos = 0;
+
+    // Sequence tag byte.
+    if *sig.get(pos)? != 0x30 {
+        return None;
+    }
+    pos += 1;
+
+    // Sequence length bytes are ignored.
+    let len_byte = *sig.get(pos)?;
+    pos += 1;
+    if len_byte & 0x80 != 0 {
+        let len_size = (len_byte - 0x80) as usize;
+        if len_size > sig.len() - pos {
+            return None;
+        }
+        pos += len_size;
+    }
+
+    let r = read_lax_integer(sig, &mut pos)?;
+    let s = read_lax_integer(sig, &mut pos)?;
+    Some((lax_integer_to_h256(r)?, lax_integer_to_h256(s)?))
+}
+
+/// Reads an integer tag, its length and the value bytes, moving `pos` to the next element.
+fn read_lax_integer<'a>(sig: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    if *sig.get(*pos)? != 0x02 {
+        return None;
+    }
+    *pos += 1;
+
+    let len_byte = *sig.get(*pos)?;
+    *pos += 1;
+    let len = if len_byte & 0x80 != 0 {
+        let mut len_size = (len_byte - 0x80) as usize;
+        if len_size > sig.len() - *pos {
+            return None;
+        }
+        // Leading zeros of the length are skipped.
+        while len_size > 0 && sig[*pos] == 0 {
+            *pos += 1;
+            len_size -= 1;
+        }
+        if len_size >= std::mem::size_of::<usize>() {
+            return None;
+        }
+        let mut len = 0_usize;
+        for _ in 0..len_size {
+            len = (len << 8) + sig[*pos] as usize;
+            *pos += 1;
+        }
+        len
+    } else {
+        len_byte as usize
+    };
+
+    if len > sig.len() - *pos {
+        return None;
+    }
+    let value = &sig[*pos..*pos + len];
+    *pos += len;
+    Some(value)
+}
+
+fn lax_integer_to_h256(value: &[u8]) -> Option<H256> {
+    // Leading zeros are ignored.
+    let first_non_zero = value
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(value.len());
+    let value = &value[first_non_zero..];
+    if value.len() > H256::LEN {
+        return None;
+    }
+
+    let mut result = H256::default();
+    result[H256::LEN - value.len()..].copy_from_slice(value);
+    Some(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +332,125 @@ mod tests {
             "Expected an error"
         );
     }
+
+    #[test]
+    fn test_is_valid_signature_encoding() {
+        #[track_caller]
+        fn test_impl(sig: &str, expected: bool) {
+            let sig = sig.decode_hex().unwrap();
+            assert_eq!(is_valid_signature_encoding(&sig), expected);
+        }
+
+        // DER signature followed by the SIGHASH_ALL byte.
+        test_impl("304402201857bc6e6e48b46046a4bd204136fc77e24c240943fb5a1f0e86387aae59b34902200a7f31478784e51c49f46ef072745a4f263d7efdbc9c6784aa2571ff4f6f2a4001", true);
+        test_impl("3046022100db421231f23d0320dbb8f1284b600cd34b8e9218628139539ff4f1f6c05495da022100ff715aab70d5317dbf8ee224eb18bec3120cfb9db1000dbb31eadaf96c71c1b181", true);
+        // The sighash type byte is missing.
+        test_impl("304402201857bc6e6e48b46046a4bd204136fc77e24c240943fb5a1f0e86387aae59b34902200a7f31478784e51c49f46ef072745a4f263d7efdbc9c6784aa2571ff4f6f2a40", false);
+        // Negative R.
+        test_impl("304402209857bc6e6e48b46046a4bd204136fc77e24c240943fb5a1f0e86387aae59b34902200a7f31478784e51c49f46ef072745a4f263d7efdbc9c6784aa2571ff4f6f2a4001", false);
+        // R is padded with an excessive zero byte.
+        test_impl("30450221000857bc6e6e48b46046a4bd204136fc77e24c240943fb5a1f0e86387aae59b34902200a7f31478784e51c49f46ef072745a4f263d7efdbc9c6784aa2571ff4f6f2a4001", false);
+        // Raw ECDSA signature.
+        test_impl("db421231f23d0320dbb8f1284b600cd34b8e9218628139539ff4f1f6c05495daff715aab70d5317dbf8ee224eb18bec3120cfb9db1000dbb31eadaf96c71c1b101", false);
+    }
+
+    #[test]
+    fn test_parse_der_lax() {
+        let r = "4e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41";
+        let s = "181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09";
+
+        #[track_caller]
+        fn test_impl(sig: &str, expected: Option<(&str, &str)>) {
+            let actual = parse_der_lax(&sig.decode_hex().unwrap());
+            let h256 = |hex: &str| H256::try_from(hex.decode_hex().unwrap().as_slice()).unwrap();
+            let expected = expected.map(|(r, s)| (h256(r), h256(s)));
+            assert_eq!(actual, expected);
+        }
+
+        // Strict DER.
+        test_impl(&format!("30440220{r}0220{s}"), Some((r, s)));
+        // R is padded with an excessive zero byte, the sequence length is wrong.
+        test_impl(&format!("3050022100{r}0220{s}"), Some((r, s)));
+        // The lengths are encoded in the long form.
+        test_impl(&format!("30814402820020{r}028120{s}"), Some((r, s)));
+        // Short S is left-padded, negative R is read as unsigned.
+        test_impl(
+            &format!("30260220{}020201ff", "ff".repeat(32)),
+            Some((&"ff".repeat(32), &format!("{}01ff", "00".repeat(30)))),
+        );
+        // Trailing garbage is ignored.
+        test_impl(&format!("30440220{r}0220{s}aabb"), Some((r, s)));
+
+        // Not a sequence.
+        test_impl(&format!("31440220{r}0220{s}"), None);
+        // R exceeds 32 bytes.
+        test_impl(&format!("30450221ff{r}0220{s}"), None);
+        // S is truncated.
+        test_impl(&format!("30440220{r}0220{}", &s[2..]), None);
+        test_impl("", None);
+    }
+
+    #[test]
+    fn test_schnorr_signature_serialization() {
+        let sig_hex = "8720a46b5b3963790d94bcc61ad57ca02fd153584315bfa161ed3455e336ba624d68df010ed934b8792c5b6a57ba86c3da31d039f9612b44d1bf054132254de9";
+
+        #[track_caller]
+        fn test_impl(sig: &str, sighash_ty: u32, expected: &str) {
+            let sig = schnorr::Signature::try_from(sig.decode_hex().unwrap().as_slice()).unwrap();
+            let sighash_ty = SighashType::from_u32(sighash_ty).unwrap();
+            let ser = BitcoinSchnorrSignature::new(sig, sighash_ty)
+                .unwrap()
+                .serialize();
+            assert_eq!(ser, expected.decode_hex().unwrap());
+
+            // Serialization round-trip.
+            let parsed = BitcoinSchnorrSignature::from_bytes(&ser).unwrap();
+            assert_eq!(parsed.serialize(), ser);
+            assert_eq!(
+                parsed.sighash_type().unwrap().serialize_as_taproot(),
+                sighash_ty.serialize_as_taproot()
+            );
+        }
+
+        // SIGHASH_DEFAULT, the sighash byte is omitted.
+        test_impl(sig_hex, 0x00, sig_hex);
+        // An explicit SIGHASH_ALL keeps its sighash byte, so the signature is 65 bytes long.
+        test_impl(sig_hex, 0x01, &format!("{sig_hex}01"));
+        test_impl(sig_hex, 0x03, &format!("{sig_hex}03"));
+        test_impl(sig_hex, 0x82, &format!("{sig_hex}82"));
+    }
+
+    #[test]
+    fn test_schnorr_signature_from_bytes() {
+        let sig_hex = "8720a46b5b3963790d94bcc61ad57ca02fd153584315bfa161ed3455e336ba624d68df010ed934b8792c5b6a57ba86c3da31d039f9612b44d1bf054132254de9";
+
+        #[track_caller]
+        fn parse(hex: &str) -> SigningResult<BitcoinSchnorrSignature> {
+            BitcoinSchnorrSignature::from_bytes(&hex.decode_hex().unwrap())
+        }
+
+        // 64 bytes, SIGHASH_DEFAULT.
+        let sig = parse(sig_hex).unwrap();
+        assert_eq!(sig.sighash_type().unwrap(), SighashType::sighash_default());
+        assert_eq!(sig.serialize().len(), 64);
+
+        // 65 bytes with an explicit SIGHASH_ALL is kept as is.
+        let sig = parse(&format!("{sig_hex}01")).unwrap();
+        assert_eq!(sig.sighash_type().unwrap(), SighashType::default());
+        assert_eq!(sig.serialize().len(), 65);
+
+        // 65 bytes with SIGHASH_DEFAULT must be rejected.
+        let err = parse(&format!("{sig_hex}00")).err().unwrap();
+        assert_eq!(
+            *err.error_type(),
+            SigningErrorType::Error_invalid_sighash_type
+        );
+
+        // Undefined Taproot sighash types.
+        parse(&format!("{sig_hex}04")).err().unwrap();
+        parse(&format!("{sig_hex}41")).err().unwrap();
+        // Invalid lengths.
+        parse(&sig_hex[2..]).err().unwrap();
+        parse(&format!("{sig_hex}0101")).err().unwrap();
+    }
 }
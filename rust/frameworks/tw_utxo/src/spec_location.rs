@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Locations of the input and output specs the builder, planner and validation errors are caused by.
+//!
+//! A location is attached to an error as a context, so a single log line pinpoints
+//! the offending spec of a large transaction, e.g.
+//! `output #3 (amount 1000, script 76a914aaaaaaaaaaaa.., address 1Ahq...)`.
+
+use crate::script::Script;
+use crate::transaction::transaction_interface::TxOutputInterface;
+use crate::transaction::transaction_parts::Amount;
+use crate::transaction::UtxoToSign;
+use std::fmt;
+use tw_encoding::hex;
+
+/// The number of the leading scriptPubkey bytes displayed.
+const SCRIPT_PREVIEW_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpecKind {
+    Input,
+    Output,
+    ChangeOutput,
+    MaxOutput,
+}
+
+impl fmt::Display for SpecKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            SpecKind::Input => "input",
+            SpecKind::Output => "output",
+            SpecKind::ChangeOutput => "change output",
+            SpecKind::MaxOutput => "max output",
+        };
+        f.write_str(kind)
+    }
+}
+
+/// Describes the offending input or output spec, see the module docs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpecLocation {
+    pub kind: SpecKind,
+    /// The index of the spec in the order the inputs or outputs are provided.
+    /// `None` for the specs provided separately, e.g. the change output.
+    pub index: Option<usize>,
+    pub amount: Option<Amount>,
+    pub script_pubkey: Option<Script>,
+    /// The address string if one was provided instead of the scriptPubkey.
+    pub address: Option<String>,
+}
+
+impl SpecLocation {
+    pub fn new(kind: SpecKind, index: Option<usize>) -> Self {
+        SpecLocation {
+            kind,
+            index,
+            amount: None,
+            script_pubkey: None,
+            address: None,
+        }
+    }
+
+    pub fn input(index: usize) -> Self {
+        SpecLocation::new(SpecKind::Input, Some(index))
+    }
+
+    pub fn output(index: usize) -> Self {
+        SpecLocation::new(SpecKind::Output, Some(index))
+    }
+
+    pub fn change_output() -> Self {
+        SpecLocation::new(SpecKind::ChangeOutput, None)
+    }
+
+    pub fn max_output() -> Self {
+        SpecLocation::new(SpecKind::MaxOutput, None)
+    }
+
+    /// Describes the UTXO by its amount and the scriptPubkey it spends.
+    pub fn of_utxo(index: usize, utxo: &UtxoToSign) -> Self {
+        SpecLocation::input(index)
+            .amount(utxo.amount)
+            .script_pubkey(utxo.prevout_script_pubkey.clone())
+    }
+
+    /// Describes the output by its amount and scriptPubkey.
+    pub fn of_output<Output: TxOutputInterface>(index: usize, output: &Output) -> Self {
+        SpecLocation::output(index)
+            .amount(output.value())
+            .script_pubkey(output.script_pubkey().clone())
+    }
+
+    pub fn amount(mut self, amount: Amount) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn script_pubkey(mut self, script_pubkey: Script) -> Self {
+        self.script_pubkey = Some(script_pubkey);
+        self
+    }
+
+    /// Sets the address string, ignored if empty.
+    pub fn address(mut self, address: &str) -> Self {
+        if !address.is_empty() {
+            self.address = Some(address.to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for SpecLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(index) = self.index {
+            write!(f, " #{index}")?;
+        }
+
+        let mut details = Vec::new();
+        if let Some(amount) = self.amount {
+            details.push(format!("amount {amount}"));
+        }
+        if let Some(ref script_pubkey) = self.script_pubkey {
+            let bytes = script_pubkey.as_slice();
+            let preview_len = bytes.len().min(SCRIPT_PREVIEW_LEN);
+            let ellipsis = if bytes.len() > preview_len { ".." } else { "" };
+            details.push(format!(
+                "script {}{ellipsis}",
+                hex::encode(&bytes[..preview_len], false)
+            ));
+        }
+        if let Some(ref address) = self.address {
+            details.push(format!("address {address}"));
+        }
+
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::DecodeHex;
+
+    #[test]
+    fn test_spec_location_display() {
+        let script = Script::from(
+            "76a914aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa88ac"
+                .decode_hex()
+                .unwrap(),
+        );
+        let location = SpecLocation::output(3)
+            .amount(1000)
+            .script_pubkey(script)
+            .address("1GSmKBCvr4Ecg7ZZsT7EXRWTCdtamrtaRV");
+        assert_eq!(
+            location.to_string(),
+            "output #3 (amount 1000, script 76a914aaaaaaaaaaaa.., address 1GSmKBCvr4Ecg7ZZsT7EXRWTCdtamrtaRV)"
+        );
+
+        let location = SpecLocation::change_output()
+            .script_pubkey(Script::from(vec![0x6a, 0x01, 0xff]))
+            .address("");
+        assert_eq!(location.to_string(), "change output (script 6a01ff)");
+
+        assert_eq!(SpecLocation::input(0).to_string(), "input #0");
+    }
+}
@@ -4,10 +4,12 @@
 
 use crate::script::{Script, Witness};
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature};
+use standard_constructor::StandardConstructor;
 use std::fmt;
 use std::rc::Rc;
 
 pub mod standard_constructor;
+pub mod tapscript_multisig;
 
 #[derive(Debug, Clone)]
 pub struct SpendingData {
@@ -17,10 +19,22 @@ pub struct SpendingData {
 
 pub trait EcdsaSpendingDataConstructor: fmt::Debug {
     fn get_spending_data(&self, sig: &BitcoinEcdsaSignature) -> SpendingData;
+
+    /// Returns the constructor as a [`StandardConstructor`] that can be serialized,
+    /// or `None` if it's a custom one.
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        None
+    }
 }
 
 pub trait SchnorrSpendingDataConstructor: fmt::Debug {
     fn get_spending_data(&self, sig: &BitcoinSchnorrSignature) -> SpendingData;
+
+    /// Returns the constructor as a [`StandardConstructor`] that can be serialized,
+    /// or `None` if it's a custom one.
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        None
+    }
 }
 
 /// A UTXO `SpendingData` constructor.
@@ -40,4 +54,13 @@ impl SpendingDataConstructor {
     pub fn schnorr<T: SchnorrSpendingDataConstructor + 'static>(constructor: T) -> Self {
         SpendingDataConstructor::Schnorr(Rc::new(constructor))
     }
+
+    /// Returns the constructor as a [`StandardConstructor`] that can be serialized,
+    /// or `None` if it's a custom one.
+    pub fn to_standard(&self) -> Option<StandardConstructor> {
+        match self {
+            SpendingDataConstructor::Ecdsa(constructor) => constructor.to_standard(),
+            SpendingDataConstructor::Schnorr(constructor) => constructor.to_standard(),
+        }
+    }
 }
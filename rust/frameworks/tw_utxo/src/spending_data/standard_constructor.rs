@@ -2,15 +2,132 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::encode::reader::Reader;
+use crate::encode::stream::Stream;
+use crate::encode::{Decodable, Encodable};
 use crate::script::standard_script::claims;
 use crate::script::{Script, Witness};
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature};
 use crate::spending_data::{
     EcdsaSpendingDataConstructor, SchnorrSpendingDataConstructor, SpendingData,
+    SpendingDataConstructor,
 };
+use tw_coin_entry::error::prelude::*;
 use tw_hash::H264;
+use tw_keypair::schnorr;
 use tw_memory::Data;
 
+const P2PKH_TAG: u8 = 0;
+const P2PK_TAG: u8 = 1;
+const P2WPKH_TAG: u8 = 2;
+const P2WSH_CLTV_P2PKH_TAG: u8 = 3;
+const P2TR_KEY_PATH_TAG: u8 = 4;
+const P2TR_SCRIPT_PATH_TAG: u8 = 5;
+
+/// One of the standard spending data constructors.
+/// Unlike [`SpendingDataConstructor`], it can be serialized, e.g. to save a signing session.
+#[derive(Clone, Debug)]
+pub enum StandardConstructor {
+    P2PKH(P2PKH),
+    P2PK,
+    P2WPKH(P2WPKH),
+    P2WSHCltvP2PKH(P2WSHCltvP2PKH),
+    P2TRKeyPath,
+    P2TRScriptPath(P2TRScriptPath),
+}
+
+impl From<StandardConstructor> for SpendingDataConstructor {
+    fn from(constructor: StandardConstructor) -> Self {
+        match constructor {
+            StandardConstructor::P2PKH(p2pkh) => SpendingDataConstructor::ecdsa(p2pkh),
+            StandardConstructor::P2PK => SpendingDataConstructor::ecdsa(P2PK),
+            StandardConstructor::P2WPKH(p2wpkh) => SpendingDataConstructor::ecdsa(p2wpkh),
+            StandardConstructor::P2WSHCltvP2PKH(p2wsh) => SpendingDataConstructor::ecdsa(p2wsh),
+            StandardConstructor::P2TRKeyPath => SpendingDataConstructor::schnorr(P2TRKeyPath),
+            StandardConstructor::P2TRScriptPath(p2tr) => SpendingDataConstructor::schnorr(p2tr),
+        }
+    }
+}
+
+impl Encodable for StandardConstructor {
+    fn encode(&self, stream: &mut Stream) {
+        match self {
+            StandardConstructor::P2PKH(p2pkh) => stream.append(&P2PKH_TAG).append(&p2pkh.pubkey),
+            StandardConstructor::P2PK => stream.append(&P2PK_TAG),
+            StandardConstructor::P2WPKH(p2wpkh) => {
+                stream.append(&P2WPKH_TAG).append(&p2wpkh.pubkey)
+            },
+            StandardConstructor::P2WSHCltvP2PKH(p2wsh) => stream
+                .append(&P2WSH_CLTV_P2PKH_TAG)
+                .append(&p2wsh.pubkey)
+                .append(&p2wsh.witness_script),
+            StandardConstructor::P2TRKeyPath => stream.append(&P2TR_KEY_PATH_TAG),
+            StandardConstructor::P2TRScriptPath(p2tr) => stream
+                .append(&P2TR_SCRIPT_PATH_TAG)
+                .append(&p2tr.payload)
+                .append(&p2tr.control_block)
+                // The parity of the output key is kept in the first byte of its compressed form.
+                .append(&p2tr.output_key.public_key().compressed()),
+        };
+    }
+
+    fn encoded_size(&self) -> usize {
+        let fields_size = match self {
+            StandardConstructor::P2PKH(p2pkh) => p2pkh.pubkey.encoded_size(),
+            StandardConstructor::P2PK | StandardConstructor::P2TRKeyPath => 0,
+            StandardConstructor::P2WPKH(p2wpkh) => p2wpkh.pubkey.encoded_size(),
+            StandardConstructor::P2WSHCltvP2PKH(p2wsh) => {
+                p2wsh.pubkey.encoded_size() + p2wsh.witness_script.encoded_size()
+            },
+            StandardConstructor::P2TRScriptPath(p2tr) => {
+                p2tr.payload.encoded_size() + p2tr.control_block.encoded_size() + H264::len()
+            },
+        };
+        P2PKH_TAG.encoded_size() + fields_size
+    }
+}
+
+impl Decodable for StandardConstructor {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let constructor = match reader.read::<u8>()? {
+            P2PKH_TAG => StandardConstructor::P2PKH(P2PKH {
+                pubkey: reader.read()?,
+            }),
+            P2PK_TAG => StandardConstructor::P2PK,
+            P2WPKH_TAG => StandardConstructor::P2WPKH(P2WPKH {
+                pubkey: reader.read()?,
+            }),
+            P2WSH_CLTV_P2PKH_TAG => StandardConstructor::P2WSHCltvP2PKH(P2WSHCltvP2PKH {
+                pubkey: reader.read()?,
+                witness_script: reader.read()?,
+            }),
+            P2TR_KEY_PATH_TAG => StandardConstructor::P2TRKeyPath,
+            P2TR_SCRIPT_PATH_TAG => {
+                let payload = reader.read()?;
+                let control_block = reader.read()?;
+                let output_key: H264 = reader.read()?;
+                let public_key = schnorr::PublicKey::try_from(output_key.as_slice())
+                    .into_tw()
+                    .context("Invalid Taproot output key")?;
+
+                StandardConstructor::P2TRScriptPath(P2TRScriptPath {
+                    payload,
+                    control_block,
+                    output_key: schnorr::TweakedPublicKey {
+                        xonly: public_key.x_only(),
+                        parity: schnorr::Parity::from_lowest_bit(output_key.as_slice()[0]),
+                    },
+                })
+            },
+            tag => {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context(format!("Unknown spending data constructor: {tag}"))
+            },
+        };
+        Ok(constructor)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct P2PKH {
     pub pubkey: H264,
@@ -23,6 +140,10 @@ impl EcdsaSpendingDataConstructor for P2PKH {
             witness: Witness::default(),
         }
     }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2PKH(self.clone()))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +156,10 @@ impl EcdsaSpendingDataConstructor for P2PK {
             witness: Witness::default(),
         }
     }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2PK)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +174,29 @@ impl EcdsaSpendingDataConstructor for P2WPKH {
             witness: claims::new_p2wpkh(sig, self.pubkey),
         }
     }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2WPKH(self.clone()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct P2WSHCltvP2PKH {
+    pub pubkey: H264,
+    pub witness_script: Script,
+}
+
+impl EcdsaSpendingDataConstructor for P2WSHCltvP2PKH {
+    fn get_spending_data(&self, sig: &BitcoinEcdsaSignature) -> SpendingData {
+        SpendingData {
+            script_sig: Script::default(),
+            witness: claims::new_p2wsh_cltv_p2pkh(sig, self.pubkey, self.witness_script.clone()),
+        }
+    }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2WSHCltvP2PKH(self.clone()))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -61,12 +209,19 @@ impl SchnorrSpendingDataConstructor for P2TRKeyPath {
             witness: claims::new_p2tr_key_path(sig.serialize()),
         }
     }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2TRKeyPath)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct P2TRScriptPath {
     pub payload: Script,
     pub control_block: Data,
+    /// The output key committed to the spent scriptPubkey.
+    /// Its parity is encoded in the first byte of the `control_block`.
+    pub output_key: schnorr::TweakedPublicKey,
 }
 
 impl SchnorrSpendingDataConstructor for P2TRScriptPath {
@@ -80,4 +235,8 @@ impl SchnorrSpendingDataConstructor for P2TRScriptPath {
             ),
         }
     }
+
+    fn to_standard(&self) -> Option<StandardConstructor> {
+        Some(StandardConstructor::P2TRScriptPath(self.clone()))
+    }
 }
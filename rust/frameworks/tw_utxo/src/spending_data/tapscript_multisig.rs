@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::script::standard_script::{claims, conditions};
+use crate::script::Script;
+use crate::signature::BitcoinSchnorrSignature;
+use crate::spending_data::SpendingData;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+use tw_memory::Data;
+
+/// Collects partial signatures of a Tapscript `k-of-n` multisig script-path spend.
+/// See [`conditions::new_tapscript_multisig`].
+///
+/// Each co-signer signs the same script-path sighash with their non-tweaked private key.
+pub struct TapscriptMultisigSignatures {
+    threshold: usize,
+    pubkeys: Vec<H256>,
+    payload: Script,
+    control_block: Data,
+    /// Signatures in the same order as `pubkeys`.
+    sigs: Vec<Option<BitcoinSchnorrSignature>>,
+}
+
+impl TapscriptMultisigSignatures {
+    pub fn new(threshold: usize, pubkeys: Vec<H256>, control_block: Data) -> SigningResult<Self> {
+        let payload = conditions::new_tapscript_multisig(threshold, &pubkeys)?;
+        let sigs = pubkeys.iter().map(|_| None).collect();
+        Ok(TapscriptMultisigSignatures {
+            threshold,
+            pubkeys,
+            payload,
+            control_block,
+            sigs,
+        })
+    }
+
+    /// Returns the leaf script that must be revealed to spend the output.
+    pub fn payload(&self) -> &Script {
+        &self.payload
+    }
+
+    /// Adds a signature of the given co-signer.
+    pub fn add_signature(
+        &mut self,
+        pubkey: &H256,
+        sig: BitcoinSchnorrSignature,
+    ) -> SigningResult<&mut Self> {
+        let key_index = self
+            .pubkeys
+            .iter()
+            .position(|key| key == pubkey)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("'{pubkey}' public key is not a part of the multisig"))?;
+
+        if self.sigs[key_index].is_some() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("'{pubkey}' public key has signed already"));
+        }
+
+        self.sigs[key_index] = Some(sig);
+        Ok(self)
+    }
+
+    /// Returns the number of collected signatures.
+    pub fn signatures_count(&self) -> usize {
+        self.sigs.iter().filter(|sig| sig.is_some()).count()
+    }
+
+    /// Whether enough signatures are collected to spend the output.
+    pub fn is_complete(&self) -> bool {
+        self.signatures_count() >= self.threshold
+    }
+
+    /// Constructs the witness with the collected signatures.
+    ///
+    /// # Important
+    ///
+    /// `OP_NUMEQUAL` requires exactly `threshold` valid signatures,
+    /// so only the first `threshold` signatures (in the public keys order) are used.
+    pub fn into_spending_data(self) -> SigningResult<SpendingData> {
+        if !self.is_complete() {
+            return SigningError::err(SigningErrorType::Error_signing).context(format!(
+                "Tapscript multisig requires {} signatures, but {} given",
+                self.threshold,
+                self.signatures_count()
+            ));
+        }
+
+        let mut remaining = self.threshold;
+        let sigs: Vec<_> = self
+            .sigs
+            .into_iter()
+            .map(|sig| match sig {
+                Some(sig) if remaining > 0 => {
+                    remaining -= 1;
+                    Some(sig)
+                },
+                _ => None,
+            })
+            .collect();
+
+        Ok(SpendingData {
+            script_sig: Script::default(),
+            witness: claims::new_tapscript_multisig(&sigs, self.payload, self.control_block),
+        })
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Test fixtures shared by the UTXO framework and the crates built on top of it.
+
+/// Previous transaction of the UTXOs spent in the transaction builder tests.
+pub const TXID: &str = "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911";
+/// Private key owning the UTXOs spent in the transaction builder tests.
+pub const PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+
+/// BIP370 test vector: a v2 PSBT of 1 input and 2 outputs with the required fields only.
+/// https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki#test-vectors
+pub const PSBT_V2: &str = "70736274ff01020402000000010401010105010201fb040200000000010e200b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc8010f04000000000001030808af0800000000000104160014c430f64c4756da310dbd1a085572ef299926272c000103088bbdeb0b0000000001041600144dd193ac964a56ac1b9e1cca8454fe2f474f851300";
+/// [`PSBT_V2`] converted to v0, i.e. with the unsigned transaction.
+pub const PSBT_V0: &str = "70736274ff01007102000000010b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc80000000000ffffffff0208af080000000000160014c430f64c4756da310dbd1a085572ef299926272c8bbdeb0b000000001600144dd193ac964a56ac1b9e1cca8454fe2f474f85130000000000000000";
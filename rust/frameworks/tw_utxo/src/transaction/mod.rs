@@ -2,12 +2,14 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::chain_params::ChainTag;
+use crate::script::script_code::ScriptCode;
 use crate::script::Script;
-use crate::sighash::SighashType;
+use crate::sighash::{AllowedSighashTypes, SighashType};
 use crate::signing_mode::SigningMethod;
 use crate::spending_data::SpendingDataConstructor;
-use crate::transaction::transaction_parts::Amount;
-use tw_coin_entry::error::prelude::SigningResult;
+use crate::transaction::transaction_parts::{Amount, LockTime};
+use tw_coin_entry::error::prelude::*;
 use tw_hash::hasher::Hasher;
 use tw_hash::H256;
 use tw_memory::Data;
@@ -31,8 +33,8 @@ pub trait TransactionPreimage {
 /// It provides an index of the UTXO to be signed and other required options.
 pub struct UtxoPreimageArgs {
     pub input_index: usize,
-    /// Script for claiming [`UtxoPreimageArgs::input_index`] UTXO.
-    pub script_pubkey: Script,
+    /// Script code for claiming [`UtxoPreimageArgs::input_index`] UTXO.
+    pub script_code: ScriptCode,
     pub amount: Amount,
     pub sighash_ty: SighashType,
     pub leaf_hash_code_separator: Option<(H256, u32)>,
@@ -51,11 +53,11 @@ pub struct UtxoTaprootPreimageArgs {
 #[derive(Clone)]
 pub struct UtxoToSign {
     /// Original `scriptPubkey` specified in the unspent transaction output.
-    /// May be the same or different from [`UtxoToSign::script_pubkey`].
+    /// May be the same or different from [`UtxoToSign::script_code`].
     pub prevout_script_pubkey: Script,
-    /// `scriptPubkey` with which the UTXO needs to be signed.
-    /// For example, if [`UtxoToSign::original_script_pubkey`] is P2WPKH, then [`UtxoToSign::script_pubkey`] will be P2PKH.
-    pub script_pubkey: Script,
+    /// Script code with which the UTXO needs to be signed.
+    /// For example, if [`UtxoToSign::prevout_script_pubkey`] is P2WPKH, then [`UtxoToSign::script_code`] will be P2PKH.
+    pub script_code: ScriptCode,
     pub signing_method: SigningMethod,
     pub spending_data_constructor: SpendingDataConstructor,
     pub spender_public_key: Data,
@@ -63,6 +65,79 @@ pub struct UtxoToSign {
     /// Taproot UTXO specific argument.
     /// TODO add `TaprootUtxoSignArgs`.
     pub leaf_hash_code_separator: Option<(H256, u32)>,
+    /// The merkle root of the script tree the P2TR output key commits to, if any.
+    /// The private key signing via the key path is tweaked with it.
+    pub taproot_merkle_root: Option<H256>,
     pub tx_hasher: Hasher,
     pub sighash_ty: SighashType,
+    /// Whether the input sequence has been set explicitly.
+    /// Otherwise, the sequence is chosen by the RBF policy of the transaction builder.
+    pub explicit_sequence: bool,
+    /// Whether a zero [`UtxoToSign::amount`] is allowed for Segwit and Taproot inputs.
+    pub allow_zero_amount: bool,
+    /// The lock time the transaction `nLockTime` must satisfy to spend the UTXO,
+    /// e.g. required by `OP_CHECKLOCKTIMEVERIFY`.
+    pub required_lock_time: Option<LockTime>,
+    /// The chain the UTXO is signed for.
+    /// The UTXO cannot be signed by a signer configured for another chain.
+    pub chain: ChainTag,
+    /// The number of confirmations of the UTXO, zero if it's unconfirmed.
+    /// `None` if unknown, then the UTXO is considered unconfirmed by the selection policies.
+    pub confirmations: Option<u32>,
+    /// Whether the UTXO is a change output of the wallet's own transaction,
+    /// so it can be spent unconfirmed with [`crate::modules::utxo_selector::SelectionPolicy::AllowOwnUnconfirmedChange`].
+    pub is_change_from_self: bool,
+}
+
+impl UtxoToSign {
+    /// Checks if the amount is set, see [`check_input_amount`].
+    pub fn check_amount(&self) -> SigningResult<()> {
+        check_input_amount(self.amount, self.signing_method, self.allow_zero_amount)
+    }
+
+    /// Checks if the UTXO is signed for the chain the signer is configured for.
+    pub fn check_chain(&self, signer_chain: ChainTag) -> SigningResult<()> {
+        if self.chain != signer_chain {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "The UTXO is built for {}, but the signer is configured for {signer_chain}",
+                self.chain
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks if the UTXO is signed with a sighash type the chain accepts,
+    /// see [`crate::chain_params::ChainParams::allowed_sighash_types`].
+    pub fn check_sighash_type(&self, allowed: &AllowedSighashTypes) -> SigningResult<()> {
+        allowed.check(self.sighash_ty)
+    }
+
+    /// Checks if the UTXO can be spent on the chain, see [`crate::chain_params::ChainParams::segwit_enabled`].
+    pub fn check_segwit(&self, segwit_enabled: bool) -> SigningResult<()> {
+        if !segwit_enabled && self.signing_method != SigningMethod::Legacy {
+            return SigningError::err(SigningErrorType::Error_segwit_not_supported).context(
+                format!(
+                    "{:?} input cannot be spent on a chain without segwit",
+                    self.signing_method
+                ),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Segwit (BIP143) and Taproot (BIP341) signatures commit to the input amount,
+/// so a zero amount, most likely left defaulted, produces a wrong sighash.
+/// The function returns an error in this case unless `allow_zero_amount` is set.
+pub fn check_input_amount(
+    amount: Amount,
+    signing_method: SigningMethod,
+    allow_zero_amount: bool,
+) -> SigningResult<()> {
+    if amount == 0 && signing_method != SigningMethod::Legacy && !allow_zero_amount {
+        return SigningError::err(SigningErrorType::Error_missing_input_amount).context(format!(
+            "Zero amount of a {signing_method:?} input. The signature commits to the amount"
+        ));
+    }
+    Ok(())
 }
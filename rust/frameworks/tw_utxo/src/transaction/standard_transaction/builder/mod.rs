@@ -2,6 +2,7 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use super::outputs_template::OutputsTemplate;
 use super::{Transaction, TransactionInput, TransactionOutput};
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::*;
@@ -10,10 +11,21 @@ use tw_hash::H256;
 mod output;
 mod utxo;
 
+use crate::chain_params::ChainTag;
+use crate::constants::{
+    BITCOIN_MAX_MONEY, MAX_BIP125_RBF_SEQUENCE, SEQUENCE_ENABLE_LOCKTIME_NO_RBF, SEQUENCE_FINAL,
+};
+use crate::fee_policy::FeePolicy;
+use crate::replay_protection::ReplayProtection;
+use crate::script::standard_script::conditions::{self, ScriptPubkeyType};
+use crate::script::Script;
+use crate::sighash::AllowedSighashTypes;
+use crate::spec_location::SpecLocation;
+use crate::transaction::transaction_parts::{Amount, LockTime};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::UtxoToSign;
 pub use output::OutputBuilder;
-pub use utxo::UtxoBuilder;
+pub use utxo::{tapscript_merkle_root, UtxoBuilder};
 
 pub fn txid_from_str(txid: &str) -> SigningResult<H256> {
     H256::from_str(txid)
@@ -25,6 +37,26 @@ pub fn txid_from_str_and_rev(txid: &str) -> SigningResult<H256> {
     txid_from_str(txid).map(H256::rev)
 }
 
+/// Chooses the sequence of the inputs that didn't set it explicitly via [`UtxoBuilder::sequence`].
+///
+/// Precedence, from the highest:
+/// 1. An explicit input sequence, e.g. a relative timelock, is never overridden.
+///    Note that any sequence below `0xfffffffe` signals Replace-By-Fee, so does a relative timelock.
+/// 2. The sequence chosen by the policy.
+///    The policy keeps the transaction `nLockTime` enforced (e.g. anti-fee-sniping),
+///    i.e. [`RbfPolicy::Disable`] uses `0xfffffffe` instead of `0xffffffff` if the lock time is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RbfPolicy {
+    /// Signal Replace-By-Fee with `0xfffffffd` sequence, which doesn't enforce a relative timelock.
+    #[default]
+    SignalAll,
+    /// Don't signal Replace-By-Fee.
+    Disable,
+    /// Sequences of all the inputs in the order they are pushed.
+    /// The sequences of the inputs that set it explicitly are ignored.
+    Custom(Vec<u32>),
+}
+
 /// Transaction builder for standard Bitcoin transaction only.
 /// It parses `BitcoinV2::Proto::SigningInput` as the standard [`super::Transaction`].
 pub struct TransactionBuilder {
@@ -33,6 +65,31 @@ pub struct TransactionBuilder {
     outputs: Vec<TransactionOutput>,
     locktime: u32,
     utxo_args: Vec<UtxoToSign>,
+    /// On-chain scriptPubkey's of the UTXOs, if known. Same order as `inputs`.
+    utxo_script_pubkeys: Vec<Option<Script>>,
+    max_money: Amount,
+    rbf_policy: RbfPolicy,
+    /// The chain the transaction is signed for, every UTXO must be built for it.
+    chain: ChainTag,
+    /// If set, the outputs are checked against the chain dust threshold.
+    fee_policy: Option<FeePolicy>,
+    /// Whether the chain has activated segwit, i.e. the witness inputs can be spent.
+    segwit_enabled: bool,
+    /// The sighash types the chain accepts, every UTXO must be signed with one of them.
+    allowed_sighash_types: AllowedSighashTypes,
+    /// The fork id the chain signatures commit to, if the chain uses the fork-id sighash.
+    fork_id: Option<u32>,
+    /// The template the builder is created from, see [`TransactionBuilder::from_template`].
+    template: Option<FrozenTemplate>,
+    /// The protection against replaying the transaction on another chain after a split.
+    replay_protection: Option<ReplayProtection>,
+}
+
+/// The outputs of a template that the payer must not alter.
+struct FrozenTemplate {
+    outputs_count: usize,
+    lock_time: Option<u32>,
+    digest: H256,
 }
 
 impl TransactionBuilder {
@@ -43,9 +100,38 @@ impl TransactionBuilder {
             outputs: Vec::new(),
             locktime: 0,
             utxo_args: Vec::default(),
+            utxo_script_pubkeys: Vec::default(),
+            max_money: BITCOIN_MAX_MONEY,
+            rbf_policy: RbfPolicy::default(),
+            chain: ChainTag::default(),
+            fee_policy: None,
+            segwit_enabled: true,
+            allowed_sighash_types: AllowedSighashTypes::AllDefined,
+            fork_id: None,
+            template: None,
+            replay_protection: None,
         }
     }
 
+    /// Creates a builder that completes the outputs-only `template`, e.g. a payment request.
+    /// The template outputs go first, then the outputs pushed by the payer, e.g. change.
+    ///
+    /// The template outputs are frozen: [`TransactionBuilder::build`] fails if they or
+    /// the required lock time are altered.
+    pub fn from_template(template: OutputsTemplate) -> Self {
+        let mut builder = TransactionBuilder::new();
+        builder.template = Some(FrozenTemplate {
+            outputs_count: template.outputs.len(),
+            lock_time: template.lock_time,
+            digest: template.digest(),
+        });
+        builder.locktime = template.lock_time.unwrap_or_default();
+        builder.outputs = template.outputs;
+        builder
+    }
+
+    /// Sets the transaction version, 2 by default.
+    /// Version 3 opts in to the TRUC topology limits, see [`crate::truc::check_topology`].
     pub fn version(&mut self, version: u32) -> &mut Self {
         self.version = version;
         self
@@ -56,9 +142,82 @@ impl TransactionBuilder {
         self
     }
 
+    /// Sets the chain specific money bound, [`BITCOIN_MAX_MONEY`] by default.
+    /// Every UTXO and output amount, as well as their sums, must not exceed it.
+    pub fn max_money(&mut self, max_money: Amount) -> &mut Self {
+        self.max_money = max_money;
+        self
+    }
+
+    /// Sets the policy of the inputs sequence, [`RbfPolicy::SignalAll`] by default.
+    pub fn rbf(&mut self, rbf_policy: RbfPolicy) -> &mut Self {
+        self.rbf_policy = rbf_policy;
+        self
+    }
+
+    /// Sets the chain the transaction is signed for, [`ChainTag::Bitcoin`] by default.
+    /// [`TransactionBuilder::build`] fails if any UTXO is built for another chain.
+    pub fn chain(&mut self, chain: ChainTag) -> &mut Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Sets the dust and fee policy of the chain, see [`crate::chain_params::ChainParams::fee_policy`].
+    /// [`TransactionBuilder::build`] fails if any output, except for `OP_RETURN`, is dust.
+    pub fn fee_policy(&mut self, fee_policy: FeePolicy) -> &mut Self {
+        self.fee_policy = Some(fee_policy);
+        self
+    }
+
+    /// Sets whether the chain has activated segwit, see [`crate::chain_params::ChainParams::segwit_enabled`].
+    /// [`TransactionBuilder::build`] fails if any UTXO is claimed with a witness on a chain without segwit.
+    pub fn segwit_enabled(&mut self, segwit_enabled: bool) -> &mut Self {
+        self.segwit_enabled = segwit_enabled;
+        self
+    }
+
+    /// Sets the sighash types the chain accepts, see [`crate::chain_params::ChainParams::allowed_sighash_types`].
+    /// [`TransactionBuilder::build`] fails if any UTXO is signed with another sighash type.
+    pub fn allowed_sighash_types(&mut self, allowed: AllowedSighashTypes) -> &mut Self {
+        self.allowed_sighash_types = allowed;
+        self
+    }
+
+    /// Sets the fork id of the chain, see [`crate::chain_params::ChainParams::fork_id`].
+    /// [`ReplayProtection::ForkIdSighash`] requires it, `None` by default.
+    pub fn fork_id(&mut self, fork_id: Option<u32>) -> &mut Self {
+        self.fork_id = fork_id;
+        self
+    }
+
+    /// Sets the protection against replaying the transaction on another chain after a split.
+    /// [`TransactionBuilder::build`] fails if the protection cannot be applied,
+    /// e.g. [`ReplayProtection::ForkIdSighash`] on a chain without the fork-id sighash.
+    ///
+    /// [`ReplayProtection::ProtectionOutput`] is appended after all the other outputs.
+    pub fn replay_protection(&mut self, replay_protection: ReplayProtection) -> &mut Self {
+        self.replay_protection = Some(replay_protection);
+        self
+    }
+
     pub fn push_input(&mut self, input: TransactionInput, arg: UtxoToSign) -> &mut Self {
         self.inputs.push(input);
         self.utxo_args.push(arg);
+        self.utxo_script_pubkeys.push(None);
+        self
+    }
+
+    /// Pushes an input along with the scriptPubkey of the UTXO as it's seen on-chain.
+    /// The claiming script declared by `arg` is cross-checked against it on [`TransactionBuilder::build`].
+    pub fn push_input_with_script_pubkey(
+        &mut self,
+        input: TransactionInput,
+        arg: UtxoToSign,
+        utxo_script_pubkey: Script,
+    ) -> &mut Self {
+        self.inputs.push(input);
+        self.utxo_args.push(arg);
+        self.utxo_script_pubkeys.push(Some(utxo_script_pubkey));
         self
     }
 
@@ -67,15 +226,251 @@ impl TransactionBuilder {
         self
     }
 
-    pub fn build(self) -> SigningResult<UnsignedTransaction<Transaction>> {
+    pub fn build(mut self) -> SigningResult<UnsignedTransaction<Transaction>> {
+        self.check_template()?;
+        self.apply_required_lock_time()?;
+        self.apply_rbf_policy()?;
+        self.check_lock_time_sequences()?;
+
+        let utxos = self.utxo_args.iter().zip(self.utxo_script_pubkeys.iter());
+        for (input_index, (utxo, utxo_script_pubkey)) in utxos.enumerate() {
+            utxo.check_chain(self.chain)
+                .with_context(|| format!("Invalid UTXO #{input_index}"))
+                .with_context(|| SpecLocation::of_utxo(input_index, utxo))?;
+            utxo.check_segwit(self.segwit_enabled)
+                .with_context(|| format!("Invalid UTXO #{input_index}"))
+                .with_context(|| SpecLocation::of_utxo(input_index, utxo))?;
+            utxo.check_sighash_type(&self.allowed_sighash_types)
+                .with_context(|| format!("Invalid UTXO #{input_index}"))
+                .with_context(|| SpecLocation::of_utxo(input_index, utxo))?;
+            if let Some(detected) = utxo_script_pubkey {
+                check_claim_type(input_index, &utxo.prevout_script_pubkey, detected)
+                    .with_context(|| SpecLocation::of_utxo(input_index, utxo))?;
+            }
+        }
+        self.apply_replay_protection()?;
+
+        let utxo_amounts = self.utxo_args.iter().map(|utxo| utxo.amount);
+        check_money_range(
+            utxo_amounts,
+            self.max_money,
+            SigningErrorType::Error_invalid_utxo_amount,
+            |input_index| SpecLocation::of_utxo(input_index, &self.utxo_args[input_index]),
+        )
+        .context("UTXO amounts are out of the chain money range")?;
+
+        let output_amounts = self.outputs.iter().map(|output| output.value);
+        check_money_range(
+            output_amounts,
+            self.max_money,
+            SigningErrorType::Error_invalid_params,
+            |output_index| SpecLocation::of_output(output_index, &self.outputs[output_index]),
+        )
+        .context("Output amounts are out of the chain money range")?;
+        self.check_dust_outputs()?;
+
         let transaction = Transaction {
             version: self.version as i32,
             inputs: self.inputs,
             outputs: self.outputs,
             locktime: self.locktime,
         };
-        UnsignedTransaction::new(transaction, self.utxo_args)
+        let mut unsigned_tx = UnsignedTransaction::new(transaction, self.utxo_args)?;
+        unsigned_tx.set_replay_protection(self.replay_protection);
+        Ok(unsigned_tx)
     }
+
+    fn apply_replay_protection(&mut self) -> SigningResult<()> {
+        let Some(ref replay_protection) = self.replay_protection else {
+            return Ok(());
+        };
+
+        replay_protection
+            .check(self.fork_id, &self.utxo_args)
+            .with_context(|| format!("Cannot apply the {replay_protection} replay protection"))?;
+        if let ReplayProtection::ProtectionOutput(output) = replay_protection {
+            self.outputs.push(output.clone());
+        }
+        Ok(())
+    }
+
+    fn check_dust_outputs(&self) -> SigningResult<()> {
+        let Some(ref fee_policy) = self.fee_policy else {
+            return Ok(());
+        };
+
+        let dust_threshold = fee_policy.dust_threshold();
+        for (output_index, output) in self.outputs.iter().enumerate() {
+            if conditions::is_op_return(&output.script_pubkey) {
+                continue;
+            }
+            let value = output.value;
+            if value < dust_threshold {
+                return SigningError::err(SigningErrorType::Error_dust_amount_requested)
+                    .context(format!(
+                        "Output #{output_index} amount {value} is below dust {dust_threshold}"
+                    ))
+                    .context(SpecLocation::of_output(output_index, output));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_template(&self) -> SigningResult<()> {
+        let Some(ref frozen) = self.template else {
+            return Ok(());
+        };
+
+        if let Some(lock_time) = frozen.lock_time {
+            if self.locktime != lock_time {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "The template requires lock time {lock_time}, but {} is set",
+                    self.locktime
+                ));
+            }
+        }
+
+        let template = OutputsTemplate {
+            outputs: self.outputs[..frozen.outputs_count].to_vec(),
+            lock_time: frozen.lock_time,
+        };
+        if template.digest() != frozen.digest {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The template outputs are altered");
+        }
+        Ok(())
+    }
+
+    /// Sets `nLockTime` to the lock time required by the inputs unless it's set explicitly.
+    /// Then checks that it satisfies the lock time of every input.
+    fn apply_required_lock_time(&mut self) -> SigningResult<()> {
+        if self.locktime == 0 {
+            self.locktime = self
+                .utxo_args
+                .iter()
+                .filter_map(|utxo| utxo.required_lock_time)
+                .map(LockTime::to_consensus_u32)
+                .max()
+                .unwrap_or_default();
+        }
+
+        for (input_index, utxo) in self.utxo_args.iter().enumerate() {
+            let Some(required) = utxo.required_lock_time else {
+                continue;
+            };
+            if !required.is_satisfied_by(self.locktime) {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context(format!(
+                        "Input #{input_index} can't be spent until {required:?}, but the transaction lock time is {}",
+                        self.locktime
+                    ))
+                    .context(SpecLocation::of_utxo(input_index, utxo));
+            }
+        }
+        Ok(())
+    }
+
+    /// `OP_CHECKLOCKTIMEVERIFY` fails if the input sequence disables `nLockTime`.
+    fn check_lock_time_sequences(&self) -> SigningResult<()> {
+        let inputs = self.inputs.iter().zip(self.utxo_args.iter());
+        for (input_index, (input, utxo)) in inputs.enumerate() {
+            if utxo.required_lock_time.is_some() && input.sequence == SEQUENCE_FINAL {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context(format!(
+                        "Input #{input_index} requires a lock time, but its sequence disables it"
+                    ))
+                    .context(SpecLocation::of_utxo(input_index, utxo));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_rbf_policy(&mut self) -> SigningResult<()> {
+        if let RbfPolicy::Custom(ref sequences) = self.rbf_policy {
+            if sequences.len() != self.inputs.len() {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Custom RBF policy expects {} sequences, but {} given",
+                    self.inputs.len(),
+                    sequences.len()
+                ));
+            }
+        }
+
+        // Keep `nLockTime` enforced if it's set.
+        let no_rbf_sequence = if self.locktime == 0 {
+            SEQUENCE_FINAL
+        } else {
+            SEQUENCE_ENABLE_LOCKTIME_NO_RBF
+        };
+
+        let inputs = self.inputs.iter_mut().zip(self.utxo_args.iter());
+        for (input_index, (input, utxo)) in inputs.enumerate() {
+            if utxo.explicit_sequence {
+                continue;
+            }
+
+            input.sequence = match self.rbf_policy {
+                RbfPolicy::SignalAll => MAX_BIP125_RBF_SEQUENCE,
+                RbfPolicy::Disable => no_rbf_sequence,
+                RbfPolicy::Custom(ref sequences) => sequences[input_index],
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Checks if every amount and the sum of the amounts do not exceed `max_money`.
+/// The offending amount is located by its index with `locate`.
+fn check_money_range<I, F>(
+    amounts: I,
+    max_money: Amount,
+    error: SigningErrorType,
+    locate: F,
+) -> SigningResult<()>
+where
+    I: IntoIterator<Item = Amount>,
+    F: Fn(usize) -> SpecLocation,
+{
+    let mut total: Amount = 0;
+    for (index, amount) in amounts.into_iter().enumerate() {
+        if amount > max_money {
+            return SigningError::err(error)
+                .context(format!("Amount {amount} exceeds the max money {max_money}"))
+                .context(locate(index));
+        }
+
+        total = total
+            .checked_add(amount)
+            .filter(|total| *total <= max_money)
+            .or_tw_err(error)
+            .with_context(|| format!("Sum of amounts exceeds the max money {max_money}"))
+            .with_context(|| locate(index))?;
+    }
+    Ok(())
+}
+
+/// Checks if the claiming script declared by the caller matches the UTXO scriptPubkey.
+/// Otherwise, the input would be signed successfully but rejected on-chain,
+/// e.g. when a P2SH-wrapped P2WPKH UTXO is claimed as a native P2WPKH one.
+fn check_claim_type(input_index: usize, declared: &Script, detected: &Script) -> SigningResult<()> {
+    if declared == detected {
+        return Ok(());
+    }
+
+    let declared_ty = ScriptPubkeyType::classify(declared);
+    let detected_ty = ScriptPubkeyType::classify(detected);
+    if declared_ty != detected_ty {
+        return SigningError::err(SigningErrorType::Error_claim_type_mismatch).context(format!(
+            "Claim type mismatch: declared={}, detected={}, input={input_index}",
+            declared_ty.as_str(),
+            detected_ty.as_str()
+        ));
+    }
+
+    SigningError::err(SigningErrorType::Error_invalid_utxo).context(format!(
+        "Input #{input_index} claiming script does not match the UTXO {} scriptPubkey",
+        detected_ty.as_str()
+    ))
 }
 
 impl Default for TransactionBuilder {
@@ -1,13 +1,17 @@
 use super::TransactionOutput;
 use crate::{
-    script::{standard_script::conditions, Script},
+    constants::MAX_SCRIPT_SIZE,
+    script::{
+        standard_script::{conditions, templates},
+        Script,
+    },
     transaction::{
         asset::brc20::{BRC20TransferInscription, Brc20Ticker},
         transaction_parts::Amount,
     },
 };
 use tw_coin_entry::error::prelude::*;
-use tw_hash::{ripemd::bitcoin_hash_160, sha2::sha256, H160, H256};
+use tw_hash::{ripemd::bitcoin_hash_160, H160, H256};
 use tw_keypair::{ecdsa, schnorr};
 
 pub const OP_RETURN_DATA_LIMIT: usize = 80;
@@ -21,11 +25,22 @@ impl OutputBuilder {
         OutputBuilder { amount }
     }
 
-    pub fn custom_script_pubkey(&self, script_pubkey: Script) -> TransactionOutput {
-        TransactionOutput {
+    /// Creates an output with the given raw scriptPubkey.
+    /// The script is validated against the size limit only,
+    /// use [`TxSummary`] to find out if the output is non-standard.
+    ///
+    /// [`TxSummary`]: crate::modules::tx_summary::TxSummary
+    pub fn custom_script_pubkey(&self, script_pubkey: Script) -> SigningResult<TransactionOutput> {
+        if script_pubkey.len() > MAX_SCRIPT_SIZE {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Custom scriptPubkey is too large: {} > {MAX_SCRIPT_SIZE} bytes",
+                script_pubkey.len()
+            ));
+        }
+        Ok(TransactionOutput {
             value: self.amount,
             script_pubkey,
-        }
+        })
     }
 
     pub fn p2sh(self, redeem_script: &[u8]) -> TransactionOutput {
@@ -41,7 +56,7 @@ impl OutputBuilder {
     pub fn p2sh_from_hash(self, redeem_hash: &H160) -> TransactionOutput {
         TransactionOutput {
             value: self.amount,
-            script_pubkey: conditions::new_p2sh(redeem_hash),
+            script_pubkey: Script::p2sh_from_hash(*redeem_hash),
         }
     }
 
@@ -53,27 +68,21 @@ impl OutputBuilder {
     }
 
     pub fn p2pkh(self, pubkey: &ecdsa::secp256k1::PublicKey) -> TransactionOutput {
-        let h = bitcoin_hash_160(pubkey.compressed().as_slice());
-        let pubkey_hash: H160 = h
-            .as_slice()
-            .try_into()
-            .expect("hash length must be 20 bytes");
-
-        self.p2pkh_from_hash(&pubkey_hash)
+        self.p2pkh_from_hash(&templates::pubkey_hash(pubkey))
     }
 
     pub fn p2pkh_from_hash(self, pubkey_hash: &H160) -> TransactionOutput {
         TransactionOutput {
             value: self.amount,
-            script_pubkey: conditions::new_p2pkh(pubkey_hash),
+            script_pubkey: Script::p2pkh_from_hash(*pubkey_hash),
         }
     }
 
     pub fn p2wsh(self, redeem_script: &Script) -> TransactionOutput {
-        let h = sha256(redeem_script.as_slice());
-        let redeem_hash: H256 = h.as_slice().try_into().expect("hash length is 32 bytes");
-
-        self.p2wsh_from_hash(&redeem_hash)
+        TransactionOutput {
+            value: self.amount,
+            script_pubkey: Script::p2wsh_from_script(redeem_script),
+        }
     }
 
     pub fn p2wsh_from_hash(self, redeem_hash: &H256) -> TransactionOutput {
@@ -84,10 +93,7 @@ impl OutputBuilder {
     }
 
     pub fn p2wpkh(self, pubkey: &ecdsa::secp256k1::PublicKey) -> TransactionOutput {
-        let h = bitcoin_hash_160(pubkey.compressed().as_slice());
-        let pubkey_hash: H160 = h.as_slice().try_into().expect("hash length is 20 bytes");
-
-        self.p2wpkh_from_hash(&pubkey_hash)
+        self.p2wpkh_from_hash(&templates::pubkey_hash(pubkey))
     }
 
     pub fn p2wpkh_from_hash(self, pubkey_hash: &H160) -> TransactionOutput {
@@ -100,7 +106,7 @@ impl OutputBuilder {
     pub fn p2tr_key_path(self, pubkey: &schnorr::PublicKey) -> TransactionOutput {
         TransactionOutput {
             value: self.amount,
-            script_pubkey: conditions::new_p2tr_key_path(&pubkey.compressed()),
+            script_pubkey: conditions::new_p2tr_key_path(pubkey),
         }
     }
 
@@ -118,7 +124,7 @@ impl OutputBuilder {
     ) -> TransactionOutput {
         TransactionOutput {
             value: self.amount,
-            script_pubkey: conditions::new_p2tr_script_path(&pubkey.compressed(), &merkle_root),
+            script_pubkey: conditions::new_p2tr_script_path(pubkey, &merkle_root),
         }
     }
 
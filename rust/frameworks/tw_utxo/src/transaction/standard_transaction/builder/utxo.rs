@@ -1,16 +1,22 @@
 use super::TransactionInput;
+use crate::chain_params::ChainTag;
+use crate::constants::SEQUENCE_FINAL;
 use crate::sighash::SighashType;
 use crate::spending_data::{standard_constructor, SpendingDataConstructor};
-use crate::transaction::UtxoToSign;
+use crate::transaction::{check_input_amount, UtxoToSign};
 use crate::{
-    script::{standard_script::conditions, Script, Witness},
+    script::{
+        script_code::ScriptCode,
+        standard_script::{claims, conditions, templates},
+        Script, Witness,
+    },
     signing_mode::SigningMethod,
     transaction::asset::brc20::{BRC20TransferInscription, Brc20Ticker},
-    transaction::transaction_parts::{Amount, OutPoint},
+    transaction::transaction_parts::{Amount, LockTime, OutPoint},
 };
 use bitcoin::hashes::Hash;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::{hasher::Hasher, ripemd::bitcoin_hash_160, H160, H256};
+use tw_hash::{hasher::Hasher, H256};
 use tw_keypair::{ecdsa, schnorr};
 use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
@@ -23,6 +29,11 @@ pub struct UtxoBuilder {
     prev_index: Option<u32>,
     amount: Option<Amount>,
     sighash_ty: Option<SighashType>,
+    explicit_sequence: bool,
+    allow_zero_amount: bool,
+    chain: ChainTag,
+    confirmations: Option<u32>,
+    is_change_from_self: bool,
 }
 
 impl UtxoBuilder {
@@ -31,13 +42,19 @@ impl UtxoBuilder {
             input: TransactionInput {
                 previous_output: OutPoint::default(),
                 script_sig: Script::new(),
-                sequence: u32::MAX,
+                // Chosen by the RBF policy of the transaction builder if not set explicitly.
+                sequence: SEQUENCE_FINAL,
                 witness: Witness::new(),
             },
             prev_txid: None,
             prev_index: None,
             amount: None,
             sighash_ty: None,
+            explicit_sequence: false,
+            allow_zero_amount: false,
+            chain: ChainTag::default(),
+            confirmations: None,
+            is_change_from_self: false,
         }
     }
 
@@ -51,8 +68,11 @@ impl UtxoBuilder {
         self
     }
 
+    /// Sets the input sequence explicitly, e.g. to enable a relative timelock.
+    /// Explicit sequences are never overridden by [`super::RbfPolicy`].
     pub fn sequence(mut self, sequence: u32) -> Self {
         self.input.sequence = sequence;
+        self.explicit_sequence = true;
         self
     }
 
@@ -61,11 +81,38 @@ impl UtxoBuilder {
         self
     }
 
+    /// Allows a zero amount for Segwit and Taproot inputs, e.g. for protocols spending provably zero-value outputs.
+    /// Otherwise, a zero amount is considered missing, as the signature commits to it.
+    pub fn allow_zero_amount(mut self) -> Self {
+        self.allow_zero_amount = true;
+        self
+    }
+
     pub fn sighash_type(mut self, sighash_ty: SighashType) -> Self {
         self.sighash_ty = Some(sighash_ty);
         self
     }
 
+    /// Sets the chain the UTXO is signed for, [`ChainTag::Bitcoin`] by default.
+    pub fn chain(mut self, chain: ChainTag) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Sets the number of confirmations of the UTXO, zero if it's unconfirmed.
+    /// Used by the [`crate::modules::utxo_selector::SelectionPolicy`] only.
+    pub fn confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = Some(confirmations);
+        self
+    }
+
+    /// Marks the UTXO as a change output of the wallet's own transaction.
+    /// Used by the [`crate::modules::utxo_selector::SelectionPolicy`] only.
+    pub fn change_from_self(mut self) -> Self {
+        self.is_change_from_self = true;
+        self
+    }
+
     fn finalize_out_point(&mut self) -> SigningResult<()> {
         // Populate the input with the previous output.
         self.input.previous_output.hash = self
@@ -80,16 +127,33 @@ impl UtxoBuilder {
         Ok(())
     }
 
-    fn finalize_amount(&self) -> SigningResult<Amount> {
-        self.amount
-            .or_tw_err(SigningErrorType::Error_internal)
+    /// Must be called after [`UtxoBuilder::finalize_out_point`].
+    fn finalize_amount(&self, signing_method: SigningMethod) -> SigningResult<Amount> {
+        let prev_index = self.input.previous_output.index;
+        let context = || format!("Invalid amount of the input spending output #{prev_index}");
+
+        let amount = self
+            .amount
+            .or_tw_err(SigningErrorType::Error_missing_input_amount)
             .context("'UtxoBuilder::amount' is not set")
+            .with_context(context)?;
+
+        check_input_amount(amount, signing_method, self.allow_zero_amount).with_context(context)?;
+        Ok(amount)
     }
 
-    fn finalize_sighash_type(&self) -> SigningResult<SighashType> {
-        self.sighash_ty
-            .or_tw_err(SigningErrorType::Error_internal)
-            .context("'UtxoBuilder::sighash_ty' is not set")
+    fn finalize_sighash_type(&self, signing_method: SigningMethod) -> SigningResult<SighashType> {
+        let sighash_ty = self
+            .sighash_ty
+            .unwrap_or_else(|| SighashType::default_for(signing_method));
+
+        if sighash_ty.is_sighash_default() && signing_method != SigningMethod::Taproot {
+            return SigningError::err(SigningErrorType::Error_sighash_default_not_allowed)
+                .context(format!(
+                    "SIGHASH_DEFAULT is allowed for Taproot inputs only, but {signing_method:?} input given"
+                ));
+        }
+        Ok(sighash_ty)
     }
 
     // TODO next iteration.
@@ -105,7 +169,7 @@ impl UtxoBuilder {
     //     redeem_hash: H160,
     // ) -> SigningResult<(TransactionInput, UtxoToSign)> {
     //     self.finalize_out_point()?;
-    //     let amount = self.finalize_amount()?;
+    //     let amount = self.finalize_amount(SigningMethod::Legacy)?;
     //
     //     Ok((
     //         self.input,
@@ -124,8 +188,8 @@ impl UtxoBuilder {
         pubkey: &ecdsa::secp256k1::PublicKey,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
         self.finalize_out_point()?;
-        let amount = self.finalize_amount()?;
-        let sighash_ty = self.finalize_sighash_type()?;
+        let amount = self.finalize_amount(SigningMethod::Legacy)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Legacy)?;
 
         // The scriptPubkey for signing is the same as declared at the unspent output.
         let script_pubkey = conditions::new_p2pk(&pubkey.compressed());
@@ -134,7 +198,7 @@ impl UtxoBuilder {
             self.input,
             UtxoToSign {
                 prevout_script_pubkey: script_pubkey.clone(),
-                script_pubkey,
+                script_code: ScriptCode::for_p2pkh(script_pubkey)?,
                 // P2PK output can be spent by a legacy address only.
                 signing_method: SigningMethod::Legacy,
                 // When the sighash is signed, build a P2PK script_sig.
@@ -144,8 +208,15 @@ impl UtxoBuilder {
                 spender_public_key: pubkey.compressed().to_vec(),
                 amount,
                 leaf_hash_code_separator: None,
+                taproot_merkle_root: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: None,
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
             },
         ))
     }
@@ -154,21 +225,20 @@ impl UtxoBuilder {
         mut self,
         pubkey: &ecdsa::secp256k1::PublicKey,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let h = bitcoin_hash_160(pubkey.compressed().as_slice());
-        let pubkey_hash: H160 = h.as_slice().try_into().expect("hash length is 20 bytes");
+        let pubkey_hash = templates::pubkey_hash(pubkey);
 
         self.finalize_out_point()?;
-        let amount = self.finalize_amount()?;
-        let sighash_ty = self.finalize_sighash_type()?;
+        let amount = self.finalize_amount(SigningMethod::Legacy)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Legacy)?;
 
         // The scriptPubkey for signing is the same as declared at the unspent output.
-        let script_pubkey = conditions::new_p2pkh(&pubkey_hash);
+        let script_pubkey = Script::p2pkh_from_hash(pubkey_hash);
 
         Ok((
             self.input,
             UtxoToSign {
                 prevout_script_pubkey: script_pubkey.clone(),
-                script_pubkey,
+                script_code: ScriptCode::for_p2pkh(script_pubkey)?,
                 // P2PK output can be spent by a legacy address only.
                 signing_method: SigningMethod::Legacy,
                 // When the sighash is signed, build a P2PKH script_sig.
@@ -180,8 +250,15 @@ impl UtxoBuilder {
                 spender_public_key: pubkey.compressed().to_vec(),
                 amount,
                 leaf_hash_code_separator: None,
+                taproot_merkle_root: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: None,
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
             },
         ))
     }
@@ -192,8 +269,8 @@ impl UtxoBuilder {
     //     let redeem_hash: H256 = h.as_slice().try_into().expect("hash length is 32 bytes");
     //
     //     self.finalize_out_point()?;
-    //     let amount = self.finalize_amount()?;
-    //     let sighash_ty = self.finalize_sighash_type()?;
+    //     let amount = self.finalize_amount(SigningMethod::Segwit)?;
+    //     let sighash_ty = self.finalize_sighash_type(SigningMethod::Segwit)?;
     //
     //     Ok((
     //         self.input,
@@ -214,12 +291,11 @@ impl UtxoBuilder {
         mut self,
         pubkey: &ecdsa::secp256k1::PublicKey,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let h = bitcoin_hash_160(pubkey.compressed().as_slice());
-        let pubkey_hash: H160 = h.as_slice().try_into().expect("hash length is 20 bytes");
+        let pubkey_hash = templates::pubkey_hash(pubkey);
 
         self.finalize_out_point()?;
-        let amount = self.finalize_amount()?;
-        let sighash_ty = self.finalize_sighash_type()?;
+        let amount = self.finalize_amount(SigningMethod::Segwit)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Segwit)?;
 
         Ok((
             self.input,
@@ -228,8 +304,8 @@ impl UtxoBuilder {
                 prevout_script_pubkey: conditions::new_p2wpkh(&pubkey_hash),
                 // To spend a P2WPKH UTXO, we need to sign the transaction with a corresponding P2PKH UTXO.
                 // Then the result script_sig will be published as a witness.
-                // Generating special scriptPubkey for P2WPKH.
-                script_pubkey: conditions::new_p2pkh(&pubkey_hash),
+                // Generating special script code for P2WPKH.
+                script_code: ScriptCode::for_p2wpkh(pubkey_hash),
                 // When the sighash is signed, build a P2WPKH witness.
                 spending_data_constructor: SpendingDataConstructor::ecdsa(
                     standard_constructor::P2WPKH {
@@ -241,8 +317,60 @@ impl UtxoBuilder {
                 signing_method: SigningMethod::Segwit,
                 amount,
                 leaf_hash_code_separator: None,
+                taproot_merkle_root: None,
+                tx_hasher: DEFAULT_TX_HASHER,
+                sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: None,
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
+            },
+        ))
+    }
+
+    /// Spends a P2WSH output of the [`Script::cltv_p2pkh`] witness script.
+    ///
+    /// The transaction `nLockTime` is set to the `lock_time` by [`super::TransactionBuilder::build`]
+    /// unless it's set explicitly, and the input sequence must enable `nLockTime`.
+    pub fn p2wsh_cltv_p2pkh(
+        mut self,
+        lock_time: LockTime,
+        pubkey: &ecdsa::secp256k1::PublicKey,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        self.finalize_out_point()?;
+        let amount = self.finalize_amount(SigningMethod::Segwit)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Segwit)?;
+
+        let witness_script = Script::cltv_p2pkh(lock_time, pubkey);
+
+        Ok((
+            self.input,
+            UtxoToSign {
+                prevout_script_pubkey: Script::p2wsh_from_script(&witness_script),
+                // The witness script is signed (BIP143).
+                script_code: ScriptCode::for_p2wsh(&witness_script),
+                // When the sighash is signed, build a witness with the witness script.
+                spending_data_constructor: SpendingDataConstructor::ecdsa(
+                    standard_constructor::P2WSHCltvP2PKH {
+                        pubkey: pubkey.compressed(),
+                        witness_script,
+                    },
+                ),
+                spender_public_key: pubkey.compressed().to_vec(),
+                signing_method: SigningMethod::Segwit,
+                amount,
+                leaf_hash_code_separator: None,
+                taproot_merkle_root: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: Some(lock_time),
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
             },
         ))
     }
@@ -252,25 +380,44 @@ impl UtxoBuilder {
         pubkey: &schnorr::PublicKey,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
         let tweaked_pubkey = pubkey.tweak(None);
-        self.p2tr_key_path_with_tweaked_pubkey(&tweaked_pubkey.x_only())
+        self.p2tr_key_path_with_tweaked_pubkey(&tweaked_pubkey.xonly)
     }
 
     pub fn p2tr_key_path_with_tweaked_pubkey(
+        self,
+        tweaked_pubkey: &schnorr::XOnlyPublicKey,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        self.p2tr_key_path_with_output_key(tweaked_pubkey, None)
+    }
+
+    /// Spends a P2TR output that commits to a script tree via the key path.
+    /// The output key is the `internal_pubkey` tweaked with the `merkle_root` of the tree.
+    pub fn p2tr_key_path_with_merkle_root(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        merkle_root: &H256,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let output_key = internal_pubkey.tweak(Some(*merkle_root));
+        self.p2tr_key_path_with_output_key(&output_key.xonly, Some(*merkle_root))
+    }
+
+    fn p2tr_key_path_with_output_key(
         mut self,
         tweaked_pubkey: &schnorr::XOnlyPublicKey,
+        merkle_root: Option<H256>,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
         self.finalize_out_point()?;
-        let amount = self.finalize_amount()?;
-        let sighash_ty = self.finalize_sighash_type()?;
+        let amount = self.finalize_amount(SigningMethod::Taproot)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Taproot)?;
 
         // The scriptPubkey for signing is the same as declared at the unspent output.
-        let script_pubkey = conditions::new_p2tr_dangerous_assume_tweaked(&tweaked_pubkey.bytes());
+        let script_pubkey = Script::p2tr_from_output_key(tweaked_pubkey);
 
         Ok((
             self.input,
             UtxoToSign {
                 prevout_script_pubkey: script_pubkey.clone(),
-                script_pubkey,
+                script_code: ScriptCode::for_p2tr_key_path(script_pubkey)?,
                 // P2TR output can be spent by a Witness (eg "bc1") address only.
                 signing_method: SigningMethod::Taproot,
                 // When the sighash is signed, build a P2TR witness.
@@ -280,58 +427,111 @@ impl UtxoBuilder {
                 spender_public_key: tweaked_pubkey.bytes().to_vec(),
                 amount,
                 leaf_hash_code_separator: None,
+                taproot_merkle_root: merkle_root,
                 // Note that we don't use the default double-hasher.
                 tx_hasher: Hasher::Sha256,
                 sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: None,
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
             },
         ))
     }
 
+    /// Spends a P2TR output via the script path with a `control_block` provided by the caller.
+    /// The control block is checked against the output key tweaked with the `merkle_root`.
     pub fn p2tr_script_path(
-        mut self,
+        self,
         internal_pubkey: &schnorr::PublicKey,
         payload: Script,
         control_block: Data,
         merkle_root: &H256,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        // Construct the leaf hash.
-        let script_buf = bitcoin::ScriptBuf::from_bytes(payload.to_vec());
-        let leaf_hash = bitcoin::taproot::TapLeafHash::from_script(
-            &script_buf,
-            bitcoin::taproot::LeafVersion::TapScript,
+        let output_key = internal_pubkey.tweak(Some(*merkle_root));
+        check_control_block(&control_block, internal_pubkey, &output_key)?;
+
+        self.p2tr_script_path_with_output_key(internal_pubkey, payload, control_block, output_key)
+    }
+
+    /// Spends a P2TR output via the script path, where the control block is built from
+    /// the `payload` leaf and its `merkle_branch` (the sibling hashes from the leaf to the root).
+    pub fn p2tr_script_path_with_merkle_branch(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        payload: Script,
+        merkle_branch: &[H256],
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        self.p2tr_script_path_signed_by(internal_pubkey, internal_pubkey, payload, merkle_branch)
+    }
+
+    /// Spends a P2TR output via the script path like [`UtxoBuilder::p2tr_script_path_with_merkle_branch`],
+    /// where the `payload` leaf is signed by the `signer` key, e.g. `<signer> OP_CHECKSIG`.
+    pub fn p2tr_script_path_signed_by(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        signer: &schnorr::PublicKey,
+        payload: Script,
+        merkle_branch: &[H256],
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let merkle_root = tapscript_merkle_root(&payload, merkle_branch);
+        let output_key = internal_pubkey.tweak(Some(merkle_root));
+        let control_block = claims::new_tapscript_control_block(
+            &internal_pubkey.x_only(),
+            &output_key,
+            merkle_branch,
         );
 
+        self.p2tr_script_path_with_output_key(signer, payload, control_block, output_key)
+    }
+
+    fn p2tr_script_path_with_output_key(
+        mut self,
+        signer: &schnorr::PublicKey,
+        payload: Script,
+        control_block: Data,
+        output_key: schnorr::TweakedPublicKey,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
         // Convert to native.
-        let leaf_hash: H256 = H256::from(leaf_hash.to_byte_array());
+        let leaf_hash = H256::from(tapscript_leaf_hash(&payload).to_byte_array());
 
         self.finalize_out_point()?;
-        let amount = self.finalize_amount()?;
-        let sighash_ty = self.finalize_sighash_type()?;
+        let amount = self.finalize_amount(SigningMethod::Taproot)?;
+        let sighash_ty = self.finalize_sighash_type(SigningMethod::Taproot)?;
 
         // Restore the original scriptPubkey declared at the unspent P2TR output.
-        let prevout_script_pubkey =
-            conditions::new_p2tr_script_path(&internal_pubkey.compressed(), merkle_root);
+        let prevout_script_pubkey = Script::p2tr_from_output_key(&output_key.xonly);
 
         Ok((
             self.input,
             UtxoToSign {
                 prevout_script_pubkey,
-                // We use the full (revealed) script as scriptPubkey here.
-                script_pubkey: payload.clone(),
+                // We use the full (revealed) script as script code here.
+                script_code: ScriptCode::for_tapscript(payload.clone())?,
                 signing_method: SigningMethod::Taproot,
                 spending_data_constructor: SpendingDataConstructor::schnorr(
                     standard_constructor::P2TRScriptPath {
                         payload,
                         control_block,
+                        output_key,
                     },
                 ),
                 // Taproot ScriptPath input should be signed with a non-tweaked private key.
-                spender_public_key: internal_pubkey.compressed().to_vec(),
+                spender_public_key: signer.compressed().to_vec(),
                 amount,
                 leaf_hash_code_separator: Some((leaf_hash, u32::MAX)),
+                taproot_merkle_root: None,
                 // Note that we don't use the default double-hasher.
                 tx_hasher: Hasher::Sha256,
                 sighash_ty,
+                explicit_sequence: self.explicit_sequence,
+                allow_zero_amount: self.allow_zero_amount,
+                required_lock_time: None,
+                chain: self.chain,
+                confirmations: self.confirmations,
+                is_change_from_self: self.is_change_from_self,
             },
         ))
     }
@@ -367,6 +567,55 @@ impl UtxoBuilder {
     }
 }
 
+/// Computes the merkle root of the script tree from the `payload` leaf and its `merkle_branch`
+/// (the sibling hashes from the leaf to the root).
+pub fn tapscript_merkle_root(payload: &Script, merkle_branch: &[H256]) -> H256 {
+    let leaf_hash = tapscript_leaf_hash(payload);
+    let merkle_root = merkle_branch.iter().fold(
+        bitcoin::taproot::TapNodeHash::from(leaf_hash),
+        |node, sibling| {
+            let sibling = bitcoin::taproot::TapNodeHash::from_byte_array(sibling.take());
+            bitcoin::taproot::TapNodeHash::from_node_hashes(node, sibling)
+        },
+    );
+    H256::from(merkle_root.to_byte_array())
+}
+
+fn tapscript_leaf_hash(payload: &Script) -> bitcoin::taproot::TapLeafHash {
+    let script_buf = bitcoin::ScriptBuf::from_bytes(payload.to_vec());
+    bitcoin::taproot::TapLeafHash::from_script(
+        &script_buf,
+        bitcoin::taproot::LeafVersion::TapScript,
+    )
+}
+
+/// Checks if the `control_block` commits to the `internal_pubkey` and the parity of the `output_key`.
+fn check_control_block(
+    control_block: &[u8],
+    internal_pubkey: &schnorr::PublicKey,
+    output_key: &schnorr::TweakedPublicKey,
+) -> SigningResult<()> {
+    let min_len = 1 + H256::LEN;
+    if control_block.len() < min_len || (control_block.len() - min_len) % H256::LEN != 0 {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Invalid Taproot control block length");
+    }
+
+    if control_block[1..min_len] != *internal_pubkey.x_only().bytes().as_slice() {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Taproot control block doesn't commit to the internal public key");
+    }
+
+    let parity = schnorr::Parity::from_lowest_bit(control_block[0]);
+    if parity != output_key.parity {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "Taproot control block parity {parity:?} doesn't match the output key parity {:?}",
+            output_key.parity
+        ));
+    }
+    Ok(())
+}
+
 impl Default for UtxoBuilder {
     fn default() -> Self {
         UtxoBuilder::new()
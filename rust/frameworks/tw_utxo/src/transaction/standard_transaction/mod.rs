@@ -2,9 +2,14 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::chain_params::ChainParams;
 use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
-use crate::encode::Encodable;
+use crate::encode::{self, Decodable, Encodable};
+use crate::script::standard_script::conditions;
+use crate::script::standard_script::opcodes::OP_RETURN;
+use crate::script::standard_script::SEGWIT_VERSION;
 use crate::script::{Script, Witness};
 use crate::signing_mode::SigningMethod;
 use crate::transaction::transaction_interface::{
@@ -15,7 +20,10 @@ use crate::transaction::transaction_sighash::fork_id_sighash::ForkIdSighash;
 use crate::transaction::transaction_sighash::legacy_sighash::LegacySighash;
 use crate::transaction::transaction_sighash::witness0_sighash::Witness0Sighash;
 use crate::transaction::{TransactionPreimage, UtxoPreimageArgs};
-use tw_coin_entry::error::prelude::{ResultContext, SigningError, SigningErrorType, SigningResult};
+use crate::weights;
+use tw_coin_entry::error::prelude::{
+    OrTWError, ResultContext, SigningError, SigningErrorType, SigningResult,
+};
 use tw_hash::hasher::sha256_d;
 use tw_hash::H256;
 
@@ -23,17 +31,22 @@ use super::transaction_sighash::taproot1_sighash::Taproot1Sighash;
 use super::UtxoTaprootPreimageArgs;
 
 pub mod builder;
+pub mod outputs_template;
+pub mod rpc;
 
 /// Must be zero.
 const WITNESS_MARKER: u8 = 0;
 /// Must be nonzero.
 const WITNESS_FLAG: u8 = 1;
 
-// Sizes of various transaction fields.
-const WITNESS_FLAG_MARKER: usize = 2;
-
-// The Segwit scale factor (witnesses are deducted).
-const SEGWIT_SCALE_FACTOR: usize = 4;
+/// `OP_RETURN OP_PUSHBYTES_36 0xaa21a9ed` prefix of the segwit commitment output.
+/// https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#commitment-structure
+const WITNESS_COMMITMENT_HEADER: [u8; 6] = [OP_RETURN, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+/// Minimum size of a serialized input: outpoint, empty scriptSig and sequence.
+const MIN_INPUT_SIZE: usize = weights::OUTPOINT_SEQUENCE_SIZE + 1;
+/// Minimum size of a serialized output: value and empty scriptPubkey.
+const MIN_OUTPUT_SIZE: usize = weights::OUTPUT_VALUE_SIZE + 1;
+const WITNESS_COMMITMENT_MIN_LEN: usize = WITNESS_COMMITMENT_HEADER.len() + H256::LEN;
 
 /// A standard Bitcoin transaction.
 ///
@@ -107,18 +120,124 @@ impl TransactionInterface for Transaction {
     }
 
     fn vsize(&self) -> usize {
-        (self.weight() + 3) / SEGWIT_SCALE_FACTOR // ceil(weight / 4)
+        weights::vsize_from_weight(self.weight())
     }
 
     fn weight(&self) -> usize {
-        self.base_size() * 3 + self.total_size()
+        weights::weight_from_sizes(self.base_size(), self.total_size())
+    }
+
+    fn total_sigop_cost<'a, I>(&self, prevout_script_pubkeys: I) -> SigningResult<usize>
+    where
+        I: IntoIterator<Item = &'a Script>,
+    {
+        let legacy_count: usize = self
+            .inputs
+            .iter()
+            .map(|input| input.script_sig.sigop_count(false))
+            .chain(
+                self.outputs
+                    .iter()
+                    .map(|output| output.script_pubkey.sigop_count(false)),
+            )
+            .sum();
+        let mut cost = legacy_count * weights::WITNESS_SCALE_FACTOR;
+
+        if self.is_coinbase() {
+            return Ok(cost);
+        }
+
+        let prevout_script_pubkeys: Vec<&Script> = prevout_script_pubkeys.into_iter().collect();
+        if prevout_script_pubkeys.len() != self.inputs.len() {
+            return SigningError::err(SigningErrorType::Error_invalid_utxo)
+                .context("Expected a scriptPubkey of every spent output");
+        }
+
+        for (input, prevout) in self.inputs.iter().zip(prevout_script_pubkeys) {
+            cost += p2sh_sigop_count(&input.script_sig, prevout) * weights::WITNESS_SCALE_FACTOR;
+            cost += witness_sigop_count(&input.script_sig, prevout, &input.witness);
+        }
+        Ok(cost)
     }
 }
 
+/// Counts sigops of the P2SH redeem script as Bitcoin Core's `GetP2SHSigOpCount` does.
+fn p2sh_sigop_count(script_sig: &Script, prevout_script_pubkey: &Script) -> usize {
+    if !conditions::is_p2sh(prevout_script_pubkey) {
+        return 0;
+    }
+    script_sig
+        .last_push_if_push_only()
+        .map(|redeem_script| Script::from(redeem_script.to_vec()).sigop_count(true))
+        .unwrap_or_default()
+}
+
+/// Counts sigops of the native or P2SH-wrapped witness program as Bitcoin Core's `CountWitnessSigOps` does.
+/// Only witness v0 programs have sigops.
+fn witness_sigop_count(
+    script_sig: &Script,
+    prevout_script_pubkey: &Script,
+    witness: &Witness,
+) -> usize {
+    let witness_program = if conditions::is_p2sh(prevout_script_pubkey) {
+        match script_sig.last_push_if_push_only() {
+            Some(redeem_script) => redeem_script,
+            None => return 0,
+        }
+    } else {
+        prevout_script_pubkey.as_slice()
+    };
+
+    match witness_program {
+        [SEGWIT_VERSION, len, program @ ..] if *len as usize == program.len() => {
+            match (program.len(), witness.as_items().last()) {
+                // P2WPKH.
+                (20, _) => 1,
+                // P2WSH, where the last witness item is the witness script.
+                (32, Some(witness_script)) => witness_script.sigop_count(true),
+                _ => 0,
+            }
+        },
+        _ => 0,
+    }
+}
+
+/// Whether the TXID of a transaction with chain-specific trailing data commits to that data.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TrailingDataTxid {
+    /// The TXID covers the standard serialization only.
+    #[default]
+    StandardOnly,
+    /// The trailing data is appended to the standard serialization before hashing.
+    IncludeTrailing,
+}
+
 impl Transaction {
+    /// Decodes a transaction strictly, i.e. returns an error if there is any data after
+    /// the standard serialization.
+    pub fn from_bytes(bytes: &[u8]) -> SigningResult<Transaction> {
+        encode::decode(bytes)
+    }
+
+    /// Decodes the standard serialization prefix and returns the remaining bytes,
+    /// e.g. extra fields appended by some forks. The remainder can be empty.
+    pub fn decode_with_trailing(bytes: &[u8]) -> SigningResult<(Transaction, &[u8])> {
+        let mut reader = Reader::new(bytes);
+        let tx = reader.read()?;
+        Ok((tx, reader.remaining()))
+    }
+
     /// TODO move to the `TransactionInterface` trait.
     pub fn txid(&self) -> Vec<u8> {
-        let encoded = self.without_witness().encode_out();
+        self.txid_with_trailing(&[], TrailingDataTxid::StandardOnly)
+    }
+
+    /// Computes the TXID of a transaction decoded by [`Transaction::decode_with_trailing`].
+    pub fn txid_with_trailing(&self, trailing: &[u8], mode: TrailingDataTxid) -> Vec<u8> {
+        let mut encoded = self.without_witness().encode_out();
+        if mode == TrailingDataTxid::IncludeTrailing {
+            encoded.extend_from_slice(trailing);
+        }
         let mut tx_hash = sha256_d(&encoded);
         tx_hash.reverse();
         tx_hash
@@ -134,12 +253,73 @@ impl Transaction {
         without_witness
     }
 
+    /// Whether the transaction is a coinbase, i.e. it has a single input spending the null outpoint.
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].previous_output.is_null()
+    }
+
+    /// Returns the segwit commitment of a coinbase transaction, if any.
+    ///
+    /// If there are multiple outputs matching the commitment pattern,
+    /// the one with the highest output index is considered (BIP141).
+    pub fn witness_commitment(&self) -> Option<H256> {
+        if !self.is_coinbase() {
+            return None;
+        }
+
+        self.outputs.iter().rev().find_map(|output| {
+            let script = output.script_pubkey.as_slice();
+            if script.len() < WITNESS_COMMITMENT_MIN_LEN
+                || !script.starts_with(&WITNESS_COMMITMENT_HEADER)
+            {
+                return None;
+            }
+            H256::try_from(&script[WITNESS_COMMITMENT_HEADER.len()..WITNESS_COMMITMENT_MIN_LEN])
+                .ok()
+        })
+    }
+
+    /// Verifies the segwit commitment of a coinbase transaction,
+    /// i.e. checks if it equals to `SHA256d(witness_root || witness_reserved_value)`.
+    ///
+    /// `witness_root` is the merkle root of the block's wtxids, where the coinbase wtxid is zero.
+    pub fn verify_witness_commitment(
+        &self,
+        witness_root: &H256,
+        witness_reserved_value: &H256,
+    ) -> SigningResult<()> {
+        let commitment = self
+            .witness_commitment()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("Expected a coinbase transaction with a witness commitment")?;
+
+        let mut preimage = witness_root.as_slice().to_vec();
+        preimage.extend_from_slice(witness_reserved_value.as_slice());
+        let expected = sha256_d(&preimage);
+
+        if commitment.as_slice() != expected.as_slice() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Witness commitment does not match the given witness root");
+        }
+        Ok(())
+    }
+
     pub fn encode_out(&self) -> Vec<u8> {
         let mut stream = Stream::new();
         self.encode(&mut stream);
         stream.out()
     }
 
+    /// Serializes the transaction according to the chain rules, see [`ChainParams::segwit_enabled`].
+    /// On a chain without segwit, the marker, flag and witness data are never serialized,
+    /// even if the inputs have witness data.
+    pub fn encode_out_for(&self, chain_params: &ChainParams) -> Vec<u8> {
+        if chain_params.segwit_enabled {
+            return self.encode_out();
+        }
+        self.without_witness().encode_out()
+    }
+
     pub fn size(&self) -> usize {
         self.total_size()
     }
@@ -170,7 +350,7 @@ impl Transaction {
 
         // Consider extended format in case witnesses are to be serialized.
         if has_witness {
-            s += WITNESS_FLAG_MARKER;
+            s += weights::WITNESS_MARKER_FLAG_SIZE;
         }
 
         let get_input_size = |i: &TransactionInput| {
@@ -217,6 +397,43 @@ impl Encodable for Transaction {
     }
 }
 
+impl Decodable for Transaction {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let version = reader.read()?;
+
+        let has_witness = reader.peek_u8() == Some(WITNESS_MARKER);
+        if has_witness {
+            reader.read::<u8>()?;
+            let flag: u8 = reader.read()?;
+            if flag != WITNESS_FLAG {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context(format!("Unsupported segwit flag '{flag}'"));
+            }
+        }
+
+        let mut inputs: Vec<TransactionInput> = reader.read_list(MIN_INPUT_SIZE)?;
+        let outputs = reader.read_list(MIN_OUTPUT_SIZE)?;
+
+        if has_witness {
+            for input in inputs.iter_mut() {
+                input.witness = reader.read()?;
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context("Segwit transaction has no witness data");
+            }
+        }
+
+        let locktime = reader.read()?;
+        Ok(Transaction {
+            version,
+            inputs,
+            outputs,
+            locktime,
+        })
+    }
+}
+
 impl TransactionPreimage for Transaction {
     fn preimage_tx(&self, args: &UtxoPreimageArgs) -> SigningResult<H256> {
         match args.signing_method {
@@ -274,6 +491,18 @@ impl TransactionInput {
     }
 }
 
+impl Decodable for TransactionInput {
+    /// Decodes the input without the witness, that is serialized separately.
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        Ok(TransactionInput {
+            previous_output: reader.read()?,
+            script_sig: reader.read()?,
+            sequence: reader.read()?,
+            witness: Witness::default(),
+        })
+    }
+}
+
 impl TxInputInterface for TransactionInput {
     fn previous_output(&self) -> &OutPoint {
         &self.previous_output
@@ -287,6 +516,10 @@ impl TxInputInterface for TransactionInput {
         self.sequence = sequence;
     }
 
+    fn script_sig(&self) -> &Script {
+        &self.script_sig
+    }
+
     fn set_script_sig(&mut self, script_sig: Script) {
         self.script_sig = script_sig;
     }
@@ -329,7 +562,8 @@ pub struct TransactionOutput {
 impl Default for TransactionOutput {
     fn default() -> Self {
         TransactionOutput {
-            value: -1,
+            // Serialized as `-1` (`0xffffffffffffffff`).
+            value: Amount::MAX,
             script_pubkey: Script::default(),
         }
     }
@@ -358,3 +592,106 @@ impl Encodable for TransactionOutput {
         self.value.encoded_size() + self.script_pubkey.encoded_size()
     }
 }
+
+impl Decodable for TransactionOutput {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        Ok(TransactionOutput {
+            value: reader.read()?,
+            script_pubkey: reader.read()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::{DecodeHex, ToHex};
+
+    /// Testnet coinbase `4be105f158ea44aec57bf12c5817d073a712ab131df6f37786872cfc70734188`,
+    /// taken from the rust-bitcoin test suite.
+    const TESTNET_COINBASE: &str = "010000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff3603da1b0e00045503bd5704c7dd8a0d0ced13bb5785010800000000000a636b706f6f6c122f4e696e6a61506f6f6c2f5345475749542fffffffff02b4e5a212000000001976a914876fbb82ec05caa6af7a3b5e5a983aae6c6cc6d688ac0000000000000000266a24aa21a9edf91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a39837040120000000000000000000000000000000000000000000000000000000000000000000000000";
+    const TESTNET_COMMITMENT: &str =
+        "f91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a3983704";
+
+    /// Coinbase of the regtest block used in the rust-bitcoin compact block test vector.
+    /// The block contains one more segwit transaction.
+    const REGTEST_COINBASE: &str = "020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff04016b0101ffffffff020006062a0100000001510000000000000000266a24aa21a9ed4a3d9f3343dafcc0d6f6d4310f2ee5ce273ed34edca6c75db3a73e7f368734200120000000000000000000000000000000000000000000000000000000000000000000000000";
+    /// Merkle root of the regtest block's wtxids, as computed by rust-bitcoin `Block::witness_root`.
+    const REGTEST_WITNESS_ROOT: &str =
+        "32752d1ba9d1d418acb8e2759f54f918a6516ce54293b101c469fa96413e138a";
+
+    fn decode_tx(hex: &str) -> Transaction {
+        Transaction::from_bytes(&hex.decode_hex().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_witness_commitment() {
+        let tx = decode_tx(REGTEST_COINBASE);
+        assert!(tx.is_coinbase());
+
+        let witness_root = H256::from(REGTEST_WITNESS_ROOT);
+        let reserved = H256::default();
+        tx.verify_witness_commitment(&witness_root, &reserved)
+            .unwrap();
+
+        // Any other witness root or reserved value doesn't match the commitment.
+        tx.verify_witness_commitment(&H256::default(), &reserved)
+            .unwrap_err();
+        let other_reserved =
+            H256::from("0000000000000000000000000000000000000000000000000000000000000001");
+        tx.verify_witness_commitment(&witness_root, &other_reserved)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_witness_commitment_testnet_coinbase() {
+        let tx = decode_tx(TESTNET_COINBASE);
+        assert!(tx.is_coinbase());
+        assert_eq!(
+            tx.txid().to_hex(),
+            "4be105f158ea44aec57bf12c5817d073a712ab131df6f37786872cfc70734188"
+        );
+        assert_eq!(
+            tx.witness_commitment(),
+            Some(H256::from(TESTNET_COMMITMENT))
+        );
+    }
+
+    #[test]
+    fn test_witness_commitment_last_output_wins() {
+        let mut tx = decode_tx(TESTNET_COINBASE);
+        // Too short to be a commitment.
+        tx.outputs.push(TransactionOutput {
+            value: 0,
+            script_pubkey: Script::from("6a04aa21a9ed".decode_hex().unwrap()),
+        });
+        assert_eq!(
+            tx.witness_commitment(),
+            Some(H256::from(TESTNET_COMMITMENT))
+        );
+
+        tx.outputs.push(TransactionOutput {
+            value: 0,
+            script_pubkey: Script::from(
+                "6a24aa21a9ed1111111111111111111111111111111111111111111111111111111111111111"
+                    .decode_hex()
+                    .unwrap(),
+            ),
+        });
+        assert_eq!(
+            tx.witness_commitment(),
+            Some(H256::from(
+                "1111111111111111111111111111111111111111111111111111111111111111"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_witness_commitment_not_coinbase() {
+        let mut tx = decode_tx(TESTNET_COINBASE);
+        tx.inputs[0].previous_output.index = 0;
+
+        assert!(!tx.is_coinbase());
+        assert_eq!(tx.witness_commitment(), None);
+    }
+}
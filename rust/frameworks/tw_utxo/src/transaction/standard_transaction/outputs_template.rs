@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Outputs-only transaction templates, e.g. payment requests.
+//!
+//! The payee constructs the requested outputs and optionally a required lock time,
+//! and passes the serialized [`OutputsTemplate`] to the payer. The payer completes the transaction
+//! via [`TransactionBuilder::from_template`](super::builder::TransactionBuilder::from_template),
+//! adding inputs, change and fees. The payee checks the final transaction with [`verify_payment`].
+
+use crate::encode::compact_integer::CompactInteger;
+use crate::encode::reader::Reader;
+use crate::encode::stream::Stream;
+use crate::encode::{self, Decodable, Encodable};
+use crate::script::Script;
+use crate::transaction::standard_transaction::{Transaction, TransactionOutput, MIN_OUTPUT_SIZE};
+use crate::transaction::transaction_parts::Amount;
+use serde_json::{json, Value as Json};
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_hash::hasher::sha256_d;
+use tw_hash::H256;
+
+#[derive(Clone, Debug, Default)]
+pub struct OutputsTemplate {
+    /// The requested outputs. The payer's builder places them before any other output.
+    pub outputs: Vec<TransactionOutput>,
+    /// The lock time the transaction must have, if any.
+    pub lock_time: Option<u32>,
+}
+
+impl OutputsTemplate {
+    /// Double SHA256 of the binary serialization.
+    pub fn digest(&self) -> H256 {
+        H256::try_from(sha256_d(&encode::encode(self)).as_slice())
+            .expect("sha256_d must return 32 bytes")
+    }
+
+    pub fn to_json(&self) -> Json {
+        let outputs: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|output| {
+                json!({
+                    "value": output.value,
+                    "script_pubkey": hex::encode(output.script_pubkey.as_slice(), false),
+                })
+            })
+            .collect();
+
+        json!({
+            "outputs": outputs,
+            "lock_time": self.lock_time,
+            "digest": hex::encode(self.digest(), false),
+        })
+    }
+
+    /// Parses the template from [`OutputsTemplate::to_json`].
+    /// The `digest` field is optional, but if it's given, it must match the template.
+    pub fn from_json(json: &Json) -> SigningResult<OutputsTemplate> {
+        let outputs = json["outputs"]
+            .as_array()
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Expected 'outputs' array")?
+            .iter()
+            .enumerate()
+            .map(|(output_index, output)| {
+                output_from_json(output)
+                    .with_context(|| format!("Invalid template output #{output_index}"))
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        let lock_time = match &json["lock_time"] {
+            Json::Null => None,
+            lock_time => Some(
+                lock_time
+                    .as_u64()
+                    .and_then(|lock_time| u32::try_from(lock_time).ok())
+                    .or_tw_err(SigningErrorType::Error_input_parse)
+                    .context("Invalid 'lock_time'")?,
+            ),
+        };
+
+        let template = OutputsTemplate { outputs, lock_time };
+        if let Some(digest) = json["digest"].as_str() {
+            if hex::encode(template.digest(), false) != digest {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context("Template digest mismatch");
+            }
+        }
+        Ok(template)
+    }
+}
+
+impl Encodable for OutputsTemplate {
+    fn encode(&self, stream: &mut Stream) {
+        stream.append_list(&self.outputs);
+        match self.lock_time {
+            Some(lock_time) => stream.append(&1_u8).append(&lock_time),
+            None => stream.append(&0_u8),
+        };
+    }
+
+    fn encoded_size(&self) -> usize {
+        let lock_time_size = self
+            .lock_time
+            .map_or(0, |lock_time| lock_time.encoded_size());
+        CompactInteger::from(self.outputs.len()).encoded_size()
+            + self.outputs.iter().map(|o| o.encoded_size()).sum::<usize>()
+            + 1
+            + lock_time_size
+    }
+}
+
+impl Decodable for OutputsTemplate {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        let outputs = reader.read_list(MIN_OUTPUT_SIZE)?;
+        let lock_time = match reader.read::<u8>()? {
+            0 => None,
+            1 => Some(reader.read()?),
+            flag => {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context(format!("Invalid lock time flag '{flag}'"))
+            },
+        };
+        Ok(OutputsTemplate { outputs, lock_time })
+    }
+}
+
+/// Checks that every output of the `template` appears in the transaction with the exact
+/// scriptPubkey and amount, and that the transaction has the required lock time.
+///
+/// The outputs may be reordered, but every transaction output pays at most one template output.
+pub fn verify_payment(tx: &Transaction, template: &OutputsTemplate) -> SigningResult<()> {
+    if let Some(lock_time) = template.lock_time {
+        if tx.locktime != lock_time {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Expected lock time {lock_time}, found {}",
+                tx.locktime
+            ));
+        }
+    }
+
+    let mut paid = vec![false; tx.outputs.len()];
+    for (output_index, expected) in template.outputs.iter().enumerate() {
+        let found = (0..tx.outputs.len()).find(|tx_index| {
+            let output = &tx.outputs[*tx_index];
+            !paid[*tx_index]
+                && output.value == expected.value
+                && output.script_pubkey == expected.script_pubkey
+        });
+
+        match found {
+            Some(tx_index) => paid[tx_index] = true,
+            None => {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Template output #{output_index} of {} is not paid",
+                    expected.value
+                ))
+            },
+        }
+    }
+    Ok(())
+}
+
+fn output_from_json(json: &Json) -> SigningResult<TransactionOutput> {
+    let value: Amount = json["value"]
+        .as_u64()
+        .or_tw_err(SigningErrorType::Error_input_parse)
+        .context("Invalid 'value'")?;
+    let script_pubkey = json["script_pubkey"]
+        .as_str()
+        .and_then(|script| hex::decode(script).ok())
+        .or_tw_err(SigningErrorType::Error_input_parse)
+        .context("Invalid 'script_pubkey'")?;
+
+    Ok(TransactionOutput {
+        value,
+        script_pubkey: Script::from(script_pubkey),
+    })
+}
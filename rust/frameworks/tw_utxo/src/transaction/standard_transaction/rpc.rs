@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! JSON representation of a transaction as returned by the Bitcoin Core
+//! `decoderawtransaction` RPC.
+
+use crate::chain_params::ChainParams;
+use crate::constants::COIN;
+use crate::script::standard_script::conditions::ScriptPubkeyType;
+use crate::script::Script;
+use crate::transaction::standard_transaction::{Transaction, TransactionInput, TransactionOutput};
+use crate::transaction::transaction_interface::TransactionInterface;
+use crate::transaction::transaction_parts::Amount;
+use serde_json::{json, Map as JsonMap, Value as Json};
+use tw_encoding::hex;
+use tw_hash::hasher::sha256_d;
+
+impl Transaction {
+    /// Returns the transaction in the same shape as `bitcoin-cli decoderawtransaction`.
+    ///
+    /// # Differences
+    ///
+    /// * `value` is a BTC decimal string, e.g. `"0.00100000"`, rather than a JSON number.
+    /// * `scriptPubKey.desc` output descriptor is not included.
+    /// * Bare multisig and unknown witness version scriptPubkey's are reported as `nonstandard`.
+    pub fn to_rpc_json(&self, chain_params: &ChainParams) -> Json {
+        let mut wtxid = sha256_d(&self.encode_out());
+        wtxid.reverse();
+
+        let vin: Vec<_> = self
+            .inputs
+            .iter()
+            .map(|input| self.input_to_rpc_json(input))
+            .collect();
+        let vout: Vec<_> = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(n, output)| output_to_rpc_json(n, output, chain_params))
+            .collect();
+
+        json!({
+            "txid": hex::encode(self.txid(), false),
+            "hash": hex::encode(wtxid, false),
+            "version": self.version,
+            "size": self.size(),
+            "vsize": self.vsize(),
+            "weight": self.weight(),
+            "locktime": self.locktime,
+            "vin": vin,
+            "vout": vout,
+        })
+    }
+
+    fn input_to_rpc_json(&self, input: &TransactionInput) -> Json {
+        let mut json = JsonMap::new();
+
+        if self.is_coinbase() {
+            json.insert(
+                "coinbase".to_string(),
+                json!(hex::encode(input.script_sig.as_slice(), false)),
+            );
+        } else {
+            let prev_txid = input.previous_output.hash.rev();
+            json.insert("txid".to_string(), json!(hex::encode(prev_txid, false)));
+            json.insert("vout".to_string(), json!(input.previous_output.index));
+            json.insert(
+                "scriptSig".to_string(),
+                script_to_rpc_json(&input.script_sig, true),
+            );
+        }
+
+        if !input.witness.is_empty() {
+            let witness: Vec<_> = input
+                .witness
+                .as_items()
+                .iter()
+                .map(|item| hex::encode(item.as_slice(), false))
+                .collect();
+            json.insert("txinwitness".to_string(), json!(witness));
+        }
+
+        json.insert("sequence".to_string(), json!(input.sequence));
+        Json::Object(json)
+    }
+}
+
+fn output_to_rpc_json(n: usize, output: &TransactionOutput, chain_params: &ChainParams) -> Json {
+    let mut script_pubkey = script_to_rpc_json(&output.script_pubkey, false);
+    let script_type = chain_params.classify(&output.script_pubkey);
+    script_pubkey["type"] = json!(rpc_script_type(script_type));
+    if let Some(address) = chain_params.address_of(&output.script_pubkey) {
+        script_pubkey["address"] = json!(address);
+    }
+
+    json!({
+        "value": format_btc_amount(output.value),
+        "n": n,
+        "scriptPubKey": script_pubkey,
+    })
+}
+
+fn script_to_rpc_json(script: &Script, is_script_sig: bool) -> Json {
+    json!({
+        "asm": script.to_asm(is_script_sig),
+        "hex": hex::encode(script.as_slice(), false),
+    })
+}
+
+/// Script type names as used by Bitcoin Core.
+fn rpc_script_type(ty: ScriptPubkeyType) -> &'static str {
+    match ty {
+        ScriptPubkeyType::P2PK => "pubkey",
+        ScriptPubkeyType::P2PKH => "pubkeyhash",
+        ScriptPubkeyType::P2SH => "scripthash",
+        ScriptPubkeyType::P2WPKH => "witness_v0_keyhash",
+        ScriptPubkeyType::P2WSH => "witness_v0_scripthash",
+        ScriptPubkeyType::P2TR => "witness_v1_taproot",
+        ScriptPubkeyType::OpReturn => "nulldata",
+        ScriptPubkeyType::Timelocked | ScriptPubkeyType::Custom => "nonstandard",
+    }
+}
+
+/// Formats the amount of satoshis as BTC with 8 decimal places, e.g. `0.00100000`.
+pub fn format_btc_amount(amount: Amount) -> String {
+    format!("{}.{:08}", amount / COIN, amount % COIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_btc_amount() {
+        assert_eq!(format_btc_amount(0), "0.00000000");
+        assert_eq!(format_btc_amount(1), "0.00000001");
+        assert_eq!(format_btc_amount(100_000), "0.00100000");
+        assert_eq!(format_btc_amount(50 * COIN), "50.00000000");
+        assert_eq!(
+            format_btc_amount(2_100_000_000_000_001),
+            "21000000.00000001"
+        );
+    }
+}
@@ -77,18 +77,21 @@ impl<Transaction: TransactionInterface> TransactionHasher<Transaction> {
         Self::sequence_hash(tx, args.tx_hasher)
     }
 
+    /// Computes a hash of all [`TransactionOutput`].
+    pub fn outputs_hash(tx: &Transaction, tx_hasher: Hasher) -> Data {
+        let mut stream = Stream::default();
+        for output in tx.outputs() {
+            stream.append(output);
+        }
+        tx_hasher.hash(&stream.out())
+    }
+
     /// Returns a hash of required [`TransactionOutput`] according to the [`UtxoPreimageArgs::sighash`].
     /// Please note the function can return a zero hash if necessary.
     pub fn preimage_outputs_hash(tx: &Transaction, args: &UtxoPreimageArgs) -> Data {
         let outputs = tx.outputs();
         match args.sighash_ty.base_type() {
-            SighashBase::All => {
-                let mut stream = Stream::default();
-                for output in outputs {
-                    stream.append(output);
-                }
-                args.tx_hasher.hash(&stream.out())
-            },
+            SighashBase::All => Self::outputs_hash(tx, args.tx_hasher),
             SighashBase::Single if args.input_index < outputs.len() => {
                 let mut stream = Stream::default();
                 stream.append(&outputs[args.input_index]);
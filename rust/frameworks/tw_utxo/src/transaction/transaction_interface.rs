@@ -2,7 +2,9 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::constants::MAX_BIP125_RBF_SEQUENCE;
 use crate::encode::Encodable;
+use tw_coin_entry::error::prelude::SigningResult;
 
 use crate::script::{Script, Witness};
 use crate::transaction::transaction_parts::OutPoint;
@@ -34,12 +36,29 @@ pub trait TransactionInterface: Clone + Encodable {
 
     fn locktime(&self) -> u32;
 
+    /// Whether the transaction signals opt-in Replace-By-Fee,
+    /// i.e. any of the inputs has a sequence not greater than [`MAX_BIP125_RBF_SEQUENCE`].
+    fn signals_rbf(&self) -> bool {
+        self.inputs()
+            .iter()
+            .any(|input| input.sequence() <= MAX_BIP125_RBF_SEQUENCE)
+    }
+
     /// The `vsize` in `vbytes` refers to a transaction's weighted size under segwit's rules.
     /// It is used to compare how much blockweight needs to be allocated to confirm a transaction.
     /// For non-segwit transactions, `vsize` = `size`.
     fn vsize(&self) -> usize;
 
     fn weight(&self) -> usize;
+
+    /// Total signature operations cost as Bitcoin Core's `GetTransactionSigOpCost` computes it,
+    /// i.e. including P2SH redeem scripts and witness sigops.
+    /// Legacy and P2SH sigops are scaled by the segwit factor.
+    ///
+    /// `prevout_script_pubkeys` are scriptPubkeys of the spent outputs in the inputs order.
+    fn total_sigop_cost<'a, I>(&self, prevout_script_pubkeys: I) -> SigningResult<usize>
+    where
+        I: IntoIterator<Item = &'a Script>;
 }
 
 pub trait TxInputInterface: Clone {
@@ -49,6 +68,8 @@ pub trait TxInputInterface: Clone {
 
     fn set_sequence(&mut self, sequence: u32);
 
+    fn script_sig(&self) -> &Script;
+
     fn set_script_sig(&mut self, script_sig: Script);
 
     fn set_witness(&mut self, witness: Witness);
@@ -2,13 +2,94 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::constants::LOCKTIME_THRESHOLD;
+use crate::encode::reader::Reader;
 use crate::encode::stream::Stream;
-use crate::encode::Encodable;
+use crate::encode::{Decodable, Encodable};
+use crate::transaction::standard_transaction::builder::txid_from_str_and_rev;
+use tw_coin_entry::error::prelude::*;
 use tw_hash::H256;
 
-/// Amount in satoshis (Can be negative) in rare cases.
-/// https://github.com/bitcoin/bitcoin/blob/bd5d1688b4311e21c0e0ff89a3ae02ef7d0543b8/src/consensus/amount.h#L11-L12
-pub type Amount = i64;
+/// Amount in satoshis (or the smallest units of the chain).
+///
+/// It's unsigned as some chains (e.g. Dogecoin) operate with amounts that are close to the `i64` bound.
+/// The amounts are validated against the chain `max_money` at the builder time,
+/// see [`TransactionBuilder::max_money`].
+///
+/// [`TransactionBuilder::max_money`]: crate::transaction::standard_transaction::builder::TransactionBuilder::max_money
+pub type Amount = u64;
+
+/// An absolute lock time, as used by `nLockTime` and `OP_CHECKLOCKTIMEVERIFY`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LockTime {
+    /// Block height, below [`LOCKTIME_THRESHOLD`].
+    Height(u32),
+    /// UNIX timestamp, not below [`LOCKTIME_THRESHOLD`].
+    Time(u32),
+}
+
+impl LockTime {
+    pub fn from_consensus(lock_time: u32) -> LockTime {
+        if lock_time < LOCKTIME_THRESHOLD {
+            LockTime::Height(lock_time)
+        } else {
+            LockTime::Time(lock_time)
+        }
+    }
+
+    /// Creates a block height lock time.
+    pub fn from_height(height: u32) -> SigningResult<LockTime> {
+        if height >= LOCKTIME_THRESHOLD {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Lock time height {height} is a timestamp"));
+        }
+        Ok(LockTime::Height(height))
+    }
+
+    /// Creates a UNIX timestamp lock time.
+    pub fn from_time(time: u32) -> SigningResult<LockTime> {
+        if time < LOCKTIME_THRESHOLD {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Lock time timestamp {time} is a block height"));
+        }
+        Ok(LockTime::Time(time))
+    }
+
+    pub fn to_consensus_u32(self) -> u32 {
+        match self {
+            LockTime::Height(lock_time) | LockTime::Time(lock_time) => lock_time,
+        }
+    }
+
+    /// Whether a transaction with the given `nLockTime` satisfies the lock time
+    /// as checked by `OP_CHECKLOCKTIMEVERIFY`, i.e. both are of the same kind and the `tx_lock_time` is not below.
+    /// Note that the input sequence must not be final either.
+    pub fn is_satisfied_by(self, tx_lock_time: u32) -> bool {
+        match (self, LockTime::from_consensus(tx_lock_time)) {
+            (LockTime::Height(lock), LockTime::Height(tx))
+            | (LockTime::Time(lock), LockTime::Time(tx)) => tx >= lock,
+            _ => false,
+        }
+    }
+}
+
+/// A transaction id in the internal byte order,
+/// i.e. reversed in relation to the hex displayed by block explorers.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Txid(pub H256);
+
+impl Txid {
+    /// Parses a txid from the hex displayed by block explorers.
+    pub fn from_display_hex(txid: &str) -> SigningResult<Txid> {
+        txid_from_str_and_rev(txid).map(Txid)
+    }
+}
+
+impl From<H256> for Txid {
+    fn from(hash: H256) -> Self {
+        Txid(hash)
+    }
+}
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct OutPoint {
@@ -16,6 +97,13 @@ pub struct OutPoint {
     pub index: u32,
 }
 
+impl OutPoint {
+    /// Whether the outpoint is the null one spent by coinbase transactions.
+    pub fn is_null(&self) -> bool {
+        self.hash.is_zero() && self.index == u32::MAX
+    }
+}
+
 impl Encodable for OutPoint {
     fn encode(&self, stream: &mut Stream) {
         stream.append(&self.hash).append(&self.index);
@@ -25,3 +113,12 @@ impl Encodable for OutPoint {
         self.hash.encoded_size() + self.index.encoded_size()
     }
 }
+
+impl Decodable for OutPoint {
+    fn decode(reader: &mut Reader) -> SigningResult<Self> {
+        Ok(OutPoint {
+            hash: reader.read()?,
+            index: reader.read()?,
+        })
+    }
+}
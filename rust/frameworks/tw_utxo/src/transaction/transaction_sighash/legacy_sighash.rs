@@ -50,7 +50,7 @@ impl<Transaction: std::fmt::Debug + TransactionInterface> LegacySighash<Transact
 
         if args.sighash_ty.anyone_can_pay() {
             let mut input_preimage = input_to_sign.clone();
-            input_preimage.set_script_sig(args.script_pubkey.clone());
+            input_preimage.set_script_sig(args.script_code.as_script().clone());
             input_preimage.set_witness(Witness::default());
             return Ok(vec![input_preimage]);
         }
@@ -63,7 +63,7 @@ impl<Transaction: std::fmt::Debug + TransactionInterface> LegacySighash<Transact
 
                 let mut input_preimage = input.clone();
                 input_preimage.set_script_sig(if is_this_input {
-                    args.script_pubkey.clone()
+                    args.script_code.as_script().clone()
                 } else {
                     Script::default()
                 });
@@ -5,29 +5,42 @@
 use crate::encode::stream::Stream;
 use crate::sighash::SighashBase;
 use crate::transaction::transaction_hashing::TransactionHasher;
-use crate::transaction::transaction_interface::TransactionInterface;
+use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
 use crate::transaction::UtxoTaprootPreimageArgs;
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
 use tw_hash::hasher::tapsighash;
 use tw_hash::H256;
 
-/// `Taproot1Sighash`is used to calculate a preimage hash of a P2WPKH or P2WSH unspent output.
+/// The `hash_type` values defined by BIP-341, any other value makes the signature invalid.
+/// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message
+const TAPROOT_SIGHASH_TYPES: [u8; 7] = [0x00, 0x01, 0x02, 0x03, 0x81, 0x82, 0x83];
+
+/// `Taproot1Sighash`is used to calculate a preimage hash of a P2TR unspent output
+/// according to BIP-341.
 pub struct Taproot1Sighash<Transaction: TransactionInterface> {
     _phantom: PhantomData<Transaction>,
 }
 
 impl<Transaction: TransactionInterface> Taproot1Sighash<Transaction> {
     pub fn sighash_tx(tx: &Transaction, tr: &UtxoTaprootPreimageArgs) -> SigningResult<H256> {
-        // TODO if anyone_can_pay flag is set, there is no need to append these hashes.
-        // See https://github.com/rust-bitcoin/rust-bitcoin/blob/b0870634f0e4bd4c36e7ab0b7c9c7deb23ae62bf/bitcoin/src/crypto/sighash.rs#L608-L622
-        let prevout_hash = TransactionHasher::<Transaction>::preimage_prevout_hash(tx, &tr.args);
-        let sequence_hash = TransactionHasher::<Transaction>::preimage_sequence_hash(tx, &tr.args);
-        let outputs_hash = TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
-        let spent_amounts_hash = TransactionHasher::<Transaction>::spent_amount_hash(tr);
-        let raw_sighash = tr.args.sighash_ty.serialize_as_taproot()?;
+        let args = &tr.args;
+        let input_index = args.input_index;
+
+        let raw_sighash = args.sighash_ty.serialize_as_taproot()?;
+        if !TAPROOT_SIGHASH_TYPES.contains(&raw_sighash) {
+            return SigningError::err(SigningErrorType::Error_invalid_sighash_type).context(
+                format!("Sighash type is not allowed in Taproot: {raw_sighash:#x}"),
+            );
+        }
+        let anyone_can_pay = args.sighash_ty.anyone_can_pay();
+        let base_type = args.sighash_ty.base_type();
 
-        let spent_script_pubkeys_hash = TransactionHasher::<Transaction>::spent_script_pubkeys(tr);
+        let input = tx
+            .inputs()
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Taproot sighash error: input_index is out of bounds")?;
 
         let mut stream = Stream::default();
 
@@ -35,36 +48,77 @@ impl<Transaction: TransactionInterface> Taproot1Sighash<Transaction> {
             .append(&0u8) // epoch
             .append(&raw_sighash)
             .append(&tx.version())
-            .append(&tx.locktime())
-            .append_raw_slice(&prevout_hash)
-            .append_raw_slice(&spent_amounts_hash)
-            .append_raw_slice(&spent_script_pubkeys_hash)
-            .append_raw_slice(&sequence_hash)
-            // TODO: Double check behavior or `Single`
-            // TODO: What about `NonePlusAnyoneCanPay`?.
-            .append_raw_slice(&outputs_hash);
+            .append(&tx.locktime());
+
+        // The data of the other inputs is committed unless `ANYONECANPAY`.
+        if !anyone_can_pay {
+            let prevout_hash = TransactionHasher::<Transaction>::prevout_hash(tx, args.tx_hasher);
+            let spent_amounts_hash = TransactionHasher::<Transaction>::spent_amount_hash(tr);
+            let spent_script_pubkeys_hash =
+                TransactionHasher::<Transaction>::spent_script_pubkeys(tr);
+            // Unlike BIP-143, the sequences are committed with `SINGLE` and `NONE` too.
+            let sequence_hash = TransactionHasher::<Transaction>::sequence_hash(tx, args.tx_hasher);
+
+            stream
+                .append_raw_slice(&prevout_hash)
+                .append_raw_slice(&spent_amounts_hash)
+                .append_raw_slice(&spent_script_pubkeys_hash)
+                .append_raw_slice(&sequence_hash);
+        }
+
+        // `SINGLE` commits to its output after the input data, `NONE` to no outputs.
+        if base_type == SighashBase::All {
+            let outputs_hash = TransactionHasher::<Transaction>::outputs_hash(tx, args.tx_hasher);
+            stream.append_raw_slice(&outputs_hash);
+        }
 
         let mut spend_type = 0u8;
 
-        if tr.args.leaf_hash_code_separator.is_some() {
+        if args.leaf_hash_code_separator.is_some() {
             spend_type |= 2u8;
         }
 
         stream.append(&spend_type);
 
-        if tr.args.sighash_ty.anyone_can_pay() {
-            return SigningError::err(SigningErrorType::Error_not_supported)
-                .context("'anyone can pay' sighash type is not supported for Taproot yet");
+        if anyone_can_pay {
+            let spent_amount = tr
+                .spent_amounts
+                .get(input_index)
+                .or_tw_err(SigningErrorType::Error_internal)
+                .context("Taproot sighash error: no spent amount of the input")?;
+            let spent_script_pubkey = tr
+                .spent_script_pubkeys
+                .get(input_index)
+                .or_tw_err(SigningErrorType::Error_internal)
+                .context("Taproot sighash error: no spent scriptPubkey of the input")?;
+
+            stream
+                .append(input.previous_output())
+                .append(spent_amount)
+                .append(spent_script_pubkey)
+                .append(&input.sequence());
         } else {
-            stream.append(&(tr.args.input_index as u32));
+            stream.append(&(input_index as u32));
         }
 
-        if tr.args.sighash_ty.base_type() == SighashBase::Single {
-            return SigningError::err(SigningErrorType::Error_not_supported)
-                .context("'single' sighash type is not supported for Taproot yet");
+        if base_type == SighashBase::Single {
+            // Unlike the legacy sighash, that signs `1` in this case, a missing output is invalid.
+            let output = tx
+                .outputs()
+                .get(input_index)
+                .or_tw_err(SigningErrorType::Error_invalid_params)
+                .with_context(|| {
+                    format!(
+                        "SIGHASH_SINGLE input #{input_index} has no output at the same index, which is invalid in Taproot"
+                    )
+                })?;
+
+            let mut output_stream = Stream::default();
+            output_stream.append(output);
+            stream.append_raw_slice(&args.tx_hasher.hash(&output_stream.out()));
         }
 
-        if let Some((leaf_hash, separator)) = tr.args.leaf_hash_code_separator {
+        if let Some((leaf_hash, separator)) = args.leaf_hash_code_separator {
             stream
                 .append_raw_slice(leaf_hash.as_slice())
                 .append(&0u8) // key-version 0
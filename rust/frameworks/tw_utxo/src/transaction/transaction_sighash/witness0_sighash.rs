@@ -39,12 +39,13 @@ impl<Transaction: TransactionInterface> Witness0Sighash<Transaction> {
         // may already be contained in hashSequence.
         stream
             .append(input.previous_output())
-            .append(&args.script_pubkey)
+            .append(&args.script_code)
             .append(&args.amount)
             .append(&input.sequence())
             .append_raw_slice(&outputs_hash)
             .append(&tx.locktime())
-            // This also includes 24-bit fork id. For example, 0 for BitcoinCash.
+            // This also includes 24-bit fork id composed as `(fork_id << 8) | base_flags`.
+            // For example, 0 for BitcoinCash and 79 for BitcoinGold.
             .append(&args.sighash_ty.raw_sighash());
 
         let hash = args.tx_hasher.hash(&stream.out());
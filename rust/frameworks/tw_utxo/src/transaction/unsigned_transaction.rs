@@ -2,7 +2,10 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::replay_protection::ReplayProtection;
+use crate::shuffle::{shuffle, ShuffleRng};
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature};
+use crate::spec_location::SpecLocation;
 use crate::spending_data::SpendingDataConstructor;
 use crate::transaction::transaction_interface::{
     TransactionInterface, TxInputInterface, TxOutputInterface,
@@ -14,6 +17,11 @@ use tw_coin_entry::error::prelude::*;
 pub struct UnsignedTransaction<Transaction> {
     transaction: Transaction,
     utxo_args: Vec<UtxoToSign>,
+    /// The index of each input in the signing request.
+    /// Kept through filtering, selection and shuffling, so the errors can locate the inputs.
+    request_indices: Vec<usize>,
+    /// The replay protection applied by the builder, if any.
+    replay_protection: Option<ReplayProtection>,
 }
 
 impl<Transaction> UnsignedTransaction<Transaction>
@@ -27,9 +35,12 @@ where
         }
 
         check_utxo_args_number(transaction.inputs().len(), utxo_args.len())?;
+        let request_indices = (0..utxo_args.len()).collect();
         Ok(UnsignedTransaction {
             transaction,
             utxo_args,
+            request_indices,
+            replay_protection: None,
         })
     }
 
@@ -49,6 +60,28 @@ where
         &self.utxo_args
     }
 
+    /// Returns the index of each input in the signing request.
+    pub fn request_indices(&self) -> &[usize] {
+        &self.request_indices
+    }
+
+    /// Returns the index of the `input_index` input in the signing request.
+    pub fn request_index(&self, input_index: usize) -> usize {
+        self.request_indices
+            .get(input_index)
+            .copied()
+            .unwrap_or(input_index)
+    }
+
+    /// Returns the replay protection applied by the builder, if any.
+    pub fn replay_protection(&self) -> Option<&ReplayProtection> {
+        self.replay_protection.as_ref()
+    }
+
+    pub(crate) fn set_replay_protection(&mut self, replay_protection: Option<ReplayProtection>) {
+        self.replay_protection = replay_protection;
+    }
+
     pub fn outputs_mut(&mut self) -> &mut [Transaction::Output] {
         self.transaction.outputs_mut()
     }
@@ -81,14 +114,30 @@ where
         estimated_tx
     }
 
+    /// Replaces the transaction inputs.
+    /// The new inputs are located by their index in `utxos` then.
     pub fn set_inputs(
         &mut self,
         utxos: Vec<Transaction::Input>,
         utxo_args: Vec<UtxoToSign>,
+    ) -> SigningResult<()> {
+        let request_indices = (0..utxo_args.len()).collect();
+        self.set_selected_inputs(utxos, utxo_args, request_indices)
+    }
+
+    /// Replaces the transaction inputs with a subset of the current ones,
+    /// where `request_indices` are their indices in the signing request.
+    pub fn set_selected_inputs(
+        &mut self,
+        utxos: Vec<Transaction::Input>,
+        utxo_args: Vec<UtxoToSign>,
+        request_indices: Vec<usize>,
     ) -> SigningResult<()> {
         check_utxo_args_number(utxos.len(), utxo_args.len())?;
+        check_utxo_args_number(request_indices.len(), utxo_args.len())?;
 
         self.utxo_args = utxo_args;
+        self.request_indices = request_indices;
         self.transaction.replace_inputs(utxos);
         Ok(())
     }
@@ -99,42 +148,89 @@ where
     where
         for<'a> F: Fn(&'a Transaction::Input, &'a UtxoToSign) -> bool,
     {
-        let (utxos, utxo_args) = self
+        let mut utxos = Vec::with_capacity(self.utxo_args.len());
+        let mut utxo_args = Vec::with_capacity(self.utxo_args.len());
+        let mut request_indices = Vec::with_capacity(self.utxo_args.len());
+
+        let inputs = self
             .transaction
             .inputs()
             .iter()
             .zip(self.utxo_args.iter())
-            .filter_map(|(utxo, utxo_args)| {
-                if f(utxo, utxo_args) {
-                    Some((utxo.clone(), utxo_args.clone()))
-                } else {
-                    None
-                }
-            })
-            .unzip();
+            .zip(self.request_indices.iter());
+        for ((utxo, utxo_arg), request_index) in inputs {
+            if f(utxo, utxo_arg) {
+                utxos.push(utxo.clone());
+                utxo_args.push(utxo_arg.clone());
+                request_indices.push(*request_index);
+            }
+        }
 
-        self.set_inputs(utxos, utxo_args)
+        self.set_selected_inputs(utxos, utxo_args, request_indices)
     }
 
-    pub fn total_input(&self) -> SigningResult<Amount> {
-        self.utxo_args
+    /// Shuffles the transaction inputs along with their [`UtxoToSign`] arguments
+    /// and request indices, so they stay aligned with the inputs to be signed.
+    ///
+    /// # Important
+    ///
+    /// Must be called before the sighashes are computed.
+    pub fn shuffle_inputs<R>(&mut self, rng: &mut R)
+    where
+        R: ShuffleRng + ?Sized,
+    {
+        let mut inputs: Vec<_> = self
+            .transaction
+            .inputs()
             .iter()
-            .try_fold(0i64, |total_in, utxo_args| {
-                total_in.checked_add(utxo_args.amount)
-            })
-            .or_tw_err(SigningErrorType::Error_tx_too_big)
-            .context("Sum of Transaction UTXO amounts is too big")
+            .cloned()
+            .zip(self.utxo_args.drain(..))
+            .zip(self.request_indices.drain(..))
+            .collect();
+        shuffle(&mut inputs, rng);
+
+        let ((utxos, utxo_args), request_indices) = inputs.into_iter().unzip();
+        self.utxo_args = utxo_args;
+        self.request_indices = request_indices;
+        self.transaction.replace_inputs(utxos);
+    }
+
+    /// Shuffles the transaction outputs, including change.
+    ///
+    /// # Important
+    ///
+    /// Must be called before the sighashes are computed.
+    pub fn shuffle_outputs<R>(&mut self, rng: &mut R)
+    where
+        R: ShuffleRng + ?Sized,
+    {
+        let mut outputs = self.transaction.outputs().to_vec();
+        shuffle(&mut outputs, rng);
+        self.transaction.replace_outputs(outputs);
+    }
+
+    pub fn total_input(&self) -> SigningResult<Amount> {
+        let mut total_in: Amount = 0;
+        for (request_index, utxo_args) in self.request_indices.iter().zip(self.utxo_args.iter()) {
+            total_in = total_in
+                .checked_add(utxo_args.amount)
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction UTXO amounts is too big")
+                .with_context(|| SpecLocation::of_utxo(*request_index, utxo_args))?;
+        }
+        Ok(total_in)
     }
 
     pub fn total_output(&self) -> SigningResult<Amount> {
-        self.transaction
-            .outputs()
-            .iter()
-            .try_fold(0i64, |total_out, output| {
-                total_out.checked_add(output.value())
-            })
-            .or_tw_err(SigningErrorType::Error_tx_too_big)
-            .context("Sum of Transaction output amounts is too big")
+        let mut total_out: Amount = 0;
+        for (output_index, output) in self.transaction.outputs().iter().enumerate() {
+            total_out = total_out
+                .checked_add(output.value())
+                .or_tw_err(SigningErrorType::Error_tx_too_big)
+                .context("Sum of Transaction output amounts is too big")
+                .with_context(|| SpecLocation::of_output(output_index, output))?;
+        }
+        Ok(total_out)
     }
 }
 
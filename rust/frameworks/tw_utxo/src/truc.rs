@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Topology limits of the version 3 transactions, Topologically Restricted Until Confirmation (TRUC).
+//! Nodes don't relay a TRUC transaction violating them, so the limits must be checked before broadcasting.
+//! https://github.com/bitcoin/bips/blob/master/bip-0431.mediawiki
+
+use crate::constants::{TRUC_CHILD_MAX_VSIZE, TRUC_MAX_VSIZE, TRUC_VERSION};
+use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+
+/// An unconfirmed transaction spent by the checked one.
+/// The confirmed parents are not restricted, so they must not be passed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnconfirmedParent {
+    /// The parent TXID in the same byte order as [`crate::transaction::transaction_parts::OutPoint::hash`].
+    pub txid: H256,
+    pub version: i32,
+    pub vsize: usize,
+    /// The number of the unconfirmed parents of this parent.
+    pub unconfirmed_parents: usize,
+}
+
+impl UnconfirmedParent {
+    pub fn is_truc(&self) -> bool {
+        self.version == TRUC_VERSION
+    }
+}
+
+/// Checks if the transaction can be relayed along with its unconfirmed parents:
+/// * A TRUC transaction is at most [`TRUC_MAX_VSIZE`].
+/// * A TRUC transaction may spend one unconfirmed TRUC parent only, which itself has no unconfirmed parents
+///   and is at most [`TRUC_MAX_VSIZE`].
+/// * Such a TRUC child is at most [`TRUC_CHILD_MAX_VSIZE`].
+/// * Neither a TRUC transaction spends an unconfirmed non-TRUC one, nor vice versa.
+///
+/// `vsize` is passed explicitly, so the transaction can be checked before it's signed,
+/// e.g. with the estimated `vsize`. Every parent must be spent by the transaction.
+pub fn check_topology<Transaction: TransactionInterface>(
+    tx: &Transaction,
+    vsize: usize,
+    unconfirmed_parents: &[UnconfirmedParent],
+) -> SigningResult<()> {
+    for parent in unconfirmed_parents {
+        let is_spent = tx
+            .inputs()
+            .iter()
+            .any(|input| input.previous_output().hash == parent.txid);
+        if !is_spent {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Unconfirmed parent {} is not spent by the transaction",
+                parent.txid
+            ));
+        }
+    }
+
+    if tx.version() != TRUC_VERSION {
+        if unconfirmed_parents.iter().any(UnconfirmedParent::is_truc) {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Version {} transaction cannot spend an unconfirmed TRUC transaction",
+                tx.version()
+            ));
+        }
+        return Ok(());
+    }
+
+    if vsize > TRUC_MAX_VSIZE {
+        return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+            "TRUC transaction vsize {vsize} exceeds {TRUC_MAX_VSIZE} vbytes"
+        ));
+    }
+
+    let parent = match unconfirmed_parents {
+        [] => return Ok(()),
+        [parent] => parent,
+        _ => {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "TRUC transaction may have only one unconfirmed parent, but {} given",
+                unconfirmed_parents.len()
+            ))
+        },
+    };
+
+    if !parent.is_truc() {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "TRUC transaction cannot spend an unconfirmed version {} transaction",
+            parent.version
+        ));
+    }
+    if parent.vsize > TRUC_MAX_VSIZE {
+        return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+            "Unconfirmed TRUC parent vsize {} exceeds {TRUC_MAX_VSIZE} vbytes",
+            parent.vsize
+        ));
+    }
+    if parent.unconfirmed_parents > 0 {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("TRUC transaction cannot spend a parent having unconfirmed parents itself");
+    }
+    if vsize > TRUC_CHILD_MAX_VSIZE {
+        return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+            "TRUC child transaction vsize {vsize} exceeds {TRUC_CHILD_MAX_VSIZE} vbytes"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Weight units (WU) of the standard transaction parts and the fee formulas.
+//!
+//! The functions don't need any [`Script`](crate::script::Script) or
+//! [`Transaction`](crate::transaction::standard_transaction::Transaction) to be constructed,
+//! so the planning math can be embedded on its own. The transaction weight, `vsize` and
+//! the fee estimation are computed by the same functions, so the numbers can't drift apart.
+
+use crate::transaction::transaction_parts::Amount;
+
+/// The base (non-witness) data is counted 4 times, the witness data is counted once.
+/// https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#transaction-size-calculations
+pub const WITNESS_SCALE_FACTOR: usize = 4;
+
+/// `version` and `locktime` fields.
+pub const TX_VERSION_LOCKTIME_SIZE: usize = 4 + 4;
+
+/// Segwit marker and flag. They are part of the witness data, so not scaled.
+pub const WITNESS_MARKER_FLAG_SIZE: usize = 2;
+
+/// `previous_output` (txid and vout) and `sequence` fields of an input.
+pub const OUTPOINT_SEQUENCE_SIZE: usize = 32 + 4 + 4;
+
+/// `value` field of an output.
+pub const OUTPUT_VALUE_SIZE: usize = 8;
+
+/// The largest DER-encoded ECDSA signature followed by the sighash type byte.
+pub const ECDSA_SIG_MAX_LEN: usize = 73;
+
+/// A Schnorr signature followed by an explicit sighash type byte.
+/// The signature is 64 bytes long if `SIGHASH_DEFAULT` is used.
+pub const SCHNORR_SIG_MAX_LEN: usize = 65;
+
+pub const COMPRESSED_PUBKEY_LEN: usize = 33;
+
+/// Size of the `CompactSize` integer, e.g. a list length prefix.
+pub const fn compact_size_len(n: u64) -> usize {
+    if n <= 0xFC {
+        1
+    } else if n <= 0xFFFF {
+        1 + 2
+    } else if n <= 0xFFFF_FFFF {
+        1 + 4
+    } else {
+        1 + 8
+    }
+}
+
+/// Size of the script that pushes `data_len` bytes onto the stack.
+pub const fn push_data_len(data_len: usize) -> usize {
+    let opcodes = if data_len <= 75 {
+        // OP_PUSHBYTES_N
+        1
+    } else if data_len <= 0xFF {
+        // OP_PUSHDATA1 <u8>
+        1 + 1
+    } else if data_len <= 0xFFFF {
+        // OP_PUSHDATA2 <u16>
+        1 + 2
+    } else {
+        // OP_PUSHDATA4 <u32>
+        1 + 4
+    };
+    opcodes + data_len
+}
+
+/// Size of a witness item of `item_len` bytes with its length prefix.
+pub const fn witness_item_size(item_len: usize) -> usize {
+    compact_size_len(item_len as u64) + item_len
+}
+
+/// The standard ways to claim a UTXO, the spending data size of which is known in advance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InputClaim {
+    /// `<sig>` scriptSig.
+    P2pk,
+    /// `<sig> <compressed pubkey>` scriptSig.
+    P2pkh,
+    /// `<sig> <compressed pubkey>` witness.
+    P2wpkh,
+    /// `<schnorr sig>` witness.
+    P2trKeyPath,
+    /// `<schnorr sig> <script> <control block>` witness of a leaf signed by a single key.
+    /// The control block is `33 + 32 * depth` bytes long, where `depth` is the leaf depth in the tree.
+    P2trScriptPath {
+        script_len: usize,
+        control_block_len: usize,
+    },
+}
+
+impl InputClaim {
+    /// The signature length assumed by the fee estimation: the spending data must not be
+    /// larger than estimated.
+    pub const fn max_sig_len(self) -> usize {
+        match self {
+            InputClaim::P2pk | InputClaim::P2pkh | InputClaim::P2wpkh => ECDSA_SIG_MAX_LEN,
+            InputClaim::P2trKeyPath | InputClaim::P2trScriptPath { .. } => SCHNORR_SIG_MAX_LEN,
+        }
+    }
+
+    /// Size of the scriptSig without its length prefix.
+    pub const fn script_sig_len(self, sig_len: usize) -> usize {
+        match self {
+            InputClaim::P2pk => push_data_len(sig_len),
+            InputClaim::P2pkh => push_data_len(sig_len) + push_data_len(COMPRESSED_PUBKEY_LEN),
+            InputClaim::P2wpkh | InputClaim::P2trKeyPath | InputClaim::P2trScriptPath { .. } => 0,
+        }
+    }
+
+    /// Size of the serialized witness including the items count, `0` if there are no witness items.
+    pub const fn witness_size(self, sig_len: usize) -> usize {
+        match self {
+            InputClaim::P2pk | InputClaim::P2pkh => 0,
+            InputClaim::P2wpkh => {
+                compact_size_len(2)
+                    + witness_item_size(sig_len)
+                    + witness_item_size(COMPRESSED_PUBKEY_LEN)
+            },
+            InputClaim::P2trKeyPath => compact_size_len(1) + witness_item_size(sig_len),
+            InputClaim::P2trScriptPath {
+                script_len,
+                control_block_len,
+            } => {
+                compact_size_len(3)
+                    + witness_item_size(sig_len)
+                    + witness_item_size(script_len)
+                    + witness_item_size(control_block_len)
+            },
+        }
+    }
+
+    /// Weight of the input signed with a signature of `sig_len` bytes including the sighash type.
+    /// See [`input_weight`] for `segwit_tx`.
+    pub const fn weight_with_sig_len(self, sig_len: usize, segwit_tx: bool) -> usize {
+        input_weight(
+            self.script_sig_len(sig_len),
+            self.witness_size(sig_len),
+            segwit_tx,
+        )
+    }
+
+    /// Weight of the input as estimated by the planner, i.e. with the largest signature.
+    pub const fn weight(self, segwit_tx: bool) -> usize {
+        self.weight_with_sig_len(self.max_sig_len(), segwit_tx)
+    }
+}
+
+/// Weight of an input with the given scriptSig (without the length prefix) and witness sizes.
+///
+/// `witness_size` is the serialized witness including the items count, `0` if there are no witness items.
+/// If the transaction has witness data (`segwit_tx`), the inputs without witness items
+/// are serialized with the zero items count that adds 1 WU.
+pub const fn input_weight(script_sig_len: usize, witness_size: usize, segwit_tx: bool) -> usize {
+    let base_size =
+        OUTPOINT_SEQUENCE_SIZE + compact_size_len(script_sig_len as u64) + script_sig_len;
+    let witness_size = if segwit_tx && witness_size == 0 {
+        compact_size_len(0)
+    } else {
+        witness_size
+    };
+    base_size * WITNESS_SCALE_FACTOR + witness_size
+}
+
+/// The standard scriptPubkey classes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputClass {
+    /// `<compressed pubkey> OP_CHECKSIG`.
+    P2pk,
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    /// `OP_RETURN <data>` with a single data push.
+    OpReturn {
+        data_len: usize,
+    },
+}
+
+impl OutputClass {
+    /// Size of the scriptPubkey without its length prefix.
+    pub const fn script_len(self) -> usize {
+        match self {
+            OutputClass::P2pk => push_data_len(COMPRESSED_PUBKEY_LEN) + 1,
+            // OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+            OutputClass::P2pkh => 1 + 1 + push_data_len(20) + 1 + 1,
+            // OP_HASH160 <20 bytes> OP_EQUAL
+            OutputClass::P2sh => 1 + push_data_len(20) + 1,
+            // OP_0 <20 bytes>
+            OutputClass::P2wpkh => 1 + push_data_len(20),
+            // OP_0 <32 bytes>
+            OutputClass::P2wsh => 1 + push_data_len(32),
+            // OP_1 <32 bytes>
+            OutputClass::P2tr => 1 + push_data_len(32),
+            OutputClass::OpReturn { data_len } => 1 + push_data_len(data_len),
+        }
+    }
+
+    pub const fn weight(self) -> usize {
+        output_weight(self.script_len())
+    }
+}
+
+/// Size of an output with the given scriptPubkey size (without the length prefix).
+pub const fn output_size(script_len: usize) -> usize {
+    OUTPUT_VALUE_SIZE + compact_size_len(script_len as u64) + script_len
+}
+
+/// Weight of an output with the given scriptPubkey size (without the length prefix).
+/// Outputs have no witness data.
+pub const fn output_weight(script_len: usize) -> usize {
+    output_size(script_len) * WITNESS_SCALE_FACTOR
+}
+
+/// Weight of the transaction fields other than inputs and outputs:
+/// `version`, inputs and outputs counts, `locktime`, and the segwit marker and flag if `segwit_tx`.
+pub const fn tx_overhead_weight(input_count: usize, output_count: usize, segwit_tx: bool) -> usize {
+    let base_size = TX_VERSION_LOCKTIME_SIZE
+        + compact_size_len(input_count as u64)
+        + compact_size_len(output_count as u64);
+    let witness_size = if segwit_tx {
+        WITNESS_MARKER_FLAG_SIZE
+    } else {
+        0
+    };
+    base_size * WITNESS_SCALE_FACTOR + witness_size
+}
+
+/// Weight of a transaction of `base_size` bytes serialized without witness data,
+/// and of `total_size` bytes serialized with witness data.
+pub const fn weight_from_sizes(base_size: usize, total_size: usize) -> usize {
+    base_size * (WITNESS_SCALE_FACTOR - 1) + total_size
+}
+
+/// `vsize` in vbytes of a transaction of the given weight, rounded up.
+pub const fn vsize_from_weight(weight: usize) -> usize {
+    weight / WITNESS_SCALE_FACTOR + (weight % WITNESS_SCALE_FACTOR != 0) as usize
+}
+
+/// How [`fee_for_weight`] rounds fractional vbytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FeeRounding {
+    /// The `vsize` is rounded up first, then multiplied by the fee rate.
+    /// Used by the planner and Bitcoin Core.
+    #[default]
+    VsizeUp,
+    /// `weight * fee_rate / 4` is rounded up.
+    Up,
+    /// `weight * fee_rate / 4` is rounded down.
+    Down,
+}
+
+/// Fee of a transaction of the given weight, `None` on overflow.
+pub const fn fee_for_weight(
+    weight: usize,
+    fee_per_vbyte: Amount,
+    rounding: FeeRounding,
+) -> Option<Amount> {
+    const SCALE: Amount = WITNESS_SCALE_FACTOR as Amount;
+
+    let weight_fee = match rounding {
+        FeeRounding::VsizeUp => return fee_for_vsize(vsize_from_weight(weight), fee_per_vbyte),
+        FeeRounding::Up | FeeRounding::Down => {
+            match (weight as Amount).checked_mul(fee_per_vbyte) {
+                Some(weight_fee) => weight_fee,
+                None => return None,
+            }
+        },
+    };
+
+    let round_up = matches!(rounding, FeeRounding::Up) && weight_fee % SCALE != 0;
+    Some(weight_fee / SCALE + round_up as Amount)
+}
+
+/// Fee of a transaction of the given `vsize`, `None` on overflow.
+pub const fn fee_for_vsize(vsize: usize, fee_per_vbyte: Amount) -> Option<Amount> {
+    (vsize as Amount).checked_mul(fee_per_vbyte)
+}
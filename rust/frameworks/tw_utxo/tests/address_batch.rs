@@ -0,0 +1,196 @@
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey};
+use bitcoin::secp256k1::Secp256k1;
+use std::str::FromStr;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::{H160, H264};
+use tw_utxo::address::batch::{AddressBatch, AddressTemplate, DerivedAddress};
+use tw_utxo::address::segwit::SegwitAddress;
+use tw_utxo::chain_params::ChainParams;
+use tw_utxo::script::standard_script::conditions;
+
+/// BIP84 test vector, account `m/84'/0'/0'` of the "abandon ... about" mnemonic.
+const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+/// The same account key with the `xpub` version bytes.
+const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+fn batch(xpub: &str, template: &str, chain_params: ChainParams) -> AddressBatch {
+    let template = AddressTemplate::from_str(template).unwrap();
+    AddressBatch::new(xpub, template, chain_params).unwrap()
+}
+
+fn addresses(derived: &[DerivedAddress]) -> Vec<&str> {
+    derived.iter().map(|d| d.address.as_str()).collect()
+}
+
+/// Derives a P2WPKH address one by one using the standard address types.
+fn single_p2wpkh(index: u32) -> (String, Vec<u8>) {
+    let secp = Secp256k1::verification_only();
+    let xpub = ExtendedPubKey::from_str(XPUB).unwrap();
+    let path = [
+        ChildNumber::from_normal_idx(0).unwrap(),
+        ChildNumber::from_normal_idx(index).unwrap(),
+    ];
+    let child = xpub.derive_pub(&secp, &path).unwrap();
+
+    let pubkey_hash = sha256_ripemd(&child.public_key.serialize());
+    let script_pubkey = conditions::new_p2wpkh(&H160::try_from(pubkey_hash.as_slice()).unwrap());
+    let address = SegwitAddress::new("bc".to_string(), script_pubkey.as_slice()[2..].to_vec())
+        .unwrap()
+        .to_string();
+    (address, script_pubkey.to_vec())
+}
+
+#[test]
+fn address_batch_bip_test_vectors() {
+    let mainnet = ChainParams::bitcoin_mainnet();
+
+    // BIP84.
+    let receive = batch(ZPUB, "wpkh(0/*)", mainnet.clone())
+        .derive_range(0..2)
+        .unwrap();
+    assert_eq!(
+        addresses(&receive),
+        [
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+        ]
+    );
+    assert_eq!(receive[1].index, 1);
+    assert_eq!(
+        receive[0].public_key,
+        H264::from("0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c")
+    );
+
+    let change = batch(ZPUB, "wpkh(1/*)", mainnet.clone())
+        .derive_range(0..1)
+        .unwrap();
+    assert_eq!(
+        addresses(&change),
+        ["bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"]
+    );
+
+    // Version bytes of the extended key do not affect the result.
+    let testnet = batch(XPUB, "wpkh(0/*)", ChainParams::bitcoin_testnet())
+        .derive_range(1..2)
+        .unwrap();
+    assert_eq!(
+        addresses(&testnet),
+        ["tb1qnjg0jd8228aq7egyzacy8cys3knf9xvrn9d67m"]
+    );
+
+    // BIP44.
+    let bip44_xpub = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+    let p2pkh = batch(bip44_xpub, "pkh(0/*)", mainnet.clone())
+        .derive_range(0..1)
+        .unwrap();
+    assert_eq!(addresses(&p2pkh), ["1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"]);
+
+    // P2SH-P2WPKH of the BIP84 key, generated with an independent implementation.
+    let p2sh = batch(ZPUB, "sh(wpkh(0/*))", mainnet.clone())
+        .derive_range(0..1)
+        .unwrap();
+    assert_eq!(addresses(&p2sh), ["3GtVZYzsKF6Feikdjd4bDyPdAiyeHANY9b"]);
+
+    // BIP86.
+    let bip86_xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+    let p2tr = batch(bip86_xpub, "tr(0/*)", mainnet)
+        .derive_range(0..2)
+        .unwrap();
+    assert_eq!(
+        addresses(&p2tr),
+        [
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+        ]
+    );
+    assert_eq!(
+        p2tr[0].script_pubkey.to_vec(),
+        tw_encoding::hex::decode(
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        )
+        .unwrap()
+    );
+}
+
+/// Derives 10,000 P2WPKH addresses in parallel and spot-checks the result against
+/// single-address derivation.
+#[test]
+fn address_batch_10000_p2wpkh() {
+    const COUNT: u32 = 10_000;
+
+    let batch = batch(ZPUB, "wpkh(0/*)", ChainParams::bitcoin_mainnet());
+
+    let derived = batch.derive_range(0..COUNT).unwrap();
+
+    assert_eq!(derived.len(), COUNT as usize);
+    assert!(derived
+        .iter()
+        .enumerate()
+        .all(|(i, address)| address.index == i as u32));
+
+    // Pseudo-random indexes.
+    for index in [0, 7, 511, 512, 1234, 4999, 5000, 8191, 9999] {
+        let (expected_address, expected_script) = single_p2wpkh(index);
+        let actual = &derived[index as usize];
+        assert_eq!(actual.address, expected_address, "index {index}");
+        assert_eq!(
+            actual.script_pubkey.as_slice(),
+            expected_script,
+            "index {index}"
+        );
+    }
+    // Derived with rust-bitcoin `ExtendedPubKey::derive_pub`.
+    assert_eq!(
+        derived[1234].address,
+        "bc1q4nm6g46ujzyjaeusralaz2nfv2rf04jjfyamkw"
+    );
+    assert_eq!(
+        derived[9999].address,
+        "bc1qhr6g4qhtaqlu8jvfex80gexwmxca2p65ujuwt8"
+    );
+}
+
+#[test]
+fn address_batch_scan_with_gap_limit() {
+    let batch = batch(ZPUB, "wpkh(0/*)", ChainParams::bitcoin_mainnet());
+    let used = ["bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"];
+    let used_index = 7;
+
+    let scanned = batch
+        .scan_with_gap_limit(0, 5, |derived| {
+            used.contains(&derived.address.as_str()) || derived.index == used_index
+        })
+        .unwrap();
+    // Used addresses are #1 and #7, so the last 5 addresses are #8..=#12.
+    let indexes: Vec<_> = scanned.iter().map(|derived| derived.index).collect();
+    assert_eq!(indexes, (0..=12).collect::<Vec<_>>());
+
+    // Nothing is used.
+    let scanned = batch.scan_with_gap_limit(100, 20, |_| false).unwrap();
+    assert_eq!(scanned.len(), 20);
+    assert_eq!(scanned[0].index, 100);
+
+    batch.scan_with_gap_limit(0, 0, |_| false).unwrap_err();
+}
+
+#[test]
+fn address_batch_invalid_params() {
+    let mainnet = ChainParams::bitcoin_mainnet();
+    let template = AddressTemplate::from_str("wpkh(0/*)").unwrap();
+
+    // Invalid checksum.
+    let mut invalid_xpub = XPUB.to_string();
+    invalid_xpub.pop();
+    invalid_xpub.push('W');
+    AddressBatch::new(&invalid_xpub, template, mainnet.clone()).unwrap_err();
+    AddressBatch::new("xpub", template, mainnet.clone()).unwrap_err();
+
+    // Hardened indexes cannot be derived from an extended public key.
+    let batch = AddressBatch::new(XPUB, template, mainnet).unwrap();
+    batch.derive_range(0x7FFF_FFFF..0x8000_0001).unwrap_err();
+    assert_eq!(
+        batch.derive_range(0x7FFF_FFFF..0x8000_0000).unwrap().len(),
+        1
+    );
+    assert!(batch.derive_range(5..5).unwrap().is_empty());
+}
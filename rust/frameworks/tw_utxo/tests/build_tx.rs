@@ -1,27 +1,62 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
 use tw_encoding::hex;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::sha2::sha256;
+use tw_hash::{H160, H256};
 
 use tw_keypair::ecdsa::secp256k1::PrivateKey;
 use tw_keypair::traits::SigningKeyTrait;
 use tw_keypair::{ecdsa, schnorr};
 use tw_misc::traits::ToBytesVec;
+use tw_utxo::chain_params::{ChainParams, ChainTag};
+use tw_utxo::constants::{
+    BITCOIN_MAX_MONEY, COIN, DOGECOIN_MAX_MONEY, MAX_SCRIPT_SIZE, MAX_STANDARD_TX_SIGOPS_COST,
+};
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
 use tw_utxo::modules::fee_estimator::FeeEstimator;
 use tw_utxo::modules::sighash_computer::SighashComputer;
 use tw_utxo::modules::tx_compiler::TxCompiler;
-use tw_utxo::sighash::SighashType;
-
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::tx_summary::TxSummaryBuilder;
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::script::standard_script::conditions::{self, ScriptPubkeyType};
+use tw_utxo::script::standard_script::opcodes::{OP_CHECKMULTISIG, OP_PUSHBYTES_0, OP_PUSHNUM_1};
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::sighash::{SighashBase, SighashType};
+use tw_utxo::signature::BitcoinSchnorrSignature;
+use tw_utxo::spending_data::tapscript_multisig::TapscriptMultisigSignatures;
+use tw_utxo::spending_data::SpendingDataConstructor;
+
+use tw_utxo::transaction::standard_transaction::builder::txid_from_str;
 use tw_utxo::transaction::standard_transaction::builder::txid_from_str_and_rev;
 use tw_utxo::transaction::standard_transaction::builder::OutputBuilder;
-use tw_utxo::transaction::standard_transaction::builder::TransactionBuilder;
 use tw_utxo::transaction::standard_transaction::builder::UtxoBuilder;
-use tw_utxo::transaction::transaction_interface::TransactionInterface;
-
-const SATS_PER_VBYTE: i64 = 20;
+use tw_utxo::transaction::standard_transaction::builder::{RbfPolicy, TransactionBuilder};
+use tw_utxo::transaction::standard_transaction::{
+    Transaction, TransactionInput, TransactionOutput,
+};
+use tw_utxo::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
+use tw_utxo::transaction::transaction_parts::{Amount, OutPoint};
+
+const SATS_PER_VBYTE: Amount = 20;
+
+/// Transaction builder that keeps the final `0xffffffff` input sequences
+/// the expected transactions below have been created with.
+fn tx_builder() -> TransactionBuilder {
+    let mut builder = TransactionBuilder::new();
+    builder.rbf(RbfPolicy::Disable);
+    builder
+}
 
 #[track_caller]
 fn verify_fee<Transaction: TransactionInterface>(
     tx: &Transaction,
-    fee_per_vbyte: i64,
-    expected: i64,
+    fee_per_vbyte: Amount,
+    expected: Amount,
 ) {
     let actual = FeeEstimator::estimate_fee(tx, fee_per_vbyte).unwrap();
     assert_eq!(actual, expected);
@@ -55,7 +90,7 @@ fn build_tx_input_legacy_output_legacy() {
 
     let output1 = OutputBuilder::new(50 * 100_000_000 - 1_000_000).p2pkh(&bob_ecdsa_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -106,7 +141,7 @@ fn build_tx_input_legacy_output_segwit() {
 
     let output1 = OutputBuilder::new(50 * 100_000_000 - 1_000_000).p2wpkh(&bob_ecdsa_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -157,7 +192,7 @@ fn build_tx_input_segwit_output_segwit() {
     let output1 =
         OutputBuilder::new(50 * 100_000_000 - 1_000_000 - 1_000_000).p2wpkh(&alice_ecdsa_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -208,7 +243,7 @@ fn build_tx_input_legacy_output_taproot() {
     let output1 =
         OutputBuilder::new(50 * 100_000_000 - 1_000_000).p2tr_key_path(&bob_schnorr_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -251,14 +286,14 @@ fn build_tx_input_taproot_output_taproot() {
         .prev_txid(txid)
         .prev_index(0)
         .amount(50 * 100_000_000 - 1_000_000)
-        .sighash_type(SighashType::default())
+        .sighash_type(SighashType::sighash_default())
         .p2tr_key_path(&bob_pubkey)
         .unwrap();
 
     let output1 = OutputBuilder::new(50 * 100_000_000 - 1_000_000 - 1_000_000)
         .p2tr_key_path(&alice_schnorr_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -276,6 +311,345 @@ fn build_tx_input_taproot_output_taproot() {
     assert_eq!(encoded, "02000000000101ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffff01806de72901000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c20140ec2d3910d41506b60aaa20520bb72f15e2d2cbd97e3a8e26ee7bad5f4c56b0f2fb0ceaddac33cb2813a33ba017ba6b1d011bab74a0426f12a2bcf47b4ed5bc8600000000")
 }
 
+/// `SIGHASH_DEFAULT` is allowed for Taproot inputs only, it has the `SIGHASH_ALL` semantics,
+/// but commits to a different digest than an explicit `SIGHASH_ALL`.
+#[test]
+fn build_tx_sighash_default() {
+    let bob_private_key =
+        hex::decode("26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227").unwrap();
+    let alice_pubkey =
+        hex::decode("0351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058").unwrap();
+
+    let alice_schnorr_pubkey = schnorr::PublicKey::try_from(alice_pubkey.as_slice()).unwrap();
+    let bob_private_key = schnorr::PrivateKey::try_from(bob_private_key.as_slice()).unwrap();
+    let bob_pubkey = bob_private_key.public();
+    let bob_ecdsa_pubkey =
+        ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.compressed().as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+            .unwrap();
+    let utxo_builder = |sighash_ty: SighashType| {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(50 * 100_000_000 - 1_000_000)
+            .sighash_type(sighash_ty)
+    };
+
+    let sign_p2tr = |sighash_ty: SighashType| {
+        let (utxo1, arg1) = utxo_builder(sighash_ty).p2tr_key_path(&bob_pubkey).unwrap();
+        let output1 = OutputBuilder::new(50 * 100_000_000 - 1_000_000 - 1_000_000)
+            .p2tr_key_path(&alice_schnorr_pubkey);
+
+        let mut builder = tx_builder();
+        builder.push_input(utxo1, arg1).push_output(output1);
+        let unsigned_tx = builder.build().unwrap();
+
+        let preimage = SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+        let sighash = preimage.sighashes.into_iter().next().unwrap().sighash;
+        let tweaked = bob_private_key.tweak(None).no_aux_rand();
+        let sig = tweaked.sign(sighash).unwrap();
+
+        let tx = TxCompiler::compile(unsigned_tx, &[sig.to_vec()]).unwrap();
+        (sighash, hex::encode(tx.encode_out(), false))
+    };
+
+    // SIGHASH_DEFAULT is accepted on a P2TR input.
+    let sighash_default = SighashType::from_u32(0).unwrap();
+    let (default_sighash, default_tx) = sign_p2tr(sighash_default);
+    // An explicit SIGHASH_ALL commits to another digest, and keeps its sighash byte.
+    let (all_sighash, all_tx) = sign_p2tr(SighashType::default());
+    assert_ne!(default_sighash, all_sighash);
+    assert_eq!(all_tx.len(), default_tx.len() + 2);
+    assert!(all_tx.ends_with("0100000000"), "{all_tx}");
+    // See `build_tx_input_taproot_output_taproot`. The 64 byte signature has no sighash byte.
+    assert_eq!(default_tx, "02000000000101ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffff01806de72901000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c20140ec2d3910d41506b60aaa20520bb72f15e2d2cbd97e3a8e26ee7bad5f4c56b0f2fb0ceaddac33cb2813a33ba017ba6b1d011bab74a0426f12a2bcf47b4ed5bc8600000000");
+
+    // SIGHASH_DEFAULT is rejected on P2WPKH and P2PKH inputs.
+    let err = utxo_builder(sighash_default)
+        .p2wpkh(&bob_ecdsa_pubkey)
+        .err()
+        .unwrap();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_sighash_default_not_allowed
+    );
+    let err = utxo_builder(sighash_default)
+        .p2pkh(&bob_ecdsa_pubkey)
+        .err()
+        .unwrap();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_sighash_default_not_allowed
+    );
+
+    // SIGHASH_DEFAULT is the default for P2TR inputs only.
+    let utxo_builder = || {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(50 * 100_000_000 - 1_000_000)
+    };
+    let (_, p2tr_arg) = utxo_builder().p2tr_key_path(&bob_pubkey).unwrap();
+    assert_eq!(p2tr_arg.sighash_ty, sighash_default);
+    let (_, p2wpkh_arg) = utxo_builder().p2wpkh(&bob_ecdsa_pubkey).unwrap();
+    assert_eq!(p2wpkh_arg.sighash_ty, SighashType::default());
+    let (_, p2pkh_arg) = utxo_builder().p2pkh(&bob_ecdsa_pubkey).unwrap();
+    assert_eq!(p2pkh_arg.sighash_ty, SighashType::default());
+}
+
+#[test]
+fn build_tx_missing_input_amount() {
+    let private_key = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+    let ecdsa_pubkey = PrivateKey::try_from(private_key).unwrap().public();
+    let schnorr_pubkey = schnorr::PrivateKey::try_from(private_key).unwrap().public();
+    let txid =
+        txid_from_str_and_rev("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+            .unwrap();
+
+    let utxo_builder = || {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .sighash_type(SighashType::default())
+    };
+    let assert_missing_amount = |err: SigningError| {
+        assert_eq!(
+            *err.error_type(),
+            SigningErrorType::Error_missing_input_amount
+        );
+    };
+
+    // Segwit and Taproot signatures commit to the amount.
+    let err = utxo_builder()
+        .amount(0)
+        .p2wpkh(&ecdsa_pubkey)
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string()
+            .contains("Invalid amount of the input spending output #0"),
+        "{err}"
+    );
+    assert_missing_amount(err);
+    let err = utxo_builder()
+        .amount(0)
+        .p2tr_key_path(&schnorr_pubkey)
+        .err()
+        .unwrap();
+    assert_missing_amount(err);
+    let err = utxo_builder().p2wpkh(&ecdsa_pubkey).err().unwrap();
+    assert_missing_amount(err);
+
+    // Legacy signatures do not commit to the amount.
+    utxo_builder().amount(0).p2pkh(&ecdsa_pubkey).unwrap();
+    // But the amount must be set still.
+    let err = utxo_builder().p2pkh(&ecdsa_pubkey).err().unwrap();
+    assert_missing_amount(err);
+
+    // Zero amount can be allowed explicitly.
+    let (utxo, arg) = utxo_builder()
+        .amount(0)
+        .allow_zero_amount()
+        .p2wpkh(&ecdsa_pubkey)
+        .unwrap();
+    let mut builder = tx_builder();
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(0).p2wpkh(&ecdsa_pubkey));
+    let unsigned_tx = builder.build().unwrap();
+    SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+
+    // The signer refuses zero amount inputs too.
+    let (utxo, mut arg) = utxo_builder()
+        .amount(0)
+        .allow_zero_amount()
+        .p2wpkh(&ecdsa_pubkey)
+        .unwrap();
+    arg.allow_zero_amount = false;
+    let mut builder = tx_builder();
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(0).p2wpkh(&ecdsa_pubkey));
+    let unsigned_tx = builder.build().unwrap();
+    let err = SighashComputer::preimage_tx(&unsigned_tx).err().unwrap();
+    assert!(err.to_string().contains("input #0"), "{err}");
+    assert_missing_amount(err);
+}
+
+#[test]
+fn build_tx_chain_mismatch() {
+    let ecdsa_pubkey = PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+    let txid = txid_from_str_and_rev(TXID).unwrap();
+
+    let build_tx = |utxo_chains: &[ChainTag], signer_chain: ChainTag| {
+        let mut builder = tx_builder();
+        builder.chain(signer_chain);
+        for (prev_index, utxo_chain) in utxo_chains.iter().enumerate() {
+            let (utxo, arg) = UtxoBuilder::new()
+                .prev_txid(txid)
+                .prev_index(prev_index as u32)
+                .amount(10_000)
+                .sighash_type(SighashType::default())
+                .chain(*utxo_chain)
+                .p2wpkh(&ecdsa_pubkey)
+                .unwrap();
+            builder.push_input(utxo, arg);
+        }
+        builder.push_output(OutputBuilder::new(5_000).p2wpkh(&ecdsa_pubkey));
+        builder.build()
+    };
+
+    // Bitcoin is used by default.
+    let (_, arg) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .amount(10_000)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&ecdsa_pubkey)
+        .unwrap();
+    assert_eq!(arg.chain, ChainTag::Bitcoin);
+
+    let unsigned_tx = build_tx(
+        &[ChainTag::Litecoin, ChainTag::Litecoin],
+        ChainTag::Litecoin,
+    );
+    assert!(unsigned_tx
+        .unwrap()
+        .input_args()
+        .iter()
+        .all(|arg| arg.chain == ChainTag::Litecoin));
+
+    // Bitcoin Cash args can't be signed by a Bitcoin signer.
+    let err = build_tx(
+        &[ChainTag::Bitcoin, ChainTag::BitcoinCash],
+        ChainTag::Bitcoin,
+    )
+    .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    let message = err.to_string();
+    assert!(message.contains("UTXO #1"), "{message}");
+    assert!(
+        message.contains("built for Bitcoin Cash, but the signer is configured for Bitcoin"),
+        "{message}"
+    );
+
+    // And vice versa.
+    let err = build_tx(&[ChainTag::Bitcoin], ChainTag::Litecoin).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("built for Bitcoin, but the signer is configured for Litecoin"),
+        "{err}"
+    );
+}
+
+#[test]
+fn build_tx_segwit_disabled() {
+    // The signed transaction of `build_tx_input_segwit_output_segwit`.
+    let segwit_tx = "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000";
+    let legacy_tx = "02000000016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237300000000";
+    let txid = "7553540501d96f986a96564aef9949d284353757f544cc001b60449e1abc52c3";
+
+    let bitcoin = ChainParams::bitcoin_mainnet();
+    let no_segwit = ChainParams {
+        segwit_enabled: false,
+        ..ChainParams::bitcoin_mainnet()
+    };
+
+    // The same transaction is serialized without the marker, flag and witness data.
+    let tx = Transaction::from_bytes(&hex::decode(segwit_tx).unwrap()).unwrap();
+    assert!(tx.has_witness());
+    assert_eq!(hex::encode(tx.encode_out_for(&bitcoin), false), segwit_tx);
+    assert_eq!(hex::encode(tx.encode_out_for(&no_segwit), false), legacy_tx);
+
+    // The TXID is the hash of the serialization without the witness data on any chain.
+    assert_eq!(hex::encode(tx.txid(), false), txid);
+
+    let private_key = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+    let ecdsa_pubkey = PrivateKey::try_from(private_key).unwrap().public();
+    let prev_txid =
+        txid_from_str_and_rev("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+            .unwrap();
+    let utxo = || {
+        UtxoBuilder::new()
+            .prev_txid(prev_txid)
+            .prev_index(0)
+            .amount(10_000)
+            .sighash_type(SighashType::default())
+    };
+    let build_tx = |(utxo, arg)| {
+        let mut builder = tx_builder();
+        builder
+            .segwit_enabled(false)
+            .push_input(utxo, arg)
+            .push_output(OutputBuilder::new(5_000).p2pkh(&ecdsa_pubkey));
+        builder.build()
+    };
+
+    // Only the legacy inputs can be spent.
+    build_tx(utxo().p2pkh(&ecdsa_pubkey).unwrap()).unwrap();
+
+    let err = build_tx(utxo().p2wpkh(&ecdsa_pubkey).unwrap()).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_segwit_not_supported
+    );
+    let message = err.to_string();
+    assert!(message.contains("UTXO #0"), "{message}");
+
+    let schnorr_pubkey =
+        schnorr::PublicKey::try_from(ecdsa_pubkey.compressed().as_slice()).unwrap();
+    let err = build_tx(utxo().p2tr_key_path(&schnorr_pubkey).unwrap()).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_segwit_not_supported
+    );
+}
+
+#[test]
+fn build_tx_allowed_sighash_types() {
+    let zcash = ChainParams::zcash_mainnet();
+    let bitcoin = ChainParams::bitcoin_mainnet();
+
+    let private_key = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+    let ecdsa_pubkey = PrivateKey::try_from(private_key).unwrap().public();
+    let prev_txid =
+        txid_from_str_and_rev("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+            .unwrap();
+    let build_tx = |params: &ChainParams, sighash_ty: SighashType| {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(prev_txid)
+            .prev_index(0)
+            .amount(10_000)
+            .sighash_type(sighash_ty)
+            .p2pkh(&ecdsa_pubkey)
+            .unwrap();
+        let mut builder = tx_builder();
+        builder
+            .segwit_enabled(params.segwit_enabled)
+            .allowed_sighash_types(params.allowed_sighash_types.clone())
+            .push_input(utxo, arg)
+            .push_output(OutputBuilder::new(5_000).p2pkh(&ecdsa_pubkey));
+        builder.build()
+    };
+    let single = SighashType::from_u32(0x03).unwrap();
+
+    // Bitcoin accepts every defined sighash type.
+    build_tx(&bitcoin, SighashType::default()).unwrap();
+    build_tx(&bitcoin, single).unwrap();
+
+    // Zcash accepts `SIGHASH_ALL` only.
+    build_tx(&zcash, SighashType::default()).unwrap();
+    let err = build_tx(&zcash, single).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_sighash_type_not_allowed
+    );
+    let message = err.to_string();
+    assert!(message.contains("UTXO #0"), "{message}");
+}
+
 #[test]
 fn build_tx_input_segwit_output_brc20_transfer_commit() {
     let alice_private_key =
@@ -306,7 +680,7 @@ fn build_tx_input_segwit_output_brc20_transfer_commit() {
 
     let output2 = OutputBuilder::new(16_400).p2wpkh(&alice_ecdsa_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder
         .push_input(utxo1, arg1.clone())
         .push_output(output1)
@@ -356,7 +730,7 @@ fn build_tx_input_brc20_transfer_commit_output_brc20_transfer_reveal() {
 
     let output1 = OutputBuilder::new(546).p2wpkh(&alice_ecdsa_pubkey);
 
-    let mut builder = TransactionBuilder::new();
+    let mut builder = tx_builder();
     builder.push_input(utxo1, arg1.clone()).push_output(output1);
     let unsigned_tx = builder.build().unwrap();
 
@@ -372,5 +746,855 @@ fn build_tx_input_brc20_transfer_commit_output_brc20_transfer_reveal() {
     let tx = TxCompiler::compile(unsigned_tx, &[sig.to_vec()]).unwrap();
 
     let encoded = hex::encode(tx.encode_out(), false);
-    assert_eq!(encoded, "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d03406a35548b8fa4620028e021a944c1d3dc6e947243a7bfc901bf63fefae0d2460efa149a6440cab51966aa4f09faef2d1e5efcba23ab4ca6e669da598022dbcfe35b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000")
+    assert_eq!(encoded, "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d0340694b812b91c4143c759d717109283fda9a195629d3a742a8132165cad13232fd2ef50c050198f404fbc56ae6923c111c417c8bf53af8fca9f5b076c8e0ec39795b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000")
+}
+
+/// Transaction on Bitcoin Cash Mainnet:
+/// https://blockchair.com/bitcoin-cash/transaction/96ee20002b34e468f9d3c5ee54f6a8ddaa61c118889c4f35395c2cd93ba5bbb4
+#[test]
+fn build_tx_input_legacy_fork_id_bitcoin_cash() {
+    let private_key =
+        hex::decode("7fdafb9db5bc501f2096e7d13d331dc7a75d9594af3d251313ba8b6200f4e384").unwrap();
+    let private_key = PrivateKey::try_from(private_key.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str("e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05").unwrap();
+
+    let (utxo1, arg1) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(2)
+        .amount(5151)
+        .sighash_type(SighashType::with_fork_id(SighashBase::All, 0).unwrap())
+        .p2pkh(&private_key.public())
+        .unwrap();
+
+    let output1 = OutputBuilder::new(600)
+        .custom_script_pubkey(Script::from(
+            hex::decode("76a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ac").unwrap(),
+        ))
+        .unwrap();
+    let output2 = OutputBuilder::new(4325)
+        .custom_script_pubkey(Script::from(
+            hex::decode("76a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac").unwrap(),
+        ))
+        .unwrap();
+
+    let mut builder = tx_builder();
+    builder
+        .version(1)
+        .push_input(utxo1, arg1)
+        .push_output(output1)
+        .push_output(output2);
+    let unsigned_tx = builder.build().unwrap();
+
+    // Compute the primage.
+    let preimage = SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+
+    // Sign the sighash.
+    let sighash = preimage.sighashes.into_iter().next().unwrap().sighash;
+    let sig = private_key.sign(sighash).unwrap();
+
+    let tx = TxCompiler::compile(unsigned_tx, &[sig.to_vec()]).unwrap();
+
+    let encoded = hex::encode(tx.encode_out(), false);
+    assert_eq!(encoded, "0100000001e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05020000006b483045022100b70d158b43cbcded60e6977e93f9a84966bc0cec6f2dfd1463d1223a90563f0d02207548d081069de570a494d0967ba388ff02641d91cadb060587ead95a98d4e3534121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff0258020000000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ace5100000000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00000000");
+}
+
+/// BitcoinGold uses the same `SIGHASH_FORK_ID` semantics, but with the fork id value of 79.
+#[test]
+fn build_tx_input_segwit_fork_id_bitcoin_gold() {
+    let private_key =
+        hex::decode("cbe13a79b82ec7f8871b336a64fd8d531f598e7c9022e29c67e824cfd54af57f").unwrap();
+    let private_key = PrivateKey::try_from(private_key.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str("5727794fa2b94aa22a226e206130524201ede9b50e032526e713c848493a890f").unwrap();
+
+    let (utxo1, arg1) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .sequence(0xfffffffd)
+        .amount(10_000)
+        .sighash_type(SighashType::with_fork_id(SighashBase::All, 79).unwrap())
+        .p2wpkh(&private_key.public())
+        .unwrap();
+
+    let output1 = OutputBuilder::new(5000)
+        .custom_script_pubkey(Script::from(
+            hex::decode("0014db746a75d9aae8995d135b1e19a04d7765242a8f").unwrap(),
+        ))
+        .unwrap();
+    let output2 = OutputBuilder::new(4859)
+        .custom_script_pubkey(Script::from(
+            hex::decode("0014ebae10950c8a35a506e0e265b928305233e802ab").unwrap(),
+        ))
+        .unwrap();
+
+    let mut builder = tx_builder();
+    builder
+        .version(1)
+        .lock_time(0x00098971)
+        .push_input(utxo1, arg1)
+        .push_output(output1)
+        .push_output(output2);
+    let unsigned_tx = builder.build().unwrap();
+
+    // Compute the primage.
+    let preimage = SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+
+    // Sign the sighash.
+    let sighash = preimage.sighashes.into_iter().next().unwrap().sighash;
+    let sig = private_key.sign(sighash).unwrap();
+
+    let tx = TxCompiler::compile(unsigned_tx, &[sig.to_vec()]).unwrap();
+
+    let encoded = hex::encode(tx.encode_out(), false);
+    assert_eq!(encoded, "010000000001015727794fa2b94aa22a226e206130524201ede9b50e032526e713c848493a890f0000000000fdffffff028813000000000000160014db746a75d9aae8995d135b1e19a04d7765242a8ffb12000000000000160014ebae10950c8a35a506e0e265b928305233e802ab0247304402202b371b7cae885463c06357d1fc6ca95ab155613f212711bc7fb115500654946d0220430af77cbbb30afe7d7dcaccb72a55da802ee0a2bfea790dfe7c4e1a4c53fd7d412103e00b5dec8078d526fba090247bd92db6b67a4dd1953b788cea9b52de9471b8cf71890900");
+}
+
+#[test]
+fn build_tx_output_custom_script_pubkey_p2wsh() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    let build_tx = |output: TransactionOutput| {
+        let (utxo1, arg1) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(50 * 100_000_000)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&bob_ecdsa_pubkey)
+            .unwrap();
+
+        let mut builder = tx_builder();
+        builder.push_input(utxo1, arg1).push_output(output);
+        builder.build().unwrap().estimate_transaction()
+    };
+
+    // P2WSH scriptPubkey given as a raw script.
+    let p2wsh_script = "00205dd8d3d2ac2fdbb8b0ff2ed3e0cd8e5f5ad7b6ea3cd4f7b4f11ed3c0e92c4f5b";
+    let output = OutputBuilder::new(50 * 100_000_000 - 1_000_000)
+        .custom_script_pubkey(Script::from(hex::decode(p2wsh_script).unwrap()))
+        .unwrap();
+    let custom_tx = build_tx(output);
+
+    let summary = TxSummaryBuilder::summarize(&custom_tx);
+    assert_eq!(summary.outputs[0].script_type, ScriptPubkeyType::P2WSH);
+    assert_eq!(summary.outputs[0].script_pubkey, p2wsh_script);
+    assert!(summary.warnings.is_empty());
+
+    // The fee is estimated using the actual scriptPubkey length:
+    // P2WSH scriptPubkey is 34 bytes long, P2WPKH is 22 bytes long.
+    let output = OutputBuilder::new(50 * 100_000_000 - 1_000_000).p2wpkh(&bob_ecdsa_pubkey);
+    let p2wpkh_tx = build_tx(output);
+    assert_eq!(custom_tx.vsize(), p2wpkh_tx.vsize() + 12);
+    verify_fee(
+        &custom_tx,
+        SATS_PER_VBYTE,
+        (p2wpkh_tx.vsize() as Amount + 12) * SATS_PER_VBYTE,
+    );
+}
+
+#[test]
+fn build_tx_output_custom_script_pubkey_non_standard() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    let (utxo1, arg1) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .amount(50 * 100_000_000)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&bob_ecdsa_pubkey)
+        .unwrap();
+
+    // Anyone-can-spend `OP_TRUE` scriptPubkey is non-standard, but allowed.
+    let output1 = OutputBuilder::new(1_000)
+        .custom_script_pubkey(Script::from(vec![OP_PUSHNUM_1]))
+        .unwrap();
+
+    let mut builder = tx_builder();
+    builder.push_input(utxo1, arg1).push_output(output1);
+    let unsigned_tx = builder.build().unwrap();
+
+    let summary = TxSummaryBuilder::summarize(unsigned_tx.transaction());
+    assert_eq!(summary.outputs[0].script_type, ScriptPubkeyType::Custom);
+    assert_eq!(summary.outputs[0].to_string(), "1000 custom 51");
+    assert_eq!(summary.warnings.len(), 1);
+
+    // Scripts larger than `MAX_SCRIPT_SIZE` are rejected.
+    OutputBuilder::new(1_000)
+        .custom_script_pubkey(Script::from(vec![OP_PUSHNUM_1; MAX_SCRIPT_SIZE + 1]))
+        .unwrap_err();
+}
+
+#[test]
+fn build_tx_input_script_sig_non_minimal_push() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    let (utxo1, arg1) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .amount(50 * 100_000_000)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&bob_ecdsa_pubkey)
+        .unwrap();
+    let output1 = OutputBuilder::new(50 * 100_000_000 - 1_000_000).p2wpkh(&bob_ecdsa_pubkey);
+
+    let mut builder = tx_builder();
+    builder.push_input(utxo1, arg1).push_output(output1);
+    let mut tx = builder.build().unwrap().transaction().clone();
+
+    // Minimal pushes: `OP_0 <pubkey>`.
+    let mut script_sig = Script::new();
+    script_sig.push(OP_PUSHBYTES_0);
+    script_sig.push_slice(&bob_pubkey);
+    tx.inputs_mut()[0].set_script_sig(script_sig);
+    assert!(TxSummaryBuilder::summarize(&tx).warnings.is_empty());
+
+    // `OP_1` encoded as a push of `0x01`.
+    tx.inputs_mut()[0].set_script_sig(Script::from(vec![0x01, 0x01]));
+    let summary = TxSummaryBuilder::summarize(&tx);
+    assert_eq!(
+        summary.warnings,
+        vec![
+            "Input #0 has a non-standard scriptSig and may not be relayed: \
+            Instruction #0 is pushed with OP_PUSHBYTES_1, but the minimal encoding is OP_1"
+                .to_string()
+        ]
+    );
+}
+
+/// 2-of-3 Tapscript multisig script-path spend, where the 1st and 3rd keys sign.
+/// The expected transaction is generated independently with the BIP-340/BIP-341 reference algorithms.
+#[test]
+fn build_tx_input_tapscript_multisig_2_of_3() {
+    let private_keys = [
+        "698bbe6e0024686eabc881e119c8d5e6160fccef4c2c769de1391c79f63e62d3",
+        "6e4967305163acf7432aeba270c6d76d4b826ffbcf916313998ddd43f508bbd6",
+        "b42f90fe50ee3fe99206b9bffbb54966abac60fa5c8dc67d2222aa7193525bb2",
+    ]
+    .map(|key| {
+        schnorr::PrivateKey::try_from(hex::decode(key).unwrap().as_slice())
+            .unwrap()
+            .no_aux_rand()
+    });
+    let pubkeys: Vec<H256> = private_keys
+        .iter()
+        .map(|key| key.public().x_only().bytes())
+        .collect();
+
+    // BIP-341 NUMS point is used as an internal key to disable key-path spending.
+    let internal_pubkey =
+        hex::decode("0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0").unwrap();
+    let internal_pubkey = schnorr::PublicKey::try_from(internal_pubkey.as_slice()).unwrap();
+    let alice_pubkey =
+        hex::decode("0351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058").unwrap();
+    let alice_pubkey = schnorr::PublicKey::try_from(alice_pubkey.as_slice()).unwrap();
+
+    let payload = conditions::new_tapscript_multisig(2, &pubkeys).unwrap();
+    assert_eq!(hex::encode(payload.as_slice(), false), "20b72b591eab4fc012805de4021367fe40181404024d566265bad8102a10469bc2ac208a7568e43638cf843fff4f672960508be600c26a2d989e7e43054ce68d93221dba207cab1380d99f305236c1aa5d715e12ae2f8f07d63230d1183a2bbabca525962fba529c");
+
+    // The script tree consists of the only leaf.
+    let merkle_root =
+        H256::from("613528a85aaf77444029f6c0b1ad6d736680fdb283b0bfc0c3bdbbb25aee102e");
+    let control_block =
+        hex::decode("c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0").unwrap();
+
+    let txid =
+        txid_from_str_and_rev("92845a705764c6f73535342ebf5850ea52e7ca5eae0dfeb995753a74bb490739")
+            .unwrap();
+
+    let (utxo1, arg1) = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .amount(100_000)
+        .sighash_type(SighashType::default())
+        .p2tr_script_path(
+            &internal_pubkey,
+            payload.clone(),
+            control_block.clone(),
+            &merkle_root,
+        )
+        .unwrap();
+    assert_eq!(
+        hex::encode(arg1.prevout_script_pubkey.as_slice(), false),
+        "51202ea441cb6376e5d9c9d3f6a1903ae1ddf441d83cad59644b550bf1010e6ff993"
+    );
+
+    let output1 = OutputBuilder::new(90_000).p2tr_key_path(&alice_pubkey);
+
+    let mut builder = tx_builder();
+    builder.push_input(utxo1, arg1).push_output(output1);
+    let unsigned_tx = builder.build().unwrap();
+
+    // Compute the primage. The sighash commits to the multisig leaf.
+    let preimage = SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+    let sighash = preimage.sighashes.into_iter().next().unwrap().sighash;
+    assert_eq!(
+        sighash,
+        H256::from("b7ef676cde55f1e11ca1703f8f7978eab699fffe856722cdab4840bc31338859")
+    );
+
+    // Collect partial signatures of the 1st and 3rd co-signers signed with non-tweaked private keys.
+    let mut multisig = TapscriptMultisigSignatures::new(2, pubkeys.clone(), control_block).unwrap();
+    assert_eq!(multisig.payload(), &payload);
+    for i in [2, 0] {
+        let sig = private_keys[i].sign(sighash).unwrap();
+        let sig = BitcoinSchnorrSignature::new(sig, SighashType::default()).unwrap();
+        assert!(!multisig.is_complete());
+        multisig.add_signature(&pubkeys[i], sig).unwrap();
+    }
+    assert!(multisig.is_complete());
+
+    let spending_data = multisig.into_spending_data().unwrap();
+    let tx = TxCompiler::compile_spending_data(unsigned_tx, vec![spending_data]).unwrap();
+
+    let encoded = hex::encode(tx.encode_out(), false);
+    assert_eq!(encoded, "02000000000101390749bb743a7595b9fe0dae5ecae752ea5058bf2e343535f7c66457705a84920000000000ffffffff01905f010000000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c205403291163b88de3827677ba71b78a34d91306d9ad26023133491282f23ca23de79095b5fcdd6b0c17f38d200c7737e5dc997527363f73f1ec3364c9c457f444b8a004024507a6d3a6ae0626c3f00806859bfbf9e435673fc067f50a57db866965ebc5a597a1eed6ca14e35fd3a09c51f3c270fc42962009ae7ded9ea9ddc6b73ab8f0e6820b72b591eab4fc012805de4021367fe40181404024d566265bad8102a10469bc2ac208a7568e43638cf843fff4f672960508be600c26a2d989e7e43054ce68d93221dba207cab1380d99f305236c1aa5d715e12ae2f8f07d63230d1183a2bbabca525962fba529c21c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac000000000");
+}
+
+/// The NUMS internal key tweaked with the single multisig leaf produces an odd output key parity.
+/// The parity is carried from the tweak to the control block of the signed witness.
+#[test]
+fn build_tx_input_p2tr_script_path_odd_parity() {
+    let pubkeys: Vec<H256> = [
+        "b72b591eab4fc012805de4021367fe40181404024d566265bad8102a10469bc2",
+        "8a7568e43638cf843fff4f672960508be600c26a2d989e7e43054ce68d93221d",
+        "7cab1380d99f305236c1aa5d715e12ae2f8f07d63230d1183a2bbabca525962f",
+    ]
+    .map(H256::from)
+    .to_vec();
+    let payload = conditions::new_tapscript_multisig(2, &pubkeys).unwrap();
+
+    let internal_pubkey =
+        hex::decode("0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0").unwrap();
+    let internal_pubkey = schnorr::PublicKey::try_from(internal_pubkey.as_slice()).unwrap();
+    let merkle_root =
+        H256::from("613528a85aaf77444029f6c0b1ad6d736680fdb283b0bfc0c3bdbbb25aee102e");
+
+    let output_key = internal_pubkey.tweak(Some(merkle_root));
+    assert_eq!(output_key.parity, schnorr::Parity::Odd);
+
+    let txid =
+        txid_from_str_and_rev("92845a705764c6f73535342ebf5850ea52e7ca5eae0dfeb995753a74bb490739")
+            .unwrap();
+    let utxo_builder = || {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(100_000)
+            .sighash_type(SighashType::default())
+    };
+
+    // The script tree consists of the only leaf, so the merkle branch is empty.
+    let (_utxo, arg) = utxo_builder()
+        .p2tr_script_path_with_merkle_branch(&internal_pubkey, payload.clone(), &[])
+        .unwrap();
+    assert_eq!(
+        hex::encode(arg.prevout_script_pubkey.as_slice(), false),
+        "51202ea441cb6376e5d9c9d3f6a1903ae1ddf441d83cad59644b550bf1010e6ff993"
+    );
+
+    // The control block is the last witness item.
+    let signer = schnorr::PrivateKey::try_from(
+        "698bbe6e0024686eabc881e119c8d5e6160fccef4c2c769de1391c79f63e62d3",
+    )
+    .unwrap()
+    .no_aux_rand();
+    let sig = signer.sign(H256::default()).unwrap();
+    let sig = BitcoinSchnorrSignature::new(sig, SighashType::default()).unwrap();
+    let SpendingDataConstructor::Schnorr(constructor) = arg.spending_data_constructor else {
+        panic!("Expected a Schnorr spending data constructor");
+    };
+    let spending_data = constructor.get_spending_data(&sig);
+    let control_block = spending_data.witness.as_items().last().unwrap();
+    assert_eq!(
+        hex::encode(control_block.as_slice(), false),
+        "c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0"
+    );
+    assert_eq!(control_block.as_slice()[0] & 1, output_key.parity.to_u8());
+
+    // A control block with the even parity bit doesn't match the output key.
+    let even_control_block =
+        hex::decode("c050929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0").unwrap();
+    let err = utxo_builder()
+        .p2tr_script_path(
+            &internal_pubkey,
+            payload.clone(),
+            even_control_block,
+            &merkle_root,
+        )
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // A control block committing to another internal key is rejected too.
+    let mut wrong_key_control_block = control_block.to_vec();
+    wrong_key_control_block[1] ^= 1;
+    utxo_builder()
+        .p2tr_script_path(
+            &internal_pubkey,
+            payload,
+            wrong_key_control_block,
+            &merkle_root,
+        )
+        .unwrap_err();
+}
+
+/// Dogecoin allows amounts that are far beyond the Bitcoin's 21M BTC limit.
+#[test]
+fn build_tx_max_money_per_chain() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    // 10 billion DOGE.
+    let amount = 10_000_000_000 * COIN;
+    assert!(amount > BITCOIN_MAX_MONEY);
+
+    let make_builder = || {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(amount)
+            .sighash_type(SighashType::default())
+            .p2pkh(&bob_ecdsa_pubkey)
+            .unwrap();
+        let output = OutputBuilder::new(amount).p2pkh(&bob_ecdsa_pubkey).unwrap();
+
+        let mut builder = tx_builder();
+        builder.push_input(utxo, arg).push_output(output);
+        builder
+    };
+
+    // Bitcoin's max money is used by default.
+    make_builder().build().unwrap_err();
+    make_builder()
+        .max_money(BITCOIN_MAX_MONEY)
+        .build()
+        .unwrap_err();
+
+    let unsigned_tx = make_builder()
+        .max_money(DOGECOIN_MAX_MONEY)
+        .build()
+        .unwrap();
+    assert_eq!(unsigned_tx.total_input().unwrap(), amount);
+    assert_eq!(unsigned_tx.transaction().outputs[0].value, amount);
+}
+
+#[test]
+fn build_tx_amounts_sum_overflow() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    let utxo = |index: u32, amount: Amount| {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(index)
+            .amount(amount)
+            .sighash_type(SighashType::default())
+            .p2pkh(&bob_ecdsa_pubkey)
+            .unwrap()
+    };
+    let output = |amount: Amount| OutputBuilder::new(amount).p2pkh(&bob_ecdsa_pubkey).unwrap();
+
+    // Every single amount fits the max money, but their sum overflows `u64`.
+    let (utxo1, arg1) = utxo(0, Amount::MAX - 1);
+    let (utxo2, arg2) = utxo(1, 2);
+    let mut builder = tx_builder();
+    builder
+        .max_money(Amount::MAX)
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_output(output(1_000));
+    builder.build().unwrap_err();
+
+    let (utxo1, arg1) = utxo(0, 1_000);
+    let mut builder = tx_builder();
+    builder
+        .max_money(Amount::MAX)
+        .push_input(utxo1, arg1)
+        .push_output(output(Amount::MAX))
+        .push_output(output(1));
+    builder.build().unwrap_err();
+
+    // The sum doesn't overflow `u64`, but exceeds the max money.
+    let (utxo1, arg1) = utxo(0, DOGECOIN_MAX_MONEY);
+    let mut builder = tx_builder();
+    builder
+        .max_money(DOGECOIN_MAX_MONEY)
+        .push_input(utxo1, arg1)
+        .push_output(output(DOGECOIN_MAX_MONEY))
+        .push_output(output(1));
+    builder.build().unwrap_err();
+}
+
+/// The same UTXOs are spent with an implicit fee, and with the fee declared as an explicit output.
+/// The explicit fee output is paid for as any other output.
+#[test]
+fn plan_tx_explicit_fee_output() {
+    let alice_pubkey =
+        hex::decode("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536").unwrap();
+    let alice_ecdsa_pubkey =
+        ecdsa::secp256k1::PublicKey::try_from(alice_pubkey.as_slice()).unwrap();
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+    // Anyone-can-spend `OP_TRUE` as a well-known fee scriptPubkey.
+    let fee_script_pubkey = Script::from(vec![OP_PUSHNUM_1]);
+
+    let make_request = |explicit_fee_output: Option<TransactionOutput>| {
+        let (utxo1, arg1) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(100_000)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&alice_ecdsa_pubkey)
+            .unwrap();
+        let output = OutputBuilder::new(50_000).p2wpkh(&bob_ecdsa_pubkey);
+        let change_output = OutputBuilder::new(0).p2wpkh(&alice_ecdsa_pubkey);
+
+        let mut builder = tx_builder();
+        builder.push_input(utxo1, arg1).push_output(output);
+        PlanRequest {
+            ty: RequestType::SendExact {
+                unsigned_tx: builder.build().unwrap(),
+                change_output: Some(change_output),
+                input_selector: InputSelector::InOrder,
+            },
+            fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+            fee_per_vbyte: SATS_PER_VBYTE,
+            explicit_fee_output,
+            selection_policy: SelectionPolicy::Any,
+            output_shuffle_seed: None,
+            unconfirmed_parents: Vec::new(),
+        }
+    };
+
+    let implicit = TxPlanner::plan(make_request(None)).unwrap();
+    let fee_output = OutputBuilder::new(0)
+        .custom_script_pubkey(fee_script_pubkey.clone())
+        .unwrap();
+    let explicit = TxPlanner::plan(make_request(Some(fee_output))).unwrap();
+
+    // The fee output (8 bytes value, 1 byte script length, 1 byte script) is estimated too,
+    // and its fee is taken from the change.
+    const FEE_OUTPUT_VSIZE: usize = 10;
+    assert_eq!(
+        explicit.plan.vsize_estimate,
+        implicit.plan.vsize_estimate + FEE_OUTPUT_VSIZE
+    );
+    for plan in [&implicit.plan, &explicit.plan] {
+        assert_eq!(
+            plan.fee_estimate,
+            plan.vsize_estimate as Amount * SATS_PER_VBYTE
+        );
+        assert_eq!(plan.total_spend, plan.total_send + plan.fee_estimate);
+    }
+    assert_eq!(explicit.plan.total_spend, implicit.plan.total_spend);
+    assert_eq!(
+        explicit.plan.change,
+        implicit.plan.change - FEE_OUTPUT_VSIZE as Amount * SATS_PER_VBYTE
+    );
+
+    // The fee is implied by the input/output difference.
+    let implicit_tx = implicit.unsigned_tx.transaction();
+    assert_eq!(implicit_tx.outputs.len(), 2);
+    assert_eq!(
+        implicit.unsigned_tx.total_input().unwrap() - implicit.unsigned_tx.total_output().unwrap(),
+        implicit.plan.fee_estimate
+    );
+
+    // The fee is declared as the last output, inputs are equal to outputs.
+    let explicit_tx = explicit.unsigned_tx.transaction();
+    assert_eq!(explicit_tx.outputs.len(), 3);
+    assert_eq!(explicit_tx.outputs[2].script_pubkey, fee_script_pubkey);
+    assert_eq!(explicit_tx.outputs[2].value, explicit.plan.fee_estimate);
+    assert_eq!(
+        explicit.unsigned_tx.total_input().unwrap(),
+        explicit.unsigned_tx.total_output().unwrap()
+    );
+
+    // The summary reports the fee output as the fee rather than a payment.
+    let implicit_summary = TxSummaryBuilder::summarize(implicit_tx);
+    let explicit_summary =
+        TxSummaryBuilder::summarize_with_fee_output(explicit_tx, &fee_script_pubkey);
+    assert_eq!(implicit_summary.fee, None);
+    assert_eq!(explicit_summary.fee, Some(explicit.plan.fee_estimate));
+    assert_eq!(
+        explicit_summary.outputs.len(),
+        implicit_summary.outputs.len()
+    );
+    assert!(explicit_summary.warnings.is_empty());
+}
+
+/// `OP_m <pubkey>... OP_n OP_CHECKMULTISIG` script with dummy public keys.
+fn multisig_script(m: u8, n: u8) -> Script {
+    let mut script = Script::new();
+    script.push_int(m as i64);
+    for i in 0..n {
+        script.push_slice(&[vec![0x02], vec![i + 1; 32]].concat());
+    }
+    script.push_int(n as i64);
+    script.push(OP_CHECKMULTISIG);
+    script
+}
+
+fn sigop_tx_input(index: u32, script_sig: Script, witness_items: Vec<Script>) -> TransactionInput {
+    let mut witness = Witness::new();
+    for item in witness_items {
+        witness.push_item(item);
+    }
+    TransactionInput {
+        previous_output: OutPoint {
+            hash: H256::from("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e"),
+            index,
+        },
+        sequence: u32::MAX,
+        script_sig,
+        witness,
+    }
+}
+
+/// The expected values are computed by the Bitcoin Core's `GetTransactionSigOpCost` rules.
+#[test]
+fn tx_sigop_cost_p2sh_and_witness_multisig() {
+    let dummy_sig = Script::from(vec![0x30; 72]);
+    let redeem_script = multisig_script(2, 3);
+    let redeem_hash = H160::try_from(sha256_ripemd(redeem_script.as_slice()).as_slice()).unwrap();
+    let witness_hash = H256::try_from(sha256(redeem_script.as_slice()).as_slice()).unwrap();
+    let pubkey_hash = H160::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+
+    // P2SH 2-of-3 multisig: `OP_0 <sig> <sig> <redeem_script>`.
+    let mut p2sh_script_sig = Script::new();
+    p2sh_script_sig.push(OP_PUSHBYTES_0);
+    p2sh_script_sig.push_slice(dummy_sig.as_slice());
+    p2sh_script_sig.push_slice(dummy_sig.as_slice());
+    p2sh_script_sig.push_slice(redeem_script.as_slice());
+
+    // P2SH-P2WPKH: `<p2wpkh_program>`.
+    let mut p2sh_p2wpkh_script_sig = Script::new();
+    p2sh_p2wpkh_script_sig.push_slice(conditions::new_p2wpkh(&pubkey_hash).as_slice());
+
+    let inputs = vec![
+        sigop_tx_input(0, p2sh_script_sig, Vec::new()),
+        sigop_tx_input(
+            1,
+            Script::new(),
+            vec![dummy_sig.clone(), Script::from(vec![0x02; 33])],
+        ),
+        sigop_tx_input(
+            2,
+            Script::new(),
+            vec![
+                Script::new(),
+                dummy_sig.clone(),
+                dummy_sig.clone(),
+                redeem_script.clone(),
+            ],
+        ),
+        sigop_tx_input(
+            3,
+            p2sh_p2wpkh_script_sig,
+            vec![dummy_sig.clone(), Script::from(vec![0x02; 33])],
+        ),
+    ];
+    let prevout_script_pubkeys = [
+        conditions::new_p2sh(&redeem_hash),
+        conditions::new_p2wpkh(&pubkey_hash),
+        conditions::new_p2wsh(&witness_hash),
+        conditions::new_p2sh(&redeem_hash),
+    ];
+
+    let tx = Transaction {
+        version: 2,
+        inputs,
+        outputs: vec![
+            OutputBuilder::new(1_000).p2wpkh_from_hash(&pubkey_hash),
+            OutputBuilder::new(1_000).p2sh_from_hash(&redeem_hash),
+        ],
+        locktime: 0,
+    };
+
+    // P2SH redeem script: 3 * 4, P2WPKH: 1, P2WSH: 3, P2SH-P2WPKH: 1.
+    assert_eq!(tx.total_sigop_cost(&prevout_script_pubkeys).unwrap(), 17);
+    // Every spent output scriptPubkey is required.
+    tx.total_sigop_cost(&prevout_script_pubkeys[..3])
+        .unwrap_err();
+}
+
+/// The expected values are computed by the Bitcoin Core's `GetTransactionSigOpCost` rules.
+#[test]
+fn tx_sigop_cost_bare_multisig() {
+    let dummy_sig = Script::from(vec![0x30; 72]);
+    let pubkey_hash = H160::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+
+    // Spending a bare 1-of-2 multisig: `OP_0 <sig>`.
+    let mut script_sig = Script::new();
+    script_sig.push(OP_PUSHBYTES_0);
+    script_sig.push_slice(dummy_sig.as_slice());
+
+    let tx = Transaction {
+        version: 2,
+        inputs: vec![sigop_tx_input(0, script_sig, Vec::new())],
+        outputs: vec![
+            OutputBuilder::new(1_000)
+                .custom_script_pubkey(multisig_script(1, 2))
+                .unwrap(),
+            OutputBuilder::new(1_000).p2pkh_from_hash(&pubkey_hash),
+        ],
+        locktime: 0,
+    };
+
+    // The spent bare multisig scriptPubkey is not counted.
+    // The created bare multisig is counted as 20 legacy sigops, P2PKH - as 1 sigop.
+    let prevout_script_pubkeys = [multisig_script(1, 2)];
+    assert_eq!(tx.total_sigop_cost(&prevout_script_pubkeys).unwrap(), 84);
+}
+
+#[test]
+fn plan_tx_sigop_cost_exceeds_standard_limit() {
+    let alice_pubkey =
+        hex::decode("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536").unwrap();
+    let alice_ecdsa_pubkey =
+        ecdsa::secp256k1::PublicKey::try_from(alice_pubkey.as_slice()).unwrap();
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+
+    let make_request = |outputs_count: usize| {
+        let (utxo1, arg1) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(10 * COIN)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&alice_ecdsa_pubkey)
+            .unwrap();
+
+        let mut builder = tx_builder();
+        builder.push_input(utxo1, arg1);
+        for _ in 0..outputs_count {
+            let output = OutputBuilder::new(1_000)
+                .custom_script_pubkey(multisig_script(1, 2))
+                .unwrap();
+            builder.push_output(output);
+        }
+
+        PlanRequest {
+            ty: RequestType::SendExact {
+                unsigned_tx: builder.build().unwrap(),
+                change_output: None,
+                input_selector: InputSelector::InOrder,
+            },
+            fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+            fee_per_vbyte: SATS_PER_VBYTE,
+            explicit_fee_output: None,
+            selection_policy: SelectionPolicy::Any,
+            output_shuffle_seed: None,
+            unconfirmed_parents: Vec::new(),
+        }
+    };
+
+    // Every bare multisig output costs 80, plus 1 for the P2WPKH input.
+    let max_outputs = (MAX_STANDARD_TX_SIGOPS_COST - 1) / 80;
+    TxPlanner::plan(make_request(max_outputs)).unwrap();
+    TxPlanner::plan(make_request(max_outputs + 1)).unwrap_err();
+}
+
+#[test]
+fn build_tx_claim_type_mismatch() {
+    let bob_pubkey =
+        hex::decode("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").unwrap();
+    let bob_ecdsa_pubkey = ecdsa::secp256k1::PublicKey::try_from(bob_pubkey.as_slice()).unwrap();
+    let bob_pubkey_hash = H160::try_from(sha256_ripemd(&bob_pubkey).as_slice()).unwrap();
+
+    let native_p2wpkh = conditions::new_p2wpkh(&bob_pubkey_hash);
+    let redeem_script_hash =
+        H160::try_from(sha256_ripemd(native_p2wpkh.as_slice()).as_slice()).unwrap();
+    let nested_p2wpkh = conditions::new_p2sh(&redeem_script_hash);
+
+    let txid =
+        txid_from_str_and_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+            .unwrap();
+    let native_utxo = || {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(0)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&bob_ecdsa_pubkey)
+            .unwrap()
+    };
+    let output = || OutputBuilder::new(40_000).p2wpkh(&bob_ecdsa_pubkey);
+
+    let build_tx = |utxo: TransactionInput, arg, utxo_script_pubkey: Script| {
+        let mut builder = tx_builder();
+        builder
+            .push_input_with_script_pubkey(utxo, arg, utxo_script_pubkey)
+            .push_output(output());
+        builder.build()
+    };
+
+    // The declared claim type matches the UTXO.
+    let (utxo, arg) = native_utxo();
+    build_tx(utxo, arg, native_p2wpkh.clone()).unwrap();
+
+    // Native P2WPKH is declared, but the UTXO is P2SH-wrapped.
+    let (utxo, arg) = native_utxo();
+    let err = build_tx(utxo, arg, nested_p2wpkh.clone()).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_claim_type_mismatch
+    );
+    assert!(err
+        .to_string()
+        .contains("declared=p2wpkh, detected=p2sh, input=0"));
+
+    // P2SH-wrapped P2WPKH is declared, but the UTXO is native.
+    let (utxo, mut arg) = native_utxo();
+    arg.prevout_script_pubkey = nested_p2wpkh;
+    let err = build_tx(utxo, arg, native_p2wpkh).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_claim_type_mismatch
+    );
+    assert!(err
+        .to_string()
+        .contains("declared=p2sh, detected=p2wpkh, input=0"));
+
+    // The claim type matches, but the UTXO belongs to another key.
+    let (utxo, arg) = native_utxo();
+    let other_p2wpkh = conditions::new_p2wpkh(&H160::default());
+    let err = build_tx(utxo, arg, other_p2wpkh).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_utxo);
 }
@@ -0,0 +1,266 @@
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_utxo::modules::claim_parser::{ClaimParser, ClaimType, DerMode, ParsedClaim};
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::transaction::standard_transaction::Transaction;
+
+const TX_170_R: &str = "4e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41";
+const TX_170_S: &str = "181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09";
+const TX_170_PUBKEY: &str = "0411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3";
+
+fn script(hex: &str) -> Script {
+    Script::from(hex.decode_hex().unwrap())
+}
+
+fn decode_tx(hex: &str) -> Transaction {
+    Transaction::from_bytes(&hex.decode_hex().unwrap()).unwrap()
+}
+
+/// Parses the claim of the `input_index` input of the transaction.
+fn parse_input(tx: &Transaction, input_index: usize, script_pubkey: &str) -> ParsedClaim {
+    let input = &tx.inputs[input_index];
+    ClaimParser::parse(&input.script_sig, &input.witness, &script(script_pubkey))
+}
+
+/// P2PK output of the block 9 coinbase.
+fn tx_170_script_pubkey() -> String {
+    format!("41{TX_170_PUBKEY}ac")
+}
+
+/// Mainnet tx f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16 (block 170).
+fn tx_170() -> Transaction {
+    decode_tx("0100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704000000004847304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d0901ffffffff0200ca9a3b00000000434104ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c1b7303b8a0626f1baded5c72a704f7e6cd84cac00286bee0000000043410411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3ac00000000")
+}
+
+#[test]
+fn test_claim_parser_p2pk() {
+    let tx = tx_170();
+    let claim = parse_input(&tx, 0, &tx_170_script_pubkey());
+
+    assert_eq!(claim.claim_type, ClaimType::P2PK);
+    assert_eq!(claim.signatures.len(), 1);
+    assert_eq!(claim.signatures[0].r.to_hex(), TX_170_R);
+    assert_eq!(claim.signatures[0].s.to_hex(), TX_170_S);
+    assert_eq!(claim.signatures[0].sighash_type, 0x01);
+    assert!(claim.signatures[0].is_strict_der);
+    assert!(!claim.has_non_canonical_signatures());
+    assert_eq!(claim.public_keys, vec![TX_170_PUBKEY.decode_hex().unwrap()]);
+    assert_eq!(claim.redeem_script, None);
+}
+
+#[test]
+fn test_claim_parser_non_canonical_signature() {
+    // Mainnet tx f7fdd091fa6d8f5e7a8c2458f5c38faffff2d3f1406b6e4fe2c99dcc0d2d1cbb,
+    // see Bitcoin Core `tx_valid.json`. Its signatures encode negative R and S integers,
+    // that OpenSSL used to accept before BIP66.
+    let tx = decode_tx("01000000023d6cf972d4dff9c519eff407ea800361dd0a121de1da8b6f4138a2f25de864b4000000008a4730440220ffda47bfc776bcd269da4832626ac332adfca6dd835e8ecd83cd1ebe7d709b0e022049cffa1cdc102a0b56e0e04913606c70af702a1149dc3b305ab9439288fee090014104266abb36d66eb4218a6dd31f09bb92cf3cfa803c7ea72c1fc80a50f919273e613f895b855fb7465ccbc8919ad1bd4a306c783f22cd3227327694c4fa4c1c439affffffff21ebc9ba20594737864352e95b727f1a565756f9d365083eb1a8596ec98c97b7010000008a4730440220503ff10e9f1e0de731407a4a245531c9ff17676eda461f8ceeb8c06049fa2c810220c008ac34694510298fa60b3f000df01caa244f165b727d4896eb84f81e46bcc4014104266abb36d66eb4218a6dd31f09bb92cf3cfa803c7ea72c1fc80a50f919273e613f895b855fb7465ccbc8919ad1bd4a306c783f22cd3227327694c4fa4c1c439affffffff01f0da5200000000001976a914857ccd42dded6df32949d4646dfa10a92458cfaa88ac00000000");
+    let script_pubkey = script("76a914bef80ecf3a44500fda1bc92176e442891662aed288ac");
+    let pubkey = "04266abb36d66eb4218a6dd31f09bb92cf3cfa803c7ea72c1fc80a50f919273e613f895b855fb7465ccbc8919ad1bd4a306c783f22cd3227327694c4fa4c1c439a";
+
+    // Negative R.
+    let input = &tx.inputs[0];
+    let claim = ClaimParser::parse(&input.script_sig, &input.witness, &script_pubkey);
+    assert_eq!(claim.claim_type, ClaimType::P2PKH);
+    assert_eq!(
+        claim.signatures[0].r.to_hex(),
+        "ffda47bfc776bcd269da4832626ac332adfca6dd835e8ecd83cd1ebe7d709b0e"
+    );
+    assert_eq!(
+        claim.signatures[0].s.to_hex(),
+        "49cffa1cdc102a0b56e0e04913606c70af702a1149dc3b305ab9439288fee090"
+    );
+    assert!(!claim.signatures[0].is_strict_der);
+    assert!(claim.has_non_canonical_signatures());
+    assert_eq!(claim.public_keys, vec![pubkey.decode_hex().unwrap()]);
+
+    // The signature is not recognized in strict mode.
+    let claim = ClaimParser::parse_with_mode(
+        &input.script_sig,
+        &input.witness,
+        &script_pubkey,
+        DerMode::Strict,
+    );
+    assert_eq!(claim, ParsedClaim::unknown());
+
+    // Negative S.
+    let claim = parse_input(&tx, 1, "76a914bef80ecf3a44500fda1bc92176e442891662aed288ac");
+    assert_eq!(claim.claim_type, ClaimType::P2PKH);
+    assert_eq!(
+        claim.signatures[0].s.to_hex(),
+        "c008ac34694510298fa60b3f000df01caa244f165b727d4896eb84f81e46bcc4"
+    );
+    assert!(claim.has_non_canonical_signatures());
+}
+
+#[test]
+fn test_claim_parser_p2pkh() {
+    // Bitcoin Cash mainnet tx 96ee20002b34e468f9d3c5ee54f6a8ddaa61c118889c4f35395c2cd93ba5bbb4.
+    let tx = decode_tx("0100000001e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05020000006b483045022100b70d158b43cbcded60e6977e93f9a84966bc0cec6f2dfd1463d1223a90563f0d02207548d081069de570a494d0967ba388ff02641d91cadb060587ead95a98d4e3534121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff0258020000000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ace5100000000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00000000");
+    let claim = parse_input(&tx, 0, "76a914aff1e0789e5fe316b729577665aa0a04d5b0f8c788ac");
+
+    assert_eq!(claim.claim_type, ClaimType::P2PKH);
+    let sig = &claim.signatures[0];
+    assert_eq!(
+        sig.r.to_hex(),
+        "b70d158b43cbcded60e6977e93f9a84966bc0cec6f2dfd1463d1223a90563f0d"
+    );
+    assert_eq!(
+        sig.s.to_hex(),
+        "7548d081069de570a494d0967ba388ff02641d91cadb060587ead95a98d4e353"
+    );
+    // SIGHASH_ALL | SIGHASH_FORKID.
+    assert_eq!(sig.sighash_type, 0x41);
+    assert!(sig.is_strict_der);
+    assert_eq!(
+        claim.public_keys,
+        vec![
+            "038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5b"
+                .decode_hex()
+                .unwrap()
+        ]
+    );
+
+    // The public key doesn't match the spent output.
+    let claim = parse_input(&tx, 0, "76a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ac");
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+}
+
+#[test]
+fn test_claim_parser_p2wpkh() {
+    // Mainnet tx 5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855.
+    let tx = decode_tx("010000000001027d4c6ade48ade606a8f9894222fb30f87b427c5861b0b7a293825ffc8bc1621f0100000000ffffffff8d0e94ed369601d13f32bd653071844dfe7139508d2e71d9c105d1faa58230b30100000000ffffffff01836d0000000000001600145360df8231ac5965147c9d90ca930a2aafb0523202483045022100f95f9ac5d39f4b47dcd8c86daaaeac86374258d9960f922333ba0d5fdaa15b7e0220761794672dc9fbd71398d608f72f5d21a0f6c1306c6b700ad0d82f747c221062012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c02483045022100eb6ba0dcc64af61b2186b7efdab1ff03784d585ee03437f9a53875e93429db080220015a268d308436d3564b83ceaed90bc7272ca164016298ea855d1936568002a7012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c00000000");
+    let script_pubkey = "001460d7ee599766db323fb1916c7f9e5d818aaf8c1b";
+    let pubkey = "03a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c";
+
+    let claim = parse_input(&tx, 0, script_pubkey);
+    assert_eq!(claim.claim_type, ClaimType::P2WPKH);
+    assert_eq!(
+        claim.signatures[0].r.to_hex(),
+        "f95f9ac5d39f4b47dcd8c86daaaeac86374258d9960f922333ba0d5fdaa15b7e"
+    );
+    assert_eq!(claim.public_keys, vec![pubkey.decode_hex().unwrap()]);
+
+    let claim = parse_input(&tx, 1, script_pubkey);
+    assert_eq!(claim.claim_type, ClaimType::P2WPKH);
+    assert_eq!(
+        claim.signatures[0].s.to_hex(),
+        "015a268d308436d3564b83ceaed90bc7272ca164016298ea855d1936568002a7"
+    );
+    assert_eq!(claim.signatures[0].sighash_type, 0x01);
+
+    // The witness is expected to be empty for a legacy output.
+    let claim = parse_input(&tx, 0, &tx_170_script_pubkey());
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+}
+
+#[test]
+fn test_claim_parser_p2sh_p2wpkh() {
+    // BIP143 P2SH-P2WPKH example.
+    let tx = decode_tx("01000000000101db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a5477010000001716001479091972186c449eb1ded22b78e40d009bdf0089feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac02473044022047ac8e878352d3ebbde1c94ce3a10d057c24175747116f8288e5d794d12d482f0220217f36a485cae903c713331d877c1f64677e3622ad4010726870540656fe9dcb012103ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a2687392040000");
+    let redeem_script = "001479091972186c449eb1ded22b78e40d009bdf0089";
+
+    let claim = parse_input(&tx, 0, "a9144733f37cf4db86fbc2efed2500b4f4e49f31202387");
+    assert_eq!(claim.claim_type, ClaimType::P2SHP2WPKH);
+    assert_eq!(claim.redeem_script, Some(script(redeem_script)));
+    assert_eq!(
+        claim.signatures[0].r.to_hex(),
+        "47ac8e878352d3ebbde1c94ce3a10d057c24175747116f8288e5d794d12d482f"
+    );
+    assert_eq!(claim.signatures[0].sighash_type, 0x01);
+    assert_eq!(
+        claim.public_keys,
+        vec![
+            "03ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a26873"
+                .decode_hex()
+                .unwrap()
+        ]
+    );
+
+    // The redeem script is expected to be pushed to the scriptSig.
+    let input = &tx.inputs[0];
+    let claim = ClaimParser::parse(
+        &Script::default(),
+        &input.witness,
+        &script("a9144733f37cf4db86fbc2efed2500b4f4e49f31202387"),
+    );
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+}
+
+#[test]
+fn test_claim_parser_p2sh_multisig() {
+    // 2-of-3 multisig of the `1`, `2` and `3` private keys, where the 1st and 3rd keys sign.
+    // The transaction is signed with rust-bitcoin 0.30.
+    let tx = decode_tx("0100000001bb1c2d0dcc9dc9e24f6e6b40f1d3f2ffaf8fc3f558248c7a5e8f6dfa91d0fdf700000000fdfd0000483045022100e1266cf3a6d3893965014f8db3ecaccf0fbe7f8febf304acf253d463ab94a6b802207b2760b933392e3e0acbdd136bd63f4add2c915fb8bb8cde8f4c6bf3199fbc6c0147304402205b7fa06072dcecd1b8ea485697cfbab64410a0b50c47ad15bbdd7cacea90ce7a022027814c7fbba82f2c74f4a16401b58b761c78f2aacaa5eb2267e2f202ca2e44f2014c6952210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f817982102c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee52102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f953aeffffffff01404b4c00000000001976a914857ccd42dded6df32949d4646dfa10a92458cfaa88ac00000000");
+    let redeem_script = "52210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f817982102c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee52102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f953ae";
+    let script_pubkey = script("a91415fc0754e73eb85d1cbce08786fadb7320ecb8dc87");
+
+    let input = &tx.inputs[0];
+    let claim = ClaimParser::parse(&input.script_sig, &input.witness, &script_pubkey);
+    assert_eq!(claim.claim_type, ClaimType::P2SHMultisig);
+    assert_eq!(claim.redeem_script, Some(script(redeem_script)));
+    assert_eq!(claim.public_keys.len(), 3);
+    assert_eq!(
+        claim.public_keys[1].to_hex(),
+        "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"
+    );
+
+    let signatures: Vec<_> = claim
+        .signatures
+        .iter()
+        .map(|sig| (sig.r.to_hex(), sig.is_strict_der))
+        .collect();
+    assert_eq!(
+        signatures,
+        vec![
+            (
+                "e1266cf3a6d3893965014f8db3ecaccf0fbe7f8febf304acf253d463ab94a6b8".to_string(),
+                true
+            ),
+            (
+                "5b7fa06072dcecd1b8ea485697cfbab64410a0b50c47ad15bbdd7cacea90ce7a".to_string(),
+                true
+            ),
+        ]
+    );
+
+    // Only one signature out of two required.
+    let script_sig = format!("00483045022100e1266cf3a6d3893965014f8db3ecaccf0fbe7f8febf304acf253d463ab94a6b802207b2760b933392e3e0acbdd136bd63f4add2c915fb8bb8cde8f4c6bf3199fbc6c014c69{redeem_script}");
+    let claim = ClaimParser::parse(&script(&script_sig), &Witness::default(), &script_pubkey);
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+}
+
+#[test]
+fn test_claim_parser_unknown() {
+    let sig = format!("47{}01", format_args!("30440220{TX_170_R}0220{TX_170_S}"));
+
+    // P2TR key-path claims are not supported.
+    let claim = ClaimParser::parse(
+        &Script::default(),
+        &Witness::default(),
+        &script("51205ee16f6144e2d46edea1427e1222ea879377e029b0c5d2e252517aee85948ec7"),
+    );
+    assert_eq!(claim, ParsedClaim::unknown());
+
+    // The scriptSig contains a non-push operation.
+    let claim = ClaimParser::parse(
+        &script(&format!("{sig}ac")),
+        &Witness::default(),
+        &script(&tx_170_script_pubkey()),
+    );
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+
+    // Not a signature.
+    let claim = ClaimParser::parse(
+        &script("03aabbcc"),
+        &Witness::default(),
+        &script(&tx_170_script_pubkey()),
+    );
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+
+    // Malformed push.
+    let claim = ClaimParser::parse(
+        &script(&sig[..20]),
+        &Witness::default(),
+        &script(&tx_170_script_pubkey()),
+    );
+    assert_eq!(claim.claim_type, ClaimType::Unknown);
+}
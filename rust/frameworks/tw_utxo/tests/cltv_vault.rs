@@ -0,0 +1,176 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::hasher::sha256_ripemd;
+use tw_keypair::ecdsa;
+use tw_utxo::constants::{MAX_BIP125_RBF_SEQUENCE, SEQUENCE_FINAL};
+use tw_utxo::modules::keys_manager::KeysManager;
+use tw_utxo::modules::sighash_computer::SighashComputer;
+use tw_utxo::modules::sighash_verifier::SighashVerifier;
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::modules::tx_summary::TxSummaryBuilder;
+use tw_utxo::script::standard_script::conditions::{self, ScriptPubkeyType};
+use tw_utxo::script::Script;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, RbfPolicy, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_parts::LockTime;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const VAULT_HEIGHT: u32 = 840_000;
+
+fn private_key() -> ecdsa::secp256k1::PrivateKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap()
+}
+
+fn vault_script() -> Script {
+    Script::cltv_p2pkh(LockTime::Height(VAULT_HEIGHT), &private_key().public())
+}
+
+/// Spends the vault to a P2WPKH output of the same key.
+fn spend_vault(
+    lock_time: Option<u32>,
+    rbf: Option<RbfPolicy>,
+) -> SigningResult<UnsignedTransaction<Transaction>> {
+    let pubkey = private_key().public();
+    let (utxo, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+        .prev_index(0)
+        .amount(100_000)
+        .sighash_type(SighashType::default())
+        .p2wsh_cltv_p2pkh(LockTime::Height(VAULT_HEIGHT), &pubkey)?;
+
+    let mut builder = TransactionBuilder::new();
+    if let Some(lock_time) = lock_time {
+        builder.lock_time(lock_time);
+    }
+    if let Some(rbf) = rbf {
+        builder.rbf(rbf);
+    }
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(99_000).p2wpkh(&pubkey));
+    builder.build()
+}
+
+/// Checks the spending of the vault UTXO the same way as the consensus rules do:
+/// the witness script commits to the P2WSH output, CLTV is satisfied,
+/// the public key matches the hash and the signature is valid.
+fn verify_vault_spend(unsigned_tx: &UnsignedTransaction<Transaction>, tx: &Transaction) {
+    let input = &tx.inputs[0];
+    let prevout_script_pubkey = &unsigned_tx.input_args()[0].prevout_script_pubkey;
+
+    let [sig, pubkey, witness_script] = input.witness.as_items() else {
+        panic!("Expected <sig> <pubkey> <witness_script> witness");
+    };
+    assert!(input.script_sig.is_empty());
+    assert_eq!(
+        Script::p2wsh_from_script(witness_script),
+        *prevout_script_pubkey
+    );
+
+    // `<lock_time> OP_CHECKLOCKTIMEVERIFY OP_DROP`
+    let (lock_time, pubkey_hash) = conditions::match_cltv_p2pkh(witness_script).unwrap();
+    assert!(lock_time.is_satisfied_by(tx.locktime));
+    assert_ne!(input.sequence, SEQUENCE_FINAL);
+
+    // `OP_DUP OP_HASH160 <pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG`
+    assert_eq!(
+        sha256_ripemd(pubkey.as_slice()).as_slice(),
+        pubkey_hash.as_slice()
+    );
+    let sighash = SighashComputer::preimage_tx(unsigned_tx).unwrap().sighashes[0].clone();
+    let (sighash_type, der) = sig.as_slice().split_last().unwrap();
+    assert_eq!(*sighash_type as u32, SighashType::default().raw_sighash());
+    SighashVerifier::<Transaction>::verify_ecdsa_signature(&sighash, pubkey.as_slice(), der)
+        .unwrap();
+}
+
+#[test]
+fn test_cltv_vault_mature_spending() {
+    let mut keys_manager = KeysManager::default();
+    keys_manager.add_ecdsa_private(private_key());
+
+    // The lock time is set to the vault height, the sequence enables it.
+    let unsigned_tx = spend_vault(None, None).unwrap();
+    assert_eq!(unsigned_tx.transaction().locktime, VAULT_HEIGHT);
+    assert_eq!(
+        unsigned_tx.transaction().inputs[0].sequence,
+        MAX_BIP125_RBF_SEQUENCE
+    );
+
+    let tx = TxSigner::sign_tx(spend_vault(None, None).unwrap(), &keys_manager).unwrap();
+    verify_vault_spend(&unsigned_tx, &tx);
+
+    // A later lock time satisfies the vault too.
+    let unsigned_tx = spend_vault(Some(VAULT_HEIGHT + 100), Some(RbfPolicy::Disable)).unwrap();
+    let tx = TxSigner::sign_tx(
+        spend_vault(Some(VAULT_HEIGHT + 100), Some(RbfPolicy::Disable)).unwrap(),
+        &keys_manager,
+    )
+    .unwrap();
+    assert_eq!(tx.locktime, VAULT_HEIGHT + 100);
+    verify_vault_spend(&unsigned_tx, &tx);
+}
+
+#[test]
+fn test_cltv_vault_premature_spending() {
+    // The block height is below the vault height.
+    let err = spend_vault(Some(VAULT_HEIGHT - 1), None).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // A timestamp lock time doesn't satisfy a block height lock.
+    let err = spend_vault(Some(1_700_000_000), None).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // The final sequence disables the lock time.
+    let err = spend_vault(None, Some(RbfPolicy::Custom(vec![SEQUENCE_FINAL]))).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // A transaction mined earlier than the vault height doesn't satisfy CLTV.
+    let lock_time = conditions::match_cltv_p2pkh(&vault_script()).unwrap().0;
+    assert!(!lock_time.is_satisfied_by(VAULT_HEIGHT - 1));
+    assert!(!lock_time.is_satisfied_by(0));
+    assert!(lock_time.is_satisfied_by(VAULT_HEIGHT));
+}
+
+#[test]
+fn test_cltv_vault_summary() {
+    let pubkey = private_key().public();
+    let (utxo, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+        .prev_index(0)
+        .amount(100_000)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&pubkey)
+        .unwrap();
+
+    // Lock the funds in the vault.
+    let vault_script = vault_script();
+    let mut builder = TransactionBuilder::new();
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(99_000).p2wsh(&vault_script));
+    let unsigned_tx = builder.build().unwrap();
+    let tx = unsigned_tx.transaction();
+
+    let summary = TxSummaryBuilder::summarize(tx);
+    assert_eq!(summary.outputs[0].script_type, ScriptPubkeyType::P2WSH);
+
+    let summary = TxSummaryBuilder::summarize_with_witness_scripts(tx, &[vault_script.clone()]);
+    assert_eq!(summary.outputs[0].script_type, ScriptPubkeyType::Timelocked);
+    assert!(summary.outputs[0]
+        .to_string()
+        .starts_with("99000 timelocked 0020"));
+    assert!(summary.warnings.is_empty());
+
+    // A bare timelocked scriptPubkey is non-standard.
+    assert_eq!(
+        ScriptPubkeyType::classify(&vault_script),
+        ScriptPubkeyType::Timelocked
+    );
+    assert!(!ScriptPubkeyType::Timelocked.is_standard());
+}
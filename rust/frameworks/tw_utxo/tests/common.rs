@@ -4,6 +4,10 @@ use bitcoin::key::UntweakedPublicKey;
 use bitcoin::{PubkeyHash, PublicKey, WPubkeyHash};
 use secp256k1::{hashes::Hash, XOnlyPublicKey};
 use tw_encoding::hex;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::modules::keys_manager::KeysManager;
+
+pub use tw_utxo::test_utils::fixtures::{PRIVATE_KEY, PSBT_V0, PSBT_V2, TXID};
 
 pub fn pubkey_hash_from_hex(hex: &str) -> PubkeyHash {
     PubkeyHash::from_byte_array(hex::decode(hex).unwrap().try_into().unwrap())
@@ -21,3 +25,17 @@ pub fn untweaked_pubkey(hex: &str) -> UntweakedPublicKey {
 pub fn txid_rev(hex: &str) -> Vec<u8> {
     hex::decode(hex).unwrap().into_iter().rev().collect()
 }
+
+/// Returns the keys manager of the [`PRIVATE_KEY`] ECDSA and Schnorr keys.
+pub fn keys_manager() -> KeysManager {
+    let mut keys_manager = KeysManager::default();
+    keys_manager
+        .add_ecdsa_private(ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap())
+        // Disable auxiliary randomness to get deterministic signatures.
+        .add_schnorr_private(
+            schnorr::PrivateKey::try_from(PRIVATE_KEY)
+                .unwrap()
+                .no_aux_rand(),
+        );
+    keys_manager
+}
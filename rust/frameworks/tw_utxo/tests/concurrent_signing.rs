@@ -0,0 +1,111 @@
+mod common;
+
+use crate::common::TXID;
+use std::sync::Arc;
+use std::thread;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::modules::keys_manager::KeysManager;
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const TX_COUNT: usize = 32;
+const THREADS: usize = 8;
+const ROUNDS: usize = 4;
+
+/// Every transaction is signed with its own private key.
+fn private_key(tx_index: usize) -> Vec<u8> {
+    let mut private_key = vec![0; 32];
+    private_key[31] = tx_index as u8 + 1;
+    private_key
+}
+
+/// Builds a transaction spending P2PKH, P2WPKH and P2TR key-path inputs of the `tx_index` key.
+fn unsigned_tx(tx_index: usize) -> UnsignedTransaction<Transaction> {
+    let private_key = private_key(tx_index);
+    let ecdsa_pubkey = ecdsa::secp256k1::PrivateKey::try_from(private_key.as_slice())
+        .unwrap()
+        .public();
+    let schnorr_pubkey = schnorr::PrivateKey::try_from(private_key.as_slice())
+        .unwrap()
+        .public();
+
+    let utxo_builder = |index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(tx_index as u32 * 3 + index)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+    };
+    let (utxo1, arg1) = utxo_builder(0).p2pkh(&ecdsa_pubkey).unwrap();
+    let (utxo2, arg2) = utxo_builder(1).p2wpkh(&ecdsa_pubkey).unwrap();
+    let (utxo3, arg3) = utxo_builder(2).p2tr_key_path(&schnorr_pubkey).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_input(utxo3, arg3)
+        .push_output(OutputBuilder::new(140_000).p2wpkh(&ecdsa_pubkey));
+    builder.build().unwrap()
+}
+
+fn sign(tx_index: usize, context: Arc<schnorr::Secp256k1Context>) -> Vec<u8> {
+    let private_key = private_key(tx_index);
+
+    let mut keys_manager = KeysManager::with_schnorr_context(context);
+    keys_manager
+        .add_ecdsa_private_bytes(&private_key)
+        .unwrap()
+        // Disable auxiliary randomness to get deterministic signatures.
+        .add_schnorr_private_bytes(&private_key, true)
+        .unwrap();
+
+    TxSigner::sign_tx(unsigned_tx(tx_index), &keys_manager)
+        .unwrap()
+        .encode_out()
+}
+
+#[test]
+fn test_concurrent_signing_matches_single_threaded() {
+    let expected: Vec<_> = (0..TX_COUNT)
+        .map(|tx_index| sign(tx_index, Arc::default()))
+        .collect();
+
+    // All threads share the same context.
+    let context = Arc::new(schnorr::Secp256k1Context::new());
+    let actual: Vec<Vec<(usize, Vec<u8>)>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread_index| {
+                let context = Arc::clone(&context);
+                scope.spawn(move || {
+                    let mut signed = Vec::new();
+                    for round in 0..ROUNDS {
+                        // Every thread signs different transactions in every round.
+                        for tx_index in (0..TX_COUNT).filter(|tx_index| {
+                            tx_index % THREADS == (thread_index + round) % THREADS
+                        }) {
+                            signed.push((tx_index, sign(tx_index, Arc::clone(&context))));
+                        }
+                    }
+                    signed
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Signing thread panicked"))
+            .collect()
+    });
+
+    let mut signed_count = 0;
+    for (tx_index, encoded) in actual.into_iter().flatten() {
+        assert_eq!(encoded, expected[tx_index], "Transaction #{tx_index}");
+        signed_count += 1;
+    }
+    assert_eq!(signed_count, TX_COUNT * ROUNDS);
+}
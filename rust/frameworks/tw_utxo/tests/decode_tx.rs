@@ -0,0 +1,136 @@
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex::{self, DecodeHex};
+use tw_utxo::transaction::standard_transaction::{TrailingDataTxid, Transaction};
+
+/// Mainnet tx f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16.
+fn legacy_tx_hex() -> String {
+    let sig = "304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09";
+    let pk_1 = "04ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c1b7303b8a0626f1baded5c72a704f7e6cd84c";
+    let pk_2 = "0411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3";
+    format!("0100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704000000004847{sig}01ffffffff0200ca9a3b000000004341{pk_1}ac00286bee000000004341{pk_2}ac00000000")
+}
+
+fn segwit_tx_hex() -> String {
+    let sig = "3045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a601";
+    let pubkey = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+    format!("020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db923730248{sig}21{pubkey}00000000")
+}
+
+#[track_caller]
+fn assert_parse_error(bytes: &[u8]) {
+    let err = Transaction::from_bytes(bytes).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_input_parse);
+}
+
+#[test]
+fn test_decode_legacy_tx() {
+    let raw = legacy_tx_hex().decode_hex().unwrap();
+    let tx = Transaction::from_bytes(&raw).unwrap();
+
+    assert_eq!(tx.version, 1);
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].previous_output.index, 0);
+    assert_eq!(tx.inputs[0].sequence, u32::MAX);
+    assert!(tx.inputs[0].witness.is_empty());
+    assert_eq!(tx.outputs.len(), 2);
+    assert_eq!(tx.outputs[0].value, 1_000_000_000);
+    assert_eq!(tx.outputs[1].value, 4_000_000_000);
+    assert_eq!(tx.locktime, 0);
+
+    assert_eq!(tx.encode_out(), raw);
+    assert_eq!(
+        hex::encode(tx.txid(), false),
+        "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16"
+    );
+}
+
+#[test]
+fn test_decode_segwit_tx() {
+    let raw = segwit_tx_hex().decode_hex().unwrap();
+    let tx = Transaction::from_bytes(&raw).unwrap();
+
+    assert_eq!(tx.version, 2);
+    assert_eq!(tx.inputs[0].witness.as_items().len(), 2);
+    assert_eq!(tx.outputs[0].value, 4_998_000_000);
+
+    assert_eq!(tx.encode_out(), raw);
+    assert_eq!(
+        hex::encode(tx.txid(), false),
+        "7553540501d96f986a96564aef9949d284353757f544cc001b60449e1abc52c3"
+    );
+}
+
+#[test]
+fn test_decode_with_trailing_data() {
+    let raw = legacy_tx_hex().decode_hex().unwrap();
+    let mut with_trailing = raw.clone();
+    with_trailing.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+    // The strict decoder rejects the trailing bytes.
+    assert_parse_error(&with_trailing);
+
+    let (tx, trailing) = Transaction::decode_with_trailing(&with_trailing).unwrap();
+    assert_eq!(trailing, &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(tx.encode_out(), raw);
+
+    // TXID covers the standard serialization only by default.
+    assert_eq!(
+        hex::encode(tx.txid(), false),
+        "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16"
+    );
+    assert_eq!(
+        tx.txid_with_trailing(trailing, TrailingDataTxid::StandardOnly),
+        tx.txid()
+    );
+    // SHA256d of the standard serialization followed by the trailing bytes.
+    assert_eq!(
+        hex::encode(
+            tx.txid_with_trailing(trailing, TrailingDataTxid::IncludeTrailing),
+            false
+        ),
+        "a10aefc1209223eeac4c7f0f37bbf6146ec68a023ff22e8c3a12d67e34d48670"
+    );
+}
+
+#[test]
+fn test_decode_with_empty_trailing_data() {
+    let raw = segwit_tx_hex().decode_hex().unwrap();
+
+    let (tx, trailing) = Transaction::decode_with_trailing(&raw).unwrap();
+    assert!(trailing.is_empty());
+    assert_eq!(tx.encode_out(), raw);
+    assert_eq!(
+        tx.txid_with_trailing(trailing, TrailingDataTxid::IncludeTrailing),
+        tx.txid()
+    );
+}
+
+#[test]
+fn test_decode_tx_invalid() {
+    let raw = legacy_tx_hex().decode_hex().unwrap();
+
+    // Truncated at any position.
+    for len in 0..raw.len() {
+        assert_parse_error(&raw[..len]);
+        Transaction::decode_with_trailing(&raw[..len]).unwrap_err();
+    }
+
+    // Unsupported segwit flag.
+    let mut invalid_flag = segwit_tx_hex().decode_hex().unwrap();
+    invalid_flag[5] = 0x02;
+    assert_parse_error(&invalid_flag);
+
+    // Segwit marker with empty witnesses.
+    let no_witness = "0200000000010100000000000000000000000000000000000000000000000000000000000000000000000000ffffffff010000000000000000000000000000";
+    assert_parse_error(&no_witness.decode_hex().unwrap());
+
+    // Non-minimal encoding of the number of inputs.
+    let mut non_minimal = raw[..4].to_vec();
+    non_minimal.extend_from_slice(&[0xfd, 0x01, 0x00]);
+    non_minimal.extend_from_slice(&raw[5..]);
+    assert_parse_error(&non_minimal);
+
+    // The number of inputs exceeds the remaining data.
+    let huge_list = "01000000feffffff7f";
+    assert_parse_error(&huge_list.decode_hex().unwrap());
+}
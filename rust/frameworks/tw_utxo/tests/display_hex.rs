@@ -0,0 +1,130 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{self, Write};
+use tw_encoding::hex::ToHex;
+use tw_hash::H256;
+use tw_utxo::encode::Encodable;
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::transaction::standard_transaction::{
+    Transaction, TransactionInput, TransactionOutput,
+};
+use tw_utxo::transaction::transaction_parts::OutPoint;
+
+/// Counts the bytes allocated by the current thread,
+/// so that the tests running in parallel do not affect each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` and the number of bytes allocated while running it.
+fn allocated_by<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+/// Discards the written string, but keeps its length.
+#[derive(Default)]
+struct LenWriter {
+    len: usize,
+}
+
+impl Write for LenWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// A consolidation-like transaction with many segwit inputs.
+fn large_tx(inputs: usize) -> Transaction {
+    let inputs = (0..inputs)
+        .map(|i| {
+            let mut witness = Witness::default();
+            witness.push_item(Script::from(vec![0x30; 72]));
+            witness.push_item(Script::from(vec![0x02; 33]));
+
+            TransactionInput {
+                previous_output: OutPoint {
+                    hash: H256::from([(i % 256) as u8; 32]),
+                    index: i as u32,
+                },
+                sequence: u32::MAX,
+                script_sig: Script::default(),
+                witness,
+            }
+        })
+        .collect();
+
+    Transaction {
+        version: 2,
+        inputs,
+        outputs: vec![TransactionOutput {
+            value: 100_000_000,
+            script_pubkey: Script::from(
+                vec![0x00, 0x14]
+                    .into_iter()
+                    .chain([0xab; 20])
+                    .collect::<Vec<_>>(),
+            ),
+        }],
+        locktime: 0,
+    }
+}
+
+#[test]
+fn test_display_hex_matches_materialized() {
+    for inputs in [0, 1, 3, 1_000] {
+        let tx = large_tx(inputs);
+        let expected = tx.encode_out().to_hex();
+
+        assert_eq!(tx.display_hex().to_string(), expected);
+        assert_eq!(format!("{}", tx.display_hex()), expected);
+
+        let mut written = String::new();
+        tx.write_hex_to(&mut written).unwrap();
+        assert_eq!(written, expected);
+    }
+}
+
+#[test]
+fn test_display_hex_allocations() {
+    let tx = large_tx(10_000);
+    let hex_len = tx.encode_out().len() * 2;
+
+    // `encode → Vec<u8> → hex String`.
+    let (materialized_len, materialized_allocated) = allocated_by(|| {
+        let mut writer = LenWriter::default();
+        writer.write_str(&tx.encode_out().to_hex()).unwrap();
+        writer.len
+    });
+
+    let (streamed_len, streamed_allocated) = allocated_by(|| {
+        let mut writer = LenWriter::default();
+        write!(writer, "{}", tx.display_hex()).unwrap();
+        writer.len
+    });
+
+    assert_eq!(materialized_len, hex_len);
+    assert_eq!(streamed_len, hex_len);
+    // At least the serialized bytes and the hex string.
+    assert!(materialized_allocated >= hex_len + hex_len / 2);
+    // Nothing is allocated per serialized byte.
+    assert_eq!(streamed_allocated, 0);
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_keypair::ecdsa;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::fee_attribution::FeeAttribution;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+
+/// Encoded sizes of the outputs.
+const P2PKH_SIZE: usize = 34;
+const P2WPKH_SIZE: usize = 31;
+const P2TR_SIZE: usize = 43;
+
+fn attribute(total_fee: Amount, vsize: usize, outputs: &[(usize, Amount)]) -> Vec<(usize, Amount)> {
+    let attribution = FeeAttribution::attribute(total_fee, vsize, outputs).unwrap();
+    assert_eq!(attribution.total(), total_fee);
+    attribution.fees
+}
+
+#[test]
+fn test_fee_attribution_proportional_to_size() {
+    // The overhead is `200 - 31 - 43 = 126` vbytes, 63 vbytes per output.
+    // The weights are `31 + 63 = 94` and `43 + 63 = 106` of 200 vbytes.
+    let fees = attribute(1_000, 200, &[(P2WPKH_SIZE, 5_000), (P2TR_SIZE, 1_000)]);
+    assert_eq!(fees, vec![(0, 470), (1, 530)]);
+
+    // No overhead.
+    let fees = attribute(740, 74, &[(P2WPKH_SIZE, 5_000), (P2TR_SIZE, 1_000)]);
+    assert_eq!(fees, vec![(0, 310), (1, 430)]);
+}
+
+#[test]
+fn test_fee_attribution_remainder() {
+    // Equal shares of `10 / 3`, the remainder is assigned to the largest output.
+    let outputs = [(P2PKH_SIZE, 500), (P2PKH_SIZE, 900), (P2PKH_SIZE, 700)];
+    let fees = attribute(10, 3 * P2PKH_SIZE, &outputs);
+    assert_eq!(fees, vec![(0, 3), (1, 4), (2, 3)]);
+
+    // The first of the largest outputs gets the remainder.
+    let outputs = [(P2PKH_SIZE, 500), (P2PKH_SIZE, 900), (P2PKH_SIZE, 900)];
+    let fees = attribute(11, 3 * P2PKH_SIZE, &outputs);
+    assert_eq!(fees, vec![(0, 3), (1, 5), (2, 3)]);
+
+    // The remainder is assigned by the value, not by the size.
+    let outputs = [(P2TR_SIZE, 1_000), (P2WPKH_SIZE, 2_000)];
+    let fees = attribute(99, 100, &outputs);
+    // Weights are `2 * 43 + 26 = 112` and `2 * 31 + 26 = 88` of 200.
+    assert_eq!(fees, vec![(0, 55), (1, 44)]);
+}
+
+#[test]
+fn test_fee_attribution_edge_cases() {
+    // Zero fee.
+    let fees = attribute(0, 200, &[(P2WPKH_SIZE, 5_000), (P2TR_SIZE, 1_000)]);
+    assert_eq!(fees, vec![(0, 0), (1, 0)]);
+
+    // A single output takes the whole fee.
+    let fees = attribute(1_234, 141, &[(P2WPKH_SIZE, 5_000)]);
+    assert_eq!(fees, vec![(0, 1_234)]);
+
+    // The estimated vsize is less than the outputs size, there is no overhead.
+    let fees = attribute(1_000, 10, &[(P2PKH_SIZE, 5_000), (P2PKH_SIZE, 1_000)]);
+    assert_eq!(fees, vec![(0, 500), (1, 500)]);
+
+    // Large amounts don't overflow.
+    let total_fee = 21_000_000 * 100_000_000;
+    let fees = attribute(
+        total_fee,
+        100_000,
+        &[(P2WPKH_SIZE, 1), (P2TR_SIZE, 2), (P2PKH_SIZE, 3)],
+    );
+    assert_eq!(fees.len(), 3);
+
+    // There are no outputs.
+    assert_eq!(
+        FeeAttribution::attribute(0, 10, &[]).unwrap(),
+        FeeAttribution::default()
+    );
+    FeeAttribution::attribute(1, 10, &[]).unwrap_err();
+}
+
+#[test]
+fn test_fee_attribution_from_plan() {
+    let public_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let mut builder = TransactionBuilder::new();
+    for (prev_index, amount) in [(0, 20_000), (1, 30_000)] {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(prev_index)
+            .amount(amount)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&public_key)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder
+        .push_output(OutputBuilder::new(10_000).p2pkh(&public_key))
+        .push_output(OutputBuilder::new(15_000).p2wpkh(&public_key));
+
+    let request = PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx: builder.build().unwrap(),
+            change_output: Some(OutputBuilder::new(0).p2wpkh(&public_key)),
+            input_selector: InputSelector::InOrder,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+        fee_per_vbyte: 10,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed: None,
+        unconfirmed_parents: Vec::new(),
+    };
+    let result = TxPlanner::plan(request).unwrap();
+    let outputs = result.unsigned_tx.transaction().outputs();
+    assert_eq!(outputs.len(), 3);
+
+    let attribution = FeeAttribution::from_plan(&result).unwrap();
+    assert_eq!(attribution.total(), result.plan.fee_estimate);
+    let indexes: Vec<_> = attribution.fees.iter().map(|(idx, _)| *idx).collect();
+    assert_eq!(indexes, vec![0, 1, 2]);
+    // The P2PKH output is larger than the P2WPKH ones.
+    assert!(attribution.fees[0].1 >= attribution.fees[1].1);
+
+    let json = attribution.to_json();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(json[2]["output_index"], 2);
+    assert_eq!(json[2]["fee"], attribution.fees[2].1);
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID as ORIGINAL_TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::modules::fee_bumper::{
+    AddInputsRequest, BumpCandidate, FeeBumpResult, FeeBumper, DEFAULT_INCREMENTAL_RELAY_FEE,
+};
+use tw_utxo::modules::utxo_selector::{ExclusionReason, InputSelector, SelectionPolicy};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, RbfPolicy, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const CANDIDATE_TXID: &str = "858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e";
+const ORIGINAL_AMOUNT: Amount = 100_000;
+const SEND_AMOUNT: Amount = 99_000;
+const DUST: Amount = 546;
+const SIGHASH_ALL: u32 = 0x01;
+const SIGHASH_ALL_ANYONECANPAY: u32 = 0x81;
+const SIGHASH_NONE_ANYONECANPAY: u32 = 0x82;
+
+fn public_key() -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public()
+}
+
+/// A transaction with a single P2WPKH input and a single P2WPKH output paying 1000 sats fee.
+fn original_tx(rbf: RbfPolicy, sighash: u32) -> UnsignedTransaction<Transaction> {
+    let public = public_key();
+    let (utxo, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(ORIGINAL_TXID).unwrap())
+        .prev_index(0)
+        .amount(ORIGINAL_AMOUNT)
+        .sighash_type(SighashType::from_u32(sighash).unwrap())
+        .p2wpkh(&public)
+        .unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .rbf(rbf)
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(SEND_AMOUNT).p2wpkh(&public));
+    builder.build().unwrap()
+}
+
+fn candidate(index: u32, amount: Amount, confirmed: bool) -> BumpCandidate<Transaction> {
+    let (input, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(CANDIDATE_TXID).unwrap())
+        .prev_index(index)
+        .amount(amount)
+        .sighash_type(SighashType::default())
+        .confirmations(u32::from(confirmed))
+        .p2wpkh(&public_key())
+        .unwrap();
+    BumpCandidate { input, arg }
+}
+
+fn request(
+    original: UnsignedTransaction<Transaction>,
+    candidates: Vec<BumpCandidate<Transaction>>,
+    fee_per_vbyte: Amount,
+) -> AddInputsRequest<Transaction> {
+    AddInputsRequest {
+        original,
+        candidates,
+        input_selector: InputSelector::InOrder,
+        fee_per_vbyte,
+        incremental_relay_fee: DEFAULT_INCREMENTAL_RELAY_FEE,
+        dust_policy: DustPolicy::FixedAmount(DUST),
+        change_output: Some(OutputBuilder::new(0).p2wpkh(&public_key())),
+        selection_policy: None,
+    }
+}
+
+fn output_values(result: &FeeBumpResult<Transaction>) -> Vec<Amount> {
+    let tx = result.unsigned_tx.transaction();
+    tx.outputs.iter().map(|output| output.value).collect()
+}
+
+#[test]
+fn test_fee_bump_add_input_no_change_original() {
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 50_000, true), candidate(1, 70_000, true)];
+
+    let result = FeeBumper::add_inputs(request(original, candidates, 20)).unwrap();
+
+    // 2 P2WPKH inputs and 2 P2WPKH outputs: 209 vbytes.
+    assert_eq!(result.plan.vsize_estimate, 209);
+    assert_eq!(result.plan.fee_estimate, 20 * 209);
+    assert_eq!(result.original_fee, ORIGINAL_AMOUNT - SEND_AMOUNT);
+    assert_eq!(result.plan.total_spend, 150_000);
+    assert_eq!(result.plan.change, 150_000 - SEND_AMOUNT - 4_180);
+    assert!(result.plan.signals_rbf);
+
+    // The first candidate is enough.
+    let tx = result.unsigned_tx.transaction();
+    assert_eq!(tx.inputs.len(), 2);
+    assert_eq!(tx.inputs[1].previous_output.index, 0);
+    assert!(tx.inputs.iter().all(|input| input.witness.is_empty()));
+    assert_eq!(output_values(&result), vec![SEND_AMOUNT, 46_820]);
+
+    // SIGHASH_ALL commits to all inputs, so the original signature is invalidated.
+    assert!(result.reusable_signatures.is_empty());
+    assert_eq!(result.inputs_to_sign, vec![0, 1]);
+}
+
+#[test]
+fn test_fee_bump_add_input_incremental_relay_fee() {
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 50_000, true)];
+
+    // `1 sat/vbyte * 209` is less than the original fee,
+    // so the replacement pays `original_fee + incremental_relay_fee * vsize`.
+    let result = FeeBumper::add_inputs(request(original, candidates, 1)).unwrap();
+    assert_eq!(result.plan.fee_estimate, 1_000 + 209);
+}
+
+#[test]
+fn test_fee_bump_add_input_unconfirmed() {
+    // BIP125 rule 2: the unconfirmed candidate cannot be added.
+    let candidates = vec![candidate(0, 50_000, false)];
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let err = FeeBumper::add_inputs(request(original, candidates, 20))
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_enough_utxos);
+
+    // The confirmed candidate is preferred even if it goes after the unconfirmed one.
+    let candidates = vec![candidate(0, 50_000, false), candidate(1, 70_000, true)];
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let result = FeeBumper::add_inputs(request(original, candidates, 20)).unwrap();
+    assert_eq!(
+        result.unsigned_tx.transaction().inputs[1]
+            .previous_output
+            .index,
+        1
+    );
+    assert_eq!(result.plan.excluded.len(), 1);
+    assert_eq!(result.plan.excluded[0].index, 0);
+    assert_eq!(result.plan.excluded[0].reason, ExclusionReason::Unconfirmed);
+
+    // Unless explicitly allowed.
+    let candidates = vec![candidate(0, 50_000, false)];
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let mut req = request(original, candidates, 20);
+    req.selection_policy = Some(SelectionPolicy::Any);
+    let result = FeeBumper::add_inputs(req).unwrap();
+    assert_eq!(result.unsigned_tx.transaction().inputs.len(), 2);
+}
+
+#[test]
+fn test_fee_bump_add_input_dust_surplus() {
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL_ANYONECANPAY);
+    // The surplus is `103_500 - 99_000 - 4_180 = 320`, that is less than dust.
+    let candidates = vec![candidate(0, 3_500, true)];
+
+    let result = FeeBumper::add_inputs(request(original, candidates, 20)).unwrap();
+
+    // The surplus is absorbed into the fee, no change output is added.
+    assert_eq!(output_values(&result), vec![SEND_AMOUNT]);
+    assert_eq!(result.plan.change, 0);
+    assert_eq!(result.plan.total_send, SEND_AMOUNT);
+    assert_eq!(result.plan.fee_estimate, 4_180 + 320);
+
+    // `SIGHASH_ALL | SIGHASH_ANYONECANPAY` commits to the own input and the unchanged outputs.
+    assert_eq!(result.reusable_signatures, vec![0]);
+    assert_eq!(result.inputs_to_sign, vec![1]);
+}
+
+#[test]
+fn test_fee_bump_add_input_no_change_output() {
+    // The dust surplus is absorbed into the fee.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 2_800, true)];
+    let request_no_change = AddInputsRequest {
+        change_output: None,
+        ..request(original, candidates, 20)
+    };
+    let result = FeeBumper::add_inputs(request_no_change).unwrap();
+    assert_eq!(output_values(&result), vec![SEND_AMOUNT]);
+    assert_eq!(result.plan.change, 0);
+    assert_eq!(result.plan.fee_estimate, 102_800 - SEND_AMOUNT);
+
+    // But the larger surplus would be burned as fee.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 50_000, true)];
+    let request_no_change = AddInputsRequest {
+        change_output: None,
+        ..request(original, candidates, 20)
+    };
+    let err = FeeBumper::add_inputs(request_no_change)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_wrong_fee);
+}
+
+#[test]
+fn test_fee_bump_add_input_reusable_signatures() {
+    // The change output invalidates `SIGHASH_ALL | SIGHASH_ANYONECANPAY` signature.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL_ANYONECANPAY);
+    let candidates = vec![candidate(0, 50_000, true)];
+    let result = FeeBumper::add_inputs(request(original, candidates, 20)).unwrap();
+    assert!(result.reusable_signatures.is_empty());
+    assert_eq!(result.inputs_to_sign, vec![0, 1]);
+
+    // `SIGHASH_NONE | SIGHASH_ANYONECANPAY` doesn't commit to other inputs and outputs.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_NONE_ANYONECANPAY);
+    let candidates = vec![candidate(0, 50_000, true)];
+    let result = FeeBumper::add_inputs(request(original, candidates, 20)).unwrap();
+    assert_eq!(result.reusable_signatures, vec![0]);
+    assert_eq!(result.inputs_to_sign, vec![1]);
+}
+
+#[test]
+fn test_fee_bump_add_input_invalid() {
+    // The original transaction doesn't signal RBF.
+    let original = original_tx(RbfPolicy::Disable, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 50_000, true)];
+    let err = FeeBumper::add_inputs(request(original, candidates, 20))
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // The candidate is spent by the original transaction already.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let mut spent = candidate(0, ORIGINAL_AMOUNT, true);
+    spent.input.previous_output = original.transaction().inputs[0].previous_output;
+    let err = FeeBumper::add_inputs(request(original, vec![spent], 20))
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // Not enough to cover the new fee.
+    let original = original_tx(RbfPolicy::SignalAll, SIGHASH_ALL);
+    let candidates = vec![candidate(0, 1_000, true)];
+    let err = FeeBumper::add_inputs(request(original, candidates, 20))
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_enough_utxos);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::chain_params::ChainParams;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectResult, SelectionPolicy};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const UTXO_AMOUNT: Amount = 160_000_000;
+const SEND_AMOUNT: Amount = 100_000_000;
+
+fn public_key() -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public()
+}
+
+fn build_tx(
+    fee_policy: Option<FeePolicy>,
+    send_amount: Amount,
+) -> SigningResult<UnsignedTransaction<Transaction>> {
+    let public = public_key();
+    let (utxo, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+        .prev_index(0)
+        .amount(UTXO_AMOUNT)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&public)
+        .unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    if let Some(fee_policy) = fee_policy {
+        builder.fee_policy(fee_policy);
+    }
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(send_amount).p2wpkh(&public));
+    builder.build()
+}
+
+fn plan(fee_policy: FeePolicy, fee_per_vbyte: Amount) -> SigningResult<SelectResult<Transaction>> {
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx: build_tx(None, SEND_AMOUNT).unwrap(),
+            change_output: Some(OutputBuilder::new(0).p2wpkh(&public_key())),
+            input_selector: InputSelector::InOrder,
+        },
+        fee_policy,
+        fee_per_vbyte,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed: None,
+        unconfirmed_parents: Vec::new(),
+    })
+}
+
+#[test]
+fn test_fee_policy_presets() {
+    assert_eq!(
+        ChainParams::bitcoin_mainnet().fee_policy,
+        FeePolicy::bitcoin()
+    );
+    assert_eq!(
+        ChainParams::bitcoin_testnet().fee_policy,
+        FeePolicy::bitcoin()
+    );
+    assert_eq!(
+        ChainParams::litecoin_mainnet().fee_policy,
+        FeePolicy::litecoin()
+    );
+
+    let (bitcoin, litecoin, dogecoin) = (
+        FeePolicy::bitcoin(),
+        FeePolicy::litecoin(),
+        FeePolicy::dogecoin(),
+    );
+    assert!(bitcoin.dust_threshold() < litecoin.dust_threshold());
+    assert!(litecoin.dust_threshold() < dogecoin.dust_threshold());
+    assert!(bitcoin.min_relay_fee_rate < litecoin.min_relay_fee_rate);
+    assert!(litecoin.min_relay_fee_rate < dogecoin.min_relay_fee_rate);
+}
+
+/// The same fee rate is fine for Bitcoin, but is below the Dogecoin minimum relay fee rate.
+#[test]
+fn test_plan_fee_rate_bitcoin_vs_dogecoin() {
+    let bitcoin = plan(FeePolicy::bitcoin(), 10).unwrap();
+    assert_eq!(bitcoin.unsigned_tx.transaction().outputs().len(), 2);
+
+    let err = plan(FeePolicy::dogecoin(), 10).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_wrong_fee);
+
+    // Bitcoin fee rate upper bound is exceeded.
+    let err = plan(FeePolicy::bitcoin(), 100_000).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_wrong_fee);
+}
+
+/// The same change amount is kept on Bitcoin, but is dust on Dogecoin.
+#[test]
+fn test_plan_change_bitcoin_vs_dogecoin() {
+    let bitcoin_policy = FeePolicy::bitcoin();
+    let bitcoin = plan(bitcoin_policy, bitcoin_policy.min_relay_fee_rate).unwrap();
+    assert_eq!(bitcoin.unsigned_tx.transaction().outputs().len(), 2);
+    assert!(bitcoin.plan.change >= bitcoin_policy.dust_threshold());
+    assert_eq!(
+        bitcoin.plan.fee_estimate + bitcoin.plan.change + SEND_AMOUNT,
+        UTXO_AMOUNT
+    );
+
+    let dogecoin_policy = FeePolicy::dogecoin();
+    let dogecoin = plan(dogecoin_policy, dogecoin_policy.min_relay_fee_rate).unwrap();
+    assert_eq!(dogecoin.unsigned_tx.transaction().outputs().len(), 1);
+    assert_eq!(dogecoin.plan.change, 0);
+    // The dust change is absorbed into the fee.
+    assert_eq!(dogecoin.plan.fee_estimate + SEND_AMOUNT, UTXO_AMOUNT);
+}
+
+#[test]
+fn test_plan_absurd_fee() {
+    let policy = FeePolicy {
+        absurd_fee_multiplier: Some(2),
+        ..FeePolicy::bitcoin()
+    };
+    plan(policy, 1).unwrap();
+    // The fee rate is within the bounds, but the fee exceeds twice the minimum relay fee.
+    let err = plan(policy, 3).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_wrong_fee);
+}
+
+#[test]
+fn test_builder_dust_outputs() {
+    build_tx(None, 50_000).unwrap();
+    build_tx(Some(FeePolicy::bitcoin()), 50_000).unwrap();
+
+    let err = build_tx(Some(FeePolicy::dogecoin()), 50_000).err().unwrap();
+    assert_eq!(
+        err.error_type(),
+        &SigningErrorType::Error_dust_amount_requested
+    );
+    build_tx(Some(FeePolicy::dogecoin()), SEND_AMOUNT).unwrap();
+}
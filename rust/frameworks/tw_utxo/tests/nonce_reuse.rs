@@ -0,0 +1,83 @@
+mod common;
+
+use crate::common::{keys_manager, PRIVATE_KEY, TXID};
+use std::cell::RefCell;
+use tw_coin_entry::coin_entry::SignatureBytes;
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::{ecdsa, schnorr};
+use tw_memory::Data;
+use tw_utxo::modules::sighash_computer::UtxoSighash;
+use tw_utxo::modules::signer_metrics::NoopSignerMetrics;
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+/// Builds a transaction spending P2PKH, P2WPKH and P2TR inputs of the same key.
+fn unsigned_tx() -> UnsignedTransaction<Transaction> {
+    let ecdsa_public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+    let schnorr_public = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+    let utxo_builder = |index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(index)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+    };
+    let (utxo1, arg1) = utxo_builder(0).p2pkh(&ecdsa_public).unwrap();
+    let (utxo2, arg2) = utxo_builder(1).p2wpkh(&ecdsa_public).unwrap();
+    let (utxo3, arg3) = utxo_builder(2).p2tr_key_path(&schnorr_public).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_input(utxo3, arg3)
+        .push_output(OutputBuilder::new(140_000).p2wpkh(&ecdsa_public));
+    builder.build().unwrap()
+}
+
+#[test]
+fn sign_tx_with_signer_distinct_nonces() {
+    let keys_manager = keys_manager();
+
+    let signed_tx = TxSigner::sign_tx_with_signer(unsigned_tx(), &NoopSignerMetrics, |sighash| {
+        TxSigner::sign_sighash(&keys_manager, sighash)
+    })
+    .unwrap();
+
+    let expected = TxSigner::sign_tx(unsigned_tx(), &keys_manager).unwrap();
+    assert_eq!(signed_tx.encode_out(), expected.encode_out());
+}
+
+#[test]
+fn sign_tx_with_signer_nonce_reuse_detected() {
+    let keys_manager = keys_manager();
+
+    // Emulates a faulty deterministic nonce: the signatures of the ECDSA inputs share
+    // the R value of the first one.
+    let first_r: RefCell<Option<Data>> = RefCell::default();
+    let faulty_signer = |sighash: &UtxoSighash| -> SigningResult<SignatureBytes> {
+        let mut signature = TxSigner::sign_sighash(&keys_manager, sighash)?;
+        let r = first_r
+            .borrow_mut()
+            .get_or_insert_with(|| signature[..32].to_vec())
+            .clone();
+        signature[..32].copy_from_slice(&r);
+        Ok(signature)
+    };
+
+    let err = TxSigner::sign_tx_with_signer(unsigned_tx(), &NoopSignerMetrics, faulty_signer)
+        .unwrap_err();
+    assert_eq!(
+        err.error_type(),
+        &SigningErrorType::Error_nonce_reuse_detected
+    );
+    assert!(err.to_string().contains("input #1"), "{err}");
+}
@@ -0,0 +1,319 @@
+use tw_coin_entry::error::prelude::*;
+use tw_hash::{H160, H256};
+use tw_utxo::modules::output_batcher::{
+    BatchPlan, FeeAllocation, FundingDescriptor, OutputBatcher, Recipient,
+};
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::transaction::standard_transaction::{
+    Transaction, TransactionInput, TransactionOutput,
+};
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::{Amount, OutPoint};
+
+/// Weight of a signed P2WPKH input with a 72-byte signature.
+const P2WPKH_INPUT_WEIGHT: usize = 272;
+
+fn p2wpkh() -> Script {
+    conditions::new_p2wpkh(&H160::default())
+}
+
+fn p2pkh() -> Script {
+    conditions::new_p2pkh(&H160::default())
+}
+
+fn p2tr() -> Script {
+    conditions::new_p2tr_dangerous_assume_tweaked(&H256::default())
+}
+
+fn funding(input_count: usize, fee_per_vbyte: Amount) -> FundingDescriptor {
+    FundingDescriptor {
+        input_count,
+        input_weight: P2WPKH_INPUT_WEIGHT,
+        segwit: true,
+        change_script_pubkey: Some(p2wpkh()),
+        fee_per_vbyte,
+    }
+}
+
+fn recipients(amounts: &[Amount]) -> Vec<Recipient> {
+    amounts
+        .iter()
+        .map(|amount| Recipient {
+            script_pubkey: p2wpkh(),
+            amount: *amount,
+        })
+        .collect()
+}
+
+fn output_values(plan: &BatchPlan) -> Vec<Amount> {
+    plan.outputs.iter().map(|output| output.value).collect()
+}
+
+/// Builds a transaction as it would be after signing the batch plan.
+fn signed_transaction(funding: &FundingDescriptor, plan: &BatchPlan) -> Transaction {
+    let input = |index: u32| {
+        let mut witness = Witness::new();
+        witness.push_item(Script::from(vec![0; 72]));
+        witness.push_item(Script::from(vec![0; 33]));
+        TransactionInput {
+            previous_output: OutPoint {
+                hash: H256::default(),
+                index,
+            },
+            sequence: u32::MAX,
+            script_sig: Script::default(),
+            witness,
+        }
+    };
+
+    let mut outputs = plan.outputs.clone();
+    if let Some(ref change) = funding.change_script_pubkey {
+        outputs.push(TransactionOutput {
+            value: 0,
+            script_pubkey: change.clone(),
+        });
+    }
+
+    Transaction {
+        version: 2,
+        inputs: (0..funding.input_count as u32).map(input).collect(),
+        outputs,
+        locktime: 0,
+    }
+}
+
+#[test]
+fn test_output_batcher_split_by_weight() {
+    let funding = funding(1, 10);
+    // Exactly fits a transaction with 2 recipients and the change output.
+    let batcher = OutputBatcher::new(funding.clone(), 686);
+
+    let plans = batcher
+        .batch(&recipients(&[10_000, 20_000, 30_000]))
+        .unwrap();
+    assert_eq!(plans.len(), 2);
+
+    assert_eq!(plans[0].recipient_indexes, vec![0, 1]);
+    assert_eq!(plans[0].weight_estimate, 686);
+    assert_eq!(plans[0].vsize_estimate, 172);
+    assert_eq!(plans[0].fee_estimate, 1_720);
+    // 573.33 and 1146.66 are rounded down, the remaining satoshi goes to the largest remainder.
+    assert_eq!(output_values(&plans[0]), vec![9_427, 18_853]);
+    assert_eq!(plans[0].total_send, 28_280);
+    assert_eq!(plans[0].total_spend, 30_000);
+
+    assert_eq!(plans[1].recipient_indexes, vec![2]);
+    assert_eq!(plans[1].weight_estimate, 562);
+    assert_eq!(plans[1].fee_estimate, 1_410);
+    assert_eq!(output_values(&plans[1]), vec![28_590]);
+    assert_eq!(plans[1].total_spend, 30_000);
+
+    for plan in plans.iter() {
+        let tx = signed_transaction(&funding, plan);
+        assert_eq!(tx.weight(), plan.weight_estimate);
+        assert_eq!(tx.vsize(), plan.vsize_estimate);
+    }
+}
+
+#[test]
+fn test_output_batcher_sender_pays() {
+    let plans = OutputBatcher::new(funding(1, 10), 686)
+        .fee_allocation(FeeAllocation::SenderPays)
+        .batch(&recipients(&[10_000, 20_000, 30_000]))
+        .unwrap();
+
+    assert_eq!(plans.len(), 2);
+    assert_eq!(output_values(&plans[0]), vec![10_000, 20_000]);
+    assert_eq!(plans[0].total_send, 30_000);
+    assert_eq!(plans[0].total_spend, 31_720);
+    assert_eq!(output_values(&plans[1]), vec![30_000]);
+    assert_eq!(plans[1].total_spend, 31_410);
+}
+
+#[test]
+fn test_output_batcher_max_outputs() {
+    let plans = OutputBatcher::new(funding(2, 1), 400_000)
+        .max_outputs(3)
+        .batch(&recipients(&[1_000; 5]))
+        .unwrap();
+
+    let indexes: Vec<_> = plans
+        .iter()
+        .map(|plan| plan.recipient_indexes.clone())
+        .collect();
+    // Every transaction has up to 2 recipients and the change output.
+    assert_eq!(indexes, vec![vec![0, 1], vec![2, 3], vec![4]]);
+}
+
+#[test]
+fn test_output_batcher_recipient_too_big() {
+    let mut recipients = recipients(&[10_000, 20_000]);
+    recipients.push(Recipient {
+        script_pubkey: Script::from(vec![0x6a; 1_000]),
+        amount: 30_000,
+    });
+
+    let err = OutputBatcher::new(funding(1, 10), 4_000)
+        .batch(&recipients)
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_tx_too_big);
+
+    // Doesn't fit even without any recipient output.
+    let err = OutputBatcher::new(funding(10, 10), 2_000)
+        .batch(&recipients[..1])
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_tx_too_big);
+}
+
+#[test]
+fn test_output_batcher_fee_exceeds_amount() {
+    // The batch fee is 172_000, that exceeds the total amount.
+    let err = OutputBatcher::new(funding(1, 1_000), 400_000)
+        .batch(&recipients(&[100_000, 1_000]))
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_enough_utxos);
+}
+
+#[test]
+fn test_output_batcher_invalid_params() {
+    let batcher = OutputBatcher::new(funding(1, 10), 400_000);
+
+    let err = batcher.batch(&[]).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_missing_input_utxos
+    );
+
+    let err = batcher.batch(&recipients(&[1_000, 0])).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    let err = OutputBatcher::new(funding(0, 10), 400_000)
+        .batch(&recipients(&[1_000]))
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // The change output leaves no room for recipients.
+    let err = OutputBatcher::new(funding(1, 10), 400_000)
+        .max_outputs(1)
+        .batch(&recipients(&[1_000]))
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+}
+
+/// A deterministic xorshift64 generator, so any failure can be reproduced.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, from: u64, to: u64) -> u64 {
+        from + self.next_u64() % (to - from)
+    }
+}
+
+#[test]
+fn test_output_batcher_reconciles_random_batches() {
+    let mut rng = Rng(0x5eed_ba7c_4e55);
+
+    for _ in 0..200 {
+        let recipients: Vec<_> = (0..rng.range(1, 3_000))
+            .map(|_| Recipient {
+                script_pubkey: match rng.range(0, 3) {
+                    0 => p2pkh(),
+                    1 => p2wpkh(),
+                    _ => p2tr(),
+                },
+                amount: rng.range(100_000, 100_000_000),
+            })
+            .collect();
+
+        let funding = FundingDescriptor {
+            change_script_pubkey: if rng.range(0, 2) == 0 {
+                Some(p2tr())
+            } else {
+                None
+            },
+            ..funding(rng.range(1, 5) as usize, rng.range(1, 50))
+        };
+        let weight_budget = rng.range(2_000, 400_000) as usize;
+        let max_outputs = rng.range(2, 500) as usize;
+        let fee_allocation = if rng.range(0, 2) == 0 {
+            FeeAllocation::Proportional
+        } else {
+            FeeAllocation::SenderPays
+        };
+
+        let plans = OutputBatcher::new(funding.clone(), weight_budget)
+            .max_outputs(max_outputs)
+            .fee_allocation(fee_allocation)
+            .batch(&recipients)
+            .unwrap();
+
+        // No recipient is dropped or reordered.
+        let indexes: Vec<_> = plans
+            .iter()
+            .flat_map(|plan| plan.recipient_indexes.iter().copied())
+            .collect();
+        assert_eq!(indexes, (0..recipients.len()).collect::<Vec<_>>());
+
+        let mut total_requested = 0;
+        let mut total_send = 0;
+        let mut total_fee = 0;
+        for plan in plans.iter() {
+            let tx = signed_transaction(&funding, plan);
+            assert_eq!(tx.weight(), plan.weight_estimate);
+            assert!(plan.weight_estimate <= weight_budget);
+            assert!(tx.outputs.len() <= max_outputs);
+            assert_eq!(
+                plan.fee_estimate,
+                plan.vsize_estimate as Amount * funding.fee_per_vbyte
+            );
+
+            let requested: Amount = plan
+                .recipient_indexes
+                .iter()
+                .map(|i| recipients[*i].amount)
+                .sum();
+            let sent: Amount = output_values(plan).iter().sum();
+            assert_eq!(sent, plan.total_send);
+            assert_eq!(plan.total_spend, plan.total_send + plan.fee_estimate);
+
+            match fee_allocation {
+                FeeAllocation::Proportional => assert_eq!(plan.total_spend, requested),
+                FeeAllocation::SenderPays => assert_eq!(plan.total_send, requested),
+            }
+            for (output, index) in plan.outputs.iter().zip(plan.recipient_indexes.iter()) {
+                assert_eq!(output.script_pubkey, recipients[*index].script_pubkey);
+            }
+
+            total_requested += requested;
+            total_send += plan.total_send;
+            total_fee += plan.fee_estimate;
+        }
+
+        match fee_allocation {
+            FeeAllocation::Proportional => assert_eq!(total_send + total_fee, total_requested),
+            FeeAllocation::SenderPays => assert_eq!(total_send, total_requested),
+        }
+    }
+}
+
+#[test]
+fn test_output_batcher_deterministic() {
+    let recipients = recipients(&[33_333, 33_333, 33_334, 50_000, 1_000_000]);
+    let batch = || {
+        OutputBatcher::new(funding(1, 7), 1_000)
+            .batch(&recipients)
+            .unwrap()
+            .iter()
+            .map(output_values)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(batch(), batch());
+}
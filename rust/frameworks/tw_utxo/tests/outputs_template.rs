@@ -0,0 +1,196 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_encoding::hex::ToHex;
+use tw_keypair::ecdsa;
+use tw_utxo::encode;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::outputs_template::{
+    verify_payment, OutputsTemplate,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+
+const LOCK_TIME: u32 = 840_000;
+
+fn public_key(private: &str) -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(private)
+        .unwrap()
+        .public()
+}
+
+fn payee() -> ecdsa::secp256k1::PublicKey {
+    public_key(PRIVATE_KEY)
+}
+
+fn payer() -> ecdsa::secp256k1::PublicKey {
+    public_key("a7e5c9f0d9c1b0e5a8f4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2")
+}
+
+/// The payee requests two payments, and a memo.
+fn invoice(lock_time: Option<u32>) -> OutputsTemplate {
+    OutputsTemplate {
+        outputs: vec![
+            OutputBuilder::new(30_000).p2wpkh(&payee()),
+            OutputBuilder::new(5_000).p2pkh(&payee()),
+            OutputBuilder::new(0).op_return(b"invoice #42").unwrap(),
+        ],
+        lock_time,
+    }
+}
+
+/// The payer completes the template with an input and a change output.
+fn pay(template: OutputsTemplate) -> TransactionBuilder {
+    let (utxo, arg) = UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+        .prev_index(0)
+        .amount(50_000)
+        .sighash_type(SighashType::default())
+        .p2wpkh(&payer())
+        .unwrap();
+
+    let mut builder = TransactionBuilder::from_template(template);
+    builder
+        .push_input(utxo, arg)
+        .push_output(OutputBuilder::new(14_000).p2wpkh(&payer()));
+    builder
+}
+
+fn paid_tx(template: &OutputsTemplate) -> Transaction {
+    pay(template.clone()).build().unwrap().into_transaction()
+}
+
+#[test]
+fn test_outputs_template_serialization() {
+    let template = invoice(Some(LOCK_TIME));
+
+    let bytes = encode::encode(&template);
+    let decoded: OutputsTemplate = encode::decode(&bytes).unwrap();
+    assert_eq!(decoded.digest(), template.digest());
+    assert_eq!(decoded.lock_time, Some(LOCK_TIME));
+    assert_eq!(decoded.outputs.len(), 3);
+
+    // The lock time is a part of the digest.
+    assert_ne!(invoice(None).digest(), template.digest());
+    let decoded: OutputsTemplate = encode::decode(&encode::encode(&invoice(None))).unwrap();
+    assert_eq!(decoded.lock_time, None);
+
+    // Invalid lock time flag.
+    let mut invalid = encode::encode(&invoice(None));
+    *invalid.last_mut().unwrap() = 2;
+    encode::decode::<OutputsTemplate>(&invalid).unwrap_err();
+
+    let json = template.to_json();
+    assert_eq!(json["lock_time"], LOCK_TIME);
+    assert_eq!(json["outputs"][0]["value"], 30_000);
+    assert_eq!(json["digest"], template.digest().to_hex());
+    let decoded = OutputsTemplate::from_json(&json).unwrap();
+    assert_eq!(decoded.digest(), template.digest());
+}
+
+#[test]
+fn test_outputs_template_json_tampered() {
+    let template = invoice(None);
+
+    let mut json = template.to_json();
+    json["outputs"][0]["value"] = 3_000.into();
+    OutputsTemplate::from_json(&json).unwrap_err();
+
+    // The digest is optional.
+    json.as_object_mut().unwrap().remove("digest");
+    let decoded = OutputsTemplate::from_json(&json).unwrap();
+    assert_eq!(decoded.outputs[0].value, 3_000);
+}
+
+#[test]
+fn test_outputs_template_paid() {
+    let template = invoice(Some(LOCK_TIME));
+    let tx = paid_tx(&template);
+
+    // The template outputs go first.
+    assert_eq!(tx.outputs.len(), 4);
+    assert_eq!(tx.outputs[0].value, 30_000);
+    assert_eq!(tx.outputs[1].value, 5_000);
+    assert_eq!(tx.outputs[3].value, 14_000);
+    assert_eq!(tx.locktime, LOCK_TIME);
+    verify_payment(&tx, &template).unwrap();
+
+    // Reordered outputs still pay the template.
+    let mut reordered = tx.clone();
+    reordered.outputs.reverse();
+    verify_payment(&reordered, &template).unwrap();
+}
+
+#[test]
+fn test_outputs_template_tampered_amount() {
+    let template = invoice(None);
+
+    let mut tx = paid_tx(&template);
+    tx.outputs[0].value -= 1;
+    verify_payment(&tx, &template).unwrap_err();
+
+    // The amount is moved to the change.
+    let mut tx = paid_tx(&template);
+    tx.outputs[1].value = 4_000;
+    tx.outputs[3].value += 1_000;
+    verify_payment(&tx, &template).unwrap_err();
+
+    // Altered after the unsigned transaction is built.
+    let mut unsigned = pay(template.clone()).build().unwrap();
+    unsigned.outputs_mut()[0].value = 20_000;
+    verify_payment(unsigned.transaction(), &template).unwrap_err();
+}
+
+#[test]
+fn test_outputs_template_tampered_script() {
+    let template = invoice(None);
+
+    // Paid to the payer instead.
+    let mut tx = paid_tx(&template);
+    tx.outputs[0].script_pubkey = OutputBuilder::new(30_000).p2wpkh(&payer()).script_pubkey;
+    verify_payment(&tx, &template).unwrap_err();
+
+    // Removed output.
+    let mut tx = paid_tx(&template);
+    tx.outputs.remove(2);
+    verify_payment(&tx, &template).unwrap_err();
+}
+
+#[test]
+fn test_outputs_template_duplicate_outputs() {
+    let output = OutputBuilder::new(10_000).p2wpkh(&payee());
+    let template = OutputsTemplate {
+        outputs: vec![output.clone(), output],
+        lock_time: None,
+    };
+
+    let mut tx = paid_tx(&template);
+    verify_payment(&tx, &template).unwrap();
+
+    // Every transaction output pays a single template output.
+    tx.outputs.remove(1);
+    verify_payment(&tx, &template).unwrap_err();
+}
+
+#[test]
+fn test_outputs_template_lock_time() {
+    let template = invoice(Some(LOCK_TIME));
+
+    // The payer can't change the required lock time.
+    let mut builder = pay(template.clone());
+    builder.lock_time(LOCK_TIME + 1);
+    builder.build().unwrap_err();
+
+    let mut tx = paid_tx(&template);
+    tx.locktime = 0;
+    verify_payment(&tx, &template).unwrap_err();
+
+    // The lock time is up to the payer if it's not required.
+    let template = invoice(None);
+    let mut builder = pay(template.clone());
+    builder.lock_time(LOCK_TIME);
+    let tx = builder.build().unwrap().into_transaction();
+    verify_payment(&tx, &template).unwrap();
+}
@@ -0,0 +1,363 @@
+mod common;
+
+use crate::common::{PSBT_V0, PSBT_V2};
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex::{self, DecodeHex};
+use tw_hash::H256;
+use tw_utxo::psbt::{
+    Psbt, PsbtInput, PsbtOutput, PsbtVersion, TX_MODIFIABLE_INPUTS, TX_MODIFIABLE_OUTPUTS,
+};
+use tw_utxo::script::Script;
+use tw_utxo::transaction::transaction_parts::OutPoint;
+
+/// BIP370 test vector: [`PSBT_V2`] with `PSBT_GLOBAL_FALLBACK_LOCKTIME` of zero.
+const PSBT_V2_FALLBACK_LOCKTIME: &str = "70736274ff0102040200000001030400000000010401010105010201fb040200000000010e200b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc8010f04000000000001030808af0800000000000104160014c430f64c4756da310dbd1a085572ef299926272c000103088bbdeb0b0000000001041600144dd193ac964a56ac1b9e1cca8454fe2f474f851300";
+/// The `PSBT_IN_OUTPUT_INDEX` field of the [`PSBT_V2`] input, the last one of the input map.
+const INPUT_OUTPUT_INDEX: &str = "010f0400000000";
+
+#[track_caller]
+fn assert_parse_error(psbt_hex: &str) {
+    let err = Psbt::from_bytes(&psbt_hex.decode_hex().unwrap()).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_input_parse);
+}
+
+#[track_caller]
+fn assert_encoded_parse_error(psbt: &Psbt) {
+    assert_parse_error(&hex::encode(psbt.encode_out(), false));
+}
+
+/// Adds the given fields to the [`PSBT_V2`] input.
+fn v2_with_input_fields(fields: &str) -> String {
+    PSBT_V2.replacen(
+        INPUT_OUTPUT_INDEX,
+        &format!("{INPUT_OUTPUT_INDEX}{fields}"),
+        1,
+    )
+}
+
+fn outpoint(byte: u8, index: u32) -> OutPoint {
+    OutPoint {
+        hash: H256::from([byte; 32]),
+        index,
+    }
+}
+
+fn input_with_locktimes(byte: u8, height: Option<u32>, time: Option<u32>) -> PsbtInput {
+    PsbtInput {
+        required_height_locktime: height,
+        required_time_locktime: time,
+        ..PsbtInput::new(outpoint(byte, 0))
+    }
+}
+
+#[test]
+fn test_psbt_v2_decode() {
+    let raw = PSBT_V2.decode_hex().unwrap();
+    let psbt = Psbt::from_bytes(&raw).unwrap();
+
+    assert_eq!(psbt.version, PsbtVersion::V2);
+    assert_eq!(psbt.tx_version, 2);
+    assert_eq!(psbt.fallback_locktime, None);
+    assert_eq!(psbt.tx_modifiable, None);
+    assert!(psbt.unknown.is_empty());
+
+    assert_eq!(psbt.inputs.len(), 1);
+    assert_eq!(
+        psbt.inputs[0].previous_output.hash,
+        H256::from("0b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc8")
+    );
+    assert_eq!(psbt.inputs[0].previous_output.index, 0);
+    assert_eq!(psbt.inputs[0].sequence, None);
+    assert!(!psbt.inputs[0].requires_locktime());
+
+    assert_eq!(psbt.outputs.len(), 2);
+    assert_eq!(psbt.outputs[0].amount, 569_096);
+    assert_eq!(psbt.outputs[1].amount, 199_998_859);
+    assert_eq!(
+        psbt.outputs[1].script_pubkey,
+        Script::from(
+            "00144dd193ac964a56ac1b9e1cca8454fe2f474f8513"
+                .decode_hex()
+                .unwrap()
+        )
+    );
+
+    let tx = psbt.unsigned_tx().unwrap();
+    assert_eq!(tx.locktime, 0);
+    assert_eq!(tx.inputs[0].sequence, u32::MAX);
+
+    assert_eq!(psbt.encode_out(), raw);
+}
+
+#[test]
+fn test_psbt_v2_decode_locktimes() {
+    let raw = PSBT_V2_FALLBACK_LOCKTIME.decode_hex().unwrap();
+    let psbt = Psbt::from_bytes(&raw).unwrap();
+    assert_eq!(psbt.fallback_locktime, Some(0));
+    assert_eq!(psbt.encode_out(), raw);
+
+    // PSBT_IN_REQUIRED_TIME_LOCKTIME.
+    let with_time = v2_with_input_fields("0111048c8dd365");
+    let psbt = Psbt::from_bytes(&with_time.decode_hex().unwrap()).unwrap();
+    assert_eq!(psbt.inputs[0].required_time_locktime, Some(1_708_363_148));
+    assert_eq!(psbt.compute_locktime().unwrap(), 1_708_363_148);
+    assert_eq!(hex::encode(psbt.encode_out(), false), with_time);
+
+    // PSBT_IN_REQUIRED_TIME_LOCKTIME and PSBT_IN_REQUIRED_HEIGHT_LOCKTIME, the height one is preferred.
+    let with_both = v2_with_input_fields("0111048c8dd36501120410270000");
+    let psbt = Psbt::from_bytes(&with_both.decode_hex().unwrap()).unwrap();
+    assert_eq!(psbt.inputs[0].required_height_locktime, Some(10_000));
+    assert_eq!(psbt.compute_locktime().unwrap(), 10_000);
+}
+
+#[test]
+fn test_psbt_v0_decode() {
+    let raw = PSBT_V0.decode_hex().unwrap();
+    let psbt = Psbt::from_bytes(&raw).unwrap();
+
+    assert_eq!(psbt.version, PsbtVersion::V0);
+    assert_eq!(psbt.tx_version, 2);
+    assert_eq!(psbt.fallback_locktime, Some(0));
+    assert_eq!(psbt.tx_modifiable, None);
+    assert!(psbt.unknown.is_empty());
+
+    assert_eq!(psbt.inputs.len(), 1);
+    assert_eq!(psbt.inputs[0].sequence, Some(u32::MAX));
+    assert!(psbt.inputs[0].unknown.is_empty());
+    assert_eq!(psbt.outputs.len(), 2);
+
+    // The same transaction as the v2 one.
+    let v2 = Psbt::from_bytes(&PSBT_V2.decode_hex().unwrap()).unwrap();
+    assert_eq!(psbt.unsigned_tx().unwrap(), v2.unsigned_tx().unwrap());
+
+    assert_eq!(psbt.encode_out(), raw);
+}
+
+#[test]
+fn test_psbt_v0_v2_round_trip() {
+    let v2 = Psbt::from_bytes(&PSBT_V2.decode_hex().unwrap()).unwrap();
+    let v0 = v2.to_v0().unwrap();
+    assert_eq!(hex::encode(v0.encode_out(), false), PSBT_V0);
+
+    // The v0 transaction has the lock time and the sequence set explicitly.
+    let decoded_v0 = Psbt::from_bytes(&PSBT_V0.decode_hex().unwrap()).unwrap();
+    let v2_again = decoded_v0.to_v2().unwrap();
+    let expected = PSBT_V2_FALLBACK_LOCKTIME.replacen(
+        INPUT_OUTPUT_INDEX,
+        &format!("{INPUT_OUTPUT_INDEX}011004ffffffff"),
+        1,
+    );
+    assert_eq!(hex::encode(v2_again.encode_out(), false), expected);
+    assert_eq!(v2_again.unsigned_tx().unwrap(), v2.unsigned_tx().unwrap());
+    assert_eq!(v2_again.to_v0().unwrap(), decoded_v0);
+
+    // The other fields are kept as is.
+    let mut v2 = v2;
+    v2.unknown
+        .insert(vec![0xfc, 0x03, 0x61, 0x62, 0x63], vec![0x01]);
+    v2.inputs[0].unknown.insert(vec![0x01], vec![0x02]);
+    v2.outputs[1].unknown.insert(vec![0x02, 0x03], vec![0x04]);
+    let v0 = Psbt::from_bytes(&v2.to_v0().unwrap().encode_out()).unwrap();
+    assert_eq!(v0.unknown, v2.unknown);
+    assert_eq!(v0.inputs[0].unknown, v2.inputs[0].unknown);
+    assert_eq!(v0.outputs[1].unknown, v2.outputs[1].unknown);
+}
+
+#[test]
+fn test_psbt_to_v2_tx_version() {
+    // A v2 PSBT requires the transaction version 2 at least.
+    let mut v0 = Psbt::from_bytes(&PSBT_V0.decode_hex().unwrap()).unwrap();
+    v0.tx_version = 1;
+    let err = v0.to_v2().unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    v0.tx_version = 3;
+    assert_eq!(v0.to_v2().unwrap().tx_version, 3);
+
+    // PSBT v2 with PSBT_GLOBAL_TX_VERSION 1.
+    assert_parse_error(&PSBT_V2.replacen("01020402000000", "01020401000000", 1));
+}
+
+#[test]
+fn test_psbt_explicit_version_0() {
+    // `PSBT_GLOBAL_VERSION` is optional for v0.
+    let with_version = PSBT_V0.replacen("70736274ff", "70736274ff01fb0400000000", 1);
+    let psbt = Psbt::from_bytes(&with_version.decode_hex().unwrap()).unwrap();
+    assert_eq!(psbt.version, PsbtVersion::V0);
+    assert_eq!(hex::encode(psbt.encode_out(), false), PSBT_V0);
+}
+
+/// The invalid BIP370 test vectors, and a few more.
+#[test]
+fn test_psbt_invalid() {
+    // Invalid magic.
+    assert_parse_error(&PSBT_V2.replacen("70736274ff", "70736274fe", 1));
+    // Unsupported version 1.
+    assert_parse_error(&PSBT_V2.replacen("01fb0402000000", "01fb0401000000", 1));
+    // Trailing data.
+    assert_parse_error(&format!("{PSBT_V2}00"));
+    // Duplicate key.
+    assert_parse_error(&PSBT_V2.replacen("01040101", "0104010101040101", 1));
+
+    // PSBT v0 with PSBT_GLOBAL_VERSION set to 2.
+    assert_parse_error(&PSBT_V0.replacen("70736274ff", "70736274ff01fb0402000000", 1));
+    // PSBT v0 with PSBT_GLOBAL_TX_VERSION.
+    assert_parse_error(&PSBT_V0.replacen("70736274ff", "70736274ff01020402000000", 1));
+
+    let v0 = Psbt::from_bytes(&PSBT_V0.decode_hex().unwrap()).unwrap();
+
+    // PSBT v0 with PSBT_IN_PREVIOUS_TXID.
+    let mut psbt = v0.clone();
+    psbt.inputs[0].unknown.insert(vec![0x0e], vec![0; 32]);
+    assert_encoded_parse_error(&psbt);
+
+    // PSBT v0 with PSBT_OUT_AMOUNT.
+    let mut psbt = v0.clone();
+    psbt.outputs[0].unknown.insert(vec![0x03], vec![0; 8]);
+    assert_encoded_parse_error(&psbt);
+
+    let v2 = Psbt::from_bytes(&PSBT_V2.decode_hex().unwrap()).unwrap();
+
+    // PSBT v2 with PSBT_GLOBAL_UNSIGNED_TX.
+    let mut psbt = v2.clone();
+    psbt.unknown.insert(vec![0x00], vec![]);
+    assert_encoded_parse_error(&psbt);
+
+    // PSBT v2 with PSBT_IN_SEQUENCE having key data.
+    let mut psbt = v2.clone();
+    psbt.inputs[0].unknown.insert(vec![0x10, 0x00], vec![0; 4]);
+    assert_encoded_parse_error(&psbt);
+
+    // PSBT v2 missing PSBT_GLOBAL_INPUT_COUNT.
+    assert_parse_error(&PSBT_V2.replacen("01040101", "", 1));
+    // PSBT v2 missing PSBT_GLOBAL_OUTPUT_COUNT.
+    assert_parse_error(&PSBT_V2.replacen("01050102", "", 1));
+    // PSBT v2 missing PSBT_IN_PREVIOUS_TXID.
+    assert_parse_error(&PSBT_V2.replacen(
+        "010e200b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc8",
+        "",
+        1,
+    ));
+    // PSBT v2 missing PSBT_IN_OUTPUT_INDEX.
+    assert_parse_error(&PSBT_V2.replacen(INPUT_OUTPUT_INDEX, "", 1));
+    // PSBT v2 missing PSBT_OUT_AMOUNT.
+    assert_parse_error(&PSBT_V2.replacen("01030808af080000000000", "", 1));
+    // PSBT v2 missing PSBT_OUT_SCRIPT.
+    assert_parse_error(&PSBT_V2.replacen(
+        "0104160014c430f64c4756da310dbd1a085572ef299926272c",
+        "",
+        1,
+    ));
+    // PSBT v2 with a negative PSBT_OUT_AMOUNT.
+    assert_parse_error(&PSBT_V2.replacen("01030808af080000000000", "010308ffffffffffffffff", 1));
+
+    // PSBT v2 with PSBT_IN_REQUIRED_TIME_LOCKTIME less than 500000000.
+    assert_parse_error(&v2_with_input_fields("011104ff64cd1d"));
+    // PSBT v2 with PSBT_IN_REQUIRED_HEIGHT_LOCKTIME greater than or equal to 500000000.
+    assert_parse_error(&v2_with_input_fields("0112040065cd1d"));
+    // PSBT v2 with PSBT_IN_REQUIRED_HEIGHT_LOCKTIME of zero.
+    assert_parse_error(&v2_with_input_fields("01120400000000"));
+}
+
+#[test]
+fn test_psbt_constructor() {
+    let mut psbt = Psbt::new_v2(2, Some(100)).unwrap();
+    assert_eq!(
+        psbt.tx_modifiable,
+        Some(TX_MODIFIABLE_INPUTS | TX_MODIFIABLE_OUTPUTS)
+    );
+    assert_eq!(psbt.compute_locktime().unwrap(), 100);
+
+    // A height lock time is preferred if every input supports both.
+    psbt.add_input(input_with_locktimes(1, Some(1_000), Some(500_000_100)))
+        .unwrap();
+    psbt.add_input(input_with_locktimes(2, Some(2_000), Some(500_000_000)))
+        .unwrap();
+    assert_eq!(psbt.compute_locktime().unwrap(), 2_000);
+
+    // The input without a lock time doesn't matter.
+    psbt.add_input(PsbtInput::new(outpoint(3, 0))).unwrap();
+
+    // The input requires a time lock time only, while the others prefer the height one.
+    psbt.add_input(input_with_locktimes(4, None, Some(500_000_200)))
+        .unwrap();
+    assert_eq!(psbt.compute_locktime().unwrap(), 500_000_200);
+
+    // The input requires a height lock time only, which conflicts with the previous one.
+    let err = psbt
+        .add_input(input_with_locktimes(5, Some(3_000), None))
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    assert_eq!(psbt.inputs.len(), 4);
+
+    // The input is spent already.
+    psbt.add_input(PsbtInput::new(outpoint(3, 0))).unwrap_err();
+    // Invalid lock time.
+    psbt.add_input(input_with_locktimes(6, Some(500_000_000), None))
+        .unwrap_err();
+
+    let script = Script::from(vec![0x51]);
+    psbt.add_output(PsbtOutput::new(1_000, script.clone()))
+        .unwrap();
+    assert_eq!(psbt.outputs.len(), 1);
+
+    // The transaction is still modifiable.
+    psbt.to_v0().unwrap_err();
+
+    psbt.finish_construction();
+    assert_eq!(psbt.tx_modifiable, Some(0));
+    psbt.add_input(PsbtInput::new(outpoint(7, 0))).unwrap_err();
+    psbt.add_output(PsbtOutput::new(1_000, script)).unwrap_err();
+
+    // The required lock times can't be converted to v0.
+    psbt.to_v0().unwrap_err();
+
+    let raw = psbt.encode_out();
+    let decoded = Psbt::from_bytes(&raw).unwrap();
+    assert_eq!(decoded, psbt);
+    assert_eq!(decoded.unsigned_tx().unwrap().locktime, 500_000_200);
+}
+
+#[test]
+fn test_psbt_constructor_to_v0() {
+    let mut psbt = Psbt::new_v2(2, None).unwrap();
+
+    // A v0 PSBT must spend at least one input.
+    psbt.finish_construction();
+    psbt.to_v0().unwrap_err();
+
+    let mut psbt = Psbt::new_v2(2, None).unwrap();
+    let input = PsbtInput {
+        sequence: Some(0xfffffffe),
+        ..PsbtInput::new(outpoint(1, 2))
+    };
+    psbt.add_input(input).unwrap();
+    psbt.add_output(PsbtOutput::new(5_000, Script::from(vec![0x51])))
+        .unwrap();
+    psbt.finish_construction();
+
+    let v0 = psbt.to_v0().unwrap();
+    assert_eq!(v0.version, PsbtVersion::V0);
+    assert_eq!(
+        v0.unsigned_tx().unwrap().encode_out(),
+        psbt.unsigned_tx().unwrap().encode_out()
+    );
+
+    let decoded = Psbt::from_bytes(&v0.encode_out()).unwrap();
+    assert_eq!(decoded.version, PsbtVersion::V0);
+    assert_eq!(decoded.inputs[0].sequence, Some(0xfffffffe));
+    assert_eq!(decoded.inputs[0].previous_output, outpoint(1, 2));
+    assert_eq!(decoded.outputs[0].amount, 5_000);
+}
+
+#[test]
+fn test_psbt_constructor_invalid() {
+    // A v2 PSBT is created for the transaction version 2 at least.
+    Psbt::new_v2(1, None).unwrap_err();
+
+    // A v0 PSBT is not modifiable.
+    let mut v0 = Psbt::from_bytes(&PSBT_V0.decode_hex().unwrap()).unwrap();
+    v0.add_input(PsbtInput::new(outpoint(1, 0))).unwrap_err();
+    v0.add_output(PsbtOutput::new(1_000, Script::default()))
+        .unwrap_err();
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::constants::{
+    MAX_BIP125_RBF_SEQUENCE, SEQUENCE_ENABLE_LOCKTIME_NO_RBF, SEQUENCE_FINAL,
+};
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::tx_summary::TxSummaryBuilder;
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, RbfPolicy, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+/// A lock time at the current block height as used by anti-fee-sniping.
+const ANTI_FEE_SNIPING_LOCKTIME: u32 = 840_000;
+/// BIP68 relative timelock of 144 blocks.
+const RELATIVE_TIMELOCK: u32 = 144;
+
+fn public_key() -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public()
+}
+
+/// Builds a transaction with two inputs, the first one with an optional explicit sequence.
+fn build_tx(
+    policy: Option<RbfPolicy>,
+    locktime: u32,
+    explicit_sequence: Option<u32>,
+) -> SigningResult<UnsignedTransaction<Transaction>> {
+    let public = public_key();
+    let txid = txid_from_str_and_rev(TXID).unwrap();
+
+    let utxo_builder = |index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(index)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+    };
+
+    let mut first = utxo_builder(0);
+    if let Some(sequence) = explicit_sequence {
+        first = first.sequence(sequence);
+    }
+    let (utxo1, arg1) = first.p2wpkh(&public).unwrap();
+    let (utxo2, arg2) = utxo_builder(1).p2wpkh(&public).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    if let Some(policy) = policy {
+        builder.rbf(policy);
+    }
+    builder
+        .lock_time(locktime)
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_output(OutputBuilder::new(90_000).p2wpkh(&public));
+    builder.build()
+}
+
+#[track_caller]
+fn assert_sequences(
+    policy: Option<RbfPolicy>,
+    locktime: u32,
+    explicit_sequence: Option<u32>,
+    expected: [u32; 2],
+    expected_signals_rbf: bool,
+) {
+    let unsigned_tx = build_tx(policy, locktime, explicit_sequence).unwrap();
+    let tx = unsigned_tx.transaction();
+
+    let sequences: Vec<_> = tx.inputs.iter().map(|input| input.sequence).collect();
+    assert_eq!(sequences, expected);
+    assert_eq!(tx.locktime, locktime);
+    assert_eq!(tx.signals_rbf(), expected_signals_rbf);
+    assert_eq!(
+        TxSummaryBuilder::summarize(tx).signals_rbf,
+        expected_signals_rbf
+    );
+}
+
+#[test]
+fn test_rbf_signal_all_by_default() {
+    assert_sequences(
+        None,
+        0,
+        None,
+        [MAX_BIP125_RBF_SEQUENCE, MAX_BIP125_RBF_SEQUENCE],
+        true,
+    );
+    assert_sequences(
+        Some(RbfPolicy::SignalAll),
+        0,
+        None,
+        [MAX_BIP125_RBF_SEQUENCE, MAX_BIP125_RBF_SEQUENCE],
+        true,
+    );
+}
+
+#[test]
+fn test_rbf_signal_all_with_locktime() {
+    // `0xfffffffd` enables `nLockTime` already.
+    assert_sequences(
+        Some(RbfPolicy::SignalAll),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        None,
+        [MAX_BIP125_RBF_SEQUENCE, MAX_BIP125_RBF_SEQUENCE],
+        true,
+    );
+}
+
+#[test]
+fn test_rbf_signal_all_with_relative_timelock() {
+    assert_sequences(
+        Some(RbfPolicy::SignalAll),
+        0,
+        Some(RELATIVE_TIMELOCK),
+        [RELATIVE_TIMELOCK, MAX_BIP125_RBF_SEQUENCE],
+        true,
+    );
+    assert_sequences(
+        Some(RbfPolicy::SignalAll),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        Some(RELATIVE_TIMELOCK),
+        [RELATIVE_TIMELOCK, MAX_BIP125_RBF_SEQUENCE],
+        true,
+    );
+}
+
+#[test]
+fn test_rbf_disable() {
+    assert_sequences(
+        Some(RbfPolicy::Disable),
+        0,
+        None,
+        [SEQUENCE_FINAL, SEQUENCE_FINAL],
+        false,
+    );
+}
+
+#[test]
+fn test_rbf_disable_with_locktime() {
+    // `0xffffffff` would make the lock time ignored.
+    assert_sequences(
+        Some(RbfPolicy::Disable),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        None,
+        [
+            SEQUENCE_ENABLE_LOCKTIME_NO_RBF,
+            SEQUENCE_ENABLE_LOCKTIME_NO_RBF,
+        ],
+        false,
+    );
+}
+
+#[test]
+fn test_rbf_disable_with_relative_timelock() {
+    // The relative timelock is kept, and it signals RBF by itself.
+    assert_sequences(
+        Some(RbfPolicy::Disable),
+        0,
+        Some(RELATIVE_TIMELOCK),
+        [RELATIVE_TIMELOCK, SEQUENCE_FINAL],
+        true,
+    );
+    assert_sequences(
+        Some(RbfPolicy::Disable),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        Some(RELATIVE_TIMELOCK),
+        [RELATIVE_TIMELOCK, SEQUENCE_ENABLE_LOCKTIME_NO_RBF],
+        true,
+    );
+    // An explicit final sequence is kept too.
+    assert_sequences(
+        Some(RbfPolicy::Disable),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        Some(SEQUENCE_FINAL),
+        [SEQUENCE_FINAL, SEQUENCE_ENABLE_LOCKTIME_NO_RBF],
+        false,
+    );
+}
+
+#[test]
+fn test_rbf_custom() {
+    let custom = RbfPolicy::Custom(vec![SEQUENCE_ENABLE_LOCKTIME_NO_RBF, 0xfffffff0]);
+    assert_sequences(
+        Some(custom.clone()),
+        0,
+        None,
+        [SEQUENCE_ENABLE_LOCKTIME_NO_RBF, 0xfffffff0],
+        true,
+    );
+    // The custom sequences are used as is with a lock time.
+    assert_sequences(
+        Some(custom.clone()),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        None,
+        [SEQUENCE_ENABLE_LOCKTIME_NO_RBF, 0xfffffff0],
+        true,
+    );
+    // The explicit relative timelock takes precedence.
+    assert_sequences(
+        Some(custom),
+        ANTI_FEE_SNIPING_LOCKTIME,
+        Some(RELATIVE_TIMELOCK),
+        [RELATIVE_TIMELOCK, 0xfffffff0],
+        true,
+    );
+
+    let non_rbf = RbfPolicy::Custom(vec![SEQUENCE_FINAL, SEQUENCE_ENABLE_LOCKTIME_NO_RBF]);
+    assert_sequences(
+        Some(non_rbf),
+        0,
+        None,
+        [SEQUENCE_FINAL, SEQUENCE_ENABLE_LOCKTIME_NO_RBF],
+        false,
+    );
+}
+
+#[test]
+fn test_rbf_custom_invalid_length() {
+    let err = build_tx(Some(RbfPolicy::Custom(vec![SEQUENCE_FINAL])), 0, None)
+        .err()
+        .unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_invalid_params);
+}
+
+#[test]
+fn test_rbf_plan_signals_rbf() {
+    let plan = |policy: RbfPolicy| {
+        let request = PlanRequest {
+            ty: RequestType::SendExact {
+                unsigned_tx: build_tx(Some(policy), 0, None).unwrap(),
+                change_output: Some(OutputBuilder::new(0).p2wpkh(&public_key())),
+                input_selector: InputSelector::InOrder,
+            },
+            fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+            fee_per_vbyte: 10,
+            explicit_fee_output: None,
+            selection_policy: SelectionPolicy::Any,
+            output_shuffle_seed: None,
+            unconfirmed_parents: Vec::new(),
+        };
+        TxPlanner::plan(request).unwrap()
+    };
+
+    let signaling = plan(RbfPolicy::SignalAll);
+    assert!(signaling.plan.signals_rbf);
+    assert!(signaling.unsigned_tx.transaction().signals_rbf());
+
+    let non_signaling = plan(RbfPolicy::Disable);
+    assert!(!non_signaling.plan.signals_rbf);
+}
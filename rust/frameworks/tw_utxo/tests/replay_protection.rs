@@ -0,0 +1,221 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::chain_params::ChainTag;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::tx_summary::TxSummaryBuilder;
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectPlan, SelectionPolicy};
+use tw_utxo::replay_protection::ReplayProtection;
+use tw_utxo::script::standard_script::conditions::ScriptPubkeyType;
+use tw_utxo::sighash::{SighashBase, SighashType};
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+/// Bitcoin Cash fork id.
+const BCH_FORK_ID: u32 = 0;
+
+/// Builds a transaction spending P2PKH inputs signed with the given sighash types
+/// for the chain of the given fork id.
+fn build_tx(
+    chain: ChainTag,
+    fork_id: Option<u32>,
+    sighash_types: &[SighashType],
+    replay_protection: ReplayProtection,
+) -> SigningResult<UnsignedTransaction<Transaction>> {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let mut builder = TransactionBuilder::new();
+    for (index, sighash_ty) in sighash_types.iter().enumerate() {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(index as u32)
+            .amount(10_000)
+            .sighash_type(*sighash_ty)
+            .chain(chain)
+            .p2pkh(&public)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder
+        .chain(chain)
+        .fork_id(fork_id)
+        .push_output(OutputBuilder::new(15_000).p2pkh(&public))
+        .replay_protection(replay_protection);
+    builder.build()
+}
+
+fn fork_id_sighash() -> SighashType {
+    SighashType::with_fork_id(SighashBase::All, BCH_FORK_ID).unwrap()
+}
+
+fn plan(unsigned_tx: UnsignedTransaction<Transaction>) -> SelectPlan {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx,
+            change_output: Some(OutputBuilder::new(0).p2pkh(&public)),
+            input_selector: InputSelector::UseAll,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+        fee_per_vbyte: 1,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed: None,
+        unconfirmed_parents: Vec::new(),
+    })
+    .unwrap()
+    .plan
+}
+
+#[test]
+fn replay_protection_fork_id_sighash() {
+    let unsigned_tx = build_tx(
+        ChainTag::BitcoinCash,
+        Some(BCH_FORK_ID),
+        &[fork_id_sighash(), fork_id_sighash()],
+        ReplayProtection::ForkIdSighash,
+    )
+    .unwrap();
+    assert!(matches!(
+        unsigned_tx.replay_protection(),
+        Some(ReplayProtection::ForkIdSighash)
+    ));
+
+    // No outputs are added.
+    let summary = TxSummaryBuilder::summarize_unsigned(&unsigned_tx);
+    assert_eq!(summary.outputs.len(), 1);
+    assert_eq!(
+        summary.replay_protection.as_deref(),
+        Some("fork-id sighash")
+    );
+
+    // The plan reports the protection too.
+    let plan = plan(unsigned_tx);
+    assert!(matches!(
+        plan.replay_protection,
+        Some(ReplayProtection::ForkIdSighash)
+    ));
+}
+
+#[test]
+fn replay_protection_fork_id_sighash_missing() {
+    let err = build_tx(
+        ChainTag::BitcoinCash,
+        Some(BCH_FORK_ID),
+        &[fork_id_sighash(), SighashType::default()],
+        ReplayProtection::ForkIdSighash,
+    )
+    .unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_invalid_sighash_type
+    );
+    assert!(err.to_string().contains("Input #1"), "{err}");
+}
+
+#[test]
+fn replay_protection_fork_id_sighash_other_fork_id() {
+    // The signatures must commit to the fork id of the chain, e.g. Bitcoin Gold.
+    let err = build_tx(
+        ChainTag::BitcoinCash,
+        Some(79),
+        &[fork_id_sighash()],
+        ReplayProtection::ForkIdSighash,
+    )
+    .unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_invalid_sighash_type
+    );
+    assert!(err.to_string().contains("fork id 79"), "{err}");
+}
+
+#[test]
+fn replay_protection_fork_id_sighash_not_supported() {
+    let err = build_tx(
+        ChainTag::Bitcoin,
+        None,
+        &[SighashType::default()],
+        ReplayProtection::ForkIdSighash,
+    )
+    .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_supported);
+    assert!(
+        err.to_string()
+            .contains("The chain does not support the fork-id sighash"),
+        "{err}"
+    );
+}
+
+#[test]
+fn replay_protection_output() {
+    let protection_output = OutputBuilder::new(0)
+        .op_return(b"replay-protected")
+        .unwrap();
+    let unsigned_tx = build_tx(
+        ChainTag::Bitcoin,
+        None,
+        &[SighashType::default(), SighashType::default()],
+        ReplayProtection::ProtectionOutput(protection_output.clone()),
+    )
+    .unwrap();
+
+    // The protection output is appended after the payment.
+    let summary = TxSummaryBuilder::summarize_unsigned(&unsigned_tx);
+    assert_eq!(summary.outputs.len(), 2);
+    assert_eq!(summary.outputs[1].value, 0);
+    assert_eq!(summary.outputs[1].script_type, ScriptPubkeyType::OpReturn);
+    assert_eq!(
+        summary.replay_protection.as_deref(),
+        Some("protection output")
+    );
+
+    // The plain summary doesn't know about the builder.
+    let summary = TxSummaryBuilder::summarize(unsigned_tx.transaction());
+    assert_eq!(summary.replay_protection, None);
+
+    // The plan reports the protection too.
+    let plan = plan(unsigned_tx);
+    assert!(matches!(
+        plan.replay_protection,
+        Some(ReplayProtection::ProtectionOutput(ref output))
+            if output.script_pubkey == protection_output.script_pubkey
+    ));
+}
+
+#[test]
+fn replay_protection_output_zero_amount() {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    // Only OP_RETURN outputs may carry no amount.
+    let err = build_tx(
+        ChainTag::Bitcoin,
+        None,
+        &[SighashType::default()],
+        ReplayProtection::ProtectionOutput(OutputBuilder::new(0).p2pkh(&public)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_dust_amount_requested
+    );
+    assert!(
+        err.to_string()
+            .contains("Cannot apply the protection output replay protection"),
+        "{err}"
+    );
+}
@@ -0,0 +1,170 @@
+use serde_json::{json, Value as Json};
+use tw_encoding::hex::{self, DecodeHex};
+use tw_utxo::chain_params::ChainParams;
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::transaction::standard_transaction::builder::txid_from_str_and_rev;
+use tw_utxo::transaction::standard_transaction::{
+    Transaction, TransactionInput, TransactionOutput,
+};
+use tw_utxo::transaction::transaction_parts::OutPoint;
+
+fn script(hex: &str) -> Script {
+    Script::from(hex.decode_hex().unwrap())
+}
+
+fn input(prev_txid: &str, script_sig: &str, witness: &[&str]) -> TransactionInput {
+    let mut witness_stack = Witness::default();
+    for item in witness {
+        witness_stack.push_item(script(item));
+    }
+
+    TransactionInput {
+        previous_output: OutPoint {
+            hash: txid_from_str_and_rev(prev_txid).unwrap(),
+            index: 0,
+        },
+        sequence: u32::MAX,
+        script_sig: script(script_sig),
+        witness: witness_stack,
+    }
+}
+
+fn output(value: u64, script_pubkey: &str) -> TransactionOutput {
+    TransactionOutput {
+        value,
+        script_pubkey: script(script_pubkey),
+    }
+}
+
+#[track_caller]
+fn test_rpc_json(tx: Transaction, expected_raw: &str, expected_json: Json) {
+    assert_eq!(hex::encode(tx.encode_out(), false), expected_raw);
+    assert_eq!(
+        tx.to_rpc_json(&ChainParams::bitcoin_mainnet()),
+        expected_json
+    );
+}
+
+/// The expected JSON follows the `bitcoin-cli decoderawtransaction` output,
+/// except that `value` is a string and `scriptPubKey.desc` is omitted.
+#[test]
+fn test_rpc_json_legacy() {
+    // Mainnet tx f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16.
+    let sig = "304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09";
+    let pk_1 = "04ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c1b7303b8a0626f1baded5c72a704f7e6cd84c";
+    let pk_2 = "0411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3";
+
+    let tx = Transaction {
+        version: 1,
+        inputs: vec![input(
+            "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9",
+            &format!("47{sig}01"),
+            &[],
+        )],
+        outputs: vec![
+            output(1_000_000_000, &format!("41{pk_1}ac")),
+            output(4_000_000_000, &format!("41{pk_2}ac")),
+        ],
+        locktime: 0,
+    };
+
+    let expected_raw = format!("0100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704000000004847{sig}01ffffffff0200ca9a3b000000004341{pk_1}ac00286bee000000004341{pk_2}ac00000000");
+    let expected_json = json!({
+        "txid": "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+        "hash": "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+        "version": 1,
+        "size": 275,
+        "vsize": 275,
+        "weight": 1100,
+        "locktime": 0,
+        "vin": [
+            {
+                "txid": "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9",
+                "vout": 0,
+                "scriptSig": {
+                    "asm": format!("{sig}[ALL]"),
+                    "hex": format!("47{sig}01"),
+                },
+                "sequence": 4294967295_u32,
+            }
+        ],
+        "vout": [
+            {
+                "value": "10.00000000",
+                "n": 0,
+                "scriptPubKey": {
+                    "asm": format!("{pk_1} OP_CHECKSIG"),
+                    "hex": format!("41{pk_1}ac"),
+                    "type": "pubkey",
+                },
+            },
+            {
+                "value": "40.00000000",
+                "n": 1,
+                "scriptPubKey": {
+                    "asm": format!("{pk_2} OP_CHECKSIG"),
+                    "hex": format!("41{pk_2}ac"),
+                    "type": "pubkey",
+                },
+            }
+        ],
+    });
+
+    test_rpc_json(tx, &expected_raw, expected_json);
+}
+
+/// Mainnet tx 6c972fe677b6ed76e51c6eb9eb3f50958932919f1858948bbc1be0b1474ccca9
+/// spending a P2WPKH output, taken from the rust-bitcoin test suite.
+#[test]
+fn test_rpc_json_segwit() {
+    let expected_raw = "020000000001018a763b78d3e17acea0625bf9e52b0dc1beb2241b2502185348ba8ff4a253176e0100000000ffffffff0280d725000000000017a914c07ed639bd46bf7087f2ae1dfde63b815a5f8b488767fda20300000000160014869ec8520fa2801c8a01bfdd2e82b19833cd0daf02473044022016243edad96b18c78b545325aaff80131689f681079fb107a67018cb7fb7830e02205520dae761d89728f73f1a7182157f6b5aecf653525855adb7ccb998c8e6143b012103b9489bde92afbcfa85129a82ffa512897105d1a27ad9806bded27e0532fc84e700000000";
+    let sig = "3044022016243edad96b18c78b545325aaff80131689f681079fb107a67018cb7fb7830e02205520dae761d89728f73f1a7182157f6b5aecf653525855adb7ccb998c8e6143b01";
+    let pubkey = "03b9489bde92afbcfa85129a82ffa512897105d1a27ad9806bded27e0532fc84e7";
+
+    let tx = Transaction::from_bytes(&expected_raw.decode_hex().unwrap()).unwrap();
+    let expected_json = json!({
+        "txid": "6c972fe677b6ed76e51c6eb9eb3f50958932919f1858948bbc1be0b1474ccca9",
+        "hash": "fdea4ae43e639d38c7446c1454f279aa7c0768741bb58ae6c01e88a8b23bef37",
+        "version": 2,
+        "size": 223,
+        "vsize": 142,
+        "weight": 565,
+        "locktime": 0,
+        "vin": [
+            {
+                "txid": "6e1753a2f48fba48531802251b24b2bec10d2be5f95b62a0ce7ae1d3783b768a",
+                "vout": 1,
+                "scriptSig": {
+                    "asm": "",
+                    "hex": "",
+                },
+                "txinwitness": [sig, pubkey],
+                "sequence": 4294967295_u32,
+            }
+        ],
+        "vout": [
+            {
+                "value": "0.02480000",
+                "n": 0,
+                "scriptPubKey": {
+                    "asm": "OP_HASH160 c07ed639bd46bf7087f2ae1dfde63b815a5f8b48 OP_EQUAL",
+                    "hex": "a914c07ed639bd46bf7087f2ae1dfde63b815a5f8b4887",
+                    "type": "scripthash",
+                    "address": "3KEqc9m4KUygL8Gc5QY3y6TyUeqExk6dp8",
+                },
+            },
+            {
+                "value": "0.61013351",
+                "n": 1,
+                "scriptPubKey": {
+                    "asm": "0 869ec8520fa2801c8a01bfdd2e82b19833cd0daf",
+                    "hex": "0014869ec8520fa2801c8a01bfdd2e82b19833cd0daf",
+                    "type": "witness_v0_keyhash",
+                    "address": "bc1qs60vs5s052qpezsphlwjaq43nqeu6rd0ax265f",
+                },
+            }
+        ],
+    });
+
+    test_rpc_json(tx, expected_raw, expected_json);
+}
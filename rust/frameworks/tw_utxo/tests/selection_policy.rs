@@ -0,0 +1,189 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::utxo_selector::{
+    ExclusionReason, InputSelector, SelectResult, SelectionPolicy,
+};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_parts::Amount;
+
+const DUST: Amount = 546;
+
+fn public_key() -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public()
+}
+
+/// The confirmation status of a test UTXO.
+enum Utxo {
+    Confirmed(u32),
+    OwnUnconfirmedChange,
+    Unconfirmed,
+    Unknown,
+}
+
+fn plan(
+    utxos: &[(Amount, Utxo)],
+    send_amount: Amount,
+    selection_policy: SelectionPolicy,
+) -> SigningResult<SelectResult<Transaction>> {
+    let public = public_key();
+
+    let mut builder = TransactionBuilder::new();
+    for (prev_index, (amount, utxo)) in utxos.iter().enumerate() {
+        let utxo_builder = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(prev_index as u32)
+            .amount(*amount)
+            .sighash_type(SighashType::default());
+        let utxo_builder = match utxo {
+            Utxo::Confirmed(confirmations) => utxo_builder.confirmations(*confirmations),
+            Utxo::OwnUnconfirmedChange => utxo_builder.confirmations(0).change_from_self(),
+            Utxo::Unconfirmed => utxo_builder.confirmations(0),
+            Utxo::Unknown => utxo_builder,
+        };
+        let (utxo, arg) = utxo_builder.p2wpkh(&public).unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder.push_output(OutputBuilder::new(send_amount).p2wpkh(&public));
+
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx: builder.build().unwrap(),
+            change_output: Some(OutputBuilder::new(0).p2wpkh(&public)),
+            input_selector: InputSelector::InOrder,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(DUST)),
+        fee_per_vbyte: 1,
+        explicit_fee_output: None,
+        selection_policy,
+        output_shuffle_seed: None,
+        unconfirmed_parents: Vec::new(),
+    })
+}
+
+fn selected_indexes(result: &SelectResult<Transaction>) -> Vec<u32> {
+    let tx = result.unsigned_tx.transaction();
+    tx.inputs
+        .iter()
+        .map(|input| input.previous_output.index)
+        .collect()
+}
+
+fn excluded(result: &SelectResult<Transaction>) -> Vec<(usize, ExclusionReason)> {
+    result
+        .plan
+        .excluded
+        .iter()
+        .map(|utxo| (utxo.index, utxo.reason))
+        .collect()
+}
+
+#[test]
+fn test_selection_policy_any() {
+    let utxos = [
+        (20_000, Utxo::Unconfirmed),
+        (20_000, Utxo::OwnUnconfirmedChange),
+        (20_000, Utxo::Confirmed(6)),
+    ];
+
+    let result = plan(&utxos, 10_000, SelectionPolicy::Any).unwrap();
+    assert_eq!(selected_indexes(&result), vec![0]);
+    assert!(result.plan.excluded.is_empty());
+}
+
+#[test]
+fn test_selection_policy_only_confirmed() {
+    let utxos = [
+        (20_000, Utxo::Unconfirmed),
+        (20_000, Utxo::OwnUnconfirmedChange),
+        (20_000, Utxo::Unknown),
+        (20_000, Utxo::Confirmed(6)),
+    ];
+
+    let result = plan(&utxos, 10_000, SelectionPolicy::OnlyConfirmed).unwrap();
+    assert_eq!(selected_indexes(&result), vec![3]);
+    assert_eq!(
+        excluded(&result),
+        vec![
+            (0, ExclusionReason::Unconfirmed),
+            (1, ExclusionReason::Unconfirmed),
+            (2, ExclusionReason::Unconfirmed),
+        ]
+    );
+
+    // Relaxing the policy allows spending the own unconfirmed change first.
+    let result = plan(&utxos, 10_000, SelectionPolicy::AllowOwnUnconfirmedChange).unwrap();
+    assert_eq!(selected_indexes(&result), vec![1]);
+    assert_eq!(
+        excluded(&result),
+        vec![
+            (0, ExclusionReason::Unconfirmed),
+            (2, ExclusionReason::Unconfirmed),
+        ]
+    );
+}
+
+#[test]
+fn test_selection_policy_min_confirmations() {
+    let utxos = [
+        (20_000, Utxo::Confirmed(1)),
+        (20_000, Utxo::Confirmed(2)),
+        (20_000, Utxo::Confirmed(6)),
+    ];
+
+    let result = plan(&utxos, 10_000, SelectionPolicy::MinConfirmations(6)).unwrap();
+    assert_eq!(selected_indexes(&result), vec![2]);
+    assert_eq!(
+        excluded(&result),
+        vec![
+            (
+                0,
+                ExclusionReason::NotEnoughConfirmations {
+                    confirmations: 1,
+                    required: 6,
+                }
+            ),
+            (
+                1,
+                ExclusionReason::NotEnoughConfirmations {
+                    confirmations: 2,
+                    required: 6,
+                }
+            ),
+        ]
+    );
+
+    let result = plan(&utxos, 10_000, SelectionPolicy::MinConfirmations(2)).unwrap();
+    assert_eq!(selected_indexes(&result), vec![1]);
+}
+
+#[test]
+fn test_selection_policy_not_enough_utxos() {
+    let utxos = [
+        (20_000, Utxo::Confirmed(6)),
+        (20_000, Utxo::Unconfirmed),
+        (DUST - 1, Utxo::Confirmed(6)),
+    ];
+
+    // The unconfirmed UTXO is required to cover the amount.
+    let err = plan(&utxos, 30_000, SelectionPolicy::OnlyConfirmed)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_enough_utxos);
+
+    let result = plan(&utxos, 30_000, SelectionPolicy::Any).unwrap();
+    assert_eq!(selected_indexes(&result), vec![0, 1]);
+    // Dust UTXOs are excluded regardless of the policy.
+    assert_eq!(excluded(&result), vec![(2, ExclusionReason::Dust)]);
+}
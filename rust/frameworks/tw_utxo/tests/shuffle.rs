@@ -0,0 +1,201 @@
+mod common;
+
+use crate::common::TXID;
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::keys_manager::KeysManager;
+use tw_utxo::modules::sighash_computer::SighashComputer;
+use tw_utxo::modules::sighash_verifier::SighashVerifier;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectResult, SelectionPolicy};
+use tw_utxo::shuffle::{shuffle, ChaChaShuffleRng};
+use tw_utxo::sighash::{SighashBase, SighashType};
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TxOutputInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const INPUT_AMOUNTS: [Amount; 3] = [10_000, 20_000, 30_000];
+const OUTPUT_AMOUNTS: [Amount; 4] = [1_000, 2_000, 3_000, 4_000];
+
+/// A different key per input.
+fn private_key(input_index: usize) -> ecdsa::secp256k1::PrivateKey {
+    let secret = [input_index as u8 + 1; 32];
+    ecdsa::secp256k1::PrivateKey::try_from(secret.as_slice()).unwrap()
+}
+
+/// Builds a transaction spending the `i`-th output of [`TXID`] in the `i`-th input.
+fn build_tx() -> UnsignedTransaction<Transaction> {
+    build_tx_with_sighash(SighashType::default())
+}
+
+/// Same as [`build_tx`], but the inputs are signed with the given sighash type.
+fn build_tx_with_sighash(sighash_type: SighashType) -> UnsignedTransaction<Transaction> {
+    let txid = txid_from_str_and_rev(TXID).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    for (i, amount) in INPUT_AMOUNTS.into_iter().enumerate() {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(i as u32)
+            .amount(amount)
+            .sighash_type(sighash_type)
+            .p2wpkh(&private_key(i).public())
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    for amount in OUTPUT_AMOUNTS {
+        builder.push_output(OutputBuilder::new(amount).p2wpkh(&private_key(0).public()));
+    }
+    builder.build().unwrap()
+}
+
+/// Builds the transaction and shuffles its inputs and outputs given the seed.
+fn build_shuffled_tx(seed: [u8; 32]) -> UnsignedTransaction<Transaction> {
+    let mut unsigned_tx = build_tx();
+
+    let mut rng = ChaChaShuffleRng::from_seed(seed);
+    unsigned_tx.shuffle_inputs(&mut rng);
+    unsigned_tx.shuffle_outputs(&mut rng);
+    unsigned_tx
+}
+
+fn plan(output_shuffle_seed: Option<[u8; 32]>) -> SelectResult<Transaction> {
+    plan_tx(build_tx(), output_shuffle_seed).unwrap()
+}
+
+fn plan_tx(
+    unsigned_tx: UnsignedTransaction<Transaction>,
+    output_shuffle_seed: Option<[u8; 32]>,
+) -> SigningResult<SelectResult<Transaction>> {
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx,
+            change_output: Some(OutputBuilder::new(0).p2wpkh(&private_key(0).public())),
+            input_selector: InputSelector::UseAll,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+        fee_per_vbyte: 1,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed,
+        unconfirmed_parents: Vec::new(),
+    })
+}
+
+fn input_order(tx: &Transaction) -> Vec<u32> {
+    tx.inputs
+        .iter()
+        .map(|input| input.previous_output.index)
+        .collect()
+}
+
+fn output_amounts(tx: &Transaction) -> Vec<Amount> {
+    tx.outputs.iter().map(|output| output.value()).collect()
+}
+
+#[test]
+fn test_shuffle_deterministic() {
+    // The order is fixed given the seed, and doesn't depend on the platform.
+    let unsigned_tx = build_shuffled_tx([7; 32]);
+    assert_eq!(input_order(unsigned_tx.transaction()), vec![2, 0, 1]);
+    assert_eq!(
+        output_amounts(unsigned_tx.transaction()),
+        vec![4_000, 3_000, 2_000, 1_000]
+    );
+
+    let same_seed_tx = build_shuffled_tx([7; 32]);
+    assert_eq!(
+        same_seed_tx.transaction().inputs,
+        unsigned_tx.transaction().inputs
+    );
+    assert_eq!(
+        output_amounts(same_seed_tx.transaction()),
+        output_amounts(unsigned_tx.transaction())
+    );
+
+    let other_seed_tx = build_shuffled_tx([8; 32]);
+    assert_eq!(input_order(other_seed_tx.transaction()), vec![2, 0, 1]);
+    assert_eq!(
+        output_amounts(other_seed_tx.transaction()),
+        vec![2_000, 3_000, 4_000, 1_000]
+    );
+}
+
+#[test]
+fn test_shuffle_signatures_valid() {
+    let mut keys_manager = KeysManager::default();
+    for i in 0..INPUT_AMOUNTS.len() {
+        keys_manager.add_ecdsa_private(private_key(i));
+    }
+
+    let unsigned_tx = build_shuffled_tx([7; 32]);
+    let tx = TxSigner::sign_tx(build_shuffled_tx([7; 32]), &keys_manager).unwrap();
+    let sighashes = SighashComputer::preimage_tx(&unsigned_tx)
+        .unwrap()
+        .sighashes;
+
+    for (i, input) in tx.inputs.iter().enumerate() {
+        // The signing arguments are moved along with the inputs.
+        let prev_index = input.previous_output.index as usize;
+        let utxo_args = &unsigned_tx.input_args()[i];
+        assert_eq!(utxo_args.amount, INPUT_AMOUNTS[prev_index]);
+
+        let [sig, pubkey] = input.witness.as_items() else {
+            panic!("Expected <sig> <pubkey> witness");
+        };
+        assert_eq!(
+            pubkey.as_slice(),
+            private_key(prev_index).public().compressed().as_slice()
+        );
+
+        let (_sighash_type, der) = sig.as_slice().split_last().unwrap();
+        SighashVerifier::<Transaction>::verify_ecdsa_signature(
+            &sighashes[i],
+            pubkey.as_slice(),
+            der,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_plan_shuffle_outputs() {
+    let planned = plan(None);
+    let shuffled = plan(Some([7; 32]));
+
+    // The change output is shuffled along with the other outputs.
+    let mut expected = output_amounts(planned.unsigned_tx.transaction());
+    assert_eq!(expected.len(), OUTPUT_AMOUNTS.len() + 1);
+    shuffle(&mut expected, &mut ChaChaShuffleRng::from_seed([7; 32]));
+    assert_eq!(output_amounts(shuffled.unsigned_tx.transaction()), expected);
+
+    // The inputs and the plan are not affected.
+    assert_eq!(
+        shuffled.unsigned_tx.transaction().inputs,
+        planned.unsigned_tx.transaction().inputs
+    );
+    assert_eq!(shuffled.plan.fee_estimate, planned.plan.fee_estimate);
+    assert_eq!(shuffled.plan.change, planned.plan.change);
+}
+
+#[test]
+fn test_plan_shuffle_outputs_sighash_single() {
+    // `SIGHASH_SINGLE` inputs sign the outputs at their indices, so the outputs cannot be shuffled.
+    let single = SighashType::from_u32(SighashBase::Single as u32).unwrap();
+    let error = plan_tx(build_tx_with_sighash(single), Some([7; 32])).unwrap_err();
+    assert_eq!(*error.error_type(), SigningErrorType::Error_invalid_params);
+
+    // But they keep their order without the seed.
+    let planned = plan_tx(build_tx_with_sighash(single), None).unwrap();
+    assert_eq!(
+        output_amounts(planned.unsigned_tx.transaction())[..OUTPUT_AMOUNTS.len()],
+        OUTPUT_AMOUNTS
+    );
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use crate::common::{keys_manager, PRIVATE_KEY, TXID};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::modules::signer_metrics::{SignerMetrics, SigningPhase};
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+
+/// Records all the reported phases.
+#[derive(Default)]
+struct RecordingMetrics {
+    events: RefCell<Vec<(SigningPhase, usize, Duration)>>,
+}
+
+impl RecordingMetrics {
+    /// Returns the number of times every `(phase, input_index)` pair has been reported.
+    fn counts(&self) -> HashMap<(SigningPhase, usize), usize> {
+        let mut counts = HashMap::new();
+        for (phase, input_index, _) in self.events.borrow().iter() {
+            *counts.entry((*phase, *input_index)).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl SignerMetrics for RecordingMetrics {
+    fn on_phase(&self, phase: SigningPhase, input_index: usize, duration: Duration) {
+        self.events
+            .borrow_mut()
+            .push((phase, input_index, duration));
+    }
+}
+
+#[test]
+fn sign_tx_reports_every_phase_once_per_input() {
+    let ecdsa_pubkey = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+    let schnorr_pubkey = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+    let txid = txid_from_str_and_rev(TXID).unwrap();
+
+    let utxo_builder = |index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(index)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+    };
+    let make_unsigned_tx = || {
+        let (utxo1, arg1) = utxo_builder(0).p2pkh(&ecdsa_pubkey).unwrap();
+        let (utxo2, arg2) = utxo_builder(1).p2wpkh(&ecdsa_pubkey).unwrap();
+        let (utxo3, arg3) = utxo_builder(2).p2tr_key_path(&schnorr_pubkey).unwrap();
+
+        let output = OutputBuilder::new(100_000).p2wpkh(&ecdsa_pubkey);
+
+        let mut builder = TransactionBuilder::new();
+        builder
+            .push_input(utxo1, arg1)
+            .push_input(utxo2, arg2)
+            .push_input(utxo3, arg3)
+            .push_output(output);
+        builder.build().unwrap()
+    };
+
+    let keys_manager = keys_manager();
+
+    let metrics = RecordingMetrics::default();
+    let signed_tx =
+        TxSigner::sign_tx_with_metrics(make_unsigned_tx(), &keys_manager, &metrics).unwrap();
+
+    let phases = [
+        SigningPhase::Preimage,
+        SigningPhase::Signing,
+        SigningPhase::Serialization,
+        SigningPhase::ScriptAssembly,
+    ];
+    let counts = metrics.counts();
+    assert_eq!(counts.len(), phases.len() * 3);
+    for input_index in 0..3 {
+        for phase in phases {
+            assert_eq!(
+                counts.get(&(phase, input_index)),
+                Some(&1),
+                "{} phase of #{input_index} input",
+                phase.as_str()
+            );
+        }
+    }
+
+    // Metrics do not affect the signed transaction.
+    let expected = TxSigner::sign_tx(make_unsigned_tx(), &keys_manager).unwrap();
+    assert_eq!(signed_tx.encode_out(), expected.encode_out());
+}
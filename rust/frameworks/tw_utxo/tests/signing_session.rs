@@ -0,0 +1,145 @@
+mod common;
+
+use crate::common::{keys_manager, PRIVATE_KEY, TXID};
+use std::cell::Cell;
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+/// Builds a transaction spending P2PKH, P2WPKH, P2TR and P2WPKH inputs of the same key.
+fn unsigned_tx() -> UnsignedTransaction<Transaction> {
+    let ecdsa_public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+    let schnorr_public = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+    let utxo_builder = |index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(index)
+            .amount(50_000)
+            .sighash_type(SighashType::default())
+    };
+    let (utxo1, arg1) = utxo_builder(0).p2pkh(&ecdsa_public).unwrap();
+    let (utxo2, arg2) = utxo_builder(1).p2wpkh(&ecdsa_public).unwrap();
+    let (utxo3, arg3) = utxo_builder(2).p2tr_key_path(&schnorr_public).unwrap();
+    let (utxo4, arg4) = utxo_builder(3).p2wpkh(&ecdsa_public).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_input(utxo3, arg3)
+        .push_input(utxo4, arg4)
+        .push_output(OutputBuilder::new(190_000).p2wpkh(&ecdsa_public));
+    builder.build().unwrap()
+}
+
+#[test]
+fn signing_session_resume_equals_uninterrupted_signing() {
+    let keys_manager = keys_manager();
+
+    let mut session = TxSigner::start_session(unsigned_tx()).unwrap();
+    session.sign_input(0, &keys_manager).unwrap();
+    session.sign_input(2, &keys_manager).unwrap();
+    let saved = session.to_bytes().unwrap();
+
+    let mut resumed = TxSigner::<Transaction>::resume(&saved).unwrap();
+    assert!(resumed.is_signed(0));
+    assert!(resumed.is_signed(2));
+    assert_eq!(resumed.remaining_inputs(), vec![1, 3]);
+
+    resumed.sign_remaining(&keys_manager).unwrap();
+    let signed_tx = resumed.finish().unwrap();
+
+    let expected = TxSigner::sign_tx(unsigned_tx(), &keys_manager).unwrap();
+    assert_eq!(signed_tx.encode_out(), expected.encode_out());
+}
+
+#[test]
+fn signing_session_preserves_signatures() {
+    let keys_manager = keys_manager();
+
+    let mut session = TxSigner::start_session(unsigned_tx()).unwrap();
+    session.sign_input(0, &keys_manager).unwrap();
+    session.sign_input(1, &keys_manager).unwrap();
+    let saved = session.to_bytes().unwrap();
+
+    let mut resumed = TxSigner::<Transaction>::resume(&saved).unwrap();
+    let calls = Cell::new(0);
+    for input_index in 0..4 {
+        resumed
+            .sign_input_with_signer(input_index, |sighash| {
+                calls.set(calls.get() + 1);
+                TxSigner::<Transaction>::sign_sighash(&keys_manager, sighash)
+            })
+            .unwrap();
+    }
+    // The signed inputs are not signed again.
+    assert_eq!(calls.get(), 2);
+    assert!(resumed.remaining_inputs().is_empty());
+
+    // The session ends with the flags of the two missing signatures,
+    // everything before them is kept as is.
+    let unchanged = &saved[..saved.len() - 2];
+    assert_eq!(&resumed.to_bytes().unwrap()[..unchanged.len()], unchanged);
+}
+
+#[test]
+fn signing_session_resume_content_digest_mismatch() {
+    let keys_manager = keys_manager();
+
+    let mut session = TxSigner::start_session(unsigned_tx()).unwrap();
+    session.sign_input(0, &keys_manager).unwrap();
+    let mut saved = session.to_bytes().unwrap();
+    // The digest follows the format version.
+    saved[1] ^= 0x01;
+
+    let err = TxSigner::<Transaction>::resume(&saved).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_input_parse);
+}
+
+#[test]
+fn signing_session_resume_tampered_signature() {
+    let keys_manager = keys_manager();
+
+    let mut session = TxSigner::start_session(unsigned_tx()).unwrap();
+    session.sign_input(0, &keys_manager).unwrap();
+    let mut saved = session.to_bytes().unwrap();
+    // The session ends with the `r || s || v` signature of the first input
+    // and the flags of the three missing signatures, so the byte is the last one of `s`.
+    let signature_byte = saved.len() - 5;
+    saved[signature_byte] ^= 0x01;
+
+    let err = TxSigner::<Transaction>::resume(&saved).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_signing);
+}
+
+#[test]
+fn signing_session_finish_not_signed() {
+    let keys_manager = keys_manager();
+
+    let mut session = TxSigner::start_session(unsigned_tx()).unwrap();
+    session.sign_input(1, &keys_manager).unwrap();
+
+    let err = session.finish().unwrap_err();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_signatures_count);
+    assert!(err.to_string().contains("0, 2, 3"), "{err}");
+}
+
+#[test]
+fn signing_session_resume_invalid_bytes() {
+    let session = TxSigner::start_session(unsigned_tx()).unwrap();
+    let mut saved = session.to_bytes().unwrap();
+    // Unknown format version.
+    saved[0] = 0xFF;
+
+    let err = TxSigner::<Transaction>::resume(&saved).err().unwrap();
+    assert_eq!(err.error_type(), &SigningErrorType::Error_input_parse);
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::modules::keys_manager::KeysManager;
+use tw_utxo::modules::sighash_computer::SighashComputer;
+use tw_utxo::modules::signer_metrics::NoopSignerMetrics;
+use tw_utxo::modules::tx_compiler::TxCompiler;
+use tw_utxo::modules::tx_signer::TxSigner;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const OTHER_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+
+fn ecdsa_public(private_key: &str) -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(private_key)
+        .unwrap()
+        .public()
+}
+
+fn utxo_builder(index: u32) -> UtxoBuilder {
+    UtxoBuilder::new()
+        .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+        .prev_index(index)
+        .amount(10_000)
+        .sighash_type(SighashType::default())
+}
+
+/// Builds a transaction sweeping `count` P2WPKH inputs of the same key.
+fn sweep_tx(count: u32) -> UnsignedTransaction<Transaction> {
+    let public = ecdsa_public(PRIVATE_KEY);
+
+    let mut builder = TransactionBuilder::new();
+    for index in 0..count {
+        let (utxo, arg) = utxo_builder(index).p2wpkh(&public).unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder.push_output(OutputBuilder::new(count as u64 * 9_000).p2wpkh(&public));
+    builder.build().unwrap()
+}
+
+/// Signs every input parsing the private key and deriving its public key again,
+/// as if the key was looked up per input.
+fn sign_reparsing_per_input(unsigned_tx: UnsignedTransaction<Transaction>) -> Transaction {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+
+    let claims = unsigned_tx
+        .input_args()
+        .iter()
+        .enumerate()
+        .map(|(input_index, utxo)| {
+            let sighash = SighashComputer::preimage_utxo(&unsigned_tx, input_index, utxo).unwrap();
+
+            let mut keys_manager = KeysManager::default();
+            keys_manager.add_ecdsa_private_bytes(&private_key).unwrap();
+            let signature = TxSigner::sign_sighash(&keys_manager, &sighash).unwrap();
+
+            TxCompiler::utxo_spending_data(utxo, &signature, input_index, &NoopSignerMetrics)
+                .unwrap()
+        })
+        .collect();
+
+    TxCompiler::compile_spending_data(unsigned_tx, claims).unwrap()
+}
+
+/// Signing speed is measured by `benches/single_key_signer.rs`.
+#[test]
+fn sign_with_single_key_sweep() {
+    const COUNT: u32 = 500;
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+
+    let expected = sign_reparsing_per_input(sweep_tx(COUNT));
+    let signed = TxSigner::sign_with_single_key(sweep_tx(COUNT), &private_key).unwrap();
+
+    assert_eq!(signed.inputs().len(), COUNT as usize);
+    assert_eq!(signed.encode_out(), expected.encode_out());
+
+    // The keys manager path gives the same transaction too.
+    let mut keys_manager = KeysManager::default();
+    keys_manager.add_ecdsa_private_bytes(&private_key).unwrap();
+    let signed_tx = TxSigner::sign_tx(sweep_tx(COUNT), &keys_manager).unwrap();
+    assert_eq!(signed_tx.encode_out(), expected.encode_out());
+}
+
+#[test]
+fn sign_with_single_key_mixed_inputs() {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+    let ecdsa_public = ecdsa_public(PRIVATE_KEY);
+    let schnorr_public = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+    let (utxo1, arg1) = utxo_builder(0).p2pkh(&ecdsa_public).unwrap();
+    let (utxo2, arg2) = utxo_builder(1).p2wpkh(&ecdsa_public).unwrap();
+    let (utxo3, arg3) = utxo_builder(2)
+        .sighash_type(SighashType::sighash_default())
+        .p2tr_key_path(&schnorr_public)
+        .unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .push_input(utxo1, arg1)
+        .push_input(utxo2, arg2)
+        .push_input(utxo3, arg3)
+        .push_output(OutputBuilder::new(25_000).p2wpkh(&ecdsa_public));
+    let unsigned_tx = builder.build().unwrap();
+
+    let signed = TxSigner::sign_with_single_key(unsigned_tx, &private_key).unwrap();
+    assert_eq!(signed.inputs().len(), 3);
+    // P2TR key-path spending: a single 64 bytes Schnorr signature.
+    assert_eq!(signed.inputs()[2].witness.as_items()[0].len(), 64);
+}
+
+#[test]
+fn sign_with_single_key_unsatisfied_inputs() {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+    let public = ecdsa_public(PRIVATE_KEY);
+    let other_public = ecdsa_public(OTHER_PRIVATE_KEY);
+
+    let mut builder = TransactionBuilder::new();
+    for (index, public) in [&other_public, &public, &other_public, &public]
+        .into_iter()
+        .enumerate()
+    {
+        let (utxo, arg) = utxo_builder(index as u32).p2wpkh(public).unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder.push_output(OutputBuilder::new(30_000).p2wpkh(&public));
+    let unsigned_tx = builder.build().unwrap();
+
+    let err = TxSigner::sign_with_single_key(unsigned_tx, &private_key).unwrap_err();
+    assert_eq!(
+        err.error_type(),
+        &SigningErrorType::Error_missing_private_key
+    );
+    assert!(
+        err.to_string()
+            .contains("The private key cannot sign the inputs: 0, 2"),
+        "{err}"
+    );
+}
+
+#[test]
+fn keys_manager_same_key_added_twice() {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+    let public = ecdsa_public(PRIVATE_KEY);
+
+    let mut keys_manager = KeysManager::default();
+    keys_manager
+        .add_ecdsa_private_bytes(&private_key)
+        .unwrap()
+        .add_ecdsa_private_bytes(&private_key)
+        .unwrap()
+        .add_ecdsa_private(ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap());
+
+    let by_public = keys_manager.get_ecdsa_private(&public).unwrap();
+    let by_bytes = keys_manager
+        .get_ecdsa_private_by_bytes(public.compressed().as_slice())
+        .unwrap();
+    let by_uncompressed = keys_manager
+        .get_ecdsa_private_by_bytes(public.uncompressed().as_slice())
+        .unwrap();
+    let pubkey_hash = tw_hash::H160::try_from(
+        tw_hash::hasher::sha256_ripemd(public.compressed().as_slice()).as_slice(),
+    )
+    .unwrap();
+    let by_hash = keys_manager
+        .get_ecdsa_private_by_pubkey_hash(&pubkey_hash)
+        .unwrap();
+
+    assert_eq!(by_public.public().compressed(), public.compressed());
+    assert_eq!(by_bytes.public().compressed(), public.compressed());
+    assert_eq!(by_uncompressed.public().compressed(), public.compressed());
+    assert_eq!(by_hash.public().compressed(), public.compressed());
+
+    let other_public = ecdsa_public(OTHER_PRIVATE_KEY);
+    let err = keys_manager.get_ecdsa_private(&other_public).err().unwrap();
+    assert_eq!(
+        err.error_type(),
+        &SigningErrorType::Error_missing_private_key
+    );
+}
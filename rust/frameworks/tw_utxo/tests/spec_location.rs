@@ -0,0 +1,154 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TxOutputInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+const DUST: Amount = 546;
+
+fn public_key() -> ecdsa::secp256k1::PublicKey {
+    ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public()
+}
+
+/// Creates a builder spending P2WPKH UTXOs of the given amounts to P2WPKH outputs of the given amounts.
+fn tx_builder(utxo_amounts: &[Amount], output_amounts: &[Amount]) -> TransactionBuilder {
+    let public = public_key();
+
+    let mut builder = TransactionBuilder::new();
+    for (prev_index, amount) in utxo_amounts.iter().enumerate() {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(prev_index as u32)
+            .amount(*amount)
+            .sighash_type(SighashType::default())
+            .p2wpkh(&public)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    for amount in output_amounts {
+        builder.push_output(OutputBuilder::new(*amount).p2wpkh(&public));
+    }
+    builder
+}
+
+fn plan(unsigned_tx: UnsignedTransaction<Transaction>) -> SigningResult<()> {
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx,
+            change_output: Some(OutputBuilder::new(0).p2wpkh(&public_key())),
+            input_selector: InputSelector::InOrder,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(DUST)),
+        fee_per_vbyte: 1,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed: None,
+        unconfirmed_parents: Vec::new(),
+    })
+    .map(|_| ())
+}
+
+/// Returns the index of the input the error `message` locates.
+fn located_input(message: &str) -> usize {
+    let (_, located) = message.split_once("input #").expect(message);
+    let index: String = located.chars().take_while(char::is_ascii_digit).collect();
+    index.parse().expect(message)
+}
+
+#[test]
+fn test_spec_location_builder() {
+    let mut builder = tx_builder(&[20_000], &[5_000, 10_000, 30_000]);
+    builder.max_money(25_000);
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    let message = err.to_string();
+    assert!(
+        message.contains("output #2 (amount 30000, script 0014"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_spec_location_planner() {
+    // The builder doesn't check the dust outputs unless the fee policy is set.
+    let unsigned_tx = tx_builder(&[50_000], &[10_000, 100, 20_000])
+        .build()
+        .unwrap();
+
+    let err = plan(unsigned_tx).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_dust_amount_requested
+    );
+    let message = err.to_string();
+    assert!(
+        message.contains("output #1 (amount 100, script 0014"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_spec_location_selection() {
+    // Every P2WPKH input adds 272 weight units, so ~1470 inputs exceed the standard weight.
+    let utxo_amounts = vec![1_000; 2_000];
+    let unsigned_tx = tx_builder(&utxo_amounts, &[1_900_000]).build().unwrap();
+
+    let err = plan(unsigned_tx).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_tx_too_big);
+    let message = err.to_string();
+    assert!(message.contains("Too many UTXOs"), "{message}");
+    assert!(message.contains("input #14"), "{message}");
+    assert!(message.contains("(amount 1000, script 0014"), "{message}");
+}
+
+#[test]
+fn test_spec_location_selection_after_dust() {
+    let utxo_amounts = vec![1_000; 2_000];
+    let unsigned_tx = tx_builder(&utxo_amounts, &[1_900_000]).build().unwrap();
+    let too_big_at = located_input(&plan(unsigned_tx).unwrap_err().to_string());
+
+    // The same UTXOs placed after a dust one, which is excluded before the selection.
+    let mut utxo_amounts_with_dust = vec![DUST - 1];
+    utxo_amounts_with_dust.extend(utxo_amounts);
+    let unsigned_tx = tx_builder(&utxo_amounts_with_dust, &[1_900_000])
+        .build()
+        .unwrap();
+
+    let err = plan(unsigned_tx).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_tx_too_big);
+    // The error locates the input by its index in the request, not in the filtered list.
+    let message = err.to_string();
+    assert_eq!(located_input(&message), too_big_at + 1, "{message}");
+    assert!(message.contains("(amount 1000, script 0014"), "{message}");
+}
+
+#[test]
+fn test_spec_location_validation() {
+    // The builder checks the money range, so the amounts are changed after.
+    let mut unsigned_tx = tx_builder(&[50_000], &[10_000]).build().unwrap();
+    unsigned_tx.outputs_mut()[0].set_value(Amount::MAX);
+    unsigned_tx.push_output(OutputBuilder::new(1).p2wpkh(&public_key()));
+
+    let err = unsigned_tx.total_output().unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_tx_too_big);
+    let message = err.to_string();
+    assert!(
+        message.contains("output #1 (amount 1, script 0014"),
+        "{message}"
+    );
+}
@@ -0,0 +1,371 @@
+use tw_coin_entry::error::prelude::*;
+use tw_utxo::address::batch::BatchScriptType;
+use tw_utxo::address::standard_derivation::StandardDerivation;
+use tw_utxo::chain_params::ChainParams;
+
+const MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+/// BIP39 seed of the [`MNEMONIC`] without a passphrase.
+const SEED: &str = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+const XPRV: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+const TPRV: &str = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+fn derivation(coin_type: u32, chain_params: ChainParams) -> StandardDerivation {
+    StandardDerivation::from_mnemonic(MNEMONIC, "", coin_type, chain_params).unwrap()
+}
+
+fn address(
+    derivation: &StandardDerivation,
+    script_type: BatchScriptType,
+    change: u32,
+    index: u32,
+) -> String {
+    derivation
+        .derive_address(script_type, 0, change, index)
+        .unwrap()
+        .address
+}
+
+#[test]
+fn test_standard_derivation_paths() {
+    let derivation = derivation(0, ChainParams::bitcoin_mainnet());
+    let path = |script_type, account, change, index| {
+        derivation
+            .path(script_type, account, change, index)
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(path(BatchScriptType::P2PKH, 0, 0, 0), "m/44'/0'/0'/0/0");
+    assert_eq!(
+        path(BatchScriptType::P2SHP2WPKH, 1, 0, 5),
+        "m/49'/0'/1'/0/5"
+    );
+    assert_eq!(path(BatchScriptType::P2WPKH, 0, 1, 2), "m/84'/0'/0'/1/2");
+    assert_eq!(path(BatchScriptType::P2TR, 2, 1, 0), "m/86'/0'/2'/1/0");
+}
+
+/// Test vectors of BIP44, BIP49, BIP84 and BIP86 as published by the BIPs, Trezor and Ledger.
+#[test]
+fn test_standard_derivation_bitcoin_mainnet() {
+    let derivation = derivation(0, ChainParams::bitcoin_mainnet());
+    let vectors = [
+        (
+            BatchScriptType::P2PKH,
+            0,
+            0,
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+        ),
+        (
+            BatchScriptType::P2PKH,
+            0,
+            1,
+            "1Ak8PffB2meyfYnbXZR9EGfLfFZVpzJvQP",
+        ),
+        (
+            BatchScriptType::P2PKH,
+            1,
+            0,
+            "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            0,
+            0,
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            0,
+            1,
+            "3LtMnn87fqUeHBUG414p9CWwnoV6E2pNKS",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            1,
+            0,
+            "34K56kSjgUCUSD8GTtuF7c9Zzwokbs6uZ7",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            0,
+            0,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            0,
+            1,
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            1,
+            0,
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+        ),
+        (
+            BatchScriptType::P2TR,
+            0,
+            0,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+        ),
+        (
+            BatchScriptType::P2TR,
+            0,
+            1,
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh",
+        ),
+        (
+            BatchScriptType::P2TR,
+            1,
+            0,
+            "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7",
+        ),
+    ];
+
+    for (script_type, change, index, expected) in vectors {
+        assert_eq!(address(&derivation, script_type, change, index), expected);
+    }
+
+    // Account extended public keys.
+    assert_eq!(
+        derivation.account_xpub(BatchScriptType::P2WPKH, 0).unwrap().to_string(),
+        "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+    );
+    assert_eq!(
+        derivation.account_xpub(BatchScriptType::P2TR, 0).unwrap().to_string(),
+        "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+    );
+}
+
+#[test]
+fn test_standard_derivation_bitcoin_testnet() {
+    let derivation = derivation(1, ChainParams::bitcoin_testnet());
+    let vectors = [
+        (
+            BatchScriptType::P2PKH,
+            0,
+            0,
+            "mkpZhYtJu2r87Js3pDiWJDmPte2NRZ8bJV",
+        ),
+        (
+            BatchScriptType::P2PKH,
+            0,
+            1,
+            "mzpbWabUQm1w8ijuJnAof5eiSTep27deVH",
+        ),
+        (
+            BatchScriptType::P2PKH,
+            1,
+            0,
+            "mi8nhzZgGZQthq6DQHbru9crMDerUdTKva",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            0,
+            0,
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            0,
+            1,
+            "2N55m54k8vr95ggehfUcNkdbUuQvaqG2GxK",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            1,
+            0,
+            "2MvdUi5o3f2tnEFh9yGvta6FzptTZtkPJC8",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            0,
+            0,
+            "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            0,
+            1,
+            "tb1qd7spv5q28348xl4myc8zmh983w5jx32cjhkn97",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            1,
+            0,
+            "tb1q9u62588spffmq4dzjxsr5l297znf3z6j5p2688",
+        ),
+        (
+            BatchScriptType::P2TR,
+            0,
+            0,
+            "tb1p8wpt9v4frpf3tkn0srd97pksgsxc5hs52lafxwru9kgeephvs7rqlqt9zj",
+        ),
+        (
+            BatchScriptType::P2TR,
+            0,
+            1,
+            "tb1p90h6z3p36n9hrzy7580h5l429uwchyg8uc9sz4jwzhdtuhqdl5eqmpwq6n",
+        ),
+        (
+            BatchScriptType::P2TR,
+            1,
+            0,
+            "tb1p6uav7en8k7zsumsqugdmg5j6930zmzy4dg7jcddshsr0fvxlqx7q7p5els",
+        ),
+    ];
+
+    for (script_type, change, index, expected) in vectors {
+        assert_eq!(address(&derivation, script_type, change, index), expected);
+    }
+}
+
+#[test]
+fn test_standard_derivation_from_seed_and_xprv() {
+    let expected = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+    let seed = tw_encoding::hex::decode(SEED).unwrap();
+
+    let derivations = [
+        StandardDerivation::from_seed(&seed, 0, ChainParams::bitcoin_mainnet()).unwrap(),
+        StandardDerivation::from_xprv(XPRV, 0, ChainParams::bitcoin_mainnet()).unwrap(),
+        // Version bytes are ignored.
+        StandardDerivation::from_xprv(TPRV, 0, ChainParams::bitcoin_mainnet()).unwrap(),
+        // Words are separated by any whitespace.
+        StandardDerivation::from_mnemonic(
+            &format!(" {}\n", MNEMONIC.replace(' ', "  ")),
+            "",
+            0,
+            ChainParams::bitcoin_mainnet(),
+        )
+        .unwrap(),
+    ];
+    for derivation in derivations {
+        assert_eq!(
+            address(&derivation, BatchScriptType::P2WPKH, 0, 0),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_standard_derivation_passphrase() {
+    // BIP39 Trezor test vector with the "TREZOR" passphrase.
+    let derivation =
+        StandardDerivation::from_mnemonic(MNEMONIC, "TREZOR", 0, ChainParams::bitcoin_mainnet())
+            .unwrap();
+    assert_eq!(
+        address(&derivation, BatchScriptType::P2WPKH, 0, 0),
+        "bc1qv5rmq0kt9yz3pm36wvzct7p3x6mtgehjul0feu"
+    );
+
+    let seed = tw_encoding::hex::decode("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04").unwrap();
+    let from_seed =
+        StandardDerivation::from_seed(&seed, 0, ChainParams::bitcoin_mainnet()).unwrap();
+    assert_eq!(
+        address(&from_seed, BatchScriptType::P2WPKH, 0, 0),
+        "bc1qv5rmq0kt9yz3pm36wvzct7p3x6mtgehjul0feu"
+    );
+}
+
+#[test]
+fn test_standard_derivation_bip39_vectors() {
+    // BIP39 Trezor test vectors with the "TREZOR" passphrase: the mnemonic and the master key.
+    let vectors = [
+        (
+            "legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will",
+            "xprv9s21ZrQH143K3Lv9MZLj16np5GzLe7tDKQfVusBni7toqJGcnKRtHSxUwbKUyUWiwpK55g1DUSsw76TF1T93VT4gz4wt5RM23pkaQLnvBh7",
+        ),
+        (
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when",
+            "xprv9s21ZrQH143K36Ao5jHRVhFGDbLP6FCx8BEEmpru77ef3bmA928BxsqvVM27WnvvyfWywiFN8K6yToqMaGYfzS6Db1EHAXT5TuyCLBXUfdm",
+        ),
+        (
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+            "xprv9s21ZrQH143K3CSnQNYC3MqAAqHwxeTLhDbhF43A4ss4ciWNmCY9zQGvAKUSqVUf2vPHBTSE1rB2pg4avopqSiLVzXEU8KziNnVPauTqLRo",
+        ),
+    ];
+
+    for (mnemonic, xprv) in vectors {
+        let from_mnemonic = StandardDerivation::from_mnemonic(
+            mnemonic,
+            "TREZOR",
+            0,
+            ChainParams::bitcoin_mainnet(),
+        )
+        .unwrap();
+        let from_xprv =
+            StandardDerivation::from_xprv(xprv, 0, ChainParams::bitcoin_mainnet()).unwrap();
+        assert_eq!(
+            address(&from_mnemonic, BatchScriptType::P2WPKH, 0, 0),
+            address(&from_xprv, BatchScriptType::P2WPKH, 0, 0)
+        );
+    }
+}
+
+#[test]
+fn test_standard_derivation_errors() {
+    let mainnet = ChainParams::bitcoin_mainnet;
+    let assert_invalid = |res: SigningResult<StandardDerivation>| {
+        let err = res.err().unwrap();
+        assert_eq!(err.error_type(), &SigningErrorType::Error_invalid_params);
+    };
+
+    assert_invalid(StandardDerivation::from_mnemonic(" \n", "", 0, mainnet()));
+    // Invalid checksum.
+    assert_invalid(StandardDerivation::from_mnemonic(
+        &MNEMONIC.replace("about", "abandon"),
+        "",
+        0,
+        mainnet(),
+    ));
+    // Not a BIP39 word.
+    assert_invalid(StandardDerivation::from_mnemonic(
+        &MNEMONIC.replace("about", "abut"),
+        "",
+        0,
+        mainnet(),
+    ));
+    // 11 words.
+    assert_invalid(StandardDerivation::from_mnemonic(
+        MNEMONIC.trim_end_matches(" about"),
+        "",
+        0,
+        mainnet(),
+    ));
+    // Unicode normalization is not supported.
+    assert_invalid(StandardDerivation::from_mnemonic(
+        MNEMONIC,
+        "pässword",
+        0,
+        mainnet(),
+    ));
+    assert_invalid(StandardDerivation::from_seed(&[1; 15], 0, mainnet()));
+    assert_invalid(StandardDerivation::from_seed(&[1; 65], 0, mainnet()));
+    assert_invalid(StandardDerivation::from_xprv("xprv", 0, mainnet()));
+    // BIP84 account extended private key `m/84'/0'/0'` is not a master key.
+    assert_invalid(StandardDerivation::from_xprv(
+        "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE",
+        0,
+        mainnet(),
+    ));
+
+    let derivation = derivation(0, mainnet());
+    let hardened = 1 << 31;
+    let derive = |account, change, index| {
+        derivation
+            .derive_address(BatchScriptType::P2WPKH, account, change, index)
+            .err()
+            .unwrap()
+    };
+    derive(hardened, 0, 0);
+    derive(0, hardened, 0);
+    derive(0, 0, hardened);
+    derive(0, 0, u32::MAX);
+    // Coin type must be a valid hardened index.
+    StandardDerivation::from_mnemonic(MNEMONIC, "", hardened, mainnet())
+        .unwrap()
+        .derive_address(BatchScriptType::P2WPKH, 0, 0, 0)
+        .unwrap_err();
+}
@@ -0,0 +1,165 @@
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
+use tw_hash::{H160, H256};
+use tw_keypair::schnorr;
+use tw_utxo::modules::sighash_computer::SighashComputer;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+/// The x-coordinate of the secp256k1 generator, used as the tweaked output key of the UTXOs.
+const OUTPUT_KEY: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const LEAF_HASH: &str = "ab5e9a8b2b3b6a1a4f1e5b4c0e6c7c1f0d2a3e4b5c6d7e8f90a1b2c3d4e5f607";
+
+/// The unsigned transaction with 3 P2TR inputs and 2 outputs, serialized by rust-bitcoin.
+const UNSIGNED_TX: &str = "0200000003ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffffac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0100000000feffffffac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0200000000fdffffff0290d0030000000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c2409c00000000000016001479091972186c449eb1ded22b78e40d009bdf008900000000";
+
+/// The sighashes of [`UNSIGNED_TX`] computed by rust-bitcoin 0.30
+/// `SighashCache::taproot_key_spend_signature_hash` and `taproot_script_spend_signature_hash`
+/// with the [`LEAF_HASH`] leaf and no code separator.
+/// (sighash type, input index, key-path sighash, script-path sighash),
+/// `None` if `SIGHASH_SINGLE` has no corresponding output.
+#[rustfmt::skip]
+const FIXTURES: &[(u32, usize, Option<(&str, &str)>)] = &[
+    (0x00, 0, Some(("89536bf86aa0654a925d8387e0376bc3d14b9db81ed1f926d45f99ce8b1018cf", "173ce7c0ca75813d5d00602cebbf1c657547efa66f914a7ec608de7f298e0233"))),
+    (0x00, 1, Some(("30b6041c2a058a50afc702c148bb3efb79e659c9ee8415eb9e4d1c403af29e11", "e14e813f65b072e31b549ed542ff9d442cf7732cd295aed48ec306ed48e47a21"))),
+    (0x00, 2, Some(("b0f467fa740e79d77ba4397d864390847368abdce26038c0da2b19076f6e5d1d", "6d170fe35c4f72b815b1c63279516e48d78d4925bc3b3ac2b61bccbef658c19c"))),
+    (0x01, 0, Some(("aad188c6780b79c65aca4410f41e8e1855207664e8c4bdec59db601c9ca0f17f", "22ca08008047729bea535c96c099dc1514c1c387e16753d37635395f67b48ece"))),
+    (0x01, 1, Some(("b120240b5149cee96e92a55f3d9626c3ef84111abfea9d8451977298e39bc4e3", "3db0c94fe52a298be638982a5900631107eb638352b91c68a876f08685a36060"))),
+    (0x01, 2, Some(("70b132b5c6ccc5126f8f7f75370144ae70510a9979ffeca7b623696c53671b3a", "63c91fd2356146ba08a297297880336215ceb091617dac41829f25b3c772b698"))),
+    (0x02, 0, Some(("17067d87c584be5e99fca885428c82879b5cda9f64d3490944ec1e299c3dd91c", "f28b2843c9440dfdc4778fdedaa6fe1f3df2f632b4fac75d17c6b7b54cdd3cde"))),
+    (0x02, 1, Some(("7726200f89107821ccde35063f44c33a10aa58061b0cf56e40eb4ed22d174420", "3da00c42d0c9cd04d2fce8a9fcd3860f76adb8ff7981f2a2e8dc54c0cc012ee6"))),
+    (0x02, 2, Some(("1248c45a94b69c0f4c6a255ab3d95a4605194ebe27f16af4445986fa155a29eb", "9ffdadf4cb8beae303f5a1edef44fac6cae39e1000c6c7976d51c1e84d1aef36"))),
+    (0x03, 0, Some(("d0a2e4f481a16def89f59e557602c6a0fd4f8109ef672b80b15c80fa9e102909", "ed99252667d7e5875dc967bb930f0b030e3f06aeda124eb442f3fad26b09699a"))),
+    (0x03, 1, Some(("99d939b5380c9639f91e1873422f4662408ccfd8ad2b32e6a9e463da51ea794c", "8a351628f3553f8f430c29bc8982bd9ca86341a257120428e1120c5c151e78b9"))),
+    (0x03, 2, None),
+    (0x81, 0, Some(("13d0746d7b4d34fa80e3974abf731d136b50b190a64aede285f11017478d92f7", "9980a73188644da02a4e514bd2dc812a905750ec6e51a055bc15709c155f6662"))),
+    (0x81, 1, Some(("f8e895b99c1c6982e6c85ff1a91d6adfe3b1813b30b188e4cb0afc4e4bf47996", "526c0e3be912f67ca9c880cf8117d4ddb723674e37f4fb5dec44c5527f50249b"))),
+    (0x81, 2, Some(("046035d254b417ea314ef81a48ffadd2dc742f9a059e235f37ba6f548995b041", "cadb5a49f39166f96d751ef2f9dfea41dfd4410a40d6db53a6b488805db0464e"))),
+    (0x82, 0, Some(("2cc3b90a926399ae026a3b154a70bd0ce82477f134d96deb1a1abc368522c218", "9ec7931bdc3355131f4d3604a4b05e51a06438773e56a70749c66ccef4f923de"))),
+    (0x82, 1, Some(("bac8ef59899a0f2f76ce2aa8d2c4bbe24c13a6b1e765482465f2c1f34904f12a", "d3946fe318db1c2315ee095fc947cb71377377da3f2ec0607d7ed3ee9b62a44e"))),
+    (0x82, 2, Some(("a2e07791474583796ce67ca178bc8f1fda0fece9c94935c5cabf27974f4af718", "8f66189682f15c138bff54d3856ebe7e5d35c0b91f2f87a82f5715eff73a7df7"))),
+    (0x83, 0, Some(("bc452a7f9a7b938818a923a440d723f001ba5c0c56f8d8dd613a56726266d590", "c7632d56293d3e46f5101e608b21f52660e04250d92cdf901320712c3e9c5193"))),
+    (0x83, 1, Some(("4972217fc715a1d45f32f9564c7cfa1b5c4c25775eb276edb24c2cfd4574e456", "f7234d857398929eb695e9ca6f7adad3f9123f49b04063dd91a7a6422b537211"))),
+    (0x83, 2, None),
+];
+
+/// Creates the [`UNSIGNED_TX`] with every input signed with the given sighash type.
+fn unsigned_tx(sighash_ty: SighashType) -> UnsignedTransaction<Transaction> {
+    let txid =
+        txid_from_str_and_rev("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+            .unwrap();
+    let output_key =
+        schnorr::XOnlyPublicKey::try_from(hex::decode(OUTPUT_KEY).unwrap().as_slice()).unwrap();
+
+    let mut builder = TransactionBuilder::new();
+    let utxos = [
+        (100_000, 0xffffffff),
+        (200_000, 0xfffffffe),
+        (300_000, 0xfffffffd),
+    ];
+    for (prev_index, (amount, sequence)) in utxos.into_iter().enumerate() {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid)
+            .prev_index(prev_index as u32)
+            .amount(amount)
+            .sequence(sequence)
+            .sighash_type(sighash_ty)
+            .p2tr_key_path_with_tweaked_pubkey(&output_key)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder
+        .push_output(
+            OutputBuilder::new(250_000).p2tr_dangerous_assume_tweaked(&H256::from(
+                "a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c2",
+            )),
+        )
+        .push_output(
+            OutputBuilder::new(40_000)
+                .p2wpkh_from_hash(&H160::from("79091972186c449eb1ded22b78e40d009bdf0089")),
+        );
+    builder.build().unwrap()
+}
+
+/// Computes the sighash of the key-path spend, or the script-path spend of the [`LEAF_HASH`] leaf.
+fn sighash(
+    unsigned_tx: &UnsignedTransaction<Transaction>,
+    input_index: usize,
+    script_path: bool,
+) -> SigningResult<String> {
+    let mut utxo = unsigned_tx.input_args()[input_index].clone();
+    if script_path {
+        utxo.leaf_hash_code_separator = Some((H256::from(LEAF_HASH), u32::MAX));
+    }
+    SighashComputer::preimage_utxo(unsigned_tx, input_index, &utxo)
+        .map(|utxo_sighash| hex::encode(utxo_sighash.sighash, false))
+}
+
+#[test]
+fn test_taproot_sighash_matrix() {
+    let tx = unsigned_tx(SighashType::sighash_default());
+    assert_eq!(
+        hex::encode(tx.transaction().encode_out(), false),
+        UNSIGNED_TX
+    );
+
+    for (raw_sighash, input_index, expected) in FIXTURES {
+        // An explicit `SIGHASH_ALL` commits to a different digest than `SIGHASH_DEFAULT`.
+        let tx = unsigned_tx(SighashType::from_u32(*raw_sighash).unwrap());
+        let context = format!("sighash type {raw_sighash:#04x}, input #{input_index}");
+
+        match expected {
+            Some((key_path, script_path)) => {
+                assert_eq!(
+                    sighash(&tx, *input_index, false).unwrap(),
+                    *key_path,
+                    "{context}"
+                );
+                assert_eq!(
+                    sighash(&tx, *input_index, true).unwrap(),
+                    *script_path,
+                    "{context}"
+                );
+            },
+            None => {
+                for script_path in [false, true] {
+                    let err = sighash(&tx, *input_index, script_path).unwrap_err();
+                    assert_eq!(
+                        *err.error_type(),
+                        SigningErrorType::Error_invalid_params,
+                        "{context}"
+                    );
+                }
+            },
+        }
+    }
+}
+
+#[test]
+fn test_taproot_sighash_single_without_output() {
+    let tx = unsigned_tx(SighashType::from_u32(0x03).unwrap());
+
+    // The inputs with a corresponding output can be signed.
+    SighashComputer::preimage_utxo(&tx, 1, &tx.input_args()[1]).unwrap();
+
+    let err = SighashComputer::preimage_tx(&tx).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    let message = err.to_string();
+    assert!(message.contains("input #2 has no output"), "{message}");
+}
+
+#[test]
+fn test_taproot_sighash_type_not_allowed() {
+    // `SIGHASH_FORK_ID` is allowed by the UTXO builder, but not by BIP-341.
+    let tx = unsigned_tx(SighashType::from_u32(0x41).unwrap());
+
+    let err = sighash(&tx, 0, false).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_invalid_sighash_type
+    );
+}
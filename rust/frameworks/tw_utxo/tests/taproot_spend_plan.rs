@@ -0,0 +1,190 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+use tw_keypair::schnorr;
+use tw_utxo::modules::fee_estimator::FeeEstimator;
+use tw_utxo::modules::taproot_spend_plan::{
+    TaprootClaimDescriptor, TaprootLeaf, TaprootSpendPath, TaprootSpendPlan,
+};
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::standard_script::opcodes::OP_CHECKSIG;
+use tw_utxo::script::Script;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    tapscript_merkle_root, txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::{Transaction, TransactionInput};
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::UtxoToSign;
+
+const SATS_PER_VBYTE: Amount = 20;
+
+fn pubkey(private_key: &str) -> schnorr::PublicKey {
+    schnorr::PrivateKey::try_from(private_key).unwrap().public()
+}
+
+/// `<signer> OP_CHECKSIG`
+fn checksig_script(signer: &schnorr::PublicKey) -> Script {
+    let mut script = Script::new();
+    script.push_slice(signer.x_only().bytes().as_slice());
+    script.push(OP_CHECKSIG);
+    script
+}
+
+/// The script tree `[A, [B, C]]`, the leaf `A` is one level closer to the root than `B` and `C`.
+fn descriptor() -> TaprootClaimDescriptor {
+    let internal = pubkey("9b7a5a1ef0a8e5a7d0f5a8b1e0fa0a4e2cc3b47a0fb6b3b8e2e2a0b3c4d5e6f7");
+    let signers = [
+        pubkey("698bbe6e0024686eabc881e119c8d5e6160fccef4c2c769de1391c79f63e62d3"),
+        pubkey(PRIVATE_KEY),
+        pubkey("0ba8e3f1a7d1c6d5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a3928170605040"),
+    ];
+    let scripts = signers.clone().map(|signer| checksig_script(&signer));
+    let leaf_hashes = scripts
+        .clone()
+        .map(|script| tapscript_merkle_root(&script, &[]));
+    let branch_bc = tapscript_merkle_root(&scripts[1], &[leaf_hashes[2]]);
+
+    let merkle_branches = [
+        vec![branch_bc],
+        vec![leaf_hashes[2], leaf_hashes[0]],
+        vec![leaf_hashes[1], leaf_hashes[0]],
+    ];
+    let leaves = scripts
+        .into_iter()
+        .zip(signers)
+        .zip(merkle_branches)
+        .map(|((script, signer), merkle_branch)| TaprootLeaf {
+            script,
+            merkle_branch,
+            signer,
+            signer_available: true,
+        })
+        .collect();
+
+    TaprootClaimDescriptor {
+        internal_pubkey: internal,
+        internal_key_available: true,
+        leaves,
+    }
+}
+
+fn claim_utxo(
+    descriptor: &TaprootClaimDescriptor,
+    plan: &TaprootSpendPlan,
+) -> (TransactionInput, UtxoToSign) {
+    let txid = txid_from_str_and_rev(TXID).unwrap();
+    let utxo = UtxoBuilder::new()
+        .prev_txid(txid)
+        .prev_index(0)
+        .amount(100_000)
+        .sighash_type(SighashType::default());
+    plan.claim_utxo(descriptor, utxo).unwrap()
+}
+
+/// Builds the transaction spending the taproot UTXO via the `plan`, and estimates it.
+fn estimate_tx(descriptor: &TaprootClaimDescriptor, plan: &TaprootSpendPlan) -> Transaction {
+    let (utxo, arg) = claim_utxo(descriptor, plan);
+    let output = OutputBuilder::new(90_000).p2tr_key_path(&descriptor.internal_pubkey);
+
+    let mut builder = TransactionBuilder::new();
+    builder.push_input(utxo, arg).push_output(output);
+    builder.build().unwrap().estimate_transaction()
+}
+
+#[test]
+fn taproot_spend_plan_prefers_key_path() {
+    let descriptor = descriptor();
+    let merkle_root = descriptor.merkle_root().unwrap().unwrap();
+
+    let plan = TaprootSpendPlan::resolve(&descriptor).unwrap();
+    assert_eq!(plan.path, TaprootSpendPath::KeyPath);
+    assert_eq!(plan.witness_weight(), 67);
+    assert_eq!(plan.input_weight(), 231);
+
+    // The output key is tweaked with the merkle root of the script tree.
+    let tx = estimate_tx(&descriptor, &plan);
+    let (_, arg) = claim_utxo(&descriptor, &plan);
+    assert_eq!(arg.taproot_merkle_root, Some(merkle_root));
+    assert_eq!(
+        arg.prevout_script_pubkey,
+        conditions::new_p2tr_script_path(&descriptor.internal_pubkey, &merkle_root)
+    );
+    assert_eq!(tx.inputs()[0].witness.as_items().len(), 1);
+}
+
+#[test]
+fn taproot_spend_plan_without_internal_key_flips_to_script_leaf() {
+    let mut descriptor = descriptor();
+    let key_path = TaprootSpendPlan::resolve(&descriptor).unwrap();
+    let key_path_tx = estimate_tx(&descriptor, &key_path);
+
+    descriptor.internal_key_available = false;
+    let script_path = TaprootSpendPlan::resolve(&descriptor).unwrap();
+    // The leaf `A` has the shortest merkle branch.
+    assert_eq!(
+        script_path.path,
+        TaprootSpendPath::ScriptPath { leaf_index: 0 }
+    );
+    // The items count, the signature, the `<signer> OP_CHECKSIG` script
+    // and the control block of the leaf version, the internal key and one sibling hash.
+    assert_eq!(script_path.witness_weight(), 1 + 66 + 35 + 66);
+
+    let script_path_tx = estimate_tx(&descriptor, &script_path);
+    assert_eq!(script_path_tx.inputs()[0].witness.as_items().len(), 3);
+    assert_eq!(
+        script_path_tx.weight() - key_path_tx.weight(),
+        script_path.input_weight() - key_path.input_weight()
+    );
+
+    let key_path_fee = FeeEstimator::estimate_fee(&key_path_tx, SATS_PER_VBYTE).unwrap();
+    let script_path_fee = FeeEstimator::estimate_fee(&script_path_tx, SATS_PER_VBYTE).unwrap();
+    assert!(script_path_fee > key_path_fee);
+    assert_eq!(
+        script_path_fee,
+        SATS_PER_VBYTE * script_path_tx.vsize() as Amount
+    );
+
+    // Without the key of the leaf `A`, the deeper leaf `B` is the cheapest satisfiable one.
+    descriptor.leaves[0].signer_available = false;
+    let deeper = TaprootSpendPlan::resolve(&descriptor).unwrap();
+    assert_eq!(deeper.path, TaprootSpendPath::ScriptPath { leaf_index: 1 });
+    assert_eq!(deeper.witness_weight(), script_path.witness_weight() + 32);
+
+    let deeper_tx = estimate_tx(&descriptor, &deeper);
+    let deeper_fee = FeeEstimator::estimate_fee(&deeper_tx, SATS_PER_VBYTE).unwrap();
+    assert!(deeper_fee > script_path_fee);
+}
+
+#[test]
+fn taproot_spend_plan_nothing_satisfiable() {
+    let mut descriptor = descriptor();
+    descriptor.internal_key_available = false;
+    descriptor
+        .leaves
+        .iter_mut()
+        .for_each(|leaf| leaf.signer_available = false);
+
+    let err = TaprootSpendPlan::resolve(&descriptor).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_missing_private_key
+    );
+    let message = err.to_string();
+    assert!(message.contains("the internal key"));
+    for leaf_index in 0..descriptor.leaves.len() {
+        assert!(message.contains(&format!("key of leaf #{leaf_index}")));
+    }
+}
+
+#[test]
+fn taproot_spend_plan_leaves_of_different_trees() {
+    let mut descriptor = descriptor();
+    descriptor.leaves[2].merkle_branch.pop();
+
+    let err = TaprootSpendPlan::resolve(&descriptor).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID as PARENT_TXID};
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_utxo::constants::{TRUC_CHILD_MAX_VSIZE, TRUC_MAX_VSIZE};
+use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::modules::tx_planner::{PlanRequest, RequestType, TxPlanner};
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+use tw_utxo::truc::{check_topology, UnconfirmedParent};
+
+const OTHER_PARENT_TXID: &str = "797d17d47ae66e598341f9dfdea020b04d4017dcf9cc33f0e51f7a6082171fb1";
+
+/// Builds a transaction of the given version spending the first output of every `prev_txids`.
+fn build_tx(version: u32, prev_txids: &[&str]) -> Transaction {
+    build_unsigned_tx(version, prev_txids).transaction().clone()
+}
+
+fn build_unsigned_tx(version: u32, prev_txids: &[&str]) -> UnsignedTransaction<Transaction> {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let mut builder = TransactionBuilder::new();
+    for txid in prev_txids {
+        let (utxo, arg) = UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(txid).unwrap())
+            .prev_index(0)
+            .amount(10_000)
+            .p2pkh(&public)
+            .unwrap();
+        builder.push_input(utxo, arg);
+    }
+    builder
+        .version(version)
+        .push_output(OutputBuilder::new(5_000).p2pkh(&public));
+    builder.build().unwrap()
+}
+
+fn plan(
+    unsigned_tx: UnsignedTransaction<Transaction>,
+    unconfirmed_parents: Vec<UnconfirmedParent>,
+) -> SigningResult<()> {
+    let public = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    TxPlanner::plan(PlanRequest {
+        ty: RequestType::SendExact {
+            unsigned_tx,
+            change_output: Some(OutputBuilder::new(0).p2pkh(&public)),
+            input_selector: InputSelector::UseAll,
+        },
+        fee_policy: FeePolicy::unbounded(DustPolicy::FixedAmount(546)),
+        fee_per_vbyte: 1,
+        explicit_fee_output: None,
+        selection_policy: SelectionPolicy::Any,
+        output_shuffle_seed: None,
+        unconfirmed_parents,
+    })
+    .map(|_| ())
+}
+
+fn unconfirmed_parent(txid: &str, version: i32) -> UnconfirmedParent {
+    UnconfirmedParent {
+        txid: txid_from_str_and_rev(txid).unwrap(),
+        version,
+        vsize: 200,
+        unconfirmed_parents: 0,
+    }
+}
+
+fn assert_topology_err(result: SigningResult<()>, expected: SigningErrorType) {
+    assert_eq!(*result.unwrap_err().error_type(), expected);
+}
+
+#[test]
+fn truc_max_vsize() {
+    let tx = build_tx(3, &[PARENT_TXID]);
+    assert_eq!(tx.version, 3);
+
+    check_topology(&tx, TRUC_MAX_VSIZE, &[]).unwrap();
+    assert_topology_err(
+        check_topology(&tx, TRUC_MAX_VSIZE + 1, &[]),
+        SigningErrorType::Error_tx_too_big,
+    );
+
+    // The limits don't apply to the other versions.
+    let tx = build_tx(2, &[PARENT_TXID]);
+    check_topology(&tx, TRUC_MAX_VSIZE + 1, &[]).unwrap();
+}
+
+#[test]
+fn truc_child_max_vsize() {
+    let tx = build_tx(3, &[PARENT_TXID]);
+    let parents = [unconfirmed_parent(PARENT_TXID, 3)];
+
+    check_topology(&tx, TRUC_CHILD_MAX_VSIZE, &parents).unwrap();
+    assert_topology_err(
+        check_topology(&tx, TRUC_CHILD_MAX_VSIZE + 1, &parents),
+        SigningErrorType::Error_tx_too_big,
+    );
+}
+
+#[test]
+fn truc_parent_max_vsize() {
+    let tx = build_tx(3, &[PARENT_TXID]);
+    let parent = |vsize| UnconfirmedParent {
+        vsize,
+        ..unconfirmed_parent(PARENT_TXID, 3)
+    };
+
+    check_topology(&tx, 200, &[parent(TRUC_MAX_VSIZE)]).unwrap();
+    assert_topology_err(
+        check_topology(&tx, 200, &[parent(TRUC_MAX_VSIZE + 1)]),
+        SigningErrorType::Error_tx_too_big,
+    );
+}
+
+#[test]
+fn truc_child_of_unconfirmed_grandparent() {
+    let tx = build_tx(3, &[PARENT_TXID]);
+    let parent = UnconfirmedParent {
+        unconfirmed_parents: 1,
+        ..unconfirmed_parent(PARENT_TXID, 3)
+    };
+
+    assert_topology_err(
+        check_topology(&tx, 200, &[parent]),
+        SigningErrorType::Error_invalid_params,
+    );
+}
+
+#[test]
+fn truc_child_of_two_unconfirmed_parents() {
+    let tx = build_tx(3, &[PARENT_TXID, OTHER_PARENT_TXID]);
+
+    let single = [unconfirmed_parent(PARENT_TXID, 3)];
+    check_topology(&tx, 200, &single).unwrap();
+
+    // The second unconfirmed parent is rejected regardless of its version.
+    for other_version in [2, 3] {
+        let parents = [
+            unconfirmed_parent(PARENT_TXID, 3),
+            unconfirmed_parent(OTHER_PARENT_TXID, other_version),
+        ];
+        assert_topology_err(
+            check_topology(&tx, 200, &parents),
+            SigningErrorType::Error_invalid_params,
+        );
+    }
+}
+
+#[test]
+fn truc_and_non_truc_mixed() {
+    // A TRUC transaction cannot spend an unconfirmed non-TRUC one.
+    let tx = build_tx(3, &[PARENT_TXID]);
+    assert_topology_err(
+        check_topology(&tx, 200, &[unconfirmed_parent(PARENT_TXID, 2)]),
+        SigningErrorType::Error_invalid_params,
+    );
+
+    // Nor vice versa.
+    let tx = build_tx(2, &[PARENT_TXID]);
+    assert_topology_err(
+        check_topology(&tx, 200, &[unconfirmed_parent(PARENT_TXID, 3)]),
+        SigningErrorType::Error_invalid_params,
+    );
+    check_topology(&tx, 200, &[unconfirmed_parent(PARENT_TXID, 2)]).unwrap();
+}
+
+#[test]
+fn truc_parent_not_spent() {
+    let tx = build_tx(3, &[PARENT_TXID]);
+    assert_topology_err(
+        check_topology(&tx, 200, &[unconfirmed_parent(OTHER_PARENT_TXID, 3)]),
+        SigningErrorType::Error_invalid_params,
+    );
+}
+
+#[test]
+fn truc_plan() {
+    // The planner checks the estimated transaction against the unconfirmed parents.
+    plan(
+        build_unsigned_tx(3, &[PARENT_TXID]),
+        vec![unconfirmed_parent(PARENT_TXID, 3)],
+    )
+    .unwrap();
+    plan(build_unsigned_tx(3, &[PARENT_TXID]), Vec::new()).unwrap();
+
+    assert_topology_err(
+        plan(
+            build_unsigned_tx(3, &[PARENT_TXID]),
+            vec![unconfirmed_parent(PARENT_TXID, 2)],
+        ),
+        SigningErrorType::Error_invalid_params,
+    );
+    assert_topology_err(
+        plan(
+            build_unsigned_tx(3, &[PARENT_TXID, OTHER_PARENT_TXID]),
+            vec![
+                unconfirmed_parent(PARENT_TXID, 3),
+                unconfirmed_parent(OTHER_PARENT_TXID, 3),
+            ],
+        ),
+        SigningErrorType::Error_invalid_params,
+    );
+}
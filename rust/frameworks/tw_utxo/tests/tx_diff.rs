@@ -0,0 +1,183 @@
+use tw_encoding::hex;
+use tw_utxo::modules::tx_diff::{tx_diff, FieldDiff};
+use tw_utxo::script::{Script, Witness};
+use tw_utxo::transaction::standard_transaction::Transaction;
+
+/// P2PKH input signed with `SIGHASH_ALL`, see `build_tx_input_legacy_output_legacy`.
+const LEGACY_TX: &str = "02000000017be4e642bb278018ab12277de9427773ad1c5f5b1d164a157e0d99aa48dc1c1e000000006a473044022078eda020d4b86fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd4590121036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536ffffffff01c0aff629010000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000";
+/// P2WPKH input signed with `SIGHASH_ALL`, see `build_tx_input_segwit_output_segwit`.
+const SEGWIT_TX: &str = "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000";
+
+fn decode_tx(tx: &str) -> Transaction {
+    Transaction::from_bytes(&hex::decode(tx).unwrap()).unwrap()
+}
+
+/// Flips the lowest bit of the byte at `index`.
+fn flip_byte(bytes: &[u8], index: usize) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes[index] ^= 1;
+    bytes
+}
+
+/// Applies the change to a copy of `tx`, and checks that the diff has one difference only.
+#[track_caller]
+fn single_diff<F>(tx: &str, change: F) -> FieldDiff
+where
+    F: FnOnce(&mut Transaction),
+{
+    let a = decode_tx(tx);
+    let mut b = decode_tx(tx);
+    change(&mut b);
+
+    let diff = tx_diff(&a, &b);
+    assert_eq!(diff.differences.len(), 1, "{diff}");
+    diff.differences[0].clone()
+}
+
+#[test]
+fn test_tx_diff_equal() {
+    let tx = decode_tx(LEGACY_TX);
+    let diff = tx_diff(&tx, &tx);
+
+    assert!(diff.is_empty());
+    assert!(!diff.is_signature_only());
+    assert_eq!(diff.to_string(), "");
+    assert_eq!(diff.to_json()["equal"], true);
+}
+
+#[test]
+fn test_tx_diff_header() {
+    let diff = single_diff(LEGACY_TX, |tx| tx.version = 1);
+    assert_eq!(diff.to_string(), "version: 2 != 1");
+
+    let diff = single_diff(LEGACY_TX, |tx| tx.locktime = 800_000);
+    assert_eq!(diff.to_string(), "lock_time: 0 != 800000");
+}
+
+#[test]
+fn test_tx_diff_input() {
+    let diff = single_diff(LEGACY_TX, |tx| tx.inputs[0].previous_output.index = 1);
+    assert_eq!(
+        diff.to_string(),
+        "inputs[0].outpoint: 1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b:0 != 1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b:1"
+    );
+
+    let diff = single_diff(LEGACY_TX, |tx| tx.inputs[0].sequence = 0xfffffffd);
+    assert_eq!(
+        diff.to_string(),
+        "inputs[0].sequence: 4294967295 != 4294967293"
+    );
+
+    let diff = single_diff(LEGACY_TX, |tx| {
+        let input = tx.inputs[0].clone();
+        tx.inputs.push(input);
+    });
+    assert_eq!(diff.to_string(), "inputs.len: 1 != 2");
+}
+
+#[test]
+fn test_tx_diff_script_sig() {
+    // The signature differs, e.g. signed with another nonce.
+    let diff = single_diff(LEGACY_TX, |tx| {
+        let script_sig = tx.inputs[0].script_sig.as_slice();
+        // A byte of the DER `r` value.
+        tx.inputs[0].script_sig = Script::from(flip_byte(script_sig, 10));
+    });
+    assert_eq!(diff.field, "inputs[0].script_sig");
+    assert!(diff.signature_only);
+    assert_eq!(diff.left, "3044022078eda020d4b86fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd459[ALL] 036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    assert_eq!(diff.right, "3044022078eda020d4b96fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd459[ALL] 036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    assert!(diff.to_string().ends_with(" [signature only]"));
+
+    // The public key differs.
+    let diff = single_diff(LEGACY_TX, |tx| {
+        let script_sig = tx.inputs[0].script_sig.as_slice();
+        tx.inputs[0].script_sig = Script::from(flip_byte(script_sig, script_sig.len() - 1));
+    });
+    assert_eq!(diff.field, "inputs[0].script_sig");
+    assert!(!diff.signature_only);
+
+    // The sighash type differs.
+    let diff = single_diff(LEGACY_TX, |tx| {
+        let script_sig = tx.inputs[0].script_sig.as_slice();
+        // `SIGHASH_ALL` to `SIGHASH_NONE`.
+        let mut changed = script_sig.to_vec();
+        changed[0x47] = 0x02;
+        tx.inputs[0].script_sig = Script::from(changed);
+    });
+    assert!(diff.right.contains("[NONE]"), "{}", diff.right);
+    assert!(!diff.signature_only);
+}
+
+#[test]
+fn test_tx_diff_witness() {
+    let diff = single_diff(SEGWIT_TX, |tx| {
+        let items = tx.inputs[0].witness.as_items();
+        let mut witness = Witness::new();
+        witness.push_item(Script::from(flip_byte(items[0].as_slice(), 10)));
+        witness.push_item(items[1].clone());
+        tx.inputs[0].witness = witness;
+    });
+    assert_eq!(diff.field, "inputs[0].witness");
+    assert!(diff.signature_only);
+    assert_eq!(diff.left, "[3045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a601 025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f]");
+
+    // The public key is missing.
+    let diff = single_diff(SEGWIT_TX, |tx| {
+        let items = tx.inputs[0].witness.as_items();
+        let mut witness = Witness::new();
+        witness.push_item(items[0].clone());
+        tx.inputs[0].witness = witness;
+    });
+    assert_eq!(diff.field, "inputs[0].witness");
+    assert!(!diff.signature_only);
+}
+
+#[test]
+fn test_tx_diff_output() {
+    let diff = single_diff(LEGACY_TX, |tx| tx.outputs[0].value -= 1);
+    assert_eq!(
+        diff.to_string(),
+        "outputs[0].value: 4999000000 != 4998999999"
+    );
+
+    let diff = single_diff(LEGACY_TX, |tx| {
+        let script_pubkey = tx.outputs[0].script_pubkey.as_slice();
+        tx.outputs[0].script_pubkey = Script::from(flip_byte(script_pubkey, 3));
+    });
+    assert_eq!(
+        diff.to_string(),
+        "outputs[0].script_pubkey: OP_DUP OP_HASH160 5eaaa4f458f9158f86afcba08dd7448d27045e3d OP_EQUALVERIFY OP_CHECKSIG != OP_DUP OP_HASH160 5faaa4f458f9158f86afcba08dd7448d27045e3d OP_EQUALVERIFY OP_CHECKSIG"
+    );
+}
+
+#[test]
+fn test_tx_diff_report() {
+    let a = decode_tx(SEGWIT_TX);
+    let mut b = decode_tx(SEGWIT_TX);
+    let items = b.inputs[0].witness.as_items();
+    let mut witness = Witness::new();
+    witness.push_item(Script::from(flip_byte(items[0].as_slice(), 10)));
+    witness.push_item(items[1].clone());
+    b.inputs[0].witness = witness;
+
+    // The signatures differ only.
+    let diff = tx_diff(&a, &b);
+    assert!(diff.is_signature_only());
+    assert_eq!(diff.structural().count(), 0);
+
+    b.locktime = 1;
+    let diff = tx_diff(&a, &b);
+    assert!(!diff.is_signature_only());
+    assert_eq!(diff.structural().count(), 1);
+    assert_eq!(diff.to_string().lines().count(), 2);
+
+    let json = diff.to_json();
+    assert_eq!(json["equal"], false);
+    assert_eq!(json["signature_only"], false);
+    assert_eq!(json["differences"][0]["field"], "inputs[0].witness");
+    assert_eq!(json["differences"][0]["signature_only"], true);
+    assert_eq!(json["differences"][1]["field"], "lock_time");
+    assert_eq!(json["differences"][1]["left"], "0");
+    assert_eq!(json["differences"][1]["right"], "1");
+}
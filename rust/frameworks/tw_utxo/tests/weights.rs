@@ -0,0 +1,252 @@
+mod common;
+
+use crate::common::{PRIVATE_KEY, TXID};
+use tw_encoding::hex::DecodeHex;
+use tw_keypair::{ecdsa, schnorr};
+use tw_utxo::script::Script;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::standard_transaction::builder::{
+    txid_from_str_and_rev, OutputBuilder, TransactionBuilder, UtxoBuilder,
+};
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::weights::{self, FeeRounding, InputClaim, OutputClass};
+
+/// A signed transaction and the claims of its inputs and the classes of its outputs.
+struct SignedFixture {
+    encoded: &'static str,
+    claims: &'static [InputClaim],
+    outputs: &'static [OutputClass],
+}
+
+/// Signed transactions of the `tw_any_coin` Bitcoin tests.
+const SIGNED_FIXTURES: &[SignedFixture] = &[
+    SignedFixture {
+        encoded: "02000000017be4e642bb278018ab12277de9427773ad1c5f5b1d164a157e0d99aa48dc1c1e000000006a473044022078eda020d4b86fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd4590121036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536ffffffff01c0aff629010000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000",
+        claims: &[InputClaim::P2pkh],
+        outputs: &[OutputClass::P2pkh],
+    },
+    SignedFixture {
+        encoded: "020000000111b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18000000006a4730440220079b598713c12210c13f059c477f4ab33f85e80749c0925e0ac655bc0a3b9ca802200e9b83fb57ab3f2c907daeb386f02ed1e459aa058168c9e3b4e9a074ec8db78d0121028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28fffffffff01e80300000000000017a9146b04883a86994629fcc84e558968e2d70a3472fd8700000000",
+        claims: &[InputClaim::P2pkh],
+        outputs: &[OutputClass::P2sh],
+    },
+    SignedFixture {
+        encoded: "0200000001fee57e13495cbd4374f3d38736834af372db344c15e17802e7e4bd55bb0710c0000000006b483045022100edd19b379131b9f7a05ff2a79313ccec181f2e3fd06901e27ffff4fa500dbf95022060ae976cd70b8af956fdf4582bb1289bdf233a5bace82e69ea9b7cfb55c583370121036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536ffffffff01402bd82901000000220020883a539555e537e0498732376a3d4d282e304bce7bfda6876a2b63b08a04f54400000000",
+        claims: &[InputClaim::P2pkh],
+        outputs: &[OutputClass::P2wsh],
+    },
+    SignedFixture {
+        encoded: "02000000013ab533f8709accfffd1de4fa29b6584ec78f5a2f23947c938f835a3e916305c5000000006b48304502210086ab2c2192e2738529d6cd9604d8ee75c5b09b0c2f4066a5c5fa3f87a26c0af602202afc7096aaa992235c43e712146057b5ed6a776d82b9129620bc5a21991c0a5301210351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058ffffffff01c0aff62901000000225120e01cfdd05da8fa1d71f987373f3790d45dea9861acb0525c86656fe50f4397a600000000",
+        claims: &[InputClaim::P2pkh],
+        outputs: &[OutputClass::P2tr],
+    },
+    SignedFixture {
+        encoded: "02000000000101821a37e92b51467733034ec48121eece8411f07e35d2654092ad2baef078ed750100000000ffffffff02b80b0000000000001600145f41cc5fcd2bcd1f0079d27d4153bafbffc15df83212000000000000225120412a773e0bba5cfb5462d024cd4bf2cce1b8688a9e7a7a3f8507ebba8f00de580140cbe4d13bc9e067b042179e2c217e4e4b1d552119d12839aa4df11c21282f9159e2c4b58a4f22b291c200c0d0c5f277902282bdd78589dff0edbea89d3f00d77400000000",
+        claims: &[InputClaim::P2trKeyPath],
+        outputs: &[OutputClass::P2wpkh, OutputClass::P2tr],
+    },
+    SignedFixture {
+        encoded: "0200000000010295792119f9b8319f92147d9dfd6165a72f3fd16fb8fd025d5e467113a1c900020000000000fffffffff0f208f4036f9f60d733c3812fa151744bc3d2f7c268577588191e411919f2af0100000000ffffffff01ad250000000000001600143c80865790a87fce1e60f9459d689a1c35633ad40140d8cd111da08d7863366d4ff23f6b3be3ce7c7496ecabdbb6a275b1591270767e163d091b208190abae3075f24f11b1291656732369e3f9a14efdf131e481701302473044022021df9a043a886a9e7068fe06a19d8aa0a4a5a21c4b4e50abdbc6aeede32ca494022049da5e9082d83e6108027f4d63965cee50a2c9b619e603692748388150b3bf840121037ef29d31b889dfbae30cff4e996f742a49aae10a03fb6f992048c8d366b4b7c900000000",
+        claims: &[InputClaim::P2trKeyPath, InputClaim::P2wpkh],
+        outputs: &[OutputClass::P2wpkh],
+    },
+    SignedFixture {
+        encoded: "01000000000101200e3119012b1ccff35c011523050038bbdd1ca38aa56774c8331edbb5ada6170000000000ffffffff031027000000000000160014daaae0d3de9d8fdee31661e61aea828b59be78640000000000000000166a14a8491d40d4f71a752ca41da0516aed80c33a1b56fc1c000000000000160014540371330ae036602f2a715adaa044ac0856312c02483045022100e29731f7474f9103c6df3434c8c62a540a21ad0e10e23df343b1e81e4b26110602202d37fb4fee5341a41f9e4e65ba2d3e0d2309425ea9806d94eb268efe6f21007001210369cdaf80b4a5fdad91e9face90e848225512884ec2e3ed572ca11dc68e75054700000000",
+        claims: &[InputClaim::P2wpkh],
+        outputs: &[
+            OutputClass::P2wpkh,
+            OutputClass::OpReturn { data_len: 20 },
+            OutputClass::P2wpkh,
+        ],
+    },
+];
+
+/// Measures the weight of the serialized transaction.
+fn measured_weight(tx: &Transaction) -> usize {
+    let base_size = tx.without_witness().encode_out().len();
+    let total_size = tx.encode_out().len();
+    weights::weight_from_sizes(base_size, total_size)
+}
+
+/// Length of the signature the input is claimed with.
+fn sig_len(claim: InputClaim, script_sig: &Script, witness_items: &[Script]) -> usize {
+    match claim {
+        // The first push opcode of the scriptSig is `OP_PUSHBYTES_<sig_len>`.
+        InputClaim::P2pk | InputClaim::P2pkh => script_sig.as_slice()[0] as usize,
+        InputClaim::P2wpkh | InputClaim::P2trKeyPath | InputClaim::P2trScriptPath { .. } => {
+            witness_items[0].len()
+        },
+    }
+}
+
+#[test]
+fn test_weights_signed_fixtures() {
+    for fixture in SIGNED_FIXTURES {
+        let encoded = fixture.encoded.decode_hex().unwrap();
+        let tx = Transaction::from_bytes(&encoded).unwrap();
+        assert_eq!(tx.inputs.len(), fixture.claims.len(), "{}", fixture.encoded);
+        assert_eq!(
+            tx.outputs.len(),
+            fixture.outputs.len(),
+            "{}",
+            fixture.encoded
+        );
+
+        let segwit_tx = tx.has_witness();
+        let mut expected =
+            weights::tx_overhead_weight(tx.inputs.len(), tx.outputs.len(), segwit_tx);
+        for (input, claim) in tx.inputs.iter().zip(fixture.claims) {
+            let sig_len = sig_len(*claim, &input.script_sig, input.witness.as_items());
+            expected += claim.weight_with_sig_len(sig_len, segwit_tx);
+        }
+        for (output, class) in tx.outputs.iter().zip(fixture.outputs) {
+            assert_eq!(
+                output.script_pubkey.len(),
+                class.script_len(),
+                "{}",
+                fixture.encoded
+            );
+            expected += class.weight();
+        }
+
+        assert_eq!(measured_weight(&tx), expected, "{}", fixture.encoded);
+        assert_eq!(tx.weight(), expected, "{}", fixture.encoded);
+        assert_eq!(tx.vsize(), weights::vsize_from_weight(expected));
+    }
+}
+
+#[test]
+fn test_weights_estimated_tx() {
+    let ecdsa_pubkey = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY)
+        .unwrap()
+        .public();
+    let schnorr_pubkey = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+    let utxo = |prev_index: u32| {
+        UtxoBuilder::new()
+            .prev_txid(txid_from_str_and_rev(TXID).unwrap())
+            .prev_index(prev_index)
+            .amount(100_000)
+            .sighash_type(SighashType::default())
+    };
+
+    let claims = [
+        InputClaim::P2pk,
+        InputClaim::P2pkh,
+        InputClaim::P2wpkh,
+        InputClaim::P2trKeyPath,
+    ];
+    let inputs = [
+        utxo(0).p2pk(&ecdsa_pubkey).unwrap(),
+        utxo(1).p2pkh(&ecdsa_pubkey).unwrap(),
+        utxo(2).p2wpkh(&ecdsa_pubkey).unwrap(),
+        utxo(3).p2tr_key_path(&schnorr_pubkey).unwrap(),
+    ];
+
+    let classes = [
+        OutputClass::P2pk,
+        OutputClass::P2pkh,
+        OutputClass::P2sh,
+        OutputClass::P2wpkh,
+        OutputClass::P2wsh,
+        OutputClass::P2tr,
+        OutputClass::OpReturn { data_len: 80 },
+    ];
+    let outputs = [
+        OutputBuilder::new(1_000).p2pk(&ecdsa_pubkey),
+        OutputBuilder::new(1_000).p2pkh(&ecdsa_pubkey),
+        OutputBuilder::new(1_000).p2sh(&[0x51]),
+        OutputBuilder::new(1_000).p2wpkh(&ecdsa_pubkey),
+        OutputBuilder::new(1_000).p2wsh(&Script::from(vec![0x51])),
+        OutputBuilder::new(1_000).p2tr_key_path(&schnorr_pubkey),
+        OutputBuilder::new(0).op_return(&[0xab; 80]).unwrap(),
+    ];
+
+    // Legacy inputs only, then all the inputs.
+    for input_count in [2, claims.len()] {
+        let mut builder = TransactionBuilder::new();
+        for (utxo, arg) in inputs[..input_count].iter().cloned() {
+            builder.push_input(utxo, arg);
+        }
+        for output in outputs.iter().cloned() {
+            builder.push_output(output);
+        }
+        let estimated_tx = builder.build().unwrap().estimate_transaction();
+
+        let segwit_tx = input_count > 2;
+        assert_eq!(estimated_tx.has_witness(), segwit_tx);
+
+        let expected = weights::tx_overhead_weight(input_count, classes.len(), segwit_tx)
+            + claims[..input_count]
+                .iter()
+                .map(|claim| claim.weight(segwit_tx))
+                .sum::<usize>()
+            + classes.iter().map(|class| class.weight()).sum::<usize>();
+
+        assert_eq!(measured_weight(&estimated_tx), expected);
+        assert_eq!(estimated_tx.weight(), expected);
+    }
+}
+
+#[test]
+fn test_weights_standard_numbers() {
+    assert_eq!(InputClaim::P2pk.weight(false), 460);
+    assert_eq!(InputClaim::P2pkh.weight(false), 596);
+    assert_eq!(InputClaim::P2pkh.weight(true), 597);
+    assert_eq!(InputClaim::P2wpkh.weight(true), 273);
+    assert_eq!(InputClaim::P2trKeyPath.weight(true), 231);
+    assert_eq!(InputClaim::P2trKeyPath.weight_with_sig_len(64, true), 230);
+
+    assert_eq!(OutputClass::P2pkh.weight(), 136);
+    assert_eq!(OutputClass::P2sh.weight(), 128);
+    assert_eq!(OutputClass::P2wpkh.weight(), 124);
+    assert_eq!(OutputClass::P2wsh.weight(), 172);
+    assert_eq!(OutputClass::P2tr.weight(), 172);
+    assert_eq!(OutputClass::OpReturn { data_len: 80 }.weight(), 368);
+
+    assert_eq!(weights::tx_overhead_weight(1, 2, false), 40);
+    assert_eq!(weights::tx_overhead_weight(1, 2, true), 42);
+    assert_eq!(weights::tx_overhead_weight(253, 2, true), 50);
+
+    // The functions can be evaluated at compile time.
+    const P2WPKH_1_IN_2_OUT: usize = weights::tx_overhead_weight(1, 2, true)
+        + InputClaim::P2wpkh.weight(true)
+        + 2 * OutputClass::P2wpkh.weight();
+    assert_eq!(P2WPKH_1_IN_2_OUT, 563);
+    assert_eq!(weights::vsize_from_weight(P2WPKH_1_IN_2_OUT), 141);
+}
+
+#[test]
+fn test_fee_for_weight_rounding() {
+    assert_eq!(
+        weights::fee_for_weight(561, 3, FeeRounding::VsizeUp),
+        Some(423)
+    );
+    assert_eq!(weights::fee_for_weight(561, 3, FeeRounding::Up), Some(421));
+    assert_eq!(
+        weights::fee_for_weight(561, 3, FeeRounding::Down),
+        Some(420)
+    );
+
+    // No rounding is needed.
+    assert_eq!(
+        weights::fee_for_weight(560, 3, FeeRounding::VsizeUp),
+        Some(420)
+    );
+    assert_eq!(weights::fee_for_weight(560, 3, FeeRounding::Up), Some(420));
+    assert_eq!(
+        weights::fee_for_weight(560, 3, FeeRounding::Down),
+        Some(420)
+    );
+
+    assert_eq!(
+        weights::fee_for_weight(561, u64::MAX, FeeRounding::Up),
+        None
+    );
+    assert_eq!(
+        weights::fee_for_weight(561, u64::MAX, FeeRounding::VsizeUp),
+        None
+    );
+    assert_eq!(weights::fee_for_vsize(141, 2), Some(282));
+}
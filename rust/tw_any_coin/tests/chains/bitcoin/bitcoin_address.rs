@@ -9,8 +9,12 @@ use tw_any_coin::test_utils::address_utils::{
     AddressBase58IsValid, AddressBech32IsValid, AddressCreateBase58WithPublicKey,
     AddressCreateBech32WithPublicKey,
 };
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_registry::coin_context::CoinRegistryContext;
 use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::supported_coin_items;
 use tw_keypair::tw::PublicKeyType;
+use tw_utxo::address::standard_bitcoin::StandardBitcoinAddress;
 
 #[test]
 fn test_bitcoin_address_normalization() {
@@ -65,6 +69,26 @@ fn test_bitcoin_address_is_valid_bech32() {
     });
 }
 
+#[test]
+fn test_litecoin_p2sh_address_is_valid() {
+    // Canonical `0x32` prefix.
+    test_address_valid(CoinType::Litecoin, "MQMHBtvnBfxTzt3K2bdxgSE7qZPHSXWsGM");
+    // Deprecated `0x05` prefix is still accepted.
+    test_address_valid(CoinType::Litecoin, "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+    // But not by Bitcoin.
+    test_address_invalid(CoinType::Bitcoin, "MQMHBtvnBfxTzt3K2bdxgSE7qZPHSXWsGM");
+}
+
+#[test]
+fn test_viacoin_p2sh_address_is_valid() {
+    // Canonical `0x21` prefix.
+    test_address_valid(CoinType::Viacoin, "ESxRxvhJP6ZKtYaMGjj48As1kgCh6hXa6X");
+    // Deprecated `0x05` prefix is still accepted.
+    test_address_valid(CoinType::Viacoin, "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+    // But not by Bitcoin.
+    test_address_invalid(CoinType::Bitcoin, "ESxRxvhJP6ZKtYaMGjj48As1kgCh6hXa6X");
+}
+
 #[test]
 fn test_bitcoin_address_invalid() {
     test_address_invalid(
@@ -81,6 +105,33 @@ fn test_bitcoin_address_invalid() {
     );
 }
 
+#[test]
+fn test_bitcoin_address_detect_network() {
+    let contexts: Vec<_> = supported_coin_items()
+        .map(|item| (item.coin_id, CoinRegistryContext::with_coin_item(item)))
+        .collect();
+    let detect = |s: &str| {
+        let coins = contexts
+            .iter()
+            .map(|(coin, context)| (*coin, context as &dyn CoinContext));
+        StandardBitcoinAddress::detect_network_by_hrp(s, coins)
+    };
+
+    // Litecoin P2WPKH address is given instead of a Bitcoin one.
+    let litecoin = "ltc1q3m3ujh350qrqdl33pv7pjw0d0m9qnm6qjcjpga";
+    test_address_invalid(CoinType::Bitcoin, litecoin);
+    assert_eq!(detect(litecoin), Some(CoinType::Litecoin));
+
+    assert_eq!(
+        detect("bc1pwse34zfpvt344rvlt7tw0ngjtfh9xasc4q03avf0lk74jzjpzjuqaz7ks5"),
+        Some(CoinType::Bitcoin)
+    );
+    // Legacy addresses are not detected by HRP.
+    assert_eq!(detect("LW6HjAU6GL9fK2LZWUA6VZCzomTdrpx3nr"), None);
+    // Unknown HRP.
+    assert_eq!(detect("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"), None);
+}
+
 #[test]
 fn test_bitcoin_address_legacy_get_data() {
     test_address_get_data(
@@ -135,7 +135,7 @@ fn test_bitcoin_compile_brc20_transfer_reveal() {
         ..Default::default()
     };
 
-    let sighash_0 = H256::from("3fd1a453f68dadba110a5b18798a06458e5f3d84dbb6f4f33334fa440a4a2530");
+    let sighash_0 = H256::from("0e4b1de2c8da701716722ce32df876f6f10d523d88886d058aaaaf7d652fd58d");
     preimage::BitcoinPreImageHelper::new(&signing)
         .coin(CoinType::Bitcoin)
         .add_expected_taproot(my_pubkey.to_vec(), sighash_0.to_vec(), None)
@@ -143,14 +143,14 @@ fn test_bitcoin_compile_brc20_transfer_reveal() {
 
     // Sign the given sighash with the non-tweaked private key.
     let signature_0 = my_private_key.sign(sighash_0).unwrap();
-    assert_eq!(signature_0.to_vec().to_hex(), "6a35548b8fa4620028e021a944c1d3dc6e947243a7bfc901bf63fefae0d2460efa149a6440cab51966aa4f09faef2d1e5efcba23ab4ca6e669da598022dbcfe3");
+    assert_eq!(signature_0.to_vec().to_hex(), "694b812b91c4143c759d717109283fda9a195629d3a742a8132165cad13232fd2ef50c050198f404fbc56ae6923c111c417c8bf53af8fca9f5b076c8e0ec3979");
 
     // Compile the transaction.
     compile::BitcoinCompileHelper::new(&signing)
         .coin(CoinType::Bitcoin)
         .add_pubkey_sig(my_pubkey.to_vec(), signature_0.to_vec())
         .compile(compile::Expected {
-            encoded: "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d03406a35548b8fa4620028e021a944c1d3dc6e947243a7bfc901bf63fefae0d2460efa149a6440cab51966aa4f09faef2d1e5efcba23ab4ca6e669da598022dbcfe35b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000",
+            encoded: "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d0340694b812b91c4143c759d717109283fda9a195629d3a742a8132165cad13232fd2ef50c050198f404fbc56ae6923c111c417c8bf53af8fca9f5b076c8e0ec39795b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000",
             txid: BRC20_REVEAL_TX_ID,
             inputs: vec![brc20_inscribed_amount],
             outputs: vec![DUST],
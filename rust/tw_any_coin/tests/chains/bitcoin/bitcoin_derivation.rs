@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_utxo::address::batch::BatchScriptType;
+use tw_utxo::address::standard_derivation::{registry_coin_type, StandardDerivation};
+use tw_utxo::chain_params::ChainParams;
+
+const MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn coin_type(coin: CoinType, derivation: Derivation) -> u32 {
+    let coin_context = CoinRegistryContext::with_coin_item(get_coin_item(coin).unwrap());
+    registry_coin_type(&coin_context, derivation).unwrap()
+}
+
+fn first_address(
+    coin_type: u32,
+    chain_params: ChainParams,
+    script_type: BatchScriptType,
+) -> String {
+    StandardDerivation::from_mnemonic(MNEMONIC, "", coin_type, chain_params)
+        .unwrap()
+        .derive_address(script_type, 0, 0, 0)
+        .unwrap()
+        .address
+}
+
+#[test]
+fn test_registry_coin_type() {
+    assert_eq!(coin_type(CoinType::Bitcoin, Derivation::Default), 0);
+    assert_eq!(coin_type(CoinType::Bitcoin, Derivation::Legacy), 0);
+    assert_eq!(coin_type(CoinType::Bitcoin, Derivation::Testnet), 1);
+    assert_eq!(coin_type(CoinType::Litecoin, Derivation::Default), 2);
+}
+
+#[test]
+fn test_bitcoin_standard_derivation_with_registry_coin_type() {
+    let mainnet = coin_type(CoinType::Bitcoin, Derivation::Default);
+    let testnet = coin_type(CoinType::Bitcoin, Derivation::Testnet);
+
+    let vectors = [
+        (
+            BatchScriptType::P2PKH,
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+            "mkpZhYtJu2r87Js3pDiWJDmPte2NRZ8bJV",
+        ),
+        (
+            BatchScriptType::P2SHP2WPKH,
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2",
+        ),
+        (
+            BatchScriptType::P2WPKH,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl",
+        ),
+        (
+            BatchScriptType::P2TR,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            "tb1p8wpt9v4frpf3tkn0srd97pksgsxc5hs52lafxwru9kgeephvs7rqlqt9zj",
+        ),
+    ];
+
+    for (script_type, expected_mainnet, expected_testnet) in vectors {
+        assert_eq!(
+            first_address(mainnet, ChainParams::bitcoin_mainnet(), script_type),
+            expected_mainnet
+        );
+        assert_eq!(
+            first_address(testnet, ChainParams::bitcoin_testnet(), script_type),
+            expected_testnet
+        );
+    }
+}
@@ -2,9 +2,11 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::chains::common::bitcoin::{plan, Amount, DUST};
+use crate::chains::common::bitcoin::{plan, Amount, DUST, ONE_BTC};
+use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 
 #[test]
 fn test_exact_selector_with_change_1() {
@@ -270,3 +272,213 @@ fn test_exact_selector_filter_utxo_dust_without_change() {
             change: 0,
         });
 }
+
+#[test]
+fn test_exact_selector_fee_attribution() {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000, 1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+
+    // The fee is not attributed unless requested.
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let output = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert!(output.fee_attribution.is_empty());
+
+    input.attribute_fee = true;
+    let output = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.fee_estimate, 820);
+
+    // Three P2PKH outputs of 34 bytes share the 410 vbytes equally,
+    // the remainder is assigned to the change output of the largest value.
+    let fees: Vec<_> = output
+        .fee_attribution
+        .iter()
+        .map(|output_fee| (output_fee.output_index, output_fee.fee))
+        .collect();
+    assert_eq!(fees, vec![(0, 273), (1, 273), (2, 274)]);
+}
+
+#[test]
+fn test_exact_selector_dogecoin_max_money() {
+    // 30M DOGE exceeds the Bitcoin max money of 21M BTC, but not the Dogecoin one.
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![30_000_000 * ONE_BTC],
+        outputs: vec![25_000_000 * ONE_BTC],
+        change: true,
+        max: false,
+        dust_threshold: ONE_BTC,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 100_000,
+    });
+    // Take the chain parameters from the registry.
+    input.chain_info = None;
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let output = planner.plan(CoinType::Dogecoin, input.clone());
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.available_amount, 30_000_000 * ONE_BTC);
+    assert_eq!(output.outputs[0].value, 25_000_000 * ONE_BTC);
+
+    let output = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(output.error, SigningError::Error_invalid_utxo_amount);
+}
+
+#[test]
+fn test_exact_selector_output_shuffle_seed() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![100_000],
+        outputs: vec![10_000, 20_000, 30_000, 40_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 1,
+    });
+    let output_values = |plan: &Proto::TransactionPlan| -> Vec<Amount> {
+        plan.outputs.iter().map(|output| output.value).collect()
+    };
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let ordered = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(ordered.error, SigningError::OK, "{}", ordered.error_message);
+
+    let input = Proto::SigningInput {
+        output_shuffle_seed: [7; 32].to_vec().into(),
+        ..input
+    };
+    let shuffled = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(
+        shuffled.error,
+        SigningError::OK,
+        "{}",
+        shuffled.error_message
+    );
+    assert_eq!(shuffled.fee_estimate, ordered.fee_estimate);
+    assert_eq!(shuffled.change, ordered.change);
+
+    // The same outputs including change, in the order given by the seed.
+    let mut sorted = output_values(&shuffled);
+    sorted.sort();
+    let mut expected = output_values(&ordered);
+    expected.sort();
+    assert_eq!(sorted, expected);
+
+    let same_seed = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(output_values(&same_seed), output_values(&shuffled));
+
+    let input = Proto::SigningInput {
+        output_shuffle_seed: [7; 31].to_vec().into(),
+        ..input
+    };
+    let invalid_seed = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(invalid_seed.error, SigningError::Error_invalid_params);
+}
+
+#[test]
+fn test_exact_selector_selection_policy() {
+    use Proto::mod_TransactionPlan::ExclusionReason;
+
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![500, 10_000, 20_000, 30_000, 40_000],
+        outputs: vec![5_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectInOrder,
+        fee_per_vb: 1,
+    });
+    let confirmations = |confirmations| Some(Proto::mod_Input::Confirmations { confirmations });
+    // #0 is dust, #1 is received unconfirmed, #2 is the own unconfirmed change.
+    input.inputs[1].confirmations = confirmations(0);
+    input.inputs[2].confirmations = confirmations(0);
+    input.inputs[2].is_change_from_self = true;
+    input.inputs[3].confirmations = confirmations(1);
+    input.inputs[4].confirmations = confirmations(6);
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let mut plan = |selection_policy, min_confirmations| {
+        let input = Proto::SigningInput {
+            selection_policy,
+            min_confirmations,
+            ..input.clone()
+        };
+        let output = planner.plan(CoinType::Bitcoin, input);
+        assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+        let selected: Vec<_> = output.inputs.iter().map(|input| input.value).collect();
+        let excluded: Vec<_> = output
+            .excluded
+            .iter()
+            .map(|excluded| {
+                let vout = excluded.out_point.as_ref().unwrap().vout;
+                assert_eq!(excluded.input_index, vout);
+                (
+                    excluded.input_index,
+                    excluded.reason,
+                    excluded.confirmations,
+                    excluded.required_confirmations,
+                )
+            })
+            .collect();
+        (selected, excluded)
+    };
+
+    let (selected, excluded) = plan(Proto::SelectionPolicy::AnyUtxo, 0);
+    assert_eq!(selected, vec![10_000]);
+    assert_eq!(excluded, vec![(0, ExclusionReason::Dust, 0, 0)]);
+
+    let (selected, excluded) = plan(Proto::SelectionPolicy::AllowOwnUnconfirmedChange, 0);
+    assert_eq!(selected, vec![20_000]);
+    assert_eq!(
+        excluded,
+        vec![
+            (0, ExclusionReason::Dust, 0, 0),
+            (1, ExclusionReason::Unconfirmed, 0, 0),
+        ]
+    );
+
+    let (selected, excluded) = plan(Proto::SelectionPolicy::MinConfirmations, 2);
+    assert_eq!(selected, vec![40_000]);
+    assert_eq!(
+        excluded,
+        vec![
+            (0, ExclusionReason::Dust, 0, 0),
+            (1, ExclusionReason::Unconfirmed, 0, 0),
+            (2, ExclusionReason::Unconfirmed, 0, 0),
+            (3, ExclusionReason::NotEnoughConfirmations, 1, 2),
+        ]
+    );
+}
+
+#[test]
+fn test_exact_selector_zero_fee_rate() {
+    // A zero fee rate disables the fee calculation,
+    // so the whole surplus is the fee, and it must not be considered absurd.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![10_000],
+        outputs: vec![9_000],
+        change: false,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::UseAll,
+        fee_per_vb: 0,
+    });
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![10_000],
+            outputs: vec![9_000],
+            vsize_estimate: 193,
+            // sum(inputs) - sum(outputs)
+            fee_estimate: 1_000,
+            change: 0,
+        });
+}
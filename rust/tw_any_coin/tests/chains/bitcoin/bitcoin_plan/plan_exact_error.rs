@@ -2,9 +2,13 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::chains::common::bitcoin::{plan, Amount, DUST};
+use crate::chains::common::bitcoin::{input, plan, Amount, DUST, SIGHASH_SINGLE};
 use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
+use tw_bitcoin::modules::planner::BitcoinPlanner;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::DecodeHex;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Common::Proto as CommonProto;
 
@@ -205,3 +209,108 @@ fn test_exact_selector_dust_amount_requested() {
         CommonProto::SigningError::Error_dust_amount_requested
     );
 }
+
+#[test]
+fn test_exact_selector_fee_rate_out_of_chain_bounds() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![100_000_000],
+        outputs: vec![50_000_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 10,
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(plan.error, CommonProto::SigningError::OK);
+
+    // The fee rate is below the Dogecoin minimum relay fee rate,
+    // even though the dust threshold is overridden.
+    let plan = planner.plan(CoinType::Dogecoin, input.clone());
+    assert_eq!(plan.error, CommonProto::SigningError::Error_wrong_fee);
+
+    // The fee rate exceeds the Bitcoin maximum fee rate.
+    let input = Proto::SigningInput {
+        fee_per_vb: 100_000,
+        ..input
+    };
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_wrong_fee);
+}
+
+#[test]
+fn test_exact_selector_segwit_input_on_dogecoin() {
+    let pubkey = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf"
+        .decode_hex()
+        .unwrap();
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![100_000_000],
+        outputs: vec![50_000_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 100_000,
+    });
+    input.inputs[0].claiming_script = input::p2wpkh(pubkey);
+    // Take the chain parameters from the registry.
+    input.chain_info = None;
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::OK,
+        "{}",
+        plan.error_message
+    );
+
+    // Dogecoin has not activated segwit.
+    let plan = planner.plan(CoinType::Dogecoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_segwit_not_supported
+    );
+}
+
+#[test]
+fn test_exact_selector_sighash_type_not_allowed_on_zcash() {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![100_000_000],
+        outputs: vec![50_000_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 10,
+    });
+    input.inputs[0].sighash_type = SIGHASH_SINGLE;
+    // Take the chain parameters from the coin.
+    input.chain_info = None;
+
+    // Zcash is not planned by the Bitcoin entry, so the coins are mocked.
+    let bitcoin = TestCoinContext {
+        id: "bitcoin".to_string(),
+        p2pkh: Some(0),
+        p2sh: Some(5),
+        ..TestCoinContext::default()
+    };
+    let zcash = TestCoinContext {
+        id: "zcash".to_string(),
+        p2pkh: Some(184),
+        p2sh: Some(189),
+        ..TestCoinContext::default()
+    };
+
+    // Bitcoin accepts every defined sighash type.
+    BitcoinPlanner::plan_impl(&bitcoin, &input).unwrap();
+
+    // Zcash accepts `SIGHASH_ALL` only.
+    let err = BitcoinPlanner::plan_impl(&zcash, &input).unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_sighash_type_not_allowed
+    );
+}
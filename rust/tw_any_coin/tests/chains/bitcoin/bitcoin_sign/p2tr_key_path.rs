@@ -14,9 +14,8 @@ const ALICE_PRIVATE_KEY: &str = "12ce558df23528f1aa86f1f51ac7e13a197a06bda27610f
 const BOB_PRIVATE_KEY: &str = "26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227";
 
 fn p2tr_key_path_address(internal_pubkey: &schnorr::PublicKey) -> String {
-    let pubkey = internal_pubkey.compressed();
     let merkle_root = None;
-    TaprootAddress::p2tr_with_public_key(BITCOIN_HRP.to_string(), &pubkey, merkle_root)
+    TaprootAddress::p2tr_with_public_key(BITCOIN_HRP.to_string(), internal_pubkey, merkle_root)
         .unwrap()
         .to_string()
 }
@@ -46,7 +45,7 @@ fn test_bitcoin_sign_input_p2pkh_output_p2tr_key_path(send_to: P2TRRecipientType
     let to_recipient = match send_to {
         P2TRRecipientType::KeyPath => output::p2tr_key_path(bob_pubkey.to_vec()),
         P2TRRecipientType::KeyPathAssumeTweaked => {
-            let bob_pubkey_tweaked = bob_pubkey.tweak(None).x_only();
+            let bob_pubkey_tweaked = bob_pubkey.tweak(None).xonly;
             output::p2tr_assume_tweaked(bob_pubkey_tweaked.bytes().to_vec())
         },
         P2TRRecipientType::P2TRAddress => output::to_address(&p2tr_key_path_address(&bob_pubkey)),
@@ -117,7 +116,7 @@ fn test_bitcoin_sign_output_p2tr(utxo_owner: P2TRClaimingScriptType) {
             input::receiver_address(&p2tr_key_path_address(&bob_pubkey))
         },
         P2TRClaimingScriptType::P2TRCustomScript => {
-            let custom_script = conditions::new_p2tr_key_path(&bob_pubkey.compressed());
+            let custom_script = conditions::new_p2tr_key_path(&bob_pubkey);
             input::custom_script(custom_script.to_vec())
         },
     };
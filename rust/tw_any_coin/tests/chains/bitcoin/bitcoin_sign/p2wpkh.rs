@@ -5,11 +5,13 @@
 use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, sign, DUST, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_keypair::ecdsa;
 use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 
 #[test]
 fn test_bitcoin_sign_p2wpkh_input_different_builders() {
@@ -83,3 +85,207 @@ fn test_bitcoin_sign_p2wpkh_input_different_builders() {
             fee: 7_097,
         });
 }
+
+#[test]
+fn test_bitcoin_sign_p2wpkh_input_zero_amount_error() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(my_private_key.public().to_vec()),
+        ..Default::default()
+    };
+    // The amount is left defaulted, but the BIP143 sighash commits to it.
+    let utxo_1 = Proto::Input {
+        out_point: input::out_point(
+            "1f62c18bfc5f8293a2b7b061587c427bf830fb224289f9a806e6ad48de6a4c7d",
+            1,
+        ),
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(my_private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 28_035,
+        to_recipient: output::to_address("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp"),
+    };
+
+    let mut signing = Proto::SigningInput {
+        version: Proto::TransactionVersion::V1,
+        private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+        inputs: vec![utxo_0, utxo_1],
+        outputs: vec![out_0],
+        input_selector: Proto::InputSelector::UseAll,
+        chain_info: btc_info(),
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing.clone());
+    assert_eq!(output.error, SigningError::Error_missing_input_amount);
+    assert!(
+        output.error_message.contains("UTXO #1"),
+        "{}",
+        output.error_message
+    );
+
+    // Zero amount can be allowed explicitly, then the UTXO is filtered out as dust.
+    signing.inputs[1].allow_zero_amount = true;
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.transaction.unwrap().inputs.len(), 1);
+}
+
+#[test]
+fn test_bitcoin_sign_p2wpkh_input_chain_mismatch_error() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(my_private_key.public().to_vec()),
+        ..Default::default()
+    };
+    // The input is built for Bitcoin Cash, but fed to a Bitcoin signer.
+    let utxo_1 = Proto::Input {
+        out_point: input::out_point(
+            "1f62c18bfc5f8293a2b7b061587c427bf830fb224289f9a806e6ad48de6a4c7d",
+            1,
+        ),
+        value: 4_863,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(my_private_key.public().to_vec()),
+        chain: Proto::ChainTag::BitcoinCash,
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 28_035,
+        to_recipient: output::to_address("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp"),
+    };
+
+    let signing = Proto::SigningInput {
+        version: Proto::TransactionVersion::V1,
+        private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+        inputs: vec![utxo_0, utxo_1],
+        outputs: vec![out_0],
+        input_selector: Proto::InputSelector::UseAll,
+        chain_info: btc_info(),
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 33,
+        chain: Proto::ChainTag::Bitcoin,
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+    assert!(
+        output
+            .error_message
+            .contains("built for Bitcoin Cash, but the signer is configured for Bitcoin"),
+        "{}",
+        output.error_message
+    );
+    assert!(
+        output.error_message.contains("UTXO #1"),
+        "{}",
+        output.error_message
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_p2wpkh_chain_from_coin() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+
+    let signing_input = |input_chain: Proto::ChainTag, signer_chain: Proto::ChainTag| {
+        let utxo_0 = Proto::Input {
+            out_point: input::out_point(
+                "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+                1,
+            ),
+            value: 30_269,
+            sighash_type: SIGHASH_ALL,
+            claiming_script: input::p2wpkh(my_private_key.public().to_vec()),
+            chain: input_chain,
+            ..Default::default()
+        };
+        let out_0 = Proto::Output {
+            value: 28_035,
+            to_recipient: output::p2wpkh(my_private_key.public().to_vec()),
+        };
+
+        Proto::SigningInput {
+            version: Proto::TransactionVersion::V1,
+            private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+            inputs: vec![utxo_0],
+            outputs: vec![out_0],
+            input_selector: Proto::InputSelector::UseAll,
+            dust_policy: dust_threshold(DUST),
+            fee_per_vb: 33,
+            chain: signer_chain,
+            ..Default::default()
+        }
+    };
+
+    // The signer is configured for the chain of the coin.
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(
+        CoinType::Litecoin,
+        signing_input(Proto::ChainTag::Litecoin, Proto::ChainTag::UseDefault),
+    );
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // So are the inputs.
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(
+        CoinType::Litecoin,
+        signing_input(Proto::ChainTag::UseDefault, Proto::ChainTag::Litecoin),
+    );
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(
+        CoinType::Litecoin,
+        signing_input(Proto::ChainTag::Bitcoin, Proto::ChainTag::UseDefault),
+    );
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+    assert!(
+        output
+            .error_message
+            .contains("built for Bitcoin, but the signer is configured for Litecoin"),
+        "{}",
+        output.error_message
+    );
+
+    // An explicit signer chain must be the chain of the coin.
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(
+        CoinType::Bitcoin,
+        signing_input(Proto::ChainTag::UseDefault, Proto::ChainTag::Litecoin),
+    );
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+    assert!(
+        output
+            .error_message
+            .contains("'SigningInput.chain' is Litecoin, but the coin belongs to Bitcoin"),
+        "{}",
+        output.error_message
+    );
+}
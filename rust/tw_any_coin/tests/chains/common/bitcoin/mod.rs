@@ -42,6 +42,7 @@ pub fn btc_info() -> Option<Proto::ChainInfo> {
     Some(Proto::ChainInfo {
         p2pkh_prefix: BITCOIN_P2PKH_PREFIX as u32,
         p2sh_prefix: BITCOIN_P2SH_PREFIX as u32,
+        ..Proto::ChainInfo::default()
     })
 }
 
@@ -4,6 +4,7 @@
 
 use crate::modules::protobuf_builder::ProtobufBuilder;
 use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::tx_builder::chain_params_from_coin;
 use std::borrow::Cow;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::coin_entry::{PublicKeyBytes, SignatureBytes};
@@ -82,17 +83,17 @@ impl BitcoinCompiler {
 
         SighashVerifier::verify_signatures(&unsigned_tx, &signatures)?;
         let signed_tx = TxCompiler::compile(unsigned_tx, &signatures)?;
-        let tx_proto = ProtobufBuilder::tx_to_proto(&signed_tx);
+        let tx_proto = ProtobufBuilder::tx_to_proto(&signed_tx)?;
 
         Ok(Proto::SigningOutput {
             transaction: Some(tx_proto),
-            encoded: Cow::from(signed_tx.encode_out()),
+            encoded: Cow::from(signed_tx.encode_out_for(&chain_params_from_coin(coin))),
             txid: Cow::from(signed_tx.txid()),
             // `vsize` could have been changed after the transaction being signed.
             vsize: signed_tx.vsize() as u64,
             weight: signed_tx.weight() as u64,
             // `fee` should haven't been changed since it's a difference between `sum(inputs)` and `sum(outputs)`.
-            fee: plan.fee_estimate,
+            fee: ProtobufBuilder::amount_to_proto(plan.fee_estimate)?,
             ..Proto::SigningOutput::default()
         })
     }
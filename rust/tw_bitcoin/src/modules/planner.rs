@@ -2,6 +2,7 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::modules::protobuf_builder::ProtobufBuilder;
 use crate::modules::signing_request::SigningRequestBuilder;
 use crate::modules::tx_builder::utxo_protobuf::parse_out_point;
 use std::borrow::Cow;
@@ -11,8 +12,9 @@ use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::modules::plan_builder::PlanBuilder;
 use tw_coin_entry::signing_output_error;
 use tw_proto::BitcoinV2::Proto;
+use tw_utxo::modules::fee_attribution::FeeAttribution;
 use tw_utxo::modules::tx_planner::TxPlanner;
-use tw_utxo::modules::utxo_selector::SelectResult;
+use tw_utxo::modules::utxo_selector::{ExcludedUtxo, ExclusionReason, SelectResult};
 
 pub struct BitcoinPlanner;
 
@@ -22,7 +24,14 @@ impl BitcoinPlanner {
         input: &Proto::SigningInput<'a>,
     ) -> SigningResult<Proto::TransactionPlan<'a>> {
         let request = SigningRequestBuilder::build(coin, input)?;
-        let SelectResult { unsigned_tx, plan } = TxPlanner::plan(request)?;
+        let select_result = TxPlanner::plan(request)?;
+
+        let fee_attribution = if input.attribute_fee {
+            Self::fee_attribution_proto(&FeeAttribution::from_plan(&select_result)?)?
+        } else {
+            Vec::default()
+        };
+        let SelectResult { unsigned_tx, plan } = select_result;
 
         // Prepare a map of source Inputs Proto `{ OutPoint -> Input }`.
         // It will be used to find a Input Proto by its `OutPoint`.
@@ -46,6 +55,12 @@ impl BitcoinPlanner {
             selected_inputs_proto.push((*utxo_proto).clone());
         }
 
+        let excluded = plan
+            .excluded
+            .iter()
+            .map(Self::excluded_input_proto)
+            .collect();
+
         // Fill out the Output Proto.
         let mut outputs_proto = Vec::with_capacity(unsigned_tx.transaction().outputs.len());
         for selected_output in unsigned_tx.transaction().outputs.iter() {
@@ -56,7 +71,7 @@ impl BitcoinPlanner {
             );
 
             outputs_proto.push(Proto::Output {
-                value: selected_output.value,
+                value: ProtobufBuilder::amount_to_proto(selected_output.value)?,
                 to_recipient,
             })
         }
@@ -64,14 +79,61 @@ impl BitcoinPlanner {
         Ok(Proto::TransactionPlan {
             inputs: selected_inputs_proto,
             outputs: outputs_proto,
-            available_amount: plan.total_spend,
-            send_amount: plan.total_send,
+            available_amount: ProtobufBuilder::amount_to_proto(plan.total_spend)?,
+            send_amount: ProtobufBuilder::amount_to_proto(plan.total_send)?,
             vsize_estimate: plan.vsize_estimate as u64,
-            fee_estimate: plan.fee_estimate,
-            change: plan.change,
+            fee_estimate: ProtobufBuilder::amount_to_proto(plan.fee_estimate)?,
+            change: ProtobufBuilder::amount_to_proto(plan.change)?,
+            fee_attribution,
+            excluded,
             ..Proto::TransactionPlan::default()
         })
     }
+
+    fn excluded_input_proto(
+        utxo: &ExcludedUtxo,
+    ) -> Proto::mod_TransactionPlan::ExcludedInput<'static> {
+        use Proto::mod_TransactionPlan::ExclusionReason as ProtoExclusionReason;
+
+        let (reason, confirmations, required_confirmations) = match utxo.reason {
+            ExclusionReason::Dust => (ProtoExclusionReason::Dust, 0, 0),
+            ExclusionReason::Unconfirmed => (ProtoExclusionReason::Unconfirmed, 0, 0),
+            ExclusionReason::NotEnoughConfirmations {
+                confirmations,
+                required,
+            } => (
+                ProtoExclusionReason::NotEnoughConfirmations,
+                confirmations,
+                required,
+            ),
+        };
+
+        Proto::mod_TransactionPlan::ExcludedInput {
+            input_index: utxo.index as u32,
+            out_point: Some(Proto::OutPoint {
+                hash: Cow::from(utxo.previous_output.hash.to_vec()),
+                vout: utxo.previous_output.index,
+            }),
+            reason,
+            confirmations,
+            required_confirmations,
+        }
+    }
+
+    fn fee_attribution_proto(
+        attribution: &FeeAttribution,
+    ) -> SigningResult<Vec<Proto::mod_TransactionPlan::OutputFee>> {
+        attribution
+            .fees
+            .iter()
+            .map(|(output_index, fee)| {
+                Ok(Proto::mod_TransactionPlan::OutputFee {
+                    output_index: *output_index as u32,
+                    fee: ProtobufBuilder::amount_to_proto(*fee)?,
+                })
+            })
+            .collect()
+    }
 }
 
 impl PlanBuilder for BitcoinPlanner {
@@ -3,26 +3,39 @@
 // Copyright © 2017 Trust Wallet.
 
 use std::borrow::Cow;
+use tw_coin_entry::error::prelude::*;
 use tw_proto::BitcoinV2::Proto;
 use tw_utxo::script::{Script, Witness};
 use tw_utxo::transaction::standard_transaction::{
     Transaction, TransactionInput, TransactionOutput,
 };
 use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
 
 pub struct ProtobufBuilder;
 
 impl ProtobufBuilder {
-    pub fn tx_to_proto(tx: &Transaction) -> Proto::Transaction<'static> {
+    pub fn tx_to_proto(tx: &Transaction) -> SigningResult<Proto::Transaction<'static>> {
         let inputs = tx.inputs().iter().map(Self::tx_input_to_proto).collect();
-        let outputs = tx.outputs().iter().map(Self::tx_output_to_proto).collect();
+        let outputs = tx
+            .outputs()
+            .iter()
+            .map(Self::tx_output_to_proto)
+            .collect::<SigningResult<_>>()?;
 
-        Proto::Transaction {
+        Ok(Proto::Transaction {
             version: tx.version(),
             lock_time: tx.locktime,
             inputs,
             outputs,
-        }
+        })
+    }
+
+    /// Converts the amount to the Protobuf `int64` type.
+    pub fn amount_to_proto(amount: Amount) -> SigningResult<i64> {
+        i64::try_from(amount)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .with_context(|| format!("Amount '{amount}' cannot be represented as int64"))
     }
 
     fn tx_input_to_proto(
@@ -41,11 +54,11 @@ impl ProtobufBuilder {
 
     fn tx_output_to_proto(
         output: &TransactionOutput,
-    ) -> Proto::mod_Transaction::TransactionOutput<'static> {
-        Proto::mod_Transaction::TransactionOutput {
+    ) -> SigningResult<Proto::mod_Transaction::TransactionOutput<'static>> {
+        Ok(Proto::mod_Transaction::TransactionOutput {
             script_pubkey: Self::script_data(&output.script_pubkey),
-            value: output.value,
-        }
+            value: Self::amount_to_proto(output.value)?,
+        })
     }
 
     fn witness_to_proto(witness: &Witness) -> Vec<Cow<'static, [u8]>> {
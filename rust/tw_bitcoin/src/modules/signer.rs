@@ -4,11 +4,11 @@
 
 use crate::modules::protobuf_builder::ProtobufBuilder;
 use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::tx_builder::chain_params_from_coin;
 use std::borrow::Cow;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::signing_output_error;
-use tw_keypair::{ecdsa, schnorr};
 use tw_proto::BitcoinV2::Proto;
 use tw_utxo::modules::keys_manager::KeysManager;
 use tw_utxo::modules::tx_planner::TxPlanner;
@@ -43,22 +43,15 @@ impl BitcoinSigner {
         let mut keys_manager = KeysManager::default();
 
         // Parse private keys and put them to the keys manager.
+        // Duplicate keys are parsed once.
         for private in input.private_keys.iter() {
-            let ecdsa_private = ecdsa::secp256k1::PrivateKey::try_from(private.as_ref())
-                .into_tw()
-                .context("Invalid ecdsa secp256k1 private key")?;
-            keys_manager.add_ecdsa_private(ecdsa_private);
+            keys_manager.add_ecdsa_private_bytes(private.as_ref())?;
 
             if has_taproot {
-                let schnorr_private = schnorr::PrivateKey::try_from(private.as_ref())
-                    .into_tw()
-                    .context("Invalid schnorr private key")?;
-
-                if input.dangerous_use_fixed_schnorr_rng {
-                    keys_manager.add_schnorr_private(schnorr_private.no_aux_rand());
-                } else {
-                    keys_manager.add_schnorr_private(schnorr_private);
-                }
+                keys_manager.add_schnorr_private_bytes(
+                    private.as_ref(),
+                    input.dangerous_use_fixed_schnorr_rng,
+                )?;
             }
         }
 
@@ -66,13 +59,13 @@ impl BitcoinSigner {
             TxSigner::sign_tx(unsigned_tx, &keys_manager).context("Error signing transaction")?;
 
         Ok(Proto::SigningOutput {
-            transaction: Some(ProtobufBuilder::tx_to_proto(&signed_tx)),
-            encoded: Cow::from(signed_tx.encode_out()),
+            transaction: Some(ProtobufBuilder::tx_to_proto(&signed_tx)?),
+            encoded: Cow::from(signed_tx.encode_out_for(&chain_params_from_coin(coin))),
             txid: Cow::from(signed_tx.txid()),
             // `vsize` could have been changed after the transaction being signed.
             vsize: signed_tx.vsize() as u64,
             // `fee` should haven't been changed since it's a difference between `sum(inputs)` and `sum(outputs)`.
-            fee: plan.fee_estimate,
+            fee: ProtobufBuilder::amount_to_proto(plan.fee_estimate)?,
             weight: signed_tx.weight() as u64,
             ..Proto::SigningOutput::default()
         })
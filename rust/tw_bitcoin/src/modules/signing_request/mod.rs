@@ -5,15 +5,22 @@
 use crate::modules::tx_builder::output_protobuf::OutputProtobuf;
 use crate::modules::tx_builder::public_keys::PublicKeys;
 use crate::modules::tx_builder::utxo_protobuf::UtxoProtobuf;
-use crate::modules::tx_builder::BitcoinChainInfo;
+use crate::modules::tx_builder::{
+    chain_params_from_coin, chain_tag_from_coin, chain_tag_from_proto, BitcoinChainInfo,
+};
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::error::prelude::*;
 use tw_proto::BitcoinV2::Proto;
+use tw_utxo::chain_params::ChainTag;
 use tw_utxo::dust::DustPolicy;
+use tw_utxo::fee_policy::FeePolicy;
 use tw_utxo::modules::tx_planner::{PlanRequest, RequestType};
-use tw_utxo::modules::utxo_selector::InputSelector;
-use tw_utxo::transaction::standard_transaction::builder::TransactionBuilder;
+use tw_utxo::modules::utxo_selector::{InputSelector, SelectionPolicy};
+use tw_utxo::script::Script;
+use tw_utxo::spec_location::SpecLocation;
+use tw_utxo::transaction::standard_transaction::builder::{OutputBuilder, TransactionBuilder};
 use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_parts::Amount;
 use Proto::mod_SigningInput::OneOfdust_policy as ProtoDustPolicy;
 
 const DEFAULT_TX_VERSION: u32 = 1;
@@ -28,23 +35,47 @@ impl SigningRequestBuilder {
         input: &Proto::SigningInput,
     ) -> SigningResult<StandardSigningRequest> {
         let chain_info = Self::chain_info(coin, &input.chain_info)?;
-        let dust_policy = Self::dust_policy(&input.dust_policy)?;
-        let fee_per_vbyte = input.fee_per_vb;
+        let chain = Self::chain(&chain_info, input.chain)?;
+        let fee_per_vbyte = Amount::try_from(input.fee_per_vb)
+            .tw_err(|_| SigningErrorType::Error_wrong_fee)
+            .context("'SigningInput.fee_per_vb' cannot be negative")?;
+        let fee_policy = Self::fee_policy(&chain_info, &input.dust_policy, fee_per_vbyte)?;
+        let output_shuffle_seed = Self::output_shuffle_seed(&input.output_shuffle_seed)?;
+        let selection_policy = Self::selection_policy(input);
         let version = Self::transaction_version(&input.version);
+        let explicit_fee_output = chain_info
+            .explicit_fee_output
+            .clone()
+            .map(|fee_script_pubkey| OutputBuilder::new(0).custom_script_pubkey(fee_script_pubkey))
+            .transpose()
+            .context("Invalid explicit fee output")?;
 
         let public_keys = Self::get_public_keys(input)?;
 
         let mut builder = TransactionBuilder::default();
-        builder.version(version).lock_time(input.lock_time);
+        builder
+            .version(version)
+            .lock_time(input.lock_time)
+            .max_money(chain_info.max_money)
+            .chain(chain)
+            .segwit_enabled(chain_info.segwit_enabled)
+            .allowed_sighash_types(chain_info.allowed_sighash_types.clone());
 
         // Parse all UTXOs.
-        for utxo_proto in input.inputs.iter() {
+        for (input_index, utxo_proto) in input.inputs.iter().enumerate() {
             let utxo_builder = UtxoProtobuf::new(&chain_info, utxo_proto, &public_keys);
 
             let (utxo, utxo_args) = utxo_builder
                 .utxo_from_proto()
-                .context("Error creating UTXO from Protobuf")?;
-            builder.push_input(utxo, utxo_args);
+                .with_context(|| format!("Error creating UTXO #{input_index} from Protobuf"))
+                .with_context(|| Self::input_location(input_index, utxo_proto))?;
+
+            if utxo_proto.script_pubkey.is_empty() {
+                builder.push_input(utxo, utxo_args);
+            } else {
+                let utxo_script_pubkey = Script::from(utxo_proto.script_pubkey.to_vec());
+                builder.push_input_with_script_pubkey(utxo, utxo_args, utxo_script_pubkey);
+            }
         }
 
         // If `max_amount_output` is set, construct a transaction with only one output.
@@ -53,22 +84,32 @@ impl SigningRequestBuilder {
 
             let max_output = output_builder
                 .output_from_proto()
-                .context("Error creating Max Output from Protobuf")?;
+                .context("Error creating Max Output from Protobuf")
+                .with_context(|| {
+                    Self::output_location(SpecLocation::max_output(), max_output_proto)
+                })?;
             builder.push_output(max_output);
 
             let unsigned_tx = builder.build()?;
             return Ok(StandardSigningRequest {
                 ty: RequestType::SendMax { unsigned_tx },
-                dust_policy,
+                fee_policy,
                 fee_per_vbyte,
+                explicit_fee_output,
+                selection_policy,
+                output_shuffle_seed,
+                unconfirmed_parents: Vec::new(),
             });
         }
 
         // `max_amount_output` isn't set, parse all Outputs.
-        for output_proto in input.outputs.iter() {
+        for (output_index, output_proto) in input.outputs.iter().enumerate() {
             let output = OutputProtobuf::new(&chain_info, output_proto)
                 .output_from_proto()
-                .context("Error creating Output from Proto")?;
+                .with_context(|| format!("Error creating Output #{output_index} from Proto"))
+                .with_context(|| {
+                    Self::output_location(SpecLocation::output(output_index), output_proto)
+                })?;
             builder.push_output(output);
         }
 
@@ -80,6 +121,9 @@ impl SigningRequestBuilder {
                 OutputProtobuf::new(&chain_info, change_output_proto)
                     .output_from_proto()
                     .context("Error creating Change Output from Proto")
+                    .with_context(|| {
+                        Self::output_location(SpecLocation::change_output(), change_output_proto)
+                    })
             })
             .transpose()?;
 
@@ -92,11 +136,49 @@ impl SigningRequestBuilder {
                 change_output,
                 input_selector,
             },
-            dust_policy,
+            fee_policy,
             fee_per_vbyte,
+            explicit_fee_output,
+            selection_policy,
+            output_shuffle_seed,
+            unconfirmed_parents: Vec::new(),
         })
     }
 
+    /// Describes the input spec as it's provided, since the UTXO could not be built from it.
+    fn input_location(input_index: usize, input: &Proto::Input) -> SpecLocation {
+        use Proto::mod_Input::OneOfclaiming_script as ScriptType;
+
+        let mut location = SpecLocation::input(input_index);
+        if let Ok(amount) = Amount::try_from(input.value) {
+            location = location.amount(amount);
+        }
+        match input.claiming_script {
+            ScriptType::script_data(ref script) => {
+                location.script_pubkey(Script::from(script.to_vec()))
+            },
+            ScriptType::receiver_address(ref address) => location.address(address),
+            ScriptType::script_builder(_) | ScriptType::None => location,
+        }
+    }
+
+    /// Describes the output spec as it's provided, since the output could not be built from it.
+    fn output_location(location: SpecLocation, output: &Proto::Output) -> SpecLocation {
+        use Proto::mod_Output::OneOfto_recipient as RecipientType;
+
+        let mut location = location;
+        if let Ok(amount) = Amount::try_from(output.value) {
+            location = location.amount(amount);
+        }
+        match output.to_recipient {
+            RecipientType::custom_script_pubkey(ref script) => {
+                location.script_pubkey(Script::from(script.to_vec()))
+            },
+            RecipientType::to_address(ref address) => location.address(address),
+            RecipientType::builder(_) | RecipientType::None => location,
+        }
+    }
+
     fn get_public_keys(input: &Proto::SigningInput) -> SigningResult<PublicKeys> {
         let mut public_keys = PublicKeys::default();
 
@@ -122,19 +204,74 @@ impl SigningRequestBuilder {
         }
     }
 
+    fn selection_policy(input: &Proto::SigningInput) -> SelectionPolicy {
+        match input.selection_policy {
+            Proto::SelectionPolicy::AnyUtxo => SelectionPolicy::Any,
+            Proto::SelectionPolicy::OnlyConfirmed => SelectionPolicy::OnlyConfirmed,
+            Proto::SelectionPolicy::MinConfirmations => {
+                SelectionPolicy::MinConfirmations(input.min_confirmations)
+            },
+            Proto::SelectionPolicy::AllowOwnUnconfirmedChange => {
+                SelectionPolicy::AllowOwnUnconfirmedChange
+            },
+        }
+    }
+
     fn dust_policy(proto: &ProtoDustPolicy) -> SigningResult<DustPolicy> {
         match proto {
-            ProtoDustPolicy::fixed_dust_threshold(fixed) => Ok(DustPolicy::FixedAmount(*fixed)),
+            ProtoDustPolicy::fixed_dust_threshold(fixed) => Amount::try_from(*fixed)
+                .map(DustPolicy::FixedAmount)
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Dust threshold cannot be negative"),
             ProtoDustPolicy::None => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("No dust policy provided"),
         }
     }
 
+    /// Returns the fee policy of the chain with the dust policy provided by the caller.
+    /// A zero fee rate disables the fee calculation, so neither the minimum relay fee rate is required
+    /// nor the fee is checked then, as it is the surplus of the inputs chosen by the caller.
+    fn fee_policy(
+        chain_info: &BitcoinChainInfo,
+        dust_policy: &ProtoDustPolicy,
+        fee_per_vbyte: Amount,
+    ) -> SigningResult<FeePolicy> {
+        let dust_policy = Self::dust_policy(dust_policy)?;
+        if fee_per_vbyte == 0 {
+            return Ok(FeePolicy::unbounded(dust_policy));
+        }
+        Ok(chain_info.fee_policy.with_dust_policy(dust_policy))
+    }
+
+    fn output_shuffle_seed(seed: &[u8]) -> SigningResult<Option<[u8; 32]>> {
+        if seed.is_empty() {
+            return Ok(None);
+        }
+        <[u8; 32]>::try_from(seed)
+            .map(Some)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("'SigningInput.output_shuffle_seed' must be 32 bytes long")
+    }
+
     fn transaction_version(proto: &Proto::TransactionVersion) -> u32 {
         match proto {
             Proto::TransactionVersion::UseDefault => DEFAULT_TX_VERSION,
             Proto::TransactionVersion::V1 => 1,
             Proto::TransactionVersion::V2 => 2,
+            Proto::TransactionVersion::V3 => 3,
+        }
+    }
+
+    /// Returns the chain of the coin. If `SigningInput.chain` is set explicitly, it must be the same.
+    fn chain(chain_info: &BitcoinChainInfo, chain: Proto::ChainTag) -> SigningResult<ChainTag> {
+        match chain_tag_from_proto(chain) {
+            Some(explicit) if explicit != chain_info.chain => {
+                SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "'SigningInput.chain' is {explicit}, but the coin belongs to {}",
+                    chain_info.chain
+                ))
+            },
+            _ => Ok(chain_info.chain),
         }
     }
 
@@ -149,10 +286,28 @@ impl SigningRequestBuilder {
                 .with_context(|| format!("Invalid {prefix_name} prefix. It must fit uint8"))
         }
 
+        let chain_params = chain_params_from_coin(coin);
+
         if let Some(info) = chain_info {
+            let max_money = match info.max_money {
+                0 => chain_params.max_money,
+                max_money => max_money,
+            };
+            let explicit_fee_output = if info.explicit_fee_output.is_empty() {
+                None
+            } else {
+                Some(Script::from(info.explicit_fee_output.to_vec()))
+            };
             return Ok(BitcoinChainInfo {
                 p2pkh_prefix: prefix_to_u8(info.p2pkh_prefix, "p2pkh")?,
                 p2sh_prefix: prefix_to_u8(info.p2sh_prefix, "p2sh")?,
+                legacy_p2sh_prefixes: Vec::new(),
+                max_money,
+                fee_policy: chain_params.fee_policy,
+                explicit_fee_output,
+                allowed_sighash_types: chain_params.allowed_sighash_types.clone(),
+                segwit_enabled: chain_params.segwit_enabled,
+                chain: chain_tag_from_coin(coin),
             });
         }
 
@@ -161,6 +316,13 @@ impl SigningRequestBuilder {
             (Some(p2pkh_prefix), Some(p2sh_prefix)) => Ok(BitcoinChainInfo {
                 p2pkh_prefix,
                 p2sh_prefix,
+                legacy_p2sh_prefixes: coin.legacy_p2sh_prefixes().to_vec(),
+                max_money: chain_params.max_money,
+                fee_policy: chain_params.fee_policy,
+                explicit_fee_output: None,
+                allowed_sighash_types: chain_params.allowed_sighash_types.clone(),
+                segwit_enabled: chain_params.segwit_enabled,
+                chain: chain_tag_from_coin(coin),
             }),
             _ => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("Neither 'SigningInput.chain_info' nor p2pkh/p2sh prefixes specified in the registry.json")
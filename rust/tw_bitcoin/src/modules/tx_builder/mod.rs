@@ -6,7 +6,74 @@ pub mod output_protobuf;
 pub mod public_keys;
 pub mod utxo_protobuf;
 
+use tw_coin_entry::coin_context::CoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::chain_params::{ChainParams, ChainTag};
+use tw_utxo::fee_policy::FeePolicy;
+use tw_utxo::script::Script;
+use tw_utxo::sighash::AllowedSighashTypes;
+use tw_utxo::transaction::transaction_parts::Amount;
+
 pub struct BitcoinChainInfo {
     pub p2pkh_prefix: u8,
     pub p2sh_prefix: u8,
+    /// Deprecated p2sh prefixes that are still accepted in addresses.
+    pub legacy_p2sh_prefixes: Vec<u8>,
+    /// Max amount that any UTXO, output or their sum can have.
+    pub max_money: Amount,
+    /// Dust and fee policy of the chain, the dust policy is overridden by `SigningInput.dust_policy`.
+    pub fee_policy: FeePolicy,
+    /// The scriptPubkey the fee must be sent to, if the chain requires an explicit fee output.
+    pub explicit_fee_output: Option<Script>,
+    /// The sighash types the chain accepts, the UTXOs signed with another type are rejected.
+    pub allowed_sighash_types: AllowedSighashTypes,
+    /// Whether the chain has activated segwit, see [`ChainParams::segwit_enabled`].
+    pub segwit_enabled: bool,
+    /// The chain of the coin, the UTXOs are built for it unless specified explicitly.
+    pub chain: ChainTag,
+}
+
+impl BitcoinChainInfo {
+    /// Whether the given prefix is either the canonical or a deprecated p2sh prefix.
+    pub fn is_p2sh_prefix(&self, prefix: u8) -> bool {
+        prefix == self.p2sh_prefix || self.legacy_p2sh_prefixes.contains(&prefix)
+    }
+}
+
+/// Returns the parameters of the chain the coin belongs to.
+/// The coins without a preset share the Bitcoin mainnet parameters, except for the fee policy:
+/// their fee rate and fee are not bounded, as the Bitcoin bounds do not fit the units of other chains.
+pub fn chain_params_from_coin(coin: &dyn CoinContext) -> ChainParams {
+    match coin.id() {
+        "bitcoin" => ChainParams::bitcoin_mainnet(),
+        "litecoin" => ChainParams::litecoin_mainnet(),
+        "doge" => ChainParams::dogecoin_mainnet(),
+        "zcash" => ChainParams::zcash_mainnet(),
+        _ => {
+            let bitcoin = ChainParams::bitcoin_mainnet();
+            ChainParams {
+                fee_policy: FeePolicy::unbounded(bitcoin.fee_policy.dust_policy),
+                ..bitcoin
+            }
+        },
+    }
+}
+
+/// Returns the chain the coin belongs to.
+pub fn chain_tag_from_coin(coin: &dyn CoinContext) -> ChainTag {
+    match coin.id() {
+        "bitcoincash" => ChainTag::BitcoinCash,
+        "litecoin" => ChainTag::Litecoin,
+        _ => ChainTag::Bitcoin,
+    }
+}
+
+/// Returns `None` if the chain is not specified explicitly.
+pub fn chain_tag_from_proto(proto: Proto::ChainTag) -> Option<ChainTag> {
+    match proto {
+        Proto::ChainTag::UseDefault => None,
+        Proto::ChainTag::Bitcoin => Some(ChainTag::Bitcoin),
+        Proto::ChainTag::BitcoinCash => Some(ChainTag::BitcoinCash),
+        Proto::ChainTag::Litecoin => Some(ChainTag::Litecoin),
+    }
 }
@@ -18,6 +18,7 @@ use tw_utxo::address::taproot::TaprootAddress;
 use tw_utxo::script::Script;
 use tw_utxo::transaction::standard_transaction::builder::OutputBuilder;
 use tw_utxo::transaction::standard_transaction::TransactionOutput;
+use tw_utxo::transaction::transaction_parts::Amount;
 
 pub struct OutputProtobuf<'a> {
     chain_info: &'a BitcoinChainInfo,
@@ -149,7 +150,7 @@ impl<'a> OutputProtobuf<'a> {
 
     pub fn custom_script(&self, script_data: Data) -> SigningResult<TransactionOutput> {
         let script = Script::from(script_data);
-        Ok(self.prepare_builder()?.custom_script_pubkey(script))
+        self.prepare_builder()?.custom_script_pubkey(script)
     }
 
     pub fn recipient_address(&self, addr: &str) -> SigningResult<TransactionOutput> {
@@ -177,7 +178,7 @@ impl<'a> OutputProtobuf<'a> {
 
         if addr.prefix() == p2pkh_prefix {
             Ok(self.prepare_builder()?.p2pkh_from_hash(&addr.payload()))
-        } else if addr.prefix() == p2sh_prefix {
+        } else if self.chain_info.is_p2sh_prefix(addr.prefix()) {
             Ok(self.prepare_builder()?.p2sh_from_hash(&addr.payload()))
         } else {
             // Unknown
@@ -239,11 +240,10 @@ impl<'a> OutputProtobuf<'a> {
     }
 
     pub fn prepare_builder(&self) -> SigningResult<OutputBuilder> {
-        if self.output.value < 0 {
-            return SigningError::err(SigningErrorType::Error_invalid_params)
-                .context("Transaction Output amount cannot be negative");
-        }
-        Ok(OutputBuilder::new(self.output.value))
+        let value = Amount::try_from(self.output.value)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Transaction Output amount cannot be negative")?;
+        Ok(OutputBuilder::new(value))
     }
 
     /// Tries to convert [`Proto::PublicKeyOrHash`] to [`Hash<N>`].
@@ -3,7 +3,7 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::modules::tx_builder::public_keys::PublicKeys;
-use crate::modules::tx_builder::BitcoinChainInfo;
+use crate::modules::tx_builder::{chain_tag_from_proto, BitcoinChainInfo};
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::*;
 use tw_hash::{H160, H256};
@@ -20,7 +20,7 @@ use tw_utxo::script::Script;
 use tw_utxo::sighash::SighashType;
 use tw_utxo::transaction::standard_transaction::builder::UtxoBuilder;
 use tw_utxo::transaction::standard_transaction::TransactionInput;
-use tw_utxo::transaction::transaction_parts::OutPoint;
+use tw_utxo::transaction::transaction_parts::{Amount, OutPoint};
 use tw_utxo::transaction::UtxoToSign;
 
 pub struct UtxoProtobuf<'a> {
@@ -194,7 +194,7 @@ impl<'a> UtxoProtobuf<'a> {
             // P2PKH
             let pubkey = self.get_ecdsa_pubkey_from_hash(&addr.payload())?;
             self.prepare_builder()?.p2pkh(&pubkey)
-        } else if p2sh_prefix == addr.prefix() {
+        } else if self.chain_info.is_p2sh_prefix(addr.prefix()) {
             // P2SH
             SigningError::err(SigningErrorType::Error_script_redeem).context(
                 "pay-to-script-hash can only be used via 'Input.InputBuilder.p2sh'.\
@@ -252,10 +252,9 @@ impl<'a> UtxoProtobuf<'a> {
         let OutPoint { hash, index } = parse_out_point(&self.input.out_point)?;
         let sighash_ty = SighashType::from_u32(self.input.sighash_type)?;
 
-        if self.input.value < 0 {
-            return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
-                .context("UTXO amount cannot be negative");
-        }
+        let amount = Amount::try_from(self.input.value)
+            .tw_err(|_| SigningErrorType::Error_invalid_utxo_amount)
+            .context("UTXO amount cannot be negative")?;
 
         let sequence = self
             .input
@@ -265,12 +264,27 @@ impl<'a> UtxoProtobuf<'a> {
             // Use the default 0xFFFFFFFF sequence value if not specified.
             .unwrap_or(u32::MAX);
 
-        Ok(UtxoBuilder::default()
+        let mut builder = UtxoBuilder::default()
             .prev_txid(hash)
             .prev_index(index)
             .sequence(sequence)
-            .amount(self.input.value)
-            .sighash_type(sighash_ty))
+            .amount(amount)
+            .chain(chain_tag_from_proto(self.input.chain).unwrap_or(self.chain_info.chain));
+        // `SIGHASH_ALL` is the normal sighash type of `TWBitcoinSigHashType` for any input,
+        // so let the builder choose the default type then, i.e `SIGHASH_DEFAULT` for Taproot inputs.
+        if sighash_ty != SighashType::default() {
+            builder = builder.sighash_type(sighash_ty);
+        }
+        if self.input.allow_zero_amount {
+            builder = builder.allow_zero_amount();
+        }
+        if let Some(ref confirmations) = self.input.confirmations {
+            builder = builder.confirmations(confirmations.confirmations);
+        }
+        if self.input.is_change_from_self {
+            builder = builder.change_from_self();
+        }
+        Ok(builder)
     }
 
     fn get_ecdsa_pubkey_from_hash(
@@ -8,6 +8,9 @@ use tw_keypair::tw::PublicKeyType;
 
 /// Extend the trait with methods required for blockchain additions.
 pub trait CoinContext {
+    /// Coin identifier as in `registry.json`, e.g. "bitcoin" or "doge".
+    fn id(&self) -> &str;
+
     /// Necessary chain property.
     fn public_key_type(&self) -> PublicKeyType;
 
@@ -23,6 +26,12 @@ pub trait CoinContext {
     /// Optional p2sh prefix (Bitcoin specific).
     fn p2sh_prefix(&self) -> Option<u8>;
 
+    /// Deprecated p2sh prefixes that are still accepted, but never used to generate addresses (Bitcoin specific).
+    fn legacy_p2sh_prefixes(&self) -> &[u8];
+
+    /// Deprecated HRPs that are still accepted, but never used to generate addresses.
+    fn legacy_hrps(&self) -> &[String];
+
     /// Returns coin derivations.
     fn derivations(&self) -> &[DerivationWithPath];
 }
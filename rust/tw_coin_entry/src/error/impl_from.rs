@@ -41,6 +41,15 @@ impl From<KeyPairError> for SigningError {
             KeyPairError::InvalidSecretKey => {
                 TWError::new(SigningErrorType::Error_invalid_private_key)
             },
+            KeyPairError::InvalidPublicKeyPrefix
+            | KeyPairError::HybridPublicKey
+            | KeyPairError::PublicKeyNotOnCurve
+            | KeyPairError::PublicKeyAtInfinity
+            | KeyPairError::SmallOrderPublicKey
+            | KeyPairError::NonCanonicalPublicKey
+            | KeyPairError::UnsupportedCurve => {
+                TWError::new(SigningErrorType::Error_invalid_params).context(err)
+            },
             KeyPairError::InvalidPublicKey
             | KeyPairError::InvalidSignature
             | KeyPairError::InvalidSignMessage
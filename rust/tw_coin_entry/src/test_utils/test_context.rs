@@ -10,11 +10,14 @@ use tw_keypair::tw::PublicKeyType;
 /// Test coin context that panics on any `CoinContext` method call.
 #[derive(Default)]
 pub struct TestCoinContext {
+    pub id: String,
     pub public_key_type: Option<PublicKeyType>,
     pub address_hasher: Option<Hasher>,
     pub hrp: Option<String>,
     pub p2pkh: Option<u8>,
     pub p2sh: Option<u8>,
+    pub legacy_p2sh: Vec<u8>,
+    pub legacy_hrps: Vec<String>,
 }
 
 impl TestCoinContext {
@@ -30,6 +33,10 @@ impl TestCoinContext {
 }
 
 impl CoinContext for TestCoinContext {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
     fn public_key_type(&self) -> PublicKeyType {
         self.public_key_type
             .expect("EmptyCoinContext::public_key_type was not set")
@@ -51,6 +58,14 @@ impl CoinContext for TestCoinContext {
         self.p2sh
     }
 
+    fn legacy_p2sh_prefixes(&self) -> &[u8] {
+        &self.legacy_p2sh
+    }
+
+    fn legacy_hrps(&self) -> &[String] {
+        &self.legacy_hrps
+    }
+
     fn derivations(&self) -> &[DerivationWithPath] {
         unimplemented!()
     }
@@ -20,6 +20,11 @@ impl CoinRegistryContext {
 }
 
 impl CoinContext for CoinRegistryContext {
+    #[inline]
+    fn id(&self) -> &str {
+        &self.item.id
+    }
+
     #[inline]
     fn public_key_type(&self) -> PublicKeyType {
         self.item.public_key_type
@@ -45,6 +50,16 @@ impl CoinContext for CoinRegistryContext {
         self.item.p2sh_prefix
     }
 
+    #[inline]
+    fn legacy_p2sh_prefixes(&self) -> &[u8] {
+        &self.item.legacy_p2sh_prefixes
+    }
+
+    #[inline]
+    fn legacy_hrps(&self) -> &[String] {
+        &self.item.legacy_hrps
+    }
+
     #[inline]
     fn derivations(&self) -> &[DerivationWithPath] {
         &self.item.derivation
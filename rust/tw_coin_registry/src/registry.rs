@@ -36,6 +36,10 @@ pub struct CoinItem {
     pub hrp: Option<String>,
     pub p2pkh_prefix: Option<u8>,
     pub p2sh_prefix: Option<u8>,
+    #[serde(default)]
+    pub legacy_p2sh_prefixes: Vec<u8>,
+    #[serde(default)]
+    pub legacy_hrps: Vec<String>,
 }
 
 #[inline]
@@ -11,6 +11,7 @@ use ecdsa::PrimeCurve;
 mod canonical;
 pub mod der;
 pub mod nist256p1;
+pub(crate) mod sec1;
 pub mod secp256k1;
 pub mod signature;
 
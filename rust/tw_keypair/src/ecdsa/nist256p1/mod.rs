@@ -19,6 +19,7 @@ pub type VerifySignature = crate::ecdsa::signature::VerifySignature<NistP256>;
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use crate::KeyPairError;
     use tw_encoding::hex;
     use tw_hash::sha3::keccak256;
     use tw_hash::{H256, H264, H520};
@@ -91,6 +92,41 @@ mod tests {
         assert_eq!(public.uncompressed(), expected_uncompressed);
     }
 
+    #[test]
+    fn test_public_key_from_invalid() {
+        let invalid = |hex: &str| PublicKey::try_from(hex).err().unwrap();
+
+        // `x = 1` is not on the curve.
+        assert_eq!(
+            invalid("020000000000000000000000000000000000000000000000000000000000000001"),
+            KeyPairError::PublicKeyNotOnCurve
+        );
+        // `y` doesn't match `x`.
+        assert_eq!(
+            invalid("046d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab918b4fe46ccbf56701fb210d67d91c5779468f6b3fdc7a63692b9b62543f47af"),
+            KeyPairError::PublicKeyNotOnCurve
+        );
+        // Hybrid encoding of a valid key.
+        assert_eq!(
+            invalid("066d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab918b4fe46ccbf56701fb210d67d91c5779468f6b3fdc7a63692b9b62543f47ae"),
+            KeyPairError::HybridPublicKey
+        );
+        assert_eq!(
+            invalid("ff6d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab"),
+            KeyPairError::InvalidPublicKeyPrefix
+        );
+        assert_eq!(
+            invalid("0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"),
+            KeyPairError::PublicKeyAtInfinity
+        );
+
+        let valid = PublicKey::try_from(
+            "026d786ab8fda678cf50f71d13641049a393b325063b8c0d4e5070de48a2caf9ab",
+        )
+        .unwrap();
+        assert!(valid.is_valid());
+    }
+
     #[test]
     fn test_public_key_recover() {
         let sign_bytes = H520::from("8859e63a0c0cc2fc7f788d7e78406157b288faa6f76f76d37c4cd1534e8d83c468f9fd6ca7dde378df594625dcde98559389569e039282275e3d87c26e36447401");
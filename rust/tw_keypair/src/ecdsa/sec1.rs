@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::{KeyPairError, KeyPairResult};
+use tw_hash::{H264, H520};
+
+const TAG_INFINITY: u8 = 0x00;
+const TAG_COMPRESSED_EVEN_Y: u8 = 0x02;
+const TAG_COMPRESSED_ODD_Y: u8 = 0x03;
+const TAG_UNCOMPRESSED: u8 = 0x04;
+const TAG_HYBRID_EVEN_Y: u8 = 0x06;
+const TAG_HYBRID_ODD_Y: u8 = 0x07;
+
+/// Checks the SEC1 prefix and the length of a public key before it is decoded,
+/// so that a decoding error can only mean the point is not on the curve.
+pub(crate) fn check_public_key_encoding(data: &[u8]) -> KeyPairResult<()> {
+    let Some(tag) = data.first() else {
+        return Err(KeyPairError::InvalidPublicKey);
+    };
+
+    let expected_len = match *tag {
+        TAG_COMPRESSED_EVEN_Y | TAG_COMPRESSED_ODD_Y => H264::len(),
+        TAG_UNCOMPRESSED => H520::len(),
+        // Also covers all-zero keys.
+        TAG_INFINITY => return Err(KeyPairError::PublicKeyAtInfinity),
+        TAG_HYBRID_EVEN_Y | TAG_HYBRID_ODD_Y => return Err(KeyPairError::HybridPublicKey),
+        _ => return Err(KeyPairError::InvalidPublicKeyPrefix),
+    };
+
+    if data.len() != expected_len {
+        return Err(KeyPairError::InvalidPublicKey);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_public_key_encoding() {
+        let key = |tag: u8, len: usize| {
+            let mut key = vec![0x11; len];
+            key[0] = tag;
+            check_public_key_encoding(&key)
+        };
+
+        assert_eq!(key(0x02, 33), Ok(()));
+        assert_eq!(key(0x03, 33), Ok(()));
+        assert_eq!(key(0x04, 65), Ok(()));
+
+        assert_eq!(key(0x02, 65), Err(KeyPairError::InvalidPublicKey));
+        assert_eq!(key(0x04, 33), Err(KeyPairError::InvalidPublicKey));
+        assert_eq!(
+            check_public_key_encoding(&[]),
+            Err(KeyPairError::InvalidPublicKey)
+        );
+
+        assert_eq!(key(0x00, 1), Err(KeyPairError::PublicKeyAtInfinity));
+        assert_eq!(
+            check_public_key_encoding(&[0; 33]),
+            Err(KeyPairError::PublicKeyAtInfinity)
+        );
+        assert_eq!(key(0x06, 65), Err(KeyPairError::HybridPublicKey));
+        assert_eq!(key(0x07, 65), Err(KeyPairError::HybridPublicKey));
+        assert_eq!(key(0x05, 33), Err(KeyPairError::InvalidPublicKeyPrefix));
+        assert_eq!(key(0xff, 65), Err(KeyPairError::InvalidPublicKeyPrefix));
+    }
+}
@@ -19,6 +19,7 @@ pub type VerifySignature = crate::ecdsa::signature::VerifySignature<Secp256k1>;
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use crate::KeyPairError;
     use tw_encoding::hex;
     use tw_encoding::hex::ToHex;
     use tw_hash::sha2::sha256;
@@ -129,6 +130,52 @@ mod tests {
         assert_eq!(public.uncompressed(), expected_uncompressed);
     }
 
+    #[test]
+    fn test_public_key_from_invalid() {
+        let invalid = |hex: &str| PublicKey::try_from(hex).err().unwrap();
+
+        // `x = 5` is not on the curve.
+        assert_eq!(
+            invalid("020000000000000000000000000000000000000000000000000000000000000005"),
+            KeyPairError::PublicKeyNotOnCurve
+        );
+        // `x` is greater than the field size.
+        assert_eq!(
+            invalid("03ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+            KeyPairError::PublicKeyNotOnCurve
+        );
+        // `y` doesn't match `x`.
+        assert_eq!(
+            invalid("0499c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde92"),
+            KeyPairError::PublicKeyNotOnCurve
+        );
+        // Hybrid encoding of a valid key.
+        assert_eq!(
+            invalid("0799c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde91"),
+            KeyPairError::HybridPublicKey
+        );
+        assert_eq!(
+            invalid("0599c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1"),
+            KeyPairError::InvalidPublicKeyPrefix
+        );
+        assert_eq!(
+            invalid("000000000000000000000000000000000000000000000000000000000000000000"),
+            KeyPairError::PublicKeyAtInfinity
+        );
+        assert_eq!(invalid("00"), KeyPairError::PublicKeyAtInfinity);
+        // Compressed prefix with the uncompressed length.
+        assert_eq!(
+            invalid("0299c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde91"),
+            KeyPairError::InvalidPublicKey
+        );
+
+        let valid = PublicKey::try_from(
+            "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1",
+        )
+        .unwrap();
+        assert!(valid.is_valid());
+    }
+
     #[test]
     fn test_verify_invalid() {
         let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
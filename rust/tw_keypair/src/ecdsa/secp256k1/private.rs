@@ -38,6 +38,29 @@ impl PrivateKey {
         let shared_secret_hash = tw_hash::sha2::sha256(shared_secret_compressed.as_bytes());
         H256::try_from(shared_secret_hash.as_slice()).expect("Expected 32 byte array sha256 hash")
     }
+
+    /// Computes an EC Diffie-Hellman shared secret in constant time, i.e. the `x` coordinate
+    /// of the public point multiplied by the secret scalar.
+    /// Unlike [`PrivateKey::shared_key_hash`], the secret is not hashed,
+    /// as required by the payment code protocols like BIP-47.
+    pub fn shared_secret(&self, pubkey: &PublicKey) -> KeyPairResult<H256> {
+        let shared_point = diffie_hellman(&self.secret, &pubkey.public);
+
+        // The secret scalar is non-zero and the curve order is prime,
+        // so the point at infinity is not expected unless the public key is invalid.
+        let compress = true;
+        let shared_point = shared_point.to_encoded_point(compress);
+        let x = shared_point.x().ok_or(KeyPairError::PublicKeyAtInfinity)?;
+        H256::try_from(x.as_slice()).map_err(|_| KeyPairError::InternalError)
+    }
+
+    /// Computes an EC Diffie-Hellman shared secret hashed with SHA-256.
+    /// See [`PrivateKey::shared_secret`].
+    pub fn shared_secret_sha256(&self, pubkey: &PublicKey) -> KeyPairResult<H256> {
+        let shared_secret = self.shared_secret(pubkey)?;
+        let hash = tw_hash::sha2::sha256(shared_secret.as_slice());
+        Ok(H256::try_from(hash.as_slice()).expect("Expected 32 byte array sha256 hash"))
+    }
 }
 
 /// This method is inspired by [elliptic_curve::ecdh::diffie_hellman](https://github.com/RustCrypto/traits/blob/f0dbe44fea56d4c17e625ababacb580fec842137/elliptic-curve/src/ecdh.rs#L60-L70)
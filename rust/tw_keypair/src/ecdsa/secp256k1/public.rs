@@ -2,6 +2,7 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::ecdsa::sec1::check_public_key_encoding;
 use crate::ecdsa::secp256k1::{Signature, VerifySignature};
 use crate::traits::VerifyingKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
@@ -37,6 +38,11 @@ impl PublicKey {
         PublicKey { public }
     }
 
+    /// Re-checks the public key as if it was constructed from its uncompressed representation.
+    pub fn is_valid(&self) -> bool {
+        PublicKey::try_from(self.uncompressed().as_slice()).is_ok()
+    }
+
     /// Returns the raw data of the compressed public key (33 bytes).
     pub fn compressed(&self) -> H264 {
         let compressed = true;
@@ -104,10 +110,12 @@ impl<'a> TryFrom<&'a [u8]> for PublicKey {
     type Error = KeyPairError;
 
     /// Expected either `H264` or `H520` slice.
+    /// Hybrid-encoded keys, the point at infinity and points that are not on the curve are rejected.
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        check_public_key_encoding(data)?;
         Ok(PublicKey {
             public: VerifyingKey::from_sec1_bytes(data)
-                .map_err(|_| KeyPairError::InvalidPublicKey)?,
+                .map_err(|_| KeyPairError::PublicKeyNotOnCurve)?,
         })
     }
 }
@@ -45,6 +45,7 @@ impl<T> Hasher512 for T where T: Digest<OutputSize = U64> {}
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use crate::KeyPairError;
     use tw_encoding::hex;
     use tw_hash::sha2::sha256;
     use tw_hash::sha3::keccak256;
@@ -247,6 +248,39 @@ mod tests {
         let _ = sha512::PublicKey::try_from(&invalid[..]).unwrap_err();
     }
 
+    #[test]
+    fn test_public_key_from_invalid() {
+        let invalid = |hex: &str| sha512::PublicKey::try_from(hex).unwrap_err();
+
+        // `y = 2` is not on the curve.
+        let off_curve = "0200000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(invalid(off_curve), KeyPairError::PublicKeyNotOnCurve);
+        // `y = p` is reduced to `y = 0`.
+        let non_canonical_y = "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+        assert_eq!(
+            invalid(non_canonical_y),
+            KeyPairError::NonCanonicalPublicKey
+        );
+        // The identity with the sign bit of `x = 0` set.
+        let negative_zero = "0100000000000000000000000000000000000000000000000000000000000080";
+        assert_eq!(invalid(negative_zero), KeyPairError::NonCanonicalPublicKey);
+        let identity = "0100000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(invalid(identity), KeyPairError::PublicKeyAtInfinity);
+        // `y = 0` is a point of order 4.
+        let all_zero = "0000000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(invalid(all_zero), KeyPairError::SmallOrderPublicKey);
+
+        assert!(!sha512::PublicKey::is_on_curve(&H256::from(off_curve)));
+        assert!(sha512::PublicKey::is_on_curve(&H256::from(non_canonical_y)));
+        assert!(sha512::PublicKey::is_on_curve(&H256::from(all_zero)));
+
+        let valid = sha512::PublicKey::try_from(
+            "4870d56d074c50e891506d78faa4fb69ca039cc5f131eb491e166b975880e867",
+        )
+        .unwrap();
+        assert!(valid.is_valid());
+    }
+
     #[test]
     fn test_debug() {
         let secret = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
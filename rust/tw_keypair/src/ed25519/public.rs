@@ -11,6 +11,7 @@ use crate::KeyPairError;
 use curve25519_dalek::constants;
 use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
 use std::fmt;
 use std::marker::PhantomData;
 use tw_encoding::hex;
@@ -66,6 +67,17 @@ impl<H: Hasher512> PublicKey<H> {
         }
     }
 
+    /// Checks if the given bytes decode to a point on the curve,
+    /// even if the encoding is not canonical or the point has a small order.
+    pub fn is_on_curve(bytes: &H256) -> bool {
+        CompressedEdwardsY(bytes.take()).decompress().is_some()
+    }
+
+    /// Re-checks the public key as if it was constructed from its bytes.
+    pub fn is_valid(&self) -> bool {
+        PublicKey::<H>::try_from(self.as_slice()).is_ok()
+    }
+
     /// Returns the raw data of the public key (32 bytes).
     pub fn to_bytes(&self) -> H256 {
         H256::from(self.compressed.to_bytes())
@@ -142,13 +154,27 @@ impl<'a, H: Hasher512> TryFrom<&'a [u8]> for PublicKey<H> {
     type Error = KeyPairError;
 
     /// Inspired by: https://github.com/dalek-cryptography/ed25519-dalek/blob/1.0.1/src/public.rs#L92-L145
+    ///
+    /// Non-canonical encodings, the identity and other small order points are rejected.
     fn try_from(pubkey: &'a [u8]) -> Result<Self, Self::Error> {
         let pubkey = H256::try_from(pubkey).map_err(|_| KeyPairError::InvalidPublicKey)?;
 
         let compressed = CompressedEdwardsY(pubkey.take());
         let point = compressed
             .decompress()
-            .ok_or(KeyPairError::InvalidPublicKey)?;
+            .ok_or(KeyPairError::PublicKeyNotOnCurve)?;
+
+        // `decompress` reduces `y` modulo `p` and ignores the sign bit of `x = 0`,
+        // so a canonical encoding must compress back to the same bytes.
+        if point.compress() != compressed {
+            return Err(KeyPairError::NonCanonicalPublicKey);
+        }
+        if point.is_identity() {
+            return Err(KeyPairError::PublicKeyAtInfinity);
+        }
+        if point.is_small_order() {
+            return Err(KeyPairError::SmallOrderPublicKey);
+        }
 
         Ok(PublicKey {
             compressed,
@@ -42,6 +42,8 @@
 //! assert_eq(eth_signature.v, H256::from(EXPECTED_V));
 //! ```
 
+use std::fmt;
+
 pub mod ecdsa;
 pub mod ed25519;
 pub mod ffi;
@@ -61,10 +63,47 @@ pub type KeyPairResult<T> = Result<T, KeyPairError>;
 pub enum KeyPairError {
     InvalidSecretKey,
     InvalidPublicKey,
+    /// The public key starts with an unknown SEC1 prefix.
+    InvalidPublicKeyPrefix,
+    /// The public key is hybrid-encoded (`0x06` or `0x07` SEC1 prefix), which is not supported.
+    HybridPublicKey,
+    /// The public key is not a point on the curve.
+    PublicKeyNotOnCurve,
+    /// The public key is the point at infinity (the identity element).
+    PublicKeyAtInfinity,
+    /// The public key is a point of a small order (a torsion point).
+    SmallOrderPublicKey,
+    /// The public key is not encoded canonically, e.g. ed25519 `y` coordinate is not reduced modulo `p`.
+    NonCanonicalPublicKey,
     InvalidSignature,
     InvalidSignMessage,
     InvalidEncryptedMessage,
     SignatureVerifyError,
     SigningError,
+    /// The operation is not supported for the elliptic curve.
+    UnsupportedCurve,
     InternalError,
 }
+
+impl fmt::Display for KeyPairError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            KeyPairError::InvalidSecretKey => "Invalid secret key",
+            KeyPairError::InvalidPublicKey => "Invalid public key",
+            KeyPairError::InvalidPublicKeyPrefix => "Invalid public key prefix",
+            KeyPairError::HybridPublicKey => "Hybrid-encoded public keys are not supported",
+            KeyPairError::PublicKeyNotOnCurve => "Public key is not on the curve",
+            KeyPairError::PublicKeyAtInfinity => "Public key is the point at infinity",
+            KeyPairError::SmallOrderPublicKey => "Public key is a small order point",
+            KeyPairError::NonCanonicalPublicKey => "Public key is not encoded canonically",
+            KeyPairError::InvalidSignature => "Invalid signature",
+            KeyPairError::InvalidSignMessage => "Invalid message to sign",
+            KeyPairError::InvalidEncryptedMessage => "Invalid encrypted message",
+            KeyPairError::SignatureVerifyError => "Signature verification failed",
+            KeyPairError::SigningError => "Signing error",
+            KeyPairError::UnsupportedCurve => "The operation is not supported for the curve",
+            KeyPairError::InternalError => "Internal error",
+        };
+        f.write_str(msg)
+    }
+}
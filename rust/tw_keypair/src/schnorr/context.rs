@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use lazy_static::lazy_static;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref SHARED_CONTEXT: Arc<Secp256k1Context> = Arc::new(Secp256k1Context::new());
+}
+
+/// An explicit `libsecp256k1` context used to parse, tweak, sign and verify with schnorr keys.
+///
+/// The methods that don't take a context use the immutable [`Secp256k1Context::shared`] one,
+/// while the `*_with_context` methods use the context owned by the caller.
+///
+/// The context is immutable once created, so it's `Send + Sync` and can be shared between threads,
+/// e.g. wrapped into an `Arc`, to avoid creating a new one on each operation.
+pub struct Secp256k1Context {
+    pub(crate) secp: secp256k1::Secp256k1<secp256k1::All>,
+}
+
+impl Secp256k1Context {
+    /// Creates a new context randomized against side-channel attacks.
+    pub fn new() -> Secp256k1Context {
+        Secp256k1Context {
+            secp: secp256k1::Secp256k1::new(),
+        }
+    }
+
+    /// Returns the process-wide context created once on the first use.
+    /// The context is never mutated, so it can be used from any thread.
+    pub fn shared() -> &'static Arc<Secp256k1Context> {
+        &SHARED_CONTEXT
+    }
+}
+
+impl Default for Secp256k1Context {
+    fn default() -> Self {
+        Secp256k1Context::new()
+    }
+}
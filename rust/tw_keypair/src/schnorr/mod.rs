@@ -4,14 +4,16 @@
 
 use bitcoin::hashes::Hash;
 
+mod context;
 mod keypair;
 mod private;
 mod public;
 mod signature;
 
+pub use context::Secp256k1Context;
 pub use keypair::KeyPair;
 pub use private::PrivateKey;
-pub use public::{PublicKey, XOnlyPublicKey};
+pub use public::{Parity, PublicKey, TweakedPublicKey, XOnlyPublicKey};
 pub use signature::Signature;
 use tw_hash::H256;
 
@@ -29,6 +31,8 @@ pub(crate) fn bitcoin_tweak(tweak: Option<H256>) -> Option<bitcoin::taproot::Tap
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use crate::KeyPairError;
+    use tw_encoding::hex;
     use tw_hash::sha3::keccak256;
     use tw_hash::{H256, H512};
     use tw_misc::traits::ToBytesVec;
@@ -113,6 +117,42 @@ mod tests {
         assert!(public.verify(actual, hash_to_sign), "Invalid signature");
     }
 
+    #[test]
+    fn test_sign_verify_with_context() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Secp256k1Context>();
+
+        let context = Secp256k1Context::new();
+        let hash_to_sign = H256::from(MSG);
+
+        let private_key = PrivateKey::try_from_slice_with_context(
+            &context,
+            hex::decode(SECRET).unwrap().as_slice(),
+        )
+        .unwrap()
+        .tweak_with_context(&context, Some(tweak_hash()))
+        .no_aux_rand();
+        let actual = private_key
+            .sign_with_context(&context, hash_to_sign)
+            .unwrap();
+
+        // Same as signed with the shared context.
+        let expected = H512::from("69e51a936bf9327d3809930bf5966c52879327bda9d6dcc6c0fd57e5f7cfebe1e403a60215cc44eacce8742125669ac63e4b33269487100e6108a5232e642bdf");
+        assert_eq!(actual, Signature::try_from(expected.as_slice()).unwrap());
+
+        let untweaked_public = PrivateKey::try_from(SECRET).unwrap().public();
+        assert_eq!(
+            untweaked_public
+                .tweak_with_context(&context, Some(tweak_hash()))
+                .public_key(),
+            private_key.public()
+        );
+        assert!(private_key
+            .public()
+            .x_only()
+            .verify_with_context(&context, actual, hash_to_sign));
+    }
+
     #[test]
     fn test_public_key_tweak() {
         let private_key = PrivateKey::try_from(SECRET).unwrap();
@@ -121,6 +161,62 @@ mod tests {
         let tweaked_private = private_key.tweak(None);
         let tweaked_public = tweaked_private.public();
 
-        assert_eq!(public_key.tweak(None), tweaked_public);
+        assert_eq!(public_key.tweak(None).public_key(), tweaked_public);
+    }
+
+    #[test]
+    fn test_public_key_tweak_odd_parity() {
+        // BIP-341 NUMS point tweaked with a single-leaf merkle root.
+        let internal_pubkey = PublicKey::try_from(
+            hex::decode("0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0")
+                .unwrap()
+                .as_slice(),
+        )
+        .unwrap();
+        let merkle_root =
+            H256::from("613528a85aaf77444029f6c0b1ad6d736680fdb283b0bfc0c3bdbbb25aee102e");
+
+        let tweaked = internal_pubkey.tweak(Some(merkle_root));
+        assert_eq!(
+            tweaked.xonly.bytes(),
+            H256::from("2ea441cb6376e5d9c9d3f6a1903ae1ddf441d83cad59644b550bf1010e6ff993")
+        );
+        assert_eq!(tweaked.parity, Parity::Odd);
+        assert_eq!(tweaked.public_key().compressed().as_slice()[0], 0x03);
+
+        // The same output key is re-derived from the x-only internal key.
+        let rederived = internal_pubkey.x_only().tweak(Some(merkle_root));
+        assert_eq!(rederived, tweaked);
+    }
+
+    #[test]
+    fn test_public_key_from_invalid() {
+        let compressed = "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1";
+        let hybrid = "0799c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde91";
+        let off_curve = "020000000000000000000000000000000000000000000000000000000000000005";
+        let wrong_prefix = "0599c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1";
+        let public = |hex: &str| PublicKey::try_from(hex::decode(hex).unwrap().as_slice());
+        let x_only =
+            |hex: &str| XOnlyPublicKey::try_from(hex::decode(hex).unwrap().as_slice()).err();
+
+        // `libsecp256k1` would accept the hybrid encoding.
+        assert_eq!(public(hybrid), Err(KeyPairError::HybridPublicKey));
+        assert_eq!(public(off_curve), Err(KeyPairError::PublicKeyNotOnCurve));
+        assert_eq!(
+            public(wrong_prefix),
+            Err(KeyPairError::InvalidPublicKeyPrefix)
+        );
+        assert_eq!(
+            x_only(wrong_prefix),
+            Some(KeyPairError::InvalidPublicKeyPrefix)
+        );
+        assert_eq!(
+            x_only(&off_curve[2..]),
+            Some(KeyPairError::PublicKeyNotOnCurve)
+        );
+        assert_eq!(x_only(&compressed[2..]), None);
+
+        let ecdsa_public = crate::ecdsa::secp256k1::PublicKey::try_from(compressed).unwrap();
+        assert_eq!(PublicKey::from(&ecdsa_public), public(compressed).unwrap());
     }
 }
@@ -1,10 +1,12 @@
+use crate::rand::OsRng;
 use crate::schnorr::bitcoin_tweak;
+use crate::schnorr::context::Secp256k1Context;
 use crate::schnorr::public::PublicKey;
 use crate::schnorr::signature::Signature;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
 use bitcoin::key::TapTweak;
-use secp256k1::SECP256K1;
+use rand_core::RngCore;
 use tw_encoding::hex;
 use tw_hash::H256;
 use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
@@ -27,16 +29,61 @@ impl PrivateKey {
     /// Tweak the private key with a given hash.
     /// Note that the private key can be tweaked with a `None` value.
     pub fn tweak(self, tweak: Option<H256>) -> PrivateKey {
+        self.tweak_with_context(Secp256k1Context::shared(), tweak)
+    }
+
+    /// Tweak the private key with a given hash using the caller's `context`.
+    pub fn tweak_with_context(self, context: &Secp256k1Context, tweak: Option<H256>) -> PrivateKey {
         let tweak = bitcoin_tweak(tweak);
 
         // Tweak the private key.
-        let tweaked = self.key_pair.tap_tweak(SECP256K1, tweak);
+        let tweaked = self.key_pair.tap_tweak(&context.secp, tweak);
         PrivateKey {
             key_pair: secp256k1::KeyPair::from(tweaked),
             no_aux_rand: self.no_aux_rand,
         }
     }
 
+    /// Signs the message using the caller's `context`.
+    /// The auxiliary random data is read from the OS random source on every call.
+    pub fn sign_with_context(
+        &self,
+        context: &Secp256k1Context,
+        message: H256,
+    ) -> KeyPairResult<Signature> {
+        // We fully rely on the `bitcoin` and `secp256k1` crates to generate Schnorr signatures.
+
+        // TODO consider checking `Utxo.leaf_hash` like at
+        // https://github.com/trustwallet/wallet-core/blob/43bf58c0c99d78789b5a11714ebc686b4268fa06/rust/tw_bitcoin/src/modules/signer.rs#L183
+
+        // Sign the message.
+        let msg = secp256k1::Message::from_slice(message.as_slice()).expect("");
+        let sig = if self.no_aux_rand {
+            context.secp.sign_schnorr_no_aux_rand(&msg, &self.key_pair)
+        } else {
+            let mut aux_rand = Zeroizing::new([0_u8; 32]);
+            OsRng.fill_bytes(&mut *aux_rand);
+            context
+                .secp
+                .sign_schnorr_with_aux_rand(&msg, &self.key_pair, &*aux_rand)
+        };
+
+        Ok(Signature::from(sig))
+    }
+
+    /// Parses the private key using the caller's `context`.
+    pub fn try_from_slice_with_context(
+        context: &Secp256k1Context,
+        value: &[u8],
+    ) -> KeyPairResult<PrivateKey> {
+        let key_pair = secp256k1::KeyPair::from_seckey_slice(&context.secp, value)
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey {
+            key_pair,
+            no_aux_rand: false,
+        })
+    }
+
     /// Disable auxiliary random data when signing. ONLY recommended for testing.
     pub fn no_aux_rand(mut self) -> PrivateKey {
         self.no_aux_rand = true;
@@ -61,20 +108,7 @@ impl SigningKeyTrait for PrivateKey {
     type Signature = Signature;
 
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
-        // We fully rely on the `bitcoin` and `secp256k1` crates to generate Schnorr signatures.
-
-        // TODO consider checking `Utxo.leaf_hash` like at
-        // https://github.com/trustwallet/wallet-core/blob/43bf58c0c99d78789b5a11714ebc686b4268fa06/rust/tw_bitcoin/src/modules/signer.rs#L183
-
-        // Sign the message.
-        let msg = secp256k1::Message::from_slice(message.as_slice()).expect("");
-        let sig = if self.no_aux_rand {
-            SECP256K1.sign_schnorr_no_aux_rand(&msg, &self.key_pair)
-        } else {
-            SECP256K1.sign_schnorr(&msg, &self.key_pair)
-        };
-
-        Ok(Signature::from(sig))
+        self.sign_with_context(Secp256k1Context::shared(), message)
     }
 }
 
@@ -97,11 +131,6 @@ impl<'a> TryFrom<&'a [u8]> for PrivateKey {
     type Error = KeyPairError;
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        let key_pair = secp256k1::KeyPair::from_seckey_slice(SECP256K1, value)
-            .map_err(|_| KeyPairError::InvalidSecretKey)?;
-        Ok(PrivateKey {
-            key_pair,
-            no_aux_rand: false,
-        })
+        PrivateKey::try_from_slice_with_context(Secp256k1Context::shared(), value)
     }
 }
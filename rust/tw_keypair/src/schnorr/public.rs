@@ -1,8 +1,9 @@
-use crate::schnorr::{bitcoin_tweak, Signature};
+use crate::ecdsa;
+use crate::ecdsa::sec1::check_public_key_encoding;
+use crate::schnorr::{bitcoin_tweak, Secp256k1Context, Signature};
 use crate::traits::VerifyingKeyTrait;
 use crate::KeyPairError;
 use bitcoin::key::TapTweak;
-use secp256k1::SECP256K1;
 use tw_hash::{H256, H264};
 use tw_misc::traits::ToBytesVec;
 
@@ -16,25 +17,98 @@ impl PublicKey {
         H264::from(self.public.serialize())
     }
 
-    pub fn tweak(&self, tweak: Option<H256>) -> PublicKey {
-        let tweak = bitcoin_tweak(tweak);
+    pub fn tweak(&self, tweak: Option<H256>) -> TweakedPublicKey {
+        self.tweak_with_context(Secp256k1Context::shared(), tweak)
+    }
+
+    /// Tweak the public key with a given hash using the caller's `context`.
+    pub fn tweak_with_context(
+        &self,
+        context: &Secp256k1Context,
+        tweak: Option<H256>,
+    ) -> TweakedPublicKey {
+        self.x_only().tweak_with_context(context, tweak)
+    }
 
+    pub fn x_only(&self) -> XOnlyPublicKey {
         let (x_only_pubkey, _parity) = self.public.x_only_public_key();
-        let (tweaked_pubkey, tweaked_parity) = x_only_pubkey.tap_tweak(SECP256K1, tweak);
+        XOnlyPublicKey {
+            public: x_only_pubkey,
+        }
+    }
+}
+
+/// The parity of the `y` coordinate of a point.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+impl Parity {
+    /// Returns `0` for even and `1` for odd parity, as encoded in the lowest bit of a Taproot control block.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Parity::Even => 0,
+            Parity::Odd => 1,
+        }
+    }
+
+    /// Reads the parity from the lowest bit of `byte`.
+    pub fn from_lowest_bit(byte: u8) -> Parity {
+        if byte & 1 == 0 {
+            Parity::Even
+        } else {
+            Parity::Odd
+        }
+    }
+}
+
+impl From<secp256k1::Parity> for Parity {
+    fn from(parity: secp256k1::Parity) -> Self {
+        match parity {
+            secp256k1::Parity::Even => Parity::Even,
+            secp256k1::Parity::Odd => Parity::Odd,
+        }
+    }
+}
+
+impl From<Parity> for secp256k1::Parity {
+    fn from(parity: Parity) -> Self {
+        match parity {
+            Parity::Even => secp256k1::Parity::Even,
+            Parity::Odd => secp256k1::Parity::Odd,
+        }
+    }
+}
+
+/// A Taproot output key: the x-only key committed to the scriptPubkey
+/// and the parity of its `y` coordinate produced by the taptweak.
+/// The parity is required to build script-path control blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TweakedPublicKey {
+    pub xonly: XOnlyPublicKey,
+    pub parity: Parity,
+}
 
+impl TweakedPublicKey {
+    /// Returns the full (compressed) form of the output key.
+    pub fn public_key(&self) -> PublicKey {
         PublicKey {
             public: secp256k1::PublicKey::from_x_only_public_key(
-                tweaked_pubkey.to_inner(),
-                tweaked_parity,
+                self.xonly.public,
+                self.parity.into(),
             ),
         }
     }
+}
 
-    pub fn x_only(&self) -> XOnlyPublicKey {
-        let (x_only_pubkey, _parity) = self.public.x_only_public_key();
-        XOnlyPublicKey {
-            public: x_only_pubkey,
-        }
+/// Both keys are validated on construction, so the conversion never fails.
+impl From<&ecdsa::secp256k1::PublicKey> for PublicKey {
+    fn from(public: &ecdsa::secp256k1::PublicKey) -> Self {
+        let public = secp256k1::PublicKey::from_slice(public.compressed().as_slice())
+            .expect("Expected a valid secp256k1 public key");
+        PublicKey { public }
     }
 }
 
@@ -56,13 +130,17 @@ impl ToBytesVec for PublicKey {
 impl<'a> TryFrom<&'a [u8]> for PublicKey {
     type Error = KeyPairError;
 
+    /// Hybrid-encoded keys, the point at infinity and points that are not on the curve are rejected.
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        let public =
-            secp256k1::PublicKey::from_slice(value).map_err(|_| KeyPairError::InvalidPublicKey)?;
+        // `libsecp256k1` accepts hybrid-encoded keys, so the encoding needs to be checked first.
+        check_public_key_encoding(value)?;
+        let public = secp256k1::PublicKey::from_slice(value)
+            .map_err(|_| KeyPairError::PublicKeyNotOnCurve)?;
         Ok(PublicKey { public })
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct XOnlyPublicKey {
     pub(crate) public: secp256k1::XOnlyPublicKey,
 }
@@ -71,6 +149,50 @@ impl XOnlyPublicKey {
     pub fn bytes(&self) -> H256 {
         H256::from(self.public.serialize())
     }
+
+    /// Re-derives the output key and its parity from an x-only *internal* key.
+    ///
+    /// # Warning
+    ///
+    /// The parity can't be recovered from an x-only key itself, so the key is tweaked again.
+    /// Make sure `self` is the untweaked internal key: tweaking an output key gives a meaningless result.
+    /// Prefer keeping the [`TweakedPublicKey`] returned by [`PublicKey::tweak`] where possible.
+    pub fn tweak(&self, tweak: Option<H256>) -> TweakedPublicKey {
+        self.tweak_with_context(Secp256k1Context::shared(), tweak)
+    }
+
+    /// Same as [`XOnlyPublicKey::tweak`], but uses the caller's `context`.
+    pub fn tweak_with_context(
+        &self,
+        context: &Secp256k1Context,
+        tweak: Option<H256>,
+    ) -> TweakedPublicKey {
+        let tweak = bitcoin_tweak(tweak);
+        let (tweaked_pubkey, tweaked_parity) = self.public.tap_tweak(&context.secp, tweak);
+
+        TweakedPublicKey {
+            xonly: XOnlyPublicKey {
+                public: tweaked_pubkey.to_inner(),
+            },
+            parity: Parity::from(tweaked_parity),
+        }
+    }
+
+    /// Verifies the signature using the caller's `context`.
+    pub fn verify_with_context(
+        &self,
+        context: &Secp256k1Context,
+        signature: Signature,
+        message: H256,
+    ) -> bool {
+        let message = secp256k1::Message::from_slice(message.as_slice())
+            .expect("Expected a valid secp256k1 message");
+
+        context
+            .secp
+            .verify_schnorr(&signature.signature, &message, &self.public)
+            .is_ok()
+    }
 }
 
 impl VerifyingKeyTrait for XOnlyPublicKey {
@@ -78,10 +200,7 @@ impl VerifyingKeyTrait for XOnlyPublicKey {
     type VerifySignature = Signature;
 
     fn verify(&self, signature: Self::VerifySignature, message: Self::SigningMessage) -> bool {
-        let message = secp256k1::Message::from_slice(message.as_slice())
-            .expect("Expected a valid secp256k1 message");
-
-        signature.signature.verify(&message, &self.public).is_ok()
+        self.verify_with_context(Secp256k1Context::shared(), signature, message)
     }
 }
 
@@ -89,16 +208,18 @@ impl<'a> TryFrom<&'a [u8]> for XOnlyPublicKey {
     type Error = KeyPairError;
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        let x_only_slice = if value.len() == H264::LEN {
-            // Drop the first parity byte.
-            &value[1..]
-        } else {
-            // Otherwise it should be `H256::LEN`. It will be checked later at [`secp256k1::XOnlyPublicKey::from_slice`].
-            value
+        let x_only_slice = match value.len() {
+            H264::LEN => {
+                // Drop the first parity byte, but make sure it's a valid compressed key prefix.
+                check_public_key_encoding(value)?;
+                &value[1..]
+            },
+            H256::LEN => value,
+            _ => return Err(KeyPairError::InvalidPublicKey),
         };
 
         let public = secp256k1::XOnlyPublicKey::from_slice(x_only_slice)
-            .map_err(|_| KeyPairError::InvalidPublicKey)?;
+            .map_err(|_| KeyPairError::PublicKeyNotOnCurve)?;
         Ok(XOnlyPublicKey { public })
     }
 }
@@ -96,6 +96,7 @@ impl PublicKeyType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::KeyPairError;
 
     #[test]
     fn test_curve_from_raw() {
@@ -134,4 +135,46 @@ mod tests {
             assert_eq!(PublicKeyType::from_raw(raw), expected);
         }
     }
+
+    #[test]
+    fn test_public_key_new_invalid() {
+        let new = |hex: &str, ty| PublicKey::new(tw_encoding::hex::decode(hex).unwrap(), ty).err();
+
+        let hybrid = "0799c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c166b489a4b7c491e7688e6ebea3a71fc3a1a48d60f98d5ce84c93b65e423fde91";
+        assert_eq!(
+            new(hybrid, PublicKeyType::Secp256k1Extended),
+            Some(KeyPairError::HybridPublicKey)
+        );
+        assert_eq!(
+            new(
+                "020000000000000000000000000000000000000000000000000000000000000005",
+                PublicKeyType::Secp256k1
+            ),
+            Some(KeyPairError::PublicKeyNotOnCurve)
+        );
+        assert_eq!(
+            new(
+                "000000000000000000000000000000000000000000000000000000000000000000",
+                PublicKeyType::Nist256p1
+            ),
+            Some(KeyPairError::PublicKeyAtInfinity)
+        );
+        assert_eq!(
+            new(
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                PublicKeyType::Ed25519
+            ),
+            Some(KeyPairError::SmallOrderPublicKey)
+        );
+
+        let valid = PublicKey::new(
+            tw_encoding::hex::decode(
+                "0399c6f51ad6f98c9c583f8e92bb7758ab2ca9a04110c0a1126ec43e5453d196c1",
+            )
+            .unwrap(),
+            PublicKeyType::Secp256k1,
+        )
+        .unwrap();
+        assert_eq!(valid.validate(), Ok(()));
+    }
 }
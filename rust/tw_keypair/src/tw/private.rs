@@ -163,6 +163,32 @@ impl PrivateKey {
         }
     }
 
+    /// Computes an EC Diffie-Hellman shared secret with the `public` key of the same `curve`.
+    /// The secret is hashed with SHA-256 if `hash` is set.
+    ///
+    /// Currently, only [`Curve::Secp256k1`] is supported.
+    pub fn shared_secret(
+        &self,
+        public: &PublicKey,
+        curve: Curve,
+        hash: bool,
+    ) -> KeyPairResult<H256> {
+        match curve {
+            Curve::Secp256k1 => {
+                let privkey = self.to_secp256k1_privkey()?;
+                let pubkey = public
+                    .to_secp256k1()
+                    .ok_or(KeyPairError::InvalidPublicKey)?;
+                if hash {
+                    privkey.shared_secret_sha256(pubkey)
+                } else {
+                    privkey.shared_secret(pubkey)
+                }
+            },
+            _ => Err(KeyPairError::UnsupportedCurve),
+        }
+    }
+
     /// Tries to convert [`PrivateKey::key`] to [`secp256k1::PrivateKey`].
     fn to_secp256k1_privkey(&self) -> KeyPairResult<secp256k1::PrivateKey> {
         secp256k1::PrivateKey::try_from(self.key().as_slice())
@@ -85,6 +85,11 @@ impl PublicKey {
         PublicKey::new(bytes, ty).is_ok()
     }
 
+    /// Re-checks the public key as if it was created from its raw data with [`PublicKey::new`].
+    pub fn validate(&self) -> KeyPairResult<()> {
+        PublicKey::new(self.to_bytes(), self.public_key_type()).map(|_| ())
+    }
+
     /// Verifies if the given `message` was signed using a private key associated with the public key.
     pub fn verify(&self, sig: &[u8], message: &[u8]) -> bool {
         fn verify_impl<Key>(verifying_key: &Key, sig: &[u8], message: &[u8]) -> bool
@@ -4,12 +4,16 @@
 
 use serde::Deserialize;
 use tw_encoding::hex::as_hex;
-use tw_hash::{H256, H520};
-use tw_keypair::ecdsa::secp256k1::{KeyPair, VerifySignature};
+use tw_hash::sha2::sha256;
+use tw_hash::{H256, H264, H520};
+use tw_keypair::ecdsa::secp256k1::{KeyPair, PrivateKey, PublicKey, VerifySignature};
 use tw_keypair::traits::{SigningKeyTrait, VerifyingKeyTrait};
+use tw_keypair::{tw, KeyPairError};
 
 /// The tests were generated in C++ using the `trezor-crypto` library.
 const SECP256K1_SIGN: &str = include_str!("secp256k1_sign.json");
+/// The tests were generated in Python using the `cryptography` (OpenSSL) library.
+const SECP256K1_SHARED_SECRET: &str = include_str!("secp256k1_shared_secret.json");
 
 #[derive(Deserialize)]
 struct Secp256k1SignTest {
@@ -21,6 +25,18 @@ struct Secp256k1SignTest {
     signature: H520,
 }
 
+#[derive(Deserialize)]
+struct Secp256k1SharedSecretTest {
+    #[serde(with = "as_hex")]
+    private: H256,
+    #[serde(with = "as_hex")]
+    public: H264,
+    #[serde(with = "as_hex")]
+    shared_secret: H256,
+    #[serde(with = "as_hex")]
+    shared_secret_sha256: H256,
+}
+
 #[test]
 fn test_secp256k1_sign_verify() {
     let tests: Vec<Secp256k1SignTest> = serde_json::from_str(SECP256K1_SIGN).unwrap();
@@ -33,3 +49,78 @@ fn test_secp256k1_sign_verify() {
         assert!(keypair.verify(verify_sign, test.hash));
     }
 }
+
+#[test]
+fn test_secp256k1_shared_secret() {
+    let tests: Vec<Secp256k1SharedSecretTest> =
+        serde_json::from_str(SECP256K1_SHARED_SECRET).unwrap();
+    for test in tests {
+        let private = PrivateKey::try_from(test.private.as_slice()).unwrap();
+        let public = PublicKey::try_from(test.public.as_slice()).unwrap();
+
+        assert_eq!(private.shared_secret(&public).unwrap(), test.shared_secret);
+        assert_eq!(
+            private.shared_secret_sha256(&public).unwrap(),
+            test.shared_secret_sha256
+        );
+    }
+}
+
+#[test]
+fn test_secp256k1_shared_secret_agreement() {
+    for i in 0..16_u8 {
+        let a = PrivateKey::try_from(sha256(&[i, 0]).as_slice()).unwrap();
+        let b = PrivateKey::try_from(sha256(&[i, 1]).as_slice()).unwrap();
+
+        // a·B == b·A
+        let a_b = a.shared_secret(&b.public()).unwrap();
+        let b_a = b.shared_secret(&a.public()).unwrap();
+        assert_eq!(a_b, b_a);
+        assert_eq!(
+            a.shared_secret_sha256(&b.public()).unwrap(),
+            b.shared_secret_sha256(&a.public()).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_tw_private_key_shared_secret() {
+    let test: Vec<Secp256k1SharedSecretTest> =
+        serde_json::from_str(SECP256K1_SHARED_SECRET).unwrap();
+    let test = &test[2];
+
+    let private = tw::PrivateKey::new(test.private.as_slice().to_vec()).unwrap();
+    let public = tw::PublicKey::new(
+        test.public.as_slice().to_vec(),
+        tw::PublicKeyType::Secp256k1,
+    )
+    .unwrap();
+
+    let actual = private
+        .shared_secret(&public, tw::Curve::Secp256k1, false)
+        .unwrap();
+    assert_eq!(actual, test.shared_secret);
+    let actual = private
+        .shared_secret(&public, tw::Curve::Secp256k1, true)
+        .unwrap();
+    assert_eq!(actual, test.shared_secret_sha256);
+
+    // The public key of another curve.
+    let nist256p1_public = private
+        .get_public_key_by_type(tw::PublicKeyType::Nist256p1)
+        .unwrap();
+    assert_eq!(
+        private
+            .shared_secret(&nist256p1_public, tw::Curve::Secp256k1, false)
+            .unwrap_err(),
+        KeyPairError::InvalidPublicKey
+    );
+
+    // Not supported yet.
+    assert_eq!(
+        private
+            .shared_secret(&nist256p1_public, tw::Curve::Nist256p1, false)
+            .unwrap_err(),
+        KeyPairError::UnsupportedCurve
+    );
+}
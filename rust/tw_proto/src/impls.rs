@@ -35,6 +35,13 @@ impl fmt::Display for SigningError {
             SigningError::Error_invalid_requested_token_amount => "Invalid input token amount",
             SigningError::Error_not_supported => "Operation not supported for the chain",
             SigningError::Error_dust_amount_requested => "Requested amount is too low (less dust)",
+            SigningError::Error_invalid_sighash_type => "Sighash type has reserved or undefined bits set",
+            SigningError::Error_claim_type_mismatch => "Declared claiming script type does not match the UTXO scriptPubkey",
+            SigningError::Error_sighash_default_not_allowed => "SIGHASH_DEFAULT is allowed for Taproot inputs only",
+            SigningError::Error_missing_input_amount => "Input amount is missing or zero, but the signature commits to it",
+            SigningError::Error_nonce_reuse_detected => "Two signatures share the same nonce while signing different digests",
+            SigningError::Error_segwit_not_supported => "Witness inputs are not supported by the chain, as it has not activated segwit",
+            SigningError::Error_sighash_type_not_allowed => "Sighash type is not allowed by the chain",
         };
         write!(f, "{err}")
     }
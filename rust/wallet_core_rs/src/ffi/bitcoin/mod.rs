@@ -1,3 +1,5 @@
 // SPDX-License-Identifier: Apache-2.0
 //
 // Copyright © 2017 Trust Wallet.
+
+pub mod utxo;
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! C API of the UTXO planner, signer and PSBT utils.
+//!
+//! Every function returns a [`CByteArrayResult`] with either the serialized output or
+//! an error code that is a `TW.Common.Proto.SigningError` value, see [`UtxoErrorKind::to_ffi_code`].
+//! The returned byte array is owned by the caller and must be released with `free_c_byte_array`.
+//! The input `TWData` is borrowed and must be deleted by the caller as usual.
+//!
+//! A panic never unwinds across the FFI boundary: it is caught and reported as `Error_internal`.
+//! The release builds abort on panic anyway, see `panic = "abort"` of `[profile.release]`.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use tw_bitcoin::modules::compiler::BitcoinCompiler;
+use tw_bitcoin::modules::planner::BitcoinPlanner;
+use tw_bitcoin::modules::signer::BitcoinSigner;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_registry::blockchain_type::BlockchainType;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_memory::ffi::c_byte_array::{CByteArray, CByteArrayResult};
+use tw_memory::ffi::c_result::ErrorCode;
+use tw_memory::ffi::tw_data::TWData;
+use tw_memory::ffi::RawPtrTrait;
+use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::psbt::Psbt;
+
+/// The kind of an error returned by the UTXO C API.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UtxoErrorKind(SigningErrorType);
+
+impl UtxoErrorKind {
+    /// Returns the FFI error code, i.e. the `TW.Common.Proto.SigningError` value.
+    pub fn to_ffi_code(self) -> ErrorCode {
+        self.0 as ErrorCode
+    }
+}
+
+impl From<&SigningError> for UtxoErrorKind {
+    fn from(error: &SigningError) -> Self {
+        UtxoErrorKind(*error.error_type())
+    }
+}
+
+/// Plans a transaction specified by the signing input and coin type.
+/// The plan contains the selected UTXOs and the estimated fee.
+///
+/// \param input The serialized data of a `TW.BitcoinV2.Proto.SigningInput`.
+/// \param coin The given coin type to plan the transaction for.
+/// \return The serialized data of a `TW.BitcoinV2.Proto.TransactionPlan` or an error code.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_utxo_plan(input: *const TWData, coin: u32) -> CByteArrayResult {
+    ffi_call(|| {
+        let input_bytes = input_as_slice(input)?;
+        let coin = coin_context(coin)?;
+        let input: Proto::SigningInput = tw_proto::deserialize(input_bytes)?;
+
+        let plan = BitcoinPlanner::plan_impl(&coin, &input)?;
+        Ok(tw_proto::serialize(&plan)?)
+    })
+}
+
+/// Signs a transaction specified by the signing input and coin type.
+///
+/// \param input The serialized data of a `TW.BitcoinV2.Proto.SigningInput`.
+/// \param coin The given coin type to sign the transaction for.
+/// \return The serialized data of a `TW.BitcoinV2.Proto.SigningOutput` or an error code.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_utxo_sign(input: *const TWData, coin: u32) -> CByteArrayResult {
+    ffi_call(|| {
+        let input_bytes = input_as_slice(input)?;
+        let coin = coin_context(coin)?;
+        let input: Proto::SigningInput = tw_proto::deserialize(input_bytes)?;
+
+        let output = BitcoinSigner::sign_impl(&coin, &input)?;
+        Ok(tw_proto::serialize(&output)?)
+    })
+}
+
+/// Computes the preimage hashes of a transaction specified by the signing input and coin type.
+/// Please note that the public key must be set in the signing input.
+///
+/// \param input The serialized data of a `TW.BitcoinV2.Proto.SigningInput`.
+/// \param coin The given coin type to compute the preimage hashes for.
+/// \return The serialized data of a `TW.BitcoinV2.Proto.PreSigningOutput` or an error code.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_utxo_preimage_hashes(
+    input: *const TWData,
+    coin: u32,
+) -> CByteArrayResult {
+    ffi_call(|| {
+        let input_bytes = input_as_slice(input)?;
+        let coin = coin_context(coin)?;
+        let input: Proto::SigningInput = tw_proto::deserialize(input_bytes)?;
+
+        let output = BitcoinCompiler::preimage_hashes(&coin, input);
+        if output.error != SigningErrorType::OK {
+            return SigningError::err(output.error).context(output.error_message);
+        }
+        Ok(tw_proto::serialize(&output)?)
+    })
+}
+
+/// Converts a PSBT into the given version.
+///
+/// \param psbt The serialized PSBT of version 0 or 2.
+/// \param version The version to convert the PSBT into, either 0 or 2.
+/// \return The serialized PSBT of the given version or an error code.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_utxo_psbt_convert(
+    psbt: *const TWData,
+    version: u32,
+) -> CByteArrayResult {
+    ffi_call(|| {
+        let psbt = Psbt::from_bytes(input_as_slice(psbt)?)?;
+
+        let converted = match version {
+            0 => psbt.to_v0()?,
+            2 => psbt.to_v2()?,
+            _ => {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context(format!("Unsupported PSBT version: {version}"))
+            },
+        };
+        Ok(converted.encode_out())
+    })
+}
+
+/// Calls the given function and maps its result onto [`CByteArrayResult`].
+/// A panic is reported as `Error_internal`.
+fn ffi_call<F>(f: F) -> CByteArrayResult
+where
+    F: FnOnce() -> SigningResult<Data>,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(data)) => CByteArrayResult::ok(CByteArray::from(data)),
+        Ok(Err(error)) => CByteArrayResult::error(UtxoErrorKind::from(&error).to_ffi_code()),
+        Err(_) => {
+            CByteArrayResult::error(UtxoErrorKind(SigningErrorType::Error_internal).to_ffi_code())
+        },
+    }
+}
+
+unsafe fn input_as_slice<'a>(input: *const TWData) -> SigningResult<&'a [u8]> {
+    TWData::from_ptr_as_ref(input)
+        .map(TWData::as_slice)
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("Input is a null pointer")
+}
+
+/// Returns the context of the given coin, checking that the coin is a Bitcoin-based chain.
+fn coin_context(coin: u32) -> SigningResult<CoinRegistryContext> {
+    let coin_item = CoinType::try_from(coin)
+        .ok()
+        .and_then(|coin| get_coin_item(coin).ok())
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .with_context(|| format!("Unknown coin type: {coin}"))?;
+
+    if coin_item.blockchain != BlockchainType::Bitcoin {
+        return SigningError::err(SigningErrorType::Error_not_supported)
+            .with_context(|| format!("'{}' is not a UTXO chain", coin_item.name));
+    }
+    Ok(CoinRegistryContext::with_coin_item(coin_item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_call_panic() {
+        let result = ffi_call(|| panic!("Unexpected state"));
+        assert_eq!(result.code, SigningErrorType::Error_internal as ErrorCode);
+
+        let result = ffi_call(|| Ok(vec![1, 2, 3]));
+        assert_eq!(unsafe { result.unwrap().into_vec() }, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,6 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+#[path = "bitcoin/bitcoin_utxo.rs"]
+mod bitcoin_utxo;
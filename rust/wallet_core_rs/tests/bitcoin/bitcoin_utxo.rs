@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_memory::ffi::c_byte_array::free_c_byte_array;
+use tw_memory::test_utils::tw_data_helper::TWDataHelper;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_Input::mod_InputBuilder::OneOfvariant as InputBuilderType;
+use tw_proto::BitcoinV2::Proto::mod_Output::mod_OutputBuilder::OneOfvariant as OutputBuilderType;
+use tw_proto::BitcoinV2::Proto::mod_PublicKeyOrHash::OneOfvariant as PublicKeyOrHashType;
+use tw_utxo::test_utils::fixtures::{PRIVATE_KEY as ALICE_PRIVATE_KEY, PSBT_V0, PSBT_V2};
+use wallet_core_rs::ffi::bitcoin::utxo::{
+    tw_bitcoin_utxo_plan, tw_bitcoin_utxo_preimage_hashes, tw_bitcoin_utxo_psbt_convert,
+    tw_bitcoin_utxo_sign,
+};
+
+const BITCOIN_COIN: u32 = 0;
+const ETHEREUM_COIN: u32 = 60;
+
+const ONE_BTC: i64 = 100_000_000;
+const MINER_FEE: i64 = 1_000_000;
+
+const ALICE_PUBLIC_KEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const BOB_PUBLIC_KEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn p2pkh(pubkey: &str) -> Proto::PublicKeyOrHash<'static> {
+    Proto::PublicKeyOrHash {
+        variant: PublicKeyOrHashType::pubkey(pubkey.decode_hex().unwrap().into()),
+    }
+}
+
+/// Builds a signing input spending a P2PKH UTXO to a P2PKH output.
+fn signing_input() -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b"
+        .decode_hex()
+        .unwrap()
+        .into_iter()
+        .rev()
+        .collect();
+
+    let utxo = Proto::Input {
+        out_point: Some(Proto::OutPoint {
+            hash: txid.into(),
+            vout: 0,
+        }),
+        value: ONE_BTC * 50,
+        sighash_type: 0x01,
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::script_builder(
+            Proto::mod_Input::InputBuilder {
+                variant: InputBuilderType::p2pkh(p2pkh(ALICE_PUBLIC_KEY)),
+            },
+        ),
+        ..Default::default()
+    };
+
+    let output = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::builder(
+            Proto::mod_Output::OutputBuilder {
+                variant: OutputBuilderType::p2pkh(p2pkh(BOB_PUBLIC_KEY)),
+            },
+        ),
+    };
+
+    Proto::SigningInput {
+        version: Proto::TransactionVersion::V2,
+        private_keys: vec![ALICE_PRIVATE_KEY.decode_hex().unwrap().into()],
+        inputs: vec![utxo],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        chain_info: Some(Proto::ChainInfo {
+            p2pkh_prefix: 0,
+            p2sh_prefix: 5,
+            ..Proto::ChainInfo::default()
+        }),
+        dust_policy: Proto::mod_SigningInput::OneOfdust_policy::fixed_dust_threshold(546),
+        ..Default::default()
+    }
+}
+
+fn serialized_input(input: &Proto::SigningInput) -> TWDataHelper {
+    TWDataHelper::create(tw_proto::serialize(input).unwrap())
+}
+
+#[test]
+fn test_bitcoin_utxo_plan() {
+    let input = serialized_input(&signing_input());
+
+    let plan_bytes = unsafe {
+        tw_bitcoin_utxo_plan(input.ptr(), BITCOIN_COIN)
+            .unwrap()
+            .into_vec()
+    };
+    let plan: Proto::TransactionPlan = tw_proto::deserialize(&plan_bytes).unwrap();
+
+    assert_eq!(plan.error, SigningErrorType::OK);
+    assert_eq!(plan.inputs.len(), 1);
+    assert_eq!(plan.vsize_estimate, 193);
+    assert_eq!(plan.fee_estimate, MINER_FEE);
+}
+
+#[test]
+fn test_bitcoin_utxo_sign() {
+    let input = serialized_input(&signing_input());
+
+    let output_bytes = unsafe {
+        tw_bitcoin_utxo_sign(input.ptr(), BITCOIN_COIN)
+            .unwrap()
+            .into_vec()
+    };
+    let output: Proto::SigningOutput = tw_proto::deserialize(&output_bytes).unwrap();
+
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert_eq!(output.encoded.to_hex(), "02000000017be4e642bb278018ab12277de9427773ad1c5f5b1d164a157e0d99aa48dc1c1e000000006a473044022078eda020d4b86fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd4590121036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536ffffffff01c0aff629010000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000");
+}
+
+#[test]
+fn test_bitcoin_utxo_preimage_hashes() {
+    let input = Proto::SigningInput {
+        private_keys: Vec::default(),
+        public_keys: vec![ALICE_PUBLIC_KEY.decode_hex().unwrap().into()],
+        ..signing_input()
+    };
+    let input = serialized_input(&input);
+
+    let output_bytes = unsafe {
+        tw_bitcoin_utxo_preimage_hashes(input.ptr(), BITCOIN_COIN)
+            .unwrap()
+            .into_vec()
+    };
+    let output: Proto::PreSigningOutput = tw_proto::deserialize(&output_bytes).unwrap();
+
+    assert_eq!(output.error, SigningErrorType::OK);
+    assert_eq!(output.sighashes.len(), 1);
+    assert_eq!(output.sighashes[0].public_key.to_hex(), ALICE_PUBLIC_KEY);
+}
+
+#[test]
+fn test_bitcoin_utxo_psbt_convert() {
+    let psbt_v2 = TWDataHelper::create(PSBT_V2.decode_hex().unwrap());
+    let converted = unsafe {
+        tw_bitcoin_utxo_psbt_convert(psbt_v2.ptr(), 0)
+            .unwrap()
+            .into_vec()
+    };
+    assert_eq!(converted.to_hex(), PSBT_V0);
+
+    // The v0 lock time and sequence are kept explicitly, so converting back to v0 gives the same PSBT.
+    let psbt_v0 = TWDataHelper::create(PSBT_V0.decode_hex().unwrap());
+    let converted = unsafe {
+        tw_bitcoin_utxo_psbt_convert(psbt_v0.ptr(), 2)
+            .unwrap()
+            .into_vec()
+    };
+    let converted = TWDataHelper::create(converted);
+    let converted_back = unsafe {
+        tw_bitcoin_utxo_psbt_convert(converted.ptr(), 0)
+            .unwrap()
+            .into_vec()
+    };
+    assert_eq!(converted_back.to_hex(), PSBT_V0);
+
+    let res = unsafe { tw_bitcoin_utxo_psbt_convert(psbt_v2.ptr(), 1) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_invalid_params as i32
+    );
+}
+
+#[test]
+fn test_bitcoin_utxo_free_result() {
+    let input = serialized_input(&signing_input());
+
+    // Create and release the result several times to make sure the memory is handled properly.
+    for _ in 0..10 {
+        let mut res = unsafe { tw_bitcoin_utxo_sign(input.ptr(), BITCOIN_COIN) };
+        assert!(res.is_ok());
+        assert!(!res.result.data().is_null());
+
+        unsafe { free_c_byte_array(&mut res.result) };
+        assert!(res.result.data().is_null());
+        // Releasing twice is a no-op.
+        unsafe { free_c_byte_array(&mut res.result) };
+    }
+}
+
+#[test]
+fn test_bitcoin_utxo_errors() {
+    let input = serialized_input(&signing_input());
+
+    let res = unsafe { tw_bitcoin_utxo_plan(std::ptr::null(), BITCOIN_COIN) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_invalid_params as i32
+    );
+
+    let res = unsafe { tw_bitcoin_utxo_plan(input.ptr(), u32::MAX) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_invalid_params as i32
+    );
+
+    let res = unsafe { tw_bitcoin_utxo_sign(input.ptr(), ETHEREUM_COIN) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_not_supported as i32
+    );
+
+    let invalid_input = TWDataHelper::create(vec![0xff; 4]);
+    let res = unsafe { tw_bitcoin_utxo_sign(invalid_input.ptr(), BITCOIN_COIN) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_input_parse as i32
+    );
+
+    let res = unsafe { tw_bitcoin_utxo_psbt_convert(invalid_input.ptr(), 2) };
+    assert!(res.is_err());
+
+    // The error of the compiler output is reported as the error code too.
+    let no_utxos = Proto::SigningInput {
+        private_keys: Vec::default(),
+        public_keys: vec![ALICE_PUBLIC_KEY.decode_hex().unwrap().into()],
+        inputs: Vec::default(),
+        ..signing_input()
+    };
+    let no_utxos = serialized_input(&no_utxos);
+    let res = unsafe { tw_bitcoin_utxo_preimage_hashes(no_utxos.ptr(), BITCOIN_COIN) };
+    assert_eq!(
+        res.into_result().unwrap_err(),
+        SigningErrorType::Error_missing_input_utxos as i32
+    );
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+import Foundation
+import SwiftProtobuf
+
+/// Error thrown by the generated `serializedDataOrThrow()` protobuf helpers.
+public enum ProtoSerializationError: Swift.Error, LocalizedError {
+    case missingRequiredFields
+    case anyTranscodeFailure
+    case other(Swift.Error)
+
+    init(_ error: Swift.Error) {
+        switch error {
+        case BinaryEncodingError.missingRequiredFields:
+            self = .missingRequiredFields
+        case BinaryEncodingError.anyTranscodeFailure:
+            self = .anyTranscodeFailure
+        default:
+            self = .other(error)
+        }
+    }
+
+    public var errorDescription: String? {
+        switch self {
+        case .missingRequiredFields:
+            return NSLocalizedString("Protobuf message is missing required fields", comment: "Error message when serializing an incomplete protobuf message")
+        case .anyTranscodeFailure:
+            return NSLocalizedString("Failed to transcode a protobuf Any field", comment: "Error message when serializing a protobuf Any field fails")
+        case .other(let error):
+            return error.localizedDescription
+        }
+    }
+}
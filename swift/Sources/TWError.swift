@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+import Foundation
+
+/// Error thrown by the generated throwing initializers, see `TW_THROWS`.
+public enum TWError: Swift.Error, LocalizedError {
+    /// The underlying C function failed to create the object from the given input.
+    case invalidInput
+
+    public var errorDescription: String? {
+        switch self {
+        case .invalidInput:
+            return NSLocalizedString("Invalid input", comment: "Error message when a throwing initializer fails")
+        }
+    }
+}